@@ -0,0 +1,36 @@
+//! BN254 scalar field inversion: Montgomery's batch trick against a loop of
+//! single inversions
+//!
+//! `cargo bench --bench field_inv`
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use zk_accelerate_rs::bn254::Fr;
+use zk_accelerate_rs::field::batch_invert;
+
+fn bench_field_inv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bn254_field_inv");
+    for n in [100, 1_000, 10_000] {
+        let step = Fr::from_u64(0x9e3779b97f4a7c15);
+        let values: Vec<Fr> = (1..=n as u64).map(|i| Fr::from_u64(i) * step).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("naive", n), &values, |b, values| {
+            b.iter(|| {
+                values
+                    .iter()
+                    .map(|v| v.inverse().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", n), &values, |b, values| {
+            b.iter_batched_ref(
+                || values.clone(),
+                |v| batch_invert(v),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_field_inv);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Batch BN254 scalar field multiplication on the Metal, NEON and scalar paths
+//!
+//! `cargo bench --bench field_mul`. Each path is pinned through the backend
+//! registry; paths this build or machine cannot run are skipped, so Metal
+//! only appears with `--features metal` on Apple Silicon.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use zk_accelerate_rs::backend::{self, Backend};
+use zk_accelerate_rs::bn254::{mul_batch, Fr};
+
+fn elements(n: usize, seed: u64) -> Vec<Fr> {
+    let step = Fr::from_u64(seed);
+    (0..n as u64).map(|i| Fr::from_u64(i + 1) * step).collect()
+}
+
+fn bench_field_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bn254_field_mul_batch");
+    for n in [1_000, 10_000, 100_000] {
+        let (a, b) = (
+            elements(n, 0x9e3779b97f4a7c15),
+            elements(n, 0x2545f4914f6cdd1d),
+        );
+        group.throughput(Throughput::Elements(n as u64));
+        for path in [Backend::Metal, Backend::Neon, Backend::Scalar] {
+            if !path.available() {
+                continue;
+            }
+            backend::set_active_backend(path.name().to_string()).unwrap();
+            group.bench_with_input(BenchmarkId::new(path.name(), n), &n, |bench, _| {
+                bench.iter(|| mul_batch(&a, &b))
+            });
+        }
+    }
+    backend::set_active_backend(backend::AUTO.to_string()).unwrap();
+    group.finish();
+}
+
+criterion_group!(benches, bench_field_mul);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+//! BLS12-381 base field multiplication: one level of Karatsuba against the
+//! schoolbook CIOS kernel
+//!
+//! `cargo bench --bench fp_mul`. Products are chained so each waits on the
+//! previous one, which is where Karatsuba's shorter `umulh` chains pay off.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use zk_accelerate_rs::bls12_381::{fp_mul_with_kernel, Fp};
+
+const CHAIN: u64 = 1024;
+
+fn bench_fp_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bls12_381_fp_mul");
+    group.throughput(Throughput::Elements(CHAIN));
+    let (x, y) = (Fp::from_u64(3).inverse().unwrap(), -Fp::from_u64(7));
+    for (name, karatsuba) in [("karatsuba", true), ("schoolbook", false)] {
+        group.bench_function(BenchmarkId::new(name, CHAIN), |b| {
+            b.iter(|| {
+                let mut acc = x;
+                for _ in 0..CHAIN {
+                    acc = fp_mul_with_kernel(&acc, &y, karatsuba);
+                }
+                acc
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fp_mul);
+criterion_main!(benches);
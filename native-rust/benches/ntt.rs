@@ -0,0 +1,45 @@
+//! Forward BN254 NTT with each butterfly kernel the CPU supports
+//!
+//! `cargo bench --bench ntt`. The NEON paired Montgomery butterflies only
+//! show up on aarch64; elsewhere the scalar kernel is measured against AVX2
+//! where available.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use zk_accelerate_rs::bn254::Fr;
+use zk_accelerate_rs::ntt::{available_kernels, ntt_in_place_with};
+
+fn inputs(log_n: u32) -> Vec<Fr> {
+    let mut x = Fr::from_u64(log_n as u64);
+    (0..1u64 << log_n)
+        .map(|i| {
+            x = x.square() + Fr::from_u64(i + 7);
+            x
+        })
+        .collect()
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ntt_bn254");
+    group.sample_size(10);
+    for log_n in [12, 16, 20] {
+        let input = inputs(log_n);
+        group.throughput(Throughput::Elements(input.len() as u64));
+        for kernel in available_kernels() {
+            group.bench_with_input(
+                BenchmarkId::new(kernel.name(), format!("2^{log_n}")),
+                &input,
+                |b, input| {
+                    b.iter_batched_ref(
+                        || input.clone(),
+                        |values| ntt_in_place_with(values, false, kernel).unwrap(),
+                        BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ntt);
+criterion_main!(benches);
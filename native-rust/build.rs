@@ -1,7 +1,15 @@
 extern crate napi_build;
 
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
-    napi_build::setup();
+    // The wasm build has no Node addon to link
+    if env::var("CARGO_CFG_TARGET_ARCH").as_deref() != Ok("wasm32") {
+        napi_build::setup();
+    }
     
     // Link Apple frameworks on macOS
     #[cfg(target_os = "macos")]
@@ -11,6 +19,7 @@ fn main() {
         println!("cargo:rustc-link-lib=framework=MetalKit");
         println!("cargo:rustc-link-lib=framework=Foundation");
         println!("cargo:rustc-link-lib=framework=CoreFoundation");
+        println!("cargo:rustc-link-lib=framework=IOKit");
         
         // Set deployment target for macOS
         println!("cargo:rustc-env=MACOSX_DEPLOYMENT_TARGET=12.0");
@@ -22,7 +31,61 @@ fn main() {
         println!("cargo:rustc-cfg=aarch64");
     }
     
+    emit_build_info();
+
     // Rerun if build script changes
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=src/lib.rs");
 }
+
+/// Export build metadata to the crate as `ZK_ACCELERATE_*` env vars
+fn emit_build_info() {
+    // Enabled features arrive as CARGO_FEATURE_<NAME>, upper-cased with
+    // '-' mapped to '_'
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|name| name.to_lowercase().replace('_', "-"))
+        })
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=ZK_ACCELERATE_FEATURES={}", features.join(","));
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_default();
+    println!("cargo:rustc-env=ZK_ACCELERATE_RUSTC_VERSION={rustc_version}");
+    println!("cargo:rustc-env=ZK_ACCELERATE_TARGET={}", env::var("TARGET").unwrap());
+    println!("cargo:rustc-env=ZK_ACCELERATE_PROFILE={}", env::var("PROFILE").unwrap());
+
+    if let Some(commit) = command_output("git", &["rev-parse", "HEAD"]) {
+        println!("cargo:rustc-env=ZK_ACCELERATE_GIT_COMMIT={commit}");
+    }
+    if let Some(head) = command_output("git", &["rev-parse", "--git-path", "HEAD"]) {
+        if Path::new(&head).exists() {
+            println!("cargo:rerun-if-changed={head}");
+        }
+    }
+
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        });
+    println!("cargo:rustc-env=ZK_ACCELERATE_BUILD_TIMESTAMP={timestamp}");
+}
+
+/// Trimmed stdout of a successful command
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim().to_string()).filter(|s| !s.is_empty())
+}
@@ -0,0 +1,140 @@
+//! Four-lane AVX2 Montgomery multiplication
+//!
+//! AVX2 has no 64x64-bit multiply either, so the kernel mirrors the NEON one:
+//! CIOS over eight 32-bit digits, with `vpmuludq` (`_mm256_mul_epu32`)
+//! producing four independent 32x32 -> 64-bit products per instruction. Each
+//! 64-bit lane of a `__m256i` carries one digit of one of the four products.
+
+use std::arch::x86_64::*;
+
+use crate::montgomery::{Fp, MontConfig};
+use crate::neon::digits;
+
+/// Whether the CPU supports AVX2 (CPUID, cached by std)
+pub fn enabled() -> bool {
+    std::arch::is_x86_feature_detected!("avx2")
+}
+
+/// Transpose a 4x4 matrix of u64, rows in, columns out
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn transpose(r: [__m256i; 4]) -> [__m256i; 4] {
+    // [a0 b0 a2 b2], [a1 b1 a3 b3], [c0 d0 c2 d2], [c1 d1 c3 d3]
+    let ab_lo = _mm256_unpacklo_epi64(r[0], r[1]);
+    let ab_hi = _mm256_unpackhi_epi64(r[0], r[1]);
+    let cd_lo = _mm256_unpacklo_epi64(r[2], r[3]);
+    let cd_hi = _mm256_unpackhi_epi64(r[2], r[3]);
+    [
+        _mm256_permute2x128_si256(ab_lo, cd_lo, 0x20),
+        _mm256_permute2x128_si256(ab_hi, cd_hi, 0x20),
+        _mm256_permute2x128_si256(ab_lo, cd_lo, 0x31),
+        _mm256_permute2x128_si256(ab_hi, cd_hi, 0x31),
+    ]
+}
+
+/// Split four elements into eight vectors of 32-bit digits, one element per lane
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn load_digits<C: MontConfig<4>>(v: &[Fp<C, 4>; 4], mask: __m256i) -> [__m256i; 8] {
+    let mut rows = [_mm256_setzero_si256(); 4];
+    for (row, x) in rows.iter_mut().zip(v) {
+        let limbs = x.to_montgomery_limbs();
+        *row = _mm256_loadu_si256(limbs.as_ptr() as *const __m256i);
+    }
+    let cols = transpose(rows);
+    let mut out = [_mm256_setzero_si256(); 8];
+    for (i, col) in cols.iter().enumerate() {
+        out[2 * i] = _mm256_and_si256(*col, mask);
+        out[2 * i + 1] = _mm256_srli_epi64(*col, 32);
+    }
+    out
+}
+
+/// Four independent Montgomery products `a[k] * b[k] * 2^-256 mod p`
+///
+/// The result is in the same Montgomery form as [`Fp`]'s scalar
+/// multiplication, since `R = 2^256` for both digit layouts.
+///
+/// # Safety
+///
+/// The CPU must support AVX2 (see [`enabled`]).
+#[target_feature(enable = "avx2")]
+pub unsafe fn mont_mul_x4<C: MontConfig<4>>(a: [Fp<C, 4>; 4], b: [Fp<C, 4>; 4]) -> [Fp<C, 4>; 4] {
+    let mask = _mm256_set1_epi64x(0xffff_ffff);
+    let p = digits(C::MODULUS).map(|d| _mm256_set1_epi64x(d as i64));
+    let inv = _mm256_set1_epi64x(C::INV as u32 as i64);
+    let av = load_digits(&a, mask);
+    let bv = load_digits(&b, mask);
+
+    // Each lane of t[j] holds a 32-bit digit; t[8], t[9] collect the overflow
+    let mut t = [_mm256_setzero_si256(); 10];
+    for bi in bv {
+        let mut carry = _mm256_setzero_si256();
+        for j in 0..8 {
+            let sum = _mm256_add_epi64(_mm256_add_epi64(t[j], carry), _mm256_mul_epu32(av[j], bi));
+            t[j] = _mm256_and_si256(sum, mask);
+            carry = _mm256_srli_epi64(sum, 32);
+        }
+        let sum = _mm256_add_epi64(t[8], carry);
+        t[8] = _mm256_and_si256(sum, mask);
+        t[9] = _mm256_srli_epi64(sum, 32);
+
+        let m = _mm256_and_si256(_mm256_mul_epu32(t[0], inv), mask);
+        let mut carry = _mm256_srli_epi64(_mm256_add_epi64(t[0], _mm256_mul_epu32(m, p[0])), 32);
+        for j in 1..8 {
+            let sum = _mm256_add_epi64(_mm256_add_epi64(t[j], carry), _mm256_mul_epu32(m, p[j]));
+            t[j - 1] = _mm256_and_si256(sum, mask);
+            carry = _mm256_srli_epi64(sum, 32);
+        }
+        let sum = _mm256_add_epi64(t[8], carry);
+        t[7] = _mm256_and_si256(sum, mask);
+        t[8] = _mm256_add_epi64(t[9], _mm256_srli_epi64(sum, 32));
+    }
+
+    let mut cols = [_mm256_setzero_si256(); 4];
+    for (i, col) in cols.iter_mut().enumerate() {
+        *col = _mm256_or_si256(t[2 * i], _mm256_slli_epi64(t[2 * i + 1], 32));
+    }
+    let rows = transpose(cols);
+    let mut overflow = [0u64; 4];
+    _mm256_storeu_si256(overflow.as_mut_ptr() as *mut __m256i, t[8]);
+    std::array::from_fn(|k| {
+        let mut limbs = [0u64; 4];
+        _mm256_storeu_si256(limbs.as_mut_ptr() as *mut __m256i, rows[k]);
+        Fp::from_montgomery_reduced(limbs, overflow[k] != 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::Fr;
+
+    #[test]
+    fn test_mont_mul_x4_matches_scalar() {
+        if !enabled() {
+            return;
+        }
+        let mut x = [
+            Fr::from_u64(0x1234_5678_9abc_def0),
+            -Fr::one(),
+            Fr::zero(),
+            Fr::from_u64(7),
+        ];
+        let mut y = [
+            -Fr::from_u64(3),
+            -Fr::one(),
+            Fr::from_u64(5),
+            -Fr::from_u64(11),
+        ];
+        for _ in 0..100 {
+            // SAFETY: AVX2 support was checked above
+            let products = unsafe { mont_mul_x4(x, y) };
+            for k in 0..4 {
+                assert_eq!(products[k], x[k] * y[k]);
+            }
+            x = std::array::from_fn(|k| x[k] * y[k] + Fr::one());
+            y = std::array::from_fn(|k| y[k].square() + x[(k + 1) % 4]);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! AVX-512 and Intel AMX detection
+//!
+//! The AVX-512 subsets come from std's cached CPUID/XGETBV checks. Intel AMX
+//! (tile matrix multiply, unrelated to Apple's AMX coprocessor) is not
+//! detectable on stable std, so CPUID leaf 7 and XCR0 are read directly and
+//! decoded separately so the decoding can be tested on any host.
+
+use napi_derive::napi;
+
+/// CPUID.(EAX=7, ECX=0):EDX bit for AMX-BF16
+pub const CPUID7_EDX_AMX_BF16: u32 = 1 << 22;
+/// CPUID.(EAX=7, ECX=0):EDX bit for AMX-TILE
+pub const CPUID7_EDX_AMX_TILE: u32 = 1 << 24;
+/// XCR0 bits for the XTILECFG and XTILEDATA state components
+pub const XCR0_AMX_TILE_STATE: u64 = (1 << 17) | (1 << 18);
+
+/// AVX-512 subsets and Intel AMX support exposed to JavaScript
+#[napi(object)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Avx512Capabilities {
+    /// AVX-512 Foundation
+    pub has_avx512f: bool,
+    /// Byte and word instructions (AVX512BW)
+    pub has_avx512bw: bool,
+    /// Vector byte permutes (AVX512_VBMI)
+    pub has_avx512vbmi: bool,
+    /// 52-bit integer multiply-add (AVX512_IFMA)
+    pub has_avx512_ifma: bool,
+    /// Per-lane population count (AVX512_VPOPCNTDQ)
+    pub has_avx512_vpopcntdq: bool,
+    /// Intel AMX BF16 tile multiply with OS-enabled tile state
+    pub has_amx_bf16: bool,
+}
+
+/// Detect AVX-512 subsets and Intel AMX (always all false off x86_64)
+#[napi]
+pub fn detect_avx512_capabilities() -> Avx512Capabilities {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::is_x86_feature_detected as has;
+
+        Avx512Capabilities {
+            has_avx512f: has!("avx512f"),
+            has_avx512bw: has!("avx512bw"),
+            has_avx512vbmi: has!("avx512vbmi"),
+            has_avx512_ifma: has!("avx512ifma"),
+            has_avx512_vpopcntdq: has!("avx512vpopcntdq"),
+            has_amx_bf16: detect_amx_bf16(),
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        Avx512Capabilities::default()
+    }
+}
+
+/// Decode AMX-BF16 from CPUID leaf 7 EDX and XCR0
+///
+/// The instructions are only usable when the OS saves tile state, so both
+/// tile components must be enabled in XCR0.
+pub fn decode_amx_bf16(cpuid7_edx: u32, xcr0: u64) -> bool {
+    let cpu = CPUID7_EDX_AMX_TILE | CPUID7_EDX_AMX_BF16;
+    cpuid7_edx & cpu == cpu && xcr0 & XCR0_AMX_TILE_STATE == XCR0_AMX_TILE_STATE
+}
+
+#[cfg(target_arch = "x86_64")]
+fn detect_amx_bf16() -> bool {
+    use std::arch::x86_64::{__cpuid, __cpuid_count};
+
+    const CPUID1_ECX_OSXSAVE: u32 = 1 << 27;
+    if __cpuid(0).eax < 7 || __cpuid(1).ecx & CPUID1_ECX_OSXSAVE == 0 {
+        return false;
+    }
+    // SAFETY: OSXSAVE means the OS has enabled XGETBV
+    let xcr0 = unsafe { xgetbv0() };
+    decode_amx_bf16(__cpuid_count(7, 0).edx, xcr0)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "xsave")]
+unsafe fn xgetbv0() -> u64 {
+    std::arch::x86_64::_xgetbv(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_avx512_capabilities() {
+        let caps = detect_avx512_capabilities();
+        if caps.has_avx512bw || caps.has_avx512vbmi || caps.has_avx512_ifma {
+            assert!(caps.has_avx512f);
+        }
+        if !cfg!(target_arch = "x86_64") {
+            assert_eq!(caps, Avx512Capabilities::default());
+        }
+        let rust_caps = crate::detect_rust_capabilities();
+        assert_eq!(rust_caps.has_avx512f, caps.has_avx512f);
+        assert_eq!(rust_caps.has_avx512bw, caps.has_avx512bw);
+        assert_eq!(rust_caps.has_avx512vbmi, caps.has_avx512vbmi);
+        assert_eq!(rust_caps.has_avx512ifma, caps.has_avx512_ifma);
+    }
+
+    #[test]
+    fn test_decode_amx_bf16() {
+        // Sapphire Rapids with a tile-enabled kernel
+        let spr_edx = CPUID7_EDX_AMX_TILE | CPUID7_EDX_AMX_BF16 | 1 << 25;
+        assert!(decode_amx_bf16(spr_edx, 0x602e7));
+        // Same CPU, kernel without AMX support (XCR0 lacks the tile state)
+        assert!(!decode_amx_bf16(spr_edx, 0x2e7));
+        // Ice Lake: no AMX bits
+        assert!(!decode_amx_bf16(0, 0x2e7));
+        assert!(!decode_amx_bf16(CPUID7_EDX_AMX_BF16, XCR0_AMX_TILE_STATE));
+    }
+}
@@ -0,0 +1,303 @@
+//! BabyBear field `p = 2^31 - 2^27 + 1` batch arithmetic
+//!
+//! Elements cross the NAPI boundary as `Uint32Array`s and are kept in
+//! Montgomery form with `R = 2^32`: the add, sub and mul batches take and
+//! return Montgomery-form values, so data converted once with
+//! `babybear_to_montgomery_batch` can stay in that form across calls.
+//!
+//! On aarch64 every operation runs four lanes per NEON instruction (see
+//! [`crate::neon::U32x4`]); reductions use `min` instead of branches.
+
+use napi::bindgen_prelude::Uint32Array;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::neon::U32x4;
+
+/// The BabyBear prime `2^31 - 2^27 + 1`
+pub const MODULUS: u32 = 0x7800_0001;
+
+/// `p^-1 mod 2^32`
+const P_INV: u32 = 0x8800_0001;
+
+/// `R^2 mod p`, multiplied in to enter Montgomery form
+const R2: u32 = 0x45dd_dde3;
+
+/// Below this many elements the batch functions run on the calling thread
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// `a + b mod p` for `a, b < p`
+#[inline]
+pub fn add(a: u32, b: u32) -> u32 {
+    // 2p < 2^32, so the sum cannot wrap
+    let sum = a + b;
+    if sum >= MODULUS {
+        sum - MODULUS
+    } else {
+        sum
+    }
+}
+
+/// `a - b mod p` for `a, b < p`
+#[inline]
+pub fn sub(a: u32, b: u32) -> u32 {
+    let (diff, under) = a.overflowing_sub(b);
+    if under {
+        diff.wrapping_add(MODULUS)
+    } else {
+        diff
+    }
+}
+
+/// Montgomery product `a * b / R mod p` for `a, b < p`
+#[inline]
+pub fn mont_mul(a: u32, b: u32) -> u32 {
+    let t = a as u64 * b as u64;
+    // m·p agrees with t in the low 32 bits, so t - m·p is an exact multiple of R
+    let m = (t as u32).wrapping_mul(P_INV);
+    let (diff, under) = t.overflowing_sub(m as u64 * MODULUS as u64);
+    let hi = (diff >> 32) as u32;
+    if under {
+        hi.wrapping_add(MODULUS)
+    } else {
+        hi
+    }
+}
+
+/// Montgomery form of a canonical value
+#[inline]
+pub fn to_montgomery(x: u32) -> u32 {
+    mont_mul(x, R2)
+}
+
+/// Canonical value of a Montgomery-form element
+#[inline]
+pub fn from_montgomery(x: u32) -> u32 {
+    mont_mul(x, 1)
+}
+
+#[inline(always)]
+fn add_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let sum = a.wrapping_add(b);
+    // Below p the subtraction wraps to a larger value and min keeps the sum
+    sum.min(sum.wrapping_sub(U32x4::splat(MODULUS)))
+}
+
+#[inline(always)]
+fn sub_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let diff = a.wrapping_sub(b);
+    // After a borrow diff + p wraps back below p, otherwise it is larger
+    diff.min(diff.wrapping_add(U32x4::splat(MODULUS)))
+}
+
+#[inline(always)]
+fn mont_mul_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let p = U32x4::splat(MODULUS);
+    let m = a.mul_lo(b).mul_lo(U32x4::splat(P_INV));
+    // The low halves of a·b and m·p cancel; the high halves differ by (-p, p)
+    let diff = a.mul_hi(b).wrapping_sub(m.mul_hi(p));
+    diff.min(diff.wrapping_add(p))
+}
+
+/// `out[i] = f(a[i], b(i))`, four lanes at a time when `neon` is set
+fn map_quads(
+    a: &[u32],
+    b: impl Fn(usize) -> u32 + Sync,
+    neon: bool,
+    scalar: fn(u32, u32) -> u32,
+    vector: fn(U32x4, U32x4) -> U32x4,
+) -> Vec<u32> {
+    let mut out = vec![0u32; a.len()];
+    crate::parallel::par_chunks_mut(&mut out, 4, PARALLEL_THRESHOLD, |offset, chunk| {
+        let quads = if neon { chunk.len() & !3 } else { 0 };
+        for (q, o) in chunk[..quads].chunks_exact_mut(4).enumerate() {
+            let i = offset + 4 * q;
+            let x = U32x4::new(a[i..i + 4].try_into().unwrap());
+            let y = U32x4::new(std::array::from_fn(|k| b(i + k)));
+            o.copy_from_slice(&vector(x, y).lanes());
+        }
+        for (k, o) in chunk.iter_mut().enumerate().skip(quads) {
+            *o = scalar(a[offset + k], b(offset + k));
+        }
+    });
+    out
+}
+
+/// Element-wise `a + b`
+pub fn add_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, |i| b[i], crate::neon::enabled(), add, add_x4)
+}
+
+/// Element-wise `a - b`
+pub fn sub_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, |i| b[i], crate::neon::enabled(), sub, sub_x4)
+}
+
+/// Element-wise Montgomery product
+pub fn mul_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, |i| b[i], crate::neon::enabled(), mont_mul, mont_mul_x4)
+}
+
+/// Convert canonical values to Montgomery form
+pub fn to_montgomery_batch(values: &[u32]) -> Vec<u32> {
+    map_quads(
+        values,
+        |_| R2,
+        crate::neon::enabled(),
+        mont_mul,
+        mont_mul_x4,
+    )
+}
+
+/// Convert Montgomery-form values to canonical form
+pub fn from_montgomery_batch(values: &[u32]) -> Vec<u32> {
+    map_quads(values, |_| 1, crate::neon::enabled(), mont_mul, mont_mul_x4)
+}
+
+/// Reject the first element that is not below `p`
+fn check_reduced(values: &[u32], name: &str) -> Result<()> {
+    match values.iter().position(|&v| v >= MODULUS) {
+        Some(i) => Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}[{i}]: value is not below the BabyBear modulus"),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_pair(a: &[u32], b: &[u32]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same length, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    check_reduced(a, "a")?;
+    check_reduced(b, "b")
+}
+
+/// Element-wise BabyBear addition of Montgomery-form values below `p`
+#[napi]
+pub fn babybear_add_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(add_batch(&a, &b).into())
+}
+
+/// Element-wise BabyBear subtraction `a - b` of Montgomery-form values
+#[napi]
+pub fn babybear_sub_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(sub_batch(&a, &b).into())
+}
+
+/// Element-wise BabyBear multiplication of Montgomery-form values
+#[napi]
+pub fn babybear_mul_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(mul_batch(&a, &b).into())
+}
+
+/// Convert canonical values below `p` to Montgomery form
+#[napi]
+pub fn babybear_to_montgomery_batch(values: Uint32Array) -> Result<Uint32Array> {
+    check_reduced(&values, "values")?;
+    Ok(to_montgomery_batch(&values).into())
+}
+
+/// Convert Montgomery-form values back to canonical form
+#[napi]
+pub fn babybear_from_montgomery_batch(values: Uint32Array) -> Result<Uint32Array> {
+    check_reduced(&values, "values")?;
+    Ok(from_montgomery_batch(&values).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: u64 = MODULUS as u64;
+
+    fn samples() -> Vec<u32> {
+        let mut values = vec![0, 1, 2, (1 << 27) - 1, 1 << 30, MODULUS - 2, MODULUS - 1];
+        let mut x = 0x2545_f491u32;
+        for _ in 0..57 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            values.push(x % MODULUS);
+        }
+        values
+    }
+
+    #[test]
+    fn test_scalar_matches_u64() {
+        for &a in &samples() {
+            for &b in &samples() {
+                let (x, y) = (a as u64, b as u64);
+                assert_eq!(add(a, b) as u64, (x + y) % P, "{a} + {b}");
+                assert_eq!(sub(a, b) as u64, (x + P - y) % P, "{a} - {b}");
+                let product = from_montgomery(mont_mul(to_montgomery(a), to_montgomery(b)));
+                assert_eq!(product as u64, x * y % P, "{a} * {b}");
+            }
+            assert_eq!(from_montgomery(to_montgomery(a)), a);
+        }
+        assert_eq!(add(MODULUS - 1, 1), 0);
+        assert_eq!(sub(0, 1), MODULUS - 1);
+        // R mod p is the Montgomery form of one
+        assert_eq!(to_montgomery(1), 0x0fff_fffe);
+        let minus_one = to_montgomery(MODULUS - 1);
+        assert_eq!(from_montgomery(mont_mul(minus_one, minus_one)), 1);
+        assert_eq!(mont_mul(to_montgomery(0), minus_one), 0);
+    }
+
+    #[test]
+    fn test_vector_matches_scalar() {
+        let values = samples();
+        let (a, b): (Vec<u32>, Vec<u32>) = values
+            .iter()
+            .flat_map(|&x| values.iter().map(move |&y| (x, y)))
+            .unzip();
+        // One extra element leaves a scalar remainder after the quads
+        let (a, b) = (&a[..a.len() - 3], &b[..b.len() - 3]);
+        type Op = (fn(u32, u32) -> u32, fn(U32x4, U32x4) -> U32x4);
+        let ops: [Op; 3] = [(add, add_x4), (sub, sub_x4), (mont_mul, mont_mul_x4)];
+        for (scalar, vector) in ops {
+            let expected = map_quads(a, |i| b[i], false, scalar, vector);
+            assert_eq!(map_quads(a, |i| b[i], true, scalar, vector), expected);
+            for i in 0..a.len() {
+                assert_eq!(expected[i], scalar(a[i], b[i]));
+            }
+        }
+        let canonical = map_quads(a, |_| 1, true, mont_mul, mont_mul_x4);
+        assert_eq!(
+            map_quads(&canonical, |_| R2, true, mont_mul, mont_mul_x4),
+            a
+        );
+    }
+
+    #[test]
+    fn test_batches() {
+        let n = 2 * PARALLEL_THRESHOLD + 7;
+        let a: Vec<u32> = (0..n as u32).map(|i| MODULUS - 1 - i).collect();
+        let b: Vec<u32> = (0..n as u32)
+            .map(|i| i.wrapping_mul(0x9e37_79b9) % MODULUS)
+            .collect();
+        let (ma, mb) = (to_montgomery_batch(&a), to_montgomery_batch(&b));
+        let sums = from_montgomery_batch(&add_batch(&ma, &mb));
+        let diffs = from_montgomery_batch(&sub_batch(&ma, &mb));
+        let products = from_montgomery_batch(&mul_batch(&ma, &mb));
+        for i in (0..n).step_by(331) {
+            let (x, y) = (a[i] as u64, b[i] as u64);
+            assert_eq!(sums[i] as u64, (x + y) % P);
+            assert_eq!(diffs[i] as u64, (x + P - y) % P);
+            assert_eq!(products[i] as u64, x * y % P);
+        }
+        let err = check_pair(&[0, MODULUS], &[0, 0]).unwrap_err();
+        assert_eq!(err.reason, "a[1]: value is not below the BabyBear modulus");
+        assert!(check_pair(&[0], &[]).is_err());
+    }
+}
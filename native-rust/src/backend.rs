@@ -0,0 +1,244 @@
+//! Backend registry
+//!
+//! Lists the kernel families compiled into this build and lets JavaScript pin
+//! dispatch to one of them. By default selection is automatic: each entry
+//! point picks the fastest available kernel for its input. After
+//! `set_active_backend(name)`, accelerated entry points (field batch
+//! multiplication, NTT) only use the pinned backend and fall back to scalar
+//! code where it has no kernel; `set_active_backend("auto")` restores
+//! automatic selection.
+
+use std::sync::RwLock;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Name that restores automatic selection
+pub const AUTO: &str = "auto";
+
+/// Kernel families the dispatcher can choose between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Scalar,
+    Neon,
+    Avx2,
+    Amx,
+    Metal,
+}
+
+/// Every backend, in listing order
+pub const ALL: [Backend; 5] = [
+    Backend::Scalar,
+    Backend::Neon,
+    Backend::Avx2,
+    Backend::Amx,
+    Backend::Metal,
+];
+
+impl Backend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Scalar => "scalar",
+            Backend::Neon => "neon",
+            Backend::Avx2 => "avx2",
+            Backend::Amx => "amx",
+            Backend::Metal => "metal",
+        }
+    }
+
+    pub fn kind(self) -> &'static str {
+        match self {
+            Backend::Scalar => "cpu-scalar",
+            Backend::Neon => "cpu-neon",
+            Backend::Avx2 => "cpu-avx2",
+            Backend::Amx => "cpu-amx",
+            Backend::Metal => "gpu-metal",
+        }
+    }
+
+    /// Whether this build can run the backend on the current machine
+    ///
+    /// AMX is listed for completeness but has no Rust kernels yet, so it is
+    /// never available here.
+    pub fn available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            Backend::Neon => crate::neon::supported(),
+            Backend::Avx2 => avx2_supported(),
+            Backend::Amx => false,
+            Backend::Metal => crate::gpu::metal_available(),
+        }
+    }
+}
+
+fn avx2_supported() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        crate::avx2::enabled()
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Explicitly selected backend, `None` for automatic selection
+static ACTIVE: RwLock<Option<Backend>> = RwLock::new(None);
+
+/// The pinned backend, or `None` when selection is automatic
+pub fn active() -> Option<Backend> {
+    *ACTIVE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether dispatch may use `backend`: always under automatic selection,
+/// otherwise only if it is the pinned one
+pub fn allows(backend: Backend) -> bool {
+    active().is_none_or(|b| b == backend)
+}
+
+/// Backend preferred by automatic selection: GPU, then SIMD, then scalar
+fn preferred() -> Backend {
+    [Backend::Metal, Backend::Neon, Backend::Avx2]
+        .into_iter()
+        .find(|b| b.available())
+        .unwrap_or(Backend::Scalar)
+}
+
+/// Resolve a backend name, rejecting unknown and unavailable ones
+///
+/// Returns `None` for [`AUTO`].
+pub fn parse(name: &str) -> Result<Option<Backend>> {
+    if name == AUTO {
+        return Ok(None);
+    }
+    let Some(backend) = ALL.into_iter().find(|b| b.name() == name) else {
+        let known: Vec<_> = ALL.iter().map(|b| b.name()).collect();
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "unknown backend {name:?}; expected {AUTO:?} or one of {}",
+                known.join(", ")
+            ),
+        ));
+    };
+    if !backend.available() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("backend {name:?} is not available on this machine"),
+        ));
+    }
+    Ok(Some(backend))
+}
+
+/// Backend description exposed to JavaScript
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendInfo {
+    /// Name accepted by `set_active_backend`
+    pub name: String,
+    /// "cpu-scalar", "cpu-neon", "cpu-avx2", "cpu-amx" or "gpu-metal"
+    pub kind: String,
+    /// Whether this build can run the backend on the current machine
+    pub available: bool,
+    /// Whether it is the pinned backend, or under automatic selection the
+    /// one preferred for large inputs
+    pub selected: bool,
+}
+
+/// List every backend with its availability and selection state
+#[napi]
+pub fn list_backends() -> Vec<BackendInfo> {
+    let selected = active().unwrap_or_else(preferred);
+    ALL.iter()
+        .map(|&b| BackendInfo {
+            name: b.name().to_string(),
+            kind: b.kind().to_string(),
+            available: b.available(),
+            selected: b == selected,
+        })
+        .collect()
+}
+
+/// Pin dispatch to one backend, or pass `"auto"` to restore automatic selection
+///
+/// Unknown and unavailable names are rejected and leave the selection
+/// unchanged.
+#[napi]
+pub fn set_active_backend(name: String) -> Result<()> {
+    let backend = parse(&name)?;
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = backend;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Held by tests that pin a backend or assert on automatic dispatch
+    static DISPATCH_LOCK: Mutex<()> = Mutex::new(());
+
+    pub(crate) fn lock_dispatch() -> MutexGuard<'static, ()> {
+        DISPATCH_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn test_list_backends() {
+        let _guard = lock_dispatch();
+        let backends = list_backends();
+        assert_eq!(backends.len(), ALL.len());
+        assert_eq!(backends.iter().filter(|b| b.selected).count(), 1);
+        assert!(backends.iter().all(|b| b.available || !b.selected));
+        let scalar = &backends[0];
+        assert_eq!(
+            (scalar.name.as_str(), scalar.kind.as_str()),
+            ("scalar", "cpu-scalar")
+        );
+        assert!(scalar.available);
+        let neon = backends.iter().find(|b| b.name == "neon").unwrap();
+        assert_eq!(
+            neon.available,
+            cfg!(target_arch = "aarch64") && !crate::overrides::get().disable_neon
+        );
+    }
+
+    #[test]
+    fn test_select_scalar() {
+        let _guard = lock_dispatch();
+        set_active_backend("scalar".to_string()).unwrap();
+        assert_eq!(active(), Some(Backend::Scalar));
+        assert!(!crate::neon::enabled());
+        assert_eq!(crate::ntt::kernel(), crate::ntt::Kernel::Scalar);
+        let selected: Vec<_> = list_backends().into_iter().filter(|b| b.selected).collect();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "scalar");
+        let a: Vec<_> = (1..=9).map(crate::bn254::Fr::from_u64).collect();
+        let products = crate::bn254::mul_batch(&a, &a);
+        assert_eq!(products[8], crate::bn254::Fr::from_u64(81));
+
+        set_active_backend(AUTO.to_string()).unwrap();
+        assert_eq!(active(), None);
+        assert!(allows(Backend::Neon) && allows(Backend::Metal));
+
+        assert_eq!(parse("scalar").unwrap(), Some(Backend::Scalar));
+        assert_eq!(parse(AUTO).unwrap(), None);
+        // Every available backend can be selected
+        for backend in ALL.into_iter().filter(|b| b.available()) {
+            assert_eq!(parse(backend.name()).unwrap(), Some(backend));
+        }
+    }
+
+    #[test]
+    fn test_rejects_bogus_and_unavailable() {
+        let _guard = lock_dispatch();
+        let before = active();
+        let err = set_active_backend("cuda".to_string()).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("unknown backend"));
+        assert!(set_active_backend("amx".to_string()).is_err());
+        if !cfg!(target_arch = "aarch64") {
+            assert!(set_active_backend("neon".to_string()).is_err());
+        }
+        assert_eq!(active(), before);
+    }
+}
@@ -0,0 +1,146 @@
+//! Barrett reduction for 256-bit prime fields
+//!
+//! The alternative to [`crate::montgomery`] for data that arrives and leaves
+//! in canonical form: operands are plain little-endian limbs, so a batch
+//! skips the conversions into and out of Montgomery form. Reducing a
+//! product needs `μ = ⌊2^512 / p⌋`, which [`Barrett::new`] computes by long
+//! division; callers build one per batch and share it across the elements.
+
+use crate::bigint::{add_limbs, add_mod, geq, mac, sub_limbs};
+
+/// Schoolbook `a * b` into `W >= N + M` limbs
+#[inline]
+pub(crate) fn mul_wide<const N: usize, const M: usize, const W: usize>(
+    a: &[u64; N],
+    b: &[u64; M],
+) -> [u64; W] {
+    debug_assert!(W >= N + M);
+    let mut out = [0u64; W];
+    for i in 0..N {
+        let mut carry = 0;
+        for j in 0..M {
+            let (lo, hi) = mac(out[i + j], a[i], b[j], carry);
+            out[i + j] = lo;
+            carry = hi;
+        }
+        out[i + M] = carry;
+    }
+    out
+}
+
+/// Barrett reduction context for one 256-bit modulus
+#[derive(Debug, Clone, Copy)]
+pub struct Barrett {
+    /// The modulus, widened by a zero limb
+    p: [u64; 5],
+    /// `⌊2^512 / p⌋`
+    mu: [u64; 5],
+}
+
+impl Barrett {
+    /// Precompute `μ` for `modulus`, which must be at least `2^192`
+    pub fn new(modulus: [u64; 4]) -> Self {
+        assert!(modulus[3] != 0, "Barrett modulus must fill the top limb");
+        let p = [modulus[0], modulus[1], modulus[2], modulus[3], 0];
+        // Long division of 2^512, one quotient bit at a time
+        let mut rem = [1u64, 0, 0, 0, 0];
+        let mut mu = [0u64; 5];
+        for bit in (0..512).rev() {
+            rem = add_limbs(&rem, &rem).0;
+            if geq(&rem, &p) {
+                rem = sub_limbs(&rem, &p).0;
+                mu[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Barrett { p, mu }
+    }
+
+    /// The modulus
+    pub fn modulus(&self) -> [u64; 4] {
+        [self.p[0], self.p[1], self.p[2], self.p[3]]
+    }
+
+    /// `x mod p` for any `x < 2^512`
+    pub fn reduce(&self, x: &[u64; 8]) -> [u64; 4] {
+        // q = ⌊⌊x / 2^192⌋ μ / 2^320⌋ undershoots ⌊x / p⌋ by at most 2
+        let q1 = [x[3], x[4], x[5], x[6], x[7]];
+        let q2: [u64; 10] = mul_wide(&q1, &self.mu);
+        let q3 = [q2[5], q2[6], q2[7], q2[8], q2[9]];
+        // x - q p, which fits in 320 bits, computed modulo 2^320
+        let qp: [u64; 10] = mul_wide(&q3, &self.p);
+        let low = [x[0], x[1], x[2], x[3], x[4]];
+        let mut r = sub_limbs(&low, &[qp[0], qp[1], qp[2], qp[3], qp[4]]).0;
+        while geq(&r, &self.p) {
+            r = sub_limbs(&r, &self.p).0;
+        }
+        [r[0], r[1], r[2], r[3]]
+    }
+
+    /// `a mod p` for any 256-bit `a`
+    pub fn reduce_narrow(&self, a: &[u64; 4]) -> [u64; 4] {
+        self.reduce(&[a[0], a[1], a[2], a[3], 0, 0, 0, 0])
+    }
+
+    /// `a * b mod p` for `a, b < p`
+    #[inline]
+    pub fn mul(&self, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        self.reduce(&mul_wide(a, b))
+    }
+
+    /// `a + b mod p` for `a, b < p`
+    #[inline]
+    pub fn add(&self, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        add_mod(a, b, &self.modulus())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::{Fr, FrConfig};
+    use crate::montgomery::MontConfig;
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+
+    #[test]
+    fn test_mu() {
+        // 2^512 / (2^255 - 19) = 2^257 + 76 + a fraction
+        let p = [
+            0xffff_ffff_ffff_ffed,
+            u64::MAX,
+            u64::MAX,
+            0x7fff_ffff_ffff_ffff,
+        ];
+        let m = Barrett::new(p);
+        assert_eq!(m.mu, [76, 0, 0, 0, 2]);
+        assert_eq!(m.modulus(), p);
+    }
+
+    #[test]
+    fn test_matches_montgomery() {
+        let m = Barrett::new(FrConfig::MODULUS);
+        let bytes = random_elements(FieldName::Bn254Fr, 2000, Some(&[5; SEED_BYTES])).unwrap();
+        let values: Vec<Fr> = bytes
+            .chunks_exact(32)
+            .map(|c| Fr::from_le_bytes(c).unwrap())
+            .collect();
+        let p_minus_one = -Fr::one();
+        let edges = [Fr::zero(), Fr::one(), p_minus_one];
+        for (x, y) in values.iter().zip(values.iter().rev()).chain(
+            edges
+                .iter()
+                .flat_map(|x| edges.iter().chain(&values[..4]).map(move |y| (x, y))),
+        ) {
+            let (a, b) = (x.to_canonical(), y.to_canonical());
+            assert_eq!(m.mul(&a, &b), (*x * *y).to_canonical(), "{x:?} * {y:?}");
+            assert_eq!(m.add(&a, &b), (*x + *y).to_canonical(), "{x:?} + {y:?}");
+        }
+        // Unreduced 256-bit input
+        let all_ones = [u64::MAX; 4];
+        assert_eq!(
+            m.reduce_narrow(&all_ones),
+            Fr::from_le_bytes_reduced(&[0xff; 32])
+                .unwrap()
+                .to_canonical()
+        );
+    }
+}
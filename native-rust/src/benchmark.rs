@@ -0,0 +1,294 @@
+//! Built-in micro-benchmarks for comparing machines
+//!
+//! `run_benchmarks` times a fixed workload per suite on the libuv thread
+//! pool and reports the mean and standard deviation of the measured runs
+//! after discarding the warmup runs. Inputs are generated from fixed seeds
+//! before timing starts, so results from different machines measure the
+//! same work. Every suite uses the crate's default backend and worker
+//! threads, as a caller of the corresponding binding would.
+
+#[cfg(not(feature = "wasm"))]
+use napi::bindgen_prelude::AsyncTask;
+#[cfg(not(feature = "wasm"))]
+use napi::{Env, Task};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::time::Instant;
+
+use crate::bn254::{self, Fr};
+use crate::random::{random_elements, FieldName, SEED_BYTES};
+
+/// Points in the `"ntt"` transform
+const NTT_SIZE: usize = 1 << 16;
+/// Points in the `"msm"` multi-scalar multiplication
+const MSM_SIZE: usize = 1 << 12;
+/// Two-input Poseidon hashes per `"poseidon"` run
+const POSEIDON_HASHES: usize = 1 << 10;
+/// Element-wise products per `"field_mul"` run
+const FIELD_MUL_SIZE: usize = 1 << 16;
+
+/// Which benchmarks to run and how often
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+    /// Measured runs per suite, at least 1
+    pub iterations: u32,
+    /// Untimed runs per suite before measuring
+    pub warmup_iterations: u32,
+    /// Any of "ntt", "msm", "poseidon" and "field_mul"; empty runs all
+    pub suites: Vec<String>,
+}
+
+/// Timing of one suite
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    pub suite: String,
+    /// Elements processed per run: points, hashes or products
+    pub elements: u32,
+    /// Mean wall-clock time per run
+    pub mean_ns: f64,
+    /// Sample standard deviation of the run times, 0 for a single run
+    pub stddev_ns: f64,
+    /// `elements` per second at the mean run time
+    pub throughput_per_sec: f64,
+}
+
+/// Results of `run_benchmarks`, in the order the suites were requested
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResults {
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// A benchmark workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Suite {
+    Ntt,
+    Msm,
+    Poseidon,
+    FieldMul,
+}
+
+impl Suite {
+    const ALL: [(&'static str, Suite); 4] = [
+        ("ntt", Suite::Ntt),
+        ("msm", Suite::Msm),
+        ("poseidon", Suite::Poseidon),
+        ("field_mul", Suite::FieldMul),
+    ];
+
+    fn parse(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, suite)| suite)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|(n, _)| *n).collect();
+                Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "suites: unknown suite \"{name}\", expected one of {}",
+                        known.join(", ")
+                    ),
+                )
+            })
+    }
+
+    fn elements(self) -> usize {
+        match self {
+            Suite::Ntt => NTT_SIZE,
+            Suite::Msm => MSM_SIZE,
+            Suite::Poseidon => POSEIDON_HASHES,
+            Suite::FieldMul => FIELD_MUL_SIZE,
+        }
+    }
+
+    /// The suite's inputs and a closure running it once on them
+    fn prepare(self) -> Result<Box<dyn FnMut() -> Result<()>>> {
+        let scalars = |n: usize, seed: u8| -> Result<Vec<Fr>> {
+            let bytes = random_elements(FieldName::Bn254Fr, n, Some(&[seed; SEED_BYTES]))?;
+            Ok(bytes
+                .chunks_exact(32)
+                .map(|b| Fr::from_le_bytes(b).expect("sampled below r"))
+                .collect())
+        };
+        Ok(match self {
+            Suite::Ntt => {
+                let input = scalars(NTT_SIZE, 1)?;
+                Box::new(move || {
+                    let mut values = input.clone();
+                    crate::ntt::ntt_in_place(&mut values, false)
+                })
+            }
+            Suite::Msm => {
+                let g = bn254::g1_generator().to_jacobian();
+                let mut acc = g;
+                let mut points = Vec::with_capacity(MSM_SIZE);
+                for _ in 0..MSM_SIZE {
+                    points.push(acc.to_affine());
+                    acc = acc.add(&g);
+                }
+                let limbs: Vec<[u64; 4]> = scalars(MSM_SIZE, 2)?
+                    .into_iter()
+                    .map(Fr::to_canonical)
+                    .collect();
+                Box::new(move || {
+                    std::hint::black_box(crate::msm::pippenger(&points, &limbs, true));
+                    Ok(())
+                })
+            }
+            Suite::Poseidon => {
+                let inputs = scalars(2 * POSEIDON_HASHES, 3)?;
+                Box::new(move || {
+                    for pair in inputs.chunks_exact(2) {
+                        std::hint::black_box(crate::poseidon::hash(pair)?);
+                    }
+                    Ok(())
+                })
+            }
+            Suite::FieldMul => {
+                let (a, b) = (scalars(FIELD_MUL_SIZE, 4)?, scalars(FIELD_MUL_SIZE, 5)?);
+                Box::new(move || {
+                    std::hint::black_box(bn254::mul_batch(&a, &b));
+                    Ok(())
+                })
+            }
+        })
+    }
+}
+
+/// Mean and sample standard deviation
+fn mean_and_stddev(samples: &[f64]) -> (f64, f64) {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    if samples.len() < 2 {
+        return (mean, 0.0);
+    }
+    let var = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var.sqrt())
+}
+
+/// Run the configured suites on the calling thread
+pub fn run(config: &BenchmarkConfig) -> Result<BenchmarkResults> {
+    if config.iterations == 0 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "iterations: must be at least 1".to_string(),
+        ));
+    }
+    let suites = if config.suites.is_empty() {
+        Suite::ALL.iter().map(|&(_, suite)| suite).collect()
+    } else {
+        config
+            .suites
+            .iter()
+            .map(|name| Suite::parse(name))
+            .collect::<Result<Vec<_>>>()?
+    };
+    let mut results = Vec::with_capacity(suites.len());
+    for suite in suites {
+        let mut once = suite.prepare()?;
+        for _ in 0..config.warmup_iterations {
+            once()?;
+        }
+        let mut samples = Vec::with_capacity(config.iterations as usize);
+        for _ in 0..config.iterations {
+            let start = Instant::now();
+            once()?;
+            samples.push(start.elapsed().as_nanos() as f64);
+        }
+        let (mean_ns, stddev_ns) = mean_and_stddev(&samples);
+        let name = Suite::ALL.iter().find(|&&(_, s)| s == suite).unwrap().0;
+        results.push(BenchmarkResult {
+            suite: name.to_string(),
+            elements: suite.elements() as u32,
+            mean_ns,
+            stddev_ns,
+            throughput_per_sec: suite.elements() as f64 / (mean_ns * 1e-9),
+        });
+    }
+    Ok(BenchmarkResults { results })
+}
+
+/// Background task behind `run_benchmarks`
+#[cfg(not(feature = "wasm"))]
+pub struct BenchmarkTask {
+    config: BenchmarkConfig,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl Task for BenchmarkTask {
+    type Output = BenchmarkResults;
+    type JsValue = BenchmarkResults;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        run(&self.config)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Time the built-in benchmark suites off the main thread
+///
+/// Suites and their workloads per run: "ntt", a 2^16-point BN254 NTT;
+/// "msm", a 2^12-point BN254 G1 multi-scalar multiplication; "poseidon",
+/// 2^10 two-input circomlib Poseidon hashes; "field_mul", 2^16 BN254
+/// scalar products. Unknown suite names and zero `iterations` reject. Not
+/// in the `wasm` build.
+#[cfg(not(feature = "wasm"))]
+#[napi(ts_return_type = "Promise<BenchmarkResults>")]
+pub fn run_benchmarks(config: BenchmarkConfig) -> AsyncTask<BenchmarkTask> {
+    AsyncTask::new(BenchmarkTask { config })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(iterations: u32, suites: &[&str]) -> BenchmarkConfig {
+        BenchmarkConfig {
+            iterations,
+            warmup_iterations: 2,
+            suites: suites.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_run_benchmarks() {
+        let suites = ["ntt", "msm", "poseidon", "field_mul"];
+        let mut task = BenchmarkTask {
+            config: config(10, &suites),
+        };
+        let results = task.compute().unwrap().results;
+        let names: Vec<&str> = results.iter().map(|r| r.suite.as_str()).collect();
+        assert_eq!(names, suites);
+        for r in &results {
+            assert!(r.mean_ns > 0.0, "{r:?}");
+            assert!(r.stddev_ns >= 0.0 && r.throughput_per_sec > 0.0, "{r:?}");
+        }
+        assert_eq!(results[1].elements, MSM_SIZE as u32);
+    }
+
+    #[test]
+    fn test_config() {
+        let results = run(&config(1, &["field_mul", "field_mul"]))
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].stddev_ns, 0.0);
+        assert_eq!(run(&config(1, &[])).unwrap().results.len(), 4);
+
+        let err = run(&config(0, &["ntt"])).unwrap_err();
+        assert_eq!(err.reason, "iterations: must be at least 1");
+        let err = run(&config(1, &["fft"])).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "suites: unknown suite \"fft\", expected one of ntt, msm, poseidon, field_mul"
+        );
+        assert_eq!(mean_and_stddev(&[1.0, 3.0]), (2.0, 2f64.sqrt()));
+    }
+}
@@ -0,0 +1,590 @@
+//! Multi-limb integer arithmetic under the prime field types
+//!
+//! Numbers are little-endian arrays of 64-bit limbs. The carry primitives and
+//! modular add/sub are generic over the limb count; the 256-bit Montgomery
+//! product, which every 4-limb field multiplies with, has its own kernel:
+//! CIOS with the carry chains in `mul`/`umulh`/`adcs` assembly on aarch64,
+//! and a portable `u128` version elsewhere. The portable kernel stays
+//! available everywhere as the reference `mont_mul_selftest` checks against.
+//! 384-bit products on aarch64 multiply by one level of Karatsuba and
+//! reduce separately, with the generic CIOS loop kept as their reference.
+
+/// Add with carry: returns `(a + b + carry) mod 2^64` and the outgoing carry
+#[inline(always)]
+pub(crate) const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// Subtract with borrow: returns `(a - b - borrow) mod 2^64` and the outgoing borrow (0 or 1)
+#[inline(always)]
+pub(crate) const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, ((t >> 64) as u64) & 1)
+}
+
+/// Multiply-accumulate: returns `a + b * c + carry` split into low and high words
+#[inline(always)]
+pub(crate) const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + (b as u128) * (c as u128) + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// Compare two little-endian limb arrays
+#[inline]
+pub(crate) const fn geq<const N: usize>(a: &[u64; N], b: &[u64; N]) -> bool {
+    let mut i = N;
+    while i > 0 {
+        i -= 1;
+        if a[i] != b[i] {
+            return a[i] > b[i];
+        }
+    }
+    true
+}
+
+/// `a - b`, returning the wrapped difference and the final borrow
+#[inline]
+pub(crate) const fn sub_limbs<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u64) {
+    let mut out = [0u64; N];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < N {
+        let (d, br) = sbb(a[i], b[i], borrow);
+        out[i] = d;
+        borrow = br;
+        i += 1;
+    }
+    (out, borrow)
+}
+
+/// `a + b`, returning the wrapped sum and the final carry
+#[inline]
+pub(crate) const fn add_limbs<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u64) {
+    let mut out = [0u64; N];
+    let mut carry = 0;
+    let mut i = 0;
+    while i < N {
+        let (s, c) = adc(a[i], b[i], carry);
+        out[i] = s;
+        carry = c;
+        i += 1;
+    }
+    (out, carry)
+}
+
+/// `a + b mod p` for `a, b < p`
+#[inline]
+pub(crate) fn add_mod<const N: usize>(a: &[u64; N], b: &[u64; N], p: &[u64; N]) -> [u64; N] {
+    let (sum, carry) = add_limbs(a, b);
+    if carry != 0 || geq(&sum, p) {
+        sub_limbs(&sum, p).0
+    } else {
+        sum
+    }
+}
+
+/// `a - b mod p` for `a, b < p`
+#[inline]
+pub(crate) fn sub_mod<const N: usize>(a: &[u64; N], b: &[u64; N], p: &[u64; N]) -> [u64; N] {
+    let (diff, borrow) = sub_limbs(a, b);
+    if borrow != 0 {
+        add_limbs(&diff, p).0
+    } else {
+        diff
+    }
+}
+
+/// CIOS Montgomery product over any limb count, before the final subtraction
+///
+/// Returns `(t, t_hi)` with `t + t_hi * 2^(64N) = a * b * 2^(-64N) mod p`,
+/// less than `2p`. `inv` is `-p^-1 mod 2^64`.
+#[inline]
+pub(crate) fn mont_mul_generic<const N: usize>(
+    a: &[u64; N],
+    b: &[u64; N],
+    p: &[u64; N],
+    inv: u64,
+) -> ([u64; N], u64) {
+    let mut t = [0u64; N];
+    let mut t_hi = 0u64;
+    for &bi in b.iter() {
+        let mut carry = 0;
+        for j in 0..N {
+            let (lo, hi) = mac(t[j], a[j], bi, carry);
+            t[j] = lo;
+            carry = hi;
+        }
+        let (s, top) = adc(t_hi, carry, 0);
+        t_hi = s;
+
+        let m = t[0].wrapping_mul(inv);
+        let (_, mut carry) = mac(t[0], m, p[0], 0);
+        for j in 1..N {
+            let (lo, hi) = mac(t[j], m, p[j], carry);
+            t[j - 1] = lo;
+            carry = hi;
+        }
+        let (s, c) = adc(t_hi, carry, 0);
+        t[N - 1] = s;
+        t_hi = top + c;
+    }
+    (t, t_hi)
+}
+
+/// Montgomery reduction (SOS) of a `2N`-limb product, before the final
+/// subtraction
+///
+/// Returns `(t, t_hi)` with `t + t_hi * 2^(64N) = x * 2^(-64N) mod p`, less
+/// than `2p` for `x < p * 2^(64N)`. Each row picks the same `m` as
+/// [`mont_mul_generic`], so the two agree limb for limb.
+#[inline]
+pub(crate) fn mont_reduce<const N: usize, const W: usize>(
+    mut x: [u64; W],
+    p: &[u64; N],
+    inv: u64,
+) -> ([u64; N], u64) {
+    debug_assert_eq!(W, 2 * N);
+    // Carry out of the top of each row, owed to the next row's top limb
+    let mut carry_hi = 0;
+    for i in 0..N {
+        let m = x[i].wrapping_mul(inv);
+        let mut carry = 0;
+        for j in 0..N {
+            let (lo, hi) = mac(x[i + j], m, p[j], carry);
+            x[i + j] = lo;
+            carry = hi;
+        }
+        let (s, c) = adc(x[i + N], carry, carry_hi);
+        x[i + N] = s;
+        carry_hi = c;
+    }
+    (x[N..].try_into().unwrap(), carry_hi)
+}
+
+/// Schoolbook 3 x 3-limb product
+#[inline(always)]
+fn mul_3x3(a: &[u64; 3], b: &[u64; 3]) -> [u64; 6] {
+    let mut out = [0u64; 6];
+    for i in 0..3 {
+        let mut carry = 0;
+        for j in 0..3 {
+            let (lo, hi) = mac(out[i + j], a[i], b[j], carry);
+            out[i + j] = lo;
+            carry = hi;
+        }
+        out[i + 3] = carry;
+    }
+    out
+}
+
+/// 384-bit product by one level of Karatsuba over 192-bit halves
+///
+/// Three 3 x 3-limb products instead of the 36 limb products of schoolbook,
+/// at the cost of the half sums and the middle term's subtractions.
+#[inline]
+pub(crate) fn mul_384_karatsuba(a: &[u64; 6], b: &[u64; 6]) -> [u64; 12] {
+    let (a0, a1) = ([a[0], a[1], a[2]], [a[3], a[4], a[5]]);
+    let (b0, b1) = ([b[0], b[1], b[2]], [b[3], b[4], b[5]]);
+    let lo = mul_3x3(&a0, &b0);
+    let hi = mul_3x3(&a1, &b1);
+
+    // (a0 + a1)(b0 + b1), where each half sum may carry into a fourth limb
+    let (sa, ca) = add_limbs(&a0, &a1);
+    let (sb, cb) = add_limbs(&b0, &b1);
+    let prod = mul_3x3(&sa, &sb);
+    let mut mid = [prod[0], prod[1], prod[2], prod[3], prod[4], prod[5], 0];
+    let mask = |c: u64, x: &[u64; 3]| {
+        [
+            x[0] & c.wrapping_neg(),
+            x[1] & c.wrapping_neg(),
+            x[2] & c.wrapping_neg(),
+        ]
+    };
+    for term in [mask(ca, &sb), mask(cb, &sa)] {
+        let (upper, c) = add_limbs(&[mid[3], mid[4], mid[5]], &term);
+        mid[3..6].copy_from_slice(&upper);
+        mid[6] += c;
+    }
+    mid[6] += ca & cb;
+
+    // mid - lo - hi = a0 b1 + a1 b0, which is non-negative
+    let widen = |x: &[u64; 6]| [x[0], x[1], x[2], x[3], x[4], x[5], 0];
+    let mid = sub_limbs(&sub_limbs(&mid, &widen(&lo)).0, &widen(&hi)).0;
+
+    let mut out = [0u64; 12];
+    out[..6].copy_from_slice(&lo);
+    out[6..].copy_from_slice(&hi);
+    let mut carry = 0;
+    for i in 0..7 {
+        (out[i + 3], carry) = adc(out[i + 3], mid[i], carry);
+    }
+    for limb in &mut out[10..] {
+        (*limb, carry) = adc(*limb, 0, carry);
+    }
+    out
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Route 384-bit products through `mont_mul_generic` on this thread, so
+    /// whole computations can be checked against the schoolbook path
+    pub(crate) static SCHOOLBOOK_384: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Run `f` with 384-bit products on the schoolbook path
+#[cfg(test)]
+pub(crate) fn with_schoolbook_384<T>(f: impl FnOnce() -> T) -> T {
+    SCHOOLBOOK_384.set(true);
+    let out = f();
+    SCHOOLBOOK_384.set(false);
+    out
+}
+
+/// 384-bit Montgomery product on the fastest kernel for this target
+///
+/// Karatsuba multiplication then [`mont_reduce`] on aarch64, where `umulh`
+/// latency hides the extra additions; elsewhere `mont_mul_generic::<6>`,
+/// which `u128` multiplies already keep ahead. Test builds take Karatsuba
+/// on every target so it is checked everywhere, and switch back to the
+/// schoolbook kernel with `with_schoolbook_384`. Both give the same limbs.
+#[inline]
+pub(crate) fn mont_mul_384(a: &[u64; 6], b: &[u64; 6], p: &[u64; 6], inv: u64) -> ([u64; 6], u64) {
+    #[cfg(test)]
+    if SCHOOLBOOK_384.get() {
+        return mont_mul_generic(a, b, p, inv);
+    }
+    #[cfg(any(target_arch = "aarch64", test))]
+    {
+        mont_reduce(mul_384_karatsuba(a, b), p, inv)
+    }
+    #[cfg(not(any(target_arch = "aarch64", test)))]
+    {
+        mont_mul_generic(a, b, p, inv)
+    }
+}
+
+/// Name of the kernel [`mont_mul_384`] runs
+pub fn mont_mul_384_kernel() -> &'static str {
+    if cfg!(target_arch = "aarch64") {
+        "karatsuba"
+    } else {
+        "schoolbook"
+    }
+}
+
+/// Portable 256-bit CIOS product, the reference for the assembly kernel
+#[inline]
+pub(crate) fn mont_mul_portable(
+    a: &[u64; 4],
+    b: &[u64; 4],
+    p: &[u64; 4],
+    inv: u64,
+) -> ([u64; 4], u64) {
+    mont_mul_generic(a, b, p, inv)
+}
+
+/// 256-bit CIOS product with each row's carry chain in one assembly block
+///
+/// `mul` and `umulh` leave the flags alone, so each row's products are
+/// interleaved with the `adds`/`adcs` chain that consumes them.
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub(crate) fn mont_mul_aarch64(
+    a: &[u64; 4],
+    b: &[u64; 4],
+    p: &[u64; 4],
+    inv: u64,
+) -> ([u64; 4], u64) {
+    let [mut t0, mut t1, mut t2, mut t3, mut t4] = [0u64; 5];
+    for &bi in b {
+        // SAFETY: register-only arithmetic with no memory access
+        unsafe {
+            std::arch::asm!(
+                // (t0..t4, top) += a * bi: low words, then high words one limb up
+                "mul {x}, {a0}, {bi}",
+                "adds {t0}, {t0}, {x}",
+                "mul {x}, {a1}, {bi}",
+                "adcs {t1}, {t1}, {x}",
+                "mul {x}, {a2}, {bi}",
+                "adcs {t2}, {t2}, {x}",
+                "mul {x}, {a3}, {bi}",
+                "adcs {t3}, {t3}, {x}",
+                "adcs {t4}, {t4}, xzr",
+                "cset {top}, cs",
+                "umulh {x}, {a0}, {bi}",
+                "adds {t1}, {t1}, {x}",
+                "umulh {x}, {a1}, {bi}",
+                "adcs {t2}, {t2}, {x}",
+                "umulh {x}, {a2}, {bi}",
+                "adcs {t3}, {t3}, {x}",
+                "umulh {x}, {a3}, {bi}",
+                "adcs {t4}, {t4}, {x}",
+                "adc {top}, {top}, xzr",
+                // m = t0 * inv; t += m * p clears t0, then shift down a limb
+                "mul {m}, {t0}, {inv}",
+                "mul {x}, {m}, {p0}",
+                "cmn {t0}, {x}",
+                "mul {x}, {m}, {p1}",
+                "adcs {t1}, {t1}, {x}",
+                "mul {x}, {m}, {p2}",
+                "adcs {t2}, {t2}, {x}",
+                "mul {x}, {m}, {p3}",
+                "adcs {t3}, {t3}, {x}",
+                "adcs {t4}, {t4}, xzr",
+                "adc {top}, {top}, xzr",
+                "umulh {x}, {m}, {p0}",
+                "adds {t0}, {t1}, {x}",
+                "umulh {x}, {m}, {p1}",
+                "adcs {t1}, {t2}, {x}",
+                "umulh {x}, {m}, {p2}",
+                "adcs {t2}, {t3}, {x}",
+                "umulh {x}, {m}, {p3}",
+                "adcs {t3}, {t4}, {x}",
+                "adc {t4}, {top}, xzr",
+                a0 = in(reg) a[0],
+                a1 = in(reg) a[1],
+                a2 = in(reg) a[2],
+                a3 = in(reg) a[3],
+                p0 = in(reg) p[0],
+                p1 = in(reg) p[1],
+                p2 = in(reg) p[2],
+                p3 = in(reg) p[3],
+                bi = in(reg) bi,
+                inv = in(reg) inv,
+                t0 = inout(reg) t0,
+                t1 = inout(reg) t1,
+                t2 = inout(reg) t2,
+                t3 = inout(reg) t3,
+                t4 = inout(reg) t4,
+                top = out(reg) _,
+                m = out(reg) _,
+                x = out(reg) _,
+                options(pure, nomem, nostack),
+            );
+        }
+    }
+    ([t0, t1, t2, t3], t4)
+}
+
+/// 256-bit CIOS product on the fastest kernel for this target
+#[inline]
+pub(crate) fn mont_mul(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4], inv: u64) -> ([u64; 4], u64) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        mont_mul_aarch64(a, b, p, inv)
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        mont_mul_portable(a, b, p, inv)
+    }
+}
+
+/// Name of the kernel [`mont_mul`] runs
+pub fn mont_mul_kernel() -> &'static str {
+    if cfg!(target_arch = "aarch64") {
+        "aarch64"
+    } else {
+        "portable"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::montgomery::MontConfig;
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    /// `(modulus, inv)` of every 256-bit field with its sampling name
+    fn fields() -> [(FieldName, [u64; 4], u64); 5] {
+        fn params<C: MontConfig<4>>(field: FieldName) -> (FieldName, [u64; 4], u64) {
+            (field, C::MODULUS, C::INV)
+        }
+        [
+            params::<crate::bn254::FrConfig>(FieldName::Bn254Fr),
+            params::<crate::bn254::FqConfig>(FieldName::Bn254Fq),
+            params::<crate::bls12_381::FrConfig>(FieldName::Bls12_381Fr),
+            params::<crate::pasta::FpConfig>(FieldName::PallasFp),
+            params::<crate::pasta::FqConfig>(FieldName::VestaFp),
+        ]
+    }
+
+    fn random_values(field: FieldName, count: usize, seed: u8) -> Vec<[u64; 4]> {
+        random_elements(field, count, Some(&[seed; SEED_BYTES]))
+            .unwrap()
+            .chunks_exact(32)
+            .map(|c| {
+                std::array::from_fn(|i| u64::from_le_bytes(c[8 * i..8 * i + 8].try_into().unwrap()))
+            })
+            .collect()
+    }
+
+    /// Schoolbook `a * b` as `W = 2N` limbs
+    fn wide_mul<const N: usize, const W: usize>(a: &[u64; N], b: &[u64; N]) -> [u64; W] {
+        let mut out = [0u64; W];
+        for i in 0..N {
+            let mut carry = 0;
+            for j in 0..N {
+                let (lo, hi) = mac(out[i + j], a[i], b[j], carry);
+                out[i + j] = lo;
+                carry = hi;
+            }
+            out[i + N] = carry;
+        }
+        out
+    }
+
+    /// `x mod p` by shift-and-subtract, one bit at a time
+    fn rem(x: &[u64; 8], p: &[u64; 4]) -> [u64; 4] {
+        let mut r = [0u64; 4];
+        for bit in (0..512).rev() {
+            let top = r[3] >> 63;
+            r = add_limbs(&r, &r).0;
+            r[0] |= (x[bit / 64] >> (bit % 64)) & 1;
+            if top != 0 || geq(&r, p) {
+                r = sub_limbs(&r, p).0;
+            }
+        }
+        r
+    }
+
+    /// Check `mont_mul` against `t * 2^256 = a * b mod p`
+    fn check_product(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4], inv: u64) {
+        let (t, t_hi) = mont_mul(a, b, p, inv);
+        assert_eq!((t, t_hi), mont_mul_portable(a, b, p, inv));
+        let t = if t_hi != 0 || geq(&t, p) {
+            sub_limbs(&t, p).0
+        } else {
+            t
+        };
+        assert!(!geq(&t, p), "{a:x?} * {b:x?}");
+        let shifted = [0, 0, 0, 0, t[0], t[1], t[2], t[3]];
+        assert_eq!(rem(&shifted, p), rem(&wide_mul(a, b), p), "{a:x?} * {b:x?}");
+    }
+
+    /// An arbitrary value below `p`, by clearing the excess of the top limb
+    #[cfg(not(target_arch = "wasm32"))]
+    fn below<const N: usize>(mut x: [u64; N], p: &[u64; N]) -> [u64; N] {
+        x[N - 1] %= p[N - 1];
+        x
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn prop_mont_mul_agrees_with_portable(
+            field in 0..5usize,
+            a in any::<[u64; 4]>(),
+            b in any::<[u64; 4]>(),
+        ) {
+            let (_, p, inv) = fields()[field];
+            let (a, b) = (below(a, &p), below(b, &p));
+            check_product(&a, &b, &p, inv);
+            check_product(&a, &a, &p, inv);
+        }
+
+        #[test]
+        fn prop_add_sub_mod_invert(
+            field in 0..5usize,
+            a in any::<[u64; 4]>(),
+            b in any::<[u64; 4]>(),
+        ) {
+            let (_, p, _) = fields()[field];
+            let (x, y) = (below(a, &p), below(b, &p));
+            let sum = add_mod(&x, &y, &p);
+            prop_assert!(!geq(&sum, &p));
+            prop_assert_eq!(sub_mod(&sum, &y, &p), x);
+            prop_assert_eq!(add_mod(&sub_mod(&x, &y, &p), &y, &p), x);
+            prop_assert_eq!(geq(&x, &y), sub_limbs(&x, &y).1 == 0);
+        }
+
+        #[test]
+        fn prop_mul_384_karatsuba_matches_schoolbook(
+            a in any::<[u64; 6]>(),
+            b in any::<[u64; 6]>(),
+        ) {
+            // Full-width operands, so the half sums carry as often as not
+            prop_assert_eq!(mul_384_karatsuba(&a, &b), wide_mul(&a, &b));
+        }
+
+        #[test]
+        fn prop_mont_mul_384_matches_generic(a in any::<[u64; 6]>(), b in any::<[u64; 6]>()) {
+            use crate::bls12_381::FpConfig;
+            let (p, inv) = (FpConfig::MODULUS, FpConfig::INV);
+            let (a, b) = (below(a, &p), below(b, &p));
+            // Limb for limb, before the final subtraction too
+            prop_assert_eq!(mont_mul_384(&a, &b, &p, inv), mont_mul_generic(&a, &b, &p, inv));
+            prop_assert_eq!(
+                mont_reduce(wide_mul::<6, 12>(&a, &b), &p, inv),
+                mont_mul_generic(&a, &b, &p, inv)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mont_mul_edge_cases() {
+        for (field, p, inv) in fields() {
+            let a = random_values(field, 1, 1)[0];
+            let p_minus_one = sub_limbs(&p, &[1, 0, 0, 0]).0;
+            for edge in [[0; 4], [1, 0, 0, 0], p_minus_one] {
+                check_product(&edge, &p_minus_one, &p, inv);
+                check_product(&edge, &a, &p, inv);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mont_mul_generic_limb_counts() {
+        // The generic kernel at the 6-limb BLS12-381 base field
+        use crate::bls12_381::FpConfig;
+        let (p, inv) = (FpConfig::MODULUS, FpConfig::INV);
+        let one_r = FpConfig::R;
+        let x = [7, 0, 0, 0, 0, 1];
+        // x * R * R^-1 = x
+        let (t, t_hi) = mont_mul_generic(&x, &one_r, &p, inv);
+        assert_eq!((t, t_hi), (x, 0));
+    }
+
+    #[test]
+    fn test_mul_384_karatsuba_edge_cases() {
+        let cases = [
+            [0; 6],
+            [1, 0, 0, 0, 0, 0],
+            [u64::MAX; 6],
+            [0, 0, 0, u64::MAX, u64::MAX, u64::MAX],
+        ];
+        for a in &cases {
+            for b in &cases {
+                assert_eq!(mul_384_karatsuba(a, b), wide_mul(a, b), "{a:x?} * {b:x?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_mont_mul_384_edge_cases() {
+        use crate::bls12_381::FpConfig;
+        let (p, inv) = (FpConfig::MODULUS, FpConfig::INV);
+        let p_minus_one = sub_limbs(&p, &[1, 0, 0, 0, 0, 0]).0;
+        let edges = [[0; 6], [1, 0, 0, 0, 0, 0], FpConfig::R, p_minus_one];
+        for a in &edges {
+            for b in &edges {
+                assert_eq!(
+                    mont_mul_384(a, b, &p, inv),
+                    mont_mul_generic(a, b, &p, inv),
+                    "{a:x?} * {b:x?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_sub_mod_edge_cases() {
+        for (_, p, _) in fields() {
+            let p_minus_one = sub_limbs(&p, &[1, 0, 0, 0]).0;
+            assert_eq!(add_mod(&p_minus_one, &[1, 0, 0, 0], &p), [0; 4]);
+            assert_eq!(sub_mod(&[0; 4], &[1, 0, 0, 0], &p), p_minus_one);
+        }
+    }
+}
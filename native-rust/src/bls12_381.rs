@@ -0,0 +1,1987 @@
+//! BLS12-381 G1, G2 and pairing arithmetic
+//!
+//! G1 points cross the NAPI boundary as 96-byte uncompressed affine
+//! encodings (`x || y`, each coordinate 48 bytes big-endian). G2 points use
+//! the 192-byte uncompressed ZCash layout, where each Fp2 coordinate is
+//! written `c1 || c0`. The point at infinity is encoded as all zero bytes,
+//! except in the `bls12_381_g1_*_batch` functions, which follow the ZCash
+//! serialization and set its infinity flag.
+//! Arithmetic runs in Jacobian coordinates and converts to affine only when
+//! serializing. The `bls12_381_fr_*` functions work on Buffers of packed
+//! 32-byte little-endian scalars, and the `bls12_381_fp2_*` functions on
+//! packed 96-byte Fp2 elements `c0 || c1`, each coefficient 48 bytes
+//! little-endian as in arkworks (unlike the big-endian coordinates of the
+//! point encodings). Pairing outputs are 576-byte Fp12
+//! elements: the twelve Fp coefficients in tower order (`c0.c0.c0`,
+//! `c0.c0.c1`, `c0.c1.c0`, ..., `c1.c2.c1`), each 48 bytes big-endian.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+use crate::constant_time;
+use crate::ec::{Affine, Jacobian, SwCurve};
+use crate::field::{
+    barrett_elementwise, elementwise, inv_batch, pow_batch, sqrt_batch, Form, Reduction,
+    SqrtBatchResult,
+};
+use crate::montgomery::{Field, Fp as PrimeField, MontConfig};
+use crate::pairing::{final_exponentiation, multi_miller_loop, PairingConfig, PairingInput, Twist};
+use crate::tower::{self, TowerConfig};
+use crate::validate::ValidationResult;
+
+/// BLS12-381 base field `p`
+#[derive(Debug, Clone, Copy)]
+pub struct FpConfig;
+
+impl MontConfig<6> for FpConfig {
+    const MODULUS: [u64; 6] = [
+        0xb9feffffffffaaab,
+        0x1eabfffeb153ffff,
+        0x6730d2a0f6b0f624,
+        0x64774b84f38512bf,
+        0x4b1ba7b6434bacd7,
+        0x1a0111ea397fe69a,
+    ];
+}
+
+/// Element of the BLS12-381 base field
+pub type Fp = PrimeField<FpConfig, 6>;
+
+/// Size in bytes of an encoded base field element
+pub const FP_BYTES: usize = 48;
+
+/// Size in bytes of an uncompressed affine G1 point
+pub const G1_BYTES: usize = 2 * FP_BYTES;
+
+/// Size in bytes of a scalar
+pub const SCALAR_BYTES: usize = 32;
+
+/// Size in bytes of an uncompressed affine G2 point
+pub const G2_BYTES: usize = 4 * FP_BYTES;
+
+/// BLS12-381 scalar field `r`
+#[derive(Debug, Clone, Copy)]
+pub struct FrConfig;
+
+impl MontConfig<4> for FrConfig {
+    const MODULUS: [u64; 4] = [
+        0xffffffff00000001,
+        0x53bda402fffe5bfe,
+        0x3339d80809a1d805,
+        0x73eda753299d7d48,
+    ];
+}
+
+/// Element of the BLS12-381 scalar field
+pub type Fr = PrimeField<FrConfig, 4>;
+
+/// The BLS12-381 extension tower, `ξ = 1 + u`
+#[derive(Debug, Clone, Copy)]
+pub struct Bls12Tower;
+
+static FROBENIUS_COEFFS: Lazy<[Fp2; 6]> = Lazy::new(tower::frobenius_coeffs::<Bls12Tower, 6>);
+
+impl TowerConfig<6> for Bls12Tower {
+    type Base = FpConfig;
+
+    const XI: [u64; 2] = [1, 1];
+
+    fn frobenius_coeffs() -> &'static [Fp2; 6] {
+        &FROBENIUS_COEFFS
+    }
+}
+
+pub type Fp2 = tower::Fp2<Bls12Tower, 6>;
+pub type Fp12 = tower::Fp12<Bls12Tower, 6>;
+
+/// The G1 curve `y^2 = x^3 + 4` over Fp
+#[derive(Debug, Clone, Copy)]
+pub struct G1Config;
+
+impl SwCurve for G1Config {
+    type Base = Fp;
+
+    fn coeff_b() -> Fp {
+        Fp::from_u64(4)
+    }
+}
+
+pub type G1Affine = Affine<G1Config>;
+pub type G1Projective = Jacobian<G1Config>;
+
+/// The G2 twist `y^2 = x^3 + 4 (1 + u)` over Fp2
+#[derive(Debug, Clone, Copy)]
+pub struct G2Config;
+
+impl SwCurve for G2Config {
+    type Base = Fp2;
+
+    fn coeff_b() -> Fp2 {
+        Fp2::new(Fp::from_u64(4), Fp::from_u64(4))
+    }
+}
+
+pub type G2Affine = Affine<G2Config>;
+pub type G2Projective = Jacobian<G2Config>;
+
+/// Optimal ate pairing parameters, `x = -0xd201000000010000`
+pub struct Bls12Pairing;
+
+impl PairingConfig<6> for Bls12Pairing {
+    type Tower = Bls12Tower;
+    type G1 = G1Config;
+    type G2 = G2Config;
+
+    const TWIST: Twist = Twist::M;
+    const LOOP_COUNT: &'static [u64] = &[0xd201000000010000];
+    const LOOP_IS_NEGATIVE: bool = true;
+    // 3 (p^4 - p^2 + 1) / r: the addition-chain hard parts of other BLS12-381
+    // libraries (zkcrypto's bls12_381 among them) compute this multiple, and
+    // using it here gives pairing outputs that compare equal to theirs
+    const FINAL_EXP_HARD: &'static [u64] = &[
+        0xaf444bdcaaab2f6b,
+        0xefcb3800a61a66d5,
+        0xb116bba59a18123a,
+        0x554e727d129be6a3,
+        0x8a65dbc1cc35fe5a,
+        0x6574220c23e5b6cc,
+        0x8c72178bc76a791c,
+        0xb415c7da454d4863,
+        0x37d47f639b68a630,
+        0x6a6a2b0ab1c47526,
+        0x6c9ae9625394f594,
+        0x6163cbf00bf566e4,
+        0x877c5e22f2639411,
+        0xb0f5836ba82b62de,
+        0xe3ae662e47a24ea0,
+        0x8d69012b6d183f47,
+        0xbcf13296f7a83fce,
+        0x7b69b9acc2cc45ea,
+        0x4237aa494c159cdc,
+        0x002e3941b8817705,
+    ];
+}
+
+const G1_GENERATOR_X: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+const G1_GENERATOR_Y: &str = "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+
+const G2_GENERATOR_X_C0: &str = "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+const G2_GENERATOR_X_C1: &str = "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e";
+const G2_GENERATOR_Y_C0: &str = "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801";
+const G2_GENERATOR_Y_C1: &str = "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be";
+
+/// Decode a fixed-size big-endian hex constant
+pub(crate) fn fp_from_hex(hex: &str) -> Fp {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    Fp::from_be_bytes(&bytes).expect("constant must be a canonical field element")
+}
+
+/// The standard G1 generator
+pub fn g1_generator() -> G1Affine {
+    G1Affine::new(fp_from_hex(G1_GENERATOR_X), fp_from_hex(G1_GENERATOR_Y))
+}
+
+/// The standard G2 generator
+pub fn g2_generator() -> G2Affine {
+    G2Affine::new(
+        Fp2::new(
+            fp_from_hex(G2_GENERATOR_X_C0),
+            fp_from_hex(G2_GENERATOR_X_C1),
+        ),
+        Fp2::new(
+            fp_from_hex(G2_GENERATOR_Y_C0),
+            fp_from_hex(G2_GENERATOR_Y_C1),
+        ),
+    )
+}
+
+/// Decode an uncompressed G1 point without checking the curve equation
+fn decode_g1_unchecked(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    if bytes.len() != G1_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G1_BYTES}-byte uncompressed G1 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G1Affine::identity());
+    }
+    let coord = |range: std::ops::Range<usize>, axis: &str| {
+        Fp::from_be_bytes(&bytes[range]).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                format!("{name}: {axis} coordinate is not below the BLS12-381 base field modulus"),
+            )
+        })
+    };
+    Ok(G1Affine::new(
+        coord(0..FP_BYTES, "x")?,
+        coord(FP_BYTES..G1_BYTES, "y")?,
+    ))
+}
+
+/// Decode an uncompressed G1 point, rejecting points that are not on the curve
+pub(crate) fn decode_g1(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    let point = decode_g1_unchecked(bytes, name)?;
+    if !point.is_on_curve() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not on the BLS12-381 G1 curve"),
+        ));
+    }
+    Ok(point)
+}
+
+/// Decode an uncompressed G1 point in the order-`r` subgroup
+pub(crate) fn decode_g1_subgroup(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    let point = decode_g1(bytes, name)?;
+    if !g1_in_subgroup(&point) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not in the BLS12-381 G1 subgroup"),
+        ));
+    }
+    Ok(point)
+}
+
+/// Encode a G1 point in 96-byte uncompressed affine form
+pub(crate) fn encode_g1(point: &G1Affine) -> Vec<u8> {
+    if point.infinity {
+        return vec![0u8; G1_BYTES];
+    }
+    let mut out = point.x.to_be_bytes();
+    out.extend(point.y.to_be_bytes());
+    out
+}
+
+/// Flag bits of the first byte of a ZCash-serialized point
+const FLAG_COMPRESSED: u8 = 0x80;
+const FLAG_INFINITY: u8 = 0x40;
+const FLAG_SORT: u8 = 0x20;
+
+/// Decode a 96-byte uncompressed G1 point in the ZCash serialization,
+/// where infinity is the infinity flag followed by zeros
+fn decode_g1_zcash(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    let invalid = |msg: &str| Err(Error::new(Status::InvalidArg, format!("{name}: {msg}")));
+    let flags = bytes[0] & (FLAG_COMPRESSED | FLAG_INFINITY | FLAG_SORT);
+    if flags & FLAG_COMPRESSED != 0 {
+        return invalid("compression flag set on an uncompressed G1 point");
+    }
+    if flags & FLAG_SORT != 0 {
+        return invalid("sort flag set on an uncompressed G1 point");
+    }
+    if flags & FLAG_INFINITY != 0 {
+        if bytes[0] != FLAG_INFINITY || bytes[1..].iter().any(|&b| b != 0) {
+            return invalid("infinity flag set with non-zero coordinates");
+        }
+        return Ok(G1Affine::identity());
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return invalid("all-zero encoding; infinity needs the infinity flag");
+    }
+    decode_g1(bytes, name)
+}
+
+/// Encode a G1 point in the 96-byte uncompressed ZCash serialization
+fn encode_g1_zcash(point: &G1Affine) -> Vec<u8> {
+    if point.infinity {
+        let mut out = vec![0u8; G1_BYTES];
+        out[0] = FLAG_INFINITY;
+        return out;
+    }
+    encode_g1(point)
+}
+
+fn decode_g1_zcash_batch(bytes: &[u8], name: &str) -> Result<Vec<G1Affine>> {
+    crate::zero_copy::elements(bytes, G1_BYTES, name)?
+        .enumerate()
+        .map(|(i, p)| decode_g1_zcash(p, &format!("{name}[{i}]")))
+        .collect()
+}
+
+/// Normalize Jacobian results together and encode them packed
+fn encode_g1_zcash_batch(points: &[G1Projective]) -> Vec<u8> {
+    G1Projective::batch_normalize(points)
+        .iter()
+        .flat_map(encode_g1_zcash)
+        .collect()
+}
+
+/// Normalize packed Jacobian triples of big-endian coordinates to packed
+/// ZCash-serialized G1 points
+pub fn g1_jacobian_to_affine_batch(points: &[u8]) -> Result<Vec<u8>> {
+    let affine =
+        crate::ec::decode_jacobian_batch(points, FP_BYTES, "BLS12-381", Fp::from_be_bytes)?;
+    Ok(affine.iter().flat_map(encode_g1_zcash).collect())
+}
+
+/// Compress packed ZCash-serialized G1 points to packed 48-byte points
+pub fn g1_compress_batch(points: &[u8]) -> Result<Vec<u8>> {
+    let points = decode_g1_zcash_batch(points, "points")?;
+    Ok(crate::ec::compress_batch(
+        &points,
+        crate::bls_sig::PUBLIC_KEY_BYTES,
+        crate::bls_sig::compress_g1,
+    ))
+}
+
+/// Decompress packed 48-byte G1 points to packed ZCash-serialized points
+pub fn g1_decompress_batch(compressed: &[u8]) -> Result<Vec<u8>> {
+    let points = crate::ec::decompress_batch(
+        compressed,
+        crate::bls_sig::PUBLIC_KEY_BYTES,
+        crate::bls_sig::decompress_g1,
+    )?;
+    Ok(points.iter().flat_map(encode_g1_zcash).collect())
+}
+
+/// Element-wise `a + b` over packed ZCash-serialized G1 points
+pub fn g1_add_batch(a: &[u8], b: &[u8]) -> Result<Vec<u8>> {
+    let a = decode_g1_zcash_batch(a, "a")?;
+    let b = decode_g1_zcash_batch(b, "b")?;
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same number of points, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    let sums: Vec<G1Projective> = a
+        .iter()
+        .zip(&b)
+        .map(|(a, b)| a.to_jacobian().add_affine(b))
+        .collect();
+    Ok(encode_g1_zcash_batch(&sums))
+}
+
+/// Element-wise `2 P` over packed ZCash-serialized G1 points
+pub fn g1_double_batch(points: &[u8]) -> Result<Vec<u8>> {
+    let doubles: Vec<G1Projective> = decode_g1_zcash_batch(points, "points")?
+        .iter()
+        .map(|p| p.to_jacobian().double())
+        .collect();
+    Ok(encode_g1_zcash_batch(&doubles))
+}
+
+/// Element-wise `-P` over packed ZCash-serialized G1 points
+pub fn g1_negate_batch(points: &[u8]) -> Result<Vec<u8>> {
+    Ok(decode_g1_zcash_batch(points, "points")?
+        .iter()
+        .flat_map(|p| encode_g1_zcash(&p.neg()))
+        .collect())
+}
+
+/// Decode a 192-byte uncompressed G2 point, rejecting points off the curve
+pub(crate) fn decode_g2(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    if bytes.len() != G2_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G2_BYTES}-byte uncompressed G2 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G2Affine::identity());
+    }
+    let fp = |i: usize| {
+        Fp::from_be_bytes(&bytes[i * FP_BYTES..(i + 1) * FP_BYTES]).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                format!("{name}: coordinate is not below the BLS12-381 base field modulus"),
+            )
+        })
+    };
+    let point = G2Affine::new(Fp2::new(fp(1)?, fp(0)?), Fp2::new(fp(3)?, fp(2)?));
+    if !point.is_on_curve() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not on the BLS12-381 G2 curve"),
+        ));
+    }
+    Ok(point)
+}
+
+/// Decode a 192-byte uncompressed G2 point in the order-`r` subgroup
+///
+/// The Miller loop only gives a bilinear result on the subgroup, so
+/// untrusted G2 inputs to pairings are decoded with this.
+pub(crate) fn decode_g2_subgroup(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    let point = decode_g2(bytes, name)?;
+    if !g2_in_subgroup(&point) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not in the BLS12-381 G2 subgroup"),
+        ));
+    }
+    Ok(point)
+}
+
+/// A point of order 13 on the G2 twist, from the 13-torsion of a twist
+/// point; the small-subgroup input of tests
+#[cfg(test)]
+pub(crate) fn g2_point_of_order_13() -> G2Affine {
+    // h / 13^2 for the twist cofactor h, which 13^2 divides exactly
+    const H_OVER_169: [u64; 8] = [
+        0x3336b3150941cfdd,
+        0xedf4046db800a837,
+        0x45e0aef29c5e8629,
+        0x5a5b213dcb710859,
+        0x81be2a9b0c648304,
+        0x60a5f9bdc250555d,
+        0xd5a3c5663541d68b,
+        0x0008d5fc7522f6c4,
+    ];
+    (1u64..)
+        .filter_map(|i| {
+            let x = Fp2::from_base(Fp::from_u64(i));
+            let y = (x.square() * x + G2Config::coeff_b()).sqrt()?;
+            // Order dividing 13^2, then 13
+            let q = G2Affine::new(x, y)
+                .to_jacobian()
+                .mul_limbs(&H_OVER_169)
+                .mul_limbs(&FrConfig::MODULUS);
+            let q13 = q.mul_limbs(&[13]);
+            let q = if q13.is_identity() { q } else { q13 };
+            (!q.is_identity()).then(|| q.to_affine())
+        })
+        .next()
+        .unwrap()
+}
+
+/// Encode a G2 point in 192-byte uncompressed affine form
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) fn encode_g2(point: &G2Affine) -> Vec<u8> {
+    if point.infinity {
+        return vec![0u8; G2_BYTES];
+    }
+    [point.x.c1, point.x.c0, point.y.c1, point.y.c0]
+        .iter()
+        .flat_map(|c| c.to_be_bytes())
+        .collect()
+}
+
+/// `β`, the cube root of unity in Fp for which `(β x, y) = [-x^2] (x, y)`
+/// on G1
+static G1_ENDO_BETA: Lazy<Fp> = Lazy::new(|| {
+    fp_from_hex("00000000000000005f19672fdf76ce51ba69c6076a0f77eaddb3a93be6f89688de17d813620a00022e01fffffffefffe")
+});
+
+/// `ξ^-((p - 1) / 3)` and `ξ^-((p - 1) / 2)`, scaling the conjugated
+/// coordinates in [`psi`]
+static PSI_COEFFS: Lazy<(Fp2, Fp2)> = Lazy::new(|| {
+    let c = Bls12Tower::frobenius_coeffs();
+    let inv = |c: Fp2| c.inverse().expect("ξ is non-zero");
+    (inv(c[2]), inv(c[3]))
+});
+
+/// `[|x|] P` for the curve parameter `x`
+fn mul_by_x<C: SwCurve>(p: &Jacobian<C>) -> Jacobian<C> {
+    p.mul_limbs(Bls12Pairing::LOOP_COUNT)
+}
+
+/// The untwist-Frobenius-twist endomorphism of the G2 twist
+fn psi(p: &G2Affine) -> G2Affine {
+    let (cx, cy) = *PSI_COEFFS;
+    G2Affine::new(p.x.conjugate() * cx, p.y.conjugate() * cy)
+}
+
+/// Whether a point on the G1 curve lies in the order-`r` subgroup
+///
+/// Scott's test (ePrint 2021/1130): exactly the subgroup has
+/// `(β x, y) = [-x^2] P`, two 64-bit multiplications instead of one by `r`.
+pub(crate) fn g1_in_subgroup(p: &G1Affine) -> bool {
+    let endo = G1Affine::new(*G1_ENDO_BETA * p.x, p.y);
+    p.infinity || endo.to_jacobian() == mul_by_x(&mul_by_x(&p.to_jacobian())).neg()
+}
+
+/// Whether a point on the G2 twist lies in the order-`r` subgroup
+///
+/// Exactly the subgroup has `ψ(P) = [x] P` (ePrint 2021/1130), one 64-bit
+/// multiplication.
+pub(crate) fn g2_in_subgroup(p: &G2Affine) -> bool {
+    p.infinity || psi(p).to_jacobian() == mul_by_x(&p.to_jacobian()).neg()
+}
+
+/// Subgroup check of packed ZCash-serialized G1 points
+pub fn g1_subgroup_check_batch(points: &[u8], stop_at_first: bool) -> Result<ValidationResult> {
+    crate::ec::subgroup_check_batch(points, G1_BYTES, stop_at_first, |p| {
+        decode_g1_zcash(p, "points").is_ok_and(|p| g1_in_subgroup(&p))
+    })
+}
+
+/// Subgroup check of packed 192-byte uncompressed G2 points
+pub fn g2_subgroup_check_batch(points: &[u8], stop_at_first: bool) -> Result<ValidationResult> {
+    crate::ec::subgroup_check_batch(points, G2_BYTES, stop_at_first, |p| {
+        decode_g2(p, "points").is_ok_and(|p| g2_in_subgroup(&p))
+    })
+}
+
+/// Decode a 32-byte little-endian BLS12-381 scalar
+pub(crate) fn decode_scalar_le(bytes: &[u8], name: &str) -> Result<Fr> {
+    if bytes.len() != 32 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a 32-byte little-endian scalar, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    Fr::from_le_bytes(bytes).ok_or_else(|| {
+        Error::new(
+            Status::InvalidArg,
+            format!("{name}: value is not below the BLS12-381 scalar field modulus"),
+        )
+    })
+}
+
+/// Add two BLS12-381 G1 points
+#[napi]
+pub fn g1_add(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    let a = decode_g1(&a, "a")?;
+    let b = decode_g1(&b, "b")?;
+    Ok(encode_g1(&a.to_jacobian().add_affine(&b).to_affine()))
+}
+
+/// Multiply a BLS12-381 G1 point by a 32-byte big-endian scalar
+///
+/// Double-and-add by default. With `constant_time` set, or omitted in
+/// constant-time mode, a Montgomery ladder over all 256 bits with complete
+/// formulas, so the work does not depend on the scalar.
+#[napi]
+pub fn g1_scalar_mul(
+    point: Vec<u8>,
+    scalar: Vec<u8>,
+    constant_time: Option<bool>,
+) -> Result<Vec<u8>> {
+    let point = decode_g1(&point, "point")?;
+    if scalar.len() != SCALAR_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "scalar: expected {SCALAR_BYTES} big-endian bytes, got {} bytes",
+                scalar.len()
+            ),
+        ));
+    }
+    let point = point.to_jacobian();
+    let product = if constant_time::resolve(constant_time) {
+        point.mul_be_bytes_ct(&scalar)
+    } else {
+        point.mul_be_bytes(&scalar)
+    };
+    Ok(encode_g1(&product.to_affine()))
+}
+
+/// Check whether a 96-byte uncompressed point lies on the BLS12-381 G1 curve
+///
+/// Non-canonical coordinates are reported as `false`; only a wrong input
+/// length is an error.
+#[napi]
+pub fn is_on_curve_g1(point: Vec<u8>) -> Result<bool> {
+    match decode_g1_unchecked(&point, "point") {
+        Ok(p) => Ok(p.is_on_curve()),
+        Err(_) if point.len() == G1_BYTES => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Add packed BLS12-381 G1 points pairwise
+///
+/// `a` and `b` hold the same number of 96-byte uncompressed points in the
+/// ZCash serialization used by blst: `x || y` big-endian, with the point at
+/// infinity written as the `0x40` infinity flag followed by zeros (not the
+/// all-zero encoding of `g1_add`). Sums are computed with mixed
+/// Jacobian-affine addition and normalized with one batched inversion.
+/// Malformed flags, coordinates `>= p` and points off the curve throw with
+/// the index of the offending point.
+#[napi]
+pub fn bls12_381_g1_add_batch(a: Buffer, b: Buffer) -> Result<Buffer> {
+    g1_add_batch(&a, &b).map(Buffer::from)
+}
+
+/// Double every packed BLS12-381 G1 point
+///
+/// Same layout and validation as [`bls12_381_g1_add_batch`].
+#[napi]
+pub fn bls12_381_g1_double_batch(points: Buffer) -> Result<Buffer> {
+    g1_double_batch(&points).map(Buffer::from)
+}
+
+/// Negate every packed BLS12-381 G1 point
+///
+/// Same layout and validation as [`bls12_381_g1_add_batch`].
+#[napi]
+pub fn bls12_381_g1_negate_batch(points: Buffer) -> Result<Buffer> {
+    g1_negate_batch(&points).map(Buffer::from)
+}
+
+/// Encode an Fp12 element as its twelve coefficients in tower order
+pub(crate) fn encode_fp12(f: &Fp12) -> Vec<u8> {
+    [f.c0, f.c1]
+        .iter()
+        .flat_map(|c| [c.c0, c.c1, c.c2])
+        .flat_map(|c| [c.c0, c.c1])
+        .flat_map(|c| c.to_be_bytes())
+        .collect()
+}
+
+/// Decode equal-length lists of G1 and G2 points into pairing inputs
+fn decode_pairs(ps: &[Vec<u8>], qs: &[Vec<u8>]) -> Result<Vec<PairingInput<Bls12Pairing, 6>>> {
+    if ps.len() != qs.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "ps and qs must have the same length, got {} and {}",
+                ps.len(),
+                qs.len()
+            ),
+        ));
+    }
+    ps.iter()
+        .zip(qs)
+        .enumerate()
+        .map(|(i, (p, q))| {
+            Ok((
+                decode_g1_subgroup(p, &format!("ps[{i}]"))?,
+                decode_g2_subgroup(q, &format!("qs[{i}]"))?,
+            ))
+        })
+        .collect()
+}
+
+/// Optimal ate pairing `e(p, q)` of a G1 and a G2 point
+///
+/// Returns the 576-byte Fp12 encoding; pairing with the point at infinity
+/// gives one. Points outside the prime-order subgroups throw, here and in
+/// the multi-pairing functions.
+#[napi]
+pub fn bls12_381_pairing(p: Vec<u8>, q: Vec<u8>) -> Result<Vec<u8>> {
+    let pairs = [(decode_g1_subgroup(&p, "p")?, decode_g2_subgroup(&q, "q")?)];
+    Ok(encode_fp12(&final_exponentiation::<Bls12Pairing, 6>(
+        &multi_miller_loop::<Bls12Pairing, 6>(&pairs),
+    )))
+}
+
+/// Product of pairings `prod e(ps[i], qs[i])`
+///
+/// The Miller loops share one accumulator and a single final
+/// exponentiation, so this is cheaper than multiplying separate pairings.
+#[napi]
+pub fn bls12_381_multi_pairing(ps: Vec<Vec<u8>>, qs: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let pairs = decode_pairs(&ps, &qs)?;
+    Ok(encode_fp12(&final_exponentiation::<Bls12Pairing, 6>(
+        &multi_miller_loop::<Bls12Pairing, 6>(&pairs),
+    )))
+}
+
+/// Whether `prod e(ps[i], qs[i])` is the identity, as in Groth16 verification
+#[napi]
+pub fn bls12_381_pairing_check(ps: Vec<Vec<u8>>, qs: Vec<Vec<u8>>) -> Result<bool> {
+    let pairs = decode_pairs(&ps, &qs)?;
+    Ok(crate::pairing::pairing_product_is_one::<Bls12Pairing, 6>(
+        &pairs,
+    ))
+}
+
+/// Field name used in the array functions' errors
+const FR_FIELD: &str = "BLS12-381 scalar field";
+
+/// Element-wise `a + b` over packed little-endian scalars in `form`
+pub fn fr_add(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FR_FIELD,
+        |[x, y]: [Fr; 2]| x + y,
+    )
+}
+
+/// Element-wise `a - b` over packed little-endian scalars in `form`
+pub fn fr_sub(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FR_FIELD,
+        |[x, y]: [Fr; 2]| x - y,
+    )
+}
+
+/// Element-wise `a * b` over packed little-endian scalars in `form`
+pub fn fr_mul(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FR_FIELD,
+        |[x, y]: [Fr; 2]| x * y,
+    )
+}
+
+/// Element-wise `a + b` over packed canonical little-endian scalars, with
+/// Barrett reduction
+pub fn fr_add_barrett(a: &[u8], b: &[u8], reduce: bool) -> Result<Vec<u8>> {
+    barrett_elementwise::<FrConfig, 2>([(a, "a"), (b, "b")], reduce, FR_FIELD, |m, [x, y]| {
+        m.add(&x, &y)
+    })
+}
+
+/// Element-wise `a * b` over packed canonical little-endian scalars, with
+/// Barrett reduction
+pub fn fr_mul_barrett(a: &[u8], b: &[u8], reduce: bool) -> Result<Vec<u8>> {
+    barrett_elementwise::<FrConfig, 2>([(a, "a"), (b, "b")], reduce, FR_FIELD, |m, [x, y]| {
+        m.mul(&x, &y)
+    })
+}
+
+/// Element-wise `-a` over packed little-endian scalars in `form`
+pub fn fr_neg(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a")],
+        reduce,
+        (form, form),
+        FR_FIELD,
+        |[x]: [Fr; 1]| -x,
+    )
+}
+
+/// Element-wise `a^2` over packed little-endian scalars in `form`
+pub fn fr_square(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a")],
+        reduce,
+        (form, form),
+        FR_FIELD,
+        |[x]: [Fr; 1]| x.square(),
+    )
+}
+
+/// Element-wise inverse of packed little-endian scalars in `form`
+pub fn fr_inv_batch(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    inv_batch::<Fr>(values, allow_zero, constant_time, form, FR_FIELD)
+}
+
+/// `x^exponent` over packed little-endian scalars in `form`
+pub fn fr_pow_batch(
+    bases: &[u8],
+    exponent: &[u8],
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    pow_batch::<FrConfig, 4>(bases, exponent, constant_time, form, FR_FIELD)
+}
+
+/// Re-encode packed little-endian scalars from one form to another
+pub fn fr_convert(values: &[u8], reduce: bool, from: Form, to: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(values, "values")],
+        reduce,
+        (from, to),
+        FR_FIELD,
+        |[x]: [Fr; 1]| x,
+    )
+}
+
+/// Element-wise `a + b` over packed 32-byte little-endian BLS12-381 scalars
+///
+/// `a` and `b` must be the same length. Values `>= r` are rejected unless
+/// `reduce` is set, in which case they are reduced modulo `r` first. With
+/// `in_montgomery_form` set, inputs and result are Montgomery-form limbs
+/// (see `bls12_381_fr_to_montgomery_batch`). `reduction: "barrett"` reduces
+/// canonical values with Barrett reduction instead of converting them to
+/// Montgomery form; the results are identical.
+#[napi]
+pub fn bls12_381_fr_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    reduction: Option<String>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    let reduce = reduce.unwrap_or(false);
+    match Reduction::parse(reduction.as_deref(), form)? {
+        Reduction::Montgomery => fr_add(&a, &b, reduce, form),
+        Reduction::Barrett => fr_add_barrett(&a, &b, reduce),
+    }
+    .map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed 32-byte little-endian BLS12-381 scalars
+#[napi]
+pub fn bls12_381_fr_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_sub(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed 32-byte little-endian BLS12-381 scalars
+///
+/// Options as for `bls12_381_fr_add`.
+#[napi]
+pub fn bls12_381_fr_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    reduction: Option<String>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    let reduce = reduce.unwrap_or(false);
+    match Reduction::parse(reduction.as_deref(), form)? {
+        Reduction::Montgomery => fr_mul(&a, &b, reduce, form),
+        Reduction::Barrett => fr_mul_barrett(&a, &b, reduce),
+    }
+    .map(Buffer::from)
+}
+
+/// Element-wise `-a` over packed 32-byte little-endian BLS12-381 scalars
+#[napi]
+pub fn bls12_381_fr_neg(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_neg(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a^2` over packed 32-byte little-endian BLS12-381 scalars
+#[napi]
+pub fn bls12_381_fr_square(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_square(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Convert packed canonical BLS12-381 scalars to Montgomery-form limbs
+#[napi]
+pub fn bls12_381_fr_to_montgomery_batch(values: Buffer, reduce: Option<bool>) -> Result<Buffer> {
+    fr_convert(
+        &values,
+        reduce.unwrap_or(false),
+        Form::Canonical,
+        Form::Montgomery,
+    )
+    .map(Buffer::from)
+}
+
+/// Convert packed Montgomery-form BLS12-381 scalars back to canonical values
+#[napi]
+pub fn bls12_381_fr_from_montgomery_batch(values: Buffer, reduce: Option<bool>) -> Result<Buffer> {
+    fr_convert(
+        &values,
+        reduce.unwrap_or(false),
+        Form::Montgomery,
+        Form::Canonical,
+    )
+    .map(Buffer::from)
+}
+
+/// Invert packed 32-byte little-endian BLS12-381 scalars with Montgomery's trick
+///
+/// Each worker chunk runs one prefix-product pass, a single field inversion
+/// and a backward pass, instead of one inversion per element. A zero
+/// element is an error naming its index unless `allow_zero` is set, in
+/// which case zeros map to zero. See `bn254_fr_inv_batch` for
+/// `constant_time`.
+#[napi]
+pub fn bls12_381_fr_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_inv_batch(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// `x^exponent` for packed 32-byte little-endian BLS12-381 scalars
+///
+/// See `bn254_fr_pow_batch`.
+#[napi]
+pub fn bls12_381_fr_pow_batch(
+    bases: Buffer,
+    exponent: Buffer,
+    constant_time: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_pow_batch(
+        &bases,
+        &exponent,
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// Field name used in the Fp and Fp2 array functions' errors
+const FP_FIELD: &str = "BLS12-381 base field";
+
+/// Element-wise `a + b` over packed little-endian base field elements in `form`
+pub fn fp_add(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp; 2]| x + y,
+    )
+}
+
+/// Element-wise `a - b` over packed little-endian base field elements in `form`
+pub fn fp_sub(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp; 2]| x - y,
+    )
+}
+
+/// Element-wise `a * b` over packed little-endian base field elements in `form`
+pub fn fp_mul(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp; 2]| x * y,
+    )
+}
+
+/// Element-wise inverse of packed little-endian base field elements in `form`
+pub fn fp_inv_batch(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    inv_batch::<Fp>(values, allow_zero, constant_time, form, FP_FIELD)
+}
+
+/// Size in bytes of a packed Fp2 element, `c0 || c1`
+pub const FP2_BYTES: usize = 2 * FP_BYTES;
+
+/// Element-wise `a + b` over packed little-endian Fp2 elements in `form`
+pub fn fp2_add(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp2; 2]| x + y,
+    )
+}
+
+/// Element-wise `a - b` over packed little-endian Fp2 elements in `form`
+pub fn fp2_sub(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp2; 2]| x - y,
+    )
+}
+
+/// Element-wise `a * b` over packed little-endian Fp2 elements in `form`
+pub fn fp2_mul(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x, y]: [Fp2; 2]| x * y,
+    )
+}
+
+/// Element-wise `a^2` over packed little-endian Fp2 elements in `form`
+pub fn fp2_square(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x]: [Fp2; 1]| x.square(),
+    )
+}
+
+/// Element-wise `c0 - c1 u` over packed little-endian Fp2 elements in `form`
+pub fn fp2_conjugate(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a")],
+        reduce,
+        (form, form),
+        FP_FIELD,
+        |[x]: [Fp2; 1]| x.conjugate(),
+    )
+}
+
+/// Element-wise inverse of packed little-endian Fp2 elements in `form`
+pub fn fp2_inv_batch(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    inv_batch::<Fp2>(values, allow_zero, constant_time, form, FP_FIELD)
+}
+
+/// Square roots of packed canonical little-endian base field elements
+///
+/// `p = 3 mod 4`, so the Tonelli-Shanks loop runs with `s = 1` and root of
+/// unity `-1`.
+pub fn fp_sqrt_batch(values: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    sqrt_batch(values, -Fp::one(), FP_FIELD)
+}
+
+/// Element-wise `a + b` over packed 48-byte little-endian BLS12-381 base
+/// field elements
+///
+/// `a` and `b` must be the same length. Values `>= p` are rejected unless
+/// `reduce` is set, in which case they are reduced modulo `p` first. With
+/// `in_montgomery_form` set, inputs and result are Montgomery-form limbs.
+#[napi]
+pub fn bls12_381_fp_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp_add(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed 48-byte little-endian BLS12-381 base
+/// field elements
+#[napi]
+pub fn bls12_381_fp_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp_sub(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed 48-byte little-endian BLS12-381 base
+/// field elements
+///
+/// Each product multiplies by one level of Karatsuba over 192-bit halves
+/// before the Montgomery reduction.
+#[napi]
+pub fn bls12_381_fp_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp_mul(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Base field product on an explicit 384-bit kernel, Karatsuba or the
+/// schoolbook CIOS loop, instead of the one this target dispatches to
+///
+/// Public only for `benches/fp_mul.rs`. Both kernels give the same result.
+#[doc(hidden)]
+pub fn fp_mul_with_kernel(a: &Fp, b: &Fp, karatsuba: bool) -> Fp {
+    let (x, y) = (a.to_montgomery_limbs(), b.to_montgomery_limbs());
+    let (p, inv) = (FpConfig::MODULUS, FpConfig::INV);
+    let (t, t_hi) = if karatsuba {
+        crate::bigint::mont_reduce(crate::bigint::mul_384_karatsuba(&x, &y), &p, inv)
+    } else {
+        crate::bigint::mont_mul_generic(&x, &y, &p, inv)
+    };
+    Fp::from_montgomery_reduced(t, t_hi != 0)
+}
+
+/// Invert packed 48-byte little-endian BLS12-381 base field elements with
+/// Montgomery's trick
+///
+/// A zero element is an error naming its index unless `allow_zero` is set,
+/// in which case zeros map to zero. See `bn254_fr_inv_batch` for
+/// `constant_time`.
+#[napi]
+pub fn bls12_381_fp_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp_inv_batch(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// Element-wise `a + b` over packed BLS12-381 Fp2 elements
+///
+/// Each element is 96 bytes, `c0 || c1` for `c0 + c1 u` with `u^2 = -1`,
+/// both coefficients 48 bytes little-endian. `a` and `b` must be the same
+/// length, a multiple of 96 bytes. Coefficients `>= p` are rejected unless
+/// `reduce` is set, in which case they are reduced modulo `p` first. With
+/// `in_montgomery_form` set, both coefficients are Montgomery-form limbs.
+#[napi]
+pub fn bls12_381_fp2_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_add(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed BLS12-381 Fp2 elements
+#[napi]
+pub fn bls12_381_fp2_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_sub(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed BLS12-381 Fp2 elements
+///
+/// Karatsuba: three base field multiplications per element.
+#[napi]
+pub fn bls12_381_fp2_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_mul(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a^2` over packed BLS12-381 Fp2 elements
+#[napi]
+pub fn bls12_381_fp2_square(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_square(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise conjugate `c0 - c1 u` over packed BLS12-381 Fp2 elements
+#[napi]
+pub fn bls12_381_fp2_conjugate(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_conjugate(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Invert packed BLS12-381 Fp2 elements with Montgomery's trick
+///
+/// A zero element is an error naming its index unless `allow_zero` is set,
+/// in which case zeros map to zero. See `bn254_fr_inv_batch` for
+/// `constant_time`.
+#[napi]
+pub fn bls12_381_fp2_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fp2_inv_batch(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// Square roots of packed 48-byte little-endian BLS12-381 base field elements
+///
+/// `roots` holds one root per element, the smaller of the two as an
+/// integer; `flags` is 1 where the element is a square (zero included) and
+/// 0 for a non-residue, whose root is left zero. Elements `>= p` are
+/// rejected.
+#[napi]
+pub fn bls12_381_fp_sqrt_batch(values: Buffer) -> Result<SqrtBatchResult> {
+    let (roots, flags) = fp_sqrt_batch(&values)?;
+    Ok(SqrtBatchResult {
+        roots: roots.into(),
+        flags: flags.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::montgomery::Field;
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn generator() -> Vec<u8> {
+        hex(&format!("{G1_GENERATOR_X}{G1_GENERATOR_Y}"))
+    }
+
+    fn scalar(v: u64) -> Vec<u8> {
+        let mut s = vec![0u8; 32];
+        s[24..].copy_from_slice(&v.to_be_bytes());
+        s
+    }
+
+    const TWO_G: &str = "0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+    const THREE_G: &str = "09ece308f9d1f0131765212deca99697b112d61f9be9a5f1f3780a51335b3ff981747a0b2ca2179b96d2c0c9024e5224032b80d3a6f5b09f8a84623389c5f80ca69a0cddabc3097f9d9c27310fd43be6e745256c634af45ca3473b0590ae30d1";
+    const GROUP_ORDER: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+
+    #[test]
+    fn test_generator_on_curve() {
+        assert!(is_on_curve_g1(generator()).unwrap());
+        assert!(is_on_curve_g1(vec![0u8; 96]).unwrap());
+        let mut off = generator();
+        off[95] ^= 1;
+        assert!(!is_on_curve_g1(off).unwrap());
+        assert!(is_on_curve_g1(vec![0u8; 95]).is_err());
+    }
+
+    #[test]
+    fn test_doubling() {
+        let g = generator();
+        assert_eq!(g1_add(g.clone(), g.clone()).unwrap(), hex(TWO_G));
+        assert_eq!(g1_add(hex(TWO_G), g.clone()).unwrap(), hex(THREE_G));
+        for ct in [Some(false), Some(true)] {
+            assert_eq!(g1_scalar_mul(g.clone(), scalar(2), ct).unwrap(), hex(TWO_G));
+            assert_eq!(
+                g1_scalar_mul(g.clone(), scalar(3), ct).unwrap(),
+                hex(THREE_G)
+            );
+        }
+    }
+
+    #[test]
+    fn test_identity_and_inverse() {
+        let g = generator();
+        let inf = vec![0u8; 96];
+        assert_eq!(g1_add(g.clone(), inf.clone()).unwrap(), g);
+        let neg = encode_g1(&decode_g1(&g, "g").unwrap().neg());
+        assert_eq!(g1_add(g.clone(), neg).unwrap(), inf);
+        for ct in [Some(false), Some(true)] {
+            assert_eq!(g1_scalar_mul(g.clone(), scalar(0), ct).unwrap(), inf);
+            assert_eq!(g1_scalar_mul(inf.clone(), scalar(5), ct).unwrap(), inf);
+            // The generator has prime order r
+            assert_eq!(g1_scalar_mul(g.clone(), hex(GROUP_ORDER), ct).unwrap(), inf);
+        }
+    }
+
+    /// `-G`: the generator's `x` with `p - y`
+    const NEG_G_Y: &str = "114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca";
+
+    fn zcash_infinity() -> Vec<u8> {
+        let mut inf = vec![0u8; G1_BYTES];
+        inf[0] = 0x40;
+        inf
+    }
+
+    fn packed(points: &[Vec<u8>]) -> Vec<u8> {
+        points.concat()
+    }
+
+    #[test]
+    fn test_g1_batch_known_answers() {
+        let (g, two_g, three_g) = (generator(), hex(TWO_G), hex(THREE_G));
+        let neg_g = hex(&format!("{G1_GENERATOR_X}{NEG_G_Y}"));
+        let inf = zcash_infinity();
+
+        let a = packed(&[
+            g.clone(),
+            two_g.clone(),
+            g.clone(),
+            inf.clone(),
+            inf.clone(),
+        ]);
+        let b = packed(&[g.clone(), g.clone(), neg_g.clone(), g.clone(), inf.clone()]);
+        assert_eq!(
+            g1_add_batch(&a, &b).unwrap(),
+            packed(&[two_g.clone(), three_g, inf.clone(), g.clone(), inf.clone()])
+        );
+        assert_eq!(
+            g1_double_batch(&packed(&[g.clone(), inf.clone()])).unwrap(),
+            packed(&[two_g.clone(), inf.clone()])
+        );
+        assert_eq!(
+            g1_negate_batch(&packed(&[g.clone(), neg_g.clone(), inf.clone()])).unwrap(),
+            packed(&[neg_g, g, inf])
+        );
+        // 2G + (-2G) through the batch path
+        let neg_two_g = g1_negate_batch(&two_g).unwrap();
+        assert_eq!(g1_add_batch(&two_g, &neg_two_g).unwrap(), zcash_infinity());
+        assert!(g1_double_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_g1_batch_rejects_invalid_encodings() {
+        let g = generator();
+        let reason = |result: Result<Vec<u8>>| result.unwrap_err().reason;
+        let batch = |bad: Vec<u8>| packed(&[g.clone(), bad]);
+
+        let mut compressed = g.clone();
+        compressed[0] |= 0x80;
+        assert_eq!(
+            reason(g1_double_batch(&batch(compressed))),
+            "points[1]: compression flag set on an uncompressed G1 point"
+        );
+        let mut sorted = g.clone();
+        sorted[0] |= 0x20;
+        assert_eq!(
+            reason(g1_negate_batch(&batch(sorted))),
+            "points[1]: sort flag set on an uncompressed G1 point"
+        );
+        let mut bad_infinity = zcash_infinity();
+        bad_infinity[95] = 1;
+        assert_eq!(
+            reason(g1_add_batch(
+                &batch(bad_infinity),
+                &packed(&[g.clone(), g.clone()])
+            )),
+            "a[1]: infinity flag set with non-zero coordinates"
+        );
+        assert_eq!(
+            reason(g1_double_batch(&batch(vec![0; G1_BYTES]))),
+            "points[1]: all-zero encoding; infinity needs the infinity flag"
+        );
+        let mut off_curve = g.clone();
+        off_curve[95] ^= 1;
+        assert_eq!(
+            reason(g1_add_batch(&batch(g.clone()), &batch(off_curve))),
+            "b[1]: point is not on the BLS12-381 G1 curve"
+        );
+        let mut wide = g.clone();
+        wide[48..].fill(0x1f);
+        assert_eq!(
+            reason(g1_double_batch(&batch(wide))),
+            "points[1]: y coordinate is not below the BLS12-381 base field modulus"
+        );
+        assert_eq!(
+            reason(g1_double_batch(&g[..95])),
+            "points: length 95 is not a multiple of 96 bytes"
+        );
+        assert_eq!(
+            reason(g1_add_batch(&batch(g.clone()), &g)),
+            "a and b must have the same number of points, got 2 and 1"
+        );
+    }
+
+    #[test]
+    fn test_g2_generator() {
+        let g2 = g2_generator();
+        assert!(g2.is_on_curve());
+        let r = FrConfig::MODULUS;
+        assert!(g2.to_jacobian().mul_limbs(&r).is_identity());
+        let bytes = encode_g2(&g2);
+        assert_eq!(decode_g2(&bytes, "g2").unwrap(), g2);
+        assert_eq!(&bytes[..2], &[0x13, 0xe0]);
+        assert!(decode_g2(&[0u8; G2_BYTES], "inf").unwrap().infinity);
+        let mut off = bytes;
+        off[191] ^= 1;
+        assert!(decode_g2(&off, "off").is_err());
+    }
+
+    #[test]
+    fn test_pairing_bilinear() {
+        use crate::pairing::{pairing, pairing_product_is_one};
+
+        let p = g1_generator();
+        let q = g2_generator();
+        let e = pairing::<Bls12Pairing, 6>(&p, &q);
+        assert_ne!(e, Fp12::one());
+        // e(P, Q)^r = 1
+        assert_eq!(e.pow(&FrConfig::MODULUS), Fp12::one());
+        // e(aP, bQ) = e(P, Q)^(ab)
+        let a_p = p.to_jacobian().mul_limbs(&[5]).to_affine();
+        let b_q = q.to_jacobian().mul_limbs(&[7]).to_affine();
+        assert_eq!(pairing::<Bls12Pairing, 6>(&a_p, &b_q), e.pow(&[35]));
+        // e(aP, Q) e(-P, aQ) = 1
+        let a_q = q.to_jacobian().mul_limbs(&[5]).to_affine();
+        assert!(pairing_product_is_one::<Bls12Pairing, 6>(&[
+            (a_p, q),
+            (p.neg(), a_q)
+        ]));
+        assert!(!pairing_product_is_one::<Bls12Pairing, 6>(&[
+            (a_p, q),
+            (p, a_q)
+        ]));
+        // Pairs with the identity contribute nothing
+        assert!(pairing_product_is_one::<Bls12Pairing, 6>(&[(
+            G1Affine::identity(),
+            q
+        )]));
+    }
+
+    #[test]
+    fn test_pairing_outside_subgroup() {
+        use crate::pairing::{multi_miller_loop, pairing};
+
+        let q13 = g2_point_of_order_13();
+        assert!(q13.is_on_curve());
+        assert!(q13.to_jacobian().mul_limbs(&[13]).is_identity());
+        // The Miller loop meets T = ±Q and T = O without panicking
+        let p = g1_generator();
+        pairing::<Bls12Pairing, 6>(&p, &q13);
+        multi_miller_loop::<Bls12Pairing, 6>(&[(p, q13), (p, g2_generator())]);
+
+        let err = decode_g2_subgroup(&encode_g2(&q13), "q").unwrap_err();
+        assert_eq!(err.reason, "q: point is not in the BLS12-381 G2 subgroup");
+        let g = decode_g2_subgroup(&encode_g2(&g2_generator()), "q").unwrap();
+        assert_eq!(g, g2_generator());
+    }
+
+    #[test]
+    fn test_known_scalar_multiple() {
+        // k * G for a fixed 256-bit k, computed with an affine Python reference
+        let k = hex("2b3c7d5e8f9a0b1c2d3e4f5061728394a5b6c7d8e9f0a1b2c3d4e5f607182930");
+        let expected = hex("023dbf34d626a2f71128ebf87cd1056e7c75efbefa7c5876b3e61e8162aeb66c7a211f0af7d64ea32c3deef60e8f3bcb0e174d47b579a2b0833065b1bb8c1a989b4b198892aa460471099b9cb94dcf179f88f5cb20cc9c0746305286a0e7b164");
+        assert_eq!(
+            g1_scalar_mul(generator(), k.clone(), Some(false)).unwrap(),
+            expected
+        );
+        assert_eq!(g1_scalar_mul(generator(), k, Some(true)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_fr_array_known_answers() {
+        // Computed with Python integers modulo r
+        let a = hex("45362718091a2b3c4d5e6f7a8b9c3d0e5f7a2b9c6ef2a8143d5ef0b7a23e1d6c");
+        let b = hex("88796a5b4c3d2e1f88796a5b4c3d2e1f88796a5b4c3d2e1f88796a5b4c3d2e1f");
+        assert_eq!(
+            fr_add(&a, &b, false, Form::Canonical).unwrap(),
+            hex("ccaf91735657595bd67bdbd5d435aed9e11bf4edb2579d007d5abde99bd45d17")
+        );
+        assert_eq!(
+            fr_sub(&b, &a, false, Form::Canonical).unwrap(),
+            hex("44434343422303e33977f9e0c344ae642ed7e0c8e522bf3d939817cdfca5fe26")
+        );
+        assert_eq!(
+            fr_mul(&a, &b, false, Form::Canonical).unwrap(),
+            hex("d87b36169e83384f18f4cebf9f5c18850ea5ee1ee54c8dc43792f0d856babf31")
+        );
+        assert_eq!(
+            fr_neg(&a, false, Form::Canonical).unwrap(),
+            hex("bcc9d8e7f5e5d4c3b1fd8e8577078045a65d766d99e5901e0b1fad71b068d007")
+        );
+        assert_eq!(
+            fr_square(&a, false, Form::Canonical).unwrap(),
+            hex("1256f07240fcfd57b0f02ec4080a3795a9020fc2c98cccd41c4357773ea8816c")
+        );
+        // -0 = 0 and (r - 1)^2 = 1
+        let r_minus_one = hex("00000000fffffffffe5bfeff02a4bd5305d8a10908d83933487d9d2953a7ed73");
+        let mut one = vec![0u8; 32];
+        one[0] = 1;
+        assert_eq!(fr_neg(&[0; 32], false, Form::Canonical).unwrap(), [0u8; 32]);
+        assert_eq!(
+            fr_square(&r_minus_one, false, Form::Canonical).unwrap(),
+            one
+        );
+        assert!(fr_square(&[], false, Form::Canonical).unwrap().is_empty());
+        assert!(fr_mul(&[], &[], false, Form::Canonical).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fr_array_two_adic_root() {
+        // arkworks' Fr::TWO_ADIC_ROOT_OF_UNITY = 7^((r - 1) / 2^32), computed
+        // with the array functions by square-and-multiply
+        let mut acc = vec![0u8; 32];
+        acc[0] = 1;
+        let mut seven = vec![0u8; 32];
+        seven[0] = 7;
+        let odd = [
+            0xfffe5bfeffffffffu64,
+            0x09a1d80553bda402,
+            0x299d7d483339d808,
+            0x0000000073eda753,
+        ];
+        for bit in (0..256).rev() {
+            acc = fr_square(&acc, false, Form::Canonical).unwrap();
+            if (odd[bit / 64] >> (bit % 64)) & 1 == 1 {
+                acc = fr_mul(&acc, &seven, false, Form::Canonical).unwrap();
+            }
+        }
+        assert_eq!(
+            acc,
+            hex("2b0d9f431f972938b980228c508336b6b413c82219689bd0201fe8df9ea1a216")
+        );
+        assert_eq!(acc, crate::plonk::root_of_unity(32).to_le_bytes());
+    }
+
+    #[test]
+    fn test_fr_array_rejects_non_canonical() {
+        let r = hex("01000000fffffffffe5bfeff02a4bd5305d8a10908d83933487d9d2953a7ed73");
+        let input = [vec![0; 32], vec![0; 32], r.clone()].concat();
+        let err = fr_square(&input, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.starts_with("a[2]: "), "{}", err.reason);
+        assert!(err.reason.contains("BLS12-381"), "{}", err.reason);
+        assert_eq!(fr_neg(&r, true, Form::Canonical).unwrap(), [0u8; 32]);
+
+        let err = fr_add(&[0; 32], &[0; 31], false, Form::Canonical).unwrap_err();
+        assert!(err.reason.contains("multiple of 32"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fr_barrett_matches_montgomery() {
+        let bytes = random_elements(FieldName::Bls12_381Fr, 600, Some(&[3; SEED_BYTES])).unwrap();
+        let (a, b) = bytes.split_at(300 * 32);
+        assert_eq!(
+            fr_mul_barrett(a, b, false).unwrap(),
+            fr_mul(a, b, false, Form::Canonical).unwrap()
+        );
+        assert_eq!(
+            fr_add_barrett(a, b, false).unwrap(),
+            fr_add(a, b, false, Form::Canonical).unwrap()
+        );
+        let high = vec![0xff; 32];
+        assert_eq!(
+            fr_mul_barrett(&high, &high, true).unwrap(),
+            fr_mul(&high, &high, true, Form::Canonical).unwrap()
+        );
+        let err = fr_add_barrett(&a[..32], &high, false).unwrap_err();
+        assert!(err.reason.starts_with("b[0]: "), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fr_montgomery_round_trip() {
+        let values: Vec<Fr> = (1..64u64)
+            .map(|i| Fr::from_u64(i).inverse().unwrap())
+            .collect();
+        let canonical: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mont = fr_convert(&canonical, false, Form::Canonical, Form::Montgomery).unwrap();
+        let expected: Vec<u8> = values
+            .iter()
+            .flat_map(|v| v.to_montgomery_le_bytes())
+            .collect();
+        assert_eq!(mont, expected);
+        let squares = fr_square(&mont, false, Form::Montgomery).unwrap();
+        assert_eq!(
+            fr_convert(&squares, false, Form::Montgomery, Form::Canonical).unwrap(),
+            fr_square(&canonical, false, Form::Canonical).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fr_inv_batch() {
+        let values: Vec<u8> = (1..3000u64)
+            .flat_map(|i| (Fr::from_u64(i).square() + Fr::from_u64(i)).to_le_bytes())
+            .collect();
+        let inverses = fr_inv_batch(&values, false, false, Form::Canonical).unwrap();
+        let products = fr_mul(&values, &inverses, false, Form::Canonical).unwrap();
+        assert!(products
+            .chunks_exact(32)
+            .all(|p| Fr::from_le_bytes(p) == Some(Fr::one())));
+
+        let err = fr_inv_batch(&[0; 32], false, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.reason, "values[0]: zero has no inverse");
+        assert_eq!(
+            fr_inv_batch(&[0; 32], true, false, Form::Canonical).unwrap(),
+            [0u8; 32]
+        );
+    }
+
+    #[test]
+    fn test_fp_array_ops() {
+        let bytes = random_elements(FieldName::Bls12_381Fp, 64, Some(&[8; SEED_BYTES])).unwrap();
+        let (a, b) = bytes.split_at(32 * FP_BYTES);
+        let parse = |v: &[u8]| -> Vec<Fp> {
+            v.chunks_exact(FP_BYTES)
+                .map(|c| Fp::from_le_bytes(c).unwrap())
+                .collect()
+        };
+        let (xs, ys) = (parse(a), parse(b));
+        let pack = |v: Vec<Fp>| -> Vec<u8> { v.iter().flat_map(|x| x.to_le_bytes()).collect() };
+        let zip =
+            |op: fn(Fp, Fp) -> Fp| pack(xs.iter().zip(&ys).map(|(x, y)| op(*x, *y)).collect());
+        let form = Form::Canonical;
+        assert_eq!(fp_add(a, b, false, form).unwrap(), zip(|x, y| x + y));
+        assert_eq!(fp_sub(a, b, false, form).unwrap(), zip(|x, y| x - y));
+        let products = fp_mul(a, b, false, form).unwrap();
+        assert_eq!(products, zip(|x, y| x * y));
+        assert_eq!(
+            crate::bigint::with_schoolbook_384(|| fp_mul(a, b, false, form)).unwrap(),
+            products
+        );
+
+        // x * x^-1 = 1, in Montgomery form end to end
+        let to_mont = |v: &[u8]| {
+            elementwise(
+                [(v, "v")],
+                false,
+                (Form::Canonical, Form::Montgomery),
+                FP_FIELD,
+                |[x]: [Fp; 1]| x,
+            )
+            .unwrap()
+        };
+        let inverses = fp_inv_batch(&to_mont(a), false, false, Form::Montgomery).unwrap();
+        let ones = fp_mul(&to_mont(a), &inverses, false, Form::Montgomery).unwrap();
+        assert_eq!(ones, to_mont(&pack(vec![Fp::one(); 32])));
+
+        let p_bytes: Vec<u8> = FpConfig::MODULUS
+            .iter()
+            .flat_map(|l| l.to_le_bytes())
+            .collect();
+        let err = fp_mul(&p_bytes, &a[..FP_BYTES], false, form).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a[0]: value is not below the BLS12-381 base field modulus"
+        );
+        assert_eq!(
+            fp_add(&p_bytes, &p_bytes, true, form).unwrap(),
+            [0u8; FP_BYTES]
+        );
+        assert!(fp_inv_batch(&[0; FP_BYTES], false, false, form).is_err());
+        assert!(fp_sub(&[0; FP_BYTES], &[0; 47], false, form).is_err());
+    }
+
+    #[test]
+    fn test_karatsuba_matches_schoolbook_on_curves() {
+        // Every Fp product in G1/G2 arithmetic and the pairing, both ways
+        let run = || {
+            let k = [
+                0x9e37_79b9_7f4a_7c15,
+                0x2545_f491_4f6c_dd1d,
+                0x1234_5678,
+                0x0bad_cafe,
+            ];
+            let p = g1_generator().to_jacobian().mul_limbs(&k).to_affine();
+            let q = g2_generator().to_jacobian().mul_limbs(&k).to_affine();
+            let e = crate::pairing::pairing::<Bls12Pairing, 6>(&p, &q);
+            let x = Fp2::new(Fp::from_u64(3), -Fp::from_u64(5));
+            (
+                encode_g1(&p),
+                encode_g2(&q),
+                e,
+                x.square().inverse().unwrap(),
+            )
+        };
+        assert_eq!(run(), crate::bigint::with_schoolbook_384(run));
+
+        let (x, y) = (Fp::from_u64(3).inverse().unwrap(), -Fp::from_u64(7));
+        for karatsuba in [true, false] {
+            assert_eq!(fp_mul_with_kernel(&x, &y, karatsuba), x * y);
+            assert_eq!(
+                fp_mul_with_kernel(&-Fp::one(), &-Fp::one(), karatsuba),
+                Fp::one()
+            );
+        }
+    }
+
+    #[test]
+    fn test_fp2_array_arkworks_vectors() {
+        // ark-bls12-381 0.4 `Fq2` serialized as c0 || c1, both little-endian:
+        // a = 1234567890123456789012345678901234567890
+        //     + 987654321098765432109876543210 u
+        // b = -1 + (2^380 - 7) u
+        let a = hex(concat!(
+            "d20a3fce965fbcacb8f3dbc07520c9a00300000000000000000000000000000000000000000000000000000000000000",
+            "ea7ec6d13824b6ff9d8148770c0000000000000000000000000000000000000000000000000000000000000000000000",
+        ));
+        let b = hex(concat!(
+            "aaaafffffffffeb9ffff53b1feffab1e24f6b0f6a0d23067bf1285f3844b7764d7ac4b43b6a71b4b9ae67f39ea11011a",
+            "f9ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0f",
+        ));
+        assert_eq!(
+            fp2_add(&a, &b, false, Form::Canonical).unwrap(),
+            hex(concat!(
+                "d10a3fce965fbcacb8f3dbc07520c9a00300000000000000000000000000000000000000000000000000000000000000",
+                "e37ec6d13824b6ff9d8148770c0000000000000000000000000000000000000000000000000000000000000000000010",
+            ))
+        );
+        assert_eq!(
+            fp2_sub(&a, &b, false, Form::Canonical).unwrap(),
+            hex(concat!(
+                "d30a3fce965fbcacb8f3dbc07520c9a00300000000000000000000000000000000000000000000000000000000000000",
+                "9c29c6d13824b5b99d819c280b00ac1e24f6b0f6a0d23067bf1285f3844b7764d7ac4b43b6a71b4b9ae67f39ea11010a",
+            ))
+        );
+        assert_eq!(
+            fp2_mul(&a, &b, false, Form::Canonical).unwrap(),
+            hex(concat!(
+                "48df46be4b33a0f662fc6233eac272062a493aff0995e7e011b92ab9b54b959f143bf422a667c183152ca7380b8f2600",
+                "073b58e038eeff722ad9ea9e80663b7a2c44e61092502c62de0242fc0e48567149704fb4389d53ce0a76f8f9c7213015",
+            ))
+        );
+        assert_eq!(
+            fp2_square(&a, false, Form::Canonical).unwrap(),
+            hex(concat!(
+                "60e6eda685035b4f38e5f7d9fdb45cd5ff08f94f86ed390d252641b359c9b2290d000000000000000000000000000000",
+                "e87fbebac6f7b805df1db05cfe0a0b1ca2c076cce4a10af9de2464745a00000000000000000000000000000000000000",
+            ))
+        );
+        assert_eq!(
+            fp2_conjugate(&a, false, Form::Canonical).unwrap(),
+            hex(concat!(
+                "d20a3fce965fbcacb8f3dbc07520c9a00300000000000000000000000000000000000000000000000000000000000000",
+                "c12b392ec7db48ba617e0b3af2ffab1e24f6b0f6a0d23067bf1285f3844b7764d7ac4b43b6a71b4b9ae67f39ea11011a",
+            ))
+        );
+
+        // Inversion, including the real element 7 + 0u
+        let mut seven = vec![0u8; FP2_BYTES];
+        seven[0] = 7;
+        let values = [a.clone(), b.clone(), seven].concat();
+        let inverses = fp2_inv_batch(&values, false, false, Form::Canonical).unwrap();
+        assert_eq!(inverses[..FP2_BYTES], hex(concat!(
+            "9b7d42c02a9f34d3cf39cba3abf1557360cbb726c70aace368adf9bbb03f357a7fe45475e9a6a8761fdffb9220f5620d",
+            "ce706387d24005a0d12c2d0e52176ab8a932d059a82184d843122e1ddabde8aebbc7b99870bb736a26c1b4125fb7ab12",
+        )));
+        assert_eq!(
+            inverses[FP2_BYTES..2 * FP2_BYTES],
+            hex(concat!(
+                "bc8845db9f71b6dcf3c3231a540b8fe7b042680d0a76271758c20c525249e4064085e973cff83370b7de25191d2a9313",
+                "3407d6943eb8c664640a352667bfa02298e9be9a9af27e3dffbfb31651e2d880e0f9183fabb8ce283ba2b4be0d062010",
+            ))
+        );
+        assert_eq!(
+            inverses[2 * FP2_BYTES..],
+            hex(concat!(
+                "f43c922449926dd1b66de73ddbb66104bc4719dacd8b2bea3f275c47a5c17e57433de69b3eaa7178f120c92c8f02b703",
+                "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            ))
+        );
+        let products = fp2_mul(&values, &inverses, false, Form::Canonical).unwrap();
+        let mut one = vec![0u8; FP2_BYTES];
+        one[0] = 1;
+        assert!(products.chunks_exact(FP2_BYTES).all(|p| p == one));
+    }
+
+    #[test]
+    fn test_fp2_array_layout_checks() {
+        let err = fp2_add(&[0; 96], &[0; 48], false, Form::Canonical).unwrap_err();
+        assert_eq!(err.reason, "b: length 48 is not a multiple of 96 bytes");
+        let err = fp2_mul(&[0; 96], &[0; 192], false, Form::Canonical).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a and b must have the same length, got 96 and 192 bytes"
+        );
+        // p in the c1 slot of the second element
+        let p: Vec<u8> = FpConfig::MODULUS
+            .iter()
+            .flat_map(|l| l.to_le_bytes())
+            .collect();
+        let values = [vec![0u8; FP2_BYTES + FP_BYTES], p].concat();
+        let err = fp2_square(&values, false, Form::Canonical).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a[1]: value is not below the BLS12-381 base field modulus"
+        );
+        assert_eq!(
+            fp2_conjugate(&values, true, Form::Canonical).unwrap(),
+            vec![0u8; 2 * FP2_BYTES]
+        );
+
+        let err = fp2_inv_batch(&[0; FP2_BYTES], false, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.reason, "values[0]: zero has no inverse");
+        assert_eq!(
+            fp2_inv_batch(&[0; FP2_BYTES], true, false, Form::Canonical).unwrap(),
+            [0u8; FP2_BYTES]
+        );
+    }
+
+    #[test]
+    fn test_fp_sqrt_batch() {
+        assert_eq!(FpConfig::TWO_ADICITY, 1);
+        // x^2 for x = 1..=500, plus the G1 generator's y^2 = x^3 + 4
+        let g = g1_generator();
+        let mut xs: Vec<Fp> = (1..=500u64).map(|i| Fp::from_u64(i * i + 17)).collect();
+        xs.push(g.y);
+        let squares: Vec<u8> = xs.iter().flat_map(|x| x.square().to_le_bytes()).collect();
+        let (roots, flags) = fp_sqrt_batch(&squares).unwrap();
+        assert!(flags.iter().all(|&f| f == 1));
+        for (x, root) in xs.iter().zip(roots.chunks_exact(FP_BYTES)) {
+            let root = Fp::from_le_bytes(root).unwrap();
+            assert!(root == *x || root == -*x);
+        }
+        let y2 = g.x.square() * g.x + Fp::from_u64(4);
+        let (root, _) = fp_sqrt_batch(&y2.to_le_bytes()).unwrap();
+        assert!(Fp::from_le_bytes(&root) == Some(g.y) || Fp::from_le_bytes(&root) == Some(-g.y));
+
+        // 2 and 3 are non-residues, -1 too since p = 3 mod 4
+        let small: Vec<u8> = [Fp::zero(), Fp::from_u64(2), Fp::from_u64(3), -Fp::one()]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let (roots, flags) = fp_sqrt_batch(&small).unwrap();
+        assert_eq!(flags, [1, 0, 0, 0]);
+        assert!(roots.iter().all(|&b| b == 0));
+
+        let err = fp_sqrt_batch(&[0; 47]).unwrap_err();
+        assert!(err.reason.contains("multiple of 48"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fp2_montgomery_form() {
+        let values: Vec<Fp2> = (1..40u64)
+            .map(|i| Fp2::new(Fp::from_u64(i), Fp::from_u64(3 * i + 1)).square())
+            .collect();
+        let canonical: Vec<u8> = values
+            .iter()
+            .flat_map(|v| [v.c0.to_le_bytes(), v.c1.to_le_bytes()].concat())
+            .collect();
+        let mont: Vec<u8> = values
+            .iter()
+            .flat_map(|v| [v.c0.to_montgomery_le_bytes(), v.c1.to_montgomery_le_bytes()].concat())
+            .collect();
+        let product = fp2_mul(&mont, &mont, false, Form::Montgomery).unwrap();
+        let expected: Vec<u8> = values
+            .iter()
+            .flat_map(|v| {
+                let s = v.square();
+                [s.c0.to_montgomery_le_bytes(), s.c1.to_montgomery_le_bytes()].concat()
+            })
+            .collect();
+        assert_eq!(product, expected);
+        assert_eq!(
+            fp2_inv_batch(&canonical, false, false, Form::Canonical).unwrap(),
+            values
+                .iter()
+                .flat_map(|v| {
+                    let inv = v.inverse().unwrap();
+                    [inv.c0.to_le_bytes(), inv.c1.to_le_bytes()].concat()
+                })
+                .collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn test_pairing_hash_to_curve_vectors() {
+        // hash_to_curve("") from the IETF hash-to-curve draft, suites
+        // BLS12381G1_XMD:SHA-256_SSWU_RO_ and BLS12381G2_XMD:SHA-256_SSWU_RO_
+        let p = hex(concat!(
+            "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+            "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+        ));
+        let q = hex(concat!(
+            "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+            "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+            "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+            "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+        ));
+        // e(p, q), computed with the zkcrypto bls12_381 crate
+        let expected = hex(concat!(
+            "032a2145464bf83cb34cf7a3c71018f6aa0ad5cb455de682567576bdd6404f80d2fd3d913ea4aa86a6b7772e7f1fbac9",
+            "0f2df34d53357f065faba1525cdfd76209be05b140c17e192745226e88a6019810e65c6393eb3096fa09104121c95926",
+            "150da9b6aa1e5239fc67504a7d7f15dd5a0a5093cd24e4e6c9a147603f8f9da703f7e2d7b2e64fe832b98cff4a1dde8d",
+            "0bdde8f30db8c1ce2b1ab88c9c72aa449f89a674881c43fc02733242bad658e10f3c17feae6716599dab8257ecfee856",
+            "0f0c4e91ca724d0dc2d189c1f9a4ceac5928e703d5c75928e11eb9e31895074e6433089ca5154a9d774f1e864eb42e64",
+            "197ecdafd072c8b8e3a117b8a582ffce25eca2d1aa189d14118b020ca0032dd7bad06c8fc8188f32df15102b771c1a49",
+            "07b917a2855212f173631df3d66b696fdb2e73e39fa3865853e93f1322f06659acad735a5b0d459a07bfaf6605d31f89",
+            "19090e602087eb7648ffce322d217538f8f2da8cfd934a812ea24e0337c54b3220a05789f63dc8ccd2c252a8f3111fae",
+            "033c59a7627b125eec58b1895e05380f44659e05ef440b6f06e93ab14cf7d35742a139e05cced16caaef127b1c23851e",
+            "0c32e2b7e858516daeeb4df241ad954b378dc65d9913d0f7483ba89a5fbb06f97d9d3a897c95eabcf0a6c0a9bb622066",
+            "00e7753bfbe705a3fbf68b34ebba70846d1dfc42dbac59cd96391cf168852501016778c09cacaebe23831a06c721b846",
+            "01dcf4f8155e89a2e6a50d71e07fc29c216b6e627dd487ad72ca5e41d23acbd20a2c94f1eff88372bc42d70cd74d4b06",
+        ));
+        let e = bls12_381_pairing(p.clone(), q.clone()).unwrap();
+        assert_eq!(e.len(), 576);
+        assert_eq!(e, expected);
+
+        let g = generator();
+        let g2 = encode_g2(&g2_generator());
+        let e_g = bls12_381_pairing(g.clone(), g2.clone()).unwrap();
+        let product = decode_pairs(&[p.clone(), g.clone()], &[q.clone(), g2.clone()])
+            .unwrap()
+            .iter()
+            .map(|(a, b)| crate::pairing::pairing::<Bls12Pairing, 6>(a, b))
+            .fold(Fp12::one(), |acc, e| acc * e);
+        assert_eq!(
+            bls12_381_multi_pairing(vec![p.clone(), g.clone()], vec![q.clone(), g2.clone()])
+                .unwrap(),
+            encode_fp12(&product)
+        );
+        assert_ne!(encode_fp12(&product), e_g);
+
+        let neg_p = encode_g1(&decode_g1(&p, "p").unwrap().neg());
+        assert!(
+            bls12_381_pairing_check(vec![p.clone(), neg_p], vec![q.clone(), q.clone()]).unwrap()
+        );
+        assert!(!bls12_381_pairing_check(vec![p.clone(), g], vec![q.clone(), g2]).unwrap());
+        assert!(bls12_381_pairing_check(vec![], vec![]).unwrap());
+        assert_eq!(
+            bls12_381_pairing(vec![0; G1_BYTES], q.clone()).unwrap(),
+            encode_fp12(&Fp12::one())
+        );
+        assert!(bls12_381_pairing_check(vec![p.clone()], vec![]).is_err());
+        assert!(bls12_381_pairing(vec![0; 95], q.clone()).is_err());
+
+        // Small-order G2 points used to abort the process in the Miller loop
+        let q13 = encode_g2(&g2_point_of_order_13());
+        let err = bls12_381_pairing(p.clone(), q13.clone()).unwrap_err();
+        assert_eq!(err.reason, "q: point is not in the BLS12-381 G2 subgroup");
+        let err = bls12_381_pairing_check(vec![p.clone(), p.clone()], vec![q.clone(), q13.clone()])
+            .unwrap_err();
+        assert_eq!(
+            err.reason,
+            "qs[1]: point is not in the BLS12-381 G2 subgroup"
+        );
+        assert!(bls12_381_multi_pairing(vec![p.clone()], vec![q13]).is_err());
+        let outside = (1u64..)
+            .find_map(|i| {
+                let x = Fp::from_u64(i);
+                let y = (x.square() * x + Fp::from_u64(4)).sqrt(-Fp::one())?;
+                Some(G1Affine::new(x, y))
+            })
+            .unwrap();
+        let err = bls12_381_pairing(encode_g1(&outside), q).unwrap_err();
+        assert_eq!(err.reason, "p: point is not in the BLS12-381 G1 subgroup");
+    }
+}
@@ -0,0 +1,737 @@
+//! BLS signatures over BLS12-381, minimal-pubkey-size variant
+//!
+//! Public keys are G1 points and signatures G2 points, as in Ethereum
+//! consensus: private keys are 32-byte big-endian scalars in `[1, r)`,
+//! public keys 48-byte and signatures 96-byte compressed points in the ZCash
+//! serialization. Messages are hashed to G2 with the RFC 9380 suite
+//! `BLS12381G2_XMD:SHA-256_SSWU_RO_` under the proof-of-possession
+//! ciphersuite tag, so [`bls_verify_aggregated`] does not require the
+//! messages to be distinct.
+//!
+//! Every decoded point is checked to be on the curve and in the prime-order
+//! subgroup; the point at infinity is not a valid public key. Signing
+//! multiplies by `sk + j r` for a fresh random `j` rather than by `sk`
+//! itself, so the double-and-add pattern does not follow the key's bits; in
+//! constant-time mode ([`crate::constant_time`]) the blinded multiplication
+//! is a Montgomery ladder instead.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+use rand_core::{OsRng, RngCore};
+
+use crate::bigint::mac;
+use crate::bls12_381::{
+    g1_generator, Bls12Pairing, Fp, Fp2, Fr, FrConfig, G1Affine, G1Config, G2Affine, G2Config,
+    G2Projective, FP_BYTES,
+};
+use crate::constant_time;
+use crate::ec::{Affine, Jacobian, SwCurve};
+use crate::hash_to_field::hash_to_field_elements;
+use crate::montgomery::{ConstantTimeField, Field, MontConfig};
+use crate::pairing::pairing_product_is_one;
+use crate::random::FieldName;
+
+/// Ciphersuite tag of the proof-of-possession scheme
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Size in bytes of a private key
+pub const PRIVATE_KEY_BYTES: usize = 32;
+
+/// Size in bytes of a compressed G1 point, i.e. a public key
+pub const PUBLIC_KEY_BYTES: usize = FP_BYTES;
+
+/// Size in bytes of a compressed G2 point, i.e. a signature
+pub const SIGNATURE_BYTES: usize = 2 * FP_BYTES;
+
+/// Flag bits in the first byte of a compressed point
+const COMPRESSED: u8 = 0x80;
+const INFINITY: u8 = 0x40;
+const SIGN: u8 = 0x20;
+
+/// `h_eff` of RFC 9380 section 8.8.2, little-endian limbs
+const H_EFF: [u64; 10] = [
+    0xe8020005aaa95551,
+    0x59894c0adebbf6b4,
+    0xe954cbc06689f6a3,
+    0x2ec0ec69d7477c1a,
+    0x6d82bf015d1212b0,
+    0x329c2f178731db95,
+    0x9986ff031508ffe1,
+    0x88e2a8e9145ad768,
+    0x584c6a0ea91b3528,
+    0x0bc69f08f2ee75b3,
+];
+
+/// Fp2 constant `c0 + c1 u` from big-endian hex coefficients
+fn fp2(c0: &str, c1: &str) -> Fp2 {
+    let fp = |hex: &str| crate::bls12_381::fp_from_hex(&format!("{hex:0>96}"));
+    Fp2::new(fp(c0), fp(c1))
+}
+
+/// Coefficients of the SSWU curve `E'` and its 3-isogeny to the G2 twist
+struct Iso3 {
+    a: Fp2,
+    b: Fp2,
+    z: Fp2,
+    /// Numerators and denominators of `x` and `y`, lowest degree first
+    x_num: [Fp2; 4],
+    x_den: [Fp2; 3],
+    y_num: [Fp2; 4],
+    y_den: [Fp2; 4],
+}
+
+const P_MINUS: &str =
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffff";
+
+/// RFC 9380 section 8.8.2 and appendix E.3
+static ISO3: Lazy<Iso3> = Lazy::new(|| {
+    let k1 = "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6";
+    let k2 = "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc7";
+    let k3 = "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38";
+    let k4 = "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706";
+    let p = |tail: &str| format!("{P_MINUS}{tail}");
+    Iso3 {
+        a: fp2("0", "f0"),
+        b: fp2("3f4", "3f4"),
+        z: fp2(&p("aaa9"), &p("aaaa")),
+        x_num: [
+            fp2(k1, k1),
+            fp2("0", &format!("{k2}1a")),
+            fp2(&format!("{k2}1e"), &format!("{k3}d")),
+            fp2(
+                "171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1",
+                "0",
+            ),
+        ],
+        x_den: [fp2("0", &p("aa63")), fp2("c", &p("aa9f")), Fp2::one()],
+        y_num: [
+            fp2(k4, k4),
+            fp2(
+                "0",
+                "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be",
+            ),
+            fp2(&format!("{k2}1c"), &format!("{k3}f")),
+            fp2(
+                "124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10",
+                "0",
+            ),
+        ],
+        y_den: [
+            fp2(&p("a8fb"), &p("a8fb")),
+            fp2("0", &p("a9d3")),
+            fp2("12", &p("aa99")),
+            Fp2::one(),
+        ],
+    }
+});
+
+/// Whether a canonical `y` is greater than `-y`, the ZCash sign convention
+fn fp_is_largest(y: &Fp) -> bool {
+    let (a, b) = (y.to_canonical(), (-*y).to_canonical());
+    a.iter().rev().gt(b.iter().rev())
+}
+
+/// [`fp_is_largest`] on `c1`, or on `c0` when `c1` is zero
+fn fp2_is_largest(y: &Fp2) -> bool {
+    if y.c1.is_zero() {
+        fp_is_largest(&y.c0)
+    } else {
+        fp_is_largest(&y.c1)
+    }
+}
+
+/// `sgn0` of RFC 9380 section 4.1 for Fp2
+fn sgn0(x: &Fp2) -> bool {
+    let odd = |c: &Fp| c.to_canonical()[0] & 1 == 1;
+    odd(&x.c0) || (x.c0.is_zero() && odd(&x.c1))
+}
+
+/// Simplified SWU map of RFC 9380 section 6.6.2 onto `E'`
+fn map_to_curve_sswu(u: Fp2) -> (Fp2, Fp2) {
+    let iso = &*ISO3;
+    let g = |x: Fp2| (x.square() + iso.a) * x + iso.b;
+    let zu2 = iso.z * u.square();
+    let tv1 = (zu2.square() + zu2).inverse();
+    let x1 = match tv1 {
+        Some(tv1) => -iso.b * iso.a.inverse().unwrap() * (Fp2::one() + tv1),
+        None => iso.b * (iso.z * iso.a).inverse().unwrap(),
+    };
+    let (x, y) = match g(x1).sqrt() {
+        Some(y) => (x1, y),
+        None => {
+            let x2 = zu2 * x1;
+            let y = g(x2).sqrt().expect("g(x2) is square when g(x1) is not");
+            (x2, y)
+        }
+    };
+    (x, if sgn0(&u) == sgn0(&y) { y } else { -y })
+}
+
+/// Horner evaluation of a polynomial with coefficients lowest degree first
+fn poly(coeffs: &[Fp2], x: Fp2) -> Fp2 {
+    coeffs.iter().rev().fold(Fp2::zero(), |acc, c| acc * x + *c)
+}
+
+/// The 3-isogeny from `E'` to the G2 twist
+fn iso_map((x, y): (Fp2, Fp2)) -> G2Affine {
+    let iso = &*ISO3;
+    match (poly(&iso.x_den, x) * poly(&iso.y_den, x)).inverse() {
+        // A denominator vanishes only at the kernel, which maps to infinity
+        None => G2Affine::identity(),
+        Some(inv) => G2Affine::new(
+            poly(&iso.x_num, x) * poly(&iso.y_den, x) * inv,
+            y * poly(&iso.y_num, x) * poly(&iso.x_den, x) * inv,
+        ),
+    }
+}
+
+/// RFC 9380 `hash_to_curve` for `BLS12381G2_XMD:SHA-256_SSWU_RO_`
+pub fn hash_to_g2(msg: &[u8], dst: &[u8]) -> Result<G2Projective> {
+    let u = hash_to_field_elements(msg, dst, 4, FieldName::Bls12_381Fp)?;
+    let fp = |i: usize| {
+        Fp::from_be_bytes(&u[i * FP_BYTES..(i + 1) * FP_BYTES]).expect("hash_to_field is canonical")
+    };
+    let q0 = iso_map(map_to_curve_sswu(Fp2::new(fp(0), fp(1))));
+    let q1 = iso_map(map_to_curve_sswu(Fp2::new(fp(2), fp(3))));
+    Ok(q0.to_jacobian().add_affine(&q1).mul_limbs(&H_EFF))
+}
+
+/// Set the flag bits on a big-endian x coordinate
+fn with_flags(mut x: Vec<u8>, largest: bool) -> Vec<u8> {
+    x[0] |= COMPRESSED | if largest { SIGN } else { 0 };
+    x
+}
+
+/// Encoding of the point at infinity in `len` bytes
+fn infinity_bytes(len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    out[0] = COMPRESSED | INFINITY;
+    out
+}
+
+/// 48-byte compressed encoding of a G1 point
+pub fn compress_g1(point: &G1Affine) -> Vec<u8> {
+    if point.infinity {
+        return infinity_bytes(PUBLIC_KEY_BYTES);
+    }
+    with_flags(point.x.to_be_bytes(), fp_is_largest(&point.y))
+}
+
+/// 96-byte compressed encoding of a G2 point, `x.c1 || x.c0`
+pub fn compress_g2(point: &G2Affine) -> Vec<u8> {
+    if point.infinity {
+        return infinity_bytes(SIGNATURE_BYTES);
+    }
+    let mut x = point.x.c1.to_be_bytes();
+    x.extend(point.x.c0.to_be_bytes());
+    with_flags(x, fp2_is_largest(&point.y))
+}
+
+fn invalid(name: &str, reason: impl std::fmt::Display) -> Error {
+    Error::new(Status::InvalidArg, format!("{name}: {reason}"))
+}
+
+/// Check the length and flags of a compressed point
+///
+/// Returns the flag-free x bytes and the sign bit, or `None` for infinity.
+fn strip_flags(
+    bytes: &[u8],
+    len: usize,
+    group: &str,
+    name: &str,
+) -> Result<Option<(Vec<u8>, bool)>> {
+    if bytes.len() != len {
+        return Err(invalid(
+            name,
+            format!(
+                "expected a {len}-byte compressed {group} point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    let flags = bytes[0];
+    if flags & COMPRESSED == 0 {
+        return Err(invalid(name, "compression flag is not set"));
+    }
+    let mut x = bytes.to_vec();
+    x[0] &= !(COMPRESSED | INFINITY | SIGN);
+    if flags & INFINITY != 0 {
+        if flags & SIGN != 0 || x.iter().any(|&b| b != 0) {
+            return Err(invalid(name, "malformed encoding of the point at infinity"));
+        }
+        return Ok(None);
+    }
+    Ok(Some((x, flags & SIGN != 0)))
+}
+
+/// Reject points outside the order-`r` subgroup
+fn check_subgroup<C: SwCurve>(point: Affine<C>, group: &str, name: &str) -> Result<Affine<C>> {
+    if !point
+        .to_jacobian()
+        .mul_limbs(&FrConfig::MODULUS)
+        .is_identity()
+    {
+        return Err(invalid(
+            name,
+            format!("point is not in the BLS12-381 {group} subgroup"),
+        ));
+    }
+    Ok(point)
+}
+
+fn parse_x(bytes: &[u8], name: &str) -> Result<Fp> {
+    Fp::from_be_bytes(bytes).ok_or_else(|| {
+        invalid(
+            name,
+            "x coordinate is not below the BLS12-381 base field modulus",
+        )
+    })
+}
+
+/// Decode a 48-byte compressed G1 point in the prime-order subgroup
+pub fn decompress_g1(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    let Some((x, largest)) = strip_flags(bytes, PUBLIC_KEY_BYTES, "G1", name)? else {
+        return Ok(G1Affine::identity());
+    };
+    let x = parse_x(&x, name)?;
+    let y = (x.square() * x + G1Config::coeff_b())
+        .sqrt(-Fp::one())
+        .ok_or_else(|| invalid(name, "point is not on the BLS12-381 G1 curve"))?;
+    let y = if fp_is_largest(&y) == largest { y } else { -y };
+    check_subgroup(G1Affine::new(x, y), "G1", name)
+}
+
+/// Decode a 96-byte compressed G2 point in the prime-order subgroup
+pub fn decompress_g2(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    let Some((x, largest)) = strip_flags(bytes, SIGNATURE_BYTES, "G2", name)? else {
+        return Ok(G2Affine::identity());
+    };
+    let x = Fp2::new(
+        parse_x(&x[FP_BYTES..], name)?,
+        parse_x(&x[..FP_BYTES], name)?,
+    );
+    let y = (x.square() * x + G2Config::coeff_b())
+        .sqrt()
+        .ok_or_else(|| invalid(name, "point is not on the BLS12-381 G2 curve"))?;
+    let y = if fp2_is_largest(&y) == largest { y } else { -y };
+    check_subgroup(G2Affine::new(x, y), "G2", name)
+}
+
+/// Decode a private key, a big-endian scalar in `[1, r)`
+fn parse_private_key(bytes: &[u8]) -> Result<Fr> {
+    if bytes.len() != PRIVATE_KEY_BYTES {
+        return Err(invalid(
+            "private_key",
+            format!(
+                "expected a {PRIVATE_KEY_BYTES}-byte big-endian scalar, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    match Fr::from_be_bytes(bytes) {
+        None => Err(invalid(
+            "private_key",
+            "value is not below the BLS12-381 group order",
+        )),
+        Some(sk) if sk.is_zero() => Err(invalid("private_key", "must not be zero")),
+        Some(sk) => Ok(sk),
+    }
+}
+
+/// Decode a public key, rejecting the point at infinity
+fn parse_public_key(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    let pk = decompress_g1(bytes, name)?;
+    if pk.infinity {
+        return Err(invalid(name, "public key is the point at infinity"));
+    }
+    Ok(pk)
+}
+
+/// `sk + j r` for a random 64-bit `j`
+///
+/// Multiplies any point of order `r` exactly as `sk` does, with a bit
+/// pattern that changes on every call.
+fn blinded_scalar(sk: &Fr) -> [u64; 5] {
+    let j = OsRng.next_u64();
+    let sk = sk.to_canonical();
+    let mut out = [0u64; 5];
+    let mut carry = 0;
+    for i in 0..4 {
+        (out[i], carry) = mac(sk[i], j, FrConfig::MODULUS[i], carry);
+    }
+    out[4] = carry;
+    out
+}
+
+/// `[sk] p` by double-and-add over a blinded scalar, or by the constant-time
+/// ladder in constant-time mode
+fn mul_secret<C: SwCurve>(p: &Jacobian<C>, sk: &Fr) -> Jacobian<C>
+where
+    C::Base: ConstantTimeField,
+{
+    let k = blinded_scalar(sk);
+    if constant_time::get_constant_time_mode() {
+        p.mul_ct(&k)
+    } else {
+        p.mul_limbs(&k)
+    }
+}
+
+/// Signature check as a single multi-pairing, `prod e(pk_i, H(m_i)) e(-g, sig) == 1`
+fn verify_pairs(keys: &[G1Affine], hashes: Vec<G2Projective>, sig: &G2Affine) -> bool {
+    let mut pairs: Vec<_> = keys
+        .iter()
+        .zip(hashes)
+        .map(|(pk, h)| (*pk, h.to_affine()))
+        .collect();
+    pairs.push((g1_generator().neg(), *sig));
+    pairing_product_is_one::<Bls12Pairing, 6>(&pairs)
+}
+
+/// 48-byte compressed public key `[sk] g1` of a 32-byte big-endian private key
+#[napi]
+pub fn bls_public_key(private_key: Vec<u8>) -> Result<Vec<u8>> {
+    let sk = parse_private_key(&private_key)?;
+    Ok(compress_g1(
+        &mul_secret(&g1_generator().to_jacobian(), &sk).to_affine(),
+    ))
+}
+
+/// Sign `message`, returning a 96-byte compressed G2 signature
+///
+/// `private_key` is a 32-byte big-endian scalar in `[1, r)`.
+#[napi]
+pub fn bls_sign(private_key: Vec<u8>, message: Vec<u8>) -> Result<Vec<u8>> {
+    let sk = parse_private_key(&private_key)?;
+    let h = hash_to_g2(&message, DST)?;
+    Ok(compress_g2(&mul_secret(&h, &sk).to_affine()))
+}
+
+/// Check a signature against a 48-byte compressed public key
+///
+/// Malformed keys and signatures, and points outside their subgroups, are
+/// errors; a well-formed signature that does not match is `false`.
+#[napi]
+pub fn bls_verify(public_key: Vec<u8>, message: Vec<u8>, signature: Vec<u8>) -> Result<bool> {
+    let pk = parse_public_key(&public_key, "public_key")?;
+    let sig = decompress_g2(&signature, "signature")?;
+    Ok(verify_pairs(&[pk], vec![hash_to_g2(&message, DST)?], &sig))
+}
+
+/// Sum of compressed G2 signatures, itself a compressed signature
+#[napi]
+pub fn bls_aggregate_signatures(sigs: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    if sigs.is_empty() {
+        return Err(invalid("sigs", "expected at least one signature"));
+    }
+    let mut acc = G2Projective::identity();
+    for (i, sig) in sigs.iter().enumerate() {
+        acc = acc.add_affine(&decompress_g2(sig, &format!("sigs[{i}]"))?);
+    }
+    Ok(compress_g2(&acc.to_affine()))
+}
+
+/// Check an aggregate signature over `messages[i]` signed by `public_keys[i]`
+///
+/// All pairs go through one multi-pairing. The lists must have the same
+/// length; empty lists verify as `false`. Messages need not be distinct,
+/// since keys are assumed to come with proofs of possession.
+#[napi]
+pub fn bls_verify_aggregated(
+    public_keys: Vec<Vec<u8>>,
+    messages: Vec<Vec<u8>>,
+    aggregated_sig: Vec<u8>,
+) -> Result<bool> {
+    if public_keys.len() != messages.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "public_keys and messages must have the same length, got {} and {}",
+                public_keys.len(),
+                messages.len()
+            ),
+        ));
+    }
+    let keys = public_keys
+        .iter()
+        .enumerate()
+        .map(|(i, pk)| parse_public_key(pk, &format!("public_keys[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let sig = decompress_g2(&aggregated_sig, "aggregated_sig")?;
+    if keys.is_empty() {
+        return Ok(false);
+    }
+    let hashes = messages
+        .iter()
+        .map(|m| hash_to_g2(m, DST))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(verify_pairs(&keys, hashes, &sig))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::{decode_g2, encode_g2, g2_generator};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn key(k: u8) -> Vec<u8> {
+        let mut sk = vec![0u8; PRIVATE_KEY_BYTES];
+        sk[0] = 0x1a;
+        sk[31] = k;
+        sk
+    }
+
+    #[test]
+    fn test_hash_to_curve_vectors() {
+        // RFC 9380 appendix J.10.1, BLS12381G2_XMD:SHA-256_SSWU_RO_, as
+        // uncompressed `x.c1 || x.c0 || y.c1 || y.c0`
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let vectors: [(&[u8], [&str; 4]); 3] = [
+            (
+                b"",
+                [
+                    "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                    "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                    "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+                    "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                    "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                    "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+                    "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                ],
+            ),
+            (
+                b"abcdef0123456789",
+                [
+                    "190d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da034d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c",
+                    "121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c298e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0",
+                    "0bb5e7572275c567462d91807de765611490205a941a5a6af3b1691bfe596c31225d3aabdf15faff860cb4ef17c7c3be",
+                    "05571a0f8d3c08d094576981f4a3b8eda0a8e771fcdcc8ecceaf1356a6acf17574518acb506e435b639353c2e14827c8",
+                ],
+            ),
+        ];
+        for (msg, coords) in vectors {
+            let expected = hex(&coords.concat());
+            let point = hash_to_g2(msg, dst).unwrap().to_affine();
+            assert_eq!(encode_g2(&point), expected, "{msg:?}");
+            assert!(decode_g2(&expected, "q").is_ok());
+        }
+    }
+
+    #[test]
+    fn test_compression_round_trip() {
+        let g1 = g1_generator();
+        let g2 = g2_generator();
+        // The well-known compressed generators
+        assert_eq!(
+            compress_g1(&g1),
+            hex("97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb")
+        );
+        assert_eq!(
+            compress_g2(&g2),
+            hex(concat!(
+                "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+                "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+            ))
+        );
+        for k in 1..6u64 {
+            let p = g1.to_jacobian().mul_limbs(&[k]).to_affine();
+            for p in [p, p.neg()] {
+                assert_eq!(decompress_g1(&compress_g1(&p), "p").unwrap(), p);
+            }
+            let q = g2.to_jacobian().mul_limbs(&[k]).to_affine();
+            for q in [q, q.neg()] {
+                assert_eq!(decompress_g2(&compress_g2(&q), "q").unwrap(), q);
+            }
+        }
+        for bytes in [
+            compress_g1(&G1Affine::identity()),
+            compress_g2(&G2Affine::identity()),
+        ] {
+            assert_eq!(bytes[0], 0xc0);
+        }
+        assert!(decompress_g1(&infinity_bytes(48), "p").unwrap().infinity);
+        assert!(decompress_g2(&infinity_bytes(96), "q").unwrap().infinity);
+    }
+
+    #[test]
+    fn test_decompression_rejects_invalid_points() {
+        let reason = |r: Result<G1Affine>| r.unwrap_err().reason;
+        let g = compress_g1(&g1_generator());
+        assert_eq!(
+            reason(decompress_g1(&g[1..], "pk")),
+            "pk: expected a 48-byte compressed G1 point, got 47 bytes"
+        );
+        let mut raw = g.clone();
+        raw[0] &= !COMPRESSED;
+        assert_eq!(
+            reason(decompress_g1(&raw, "pk")),
+            "pk: compression flag is not set"
+        );
+        let mut bad_infinity = infinity_bytes(48);
+        bad_infinity[47] = 1;
+        assert_eq!(
+            reason(decompress_g1(&bad_infinity, "pk")),
+            "pk: malformed encoding of the point at infinity"
+        );
+        let mut too_big = vec![0xff; 48];
+        too_big[0] = 0x9f;
+        assert_eq!(
+            reason(decompress_g1(&too_big, "pk")),
+            "pk: x coordinate is not below the BLS12-381 base field modulus"
+        );
+        // x = 0 gives y^2 = 4, a point of order 3 outside the subgroup
+        let mut zero = vec![0u8; 48];
+        zero[0] = COMPRESSED;
+        assert_eq!(
+            reason(decompress_g1(&zero, "pk")),
+            "pk: point is not in the BLS12-381 G1 subgroup"
+        );
+        // x = 1 gives y^2 = 5, a non-residue
+        let mut one = zero.clone();
+        one[47] = 1;
+        assert_eq!(
+            reason(decompress_g1(&one, "pk")),
+            "pk: point is not on the BLS12-381 G1 curve"
+        );
+
+        // A twist point off the subgroup: x = 1 + u lifts only if x^3 + b
+        // is square, so search a few small x
+        let off = (1..20u64)
+            .map(|k| Fp2::new(Fp::from_u64(k), Fp::one()))
+            .find_map(|x| {
+                (x.square() * x + G2Config::coeff_b())
+                    .sqrt()
+                    .map(|y| G2Affine::new(x, y))
+            })
+            .unwrap();
+        assert!(off.is_on_curve());
+        assert_eq!(
+            decompress_g2(&compress_g2(&off), "sig").unwrap_err().reason,
+            "sig: point is not in the BLS12-381 G2 subgroup"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let sk = key(7);
+        let pk = bls_public_key(sk.clone()).unwrap();
+        assert_eq!(pk.len(), PUBLIC_KEY_BYTES);
+        // Blinding does not change the result
+        assert_eq!(bls_public_key(sk.clone()).unwrap(), pk);
+        let sig = bls_sign(sk.clone(), b"hello".to_vec()).unwrap();
+        assert_eq!(sig.len(), SIGNATURE_BYTES);
+        assert_eq!(bls_sign(sk.clone(), b"hello".to_vec()).unwrap(), sig);
+
+        assert!(bls_verify(pk.clone(), b"hello".to_vec(), sig.clone()).unwrap());
+        assert!(!bls_verify(pk.clone(), b"hellO".to_vec(), sig.clone()).unwrap());
+        let other = bls_public_key(key(8)).unwrap();
+        assert!(!bls_verify(other, b"hello".to_vec(), sig.clone()).unwrap());
+        let infinity = infinity_bytes(SIGNATURE_BYTES);
+        assert!(!bls_verify(pk.clone(), b"hello".to_vec(), infinity).unwrap());
+
+        assert_eq!(
+            bls_verify(infinity_bytes(48), b"hello".to_vec(), sig.clone())
+                .unwrap_err()
+                .reason,
+            "public_key: public key is the point at infinity"
+        );
+        assert!(bls_verify(pk.clone(), b"hello".to_vec(), sig[..95].to_vec()).is_err());
+
+        assert_eq!(
+            bls_sign(vec![0; 32], vec![]).unwrap_err().reason,
+            "private_key: must not be zero"
+        );
+        let r: Vec<u8> = FrConfig::MODULUS
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect();
+        assert_eq!(
+            bls_sign(r, vec![]).unwrap_err().reason,
+            "private_key: value is not below the BLS12-381 group order"
+        );
+        assert!(bls_public_key(vec![1; 31]).is_err());
+    }
+
+    #[test]
+    fn test_eth2_vector() {
+        // Ethereum consensus spec tests, bls/sign
+        let sk = hex("263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3");
+        let msg = vec![0u8; 32];
+        let pk = hex("a491d1b0ecd9bb917989f0e74f0dea0422eac4a873e5e2644f368dffb9a6e20fd6e10c1b77654d067c0618f6e5a7f79a");
+        let sig = hex(concat!(
+            "b6ed936746e01f8ecf281f020953fbf1f01debd5657c4a383940b020b26507f6076334f91e2366c96e9ab279fb5158090",
+            "352ea1c5b0c9274504f4f0e7053af24802e51e4568d164fe986834f41e55c8e850ce1f98458c0cfc9ab380b55285a55",
+        ));
+        assert_eq!(bls_public_key(sk.clone()).unwrap(), pk);
+        assert_eq!(bls_sign(sk, msg.clone()).unwrap(), sig);
+        assert!(bls_verify(pk, msg, sig).unwrap());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let keys: Vec<Vec<u8>> = (1..4).map(key).collect();
+        let pks: Vec<Vec<u8>> = keys
+            .iter()
+            .map(|sk| bls_public_key(sk.clone()).unwrap())
+            .collect();
+        // The last two sign the same message
+        let msgs: Vec<Vec<u8>> = vec![b"a".to_vec(), b"b".to_vec(), b"b".to_vec()];
+        let sigs: Vec<Vec<u8>> = keys
+            .iter()
+            .zip(&msgs)
+            .map(|(sk, m)| bls_sign(sk.clone(), m.clone()).unwrap())
+            .collect();
+        let agg = bls_aggregate_signatures(sigs.clone()).unwrap();
+        assert!(bls_verify_aggregated(pks.clone(), msgs.clone(), agg.clone()).unwrap());
+        assert_eq!(
+            bls_aggregate_signatures(sigs[..1].to_vec()).unwrap(),
+            sigs[0]
+        );
+        assert!(
+            bls_verify_aggregated(pks[..1].to_vec(), msgs[..1].to_vec(), sigs[0].clone()).unwrap()
+        );
+
+        let swapped = vec![msgs[1].clone(), msgs[0].clone(), msgs[2].clone()];
+        assert!(!bls_verify_aggregated(pks.clone(), swapped, agg.clone()).unwrap());
+        let partial = bls_aggregate_signatures(sigs[..2].to_vec()).unwrap();
+        assert!(!bls_verify_aggregated(pks.clone(), msgs.clone(), partial).unwrap());
+        assert!(!bls_verify_aggregated(vec![], vec![], agg.clone()).unwrap());
+
+        assert_eq!(
+            bls_verify_aggregated(pks.clone(), msgs[..2].to_vec(), agg.clone())
+                .unwrap_err()
+                .reason,
+            "public_keys and messages must have the same length, got 3 and 2"
+        );
+        let mut bad = pks.clone();
+        bad[1] = infinity_bytes(48);
+        assert_eq!(
+            bls_verify_aggregated(bad, msgs, agg).unwrap_err().reason,
+            "public_keys[1]: public key is the point at infinity"
+        );
+        assert_eq!(
+            bls_aggregate_signatures(vec![]).unwrap_err().reason,
+            "sigs: expected at least one signature"
+        );
+        let mut bad = sigs;
+        bad[2][0] &= !COMPRESSED;
+        assert_eq!(
+            bls_aggregate_signatures(bad).unwrap_err().reason,
+            "sigs[2]: compression flag is not set"
+        );
+    }
+}
@@ -0,0 +1,2652 @@
+//! BN254 (alt_bn128) field, G1 and G2 arithmetic
+//!
+//! Field elements cross the NAPI boundary as 32-byte big-endian canonical
+//! encodings and are held in Montgomery form internally; the `bn254_fr_*`
+//! array functions instead take Buffers of packed 32-byte little-endian
+//! elements, the layout provers keep their vectors in. G1 points use the
+//! 64-byte uncompressed affine encoding of the Ethereum precompiles
+//! (`x || y`, each coordinate 32 bytes big-endian, infinity as all zeros).
+//! G2 points are 128 bytes in the same precompile layout, each Fq2
+//! coordinate written `c1 || c0`.
+//! The `bn254_g1_*_batch` functions take packed 64-byte points whose
+//! coordinates are instead little-endian, like the scalar arrays.
+//! Compressed G1 points are 33 bytes: a flag byte, then `x` big-endian. The
+//! flag byte's high bit is set when `y` is the larger of `y` and `-y` as an
+//! integer, and bit 6 marks the point at infinity, whose `x` is all zeros.
+//! Compressed G2 points are 65 bytes, the same flag byte followed by `x` as
+//! `c1 || c0`. These are arkworks' flag bits, moved out of the top of `x`
+//! into a byte of their own.
+//! Fq12 elements are 384 bytes: the twelve Fq coefficients in tower order
+//! (`c0.c0.c0`, `c0.c0.c1`, `c0.c1.c0`, ..., `c1.c2.c1`), each 32 bytes
+//! big-endian, over `Fq2 = Fq[u] / (u^2 + 1)`, `Fq6 = Fq2[v] / (v^3 - (9 + u))`
+//! and `Fq12 = Fq6[w] / (w^2 - v)` as in arkworks.
+//!
+//! Only `bn254_field_mul_ct`, and `bn254_field_inv`, `bn254_fr_inv_batch`,
+//! `bn254_fr_pow_batch`, `bn254_g1_scalar_mul` and `bn254_g2_scalar_mul` with
+//! `constant_time` set or constant-time mode on (see
+//! [`crate::constant_time`]), run in constant time; every other function
+//! here is variable time and meant for public values.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, JsTypedArray, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+use crate::constant_time;
+use crate::ec::{Affine, Jacobian, SwCurve};
+use crate::field::{
+    barrett_elementwise, batch_invert, elementwise, inv_batch, pow_batch, sqrt_batch, Form,
+    Reduction, SqrtBatchResult,
+};
+use crate::montgomery::{geq, Field, Fp, MontConfig};
+use crate::pairing::{self, PairingConfig, Twist};
+use crate::tower::{self, TowerConfig};
+use crate::validate::ValidationResult;
+
+/// BN254 scalar field `r`
+/// = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+#[derive(Debug, Clone, Copy)]
+pub struct FrConfig;
+
+impl MontConfig<4> for FrConfig {
+    const MODULUS: [u64; 4] = [
+        0x43e1f593f0000001,
+        0x2833e84879b97091,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ];
+}
+
+/// Element of the BN254 scalar field
+pub type Fr = Fp<FrConfig, 4>;
+
+/// Size in bytes of an encoded scalar field element
+pub const FR_BYTES: usize = 32;
+
+/// BN254 base field `q`
+/// = 21888242871839275222246405745257275088696311157297823662689037894645226208583
+#[derive(Debug, Clone, Copy)]
+pub struct FqConfig;
+
+impl MontConfig<4> for FqConfig {
+    const MODULUS: [u64; 4] = [
+        0x3c208c16d87cfd47,
+        0x97816a916871ca8d,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ];
+}
+
+/// Element of the BN254 base field
+pub type Fq = Fp<FqConfig, 4>;
+
+/// Size in bytes of an uncompressed affine G1 point
+pub const G1_BYTES: usize = 64;
+
+/// The G1 curve `y^2 = x^3 + 3` over Fq
+#[derive(Debug, Clone, Copy)]
+pub struct G1Config;
+
+impl SwCurve for G1Config {
+    type Base = Fq;
+
+    fn coeff_b() -> Fq {
+        Fq::from_u64(3)
+    }
+}
+
+pub type G1Affine = Affine<G1Config>;
+pub type G1Projective = Jacobian<G1Config>;
+
+/// The standard G1 generator `(1, 2)`
+pub fn g1_generator() -> G1Affine {
+    G1Affine::new(Fq::from_u64(1), Fq::from_u64(2))
+}
+
+/// Decode a 64-byte uncompressed G1 point, rejecting points off the curve
+pub(crate) fn decode_g1(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    if bytes.len() != G1_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G1_BYTES}-byte uncompressed G1 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G1Affine::identity());
+    }
+    let coord = |range: std::ops::Range<usize>, axis: &str| {
+        Fq::from_be_bytes(&bytes[range]).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                format!("{name}: {axis} coordinate is not below the BN254 base field modulus"),
+            )
+        })
+    };
+    let point = G1Affine::new(coord(0..32, "x")?, coord(32..64, "y")?);
+    if !point.is_on_curve() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not on the BN254 G1 curve"),
+        ));
+    }
+    Ok(point)
+}
+
+/// Encode a G1 point in 64-byte uncompressed affine form
+pub(crate) fn encode_g1(point: &G1Affine) -> Vec<u8> {
+    if point.infinity {
+        return vec![0u8; G1_BYTES];
+    }
+    let mut out = point.x.to_be_bytes();
+    out.extend(point.y.to_be_bytes());
+    out
+}
+
+/// Decode packed 64-byte G1 points with little-endian coordinates
+///
+/// All zeros is the point at infinity. Coordinates must be canonical; the
+/// curve equation is checked only when `validate` is set.
+fn decode_g1_le_batch(bytes: &[u8], name: &str, validate: bool) -> Result<Vec<G1Affine>> {
+    crate::zero_copy::elements(bytes, G1_BYTES, name)?
+        .enumerate()
+        .map(|(i, p)| {
+            if p.iter().all(|&b| b == 0) {
+                return Ok(G1Affine::identity());
+            }
+            let coord = |range: std::ops::Range<usize>, axis: &str| {
+                Fq::from_le_bytes(&p[range]).ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!(
+                            "{name}[{i}]: {axis} coordinate is not below the BN254 base field modulus"
+                        ),
+                    )
+                })
+            };
+            let point = G1Affine::new(coord(0..32, "x")?, coord(32..64, "y")?);
+            if validate && !point.is_on_curve() {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("{name}[{i}]: point is not on the BN254 G1 curve"),
+                ));
+            }
+            Ok(point)
+        })
+        .collect()
+}
+
+/// Encode points as packed 64-byte G1 points with little-endian coordinates
+fn encode_g1_le_batch(points: &[G1Affine]) -> Vec<u8> {
+    let mut out = vec![0u8; points.len() * G1_BYTES];
+    for (p, chunk) in points.iter().zip(out.chunks_exact_mut(G1_BYTES)) {
+        if !p.infinity {
+            chunk[..32].copy_from_slice(&p.x.to_le_bytes());
+            chunk[32..].copy_from_slice(&p.y.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Normalize packed Jacobian triples of little-endian coordinates to
+/// packed little-endian G1 points
+pub fn g1_jacobian_to_affine_batch(points: &[u8]) -> Result<Vec<u8>> {
+    let affine = crate::ec::decode_jacobian_batch(points, 32, "BN254", Fq::from_le_bytes)?;
+    Ok(encode_g1_le_batch(&affine))
+}
+
+/// Element-wise `a + b` over packed little-endian G1 points
+pub fn g1_add_batch(a: &[u8], b: &[u8], validate: bool) -> Result<Vec<u8>> {
+    let a = decode_g1_le_batch(a, "a", validate)?;
+    let b = decode_g1_le_batch(b, "b", validate)?;
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same number of points, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    let sums: Vec<G1Projective> = a
+        .iter()
+        .zip(&b)
+        .map(|(a, b)| a.to_jacobian().add_affine(b))
+        .collect();
+    Ok(encode_g1_le_batch(&G1Projective::batch_normalize(&sums)))
+}
+
+/// Element-wise `2 P` over packed little-endian G1 points
+pub fn g1_double_batch(points: &[u8], validate: bool) -> Result<Vec<u8>> {
+    let doubles: Vec<G1Projective> = decode_g1_le_batch(points, "points", validate)?
+        .iter()
+        .map(|p| p.to_jacobian().double())
+        .collect();
+    Ok(encode_g1_le_batch(&G1Projective::batch_normalize(&doubles)))
+}
+
+/// Size in bytes of a compressed G1 point
+pub const G1_COMPRESSED_BYTES: usize = 33;
+
+/// Compressed flag bit: `y` is the larger of `y` and `-y`
+const FLAG_Y_LARGER: u8 = 0x80;
+/// Compressed flag bit: the point at infinity
+const FLAG_INFINITY: u8 = 0x40;
+
+/// Whether `y` is the larger of `y` and `-y` as an integer
+fn is_larger(y: &Fq) -> bool {
+    geq(&y.to_canonical(), &(-*y).to_canonical()) && !y.is_zero()
+}
+
+/// Encode a G1 point in 33-byte compressed form
+pub(crate) fn compress_g1(point: &G1Affine) -> Vec<u8> {
+    let mut out = vec![0u8; G1_COMPRESSED_BYTES];
+    if point.infinity {
+        out[0] = FLAG_INFINITY;
+        return out;
+    }
+    if is_larger(&point.y) {
+        out[0] = FLAG_Y_LARGER;
+    }
+    out[1..].copy_from_slice(&point.x.to_be_bytes());
+    out
+}
+
+/// Decode a 33-byte compressed G1 point, recovering `y` from `y^2 = x^3 + 3`
+pub(crate) fn decompress_g1(bytes: &[u8], name: &str) -> Result<G1Affine> {
+    if bytes.len() != G1_COMPRESSED_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G1_COMPRESSED_BYTES}-byte compressed G1 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    let invalid = |reason: &str| Error::new(Status::InvalidArg, format!("{name}: {reason}"));
+    let flags = bytes[0];
+    if flags & !(FLAG_Y_LARGER | FLAG_INFINITY) != 0 {
+        return Err(invalid("unknown flag bits set in the first byte"));
+    }
+    if flags & FLAG_INFINITY != 0 {
+        if flags != FLAG_INFINITY || bytes[1..].iter().any(|&b| b != 0) {
+            return Err(invalid("malformed encoding of the point at infinity"));
+        }
+        return Ok(G1Affine::identity());
+    }
+    let x = Fq::from_be_bytes(&bytes[1..])
+        .ok_or_else(|| invalid("x coordinate is not below the BN254 base field modulus"))?;
+    // q = 3 mod 4, so this is y = (x^3 + 3)^((q + 1) / 4)
+    let y = (x.square() * x + G1Config::coeff_b())
+        .sqrt(-Fq::one())
+        .ok_or_else(|| invalid("point is not on the BN254 G1 curve"))?;
+    let y = if is_larger(&y) == (flags & FLAG_Y_LARGER != 0) {
+        y
+    } else {
+        -y
+    };
+    Ok(G1Affine::new(x, y))
+}
+
+/// Compress packed little-endian G1 points to packed 33-byte points
+pub fn g1_compress_batch(points: &[u8]) -> Result<Vec<u8>> {
+    let points = decode_g1_le_batch(points, "points", true)?;
+    Ok(crate::ec::compress_batch(
+        &points,
+        G1_COMPRESSED_BYTES,
+        compress_g1,
+    ))
+}
+
+/// Decompress packed 33-byte G1 points to packed little-endian points
+pub fn g1_decompress_batch(compressed: &[u8]) -> Result<Vec<u8>> {
+    let points = crate::ec::decompress_batch(compressed, G1_COMPRESSED_BYTES, decompress_g1)?;
+    Ok(encode_g1_le_batch(&points))
+}
+
+/// `β`, a primitive cube root of unity in Fq: the endomorphism
+/// `φ(x, y) = (β x, y)` acts on G1 as multiplication by [`GLV_LAMBDA`]
+const GLV_BETA: [u64; 4] = [
+    0xe4bd44e5607cfd48,
+    0xc28f069fbb966e3d,
+    0x5e6dd9e7e0acccb0,
+    0x30644e72e131a029,
+];
+
+/// `λ`, the root of `λ^2 + λ + 1 = 0 mod r` matching [`GLV_BETA`]
+const GLV_LAMBDA: [u64; 4] = [
+    0xb8ca0b2d36636f23,
+    0xcc37a73fec2bc5e9,
+    0x048b6e193fd84104,
+    0x30644e72e131a029,
+];
+
+// The standard GLV lattice of `(a, b)` with `a + b λ = 0 mod r`, found by
+// the extended Euclidean algorithm on `(r, λ)`:
+//
+//   v1 = (a1, -a2) = (147946756881789319000765030803803410728, -9931322734385697763)
+//   v2 = (a2,  b2) = (9931322734385697763, 147946756881789319010696353538189108491)
+//
+// with determinant `a1 b2 + a2^2 = r` and `b2 = a1 + a2`. Writing `(k, 0)`
+// in this basis and rounding the coordinates `k b2 / r` and `k a2 / r` gives
+// a lattice point within half a basis vector of it, so both halves of the
+// split are below 2^127.
+
+/// `a1` of the GLV lattice basis
+const GLV_A1: [u64; 2] = [0x8211bbeb7d4f1128, 0x6f4d8248eeb859fc];
+/// `a2` of the GLV lattice basis, also `-b1`
+const GLV_A2: u64 = 0x89d3256894d213e3;
+/// `b2` of the GLV lattice basis
+const GLV_B2: [u64; 2] = [0x0be4e1541221250b, 0x6f4d8248eeb859fd];
+/// `round(2^256 b2 / r)`
+const GLV_G1: [u64; 3] = [0x5398fd0300ff6565, 0x4ccef014a773d2d2, 0x2];
+/// `round(2^256 a2 / r)`
+const GLV_G2: [u64; 2] = [0xd91d232ec7e0b3d7, 0x2];
+
+/// `round(k g / 2^256)` for a canonical `k` and one of [`GLV_G1`], [`GLV_G2`]
+fn glv_round<const M: usize, const W: usize>(k: &[u64; 4], g: &[u64; M]) -> Fr {
+    let product: [u64; W] = crate::barrett::mul_wide(k, g);
+    let carry = product[3] >> 63;
+    let mut c = [0u64; 4];
+    c[..W - 4].copy_from_slice(&product[4..]);
+    // At most 2^130, far below r
+    Fr::from_canonical(c).expect("rounded coordinate below r") + Fr::from_u64(carry)
+}
+
+/// Split `k` into `(k1, k2)` with `k = k1 + k2 λ mod r`, each returned as a
+/// sign (`true` for negative) and a magnitude below 2^127
+pub fn glv_decompose(k: &Fr) -> [(bool, u128); 2] {
+    let fr = |limbs: &[u64]| {
+        let mut c = [0u64; 4];
+        c[..limbs.len()].copy_from_slice(limbs);
+        Fr::from_canonical(c).expect("lattice constants are below r")
+    };
+    let canonical = k.to_canonical();
+    let c1 = glv_round::<3, 7>(&canonical, &GLV_G1);
+    let c2 = glv_round::<2, 6>(&canonical, &GLV_G2);
+    let (a1, a2, b2) = (fr(&GLV_A1), fr(&[GLV_A2]), fr(&GLV_B2));
+    // (k, 0) - c1 v1 - c2 v2
+    let k1 = *k - c1 * a1 - c2 * a2;
+    let k2 = c1 * a2 - c2 * b2;
+    debug_assert!(k1 + k2 * fr(&GLV_LAMBDA) == *k);
+    [k1, k2].map(|half| {
+        // Exactly one of `half` and `-half` is below 2^128, as r > 2^129
+        let (negative, magnitude) = match half.to_canonical() {
+            [lo, hi, 0, 0] => (false, [lo, hi]),
+            _ => {
+                let [lo, hi, ..] = (-half).to_canonical();
+                (true, [lo, hi])
+            }
+        };
+        (
+            negative,
+            (magnitude[1] as u128) << 64 | magnitude[0] as u128,
+        )
+    })
+}
+
+/// `k P` by the GLV method: `k1 P + k2 φ(P)` with a joint double-and-add
+/// (Straus) over the two half-width scalars, so about half the doublings of
+/// plain double-and-add
+pub fn g1_mul_glv(point: &G1Affine, k: &Fr) -> G1Projective {
+    if point.infinity {
+        return G1Projective::identity();
+    }
+    let [(neg1, k1), (neg2, k2)] = glv_decompose(k);
+    let beta = Fq::from_canonical(GLV_BETA).expect("β is below q");
+    let endo = G1Affine::new(point.x * beta, point.y);
+    let p1 = if neg1 { point.neg() } else { *point };
+    let p2 = if neg2 { endo.neg() } else { endo };
+    let both = p1.to_jacobian().add_affine(&p2);
+    let bits = 128 - (k1 | k2).leading_zeros();
+    let mut acc = G1Projective::identity();
+    for i in (0..bits).rev() {
+        acc = acc.double();
+        match ((k1 >> i) & 1, (k2 >> i) & 1) {
+            (1, 0) => acc = acc.add_affine(&p1),
+            (0, 1) => acc = acc.add_affine(&p2),
+            (1, 1) => acc = acc.add(&both),
+            _ => {}
+        }
+    }
+    acc
+}
+
+/// How [`bn254_g1_scalar_mul`] multiplies in variable time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ScalarMulConfig {
+    /// Split the scalar with [`glv_decompose`] and use [`g1_mul_glv`]
+    pub use_glv: bool,
+}
+
+impl ScalarMulConfig {
+    /// GLV only pays for its decomposition on scalars wider than its halves
+    pub(crate) fn for_scalar(scalar: &[u8]) -> Self {
+        ScalarMulConfig {
+            use_glv: scalar.iter().take(FR_BYTES - 16).any(|&b| b != 0),
+        }
+    }
+}
+
+/// Check a scalar argument is 32 bytes
+fn check_scalar_len(scalar: &[u8]) -> Result<()> {
+    if scalar.len() != FR_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "scalar: expected {FR_BYTES} big-endian bytes, got {} bytes",
+                scalar.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Reduce a 32-byte big-endian integer mod r
+fn fr_from_be_bytes_reduced(scalar: &[u8]) -> Fr {
+    let le: Vec<u8> = scalar.iter().rev().copied().collect();
+    Fr::from_le_bytes_reduced(&le).expect("32 bytes")
+}
+
+/// Variable-time `scalar · point` for a big-endian scalar, as `config` says
+pub(crate) fn g1_mul_with(
+    point: &G1Affine,
+    scalar: &[u8],
+    config: ScalarMulConfig,
+) -> G1Projective {
+    if config.use_glv {
+        // G1 has prime order r, so reducing the scalar keeps the product
+        g1_mul_glv(point, &fr_from_be_bytes_reduced(scalar))
+    } else {
+        point.to_jacobian().mul_be_bytes(scalar)
+    }
+}
+
+/// The BN254 extension tower, `ξ = 9 + u`
+#[derive(Debug, Clone, Copy)]
+pub struct Bn254Tower;
+
+static FROBENIUS_COEFFS: Lazy<[Fq2; 6]> = Lazy::new(tower::frobenius_coeffs::<Bn254Tower, 4>);
+
+impl TowerConfig<4> for Bn254Tower {
+    type Base = FqConfig;
+
+    const XI: [u64; 2] = [9, 1];
+
+    fn frobenius_coeffs() -> &'static [Fq2; 6] {
+        &FROBENIUS_COEFFS
+    }
+}
+
+pub type Fq2 = tower::Fp2<Bn254Tower, 4>;
+pub type Fq6 = tower::Fp6<Bn254Tower, 4>;
+pub type Fq12 = tower::Fp12<Bn254Tower, 4>;
+
+/// Size in bytes of an encoded Fq12 element
+pub const FQ12_BYTES: usize = 384;
+
+/// `(q^4 - q^2 + 1) / r`, the hard part of the final exponentiation
+const FINAL_EXP_HARD: [u64; 12] = [
+    0xe81bb482ccdf42b1,
+    0x5abf5cc4f49c36d4,
+    0xf1154e7e1da014fd,
+    0xdcc7b44c87cdbacf,
+    0xaaa441e3954bcf8a,
+    0x6b887d56d5095f23,
+    0x79581e16f3fd90c6,
+    0x3b1b1355d189227d,
+    0x4e529a5861876f6b,
+    0x6c0eb522d5b12278,
+    0x331ec15183177faf,
+    0x01baaa710b0759ad,
+];
+
+/// Encode an Fq12 element as its twelve coefficients in tower order
+pub(crate) fn encode_fq12(f: &Fq12) -> Vec<u8> {
+    [f.c0, f.c1]
+        .iter()
+        .flat_map(|c| [c.c0, c.c1, c.c2])
+        .flat_map(|c| [c.c0, c.c1])
+        .flat_map(|c| c.to_be_bytes())
+        .collect()
+}
+
+/// Decode a 384-byte Fq12 element, rejecting non-canonical coefficients
+pub(crate) fn decode_fq12(bytes: &[u8], name: &str) -> Result<Fq12> {
+    if bytes.len() != FQ12_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {FQ12_BYTES}-byte Fq12 element, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    let mut coeffs = [Fq::zero(); 12];
+    for (i, (c, chunk)) in coeffs.iter_mut().zip(bytes.chunks_exact(32)).enumerate() {
+        *c = Fq::from_be_bytes(chunk).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                format!("{name}: coefficient {i} is not below the BN254 base field modulus"),
+            )
+        })?;
+    }
+    let fq2 = |i: usize| Fq2::new(coeffs[2 * i], coeffs[2 * i + 1]);
+    Ok(Fq12::new(
+        Fq6::new(fq2(0), fq2(1), fq2(2)),
+        Fq6::new(fq2(3), fq2(4), fq2(5)),
+    ))
+}
+
+/// `f^((q^12 - 1) / r)`, mapping a Miller loop output into GT
+pub fn final_exponentiation(f: &Fq12) -> Fq12 {
+    pairing::final_exponentiation_with(f, &FINAL_EXP_HARD)
+}
+
+/// Size in bytes of an uncompressed affine G2 point
+pub const G2_BYTES: usize = 128;
+
+/// `3 / ξ`, the G2 twist coefficient
+static G2_COEFF_B: Lazy<Fq2> = Lazy::new(|| {
+    Fq2::new(Fq::from_u64(3), Fq::zero()) * Fq2::xi().inverse().expect("ξ is non-zero")
+});
+
+/// The G2 twist `y^2 = x^3 + 3 / (9 + u)` over Fq2
+#[derive(Debug, Clone, Copy)]
+pub struct G2Config;
+
+impl SwCurve for G2Config {
+    type Base = Fq2;
+
+    fn coeff_b() -> Fq2 {
+        *G2_COEFF_B
+    }
+}
+
+pub type G2Affine = Affine<G2Config>;
+pub type G2Projective = Jacobian<G2Config>;
+
+/// Ate pairing parameters, loop count `t - 1 = 6x^2`
+pub struct Bn254Pairing;
+
+impl PairingConfig<4> for Bn254Pairing {
+    type Tower = Bn254Tower;
+    type G1 = G1Config;
+    type G2 = G2Config;
+
+    const TWIST: Twist = Twist::D;
+    const LOOP_COUNT: &'static [u64] = &[SIX_X_SQUARED as u64, (SIX_X_SQUARED >> 64) as u64];
+    const LOOP_IS_NEGATIVE: bool = false;
+    const FINAL_EXP_HARD: &'static [u64] = &FINAL_EXP_HARD;
+}
+
+/// The G2 generator of the Ethereum precompiles
+pub fn g2_generator() -> G2Affine {
+    let fq = |limbs| Fq::from_canonical(limbs).expect("generator coordinates are canonical");
+    G2Affine::new(
+        Fq2::new(
+            fq([
+                0x46debd5cd992f6ed,
+                0x674322d4f75edadd,
+                0x426a00665e5c4479,
+                0x1800deef121f1e76,
+            ]),
+            fq([
+                0x97e485b7aef312c2,
+                0xf1aa493335a9e712,
+                0x7260bfb731fb5d25,
+                0x198e9393920d483a,
+            ]),
+        ),
+        Fq2::new(
+            fq([
+                0x4ce6cc0166fa7daa,
+                0xe3d1e7690c43d37b,
+                0x4aab71808dcb408f,
+                0x12c85ea5db8c6deb,
+            ]),
+            fq([
+                0x55acdadcd122975b,
+                0xbc4b313370b38ef3,
+                0xec9e99ad690c3395,
+                0x090689d0585ff075,
+            ]),
+        ),
+    )
+}
+
+/// Encode a G2 point in 128-byte uncompressed affine form
+pub(crate) fn encode_g2(point: &G2Affine) -> Vec<u8> {
+    if point.infinity {
+        return vec![0u8; G2_BYTES];
+    }
+    [point.x.c1, point.x.c0, point.y.c1, point.y.c0]
+        .iter()
+        .flat_map(|c| c.to_be_bytes())
+        .collect()
+}
+
+/// Decode a 128-byte uncompressed G2 point without checking the curve equation
+fn decode_g2_unchecked(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    if bytes.len() != G2_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G2_BYTES}-byte uncompressed G2 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(G2Affine::identity());
+    }
+    let coeffs = bytes
+        .chunks_exact(32)
+        .map(|c| {
+            Fq::from_be_bytes(c).ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!("{name}: coordinate is not below the BN254 base field modulus"),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(G2Affine::new(
+        Fq2::new(coeffs[1], coeffs[0]),
+        Fq2::new(coeffs[3], coeffs[2]),
+    ))
+}
+
+/// Decode a 128-byte uncompressed G2 point, naming the argument in errors
+///
+/// Checks that the point is on the curve but, like [`decode_g1`], not that
+/// it is in the prime-order subgroup.
+pub(crate) fn decode_g2(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    let point = decode_g2_unchecked(bytes, name)?;
+    if !point.is_on_curve() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not on the BN254 G2 curve"),
+        ));
+    }
+    Ok(point)
+}
+
+/// Decode a 128-byte uncompressed G2 point in the order-`r` subgroup
+///
+/// The Miller loop only gives a bilinear result on the subgroup, so
+/// untrusted G2 inputs to pairings are decoded with this.
+pub(crate) fn decode_g2_subgroup(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    let point = decode_g2(bytes, name)?;
+    if !g2_in_subgroup(&point) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: point is not in the BN254 G2 subgroup"),
+        ));
+    }
+    Ok(point)
+}
+
+/// `6 x^2` for the BN parameter `x = 4965661367192848881`
+const SIX_X_SQUARED: u128 = 6 * 4965661367192848881u128 * 4965661367192848881;
+
+/// Whether a point on the G2 twist lies in the order-`r` subgroup
+///
+/// Exactly the subgroup has `ψ(P) = [6 x^2] P` for the
+/// untwist-Frobenius-twist endomorphism `ψ` (El Housni, Guillevic and
+/// Piellard, ePrint 2022/352), a 127-bit multiplication instead of one by
+/// `r`.
+pub(crate) fn g2_in_subgroup(p: &G2Affine) -> bool {
+    if p.infinity {
+        return true;
+    }
+    let c = Bn254Tower::frobenius_coeffs();
+    let psi = G2Affine::new(p.x.conjugate() * c[2], p.y.conjugate() * c[3]);
+    let k = [SIX_X_SQUARED as u64, (SIX_X_SQUARED >> 64) as u64];
+    psi.to_jacobian() == p.to_jacobian().mul_limbs(&k)
+}
+
+/// Subgroup check of packed little-endian G1 points; the cofactor is 1, so
+/// this only checks the encoding and the curve equation
+pub fn g1_subgroup_check_batch(points: &[u8], stop_at_first: bool) -> Result<ValidationResult> {
+    crate::ec::subgroup_check_batch(points, G1_BYTES, stop_at_first, |p| {
+        decode_g1_le_batch(p, "points", true).is_ok()
+    })
+}
+
+/// Subgroup check of packed 128-byte uncompressed G2 points
+pub fn g2_subgroup_check_batch(points: &[u8], stop_at_first: bool) -> Result<ValidationResult> {
+    crate::ec::subgroup_check_batch(points, G2_BYTES, stop_at_first, |p| {
+        decode_g2(p, "points").is_ok_and(|p| g2_in_subgroup(&p))
+    })
+}
+
+/// Size in bytes of a compressed G2 point
+pub const G2_COMPRESSED_BYTES: usize = 65;
+
+/// Whether `y` is the larger of `y` and `-y`, comparing `c1` first and
+/// `c0` when `c1` is zero
+fn is_larger_fq2(y: &Fq2) -> bool {
+    if y.c1.is_zero() {
+        is_larger(&y.c0)
+    } else {
+        is_larger(&y.c1)
+    }
+}
+
+/// Encode a G2 point in 65-byte compressed form
+pub(crate) fn compress_g2(point: &G2Affine) -> Vec<u8> {
+    let mut out = vec![0u8; G2_COMPRESSED_BYTES];
+    if point.infinity {
+        out[0] = FLAG_INFINITY;
+        return out;
+    }
+    if is_larger_fq2(&point.y) {
+        out[0] = FLAG_Y_LARGER;
+    }
+    out[1..33].copy_from_slice(&point.x.c1.to_be_bytes());
+    out[33..].copy_from_slice(&point.x.c0.to_be_bytes());
+    out
+}
+
+/// Decode a 65-byte compressed G2 point, recovering `y` from the twist
+/// equation
+pub(crate) fn decompress_g2(bytes: &[u8], name: &str) -> Result<G2Affine> {
+    if bytes.len() != G2_COMPRESSED_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {G2_COMPRESSED_BYTES}-byte compressed G2 point, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    let invalid = |reason: &str| Error::new(Status::InvalidArg, format!("{name}: {reason}"));
+    let flags = bytes[0];
+    if flags & !(FLAG_Y_LARGER | FLAG_INFINITY) != 0 {
+        return Err(invalid("unknown flag bits set in the first byte"));
+    }
+    if flags & FLAG_INFINITY != 0 {
+        if flags != FLAG_INFINITY || bytes[1..].iter().any(|&b| b != 0) {
+            return Err(invalid("malformed encoding of the point at infinity"));
+        }
+        return Ok(G2Affine::identity());
+    }
+    let coord = |range: std::ops::Range<usize>| {
+        Fq::from_be_bytes(&bytes[range])
+            .ok_or_else(|| invalid("x coordinate is not below the BN254 base field modulus"))
+    };
+    let x = Fq2::new(coord(33..65)?, coord(1..33)?);
+    let y = (x.square() * x + G2Config::coeff_b())
+        .sqrt()
+        .ok_or_else(|| invalid("point is not on the BN254 G2 curve"))?;
+    let y = if is_larger_fq2(&y) == (flags & FLAG_Y_LARGER != 0) {
+        y
+    } else {
+        -y
+    };
+    Ok(G2Affine::new(x, y))
+}
+
+/// Decode a 32-byte big-endian scalar field element, naming the argument in errors
+pub(crate) fn parse_fr(bytes: &[u8], name: &str) -> Result<Fr> {
+    if bytes.len() != FR_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {FR_BYTES}-byte big-endian field element, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    Fr::from_be_bytes(bytes).ok_or_else(|| {
+        Error::new(
+            Status::InvalidArg,
+            format!("{name}: value is not below the BN254 scalar field modulus"),
+        )
+    })
+}
+
+/// Add two BN254 scalar field elements
+#[napi]
+pub fn bn254_field_add(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    Ok((parse_fr(&a, "a")? + parse_fr(&b, "b")?).to_be_bytes())
+}
+
+/// Subtract two BN254 scalar field elements (`a - b`)
+#[napi]
+pub fn bn254_field_sub(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    Ok((parse_fr(&a, "a")? - parse_fr(&b, "b")?).to_be_bytes())
+}
+
+/// Multiply two BN254 scalar field elements
+///
+/// Variable time: the Montgomery reduction branches on the operands. Use
+/// `bn254_field_mul_ct` when either operand is secret.
+#[napi]
+pub fn bn254_field_mul(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    Ok((parse_fr(&a, "a")? * parse_fr(&b, "b")?).to_be_bytes())
+}
+
+/// Decode a 32-byte big-endian scalar without branching on its value,
+/// beyond rejecting non-canonical encodings
+fn parse_fr_ct(bytes: &[u8], name: &str) -> Result<Fr> {
+    if bytes.len() != FR_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a {FR_BYTES}-byte big-endian field element, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    Fr::from_canonical_ct(limbs).ok_or_else(|| {
+        Error::new(
+            Status::InvalidArg,
+            format!("{name}: value is not below the BN254 scalar field modulus"),
+        )
+    })
+}
+
+/// Multiply two BN254 scalar field elements in constant time
+///
+/// Decoding, the Montgomery conversions and the product all use
+/// branch-free reductions, so the running time depends only on whether the
+/// inputs are valid encodings, not on their values.
+#[napi]
+pub fn bn254_field_mul_ct(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    let product = parse_fr_ct(&a, "a")?.mul_ct(&parse_fr_ct(&b, "b")?);
+    Ok(product
+        .to_canonical_ct()
+        .iter()
+        .rev()
+        .flat_map(|l| l.to_be_bytes())
+        .collect())
+}
+
+/// Below this many products the CPU path runs on the calling thread
+pub(crate) const MUL_BATCH_PARALLEL_THRESHOLD: usize = 1 << 12;
+
+/// Multiply BN254 scalar field elements pairwise
+///
+/// On Apple Silicon builds with the `metal` feature, large batches on
+/// machines reporting AMX are dispatched to the GPU, as is every batch when
+/// the `metal` backend is pinned. Otherwise the products
+/// are split across worker threads, using the paired NEON kernel on aarch64
+/// and scalar Montgomery multiplication elsewhere.
+#[napi]
+pub fn bn254_field_mul_batch(a: Vec<Vec<u8>>, b: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same length, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    let parse = |values: &[Vec<u8>], name: &str| {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| parse_fr(v, &format!("{name}[{i}]")))
+            .collect::<Result<Vec<_>>>()
+    };
+    let products = mul_batch(&parse(&a, "a")?, &parse(&b, "b")?);
+    Ok(products.iter().map(|p| p.to_be_bytes()).collect())
+}
+
+/// Element-wise products of two packed arrays of 32-byte big-endian elements
+pub fn mul_batch_packed(a: &[u8], b: &[u8]) -> Result<Vec<Fr>> {
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same length, got {} and {} bytes",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    let a = crate::zero_copy::parse_packed_fr(a, "a")?;
+    let b = crate::zero_copy::parse_packed_fr(b, "b")?;
+    Ok(mul_batch(&a, &b))
+}
+
+/// [`bn254_field_mul_batch`] over `Uint8Array`s of packed elements, read in
+/// place
+///
+/// See [`crate::zero_copy`] for the layout and the safety contract.
+#[napi]
+pub fn bn254_field_mul_batch_packed(a: JsTypedArray, b: JsTypedArray) -> Result<Buffer> {
+    let (a, b) = (a.into_value()?, b.into_value()?);
+    let products = mul_batch_packed(
+        crate::zero_copy::bytes(&a, "a")?,
+        crate::zero_copy::bytes(&b, "b")?,
+    )?;
+    Ok(crate::zero_copy::encode_packed_fr(&products).into())
+}
+
+/// [`bn254_field_mul_batch_packed`] writing the products into `output`
+///
+/// `output` must be exactly as long as `a` and may be the same array as
+/// either input.
+#[napi]
+pub fn bn254_field_mul_batch_into(
+    a: JsTypedArray,
+    b: JsTypedArray,
+    output: JsTypedArray,
+) -> Result<()> {
+    let products = {
+        let (a, b) = (a.into_value()?, b.into_value()?);
+        mul_batch_packed(
+            crate::zero_copy::bytes(&a, "a")?,
+            crate::zero_copy::bytes(&b, "b")?,
+        )?
+    };
+    let mut output = output.into_value()?;
+    crate::zero_copy::write_packed_fr(
+        crate::zero_copy::bytes_mut(&mut output, "output")?,
+        &products,
+        "output",
+    )
+}
+
+/// Element-wise products, choosing between the Metal, NEON and scalar paths
+pub fn mul_batch(a: &[Fr], b: &[Fr]) -> Vec<Fr> {
+    #[cfg(all(feature = "metal", target_os = "macos", target_arch = "aarch64"))]
+    if match crate::backend::active() {
+        Some(backend) => backend == crate::backend::Backend::Metal,
+        None => {
+            let caps = crate::detect_rust_capabilities();
+            caps.has_amx && a.len() >= crate::tuning::metal_min_batch(&caps.chip_family)
+        }
+    } {
+        if let Some(kernel) = crate::gpu::metal_available()
+            .then(crate::metal_kernels::mont_mul_kernel)
+            .flatten()
+        {
+            return kernel.mul(a, b);
+        }
+    }
+    mul_batch_cpu(a, b, crate::neon::enabled())
+}
+
+fn mul_batch_cpu(a: &[Fr], b: &[Fr], neon: bool) -> Vec<Fr> {
+    let mut out = vec![Fr::zero(); a.len()];
+    crate::parallel::par_chunks_mut(
+        &mut out,
+        2,
+        MUL_BATCH_PARALLEL_THRESHOLD,
+        |offset, chunk| {
+            let range = offset..offset + chunk.len();
+            if neon {
+                crate::neon::mul_batch(&a[range.clone()], &b[range], chunk);
+            } else {
+                for ((o, x), y) in chunk.iter_mut().zip(&a[range.clone()]).zip(&b[range]) {
+                    *o = *x * *y;
+                }
+            }
+        },
+    );
+    out
+}
+
+/// Invert a BN254 scalar field element
+///
+/// Uses the variable-time binary extended Euclidean algorithm. When
+/// `constant_time` is set, or omitted in constant-time mode, Fermat inversion
+/// (`a^(r-2)`) is used instead so the operation sequence does not depend on
+/// the input. Zero has no inverse and is reported as an error.
+#[napi]
+pub fn bn254_field_inv(a: Vec<u8>, constant_time: Option<bool>) -> Result<Vec<u8>> {
+    let a = parse_fr(&a, "a")?;
+    let inv = if constant_time::resolve(constant_time) {
+        a.inverse_ct()
+    } else {
+        a.inverse()
+    };
+    inv.map(|v| v.to_be_bytes())
+        .ok_or_else(|| Error::new(Status::InvalidArg, "a: zero has no inverse".to_string()))
+}
+
+/// Invert many BN254 scalar field elements with a single field inversion
+///
+/// Elements are 32-byte big-endian; zero inputs map to zero instead of
+/// failing the whole batch.
+#[napi]
+pub fn bn254_field_inv_batch(elements: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+    constant_time::require_variable_time("bn254_field_inv_batch")?;
+    let mut values = elements
+        .iter()
+        .enumerate()
+        .map(|(i, v)| parse_fr(v, &format!("elements[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    batch_invert(&mut values);
+    Ok(values.iter().map(|v| v.to_be_bytes()).collect())
+}
+
+/// Compress a 64-byte uncompressed BN254 G1 point to 33 bytes
+#[napi]
+pub fn bn254_g1_compress(point: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(compress_g1(&decode_g1(&point, "point")?))
+}
+
+/// Decompress a 33-byte BN254 G1 point to the 64-byte uncompressed encoding
+///
+/// Fails if `x` is not the x-coordinate of a point on the curve.
+#[napi]
+pub fn bn254_g1_decompress(compressed: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(encode_g1(&decompress_g1(&compressed, "compressed")?))
+}
+
+/// Multiply a 64-byte BN254 G1 point by a 32-byte big-endian scalar
+///
+/// The scalar is any 256-bit integer, not necessarily below `r`. With
+/// `constant_time` set, or omitted in constant-time mode, a Montgomery
+/// ladder over all 256 bits. Otherwise scalars wider than 128 bits take the
+/// GLV path of [`bn254_g1_scalar_mul_glv`] and shorter ones double-and-add.
+#[napi]
+pub fn bn254_g1_scalar_mul(
+    point: Vec<u8>,
+    scalar: Vec<u8>,
+    constant_time: Option<bool>,
+) -> Result<Vec<u8>> {
+    let point = decode_g1(&point, "point")?;
+    check_scalar_len(&scalar)?;
+    let product = if constant_time::resolve(constant_time) {
+        point.to_jacobian().mul_be_bytes_ct(&scalar)
+    } else {
+        g1_mul_with(&point, &scalar, ScalarMulConfig::for_scalar(&scalar))
+    };
+    Ok(encode_g1(&product.to_affine()))
+}
+
+/// Multiply a BN254 G1 point by a 32-byte big-endian scalar with the GLV
+/// endomorphism
+///
+/// Reduces the scalar mod `r`, splits it into two signed halves below 2^127
+/// with `k = k1 + k2 λ`, and adds `k1 P + k2 φ(P)` in one double-and-add
+/// pass, where `φ(x, y) = (β x, y)`. Variable time; throws in constant-time
+/// mode.
+#[napi]
+pub fn bn254_g1_scalar_mul_glv(point: Vec<u8>, scalar: Vec<u8>) -> Result<Vec<u8>> {
+    constant_time::require_variable_time("bn254_g1_scalar_mul_glv")?;
+    let point = decode_g1(&point, "point")?;
+    check_scalar_len(&scalar)?;
+    let product = g1_mul_with(&point, &scalar, ScalarMulConfig { use_glv: true });
+    Ok(encode_g1(&product.to_affine()))
+}
+
+/// Add packed BN254 G1 points pairwise
+///
+/// `a` and `b` hold the same number of 64-byte affine points `x || y` with
+/// 32-byte little-endian coordinates, the point at infinity as all zeros.
+/// The sums are computed in Jacobian coordinates and normalized together
+/// with a single batched inversion, so `P + (-P)` comes back as zeros.
+/// Points off the curve throw unless `skip_validation` is set; coordinates
+/// `>= q` always throw.
+#[napi]
+pub fn bn254_g1_add_batch(a: Buffer, b: Buffer, skip_validation: Option<bool>) -> Result<Buffer> {
+    g1_add_batch(&a, &b, !skip_validation.unwrap_or(false)).map(Buffer::from)
+}
+
+/// Double every packed BN254 G1 point
+///
+/// Same layout and validation as [`bn254_g1_add_batch`].
+#[napi]
+pub fn bn254_g1_double_batch(points: Buffer, skip_validation: Option<bool>) -> Result<Buffer> {
+    g1_double_batch(&points, !skip_validation.unwrap_or(false)).map(Buffer::from)
+}
+
+/// The standard BN254 G2 generator in the 128-byte uncompressed encoding
+#[napi]
+pub fn bn254_g2_generator() -> Vec<u8> {
+    encode_g2(&g2_generator())
+}
+
+/// Add two 128-byte uncompressed BN254 G2 points
+#[napi]
+pub fn bn254_g2_add(a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>> {
+    let a = decode_g2(&a, "a")?;
+    let b = decode_g2(&b, "b")?;
+    Ok(encode_g2(&a.to_jacobian().add_affine(&b).to_affine()))
+}
+
+/// Multiply a BN254 G2 point by a 32-byte big-endian scalar
+///
+/// The scalar is any 256-bit integer, not necessarily below `r`.
+/// Double-and-add by default. With `constant_time` set, or omitted in
+/// constant-time mode, a Montgomery ladder over all 256 bits.
+#[napi]
+pub fn bn254_g2_scalar_mul(
+    point: Vec<u8>,
+    scalar: Vec<u8>,
+    constant_time: Option<bool>,
+) -> Result<Vec<u8>> {
+    let point = decode_g2(&point, "point")?;
+    check_scalar_len(&scalar)?;
+    let point = point.to_jacobian();
+    let product = if constant_time::resolve(constant_time) {
+        point.mul_be_bytes_ct(&scalar)
+    } else {
+        point.mul_be_bytes(&scalar)
+    };
+    Ok(encode_g2(&product.to_affine()))
+}
+
+/// Compress a 128-byte uncompressed BN254 G2 point to 65 bytes
+///
+/// A flag byte as for G1, then `x` as `c1 || c0`. The sign compares the
+/// `c1` coefficients of `y` and `-y`, or `c0` when `c1` is zero.
+#[napi]
+pub fn bn254_g2_compress(point: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(compress_g2(&decode_g2(&point, "point")?))
+}
+
+/// Decompress a 65-byte BN254 G2 point to the 128-byte uncompressed encoding
+///
+/// Fails if `x` is not the x-coordinate of a point on the twist. Like the
+/// uncompressed decoding, this does not check subgroup membership.
+#[napi]
+pub fn bn254_g2_decompress(compressed: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(encode_g2(&decompress_g2(&compressed, "compressed")?))
+}
+
+/// Check whether a 128-byte uncompressed point lies on the BN254 G2 twist
+///
+/// Non-canonical coordinates are reported as `false`; only a wrong input
+/// length is an error.
+#[napi]
+pub fn bn254_g2_is_on_curve(point: Vec<u8>) -> Result<bool> {
+    match decode_g2_unchecked(&point, "point") {
+        Ok(p) => Ok(p.is_on_curve()),
+        Err(_) if point.len() == G2_BYTES => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Product of two encoded Fq12 elements
+pub fn fq12_mul(a: &[u8], b: &[u8]) -> Result<Vec<u8>> {
+    let a = decode_fq12(a, "a")?;
+    let b = decode_fq12(b, "b")?;
+    Ok(encode_fq12(&(a * b)))
+}
+
+/// Final exponentiation of an encoded Fq12 element
+pub fn fq12_final_exponentiation(f: &[u8]) -> Result<Vec<u8>> {
+    Ok(encode_fq12(&final_exponentiation(&decode_fq12(f, "f")?)))
+}
+
+/// Multiply two 384-byte BN254 Fq12 elements
+#[napi]
+pub fn bn254_fp12_mul(a: Buffer, b: Buffer) -> Result<Buffer> {
+    fq12_mul(&a, &b).map(Buffer::from)
+}
+
+/// Raise a 384-byte BN254 Fq12 element to `(q^12 - 1) / r`
+///
+/// This is the last step of a pairing: a Miller loop output maps to its
+/// pairing value in the order-`r` subgroup GT. Zero maps to zero.
+#[napi]
+pub fn bn254_fp12_final_exponentiation(f: Buffer) -> Result<Buffer> {
+    fq12_final_exponentiation(&f).map(Buffer::from)
+}
+
+/// Field name used in the array functions' errors
+const FIELD: &str = "BN254 scalar field";
+
+/// Element-wise `a + b` over packed little-endian scalars in `form`
+pub fn fr_add(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FIELD,
+        |[x, y]: [Fr; 2]| x + y,
+    )
+}
+
+/// Element-wise `a - b` over packed little-endian scalars in `form`
+pub fn fr_sub(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FIELD,
+        |[x, y]: [Fr; 2]| x - y,
+    )
+}
+
+/// Element-wise `a * b` over packed little-endian scalars in `form`
+pub fn fr_mul(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        FIELD,
+        |[x, y]: [Fr; 2]| x * y,
+    )
+}
+
+/// Element-wise `a + b` over packed canonical little-endian scalars, with
+/// Barrett reduction
+pub fn fr_add_barrett(a: &[u8], b: &[u8], reduce: bool) -> Result<Vec<u8>> {
+    barrett_elementwise::<FrConfig, 2>([(a, "a"), (b, "b")], reduce, FIELD, |m, [x, y]| {
+        m.add(&x, &y)
+    })
+}
+
+/// Element-wise `a * b` over packed canonical little-endian scalars, with
+/// Barrett reduction
+pub fn fr_mul_barrett(a: &[u8], b: &[u8], reduce: bool) -> Result<Vec<u8>> {
+    barrett_elementwise::<FrConfig, 2>([(a, "a"), (b, "b")], reduce, FIELD, |m, [x, y]| {
+        m.mul(&x, &y)
+    })
+}
+
+/// Element-wise `-a` over packed little-endian scalars in `form`
+pub fn fr_neg(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise([(a, "a")], reduce, (form, form), FIELD, |[x]: [Fr; 1]| -x)
+}
+
+/// Element-wise inverse of packed little-endian scalars in `form`
+pub fn fr_inv_batch(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    inv_batch::<Fr>(values, allow_zero, constant_time, form, FIELD)
+}
+
+/// `x^exponent` over packed little-endian scalars in `form`
+pub fn fr_pow_batch(
+    bases: &[u8],
+    exponent: &[u8],
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    pow_batch::<FrConfig, 4>(bases, exponent, constant_time, form, FIELD)
+}
+
+/// Square roots of packed canonical little-endian scalars, with QR flags
+pub fn fr_sqrt_batch(values: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    sqrt_batch(
+        values,
+        crate::ntt::root_of_unity(crate::ntt::TWO_ADICITY),
+        FIELD,
+    )
+}
+
+/// Re-encode packed little-endian scalars from one form to another
+pub fn fr_convert(values: &[u8], reduce: bool, from: Form, to: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(values, "values")],
+        reduce,
+        (from, to),
+        FIELD,
+        |[x]: [Fr; 1]| x,
+    )
+}
+
+/// Element-wise `a + b` over packed 32-byte little-endian BN254 scalars
+///
+/// `a` and `b` must be the same length. Values `>= r` are rejected unless
+/// `reduce` is set, in which case they are reduced modulo `r` first. With
+/// `in_montgomery_form` set, inputs and result are Montgomery-form limbs
+/// (see `bn254_fr_to_montgomery_batch`). `reduction: "barrett"` reduces
+/// canonical values with Barrett reduction instead of converting them to
+/// Montgomery form; the results are identical.
+#[napi]
+pub fn bn254_fr_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    reduction: Option<String>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    let reduce = reduce.unwrap_or(false);
+    match Reduction::parse(reduction.as_deref(), form)? {
+        Reduction::Montgomery => fr_add(&a, &b, reduce, form),
+        Reduction::Barrett => fr_add_barrett(&a, &b, reduce),
+    }
+    .map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed 32-byte little-endian BN254 scalars
+#[napi]
+pub fn bn254_fr_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_sub(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed 32-byte little-endian BN254 scalars
+///
+/// Options as for `bn254_fr_add`.
+#[napi]
+pub fn bn254_fr_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    reduction: Option<String>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    let reduce = reduce.unwrap_or(false);
+    match Reduction::parse(reduction.as_deref(), form)? {
+        Reduction::Montgomery => fr_mul(&a, &b, reduce, form),
+        Reduction::Barrett => fr_mul_barrett(&a, &b, reduce),
+    }
+    .map(Buffer::from)
+}
+
+/// Element-wise `-a` over packed 32-byte little-endian BN254 scalars
+#[napi]
+pub fn bn254_fr_neg(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_neg(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Convert packed canonical BN254 scalars to Montgomery-form limbs
+#[napi]
+pub fn bn254_fr_to_montgomery_batch(values: Buffer, reduce: Option<bool>) -> Result<Buffer> {
+    fr_convert(
+        &values,
+        reduce.unwrap_or(false),
+        Form::Canonical,
+        Form::Montgomery,
+    )
+    .map(Buffer::from)
+}
+
+/// Convert packed Montgomery-form BN254 scalars back to canonical values
+#[napi]
+pub fn bn254_fr_from_montgomery_batch(values: Buffer, reduce: Option<bool>) -> Result<Buffer> {
+    fr_convert(
+        &values,
+        reduce.unwrap_or(false),
+        Form::Montgomery,
+        Form::Canonical,
+    )
+    .map(Buffer::from)
+}
+
+/// Invert packed 32-byte little-endian BN254 scalars with Montgomery's trick
+///
+/// Each worker chunk runs one prefix-product pass, a single field inversion
+/// and a backward pass, instead of one inversion per element. A zero
+/// element is an error naming its index unless `allow_zero` is set, in
+/// which case zeros map to zero. With `constant_time` set, or omitted in
+/// constant-time mode, every element gets its own Fermat inversion instead.
+#[napi]
+pub fn bn254_fr_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_inv_batch(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// `x^exponent` for packed 32-byte little-endian BN254 scalars
+///
+/// `exponent` is big-endian and shared by every element; an empty or zero
+/// exponent gives all ones. Uses a fixed 4-bit window. With `constant_time`
+/// set, or omitted in constant-time mode, the exponentiation does not branch
+/// on the bases; combine it with
+/// `in_montgomery_form` for secret data, since canonical conversions are
+/// variable time.
+#[napi]
+pub fn bn254_fr_pow_batch(
+    bases: Buffer,
+    exponent: Buffer,
+    constant_time: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    fr_pow_batch(
+        &bases,
+        &exponent,
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// Square roots of packed 32-byte little-endian BN254 scalars
+///
+/// `roots` holds one root per element, the smaller of the two as an
+/// integer; `flags` is 1 where the element is a square (zero included) and
+/// 0 for a non-residue, whose root is left zero. Elements `>= r` are
+/// rejected.
+#[napi]
+pub fn bn254_fr_sqrt_batch(values: Buffer) -> Result<SqrtBatchResult> {
+    let (roots, flags) = fr_sqrt_batch(&values)?;
+    Ok(SqrtBatchResult {
+        roots: roots.into(),
+        flags: flags.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::ELEMENTWISE_PARALLEL_THRESHOLD;
+    use crate::montgomery::geq;
+
+    fn hex(s: &str) -> Vec<u8> {
+        let s = format!("{s:0>64}");
+        (0..32)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const R_MINUS_ONE: &str = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000";
+
+    #[test]
+    fn test_zero_and_one() {
+        let zero = hex("0");
+        let one = hex("1");
+        assert_eq!(bn254_field_add(zero.clone(), one.clone()).unwrap(), one);
+        assert_eq!(bn254_field_mul(zero.clone(), one.clone()).unwrap(), zero);
+        assert_eq!(bn254_field_mul(one.clone(), one.clone()).unwrap(), one);
+        assert_eq!(bn254_field_inv(one.clone(), None).unwrap(), one);
+        assert!(bn254_field_inv(zero, None).is_err());
+    }
+
+    #[test]
+    fn test_modulus_minus_one() {
+        let m1 = hex(R_MINUS_ONE);
+        let one = hex("1");
+        // (r - 1) + 1 wraps to zero, 0 - 1 wraps to r - 1
+        assert_eq!(bn254_field_add(m1.clone(), one.clone()).unwrap(), hex("0"));
+        assert_eq!(bn254_field_sub(hex("0"), one.clone()).unwrap(), m1);
+        // (-1)^2 = 1 and (-1)^-1 = -1
+        assert_eq!(bn254_field_mul(m1.clone(), m1.clone()).unwrap(), one);
+        assert_eq!(bn254_field_inv(m1.clone(), None).unwrap(), m1);
+    }
+
+    #[test]
+    fn test_mul_ct_matches_variable_time() {
+        let mut values: Vec<Vec<u8>> = ["0", "1", "2", R_MINUS_ONE]
+            .iter()
+            .map(|s| hex(s))
+            .collect();
+        // (r - 1) / 2, r - 2, and a spread of full-width values
+        values.push(hex(
+            "183227397098d014dc2822db40c0ac2e9419f4243cdcb848a1f0fac9f8000000",
+        ));
+        values.push(hex(
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593efffffff",
+        ));
+        let mut x = Fr::from_u64(0x9e3779b97f4a7c15);
+        for _ in 0..32 {
+            x = x * x + Fr::from_u64(7);
+            values.push(x.to_be_bytes());
+        }
+        for a in &values {
+            for b in &values {
+                assert_eq!(
+                    bn254_field_mul_ct(a.clone(), b.clone()).unwrap(),
+                    bn254_field_mul(a.clone(), b.clone()).unwrap()
+                );
+            }
+        }
+        let err = bn254_field_mul_ct(vec![0xff; 32], hex("1")).unwrap_err();
+        assert!(err.reason.starts_with("a:"), "{}", err.reason);
+        assert!(bn254_field_mul_ct(hex("1"), vec![0; 31]).is_err());
+    }
+
+    #[test]
+    fn test_inv_batch() {
+        // Long enough to take the chunked parallel path
+        let mut values: Vec<Fr> = (0..3000u64)
+            .map(|i| {
+                if i % 97 == 0 {
+                    Fr::zero()
+                } else {
+                    Fr::from_u64(i).square() - Fr::one()
+                }
+            })
+            .collect();
+        let expected: Vec<Fr> = values
+            .iter()
+            .map(|v| v.inverse().unwrap_or(Fr::zero()))
+            .collect();
+        batch_invert(&mut values);
+        assert_eq!(values, expected);
+
+        let inputs = vec![hex("0"), hex("1"), hex(R_MINUS_ONE), hex("2")];
+        let out = bn254_field_inv_batch(inputs).unwrap();
+        assert_eq!(out[0], hex("0"));
+        assert_eq!(out[1], hex("1"));
+        assert_eq!(out[2], hex(R_MINUS_ONE));
+        assert_eq!(out[3], bn254_field_inv(hex("2"), None).unwrap());
+        assert!(bn254_field_inv_batch(vec![]).unwrap().is_empty());
+        assert!(bn254_field_inv_batch(vec![vec![0xff; 32]]).is_err());
+    }
+
+    fn le_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fr_array_known_answers() {
+        // Computed with Python integers modulo r
+        let a = le_hex("efcdab9078563412efcdab9078563412efcdab9078563412efcdab9078563412");
+        let b = le_hex("32547698a0cbed1f32547698a0cbed1f32547698a0cbed1f32547698a0cbed2f");
+        let (x, y) = (
+            [a.clone(), b.clone()].concat(),
+            [b.clone(), vec![0; 32]].concat(),
+        );
+        let sum = fr_add(&x, &y, false, Form::Canonical).unwrap();
+        assert_eq!(
+            sum[..32],
+            le_hex("20222239852c40ee8fb168afd039ee09c4c9a0a762dcd179f781f047a6d3bd11")
+        );
+        assert_eq!(sum[32..], b[..]);
+        let diff = fr_sub(&x, &y, false, Form::Canonical).unwrap();
+        assert_eq!(
+            diff[..32],
+            le_hex("be7935e86b8028364eeaee7120737a1a1ad2b6798ed096aae61967d94ad9aa12")
+        );
+        let product = fr_mul(&x, &y, false, Form::Canonical).unwrap();
+        assert_eq!(
+            product[..32],
+            le_hex("72a52f5b20cb224a21bb0a83337d7b7fe910ba9d872b89304a6c09be9857b001")
+        );
+        assert_eq!(product[32..], [0u8; 32]);
+        assert_eq!(
+            fr_neg(&a, false, Form::Canonical).unwrap(),
+            le_hex("1232545f1b9fad31a2a20de9cf91ff156e8ad5f03def1ba63ad28550faf72f1e")
+        );
+        assert!(fr_add(&[], &[], false, Form::Canonical).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fr_array_out_of_range() {
+        let r_plus_5 = le_hex("060000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430");
+        let input = [r_plus_5, vec![0xff; 32]].concat();
+
+        let err = fr_add(&[0; 64], &input, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.starts_with("b[0]:"), "{}", err.reason);
+
+        let reduced = fr_add(&[0; 64], &input, true, Form::Canonical).unwrap();
+        let mut five = [0u8; 32];
+        five[0] = 5;
+        assert_eq!(reduced[..32], five);
+        assert_eq!(
+            reduced[32..],
+            le_hex("faffff4f1c3496ac29cd609f9576fc362e4679786fa36e662fdf079ac1770a0e")
+        );
+    }
+
+    #[test]
+    fn test_fr_array_lengths() {
+        let err = fr_mul(&[0; 64], &[0; 32], false, Form::Canonical).unwrap_err();
+        assert!(err.reason.contains("same length"), "{}", err.reason);
+        let err = fr_neg(&[0; 33], false, Form::Canonical).unwrap_err();
+        assert!(err.reason.contains("multiple of 32"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fr_array_parallel_matches_scalar() {
+        let n = 3 * ELEMENTWISE_PARALLEL_THRESHOLD + 7;
+        let a: Vec<Fr> = (0..n as u64).map(|i| Fr::from_u64(i * 7919 + 1)).collect();
+        let b: Vec<Fr> = (0..n as u64).map(|i| -Fr::from_u64(i * 104729)).collect();
+        let pack = |v: &[Fr]| v.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+        let expected: Vec<Fr> = a.iter().zip(&b).map(|(x, y)| *x * *y).collect();
+        assert_eq!(
+            fr_mul(&pack(&a), &pack(&b), false, Form::Canonical).unwrap(),
+            pack(&expected)
+        );
+
+        // The reported element is the first bad one, whichever worker saw it
+        let mut bytes = pack(&a);
+        for i in [n - 1, n / 2] {
+            bytes[i * FR_BYTES..(i + 1) * FR_BYTES].fill(0xff);
+        }
+        let err = fr_neg(&bytes, false, Form::Canonical).unwrap_err();
+        assert!(
+            err.reason.starts_with(&format!("a[{}]:", n / 2)),
+            "{}",
+            err.reason
+        );
+    }
+
+    #[test]
+    fn test_mul_batch_paths_agree() {
+        let a: Vec<Fr> = (0..5000u64)
+            .map(|i| Fr::from_u64(i * 0x9e37_79b9) - Fr::one())
+            .collect();
+        let b: Vec<Fr> = (0..5000u64)
+            .map(|i| -Fr::from_u64(i + 7).square())
+            .collect();
+        let expected: Vec<Fr> = a.iter().zip(&b).map(|(x, y)| *x * *y).collect();
+        assert_eq!(mul_batch_cpu(&a, &b, false), expected);
+        assert_eq!(mul_batch_cpu(&a, &b, true), expected);
+        assert_eq!(mul_batch(&a, &b), expected);
+
+        let encoded = |v: &[Fr]| v.iter().take(3).map(|x| x.to_be_bytes()).collect();
+        assert_eq!(
+            bn254_field_mul_batch(encoded(&a), encoded(&b)).unwrap(),
+            encoded(&expected)
+        );
+        assert!(bn254_field_mul_batch(encoded(&a), vec![]).is_err());
+        assert!(bn254_field_mul_batch(vec![vec![0xff; 32]], vec![vec![0; 32]]).is_err());
+    }
+
+    #[test]
+    fn test_known_pairs() {
+        // (a, b, a + b, a - b, a * b, a^-1), computed independently with Python big integers
+        let vectors = [
+            (
+                "078bfae2414c343c1027c4d1c386bbc4cd613e30d8f16adf91b7584a2265b1f5",
+                "0d6fe64bc9e9c616612e7696a6cecc1b78e510617311d8a3c2ce6f447ed4d57b",
+                "14fbe12e0b35fa5271563b686a5587e046464e924c0343835485c78ea13a8770",
+                "2a80630958940e4f674993f19e3948067cb01617df9902cd12cade999390dc7b",
+                "17badba847377e05a028d78da4056ee1eec90607044143890d29395dd5f996bd",
+                "0c87581b72158a09c12946390cf925b753202e296bdbdc705079da7f6097fc91",
+            ),
+            (
+                "26e0439d6ec9d28663ca828dd5f4b3b2e4b06ce60741c7a87ce42c8218072e8c",
+                "016db9b807d4bedc51431193e6c3f3391a2b8f1ff1fd42a29755d4c13a902931",
+                "284dfd55769e9162b50d9421bcb8a6ebfedbfc05f93f0a4b143a0143529757bd",
+                "257289e566f513aa128770f9ef30c079ca84ddc615448505e58e57c0dd77055b",
+                "0847f8c9527b62aedc0dc83519e2170e53060cb73fb2244bcc23c9d5cf282a9f",
+                "1c9839e8fa2ec7a1f7f95974f5bbea5195d7b65ce4a518ce173a748eb07b188b",
+            ),
+            (
+                "2bef59fe619699cfe1988ad9f06c144a025b413f8a9a021ea648a7dd06839eb9",
+                "2b517c8f3b1a11df587fd2803bab6c398d88348a7eed8d14f06d3fef701966a0",
+                "26dc881abb7f0b8581c817a3aa96282667af8d818fce1ea252d3f238869d0558",
+                "009ddd6f267c87f08918b859b4c0a81074d30cb50bac7509b5db67ed966a3819",
+                "29bbe36accff28b816baa630c2a1d9643976ff3c24cb6921985781669355b6a7",
+                "2b6d08f6aab507eb9ea8d1fa04c1edc6d3890c06781b1c0a0de86f1def83c6bd",
+            ),
+        ];
+        for (a, b, sum, diff, prod, inv) in vectors {
+            assert_eq!(bn254_field_add(hex(a), hex(b)).unwrap(), hex(sum));
+            assert_eq!(bn254_field_sub(hex(a), hex(b)).unwrap(), hex(diff));
+            assert_eq!(bn254_field_mul(hex(a), hex(b)).unwrap(), hex(prod));
+            assert_eq!(bn254_field_inv(hex(a), None).unwrap(), hex(inv));
+            assert_eq!(bn254_field_inv(hex(a), Some(true)).unwrap(), hex(inv));
+        }
+    }
+
+    #[test]
+    fn test_g1_encoding() {
+        let g = g1_generator();
+        assert!(g.is_on_curve());
+        let bytes = encode_g1(&g);
+        assert_eq!(bytes[31], 1);
+        assert_eq!(bytes[63], 2);
+        assert_eq!(decode_g1(&bytes, "g").unwrap(), g);
+        assert!(decode_g1(&[0u8; 64], "inf").unwrap().infinity);
+        let mut off = bytes.clone();
+        off[63] = 3;
+        assert!(decode_g1(&off, "off").is_err());
+        assert!(decode_g1(&bytes[..63], "short").is_err());
+    }
+
+    #[test]
+    fn test_g2_generator() {
+        let g = g2_generator();
+        assert!(g.is_on_curve());
+        assert!(g.to_jacobian().mul_limbs(&FrConfig::MODULUS).is_identity());
+        let bytes = encode_g2(&g);
+        assert_eq!(
+            bytes[..32],
+            hex("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2")
+        );
+        assert_eq!(encode_g2(&G2Affine::identity()), vec![0u8; G2_BYTES]);
+    }
+
+    /// Raw 256-bit big-endian scalars from an xorshift stream, not reduced
+    fn raw_scalars(len: usize, seed: u64) -> Vec<Vec<u8>> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                (0..4)
+                    .flat_map(|_| {
+                        x ^= x << 13;
+                        x ^= x >> 7;
+                        x ^= x << 17;
+                        x.to_be_bytes()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_glv_endomorphism() {
+        let g = g1_generator();
+        let beta = Fq::from_canonical(GLV_BETA).unwrap();
+        assert_eq!(beta * beta * beta, Fq::one());
+        let lambda = Fr::from_canonical(GLV_LAMBDA).unwrap();
+        assert_eq!(lambda.square() + lambda + Fr::one(), Fr::zero());
+        let endo = G1Affine::new(g.x * beta, g.y);
+        assert_eq!(g.to_jacobian().mul_limbs(&GLV_LAMBDA).to_affine(), endo);
+
+        for scalar in raw_scalars(200, 0x5eed) {
+            let k = fr_from_be_bytes_reduced(&scalar);
+            let [(neg1, k1), (neg2, k2)] = glv_decompose(&k);
+            assert!(k1 < 1 << 127 && k2 < 1 << 127, "{k:?}");
+            let signed = |neg: bool, m: u128| {
+                let v = Fr::from_canonical([m as u64, (m >> 64) as u64, 0, 0]).unwrap();
+                if neg {
+                    -v
+                } else {
+                    v
+                }
+            };
+            assert_eq!(signed(neg1, k1) + signed(neg2, k2) * lambda, k);
+        }
+    }
+
+    #[test]
+    fn test_g1_scalar_mul_glv_matches_double_and_add() {
+        let g = g1_generator().to_jacobian();
+        let r_bytes = FrConfig::MODULUS
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect();
+        let mut scalars = raw_scalars(60, 0x9e3779b97f4a7c15);
+        scalars.extend([
+            vec![0; 32],
+            Fr::one().to_be_bytes(),
+            (-Fr::one()).to_be_bytes(),
+            r_bytes,
+            vec![0xff; 32],
+            Fr::from_canonical(GLV_LAMBDA).unwrap().to_be_bytes(),
+        ]);
+        let points = raw_scalars(scalars.len(), 7);
+        for (scalar, seed) in scalars.iter().zip(&points) {
+            let point = g.mul_be_bytes(seed).to_affine();
+            let encoded = encode_g1(&point);
+            let expected = encode_g1(&point.to_jacobian().mul_be_bytes(scalar).to_affine());
+            assert_eq!(
+                bn254_g1_scalar_mul_glv(encoded.clone(), scalar.clone()).unwrap(),
+                expected
+            );
+            for ct in [Some(false), Some(true)] {
+                assert_eq!(
+                    bn254_g1_scalar_mul(encoded.clone(), scalar.clone(), ct).unwrap(),
+                    expected
+                );
+            }
+        }
+
+        let inf = vec![0; G1_BYTES];
+        let k = raw_scalars(1, 3).remove(0);
+        assert_eq!(bn254_g1_scalar_mul_glv(inf.clone(), k).unwrap(), inf);
+        assert!(bn254_g1_scalar_mul_glv(encode_g1(&g.to_affine()), vec![1; 31]).is_err());
+    }
+
+    #[test]
+    fn test_scalar_mul_config() {
+        let mut short = vec![0; 32];
+        short[16] = 0x80;
+        assert!(!ScalarMulConfig::for_scalar(&short).use_glv);
+        short[15] = 1;
+        assert!(ScalarMulConfig::for_scalar(&short).use_glv);
+        assert!(!ScalarMulConfig::for_scalar(&[0; 32]).use_glv);
+        // Both paths agree on short scalars too
+        let g = g1_generator();
+        let k = &raw_scalars(1, 11)[0][16..];
+        let mut scalar = vec![0; 16];
+        scalar.extend(k);
+        let glv = g1_mul_with(&g, &scalar, ScalarMulConfig { use_glv: true });
+        let plain = g1_mul_with(&g, &scalar, ScalarMulConfig { use_glv: false });
+        assert_eq!(glv, plain);
+    }
+
+    /// Packed little-endian encoding of arkworks G1 points
+    fn ark_g1_le(points: &[ark_bn254::G1Affine]) -> Vec<u8> {
+        use ark_ec::AffineRepr;
+        use ark_ff::{BigInteger, PrimeField};
+        points
+            .iter()
+            .flat_map(|p| match p.xy() {
+                None => vec![0; G1_BYTES],
+                Some((x, y)) => {
+                    let mut out = x.into_bigint().to_bytes_le();
+                    out.extend(y.into_bigint().to_bytes_le());
+                    out
+                }
+            })
+            .collect()
+    }
+
+    fn ark_sample_points(n: u64, seed: u64) -> Vec<ark_bn254::G1Affine> {
+        use ark_ec::{AffineRepr, CurveGroup};
+        let g = ark_bn254::G1Affine::generator();
+        (0..n)
+            .map(|i| (g * ark_bn254::Fr::from(seed * 1000 + i * i + 1)).into_affine())
+            .collect()
+    }
+
+    #[test]
+    fn test_g1_batch_ops_match_arkworks() {
+        use ark_ec::{AffineRepr, CurveGroup};
+        let a = ark_sample_points(20, 1);
+        let b = ark_sample_points(20, 2);
+        let sums: Vec<_> = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| (*a + *b).into_affine())
+            .collect();
+        let doubles: Vec<_> = a.iter().map(|a| (*a + *a).into_affine()).collect();
+        let (a_le, b_le) = (ark_g1_le(&a), ark_g1_le(&b));
+        assert_eq!(g1_add_batch(&a_le, &b_le, true).unwrap(), ark_g1_le(&sums));
+        assert_eq!(g1_double_batch(&a_le, true).unwrap(), ark_g1_le(&doubles));
+        // P + P takes the doubling branch of mixed addition
+        assert_eq!(
+            g1_add_batch(&a_le, &a_le, true).unwrap(),
+            ark_g1_le(&doubles)
+        );
+
+        // Identity on either side, P + (-P) and doubling infinity
+        let inf = ark_bn254::G1Affine::identity();
+        let p = a[3];
+        let lhs = ark_g1_le(&[inf, p, inf, p]);
+        let rhs = ark_g1_le(&[p, inf, inf, -p]);
+        assert_eq!(
+            g1_add_batch(&lhs, &rhs, true).unwrap(),
+            ark_g1_le(&[p, p, inf, inf])
+        );
+        assert_eq!(g1_double_batch(&ark_g1_le(&[inf]), true).unwrap(), [0; 64]);
+        assert!(g1_add_batch(&[], &[], true).unwrap().is_empty());
+
+        // The generator (1, 2) in little-endian form
+        let mut g = vec![0; G1_BYTES];
+        g[0] = 1;
+        g[32] = 2;
+        assert_eq!(
+            g1_double_batch(&g, true).unwrap(),
+            ark_g1_le(&[(ark_bn254::G1Affine::generator() * ark_bn254::Fr::from(2)).into_affine()])
+        );
+    }
+
+    #[test]
+    fn test_g1_batch_validation() {
+        let mut off = ark_g1_le(&ark_sample_points(2, 3));
+        off[64] ^= 1;
+        let err = g1_double_batch(&off, true).unwrap_err();
+        assert_eq!(err.reason, "points[1]: point is not on the BN254 G1 curve");
+        assert_eq!(g1_double_batch(&off, false).unwrap().len(), 2 * G1_BYTES);
+
+        let err = g1_add_batch(&off[..64], &off[64..], true).unwrap_err();
+        assert_eq!(err.reason, "b[0]: point is not on the BN254 G1 curve");
+        let mut wide = off.clone();
+        wide[32..64].fill(0xff);
+        let err = g1_double_batch(&wide, false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "points[0]: y coordinate is not below the BN254 base field modulus"
+        );
+        let err = g1_add_batch(&off, &off[..64], false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a and b must have the same number of points, got 2 and 1"
+        );
+        let err = g1_double_batch(&off[..63], false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "points: length 63 is not a multiple of 64 bytes"
+        );
+    }
+
+    #[test]
+    fn test_g1_compression() {
+        // The generator (1, 2): 2 is the smaller root, so no sign flag
+        let g = g1_generator();
+        let compressed = bn254_g1_compress(encode_g1(&g)).unwrap();
+        assert_eq!(compressed.len(), G1_COMPRESSED_BYTES);
+        assert_eq!(compressed[0], 0);
+        assert_eq!(compressed[1..], hex("1"));
+        assert_eq!(bn254_g1_decompress(compressed).unwrap(), encode_g1(&g));
+
+        // -G shares x and takes the other root
+        let neg = g.neg();
+        let compressed = bn254_g1_compress(encode_g1(&neg)).unwrap();
+        assert_eq!(compressed[0], 0x80);
+        assert_eq!(bn254_g1_decompress(compressed).unwrap(), encode_g1(&neg));
+
+        // Both roots of another point
+        let p = g
+            .to_jacobian()
+            .mul_limbs(&[0x1234_5678_9abc_def0])
+            .to_affine();
+        for q in [p, p.neg()] {
+            let compressed = bn254_g1_compress(encode_g1(&q)).unwrap();
+            assert_eq!(bn254_g1_decompress(compressed).unwrap(), encode_g1(&q));
+        }
+
+        let mut infinity = vec![0u8; G1_COMPRESSED_BYTES];
+        infinity[0] = 0x40;
+        assert_eq!(bn254_g1_compress(vec![0; G1_BYTES]).unwrap(), infinity);
+        assert_eq!(bn254_g1_decompress(infinity).unwrap(), vec![0; G1_BYTES]);
+    }
+
+    /// `2G` and `3G` for the G2 generator, from affine addition over Fq2 as
+    /// py_ecc's `bn128` does it
+    const G2_DOUBLE: &str = "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad7927dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de15204bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e";
+    const G2_TRIPLE: &str = "1014772f57bb9742735191cd5dcfe4ebbc04156b6878a0a7c9824f32ffb66e8506064e784db10e9051e52826e192715e8d7e478cb09a5e0012defa0694fbc7f5021e2335f3354bb7922ffcc2f38d3323dd9453ac49b55441452aeaca147711b2058e1d5681b5b9e0074b0f9c8d2c68a069b920d74521e79765036d57666c5597";
+
+    fn g2_hex(s: &str) -> Vec<u8> {
+        (0..4).flat_map(|i| hex(&s[64 * i..64 * (i + 1)])).collect()
+    }
+
+    #[test]
+    fn test_g2_add_and_scalar_mul() {
+        let g = bn254_g2_generator();
+        assert_eq!(g, encode_g2(&g2_generator()));
+        let double = bn254_g2_add(g.clone(), g.clone()).unwrap();
+        assert_eq!(double, g2_hex(G2_DOUBLE));
+        let triple = bn254_g2_add(g.clone(), double.clone()).unwrap();
+        assert_eq!(triple, g2_hex(G2_TRIPLE));
+        assert_eq!(bn254_g2_add(g.clone(), vec![0; G2_BYTES]).unwrap(), g);
+
+        let neg = encode_g2(&g2_generator().neg());
+        assert_eq!(bn254_g2_add(g.clone(), neg).unwrap(), vec![0; G2_BYTES]);
+
+        for ct in [Some(false), Some(true)] {
+            assert_eq!(
+                bn254_g2_scalar_mul(g.clone(), hex("3"), ct).unwrap(),
+                triple
+            );
+            // r · G is the point at infinity
+            let r = FrConfig::MODULUS
+                .iter()
+                .rev()
+                .flat_map(|l| l.to_be_bytes())
+                .collect();
+            assert_eq!(
+                bn254_g2_scalar_mul(g.clone(), r, ct).unwrap(),
+                vec![0; G2_BYTES]
+            );
+            // (r - 1) · G is -G
+            assert_eq!(
+                bn254_g2_scalar_mul(g.clone(), hex(R_MINUS_ONE), ct).unwrap(),
+                encode_g2(&g2_generator().neg())
+            );
+        }
+        let err = bn254_g2_scalar_mul(g.clone(), vec![1; 31], None).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "scalar: expected 32 big-endian bytes, got 31 bytes"
+        );
+        let err = bn254_g2_add(g, vec![0; G1_BYTES]).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "b: expected a 128-byte uncompressed G2 point, got 64 bytes"
+        );
+    }
+
+    #[test]
+    fn test_g2_compression() {
+        let g = bn254_g2_generator();
+        let compressed = bn254_g2_compress(g.clone()).unwrap();
+        assert_eq!(compressed.len(), G2_COMPRESSED_BYTES);
+        // The generator's y.c1 is the smaller of its and -y's
+        assert_eq!(compressed[0], 0);
+        assert_eq!(compressed[1..], g[..64]);
+        assert_eq!(bn254_g2_decompress(compressed).unwrap(), g);
+
+        for p in [g2_hex(G2_DOUBLE), g2_hex(G2_TRIPLE)] {
+            let neg = encode_g2(&decode_g2(&p, "p").unwrap().neg());
+            let (a, b) = (
+                bn254_g2_compress(p.clone()).unwrap(),
+                bn254_g2_compress(neg.clone()).unwrap(),
+            );
+            assert_eq!(a[0] ^ b[0], 0x80);
+            assert_eq!(bn254_g2_decompress(a).unwrap(), p);
+            assert_eq!(bn254_g2_decompress(b).unwrap(), neg);
+        }
+
+        let mut infinity = vec![0u8; G2_COMPRESSED_BYTES];
+        infinity[0] = 0x40;
+        assert_eq!(bn254_g2_compress(vec![0; G2_BYTES]).unwrap(), infinity);
+        assert_eq!(bn254_g2_decompress(infinity).unwrap(), vec![0; G2_BYTES]);
+
+        assert!(bn254_g2_decompress(vec![0; G1_COMPRESSED_BYTES]).is_err());
+        let mut bad = vec![0u8; G2_COMPRESSED_BYTES];
+        bad[0] = 0x20;
+        let err = bn254_g2_decompress(bad).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "compressed: unknown flag bits set in the first byte"
+        );
+    }
+
+    #[test]
+    fn test_g2_is_on_curve() {
+        let g = bn254_g2_generator();
+        assert!(bn254_g2_is_on_curve(g.clone()).unwrap());
+        assert!(bn254_g2_is_on_curve(vec![0; G2_BYTES]).unwrap());
+        let mut off = g.clone();
+        off[127] ^= 1;
+        assert!(!bn254_g2_is_on_curve(off.clone()).unwrap());
+        assert!(bn254_g2_add(g.clone(), off).is_err());
+        let mut wide = g;
+        wide[..32].fill(0xff);
+        assert!(!bn254_g2_is_on_curve(wide).unwrap());
+        assert!(bn254_g2_is_on_curve(vec![0; 64]).is_err());
+    }
+
+    /// Twelve pseudo-random coefficients, encoded
+    fn sample_fq12(seed: u8) -> Vec<u8> {
+        use crate::random::{random_elements, FieldName, SEED_BYTES};
+        let le = random_elements(FieldName::Bn254Fq, 12, Some(&[seed; SEED_BYTES])).unwrap();
+        le.chunks_exact(32)
+            .flat_map(|c| c.iter().rev().copied().collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn ark_fq12(bytes: &[u8]) -> ark_bn254::Fq12 {
+        use ark_ff::PrimeField;
+        let fq = |i: usize| ark_bn254::Fq::from_be_bytes_mod_order(&bytes[i * 32..(i + 1) * 32]);
+        let fq2 = |i: usize| ark_bn254::Fq2::new(fq(2 * i), fq(2 * i + 1));
+        ark_bn254::Fq12::new(
+            ark_bn254::Fq6::new(fq2(0), fq2(1), fq2(2)),
+            ark_bn254::Fq6::new(fq2(3), fq2(4), fq2(5)),
+        )
+    }
+
+    fn from_ark_fq2(c: &ark_bn254::Fq2) -> Fq2 {
+        use ark_ff::{BigInteger, PrimeField};
+        let fq = |x: &ark_bn254::Fq| Fq::from_be_bytes(&x.into_bigint().to_bytes_be()).unwrap();
+        Fq2::new(fq(&c.c0), fq(&c.c1))
+    }
+
+    #[test]
+    fn test_frobenius_coeffs_match_arkworks() {
+        use ark_ff::{Fp12Config, Fp6Config};
+        let w = from_ark_fq2(&ark_bn254::Fq12Config::FROBENIUS_COEFF_FP12_C1[1]);
+        let v = from_ark_fq2(&ark_bn254::Fq6Config::FROBENIUS_COEFF_FP6_C1[1]);
+        let v2 = from_ark_fq2(&ark_bn254::Fq6Config::FROBENIUS_COEFF_FP6_C2[1]);
+        let g = Bn254Tower::frobenius_coeffs();
+        assert_eq!(g[0], Fq2::one());
+        assert_eq!(g[1], w);
+        assert_eq!(g[2], v);
+        assert_eq!(g[3], w * v);
+        assert_eq!(g[4], v2);
+        assert_eq!(g[5], w * v2);
+
+        use ark_ff::Field as _;
+        let a = sample_fq12(1);
+        let mut ark = ark_fq12(&a);
+        let mut ours = decode_fq12(&a, "a").unwrap();
+        for _ in 0..3 {
+            ark.frobenius_map_in_place(1);
+            ours = ours.frobenius();
+            assert_eq!(encode_fq12(&ours), encode_fq12(&from_ark(&ark)));
+        }
+    }
+
+    fn from_ark(f: &ark_bn254::Fq12) -> Fq12 {
+        Fq12::new(
+            Fq6::new(
+                from_ark_fq2(&f.c0.c0),
+                from_ark_fq2(&f.c0.c1),
+                from_ark_fq2(&f.c0.c2),
+            ),
+            Fq6::new(
+                from_ark_fq2(&f.c1.c0),
+                from_ark_fq2(&f.c1.c1),
+                from_ark_fq2(&f.c1.c2),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_fq12_mul_matches_arkworks() {
+        let (a, b) = (sample_fq12(2), sample_fq12(3));
+        let product = fq12_mul(&a, &b).unwrap();
+        assert_eq!(product.len(), FQ12_BYTES);
+        assert_eq!(
+            product,
+            encode_fq12(&from_ark(&(ark_fq12(&a) * ark_fq12(&b))))
+        );
+
+        let x = decode_fq12(&a, "a").unwrap();
+        assert_eq!(x.square(), x * x);
+        assert_eq!(x * x.inverse().unwrap(), Fq12::one());
+        assert_eq!(encode_fq12(&decode_fq12(&product, "p").unwrap()), product);
+    }
+
+    #[test]
+    fn test_final_exponentiation() {
+        let (a, b) = (sample_fq12(4), sample_fq12(5));
+        let fa = fq12_final_exponentiation(&a).unwrap();
+        let fb = fq12_final_exponentiation(&b).unwrap();
+        let fab = fq12_final_exponentiation(&fq12_mul(&a, &b).unwrap()).unwrap();
+        assert_eq!(fq12_mul(&fa, &fb).unwrap(), fab);
+
+        // The result has order r, and cyclotomic squaring agrees there
+        let g = decode_fq12(&fa, "fa").unwrap();
+        assert_ne!(g, Fq12::one());
+        assert_eq!(g.pow(&FrConfig::MODULUS), Fq12::one());
+        assert_eq!(g.cyclotomic_square(), g.square());
+        assert_eq!(g.cyclotomic_pow(&[5]), g.pow(&[5]));
+
+        // Elements of the proper subfield Fq6 map to one
+        let mut sub = a.clone();
+        sub[192..].fill(0);
+        assert_eq!(
+            fq12_final_exponentiation(&sub).unwrap(),
+            encode_fq12(&Fq12::one())
+        );
+        let zero = vec![0; FQ12_BYTES];
+        assert_eq!(fq12_final_exponentiation(&zero).unwrap(), zero);
+    }
+
+    #[test]
+    fn test_final_exponentiation_of_arkworks_miller_loop() {
+        use ark_ec::pairing::Pairing;
+        use ark_ec::AffineRepr;
+        let p = ark_bn254::G1Affine::generator();
+        let q = ark_bn254::G2Affine::generator();
+        let ml = ark_bn254::Bn254::miller_loop(p, q).0;
+        let expected = ark_bn254::Bn254::pairing(p, q).0;
+        // arkworks' hard part computes the power 2x (6x^2 + 3x + 1) of the
+        // reduced pairing, for the curve parameter x
+        let multiple = [0x2e5d4e223ddedaf4, 0x1ea96b02d9d9e38d, 0x3bec47df15e307c8];
+        let ours = final_exponentiation(&from_ark(&ml)).cyclotomic_pow(&multiple);
+        assert_eq!(encode_fq12(&ours), encode_fq12(&from_ark(&expected)));
+    }
+
+    #[test]
+    fn test_pairing_is_bilinear() {
+        let pair = |a: u64, b: u64| {
+            let p = g1_generator().to_jacobian().mul_limbs(&[a]).to_affine();
+            let q = g2_generator().to_jacobian().mul_limbs(&[b]).to_affine();
+            pairing::pairing::<Bn254Pairing, 4>(&p, &q)
+        };
+        let e = pair(1, 1);
+        assert_ne!(e, Fq12::one());
+        assert_eq!(e.pow(&FrConfig::MODULUS), Fq12::one());
+        assert_eq!(pair(6, 35), e.pow(&[210]));
+        assert_eq!(pair(210, 1), pair(1, 210));
+
+        let p = g1_generator();
+        let q = g2_generator();
+        let neg_q = q.to_jacobian().neg().to_affine();
+        assert!(pairing::pairing_product_is_one::<Bn254Pairing, 4>(&[
+            (p, q),
+            (p, neg_q)
+        ]));
+        assert!(!pairing::pairing_product_is_one::<Bn254Pairing, 4>(&[
+            (p, q),
+            (p, q)
+        ]));
+    }
+
+    #[test]
+    fn test_fq12_rejects_bad_encodings() {
+        let err = fq12_mul(&[0; 383], &[0; FQ12_BYTES]).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a: expected a 384-byte Fq12 element, got 383 bytes"
+        );
+        let mut wide = sample_fq12(6);
+        wide[32..64].fill(0xff);
+        let err = fq12_final_exponentiation(&wide).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "f: coefficient 1 is not below the BN254 base field modulus"
+        );
+    }
+
+    #[test]
+    fn test_g1_decompression_rejects() {
+        // x = 0 gives y^2 = 3, a non-residue mod q
+        let err = bn254_g1_decompress(vec![0; G1_COMPRESSED_BYTES]).unwrap_err();
+        assert_eq!(err.reason, "compressed: point is not on the BN254 G1 curve");
+        assert!(bn254_g1_decompress(vec![0; 32]).is_err());
+        let mut bad = vec![0u8; G1_COMPRESSED_BYTES];
+        bad[0] = 0x40;
+        bad[32] = 1;
+        assert!(bn254_g1_decompress(bad).is_err());
+        let mut q = vec![0x20u8];
+        q.extend(hex("1"));
+        assert!(bn254_g1_decompress(q).is_err());
+        let mut x = vec![0u8];
+        x.extend(hex(
+            "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+        ));
+        let err = bn254_g1_decompress(x).unwrap_err();
+        assert!(err.reason.contains("modulus"), "{}", err.reason);
+        assert!(bn254_g1_compress(vec![1; G1_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_encodings() {
+        assert!(bn254_field_add(vec![0u8; 31], hex("1")).is_err());
+        let mut r = hex(R_MINUS_ONE);
+        r[31] += 1;
+        assert!(bn254_field_mul(r, hex("1")).is_err());
+    }
+
+    fn pack_mont(values: &[Fr]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|v| v.to_montgomery_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_fr_montgomery_round_trip() {
+        let values: Vec<Fr> = (0..2 * ELEMENTWISE_PARALLEL_THRESHOLD as u64 + 3)
+            .map(|i| Fr::from_u64(i).square() - Fr::from_u64(5))
+            .collect();
+        let canonical: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mont = fr_convert(&canonical, false, Form::Canonical, Form::Montgomery).unwrap();
+        assert_eq!(mont, pack_mont(&values));
+        assert_eq!(
+            fr_convert(&mont, false, Form::Montgomery, Form::Canonical).unwrap(),
+            canonical
+        );
+        // One in Montgomery form is R mod r
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(
+            fr_convert(&one, false, Form::Canonical, Form::Montgomery).unwrap(),
+            Fr::one().to_montgomery_le_bytes()
+        );
+
+        // Raw limbs >= r are rejected, or reduced like canonical values
+        let r_plus_5 = le_hex("060000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430");
+        let err = fr_convert(&r_plus_5, false, Form::Montgomery, Form::Canonical).unwrap_err();
+        assert!(err.reason.starts_with("values[0]:"), "{}", err.reason);
+        assert_eq!(
+            fr_convert(&r_plus_5, true, Form::Montgomery, Form::Montgomery).unwrap(),
+            pack_mont(&[Fr::from_montgomery_limbs([5, 0, 0, 0]).unwrap()])
+        );
+    }
+
+    #[test]
+    fn test_fr_montgomery_pipeline_matches_canonical() {
+        let a: Vec<Fr> = (1..200u64)
+            .map(|i| Fr::from_u64(i * 7919).inverse().unwrap())
+            .collect();
+        let b: Vec<Fr> = (1..200u64).map(|i| -Fr::from_u64(i * 104729)).collect();
+        let (ca, cb) = (
+            a.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>(),
+            b.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>(),
+        );
+        let canonical = {
+            let x = fr_mul(&ca, &cb, false, Form::Canonical).unwrap();
+            let x = fr_add(&x, &ca, false, Form::Canonical).unwrap();
+            let x = fr_sub(&x, &cb, false, Form::Canonical).unwrap();
+            fr_neg(&x, false, Form::Canonical).unwrap()
+        };
+        let (ma, mb) = (pack_mont(&a), pack_mont(&b));
+        let mont = {
+            let x = fr_mul(&ma, &mb, false, Form::Montgomery).unwrap();
+            let x = fr_add(&x, &ma, false, Form::Montgomery).unwrap();
+            let x = fr_sub(&x, &mb, false, Form::Montgomery).unwrap();
+            fr_neg(&x, false, Form::Montgomery).unwrap()
+        };
+        assert_eq!(
+            fr_convert(&mont, false, Form::Montgomery, Form::Canonical).unwrap(),
+            canonical
+        );
+    }
+
+    #[test]
+    fn test_fr_montgomery_spot_check() {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        // Small canonical integers passed as Montgomery form
+        let mut values = pack_mont(&(1..100u64).map(Fr::from_u64).collect::<Vec<_>>());
+        values[98 * 32..].fill(0);
+        values[98 * 32] = 3;
+        let err = fr_mul(&values, &values, false, Form::Montgomery).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(
+            err.reason,
+            "a[98]: looks like a canonical value, but in_montgomery_form is set"
+        );
+        // Zero is the same in both forms
+        assert!(fr_neg(&[0; 64], false, Form::Montgomery).is_ok());
+        assert!(fr_convert(&values[..32], false, Form::Montgomery, Form::Canonical).is_ok());
+    }
+
+    /// Packed canonical little-endian elements from an xorshift stream, reduced mod r
+    fn random_fr(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        let mut out = Vec::with_capacity(len * FR_BYTES);
+        for _ in 0..len {
+            let mut bytes = [0u8; FR_BYTES];
+            for chunk in bytes.chunks_mut(8) {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                chunk.copy_from_slice(&x.to_le_bytes());
+            }
+            out.extend(Fr::from_le_bytes_reduced(&bytes).unwrap().to_le_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_fr_barrett_matches_montgomery() {
+        for (n, seed) in [(1, 3), (97, 5), (ELEMENTWISE_PARALLEL_THRESHOLD + 3, 7)] {
+            let a = random_fr(n, seed);
+            let b = random_fr(n, seed + 1);
+            assert_eq!(
+                fr_mul_barrett(&a, &b, false).unwrap(),
+                fr_mul(&a, &b, false, Form::Canonical).unwrap()
+            );
+            assert_eq!(
+                fr_add_barrett(&a, &b, false).unwrap(),
+                fr_add(&a, &b, false, Form::Canonical).unwrap()
+            );
+        }
+        // Unreduced input and the edges of the range
+        let p_minus_one = (-Fr::one()).to_le_bytes();
+        let a = [
+            vec![0xff; 32],
+            p_minus_one.clone(),
+            p_minus_one,
+            vec![0; 32],
+        ]
+        .concat();
+        let b = [random_fr(2, 11), vec![0xff; 64]].concat();
+        assert_eq!(
+            fr_mul_barrett(&a, &b, true).unwrap(),
+            fr_mul(&a, &b, true, Form::Canonical).unwrap()
+        );
+        assert_eq!(
+            fr_add_barrett(&a, &b, true).unwrap(),
+            fr_add(&a, &b, true, Form::Canonical).unwrap()
+        );
+        let err = fr_mul_barrett(&a, &b, false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            format!("a[0]: value is not below the {FIELD} modulus")
+        );
+        assert!(fr_add_barrett(&a[..64], &b, false).is_err());
+    }
+
+    #[test]
+    fn test_reduction_option() {
+        use crate::field::Reduction;
+
+        assert_eq!(
+            Reduction::parse(None, Form::Montgomery).unwrap(),
+            Reduction::Montgomery
+        );
+        assert_eq!(
+            Reduction::parse(Some("barrett"), Form::Canonical).unwrap(),
+            Reduction::Barrett
+        );
+        let err = Reduction::parse(Some("barrett"), Form::Montgomery).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("in_montgomery_form"), "{}", err.reason);
+        let err = Reduction::parse(Some("Barrett"), Form::Canonical).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "reduction: expected \"montgomery\" or \"barrett\", got \"Barrett\""
+        );
+    }
+
+    #[test]
+    fn test_fr_inv_batch_random() {
+        // x * inv(x) == 1 across batches large enough to split across workers
+        for (len, seed) in [(1, 1), (5, 2), (1000, 3), (5000, 4)] {
+            let values = random_fr(len, seed);
+            let inverses = fr_inv_batch(&values, false, false, Form::Canonical).unwrap();
+            let products = fr_mul(&values, &inverses, false, Form::Canonical).unwrap();
+            assert!(products
+                .chunks_exact(FR_BYTES)
+                .all(|p| Fr::from_le_bytes(p) == Some(Fr::one())));
+
+            let mont = fr_convert(&values, false, Form::Canonical, Form::Montgomery).unwrap();
+            let mont_inverses = fr_inv_batch(&mont, false, false, Form::Montgomery).unwrap();
+            assert_eq!(
+                fr_convert(&mont_inverses, false, Form::Montgomery, Form::Canonical).unwrap(),
+                inverses
+            );
+        }
+        assert!(fr_inv_batch(&[], false, false, Form::Canonical)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fr_inv_batch_zeros() {
+        let mut values = random_fr(10, 5);
+        values[7 * FR_BYTES..8 * FR_BYTES].fill(0);
+        let err = fr_inv_batch(&values, false, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(err.reason, "values[7]: zero has no inverse");
+
+        let inverses = fr_inv_batch(&values, true, false, Form::Canonical).unwrap();
+        assert_eq!(inverses[7 * FR_BYTES..8 * FR_BYTES], [0u8; FR_BYTES]);
+        let expected = Fr::from_le_bytes(&values[..FR_BYTES])
+            .unwrap()
+            .inverse()
+            .unwrap();
+        assert_eq!(inverses[..FR_BYTES], expected.to_le_bytes());
+
+        let err = fr_inv_batch(&[0xff; 64], true, false, Form::Canonical).unwrap_err();
+        assert!(err.reason.starts_with("values[0]:"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fr_sqrt_batch() {
+        assert_eq!(FrConfig::TWO_ADICITY, crate::ntt::TWO_ADICITY);
+        // Squares of random scalars come back as the smaller root
+        let values = random_fr(3000, 6);
+        let squares = fr_mul(&values, &values, false, Form::Canonical).unwrap();
+        let (roots, flags) = fr_sqrt_batch(&squares).unwrap();
+        assert!(flags.iter().all(|&f| f == 1));
+        assert_eq!(
+            fr_mul(&roots, &roots, false, Form::Canonical).unwrap(),
+            squares
+        );
+        for (x, root) in values
+            .chunks_exact(FR_BYTES)
+            .zip(roots.chunks_exact(FR_BYTES))
+        {
+            let x = Fr::from_le_bytes(x).unwrap();
+            let root = Fr::from_le_bytes(root).unwrap();
+            assert!(root == x || root == -x);
+            assert!(!geq(&root.to_canonical(), &(-root).to_canonical()) || root.is_zero());
+        }
+
+        // 5 and 7 are non-residues, 0 maps to 0 and 4 to 2
+        let small = [0u64, 4, 5, 7, 9]
+            .iter()
+            .flat_map(|&v| Fr::from_u64(v).to_le_bytes())
+            .collect::<Vec<u8>>();
+        let (roots, flags) = fr_sqrt_batch(&small).unwrap();
+        assert_eq!(flags, [1, 1, 0, 0, 1]);
+        let expected = [0u64, 2, 0, 0, 3]
+            .iter()
+            .flat_map(|&v| Fr::from_u64(v).to_le_bytes())
+            .collect::<Vec<u8>>();
+        assert_eq!(roots, expected);
+
+        let err = fr_sqrt_batch(&[0xff; 64]).unwrap_err();
+        assert!(err.reason.starts_with("values[0]:"), "{}", err.reason);
+        let err = fr_sqrt_batch(&[0; 33]).unwrap_err();
+        assert!(err.reason.contains("multiple of 32"), "{}", err.reason);
+    }
+
+    #[test]
+    fn test_fr_pow_batch() {
+        // r - 2 as 32 big-endian bytes, and a short odd exponent
+        let mut r_minus_two = [0u8; 32];
+        let mut limbs = FrConfig::MODULUS;
+        limbs[0] -= 2;
+        for (chunk, limb) in r_minus_two.rchunks_mut(8).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        let short = [0x01, 0x00, 0x03];
+        let values = random_fr(2000, 7);
+        for exponent in [&r_minus_two[..], &short[..]] {
+            let limbs: Vec<u64> = exponent
+                .rchunks(8)
+                .map(|c| c.iter().fold(0u64, |acc, &b| acc << 8 | b as u64))
+                .collect();
+            let expected: Vec<u8> = values
+                .chunks_exact(FR_BYTES)
+                .flat_map(|x| Fr::from_le_bytes(x).unwrap().pow(&limbs).to_le_bytes())
+                .collect();
+            for constant_time in [false, true] {
+                assert_eq!(
+                    fr_pow_batch(&values, exponent, constant_time, Form::Canonical).unwrap(),
+                    expected
+                );
+            }
+            let mont = fr_convert(&values, false, Form::Canonical, Form::Montgomery).unwrap();
+            let powers = fr_pow_batch(&mont, exponent, true, Form::Montgomery).unwrap();
+            assert_eq!(
+                fr_convert(&powers, false, Form::Montgomery, Form::Canonical).unwrap(),
+                expected
+            );
+        }
+
+        // Zero or empty exponent gives ones, even for a zero base
+        let ones: Vec<u8> = (0..3).flat_map(|_| Fr::one().to_le_bytes()).collect();
+        let mut values = random_fr(3, 8);
+        values[..FR_BYTES].fill(0);
+        for exponent in [&[][..], &[0; 40][..]] {
+            for constant_time in [false, true] {
+                assert_eq!(
+                    fr_pow_batch(&values, exponent, constant_time, Form::Canonical).unwrap(),
+                    ones
+                );
+            }
+        }
+        assert!(fr_pow_batch(&[], &r_minus_two, false, Form::Canonical)
+            .unwrap()
+            .is_empty());
+
+        let err = fr_pow_batch(&[0; 33], &short, false, Form::Canonical).unwrap_err();
+        assert!(err.reason.contains("multiple of 32"), "{}", err.reason);
+        let err = fr_pow_batch(&[0xff; 32], &short, true, Form::Canonical).unwrap_err();
+        assert!(err.reason.starts_with("bases[0]:"), "{}", err.reason);
+    }
+
+    /// `cargo test --release bench_fr_montgomery_pipeline -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_fr_montgomery_pipeline() {
+        use std::time::Instant;
+
+        const STEPS: usize = 8;
+        let n = 1u64 << 20;
+        let a: Vec<u8> = (0..n)
+            .flat_map(|i| (Fr::from_u64(i) * Fr::from_u64(0x9e3779b97f4a7c15)).to_le_bytes())
+            .collect();
+        let b: Vec<u8> = (0..n)
+            .flat_map(|i| (-Fr::from_u64(i + 1)).to_le_bytes())
+            .collect();
+        let run = |form: Form, a: &[u8], b: &[u8]| {
+            let mut x = a.to_vec();
+            for step in 0..STEPS {
+                x = if step % 2 == 0 {
+                    fr_mul(&x, b, false, form).unwrap()
+                } else {
+                    fr_add(&x, a, false, form).unwrap()
+                };
+            }
+            x
+        };
+
+        let start = Instant::now();
+        let canonical = run(Form::Canonical, &a, &b);
+        let canonical_time = start.elapsed();
+
+        let start = Instant::now();
+        let to_mont = |v: &[u8]| fr_convert(v, false, Form::Canonical, Form::Montgomery).unwrap();
+        let x = run(Form::Montgomery, &to_mont(&a), &to_mont(&b));
+        let mont = fr_convert(&x, false, Form::Montgomery, Form::Canonical).unwrap();
+        let mont_time = start.elapsed();
+
+        assert_eq!(mont, canonical);
+        println!(
+            "2^20 elements, {STEPS} steps: canonical {canonical_time:?}, \
+             Montgomery-resident {mont_time:?} ({:.2}x)",
+            canonical_time.as_secs_f64() / mont_time.as_secs_f64()
+        );
+    }
+
+    /// `cargo test --release bench_fr_barrett_crossover -- --ignored --nocapture`
+    ///
+    /// Per-call Montgomery vs Barrett `fr_mul` on canonical input; Barrett
+    /// pays for `μ` once per call, Montgomery for two conversions per element.
+    #[test]
+    #[ignore]
+    fn bench_fr_barrett_crossover() {
+        use std::time::Instant;
+
+        // Roughly the same number of elements per measurement at every size
+        const TOTAL: usize = 1 << 22;
+        let mut crossover = None;
+        for log_n in (0..=20).step_by(2) {
+            let n = 1usize << log_n;
+            let a = random_fr(n, 13);
+            let b = random_fr(n, 17);
+            let reps = (TOTAL / n).max(1);
+            let time = |f: &dyn Fn() -> Vec<u8>| {
+                let start = Instant::now();
+                for _ in 0..reps {
+                    std::hint::black_box(f());
+                }
+                start.elapsed() / reps as u32
+            };
+            let mont = time(&|| fr_mul(&a, &b, false, Form::Canonical).unwrap());
+            let barrett = time(&|| fr_mul_barrett(&a, &b, false).unwrap());
+            if barrett < mont && crossover.is_none() {
+                crossover = Some(n);
+            }
+            println!(
+                "2^{log_n:<2} elements: Montgomery {mont:?}, Barrett {barrett:?} ({:.2}x)",
+                mont.as_secs_f64() / barrett.as_secs_f64()
+            );
+        }
+        match crossover {
+            Some(n) => println!("Barrett is faster from {n} elements"),
+            None => println!("Barrett is not faster at any size"),
+        }
+    }
+}
@@ -0,0 +1,497 @@
+//! Bulletproofs vector commitments and inner product arguments on BN254 G1
+//!
+//! [`bulletproofs_commit`] computes `<a, G> + <b, H> + r·Q` as one
+//! multi-scalar multiplication. [`inner_product_proof`] proves knowledge of
+//! `a` and `b` with `P = <a, G> + <b, H> + <a, b>·U` in `log2 n` rounds of
+//! the halving argument of Bünz et al. (section 3), in the form of
+//! dalek-cryptography's `InnerProductProof`: each round sends
+//!
+//! ```text
+//! L = <a_lo, G_hi> + <b_hi, H_lo> + <a_lo, b_hi>·U
+//! R = <a_hi, G_lo> + <b_lo, H_hi> + <a_hi, b_lo>·U
+//! ```
+//!
+//! draws `x` from a [`Transcript`] over both, and folds
+//! `a = x·a_lo + x⁻¹·a_hi`, `b = x⁻¹·b_lo + x·b_hi`, `G = x⁻¹·G_lo + x·G_hi`
+//! and `H = x·H_lo + x⁻¹·H_hi`. [`inner_product_verify`] folds the
+//! generators implicitly and checks everything with one MSM.
+//!
+//! Points are 64-byte uncompressed BN254 G1 points and scalars 32-byte
+//! big-endian canonical elements, as in [`crate::transcript`].
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{decode_g1, encode_g1, parse_fr, Fr, G1Affine, G1Projective};
+use crate::msm::pippenger;
+use crate::parallel::par_map;
+use crate::transcript::Transcript;
+
+const TRANSCRIPT_LABEL: &str = "zk-accelerate inner product";
+
+/// A proof from [`inner_product_proof`]
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IppProof {
+    /// `L` of each round, 64-byte G1 points
+    pub l_vec: Vec<Vec<u8>>,
+    /// `R` of each round, 64-byte G1 points
+    pub r_vec: Vec<Vec<u8>>,
+    /// The folded `a`, a 32-byte big-endian scalar
+    pub a_final: Vec<u8>,
+    /// The folded `b`, a 32-byte big-endian scalar
+    pub b_final: Vec<u8>,
+}
+
+fn invalid(reason: String) -> Error {
+    Error::new(Status::InvalidArg, reason)
+}
+
+fn parse_scalars(values: &[Vec<u8>], name: &str) -> Result<Vec<Fr>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| parse_fr(v, &format!("{name}[{i}]")))
+        .collect()
+}
+
+fn parse_points(points: &[Vec<u8>], name: &str) -> Result<Vec<G1Affine>> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| decode_g1(p, &format!("{name}[{i}]")))
+        .collect()
+}
+
+/// Check that every vector has the length of the first
+fn check_lengths(vectors: &[(&str, usize)]) -> Result<usize> {
+    let (first, n) = vectors[0];
+    for &(name, len) in &vectors[1..] {
+        if len != n {
+            return Err(invalid(format!(
+                "{name}: expected {n} elements to match {first}, got {len}"
+            )));
+        }
+    }
+    Ok(n)
+}
+
+fn check_power_of_two(n: usize) -> Result<()> {
+    if !n.is_power_of_two() {
+        return Err(invalid(format!(
+            "generators_g: expected a power-of-two length, got {n}"
+        )));
+    }
+    Ok(())
+}
+
+/// `Σ scalars[i]·points[i]`
+fn msm(points: &[G1Affine], scalars: &[Fr]) -> G1Projective {
+    let limbs: Vec<[u64; 4]> = scalars.iter().map(|s| s.to_canonical()).collect();
+    pippenger(points, &limbs, true)
+}
+
+fn inner(a: &[Fr], b: &[Fr]) -> Fr {
+    a.iter()
+        .zip(b)
+        .fold(Fr::zero(), |acc, (&x, &y)| acc + x * y)
+}
+
+/// `lo·x_lo + hi·x_hi` for every pair of generators
+fn fold_points(lo: &[G1Affine], hi: &[G1Affine], x_lo: Fr, x_hi: Fr) -> Vec<G1Affine> {
+    let pairs: Vec<(&G1Affine, &G1Affine)> = lo.iter().zip(hi).collect();
+    let (x_lo, x_hi) = (x_lo.to_canonical(), x_hi.to_canonical());
+    let folded = par_map(&pairs, |(l, h)| {
+        l.to_jacobian()
+            .mul_limbs(&x_lo)
+            .add(&h.to_jacobian().mul_limbs(&x_hi))
+    });
+    G1Projective::batch_normalize(&folded)
+}
+
+fn fold_scalars(lo: &[Fr], hi: &[Fr], x_lo: Fr, x_hi: Fr) -> Vec<Fr> {
+    lo.iter()
+        .zip(hi)
+        .map(|(&l, &h)| l * x_lo + h * x_hi)
+        .collect()
+}
+
+fn start_transcript(n: usize) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL.to_string());
+    transcript.append_message("n".to_string(), (n as u64).to_be_bytes().to_vec());
+    transcript
+}
+
+/// Absorb a round's `L` and `R` and draw its challenge and inverse
+fn round_challenge(transcript: &mut Transcript, l: &[u8], r: &[u8]) -> (Fr, Fr) {
+    transcript.append_message("L".to_string(), l.to_vec());
+    transcript.append_message("R".to_string(), r.to_vec());
+    loop {
+        // A zero challenge has no inverse and is redrawn
+        let x = transcript.challenge_fr("x");
+        if let Some(x_inv) = x.inverse() {
+            return (x, x_inv);
+        }
+    }
+}
+
+/// `<a, G> + <b, H> + r·Q`
+pub fn commit(
+    a: &[Fr],
+    b: &[Fr],
+    r: Fr,
+    g: &[G1Affine],
+    h: &[G1Affine],
+    q: G1Affine,
+) -> G1Projective {
+    let points: Vec<G1Affine> = g.iter().chain(h).copied().chain([q]).collect();
+    let scalars: Vec<Fr> = a.iter().chain(b).copied().chain([r]).collect();
+    msm(&points, &scalars)
+}
+
+/// Inner product argument for `a` and `b` under the given generators
+///
+/// The vectors must share a power-of-two length.
+pub fn prove(
+    mut a: Vec<Fr>,
+    mut b: Vec<Fr>,
+    mut g: Vec<G1Affine>,
+    mut h: Vec<G1Affine>,
+    u: G1Affine,
+) -> (Vec<G1Affine>, Vec<G1Affine>, Fr, Fr) {
+    let mut transcript = start_transcript(a.len());
+    let (mut l_vec, mut r_vec) = (Vec::new(), Vec::new());
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_lo, a_hi) = a.split_at(half);
+        let (b_lo, b_hi) = b.split_at(half);
+        let (g_lo, g_hi) = g.split_at(half);
+        let (h_lo, h_hi) = h.split_at(half);
+        let c_l = inner(a_lo, b_hi);
+        let c_r = inner(a_hi, b_lo);
+        let l = commit(a_lo, b_hi, c_l, g_hi, h_lo, u).to_affine();
+        let r = commit(a_hi, b_lo, c_r, g_lo, h_hi, u).to_affine();
+        let (x, x_inv) = round_challenge(&mut transcript, &encode_g1(&l), &encode_g1(&r));
+        l_vec.push(l);
+        r_vec.push(r);
+
+        a = fold_scalars(a_lo, a_hi, x, x_inv);
+        b = fold_scalars(b_lo, b_hi, x_inv, x);
+        g = fold_points(g_lo, g_hi, x_inv, x);
+        h = fold_points(h_lo, h_hi, x, x_inv);
+    }
+    (l_vec, r_vec, a[0], b[0])
+}
+
+/// Check an inner product argument against `P = <a, G> + <b, H> + <a, b>·U`
+#[allow(clippy::too_many_arguments)]
+pub fn verify(
+    p: G1Affine,
+    l_vec: &[G1Affine],
+    r_vec: &[G1Affine],
+    a_final: Fr,
+    b_final: Fr,
+    g: &[G1Affine],
+    h: &[G1Affine],
+    u: G1Affine,
+) -> bool {
+    let n = g.len();
+    if l_vec.len() != r_vec.len() || 1 << l_vec.len() != n {
+        return false;
+    }
+    let mut transcript = start_transcript(n);
+    let challenges: Vec<(Fr, Fr)> = l_vec
+        .iter()
+        .zip(r_vec)
+        .map(|(l, r)| round_challenge(&mut transcript, &encode_g1(l), &encode_g1(r)))
+        .collect();
+
+    // The folded G is Σ s_i·G_i with s_i the product of x_j for each set
+    // bit j of i, counting round 0 as the top bit, and x_j⁻¹ for each clear
+    // one; the folded H has 1 / s_i
+    let mut s = vec![Fr::one(); n];
+    for (j, &(x, x_inv)) in challenges.iter().enumerate() {
+        let bit = n >> (j + 1);
+        for (i, s_i) in s.iter_mut().enumerate() {
+            *s_i *= if i & bit != 0 { x } else { x_inv };
+        }
+    }
+    let mut s_inv = s.clone();
+    crate::field::batch_invert(&mut s_inv);
+
+    // Σ x_j²·L_j + Σ x_j⁻²·R_j + P - a·<s, G> - b·<s⁻¹, H> - a·b·U = 0
+    let mut points: Vec<G1Affine> = l_vec.iter().chain(r_vec).copied().collect();
+    let mut scalars: Vec<Fr> = challenges.iter().map(|(x, _)| x.square()).collect();
+    scalars.extend(challenges.iter().map(|(_, x_inv)| x_inv.square()));
+    points.extend([p, u]);
+    scalars.extend([Fr::one(), -(a_final * b_final)]);
+    points.extend(g.iter().chain(h));
+    scalars.extend(s.iter().map(|&s_i| -(a_final * s_i)));
+    scalars.extend(s_inv.iter().map(|&s_i| -(b_final * s_i)));
+    msm(&points, &scalars).is_identity()
+}
+
+/// Vector Pedersen commitment `<a, G> + <b, H> + r·Q` on BN254 G1
+///
+/// `a`, `b`, `generators_g` and `generators_h` must have the same length.
+/// Runs as a single `2n + 1`-point Pippenger MSM. With `r = <a, b>` and
+/// `q = u` this is the `P` that [`inner_product_verify`] takes.
+#[napi]
+pub fn bulletproofs_commit(
+    a: Vec<Vec<u8>>,
+    b: Vec<Vec<u8>>,
+    r: Vec<u8>,
+    generators_g: Vec<Vec<u8>>,
+    generators_h: Vec<Vec<u8>>,
+    q: Vec<u8>,
+) -> Result<Vec<u8>> {
+    crate::constant_time::require_variable_time("bulletproofs_commit")?;
+    check_lengths(&[
+        ("a", a.len()),
+        ("b", b.len()),
+        ("generators_g", generators_g.len()),
+        ("generators_h", generators_h.len()),
+    ])?;
+    let commitment = commit(
+        &parse_scalars(&a, "a")?,
+        &parse_scalars(&b, "b")?,
+        parse_fr(&r, "r")?,
+        &parse_points(&generators_g, "generators_g")?,
+        &parse_points(&generators_h, "generators_h")?,
+        decode_g1(&q, "q")?,
+    );
+    Ok(encode_g1(&commitment.to_affine()))
+}
+
+/// Prove `P = <a, G> + <b, H> + <a, b>·U` for the given vectors
+///
+/// `a`, `b` and both generator vectors must share a power-of-two length
+/// `n`; the proof has `log2 n` rounds. The proof is not zero knowledge on
+/// its own: `a_final` and `b_final` are revealed, as in Bulletproofs, where
+/// the vectors are already blinded.
+#[napi]
+pub fn inner_product_proof(
+    a: Vec<Vec<u8>>,
+    b: Vec<Vec<u8>>,
+    generators_g: Vec<Vec<u8>>,
+    generators_h: Vec<Vec<u8>>,
+    u: Vec<u8>,
+) -> Result<IppProof> {
+    crate::constant_time::require_variable_time("inner_product_proof")?;
+    let n = check_lengths(&[
+        ("generators_g", generators_g.len()),
+        ("generators_h", generators_h.len()),
+        ("a", a.len()),
+        ("b", b.len()),
+    ])?;
+    check_power_of_two(n)?;
+    let (l_vec, r_vec, a_final, b_final) = prove(
+        parse_scalars(&a, "a")?,
+        parse_scalars(&b, "b")?,
+        parse_points(&generators_g, "generators_g")?,
+        parse_points(&generators_h, "generators_h")?,
+        decode_g1(&u, "u")?,
+    );
+    Ok(IppProof {
+        l_vec: l_vec.iter().map(encode_g1).collect(),
+        r_vec: r_vec.iter().map(encode_g1).collect(),
+        a_final: a_final.to_be_bytes(),
+        b_final: b_final.to_be_bytes(),
+    })
+}
+
+/// Verify an [`inner_product_proof`] against the commitment
+/// `P = <a, G> + <b, H> + <a, b>·U`
+///
+/// Malformed points and scalars throw; a proof with the wrong number of
+/// rounds for the generators is rejected with `false`.
+#[napi]
+pub fn inner_product_verify(
+    commitment: Vec<u8>,
+    proof: IppProof,
+    generators_g: Vec<Vec<u8>>,
+    generators_h: Vec<Vec<u8>>,
+    u: Vec<u8>,
+) -> Result<bool> {
+    let n = check_lengths(&[
+        ("generators_g", generators_g.len()),
+        ("generators_h", generators_h.len()),
+    ])?;
+    check_power_of_two(n)?;
+    Ok(verify(
+        decode_g1(&commitment, "commitment")?,
+        &parse_points(&proof.l_vec, "proof.l_vec")?,
+        &parse_points(&proof.r_vec, "proof.r_vec")?,
+        parse_fr(&proof.a_final, "proof.a_final")?,
+        parse_fr(&proof.b_final, "proof.b_final")?,
+        &parse_points(&generators_g, "generators_g")?,
+        &parse_points(&generators_h, "generators_h")?,
+        decode_g1(&u, "u")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::g1_generator;
+
+    fn fr(v: u64) -> Vec<u8> {
+        Fr::from_u64(v).to_be_bytes()
+    }
+
+    /// `n` generators as distinct multiples of the BN254 generator
+    fn generators(n: usize, seed: u64) -> Vec<Vec<u8>> {
+        let g = g1_generator().to_jacobian();
+        (0..n as u64)
+            .map(|i| encode_g1(&g.mul_limbs(&[seed * 1_000_003 + i * 7919 + 1]).to_affine()))
+            .collect()
+    }
+
+    /// Vectors, generators and `P` of an inner product statement
+    struct Statement {
+        a: Vec<Vec<u8>>,
+        b: Vec<Vec<u8>>,
+        g: Vec<Vec<u8>>,
+        h: Vec<Vec<u8>>,
+        u: Vec<u8>,
+        p: Vec<u8>,
+    }
+
+    fn statement(n: usize) -> Statement {
+        let a: Vec<Vec<u8>> = (0..n as u64).map(|i| fr(i * i + 3)).collect();
+        let b: Vec<Vec<u8>> = (0..n as u64)
+            .map(|i| (-Fr::from_u64(2 * i + 1)).to_be_bytes())
+            .collect();
+        let (g, h) = (generators(n, 1), generators(n, 2));
+        let u = generators(1, 3).remove(0);
+        let c = inner(
+            &parse_scalars(&a, "a").unwrap(),
+            &parse_scalars(&b, "b").unwrap(),
+        );
+        let p = bulletproofs_commit(
+            a.clone(),
+            b.clone(),
+            c.to_be_bytes(),
+            g.clone(),
+            h.clone(),
+            u.clone(),
+        )
+        .unwrap();
+        Statement { a, b, g, h, u, p }
+    }
+
+    #[test]
+    fn test_commit() {
+        let g = g1_generator().to_jacobian();
+        let point = |k: u64| encode_g1(&g.mul_limbs(&[k]).to_affine());
+        // 2·G1 + 3·G2 + 5·H1 + 7·H2 + 11·Q with G1 = G, G2 = 2G, H1 = 3G,
+        // H2 = 4G and Q = 5G is (2 + 6 + 15 + 28 + 55)·G
+        let commitment = bulletproofs_commit(
+            vec![fr(2), fr(3)],
+            vec![fr(5), fr(7)],
+            fr(11),
+            vec![point(1), point(2)],
+            vec![point(3), point(4)],
+            point(5),
+        )
+        .unwrap();
+        assert_eq!(commitment, point(106));
+        assert_eq!(
+            bulletproofs_commit(vec![], vec![], fr(0), vec![], vec![], point(1)).unwrap(),
+            vec![0; 64]
+        );
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        for n in [1, 2, 8, 32] {
+            let Statement { a, b, g, h, u, p } = statement(n);
+            let proof =
+                inner_product_proof(a.clone(), b.clone(), g.clone(), h.clone(), u.clone()).unwrap();
+            assert_eq!(proof.l_vec.len(), n.trailing_zeros() as usize);
+            assert!(
+                inner_product_verify(p.clone(), proof.clone(), g.clone(), h.clone(), u.clone())
+                    .unwrap(),
+                "n = {n}"
+            );
+            if n == 1 {
+                assert_eq!((proof.a_final, proof.b_final), (a[0].clone(), b[0].clone()));
+                continue;
+            }
+
+            // A different commitment, challenge input or final scalar fails
+            let other = statement(n / 2).p;
+            assert!(
+                !inner_product_verify(other, proof.clone(), g.clone(), h.clone(), u.clone())
+                    .unwrap()
+            );
+            let mut swapped = proof.clone();
+            let last = swapped.l_vec.len() - 1;
+            swapped.l_vec.swap(0, last);
+            std::mem::swap(&mut swapped.l_vec[0], &mut swapped.r_vec[0]);
+            assert!(
+                !inner_product_verify(p.clone(), swapped, g.clone(), h.clone(), u.clone()).unwrap()
+            );
+            let mut tampered = proof.clone();
+            tampered.a_final =
+                (parse_fr(&tampered.a_final, "a").unwrap() + Fr::one()).to_be_bytes();
+            assert!(
+                !inner_product_verify(p.clone(), tampered, g.clone(), h.clone(), u.clone())
+                    .unwrap()
+            );
+            let mut short = proof.clone();
+            short.l_vec.pop();
+            short.r_vec.pop();
+            assert!(
+                !inner_product_verify(p.clone(), short, g.clone(), h.clone(), u.clone()).unwrap()
+            );
+            // Generators in a different order commit to something else
+            let mut g_rev = g.clone();
+            g_rev.reverse();
+            assert!(!inner_product_verify(p, proof, g_rev, h, u).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let Statement { a, b, g, h, u, .. } = statement(4);
+        let err = inner_product_proof(
+            a[..3].to_vec(),
+            b[..3].to_vec(),
+            g[..3].to_vec(),
+            h[..3].to_vec(),
+            u.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.reason,
+            "generators_g: expected a power-of-two length, got 3"
+        );
+        let err = inner_product_proof(a.clone(), b[..2].to_vec(), g.clone(), h.clone(), u.clone())
+            .unwrap_err();
+        assert_eq!(
+            err.reason,
+            "b: expected 4 elements to match generators_g, got 2"
+        );
+        let err = bulletproofs_commit(
+            a.clone(),
+            b.clone(),
+            vec![0xff; 32],
+            g.clone(),
+            h.clone(),
+            u.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.reason,
+            "r: value is not below the BN254 scalar field modulus"
+        );
+        let mut off_curve = h.clone();
+        off_curve[2][63] ^= 1;
+        let err = inner_product_proof(a, b, g, off_curve, u).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "generators_h[2]: point is not on the BN254 G1 curve"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! LRU cache of proofs keyed by witness hash
+//!
+//! Applications that prove the same witness repeatedly can look the proof
+//! up by [`hash_witness`] instead of re-proving. The cache is a hash map
+//! plus a recency index ordered by a use counter, behind one mutex, so a
+//! single `ProofCache` can be shared by JS worker threads.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use napi::Result;
+use napi_derive::napi;
+
+use crate::bn254::{parse_fr, Fr};
+
+/// Witness elements absorbed per Poseidon call, besides the running digest
+const WITNESS_CHUNK: usize = 6;
+
+/// Poseidon digest of a list of 32-byte big-endian BN254 scalars
+///
+/// The digest starts as `Poseidon(n)` for `n` elements and absorbs them six
+/// at a time as `Poseidon(digest, chunk...)` with circomlib's parameters.
+pub fn witness_digest(witness: &[Fr]) -> Result<Fr> {
+    let mut acc = crate::poseidon::hash(&[Fr::from_u64(witness.len() as u64)])?;
+    for chunk in witness.chunks(WITNESS_CHUNK) {
+        let mut inputs = Vec::with_capacity(chunk.len() + 1);
+        inputs.push(acc);
+        inputs.extend_from_slice(chunk);
+        acc = crate::poseidon::hash(&inputs)?;
+    }
+    Ok(acc)
+}
+
+/// 32-byte big-endian Poseidon hash of a witness, the key for [`ProofCache`]
+///
+/// Each element is a 32-byte big-endian BN254 scalar field element.
+#[napi]
+pub fn hash_witness(witness: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let values = witness
+        .iter()
+        .enumerate()
+        .map(|(i, bytes)| parse_fr(bytes, &format!("witness[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(witness_digest(&values)?.to_be_bytes())
+}
+
+/// Counters of a [`ProofCache`]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found a proof
+    pub hits: u64,
+    /// Lookups that found nothing
+    pub misses: u64,
+    /// Proofs dropped to make room for newer ones
+    pub evictions: u64,
+}
+
+#[derive(Default)]
+struct Lru {
+    /// Proof and last-use tick for each witness hash
+    entries: HashMap<Vec<u8>, (Vec<u8>, u64)>,
+    /// Witness hashes by last-use tick, least recent first
+    order: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl Lru {
+    /// Mark `key` as most recently used
+    fn touch(&mut self, key: &[u8]) -> Option<&mut Vec<u8>> {
+        self.tick += 1;
+        let (proof, used) = self.entries.get_mut(key)?;
+        let key = self.order.remove(used).expect("indexed entry");
+        *used = self.tick;
+        self.order.insert(self.tick, key);
+        Some(proof)
+    }
+}
+
+/// Thread-safe LRU cache from witness hashes to proofs
+#[napi]
+pub struct ProofCache {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[napi]
+impl ProofCache {
+    /// Cache holding at most `capacity` proofs; zero disables caching
+    #[napi(constructor)]
+    pub fn new(capacity: u32) -> Self {
+        ProofCache {
+            capacity: capacity as usize,
+            inner: Mutex::new(Lru::default()),
+        }
+    }
+
+    /// The proof cached for `witness_hash`, marking it most recently used
+    #[napi]
+    pub fn get(&self, witness_hash: Vec<u8>) -> Option<Vec<u8>> {
+        let mut lru = self.inner.lock().unwrap();
+        let proof = lru.touch(&witness_hash).cloned();
+        match proof {
+            Some(_) => lru.stats.hits += 1,
+            None => lru.stats.misses += 1,
+        }
+        proof
+    }
+
+    /// Cache `proof` for `witness_hash`, evicting the least recently used
+    /// proof when full
+    ///
+    /// Storing under a hash that is already cached replaces its proof.
+    #[napi]
+    pub fn put(&self, witness_hash: Vec<u8>, proof: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut lru = self.inner.lock().unwrap();
+        if let Some(cached) = lru.touch(&witness_hash) {
+            *cached = proof;
+            return;
+        }
+        if lru.entries.len() == self.capacity {
+            let (_, oldest) = lru.order.pop_first().expect("full cache is non-empty");
+            lru.entries.remove(&oldest);
+            lru.stats.evictions += 1;
+        }
+        let tick = lru.tick;
+        lru.order.insert(tick, witness_hash.clone());
+        lru.entries.insert(witness_hash, (proof, tick));
+    }
+
+    /// Hit, miss and eviction counts since the cache was created
+    #[napi]
+    pub fn stats(&self) -> CacheStats {
+        self.inner.lock().unwrap().stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn key(i: u64) -> Vec<u8> {
+        hash_witness(vec![Fr::from_u64(i).to_be_bytes()]).unwrap()
+    }
+
+    #[test]
+    fn test_hash_witness() {
+        let witness: Vec<Vec<u8>> = (0..13).map(|i| Fr::from_u64(i).to_be_bytes()).collect();
+        let digest = hash_witness(witness.clone()).unwrap();
+        assert_eq!(digest.len(), 32);
+        assert_eq!(hash_witness(witness.clone()).unwrap(), digest);
+        // Order, length and every element matter
+        let mut reversed = witness.clone();
+        reversed.reverse();
+        assert_ne!(hash_witness(reversed).unwrap(), digest);
+        assert_ne!(hash_witness(witness[..12].to_vec()).unwrap(), digest);
+        let mut padded = witness.clone();
+        padded.push(Fr::zero().to_be_bytes());
+        assert_ne!(hash_witness(padded).unwrap(), digest);
+        assert_ne!(hash_witness(vec![]).unwrap(), key(0));
+        assert_eq!(
+            hash_witness(vec![vec![0; 31]]).unwrap_err().reason,
+            "witness[0]: expected a 32-byte big-endian field element, got 31 bytes"
+        );
+    }
+
+    #[test]
+    fn test_lru_eviction_order() {
+        let cache = ProofCache::new(3);
+        for i in 0..3 {
+            cache.put(key(i), vec![i as u8]);
+        }
+        // Using 0 leaves 1 as the least recently used
+        assert_eq!(cache.get(key(0)), Some(vec![0]));
+        cache.put(key(3), vec![3]);
+        assert_eq!(cache.get(key(1)), None);
+        // Replacing 2 refreshes it, so 0 goes next
+        cache.put(key(2), vec![22]);
+        cache.put(key(4), vec![4]);
+        assert_eq!(cache.get(key(0)), None);
+        assert_eq!(cache.get(key(2)), Some(vec![22]));
+        assert_eq!(cache.get(key(3)), Some(vec![3]));
+        assert_eq!(cache.get(key(4)), Some(vec![4]));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 4,
+                misses: 2,
+                evictions: 2,
+            }
+        );
+
+        let disabled = ProofCache::new(0);
+        disabled.put(key(0), vec![0]);
+        assert_eq!(disabled.get(key(0)), None);
+        assert_eq!(disabled.stats().evictions, 0);
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        const THREADS: u64 = 4;
+        const OPS: u64 = 5000;
+        let keys: Arc<Vec<Vec<u8>>> = Arc::new((0..24).map(key).collect());
+        let cache = Arc::new(ProofCache::new(16));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let (cache, keys) = (cache.clone(), keys.clone());
+                std::thread::spawn(move || {
+                    for op in 0..OPS {
+                        let i = ((op * 7 + t * 13) % keys.len() as u64) as usize;
+                        match cache.get(keys[i].clone()) {
+                            Some(proof) => assert_eq!(proof, keys[i].repeat(2)),
+                            None => cache.put(keys[i].clone(), keys[i].repeat(2)),
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, THREADS * OPS);
+        // Racing misses on one key replace rather than evict
+        assert!(stats.hits > 0 && stats.evictions > 0);
+        assert!(stats.evictions + 16 <= stats.misses);
+        let lru = cache.inner.lock().unwrap();
+        assert_eq!(lru.entries.len(), 16);
+        assert_eq!(lru.order.len(), 16);
+        for (key, (_, used)) in &lru.entries {
+            assert_eq!(&lru.order[used], key);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Apple Silicon chip identification
+//!
+//! `machdep.cpu.brand_string` carries the marketing name ("Apple M2 Pro"),
+//! which is split into a generation (`M2`) and a variant (`Pro`) so tuning
+//! tables can be keyed on it. The GPU core count is not exposed by Metal and
+//! is read from the `AGXAccelerator` entry in the IORegistry instead; the
+//! Neural Engine shows up there as an `H11ANEIn` service.
+
+/// Generation and variant of an Apple Silicon chip
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppleChip {
+    /// Generation, e.g. "M1" or "M4"
+    pub family: String,
+    /// "base", "Pro", "Max" or "Ultra"
+    pub variant: String,
+}
+
+/// Parse an Apple Silicon brand string, `None` for anything else
+///
+/// Accepts any `Apple M<n>` generation so chips newer than this table are
+/// still reported; an unrecognised suffix leaves the variant empty.
+pub fn parse_brand_string(brand: &str) -> Option<AppleChip> {
+    let mut words = brand.split_whitespace();
+    if words.next()? != "Apple" {
+        return None;
+    }
+    let family = words.next()?;
+    let generation = family.strip_prefix('M')?;
+    if generation.is_empty() || !generation.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let variant = match words.next() {
+        None => "base",
+        Some("Pro") => "Pro",
+        Some("Max") => "Max",
+        Some("Ultra") => "Ultra",
+        Some(_) => "",
+    };
+    Some(AppleChip {
+        family: family.to_string(),
+        variant: variant.to_string(),
+    })
+}
+
+/// Number of GPU cores from the IORegistry, 0 if unknown or not on macOS
+pub fn detect_gpu_core_count() -> u32 {
+    #[cfg(target_os = "macos")]
+    {
+        crate::iokit::accelerator_property_u64("gpu-core-count").unwrap_or(0) as u32
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        0
+    }
+}
+
+/// Whether the IORegistry lists an Apple Neural Engine, false if not on macOS
+pub fn detect_neural_engine() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        crate::iokit::service_exists(c"H11ANEIn")
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_brand_strings() {
+        let cases = [
+            ("Apple M1", "M1", "base"),
+            ("Apple M1 Pro", "M1", "Pro"),
+            ("Apple M1 Max", "M1", "Max"),
+            ("Apple M1 Ultra", "M1", "Ultra"),
+            ("Apple M2", "M2", "base"),
+            ("Apple M2 Pro", "M2", "Pro"),
+            ("Apple M2 Max", "M2", "Max"),
+            ("Apple M2 Ultra", "M2", "Ultra"),
+            ("Apple M3", "M3", "base"),
+            ("Apple M3 Pro", "M3", "Pro"),
+            ("Apple M3 Max", "M3", "Max"),
+            ("Apple M3 Ultra", "M3", "Ultra"),
+            ("Apple M4", "M4", "base"),
+            ("Apple M4 Pro", "M4", "Pro"),
+            ("Apple M4 Max", "M4", "Max"),
+            ("Apple M5", "M5", "base"),
+        ];
+        for (brand, family, variant) in cases {
+            assert_eq!(
+                parse_brand_string(brand),
+                Some(AppleChip {
+                    family: family.to_string(),
+                    variant: variant.to_string(),
+                }),
+                "{brand}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_non_apple_brand_strings() {
+        for brand in [
+            "",
+            "Apple",
+            "Apple processor",
+            "VirtualApple @ 2.50GHz processor",
+            "Intel(R) Core(TM) i9-9880H CPU @ 2.30GHz",
+            "ARM Neoverse-V1",
+            "Apple Mx",
+        ] {
+            assert_eq!(parse_brand_string(brand), None, "{brand}");
+        }
+        // Unknown suffixes keep the generation
+        assert_eq!(
+            parse_brand_string("Apple M9 Hyper").map(|c| (c.family, c.variant)),
+            Some(("M9".to_string(), String::new()))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    fn test_apple_silicon_registry() {
+        assert!(detect_gpu_core_count() > 0);
+        assert!(detect_neural_engine());
+    }
+}
@@ -0,0 +1,590 @@
+//! Readers for circom `.r1cs` constraint systems and snarkjs Groth16 `.zkey`
+//! proving keys over BN254
+//!
+//! Both files, like the `.wtns` witnesses of [`crate::witness`], are binfile
+//! containers: a 4-byte magic, a `u32` version and a `u32` section count,
+//! then each section as a `u32` type, a `u64` byte length and the body, all
+//! little-endian. Sections may appear in any order.
+//!
+//! R1CS coefficients are canonical little-endian scalars. zkey curve points
+//! are affine coordinates in Montgomery form, little-endian, with the point
+//! at infinity as all zeros; its A/B coefficients are stored multiplied by
+//! `R^2` so snarkjs can use them against a canonical witness directly.
+
+use std::collections::HashMap;
+
+use memmap2::Mmap;
+use napi::{Error, Result, Status};
+
+use crate::bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use crate::montgomery::MontConfig;
+
+/// Bytes per BN254 field element in both formats
+pub(crate) const FIELD_BYTES: usize = 32;
+
+/// Little-endian cursor over a byte slice that names its source in errors
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    name: &'a str,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], name: &'a str) -> Self {
+        Reader {
+            bytes,
+            pos: 0,
+            name,
+        }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(invalid(
+                self.name,
+                format!("truncated: needed {len} bytes at offset {}", self.pos),
+            ));
+        };
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Everything not yet read
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let out = &self.bytes[self.pos..];
+        self.pos = self.bytes.len();
+        out
+    }
+
+    /// A `u32` count, rejecting counts whose items of `item_bytes` each
+    /// cannot fit in the rest of the input
+    pub(crate) fn count(&mut self, item_bytes: usize) -> Result<usize> {
+        let n = self.u32()? as usize;
+        if n.saturating_mul(item_bytes) > self.bytes.len() - self.pos {
+            return Err(invalid(
+                self.name,
+                format!("count {n} at offset {} overruns the input", self.pos - 4),
+            ));
+        }
+        Ok(n)
+    }
+}
+
+fn invalid(name: &str, reason: String) -> Error {
+    Error::new(Status::InvalidArg, format!("{name}: {reason}"))
+}
+
+/// Split a binfile into its sections, keeping the first of each type
+pub(crate) fn sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+    max_version: u32,
+    name: &'a str,
+) -> Result<HashMap<u32, Reader<'a>>> {
+    let mut reader = Reader::new(bytes, name);
+    if reader.take(4).ok() != Some(&magic[..]) {
+        return Err(invalid(
+            name,
+            format!(
+                "missing the \"{}\" file magic",
+                String::from_utf8_lossy(magic)
+            ),
+        ));
+    }
+    let version = reader.u32()?;
+    if !(1..=max_version).contains(&version) {
+        return Err(invalid(name, format!("unsupported version {version}")));
+    }
+    let count = reader.u32()?;
+    let mut out = HashMap::new();
+    for _ in 0..count {
+        let kind = reader.u32()?;
+        let len = usize::try_from(reader.u64()?).unwrap_or(usize::MAX);
+        let body = reader.take(len)?;
+        out.entry(kind).or_insert(Reader::new(body, name));
+    }
+    Ok(out)
+}
+
+pub(crate) fn section<'a>(
+    sections: &mut HashMap<u32, Reader<'a>>,
+    kind: u32,
+    name: &str,
+) -> Result<Reader<'a>> {
+    sections
+        .remove(&kind)
+        .ok_or_else(|| invalid(name, format!("missing section {kind}")))
+}
+
+/// An IO failure on `path`, which rejects with `GenericFailure`
+pub(crate) fn io_error(path: &str, e: std::io::Error) -> Error {
+    Error::new(Status::GenericFailure, format!("{path}: {e}"))
+}
+
+/// Map a file read-only, for readers that only need its headers
+pub(crate) fn map_file(path: &str) -> Result<Mmap> {
+    let file = std::fs::File::open(path).map_err(|e| io_error(path, e))?;
+    // SAFETY: the map is read-only; as for any reader of the file, it must
+    // not be truncated while the map is in use
+    unsafe { Mmap::map(&file) }.map_err(|e| io_error(path, e))
+}
+
+/// Check a stored field size and modulus against `C`
+fn check_prime<C: MontConfig<4>>(reader: &mut Reader, what: &str) -> Result<()> {
+    let size = reader.u32()? as usize;
+    let modulus = reader.take(size)?;
+    let expected: Vec<u8> = C::MODULUS.iter().flat_map(|l| l.to_le_bytes()).collect();
+    if modulus != expected {
+        return Err(invalid(
+            reader.name,
+            format!("{what} is not BN254's; only bn128 circuits are supported"),
+        ));
+    }
+    Ok(())
+}
+
+/// A sparse linear combination `Σ coeff · w[wire]`
+pub type LinearCombination = Vec<(u32, Fr)>;
+
+/// An R1CS constraint `<a, w> · <b, w> = <c, w>`
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination,
+}
+
+/// Sizes from the header section of a `.r1cs` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct R1csHeader {
+    pub n_wires: usize,
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    pub n_constraints: usize,
+}
+
+impl R1csHeader {
+    /// Parse only the header of a `.r1cs` file, skipping the constraints
+    pub fn parse(bytes: &[u8], name: &str) -> Result<Self> {
+        Self::read(&mut sections(bytes, b"r1cs", 1, name)?, name)
+    }
+
+    fn read(sections: &mut HashMap<u32, Reader>, name: &str) -> Result<Self> {
+        let mut header = section(sections, 1, name)?;
+        check_prime::<crate::bn254::FrConfig>(&mut header, "the constraint field")?;
+        let n_wires = header.u32()? as usize;
+        let n_pub_out = header.u32()? as usize;
+        let n_pub_in = header.u32()? as usize;
+        let n_prv_in = header.u32()? as usize;
+        let _n_labels = header.u64()?;
+        let n_constraints = header.u32()? as usize;
+        if 1 + n_pub_out + n_pub_in + n_prv_in > n_wires {
+            return Err(invalid(
+                name,
+                format!("{n_wires} wires cannot hold the declared signals"),
+            ));
+        }
+        Ok(R1csHeader {
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prv_in,
+            n_constraints,
+        })
+    }
+}
+
+/// A circom constraint system
+///
+/// Wires are ordered as circom numbers them: the constant 1, the public
+/// outputs, the public inputs, then everything private.
+#[derive(Debug, Clone)]
+pub struct R1cs {
+    pub n_wires: usize,
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    pub constraints: Vec<Constraint>,
+}
+
+impl R1cs {
+    /// Parse a `.r1cs` file
+    pub fn parse(bytes: &[u8], name: &str) -> Result<Self> {
+        let mut sections = sections(bytes, b"r1cs", 1, name)?;
+        let R1csHeader {
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prv_in,
+            n_constraints,
+        } = R1csHeader::read(&mut sections, name)?;
+
+        let mut body = section(&mut sections, 2, name)?;
+        // Each constraint holds at least three term counts
+        if n_constraints.saturating_mul(12) > body.bytes.len() {
+            return Err(invalid(
+                name,
+                format!("{n_constraints} constraints overrun the constraint section"),
+            ));
+        }
+        let lc = |body: &mut Reader| -> Result<LinearCombination> {
+            let terms = body.count(4 + FIELD_BYTES)?;
+            (0..terms)
+                .map(|_| {
+                    let wire = body.u32()?;
+                    if wire as usize >= n_wires {
+                        return Err(invalid(
+                            name,
+                            format!("wire {wire} is out of range for {n_wires} wires"),
+                        ));
+                    }
+                    let coeff = Fr::from_le_bytes(body.take(FIELD_BYTES)?).ok_or_else(|| {
+                        invalid(name, "coefficient is not a canonical scalar".to_string())
+                    })?;
+                    Ok((wire, coeff))
+                })
+                .collect()
+        };
+        let constraints = (0..n_constraints)
+            .map(|_| {
+                Ok(Constraint {
+                    a: lc(&mut body)?,
+                    b: lc(&mut body)?,
+                    c: lc(&mut body)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(R1cs {
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prv_in,
+            constraints,
+        })
+    }
+
+    /// Number of public signals, outputs first
+    pub fn n_public(&self) -> usize {
+        self.n_pub_out + self.n_pub_in
+    }
+}
+
+/// Which QAP polynomial a zkey coefficient belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matrix {
+    A,
+    B,
+}
+
+/// One nonzero entry of the A or B matrix as snarkjs lays the QAP out
+#[derive(Debug, Clone, Copy)]
+pub struct Coefficient {
+    pub matrix: Matrix,
+    pub constraint: u32,
+    pub signal: u32,
+    pub value: Fr,
+}
+
+/// A snarkjs Groth16 proving key
+///
+/// `a`, `b_g1` and `b_g2` hold one point per wire, `c` one per private wire
+/// (after the `n_public + 1` public ones), and `h` one per domain point.
+#[derive(Debug, Clone)]
+pub struct Zkey {
+    pub n_vars: usize,
+    pub n_public: usize,
+    pub domain_size: usize,
+    pub alpha_g1: G1Affine,
+    pub beta_g1: G1Affine,
+    pub beta_g2: G2Affine,
+    pub gamma_g2: G2Affine,
+    pub delta_g1: G1Affine,
+    pub delta_g2: G2Affine,
+    pub ic: Vec<G1Affine>,
+    pub coefficients: Vec<Coefficient>,
+    pub a: Vec<G1Affine>,
+    pub b_g1: Vec<G1Affine>,
+    pub b_g2: Vec<G2Affine>,
+    pub c: Vec<G1Affine>,
+    pub h: Vec<G1Affine>,
+}
+
+/// zkey protocol id of Groth16
+const GROTH16_PROTOCOL: u32 = 1;
+
+fn read_fq(reader: &mut Reader) -> Result<Fq> {
+    Fq::from_montgomery_le_bytes(reader.take(FIELD_BYTES)?).ok_or_else(|| {
+        invalid(
+            reader.name,
+            "coordinate is not below the BN254 base field modulus".to_string(),
+        )
+    })
+}
+
+pub(crate) fn read_g1(reader: &mut Reader) -> Result<G1Affine> {
+    let (x, y) = (read_fq(reader)?, read_fq(reader)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    let point = G1Affine::new(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid(
+            reader.name,
+            "point is not on the BN254 G1 curve".to_string(),
+        ));
+    }
+    Ok(point)
+}
+
+pub(crate) fn read_g2(reader: &mut Reader) -> Result<G2Affine> {
+    let x = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    let y = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    if [x.c0, x.c1, y.c0, y.c1].iter().all(|c| c.is_zero()) {
+        return Ok(G2Affine::identity());
+    }
+    let point = G2Affine::new(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid(
+            reader.name,
+            "point is not on the BN254 G2 curve".to_string(),
+        ));
+    }
+    Ok(point)
+}
+
+fn read_points<T>(
+    reader: &mut Reader,
+    count: usize,
+    read: fn(&mut Reader) -> Result<T>,
+) -> Result<Vec<T>> {
+    (0..count).map(|_| read(reader)).collect()
+}
+
+/// Sizes from the protocol and header sections of a `.zkey` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZkeyHeader {
+    pub n_vars: usize,
+    pub n_public: usize,
+    pub domain_size: usize,
+}
+
+impl ZkeyHeader {
+    /// Parse only the header of a `.zkey` file, skipping the points
+    pub fn parse(bytes: &[u8], name: &str) -> Result<Self> {
+        let mut sections = sections(bytes, b"zkey", 1, name)?;
+        Ok(Self::read(&mut sections, name)?.0)
+    }
+
+    /// Check the protocol and read the sizes, returning the header section
+    /// positioned at the verifying key points
+    pub(crate) fn read<'a>(
+        sections: &mut HashMap<u32, Reader<'a>>,
+        name: &str,
+    ) -> Result<(Self, Reader<'a>)> {
+        let protocol = section(sections, 1, name)?.u32()?;
+        if protocol != GROTH16_PROTOCOL {
+            return Err(invalid(
+                name,
+                format!("protocol {protocol} is not Groth16 ({GROTH16_PROTOCOL})"),
+            ));
+        }
+
+        let mut header = section(sections, 2, name)?;
+        check_prime::<crate::bn254::FqConfig>(&mut header, "the base field")?;
+        check_prime::<crate::bn254::FrConfig>(&mut header, "the scalar field")?;
+        let n_vars = header.u32()? as usize;
+        let n_public = header.u32()? as usize;
+        let domain_size = header.u32()? as usize;
+        if n_public >= n_vars {
+            return Err(invalid(
+                name,
+                format!("{n_public} public signals do not fit {n_vars} variables"),
+            ));
+        }
+        if !domain_size.is_power_of_two() || domain_size.trailing_zeros() >= crate::ntt::TWO_ADICITY
+        {
+            return Err(invalid(
+                name,
+                format!(
+                    "domain size {domain_size} is not a power of two below 2^{}",
+                    crate::ntt::TWO_ADICITY
+                ),
+            ));
+        }
+        let sizes = ZkeyHeader {
+            n_vars,
+            n_public,
+            domain_size,
+        };
+        Ok((sizes, header))
+    }
+}
+
+impl Zkey {
+    /// Parse a Groth16 `.zkey` file
+    pub fn parse(bytes: &[u8], name: &str) -> Result<Self> {
+        Self::read(&mut sections(bytes, b"zkey", 1, name)?, name)
+    }
+
+    /// Read a proving key from its sections, leaving any others in `sections`
+    pub(crate) fn read(sections: &mut HashMap<u32, Reader>, name: &str) -> Result<Self> {
+        let (
+            ZkeyHeader {
+                n_vars,
+                n_public,
+                domain_size,
+            },
+            mut header,
+        ) = ZkeyHeader::read(sections, name)?;
+        let alpha_g1 = read_g1(&mut header)?;
+        let beta_g1 = read_g1(&mut header)?;
+        let beta_g2 = read_g2(&mut header)?;
+        let gamma_g2 = read_g2(&mut header)?;
+        let delta_g1 = read_g1(&mut header)?;
+        let delta_g2 = read_g2(&mut header)?;
+
+        let ic = read_points(&mut section(sections, 3, name)?, n_public + 1, read_g1)?;
+
+        let mut coeffs = section(sections, 4, name)?;
+        let n_coeffs = coeffs.count(12 + FIELD_BYTES)?;
+        let coefficients = (0..n_coeffs)
+            .map(|_| {
+                let matrix = match coeffs.u32()? {
+                    0 => Matrix::A,
+                    1 => Matrix::B,
+                    m => return Err(invalid(name, format!("unknown coefficient matrix {m}"))),
+                };
+                let constraint = coeffs.u32()?;
+                let signal = coeffs.u32()?;
+                if constraint as usize >= domain_size || signal as usize >= n_vars {
+                    return Err(invalid(
+                        name,
+                        format!("coefficient ({constraint}, {signal}) is out of range"),
+                    ));
+                }
+                // Stored as the canonical value of c R^2: two Montgomery
+                // reductions recover c
+                let value = Fr::from_montgomery_le_bytes(coeffs.take(FIELD_BYTES)?)
+                    .and_then(|v| Fr::from_montgomery_limbs(v.to_canonical()))
+                    .ok_or_else(|| {
+                        invalid(name, "coefficient is not a reduced scalar".to_string())
+                    })?;
+                Ok(Coefficient {
+                    matrix,
+                    constraint,
+                    signal,
+                    value,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let a = read_points(&mut section(sections, 5, name)?, n_vars, read_g1)?;
+        let b_g1 = read_points(&mut section(sections, 6, name)?, n_vars, read_g1)?;
+        let b_g2 = read_points(&mut section(sections, 7, name)?, n_vars, read_g2)?;
+        let c = read_points(
+            &mut section(sections, 8, name)?,
+            n_vars - n_public - 1,
+            read_g1,
+        )?;
+        let h = read_points(&mut section(sections, 9, name)?, domain_size, read_g1)?;
+
+        Ok(Zkey {
+            n_vars,
+            n_public,
+            domain_size,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g1,
+            delta_g2,
+            ic,
+            coefficients,
+            a,
+            b_g1,
+            b_g2,
+            c,
+            h,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLIER_R1CS: &[u8] = include_bytes!("../tests/fixtures/multiplier.r1cs");
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../tests/fixtures/multiplier.zkey");
+
+    #[test]
+    fn test_parse_multiplier() {
+        let r1cs = R1cs::parse(MULTIPLIER_R1CS, "r1cs").unwrap();
+        assert_eq!(
+            (r1cs.n_wires, r1cs.n_pub_out, r1cs.n_pub_in, r1cs.n_prv_in),
+            (4, 1, 0, 2)
+        );
+        assert_eq!(r1cs.constraints.len(), 1);
+        // -a * b = -c
+        let minus_one = -Fr::one();
+        let k = &r1cs.constraints[0];
+        assert_eq!(k.a, vec![(2, minus_one)]);
+        assert_eq!(k.b, vec![(3, Fr::one())]);
+        assert_eq!(k.c, vec![(1, minus_one)]);
+
+        let zkey = Zkey::parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+        assert_eq!((zkey.n_vars, zkey.n_public, zkey.domain_size), (4, 1, 4));
+        assert_eq!(zkey.ic.len(), 2);
+        assert_eq!((zkey.a.len(), zkey.b_g2.len(), zkey.c.len()), (4, 4, 2));
+        assert_eq!(zkey.h.len(), 4);
+        // The r1cs entries of A and B, then one A entry per public signal
+        // and the constant
+        assert!(zkey
+            .coefficients
+            .iter()
+            .any(|c| c.matrix == Matrix::A && c.signal == 2 && c.value == minus_one));
+        assert!(zkey
+            .coefficients
+            .iter()
+            .any(|c| c.matrix == Matrix::A && c.constraint == 2 && c.value == Fr::one()));
+    }
+
+    #[test]
+    fn test_rejects_malformed_files() {
+        let err = R1cs::parse(&MULTIPLIER_R1CS[..40], "r1cs").unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.starts_with("r1cs: truncated"), "{}", err.reason);
+
+        let err = Zkey::parse(MULTIPLIER_R1CS, "zkey").unwrap_err();
+        assert_eq!(err.reason, "zkey: missing the \"zkey\" file magic");
+
+        let mut version = MULTIPLIER_ZKEY.to_vec();
+        version[4] = 2;
+        let err = Zkey::parse(&version, "zkey").unwrap_err();
+        assert_eq!(err.reason, "zkey: unsupported version 2");
+
+        // Flip a byte of the stored scalar field prime
+        let mut prime = MULTIPLIER_R1CS.to_vec();
+        let at = prime.windows(4).position(|w| w == [1, 0, 0, 0xf0]).unwrap();
+        prime[at] ^= 2;
+        let err = R1cs::parse(&prime, "r1cs").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "r1cs: the constraint field is not BN254's; only bn128 circuits are supported"
+        );
+    }
+}
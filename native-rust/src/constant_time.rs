@@ -0,0 +1,253 @@
+//! Constant-time execution mode for secret-dependent field and curve operations
+//!
+//! Operations that take a `constant_time` option honor it per call; when it
+//! is omitted they follow the process-wide mode set by
+//! [`set_constant_time_mode`]. In constant-time mode:
+//!
+//! - inversion uses Fermat's little theorem, one fixed-window `x^(p - 2)`
+//!   per element, instead of batched or binary extended Euclidean
+//!   inversion: `bn254_field_inv`, `bn254_fr_inv_batch`,
+//!   `bls12_381_fr_inv_batch`, `bls12_381_fp_inv_batch`,
+//!   `bls12_381_fp2_inv_batch`, `pallas_fp_inv_batch`, `vesta_fp_inv_batch`
+//! - exponentiation multiplies every window, reading the table with a
+//!   constant-time scan: `bn254_fr_pow_batch`, `bls12_381_fr_pow_batch`,
+//!   `pallas_fp_pow_batch`, `vesta_fp_pow_batch`
+//! - scalar multiplication is a Montgomery ladder over complete formulas
+//!   with constant-time swaps, instead of double-and-add or GLV:
+//!   `g1_scalar_mul`, `bn254_g1_scalar_mul`, `bn254_g2_scalar_mul`,
+//!   `bls_public_key`, `bls_sign`
+//!
+//! Inversion, scalar and multi-scalar multiplication and proving functions
+//! without a constant-time path throw while the mode is on rather than
+//! silently running variable time code: `bn254_field_inv_batch`,
+//! `goldilocks_inv_batch`, `m31_inv_batch`, `bn254_g1_scalar_mul_glv`, the
+//! `msm_bn254_g1` family, `pedersen_commit`, `pedersen_commit_batch`,
+//! `bulletproofs_commit`, `inner_product_proof`, `kzg_commit`, `kzg_open`
+//! and `prove_groth16`.
+//!
+//! Only the arithmetic is covered: parsing, range checks and conversion
+//! from canonical form stay variable time, and whether a value is zero may
+//! leak. Use the Montgomery-form options where available for secret data.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn the process-wide constant-time mode on or off
+///
+/// The mode is the default for every `constant_time` option and makes
+/// operations without a constant-time implementation throw. Off by default.
+#[napi]
+pub fn set_constant_time_mode(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the process-wide constant-time mode is on
+#[napi]
+pub fn get_constant_time_mode() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A per-call `constant_time` option, defaulting to the process-wide mode
+pub(crate) fn resolve(requested: Option<bool>) -> bool {
+    requested.unwrap_or_else(get_constant_time_mode)
+}
+
+/// Fail `operation`, which has no constant-time implementation, when the
+/// process-wide mode is on
+pub(crate) fn require_variable_time(operation: &str) -> Result<()> {
+    check_variable_time(get_constant_time_mode(), operation)
+}
+
+fn check_variable_time(enabled: bool, operation: &str) -> Result<()> {
+    if enabled {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{operation}: no constant-time implementation; disable constant-time mode to use it"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{Jacobian, SwCurve};
+    use crate::field::Form;
+    use crate::montgomery::{ConstantTimeField, MontConfig};
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+    use crate::{bls12_381, bn254, pasta};
+
+    #[test]
+    fn test_mode_resolution() {
+        assert!(resolve(Some(true)));
+        assert!(!resolve(Some(false)));
+        assert!(check_variable_time(false, "kzg_commit").is_ok());
+        let err = check_variable_time(true, "kzg_commit").unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(
+            err.reason,
+            "kzg_commit: no constant-time implementation; disable constant-time mode to use it"
+        );
+    }
+
+    /// Scalars as little-endian limbs: edge cases around 0 and `r`, then
+    /// pseudo-random 256-bit values
+    fn scalars(r: [u64; 4]) -> Vec<Vec<u64>> {
+        let mut r_minus_one = r;
+        r_minus_one[0] -= 1;
+        let mut out = vec![
+            vec![0; 4],
+            vec![1, 0, 0, 0],
+            vec![2, 0, 0, 0],
+            r_minus_one.to_vec(),
+            r.to_vec(),
+            vec![u64::MAX; 4],
+            // Longer than the group order, as blinded BLS scalars are
+            vec![7, 0, 0, 1 << 63, 3],
+        ];
+        let mut x = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..8 {
+            out.push(
+                (0..4)
+                    .map(|_| {
+                        x ^= x << 13;
+                        x ^= x >> 7;
+                        x ^= x << 17;
+                        x
+                    })
+                    .collect(),
+            );
+        }
+        out
+    }
+
+    fn check_ladder<C: SwCurve>(g: Jacobian<C>, r: [u64; 4])
+    where
+        C::Base: ConstantTimeField,
+    {
+        // Generic Z as well as Z = 1, and the point at infinity
+        for p in [g, g.double().add(&g), Jacobian::identity()] {
+            for k in scalars(r) {
+                assert_eq!(p.mul_ct(&k), p.mul_limbs(&k), "{k:x?}");
+                let be: Vec<u8> = k.iter().rev().flat_map(|l| l.to_be_bytes()).collect();
+                assert_eq!(p.mul_be_bytes_ct(&be), p.mul_limbs(&k), "{k:x?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ladder_matches_double_and_add() {
+        check_ladder(
+            bn254::g1_generator().to_jacobian(),
+            bn254::FrConfig::MODULUS,
+        );
+        check_ladder(
+            bn254::g2_generator().to_jacobian(),
+            bn254::FrConfig::MODULUS,
+        );
+        check_ladder(
+            bls12_381::g1_generator().to_jacobian(),
+            bls12_381::FrConfig::MODULUS,
+        );
+        check_ladder(
+            bls12_381::g2_generator().to_jacobian(),
+            bls12_381::FrConfig::MODULUS,
+        );
+    }
+
+    type InvFn = fn(&[u8], bool, bool, Form) -> Result<Vec<u8>>;
+
+    #[test]
+    fn test_ct_inversion_matches_batched() {
+        let cases: [(FieldName, usize, InvFn); 6] = [
+            (FieldName::Bn254Fr, 1, bn254::fr_inv_batch),
+            (FieldName::Bls12_381Fr, 1, bls12_381::fr_inv_batch),
+            (FieldName::Bls12_381Fp, 1, bls12_381::fp_inv_batch),
+            (FieldName::Bls12_381Fp, 2, bls12_381::fp2_inv_batch),
+            (FieldName::PallasFp, 1, pasta::inv::<pasta::FpConfig>),
+            (FieldName::VestaFp, 1, pasta::inv::<pasta::FqConfig>),
+        ];
+        for (field, degree, inv) in cases {
+            let size = degree * field.element_bytes();
+            let mut values = random_elements(field, 150 * degree, Some(&[9; SEED_BYTES])).unwrap();
+            values[3 * size..4 * size].fill(0);
+            for form in [Form::Canonical, Form::Montgomery] {
+                let batched = inv(&values, true, false, form).unwrap();
+                assert_eq!(
+                    inv(&values, true, true, form).unwrap(),
+                    batched,
+                    "{field:?}"
+                );
+                assert_eq!(batched[3 * size..4 * size], vec![0; size]);
+                let err = inv(&values, false, true, form).unwrap_err();
+                assert_eq!(err.reason, "values[3]: zero has no inverse");
+            }
+        }
+    }
+
+    /// Welch's t-statistic between two timing samples
+    fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+        let stats = |x: &[f64]| {
+            let n = x.len() as f64;
+            let mean = x.iter().sum::<f64>() / n;
+            let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (mean, var / n)
+        };
+        let ((ma, va), (mb, vb)) = (stats(a), stats(b));
+        (ma - mb) / (va + vb).sqrt()
+    }
+
+    /// dudect-style leakage check (<https://github.com/oreparaz/dudect>):
+    /// time a fixed zero scalar against random ones, interleaved at random,
+    /// and compare with Welch's t-test. |t| above ~5 indicates a leak; the
+    /// double-and-add reference should show one and the ladder should not.
+    #[test]
+    #[ignore]
+    fn bench_dudect_scalar_mul() {
+        use std::time::Instant;
+
+        const SAMPLES: usize = 4000;
+        let g = bn254::g1_generator().to_jacobian();
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        type Mul = fn(&bn254::G1Projective, &[u64]) -> bn254::G1Projective;
+        let cases: [(&str, Mul); 2] = [
+            ("ladder", |p, k| p.mul_ct(k)),
+            ("double-and-add", |p, k| p.mul_limbs(k)),
+        ];
+        for (name, mul) in cases {
+            let (mut fixed, mut random) = (Vec::new(), Vec::new());
+            for _ in 0..SAMPLES {
+                let is_fixed = next() & 1 == 0;
+                let k = if is_fixed {
+                    [0; 4]
+                } else {
+                    [next(), next(), next(), next() >> 3]
+                };
+                let start = Instant::now();
+                std::hint::black_box(mul(&g, std::hint::black_box(&k)));
+                let elapsed = start.elapsed().as_nanos() as f64;
+                if is_fixed {
+                    fixed.push(elapsed);
+                } else {
+                    random.push(elapsed);
+                }
+            }
+            println!(
+                "{name}: t = {:.2} over {SAMPLES} samples",
+                welch_t(&fixed, &random)
+            );
+        }
+    }
+}
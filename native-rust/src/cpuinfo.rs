@@ -0,0 +1,110 @@
+//! CPU model name detection
+//!
+//! macOS reports the marketing name through `machdep.cpu.brand_string`.
+//! Linux exposes `model name` in `/proc/cpuinfo` on x86; aarch64 kernels only
+//! list the MIDR implementer and part numbers, which are mapped to core names
+//! for the common server and handset parts. Windows reads
+//! `ProcessorNameString` from the registry.
+
+/// Detect the CPU model name, empty if unknown
+pub fn detect_cpu_model() -> String {
+    #[cfg(target_os = "macos")]
+    {
+        crate::sysctl::read_string("machdep.cpu.brand_string").unwrap_or_default()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|text| parse_cpu_model(&text))
+            .unwrap_or_default()
+    }
+    #[cfg(windows)]
+    {
+        crate::win32::processor_name().unwrap_or_default()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+    {
+        String::new()
+    }
+}
+
+/// Extract a model name from `/proc/cpuinfo` text
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    let field = |key: &str| {
+        cpuinfo.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim().to_string())
+        })
+    };
+    if let Some(name) = field("model name").filter(|n| !n.is_empty()) {
+        return Some(name);
+    }
+    let implementer = parse_hex(&field("CPU implementer")?)?;
+    let part = parse_hex(&field("CPU part")?)?;
+    Some(match arm_core_name(implementer, part) {
+        Some(name) => name.to_string(),
+        None => format!("ARM implementer {implementer:#x} part {part:#x}"),
+    })
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).ok()
+}
+
+/// Core names for MIDR `(implementer, part)` pairs
+fn arm_core_name(implementer: u32, part: u32) -> Option<&'static str> {
+    Some(match (implementer, part) {
+        (0x41, 0xd03) => "ARM Cortex-A53",
+        (0x41, 0xd08) => "ARM Cortex-A72",
+        (0x41, 0xd0b) => "ARM Cortex-A76",
+        (0x41, 0xd0c) => "ARM Neoverse-N1",
+        (0x41, 0xd40) => "ARM Neoverse-V1",
+        (0x41, 0xd49) => "ARM Neoverse-N2",
+        (0x41, 0xd4f) => "ARM Neoverse-V2",
+        (0x41, 0xd8e) => "ARM Neoverse-N3",
+        (0x61, _) => "Apple",
+        (0xc0, 0xac3) => "Ampere AmpereOne",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_x86_model_name() {
+        let text =
+            "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel name\t: Intel(R) Xeon(R) Processor\n";
+        assert_eq!(
+            parse_cpu_model(text).as_deref(),
+            Some("Intel(R) Xeon(R) Processor")
+        );
+    }
+
+    #[test]
+    fn test_parse_aarch64_midr() {
+        let graviton3 = "processor\t: 0\nBogoMIPS\t: 2100.00\nCPU implementer\t: 0x41\nCPU architecture: 8\nCPU variant\t: 0x1\nCPU part\t: 0xd40\n";
+        assert_eq!(
+            parse_cpu_model(graviton3).as_deref(),
+            Some("ARM Neoverse-V1")
+        );
+        let unknown = "CPU implementer\t: 0x51\nCPU part\t: 0x802\n";
+        assert_eq!(
+            parse_cpu_model(unknown).as_deref(),
+            Some("ARM implementer 0x51 part 0x802")
+        );
+        assert_eq!(parse_cpu_model("processor\t: 0\n"), None);
+    }
+
+    #[test]
+    fn test_detect_cpu_model() {
+        let model = detect_cpu_model();
+        if cfg!(target_os = "macos") {
+            assert!(!model.is_empty());
+        }
+        assert_eq!(model.trim(), model);
+    }
+}
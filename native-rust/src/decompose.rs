@@ -0,0 +1,297 @@
+//! Windowed digit decomposition of packed scalars
+//!
+//! `decompose_scalars` splits every scalar of a packed buffer into
+//! `window_bits`-wide digits, least significant first, the form bucket
+//! MSMs and lookup-based range checks consume. Scalars use the encoding of
+//! `random_field_elements`: canonical, little-endian, at the field's element
+//! size.
+//!
+//! Unsigned digits lie in `[0, 2^w)` and `ceil(bits / w)` of them cover a
+//! scalar below a `bits`-bit modulus. Signed digits lie in
+//! `[-2^(w-1), 2^(w-1))`: a digit at or above `2^(w-1)` has `2^w` taken
+//! off and carries one into the next window. The top window must absorb a
+//! carry without producing one, which `ceil((bits + 2) / w)` windows
+//! guarantee: its raw bits then stay below `2^(w-2)`.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::field::ELEMENTWISE_PARALLEL_THRESHOLD;
+use crate::random::FieldName;
+use crate::validate::check_canonical;
+
+/// Digits of every scalar of a [`decompose_scalars`] call
+#[napi(object)]
+pub struct ScalarDecomposition {
+    /// `windows` digits per scalar, least significant first, each
+    /// `digit_bytes` little-endian bytes; two's complement when signed
+    pub digits: Buffer,
+    /// Digits per scalar
+    pub windows: u32,
+    /// 2 when `window_bits <= 16`, else 4
+    pub digit_bytes: u32,
+    /// Bit length of the field modulus the window count derives from
+    pub modulus_bits: u32,
+}
+
+/// Bit length of the little-endian `modulus`
+fn bit_len(modulus: &[u8]) -> usize {
+    modulus
+        .iter()
+        .rposition(|&b| b != 0)
+        .map_or(0, |i| 8 * i + 8 - modulus[i].leading_zeros() as usize)
+}
+
+/// Digits per scalar for a `bits`-bit modulus
+pub fn window_count(bits: usize, window_bits: usize, signed: bool) -> usize {
+    (bits + 2 * signed as usize).div_ceil(window_bits)
+}
+
+/// The `w` bits of the little-endian `scalar` starting at bit `offset`,
+/// zero past its end
+fn window(scalar: &[u8], offset: usize, w: usize) -> u64 {
+    let mut value = 0u64;
+    let first = offset / 8;
+    for (k, &byte) in scalar
+        .iter()
+        .skip(first)
+        .take(w.div_ceil(8) + 1)
+        .enumerate()
+    {
+        value |= (byte as u64) << (8 * k);
+    }
+    (value >> (offset % 8)) & ((1u64 << w) - 1)
+}
+
+/// Write the digits of one scalar into `out`, `windows` digits of `size`
+/// bytes each
+fn decompose_one(scalar: &[u8], w: usize, signed: bool, size: usize, out: &mut [u8]) {
+    let half = 1i64 << (w - 1);
+    let mut carry = 0i64;
+    for (i, digit) in out.chunks_exact_mut(size).enumerate() {
+        let mut d = window(scalar, i * w, w) as i64 + carry;
+        if signed {
+            carry = (d >= half) as i64;
+            d -= carry << w;
+        }
+        digit.copy_from_slice(&d.to_le_bytes()[..size]);
+    }
+    debug_assert!(carry == 0, "window count covers the final carry");
+}
+
+/// Digits and layout of [`decompose`]; see [`ScalarDecomposition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digits {
+    pub digits: Vec<u8>,
+    pub windows: usize,
+    pub digit_bytes: usize,
+    pub modulus_bits: usize,
+}
+
+/// Split packed little-endian `scalars` of `field` into `window_bits`-wide
+/// digits
+pub fn decompose(
+    scalars: &[u8],
+    window_bits: u32,
+    field: FieldName,
+    signed: bool,
+) -> Result<Digits> {
+    if !(1..=32).contains(&window_bits) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("window_bits: expected 1 to 32, got {window_bits}"),
+        ));
+    }
+    if signed && window_bits == 1 {
+        // [-1, 1) has no positive digit
+        return Err(Error::new(
+            Status::InvalidArg,
+            "window_bits: signed digits need at least 2 bits".to_string(),
+        ));
+    }
+    check_canonical(scalars, field, "scalars")?;
+    let w = window_bits as usize;
+    let size = field.element_bytes();
+    let bits = bit_len(&field.modulus_le_bytes());
+    let windows = window_count(bits, w, signed);
+    let digit_bytes = if w <= 16 { 2 } else { 4 };
+    let stride = windows * digit_bytes;
+    let mut digits = vec![0u8; scalars.len() / size * stride];
+    crate::parallel::par_chunks_mut(
+        &mut digits,
+        stride,
+        ELEMENTWISE_PARALLEL_THRESHOLD * stride,
+        |offset, chunk| {
+            let first = offset / stride;
+            for (i, out) in chunk.chunks_exact_mut(stride).enumerate() {
+                let scalar = &scalars[(first + i) * size..(first + i + 1) * size];
+                decompose_one(scalar, w, signed, digit_bytes, out);
+            }
+        },
+    );
+    Ok(Digits {
+        digits,
+        windows,
+        digit_bytes,
+        modulus_bits: bits,
+    })
+}
+
+/// Split packed scalars into `window_bits`-wide digits, least significant
+/// first
+///
+/// `field` is any name `random_field_elements` accepts and `scalars` packed
+/// little-endian canonical elements of it. `window_bits` is 1 to 32, or 2 to 32 when signed; digits
+/// are 16-bit when it is at most 16, else 32-bit. With `signed` set digits
+/// lie in `[-2^(w-1), 2^(w-1))` as two's complement and there may be one
+/// more window; read them with an `Int16Array` or `Int32Array`. The window
+/// count is returned alongside the digits.
+#[napi]
+pub fn decompose_scalars(
+    scalars: Buffer,
+    window_bits: u32,
+    field: String,
+    signed: Option<bool>,
+) -> Result<ScalarDecomposition> {
+    let result = decompose(
+        &scalars,
+        window_bits,
+        FieldName::parse(&field)?,
+        signed.unwrap_or(false),
+    )?;
+    Ok(ScalarDecomposition {
+        digits: result.digits.into(),
+        windows: result.windows as u32,
+        digit_bytes: result.digit_bytes as u32,
+        modulus_bits: result.modulus_bits as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_elements, SEED_BYTES};
+
+    /// Digits of `size` little-endian bytes as integers
+    fn values(digits: &[u8], size: usize, signed: bool) -> Vec<i64> {
+        digits
+            .chunks_exact(size)
+            .map(|d| {
+                let mut le = [0u8; 8];
+                le[..size].copy_from_slice(d);
+                let v = i64::from_le_bytes(le);
+                let shift = 64 - 8 * size as u32;
+                if signed {
+                    (v << shift) >> shift
+                } else {
+                    v
+                }
+            })
+            .collect()
+    }
+
+    /// `Σ digit[i] · 2^(w·i)` as a little-endian byte string of `len` bytes,
+    /// carrying signed values bit by bit
+    fn recompose(values: &[i64], w: usize, len: usize) -> Vec<u8> {
+        // Accumulate into bits, one signed value per bit position
+        let mut acc = vec![0i128; w * values.len() + 64];
+        for (i, &v) in values.iter().enumerate() {
+            acc[i * w] += v as i128;
+        }
+        let mut out = vec![0u8; len];
+        let mut carry = 0i128;
+        for (bit, a) in acc.iter().enumerate() {
+            let t = a + carry;
+            let b = t.rem_euclid(2);
+            carry = (t - b) / 2;
+            if bit < 8 * len {
+                out[bit / 8] |= (b as u8) << (bit % 8);
+            } else {
+                assert_eq!(b, 0, "digits overflow the element size");
+            }
+        }
+        assert_eq!(carry, 0, "digits are negative in total");
+        out
+    }
+
+    #[test]
+    fn test_reconstructs_scalars() {
+        for (name, field) in FieldName::ALL {
+            let size = field.element_bytes();
+            let mut scalars = random_elements(field, 9, Some(&[7; SEED_BYTES])).unwrap();
+            // p - 1 exercises the top window and the final carry
+            let mut p_minus_one = field.modulus_le_bytes();
+            p_minus_one[0] -= 1;
+            scalars.extend(&p_minus_one);
+            scalars.extend(vec![0; size]);
+            let n = scalars.len() / size;
+            for w in [1, 2, 4, 13, 16, 17, 32] {
+                for signed in [false, true] {
+                    if signed && w == 1 {
+                        continue;
+                    }
+                    let result = decompose(&scalars, w, field, signed).unwrap();
+                    let w = w as usize;
+                    let (bytes, windows) = (result.digit_bytes, result.windows);
+                    assert_eq!(result.digits.len(), n * windows * bytes, "{name} w={w}");
+                    let digits = values(&result.digits, bytes, signed);
+                    for (i, scalar) in scalars.chunks_exact(size).enumerate() {
+                        let own = &digits[i * windows..(i + 1) * windows];
+                        assert_eq!(
+                            recompose(own, w, size),
+                            scalar,
+                            "{name} w={w} signed={signed} scalar {i}"
+                        );
+                        let range = if signed {
+                            -(1i64 << (w - 1))..1 << (w - 1)
+                        } else {
+                            0..1 << w
+                        };
+                        assert!(own.iter().all(|d| range.contains(d)), "{name} w={w}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_window_counts() {
+        let bn254 = decompose(&[], 16, FieldName::Bn254Fr, false).unwrap();
+        assert_eq!(
+            (bn254.modulus_bits, bn254.windows, bn254.digit_bytes),
+            (254, 16, 2)
+        );
+        let signed = decompose(&[], 16, FieldName::Bn254Fr, true).unwrap();
+        assert_eq!(signed.windows, 16);
+        let signed = decompose(&[], 17, FieldName::Bn254Fr, true).unwrap();
+        assert_eq!((signed.windows, signed.digit_bytes), (16, 4));
+        // 64 bits fill four 16-bit windows exactly; signed needs a fifth
+        let goldilocks = decompose(&[], 16, FieldName::Goldilocks, true).unwrap();
+        assert_eq!((goldilocks.modulus_bits, goldilocks.windows), (64, 5));
+        assert_eq!(window_count(31, 8, false), 4);
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let field = FieldName::Bn254Fr;
+        let err = decompose(&[0; 32], 0, field, false).unwrap_err();
+        assert_eq!(err.reason, "window_bits: expected 1 to 32, got 0");
+        assert!(decompose(&[0; 32], 33, field, true).is_err());
+        let err = decompose(&[0; 32], 1, field, true).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "window_bits: signed digits need at least 2 bits"
+        );
+        let err = decompose(&[0; 31], 8, field, false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "scalars: length 31 is not a multiple of 32 bytes"
+        );
+        let err = decompose(&field.modulus_le_bytes(), 8, field, false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "scalars[0]: value is not below the bn254-fr modulus"
+        );
+    }
+}
@@ -0,0 +1,1181 @@
+//! Generic short Weierstrass curve arithmetic for `y^2 = x^3 + b`
+//!
+//! Every pairing-friendly curve exposed by the crate has `a = 0`, so the
+//! formulas below are the `a = 0` specialisations from the Explicit-Formulas
+//! Database. Points are kept in Jacobian coordinates `(X, Y, Z)` representing
+//! the affine point `(X / Z^2, Y / Z^3)`; `Z = 0` is the point at infinity.
+//!
+//! [`g1_jacobian_to_affine_batch`] normalizes Jacobian G1 points from JS
+//! with one batched inversion per worker thread, and [`g1_compress_batch`]
+//! and [`g1_decompress_batch`] convert whole arrays of G1 points to and from
+//! their compressed encodings. [`g1_subgroup_check_batch`] and
+//! [`g2_subgroup_check_batch`] check untrusted points for prime-order
+//! subgroup membership with the curves' endomorphisms.
+
+use std::fmt;
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::montgomery::{ConstantTimeField, Field};
+use crate::validate::ValidationResult;
+
+/// Points below which [`Jacobian::batch_normalize`] stays on one thread
+const NORMALIZE_PARALLEL_THRESHOLD: usize = 1 << 10;
+
+/// Points below which [`decompress_batch`] stays on one thread; each costs
+/// a square root
+const DECOMPRESS_PARALLEL_THRESHOLD: usize = 1 << 8;
+
+/// Points below which [`subgroup_check_batch`] stays on one thread; each
+/// costs a scalar multiplication of at most 128 bits
+const SUBGROUP_PARALLEL_THRESHOLD: usize = 1 << 6;
+
+/// Parameters of a short Weierstrass curve with `a = 0`
+pub trait SwCurve: 'static + Copy + Send + Sync + fmt::Debug {
+    /// Field the coordinates live in
+    type Base: Field;
+
+    /// The curve coefficient `b`
+    fn coeff_b() -> Self::Base;
+}
+
+/// A point in affine coordinates
+#[derive(Clone, Copy)]
+pub struct Affine<C: SwCurve> {
+    pub x: C::Base,
+    pub y: C::Base,
+    pub infinity: bool,
+}
+
+/// A point in Jacobian projective coordinates
+#[derive(Clone, Copy)]
+pub struct Jacobian<C: SwCurve> {
+    pub x: C::Base,
+    pub y: C::Base,
+    pub z: C::Base,
+}
+
+impl<C: SwCurve> fmt::Debug for Affine<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.infinity {
+            write!(f, "Affine(infinity)")
+        } else {
+            write!(f, "Affine({:?}, {:?})", self.x, self.y)
+        }
+    }
+}
+
+impl<C: SwCurve> fmt::Debug for Jacobian<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_affine())
+    }
+}
+
+impl<C: SwCurve> Affine<C> {
+    /// The point at infinity
+    pub fn identity() -> Self {
+        Affine {
+            x: C::Base::zero(),
+            y: C::Base::zero(),
+            infinity: true,
+        }
+    }
+
+    /// A finite point from its coordinates (not validated)
+    pub fn new(x: C::Base, y: C::Base) -> Self {
+        Affine {
+            x,
+            y,
+            infinity: false,
+        }
+    }
+
+    /// Whether the point satisfies the curve equation (infinity always does)
+    pub fn is_on_curve(&self) -> bool {
+        if self.infinity {
+            return true;
+        }
+        self.y.square() == self.x.square() * self.x + C::coeff_b()
+    }
+
+    /// Negation `(x, -y)`
+    pub fn neg(&self) -> Self {
+        Affine {
+            x: self.x,
+            y: -self.y,
+            infinity: self.infinity,
+        }
+    }
+
+    /// Lift to Jacobian coordinates
+    pub fn to_jacobian(self) -> Jacobian<C> {
+        if self.infinity {
+            Jacobian::identity()
+        } else {
+            Jacobian {
+                x: self.x,
+                y: self.y,
+                z: C::Base::one(),
+            }
+        }
+    }
+}
+
+impl<C: SwCurve> Jacobian<C> {
+    /// The point at infinity
+    pub fn identity() -> Self {
+        Jacobian {
+            x: C::Base::one(),
+            y: C::Base::one(),
+            z: C::Base::zero(),
+        }
+    }
+
+    /// Whether this is the point at infinity
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Convert to affine coordinates (one field inversion)
+    pub fn to_affine(self) -> Affine<C> {
+        match self.z.inverse() {
+            None => Affine::identity(),
+            Some(zinv) => {
+                let zinv2 = zinv.square();
+                Affine::new(self.x * zinv2, self.y * zinv2 * zinv)
+            }
+        }
+    }
+
+    /// Convert many points to affine, splitting them across worker threads
+    /// with one batched inversion of the `Z` coordinates per chunk
+    ///
+    /// Points at infinity are skipped by the inversion and come back as
+    /// [`Affine::identity`].
+    pub fn batch_normalize(points: &[Self]) -> Vec<Affine<C>> {
+        let mut out = vec![Affine::identity(); points.len()];
+        crate::parallel::par_chunks_mut(
+            &mut out,
+            1,
+            NORMALIZE_PARALLEL_THRESHOLD,
+            |offset, chunk| {
+                let points = &points[offset..offset + chunk.len()];
+                let mut zinv: Vec<C::Base> = points.iter().map(|p| p.z).collect();
+                crate::field::batch_invert_serial(&mut zinv);
+                for ((out, p), zinv) in chunk.iter_mut().zip(points).zip(zinv) {
+                    if !p.is_identity() {
+                        let zinv2 = zinv.square();
+                        *out = Affine::new(p.x * zinv2, p.y * zinv2 * zinv);
+                    }
+                }
+            },
+        );
+        out
+    }
+
+    /// Negation
+    pub fn neg(&self) -> Self {
+        Jacobian {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+
+    /// Point doubling (`dbl-2009-l`)
+    pub fn double(&self) -> Self {
+        if self.is_identity() {
+            return *self;
+        }
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = ((self.x + b).square() - a - c).double();
+        let e = a.double() + a;
+        let f = e.square();
+        let x3 = f - d.double();
+        let y3 = e * (d - x3) - c.double().double().double();
+        let z3 = (self.y * self.z).double();
+        Jacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Point addition (`add-2007-bl`), handling doubling and inverse inputs
+    pub fn add(&self, other: &Self) -> Self {
+        if self.is_identity() {
+            return *other;
+        }
+        if other.is_identity() {
+            return *self;
+        }
+        let z1z1 = self.z.square();
+        let z2z2 = other.z.square();
+        let u1 = self.x * z2z2;
+        let u2 = other.x * z1z1;
+        let s1 = self.y * other.z * z2z2;
+        let s2 = other.y * self.z * z1z1;
+        let h = u2 - u1;
+        let r = (s2 - s1).double();
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double()
+            } else {
+                Self::identity()
+            };
+        }
+        let i = h.double().square();
+        let j = h * i;
+        let v = u1 * i;
+        let x3 = r.square() - j - v.double();
+        let y3 = r * (v - x3) - (s1 * j).double();
+        let z3 = ((self.z + other.z).square() - z1z1 - z2z2) * h;
+        Jacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Mixed addition with an affine point (`madd-2007-bl`)
+    pub fn add_affine(&self, other: &Affine<C>) -> Self {
+        if other.infinity {
+            return *self;
+        }
+        if self.is_identity() {
+            return other.to_jacobian();
+        }
+        let z1z1 = self.z.square();
+        let u2 = other.x * z1z1;
+        let s2 = other.y * self.z * z1z1;
+        let h = u2 - self.x;
+        let r = (s2 - self.y).double();
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double()
+            } else {
+                Self::identity()
+            };
+        }
+        let hh = h.square();
+        let i = hh.double().double();
+        let j = h * i;
+        let v = self.x * i;
+        let x3 = r.square() - j - v.double();
+        let y3 = r * (v - x3) - (self.y * j).double();
+        let z3 = (self.z + h).square() - z1z1 - hh;
+        Jacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Scalar multiplication by a big-endian byte string (double-and-add)
+    pub fn mul_be_bytes(&self, scalar: &[u8]) -> Self {
+        let mut acc = Self::identity();
+        for byte in scalar {
+            for bit in (0..8).rev() {
+                acc = acc.double();
+                if (byte >> bit) & 1 == 1 {
+                    acc = acc.add(self);
+                }
+            }
+        }
+        acc
+    }
+
+    /// Scalar multiplication by little-endian 64-bit limbs (double-and-add)
+    pub fn mul_limbs(&self, scalar: &[u64]) -> Self {
+        let mut acc = Self::identity();
+        for limb in scalar.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc.double();
+                if (limb >> bit) & 1 == 1 {
+                    acc = acc.add(self);
+                }
+            }
+        }
+        acc
+    }
+}
+
+/// A point in homogeneous projective coordinates `(X : Y : Z)`, the affine
+/// point `(X / Z, Y / Z)`, for the complete formulas of [`Jacobian::mul_ct`]
+#[derive(Clone, Copy)]
+struct Homogeneous<F> {
+    x: F,
+    y: F,
+    z: F,
+}
+
+impl<F: ConstantTimeField> Homogeneous<F> {
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        F::conditional_swap(&mut a.x, &mut b.x, choice);
+        F::conditional_swap(&mut a.y, &mut b.y, choice);
+        F::conditional_swap(&mut a.z, &mut b.z, choice);
+    }
+
+    /// Complete addition for `a = 0` (Renes-Costello-Batina, ePrint 2015/1060,
+    /// Algorithm 7), with `b3 = 3b`
+    fn add(&self, other: &Self, b3: &F) -> Self {
+        let (x1, y1, z1) = (self.x, self.y, self.z);
+        let (x2, y2, z2) = (other.x, other.y, other.z);
+        let t0 = x1.mul_ct(&x2);
+        let t1 = y1.mul_ct(&y2);
+        let t2 = z1.mul_ct(&z2);
+        let t3 = x1.add_ct(&y1).mul_ct(&x2.add_ct(&y2));
+        let t3 = t3.sub_ct(&t0.add_ct(&t1));
+        let t4 = y1.add_ct(&z1).mul_ct(&y2.add_ct(&z2));
+        let t4 = t4.sub_ct(&t1.add_ct(&t2));
+        let y3 = x1.add_ct(&z1).mul_ct(&x2.add_ct(&z2));
+        let y3 = y3.sub_ct(&t0.add_ct(&t2));
+        let t0 = t0.add_ct(&t0).add_ct(&t0);
+        let t2 = b3.mul_ct(&t2);
+        let z3 = t1.add_ct(&t2);
+        let t1 = t1.sub_ct(&t2);
+        let y3 = b3.mul_ct(&y3);
+        let x3 = t3.mul_ct(&t1).sub_ct(&t4.mul_ct(&y3));
+        let y3 = t1.mul_ct(&z3).add_ct(&y3.mul_ct(&t0));
+        let z3 = z3.mul_ct(&t4).add_ct(&t0.mul_ct(&t3));
+        Homogeneous {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Complete doubling for `a = 0` (ePrint 2015/1060, Algorithm 9)
+    fn double(&self, b3: &F) -> Self {
+        let (x, y, z) = (self.x, self.y, self.z);
+        let t0 = y.mul_ct(&y);
+        let z3 = t0.add_ct(&t0);
+        let z3 = z3.add_ct(&z3);
+        let z3 = z3.add_ct(&z3);
+        let t1 = y.mul_ct(&z);
+        let t2 = b3.mul_ct(&z.mul_ct(&z));
+        let x3 = t2.mul_ct(&z3);
+        let y3 = t0.add_ct(&t2);
+        let z3 = t1.mul_ct(&z3);
+        let t2 = t2.add_ct(&t2).add_ct(&t2);
+        let t0 = t0.sub_ct(&t2);
+        let y3 = x3.add_ct(&t0.mul_ct(&y3));
+        let x3 = t0.mul_ct(&x.mul_ct(&y));
+        Homogeneous {
+            x: x3.add_ct(&x3),
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+impl<C: SwCurve> Jacobian<C>
+where
+    C::Base: ConstantTimeField,
+{
+    /// Scalar multiplication by little-endian 64-bit limbs without
+    /// scalar-dependent branches
+    ///
+    /// A Montgomery ladder over all `64 * scalar.len()` bits, using complete
+    /// formulas (none of the curves here has a point of order two) and
+    /// constant-time swaps, so the operation sequence depends only on the
+    /// scalar's length. The point itself is treated as public.
+    pub fn mul_ct(&self, scalar: &[u64]) -> Self {
+        self.ladder(
+            scalar
+                .iter()
+                .rev()
+                .flat_map(|limb| (0..64).rev().map(move |bit| (limb >> bit) as u8 & 1)),
+        )
+    }
+
+    /// [`Jacobian::mul_ct`] for a big-endian byte string
+    pub fn mul_be_bytes_ct(&self, scalar: &[u8]) -> Self {
+        self.ladder(
+            scalar
+                .iter()
+                .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1)),
+        )
+    }
+
+    /// Montgomery ladder over `bits`, most significant first
+    fn ladder(&self, bits: impl Iterator<Item = u8>) -> Self {
+        let b = C::coeff_b();
+        let b3 = b.add_ct(&b).add_ct(&b);
+        // (X, Y, Z) -> (X Z, Y, Z^3), mapping infinity to (0 : 1 : 0)
+        let infinity = Choice::from(self.is_identity() as u8);
+        let mut r1 = Homogeneous {
+            x: self.x.mul_ct(&self.z),
+            y: C::Base::conditional_select(&self.y, &C::Base::one(), infinity),
+            z: self.z.mul_ct(&self.z).mul_ct(&self.z),
+        };
+        let mut r0 = Homogeneous {
+            x: C::Base::zero(),
+            y: C::Base::one(),
+            z: C::Base::zero(),
+        };
+        for bit in bits {
+            let swap = Choice::from(bit);
+            Homogeneous::conditional_swap(&mut r0, &mut r1, swap);
+            r1 = r0.add(&r1, &b3);
+            r0 = r0.double(&b3);
+            Homogeneous::conditional_swap(&mut r0, &mut r1, swap);
+        }
+        // (X, Y, Z) -> (X Z, Y Z^2, Z)
+        Jacobian {
+            x: r0.x.mul_ct(&r0.z),
+            y: r0.y.mul_ct(&r0.z).mul_ct(&r0.z),
+            z: r0.z,
+        }
+    }
+}
+
+impl<C: SwCurve> PartialEq for Jacobian<C> {
+    /// Projective equality: `X1 Z2^2 == X2 Z1^2` and `Y1 Z2^3 == Y2 Z1^3`
+    fn eq(&self, other: &Self) -> bool {
+        match (self.is_identity(), other.is_identity()) {
+            (true, true) => true,
+            (false, false) => {
+                let z1z1 = self.z.square();
+                let z2z2 = other.z.square();
+                self.x * z2z2 == other.x * z1z1
+                    && self.y * z2z2 * other.z == other.y * z1z1 * self.z
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<C: SwCurve> Eq for Jacobian<C> {}
+
+impl<C: SwCurve> PartialEq for Affine<C> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.infinity, other.infinity) {
+            (true, true) => true,
+            (false, false) => self.x == other.x && self.y == other.y,
+            _ => false,
+        }
+    }
+}
+
+impl<C: SwCurve> Eq for Affine<C> {}
+
+/// Decode packed `(X, Y, Z)` triples of `size`-byte coordinates, normalize
+/// them and check the results are on the curve
+///
+/// `curve` names the curve in errors, such as `"BN254"`.
+pub(crate) fn decode_jacobian_batch<C: SwCurve>(
+    bytes: &[u8],
+    size: usize,
+    curve: &str,
+    coord: impl Fn(&[u8]) -> Option<C::Base>,
+) -> Result<Vec<Affine<C>>> {
+    let name = "points";
+    let points = crate::zero_copy::elements(bytes, 3 * size, name)?
+        .enumerate()
+        .map(|(i, p)| {
+            let mut xyz = p.chunks_exact(size).zip(["x", "y", "z"]).map(|(c, axis)| {
+                coord(c).ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!(
+                            "{name}[{i}]: {axis} coordinate is not below the {curve} base field modulus"
+                        ),
+                    )
+                })
+            });
+            Ok(Jacobian {
+                x: xyz.next().unwrap()?,
+                y: xyz.next().unwrap()?,
+                z: xyz.next().unwrap()?,
+            })
+        })
+        .collect::<Result<Vec<Jacobian<C>>>>()?;
+    let affine = Jacobian::batch_normalize(&points);
+    if let Some(i) = affine.iter().position(|p| !p.is_on_curve()) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}[{i}]: point is not on the {curve} G1 curve"),
+        ));
+    }
+    Ok(affine)
+}
+
+/// Curves the `g1_*_batch` functions accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum G1Curve {
+    Bn254,
+    Bls12_381,
+}
+
+impl G1Curve {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "bn254" => Ok(G1Curve::Bn254),
+            "bls12-381" => Ok(G1Curve::Bls12_381),
+            _ => Err(Error::new(
+                Status::InvalidArg,
+                format!("curve: unknown curve \"{name}\", expected bn254 or bls12-381"),
+            )),
+        }
+    }
+}
+
+/// Compress each point with `compress` into packed `size`-byte encodings,
+/// across worker threads
+pub(crate) fn compress_batch<C: SwCurve>(
+    points: &[Affine<C>],
+    size: usize,
+    compress: impl Fn(&Affine<C>) -> Vec<u8> + Sync,
+) -> Vec<u8> {
+    let mut out = vec![0u8; points.len() * size];
+    crate::parallel::par_chunks_mut(
+        &mut out,
+        size,
+        crate::field::ELEMENTWISE_PARALLEL_THRESHOLD * size,
+        |offset, chunk| {
+            let points = &points[offset / size..];
+            for (p, encoded) in points.iter().zip(chunk.chunks_exact_mut(size)) {
+                encoded.copy_from_slice(&compress(p));
+            }
+        },
+    );
+    out
+}
+
+/// Decode packed `size`-byte compressed points with `decompress` across
+/// worker threads, failing on the lowest-indexed invalid point
+pub(crate) fn decompress_batch<C: SwCurve>(
+    bytes: &[u8],
+    size: usize,
+    decompress: impl Fn(&[u8], &str) -> Result<Affine<C>> + Sync,
+) -> Result<Vec<Affine<C>>> {
+    let name = "compressed";
+    let encoded: Vec<&[u8]> = crate::zero_copy::elements(bytes, size, name)?.collect();
+    let mut points: Vec<Result<Affine<C>>> =
+        encoded.iter().map(|_| Ok(Affine::identity())).collect();
+    crate::parallel::par_chunks_mut(
+        &mut points,
+        1,
+        DECOMPRESS_PARALLEL_THRESHOLD,
+        |offset, chunk| {
+            for (i, point) in chunk.iter_mut().enumerate() {
+                let index = offset + i;
+                *point = decompress(encoded[index], &format!("{name}[{index}]"));
+            }
+        },
+    );
+    points.into_iter().collect()
+}
+
+/// Flag the packed `size`-byte points that `in_subgroup` rejects, across
+/// worker threads
+pub(crate) fn subgroup_check_batch(
+    points: &[u8],
+    size: usize,
+    stop_at_first: bool,
+    in_subgroup: impl Fn(&[u8]) -> bool + Sync,
+) -> Result<ValidationResult> {
+    // Only for its length check; the scan splits the points itself
+    let _ = crate::zero_copy::elements(points, size, "points")?;
+    let scanned = crate::validate::scan(
+        points,
+        size,
+        SUBGROUP_PARALLEL_THRESHOLD,
+        stop_at_first,
+        |p| !in_subgroup(p),
+    );
+    Ok(ValidationResult::from_scan(scanned, stop_at_first))
+}
+
+/// Normalize packed Jacobian G1 points of `curve` to its affine batch layout
+pub fn g1_jacobian_to_affine(points: &[u8], curve: &str) -> Result<Vec<u8>> {
+    match G1Curve::parse(curve)? {
+        G1Curve::Bn254 => crate::bn254::g1_jacobian_to_affine_batch(points),
+        G1Curve::Bls12_381 => crate::bls12_381::g1_jacobian_to_affine_batch(points),
+    }
+}
+
+/// Compress packed G1 points of `curve` from its affine batch layout
+pub fn g1_compress(points: &[u8], curve: &str) -> Result<Vec<u8>> {
+    match G1Curve::parse(curve)? {
+        G1Curve::Bn254 => crate::bn254::g1_compress_batch(points),
+        G1Curve::Bls12_381 => crate::bls12_381::g1_compress_batch(points),
+    }
+}
+
+/// Decompress packed G1 points of `curve` to its affine batch layout
+pub fn g1_decompress(compressed: &[u8], curve: &str) -> Result<Vec<u8>> {
+    match G1Curve::parse(curve)? {
+        G1Curve::Bn254 => crate::bn254::g1_decompress_batch(compressed),
+        G1Curve::Bls12_381 => crate::bls12_381::g1_decompress_batch(compressed),
+    }
+}
+
+/// Convert packed Jacobian `(X, Y, Z)` G1 points to affine
+///
+/// `curve` is `"bn254"`, with 96-byte triples of little-endian coordinates
+/// and 64-byte results as in `bn254_g1_add_batch`, or `"bls12-381"`, with
+/// 144-byte triples of big-endian coordinates and 96-byte ZCash-serialized
+/// results as in
+/// `bls12_381_g1_add_batch`. `Z = 0` gives the infinity encoding
+/// whatever `X` and `Y` are. All `Z` are inverted together, one batch per
+/// worker thread. Coordinates `>= p` and points off the curve throw.
+#[napi]
+pub fn g1_jacobian_to_affine_batch(points: Buffer, curve: String) -> Result<Buffer> {
+    g1_jacobian_to_affine(&points, &curve).map(Buffer::from)
+}
+
+/// Compress packed G1 points
+///
+/// `points` uses the layout of [`g1_jacobian_to_affine_batch`]'s results.
+/// `"bn254"` gives 33-byte points as `bn254_g1_compress` does: a flag byte
+/// with arkworks' flag bits (0x80 when `y` is the larger of `y` and `-y`,
+/// 0x40 for infinity), then `x` big-endian. `"bls12-381"` gives 48-byte
+/// ZCash-compressed points, the encoding `bls_public_key` returns. Points
+/// off the curve throw with their index.
+#[napi]
+pub fn g1_compress_batch(points: Buffer, curve: String) -> Result<Buffer> {
+    g1_compress(&points, &curve).map(Buffer::from)
+}
+
+/// Decompress packed G1 points compressed by [`g1_compress_batch`]
+///
+/// The square roots recovering `y` are spread across worker threads. An
+/// `x` with no point on the curve, bad flag bits and coordinates `>= p`
+/// throw with the index of the first such point. BLS12-381 points must
+/// also lie in the prime-order subgroup, as the ZCash format requires.
+#[napi]
+pub fn g1_decompress_batch(compressed: Buffer, curve: String) -> Result<Buffer> {
+    g1_decompress(&compressed, &curve).map(Buffer::from)
+}
+
+/// Check packed G1 points of `curve` in its affine batch layout for
+/// subgroup membership
+pub fn g1_subgroup_check(
+    points: &[u8],
+    curve: &str,
+    stop_at_first: bool,
+) -> Result<ValidationResult> {
+    match G1Curve::parse(curve)? {
+        G1Curve::Bn254 => crate::bn254::g1_subgroup_check_batch(points, stop_at_first),
+        G1Curve::Bls12_381 => crate::bls12_381::g1_subgroup_check_batch(points, stop_at_first),
+    }
+}
+
+/// Check packed uncompressed G2 points of `curve` for subgroup membership
+pub fn g2_subgroup_check(
+    points: &[u8],
+    curve: &str,
+    stop_at_first: bool,
+) -> Result<ValidationResult> {
+    match G1Curve::parse(curve)? {
+        G1Curve::Bn254 => crate::bn254::g2_subgroup_check_batch(points, stop_at_first),
+        G1Curve::Bls12_381 => crate::bls12_381::g2_subgroup_check_batch(points, stop_at_first),
+    }
+}
+
+/// Check that packed G1 points lie in the prime-order subgroup
+///
+/// `points` uses the layout of [`g1_jacobian_to_affine_batch`]'s results.
+/// BN254 G1 has cofactor 1, so there every point on the curve passes.
+/// BLS12-381 points are checked with the GLV endomorphism `(β x, y)`, which
+/// acts as `[-x^2]` on the subgroup only. Points that are off the curve or
+/// badly encoded fail like points outside the subgroup; only a length that
+/// is not a multiple of the point size throws. Every point is checked and
+/// counted unless `stop_at_first` is set, which returns once the first
+/// failure is known and leaves `count_bad` out. Points are spread across
+/// worker threads.
+#[napi]
+pub fn g1_subgroup_check_batch(
+    points: Buffer,
+    curve: String,
+    stop_at_first: Option<bool>,
+) -> Result<ValidationResult> {
+    g1_subgroup_check(&points, &curve, stop_at_first.unwrap_or(false))
+}
+
+/// Check that packed uncompressed G2 points lie in the prime-order subgroup
+///
+/// `"bn254"` points are the 128-byte precompile encoding of
+/// `bn254_g2_add`, and `"bls12-381"` points the 192-byte encoding of
+/// `bls12_381_pairing`; all zeros is infinity in both. Each is checked
+/// with the untwist-Frobenius-twist endomorphism `ψ`: `ψ(P) = [6 x^2] P`
+/// on BN254 and `ψ(P) = [x] P` on BLS12-381 hold exactly on the subgroup.
+/// Failures and `stop_at_first` behave as in [`g1_subgroup_check_batch`].
+#[napi]
+pub fn g2_subgroup_check_batch(
+    points: Buffer,
+    curve: String,
+    stop_at_first: Option<bool>,
+) -> Result<ValidationResult> {
+    g2_subgroup_check(&points, &curve, stop_at_first.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::{self, FP_BYTES};
+    use crate::bn254::{self, Fq};
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// `G, 2G, 3G, ...` with the listed indices at infinity, and a random
+    /// Jacobian representative of each: `(x z^2, y z^3, z)`, or `Z = 0`
+    /// with junk `X` and `Y` for infinity
+    fn representatives<C: SwCurve>(
+        g: Affine<C>,
+        zs: &[C::Base],
+        infinities: &[usize],
+    ) -> (Vec<Affine<C>>, Vec<Jacobian<C>>) {
+        let mut acc = g.to_jacobian();
+        let mut affine = Vec::new();
+        let mut jacobian = Vec::new();
+        for (i, &z) in zs.iter().enumerate() {
+            if infinities.contains(&i) {
+                affine.push(Affine::identity());
+                jacobian.push(Jacobian {
+                    x: z,
+                    y: z.square(),
+                    z: C::Base::zero(),
+                });
+            } else {
+                let p = acc.to_affine();
+                let z2 = z.square();
+                affine.push(p);
+                jacobian.push(Jacobian {
+                    x: p.x * z2,
+                    y: p.y * z2 * z,
+                    z,
+                });
+            }
+            acc = acc.add_affine(&g);
+        }
+        (affine, jacobian)
+    }
+
+    #[test]
+    fn test_jacobian_to_affine_round_trip() {
+        let n = NORMALIZE_PARALLEL_THRESHOLD + 37;
+        let seed = [5; SEED_BYTES];
+        let zs: Vec<Fq> = random_elements(FieldName::Bn254Fq, n, Some(&seed))
+            .unwrap()
+            .chunks_exact(32)
+            .map(|b| Fq::from_le_bytes(b).unwrap())
+            .collect();
+        let infinities = [0, 3, 4, 500, n - 1];
+        let (affine, jacobian) = representatives(bn254::g1_generator(), &zs, &infinities);
+        assert_eq!(Jacobian::batch_normalize(&jacobian), affine);
+        let bytes: Vec<u8> = jacobian
+            .iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .flat_map(Fq::to_le_bytes)
+            .collect();
+        let expected: Vec<u8> = affine
+            .iter()
+            .flat_map(|p| match p.infinity {
+                true => vec![0; 64],
+                false => [p.x.to_le_bytes(), p.y.to_le_bytes()].concat(),
+            })
+            .collect();
+        assert_eq!(g1_jacobian_to_affine(&bytes, "bn254").unwrap(), expected);
+
+        let zs: Vec<bls12_381::Fp> = random_elements(FieldName::Bls12_381Fp, 20, Some(&seed))
+            .unwrap()
+            .chunks_exact(FP_BYTES)
+            .map(|b| bls12_381::Fp::from_le_bytes(b).unwrap())
+            .collect();
+        let (affine, jacobian) = representatives(bls12_381::g1_generator(), &zs, &[1, 2, 19]);
+        let bytes: Vec<u8> = jacobian
+            .iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .flat_map(bls12_381::Fp::to_be_bytes)
+            .collect();
+        let expected: Vec<u8> = affine
+            .iter()
+            .flat_map(|p| {
+                let mut encoded = bls12_381::encode_g1(p);
+                encoded[0] |= 0x40 * p.infinity as u8;
+                encoded
+            })
+            .collect();
+        assert_eq!(
+            g1_jacobian_to_affine(&bytes, "bls12-381").unwrap(),
+            expected
+        );
+        assert!(g1_jacobian_to_affine(&[], "bn254").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_jacobian_to_affine_rejects_bad_input() {
+        let reason =
+            |bytes: &[u8], curve: &str| g1_jacobian_to_affine(bytes, curve).unwrap_err().reason;
+        let g = bn254::g1_generator();
+        let mut point = [g.x, g.y, Fq::one()].map(Fq::to_le_bytes).concat();
+        assert_eq!(
+            reason(&point, "secp256k1"),
+            "curve: unknown curve \"secp256k1\", expected bn254 or bls12-381"
+        );
+        assert_eq!(
+            reason(&point[1..], "bn254"),
+            "points: length 95 is not a multiple of 96 bytes"
+        );
+        let mut twice = point.clone();
+        twice[32] ^= 1;
+        twice.extend(&point);
+        assert_eq!(
+            reason(&twice, "bn254"),
+            "points[0]: point is not on the BN254 G1 curve"
+        );
+        point[64..].fill(0xff);
+        assert_eq!(
+            reason(&point, "bn254"),
+            "points[0]: z coordinate is not below the BN254 base field modulus"
+        );
+        assert_eq!(
+            reason(&[0xff; 3 * FP_BYTES], "bls12-381"),
+            "points[0]: x coordinate is not below the BLS12-381 base field modulus"
+        );
+    }
+
+    /// `G, 2G, 3G, ...` for `n` points, then the point at infinity
+    fn multiples<C: SwCurve>(g: Affine<C>, n: usize) -> Vec<Affine<C>> {
+        let mut acc = Jacobian::identity();
+        let mut points: Vec<Jacobian<C>> = (0..n)
+            .map(|_| {
+                acc = acc.add_affine(&g);
+                acc.mul_limbs(&[0x9e3779b97f4a7c15])
+            })
+            .collect();
+        points.push(Jacobian::identity());
+        Jacobian::batch_normalize(&points)
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let n = DECOMPRESS_PARALLEL_THRESHOLD + 9;
+        let bn254_points: Vec<u8> = multiples(bn254::g1_generator(), n)
+            .iter()
+            .flat_map(|p| match p.infinity {
+                true => vec![0; 64],
+                false => [p.x.to_le_bytes(), p.y.to_le_bytes()].concat(),
+            })
+            .collect();
+        let bls_points: Vec<u8> = multiples(bls12_381::g1_generator(), n)
+            .iter()
+            .flat_map(|p| {
+                let mut encoded = bls12_381::encode_g1(p);
+                encoded[0] |= 0x40 * p.infinity as u8;
+                encoded
+            })
+            .collect();
+        for (curve, points, size) in [("bn254", bn254_points, 33), ("bls12-381", bls_points, 48)] {
+            let compressed = g1_compress(&points, curve).unwrap();
+            assert_eq!(compressed.len(), (n + 1) * size, "{curve}");
+            assert_eq!(
+                g1_decompress(&compressed, curve).unwrap(),
+                points,
+                "{curve}"
+            );
+            assert!(g1_compress(&[], curve).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_compressed_generators() {
+        // BN254 carries arkworks' flags in a separate first byte
+        let g = bn254::g1_generator();
+        let mut g_le = [g.x.to_le_bytes(), g.y.to_le_bytes()].concat();
+        g_le.extend([g.x.to_le_bytes(), (-g.y).to_le_bytes()].concat());
+        g_le.extend([0; 64]);
+        let mut expected = vec![0u8; 3 * 33];
+        expected[32] = 1;
+        expected[33] = 0x80;
+        expected[65] = 1;
+        expected[66] = 0x40;
+        assert_eq!(g1_compress(&g_le, "bn254").unwrap(), expected);
+
+        // The ZCash encodings of the BLS12-381 generator and infinity
+        let g = bls12_381::encode_g1(&bls12_381::g1_generator());
+        let mut expected = hex(
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac58\
+             6c55e83ff97a1aeffb3af00adb22c6bb",
+        );
+        expected.push(0xc0);
+        expected.extend([0; 47]);
+        let mut points = g.clone();
+        points.push(0x40);
+        points.extend([0; 95]);
+        assert_eq!(g1_compress(&points, "bls12-381").unwrap(), expected);
+        assert_eq!(g1_decompress(&expected, "bls12-381").unwrap(), points);
+    }
+
+    #[test]
+    fn test_compress_matches_arkworks() {
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_serialize::CanonicalSerialize;
+
+        let g = ark_bn254::G1Affine::generator();
+        let mut scalar = ark_bn254::Fr::from(0x1234_5678u64);
+        for _ in 0..20 {
+            scalar = scalar * scalar + ark_bn254::Fr::from(7u64);
+            let p = (g * scalar).into_affine();
+            let mut ark = Vec::new();
+            p.serialize_compressed(&mut ark).unwrap();
+            let mut uncompressed = Vec::new();
+            p.x.serialize_uncompressed(&mut uncompressed).unwrap();
+            p.y.serialize_uncompressed(&mut uncompressed).unwrap();
+
+            // arkworks packs the flags into the top bits of little-endian x
+            let mut expected = vec![ark[31] & 0xc0];
+            ark[31] &= 0x3f;
+            expected.extend(ark.iter().rev());
+            assert_eq!(g1_compress(&uncompressed, "bn254").unwrap(), expected);
+            assert_eq!(g1_decompress(&expected, "bn254").unwrap(), uncompressed);
+        }
+    }
+
+    #[test]
+    fn test_decompress_rejects_bad_points() {
+        let reason = |bytes: &[u8], curve: &str| g1_decompress(bytes, curve).unwrap_err().reason;
+        let g = bn254::compress_g1(&bn254::g1_generator());
+        // The first x with no point above it
+        let x = (0u64..)
+            .map(Fq::from_u64)
+            .find(|x| {
+                (x.square() * *x + Fq::from_u64(3))
+                    .sqrt(-Fq::one())
+                    .is_none()
+            })
+            .unwrap();
+        let mut batch = g.repeat(DECOMPRESS_PARALLEL_THRESHOLD + 3);
+        let mut off_curve = vec![0];
+        off_curve.extend(x.to_be_bytes());
+        batch[33 * 200..33 * 201].copy_from_slice(&off_curve);
+        batch[33 * 250] = 0x01;
+        assert_eq!(
+            reason(&batch, "bn254"),
+            "compressed[200]: point is not on the BN254 G1 curve"
+        );
+        assert_eq!(
+            reason(&batch[1..], "bn254"),
+            "compressed: length 8546 is not a multiple of 33 bytes"
+        );
+        assert_eq!(
+            reason(&[0xff; 33], "bn254"),
+            "compressed[0]: unknown flag bits set in the first byte"
+        );
+        assert_eq!(
+            reason(&g, "pasta"),
+            "curve: unknown curve \"pasta\", expected bn254 or bls12-381"
+        );
+
+        let mut bls = bls12_381::encode_g1(&bls12_381::g1_generator());
+        bls.truncate(48);
+        let mut batch = crate::bls_sig::compress_g1(&bls12_381::g1_generator());
+        batch.extend(&bls);
+        assert_eq!(
+            reason(&batch, "bls12-381"),
+            "compressed[1]: compression flag is not set"
+        );
+        let err = g1_compress(&bls12_381::encode_g1(&Affine::identity()), "bls12-381");
+        assert_eq!(
+            err.unwrap_err().reason,
+            "points[0]: all-zero encoding; infinity needs the infinity flag"
+        );
+    }
+
+    /// Points above those of `n` random x-coordinates that have one: on the
+    /// curve, and for these curves almost never in the subgroup. Each x is
+    /// decoded by `base` from `degree` elements of `field`.
+    fn random_points<C: SwCurve>(
+        field: FieldName,
+        degree: usize,
+        n: usize,
+        base: impl Fn(&[u8]) -> C::Base,
+        sqrt: impl Fn(C::Base) -> Option<C::Base>,
+    ) -> Vec<Affine<C>> {
+        let bytes = random_elements(field, degree * n, Some(&[9; SEED_BYTES])).unwrap();
+        bytes
+            .chunks_exact(degree * field.element_bytes())
+            .filter_map(|b| {
+                let x = base(b);
+                sqrt(x.square() * x + C::coeff_b()).map(|y| Affine::new(x, y))
+            })
+            .collect()
+    }
+
+    /// Subgroup membership the slow way, by multiplying by the group order
+    fn in_subgroup_slow<C: SwCurve>(p: &Affine<C>, order: &[u64]) -> bool {
+        p.to_jacobian().mul_limbs(order).is_identity()
+    }
+
+    fn bls12_381_fp2(b: &[u8]) -> bls12_381::Fp2 {
+        let fp = |b: &[u8]| bls12_381::Fp::from_le_bytes(b).unwrap();
+        bls12_381::Fp2::new(fp(&b[..FP_BYTES]), fp(&b[FP_BYTES..]))
+    }
+
+    fn bn254_fq2(b: &[u8]) -> bn254::Fq2 {
+        bn254::Fq2::new(
+            Fq::from_le_bytes(&b[..32]).unwrap(),
+            Fq::from_le_bytes(&b[32..]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_bls12_381_g1_subgroup_check() {
+        use crate::montgomery::MontConfig;
+        let r = bls12_381::FrConfig::MODULUS;
+        let cofactor = [0x8c00aaab0000aaab, 0x396c8c005555e156];
+        let outside = random_points::<bls12_381::G1Config>(
+            FieldName::Bls12_381Fp,
+            1,
+            16,
+            |b| bls12_381::Fp::from_le_bytes(b).unwrap(),
+            |v| v.sqrt(-bls12_381::Fp::one()),
+        );
+        assert!(outside.len() > 4);
+        let mut cleared = vec![];
+        let mut small_order = vec![];
+        for p in &outside {
+            // [h] P lands in the subgroup, and [r] P in the small subgroup
+            // of order dividing h that small-subgroup attacks use
+            cleared.push(p.to_jacobian().mul_limbs(&cofactor).to_affine());
+            small_order.push(p.to_jacobian().mul_limbs(&r).to_affine());
+        }
+        for (points, expected) in [(&outside, false), (&cleared, true), (&small_order, false)] {
+            for p in points {
+                assert!(!p.infinity && p.is_on_curve());
+                assert_eq!(in_subgroup_slow(p, &r), expected);
+                assert_eq!(bls12_381::g1_in_subgroup(p), expected);
+            }
+        }
+
+        let encode = |p: &bls12_381::G1Affine| match p.infinity {
+            true => [vec![0x40], vec![0; 95]].concat(),
+            false => bls12_381::encode_g1(p),
+        };
+        let mut points = multiples(bls12_381::g1_generator(), 99);
+        points.extend(&cleared);
+        let good: Vec<u8> = points.iter().flat_map(encode).collect();
+        let result = g1_subgroup_check(&good, "bls12-381", false).unwrap();
+        assert_eq!(
+            (result.valid, result.first_bad_index, result.count_bad),
+            (true, None, None)
+        );
+
+        let mut batch = good.clone();
+        let mut off_curve = encode(&outside[0]);
+        off_curve[95] ^= 1;
+        for (i, p) in [
+            (10, encode(&outside[0])),
+            (40, encode(&small_order[1])),
+            (41, off_curve),
+        ] {
+            batch[96 * i..96 * (i + 1)].copy_from_slice(&p);
+        }
+        let result = g1_subgroup_check(&batch, "bls12-381", false).unwrap();
+        assert_eq!(
+            (result.valid, result.first_bad_index, result.count_bad),
+            (false, Some(10), Some(3))
+        );
+        let result = g1_subgroup_check(&batch, "bls12-381", true).unwrap();
+        assert_eq!(
+            (result.valid, result.first_bad_index, result.count_bad),
+            (false, Some(10), None)
+        );
+    }
+
+    #[test]
+    fn test_g2_subgroup_check() {
+        use crate::montgomery::MontConfig;
+        let bls_outside = random_points::<bls12_381::G2Config>(
+            FieldName::Bls12_381Fp,
+            2,
+            16,
+            bls12_381_fp2,
+            |v| v.sqrt(),
+        );
+        let bn_outside =
+            random_points::<bn254::G2Config>(FieldName::Bn254Fq, 2, 16, bn254_fq2, |v| v.sqrt());
+        assert!(bls_outside.len() > 2 && bn_outside.len() > 2);
+        let bls_inside = multiples(bls12_381::g2_generator(), 20);
+        let bn_inside = multiples(bn254::g2_generator(), 20);
+        let bls_r = bls12_381::FrConfig::MODULUS;
+        let bn_r = bn254::FrConfig::MODULUS;
+        for p in bls_outside.iter().chain(&bls_inside) {
+            assert_eq!(bls12_381::g2_in_subgroup(p), in_subgroup_slow(p, &bls_r));
+        }
+        for p in bn_outside.iter().chain(&bn_inside) {
+            assert_eq!(bn254::g2_in_subgroup(p), in_subgroup_slow(p, &bn_r));
+        }
+        assert!(bls_inside.iter().all(bls12_381::g2_in_subgroup));
+        assert!(!bls_outside.iter().any(bls12_381::g2_in_subgroup));
+        assert!(!bn_outside.iter().any(bn254::g2_in_subgroup));
+
+        // Outside points at 3 and 7; the identity is last
+        let mut bls = bls_inside.clone();
+        let mut bn = bn_inside.clone();
+        for i in [3, 7] {
+            bls[i] = bls_outside[i % 2];
+            bn[i] = bn_outside[i % 2];
+        }
+        let bls: Vec<u8> = bls.iter().flat_map(bls12_381::encode_g2).collect();
+        let bn: Vec<u8> = bn.iter().flat_map(bn254::encode_g2).collect();
+        for (points, curve) in [(bls, "bls12-381"), (bn, "bn254")] {
+            let result = g2_subgroup_check(&points, curve, false).unwrap();
+            assert_eq!(
+                (result.valid, result.first_bad_index, result.count_bad),
+                (false, Some(3), Some(2)),
+                "{curve}"
+            );
+            let size = points.len() / 21;
+            let result = g2_subgroup_check(&points[..3 * size], curve, true).unwrap();
+            assert!(result.valid, "{curve}");
+            let err = g2_subgroup_check(&points[1..], curve, true).unwrap_err();
+            assert_eq!(
+                err.reason,
+                format!(
+                    "points: length {} is not a multiple of {size} bytes",
+                    points.len() - 1
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_bn254_g1_subgroup_check() {
+        // Cofactor 1: every point on the curve passes
+        let mut points: Vec<u8> = multiples(bn254::g1_generator(), 70)
+            .iter()
+            .flat_map(|p| match p.infinity {
+                true => vec![0; 64],
+                false => [p.x.to_le_bytes(), p.y.to_le_bytes()].concat(),
+            })
+            .collect();
+        assert!(g1_subgroup_check(&points, "bn254", false).unwrap().valid);
+        points[64 * 5] ^= 1;
+        points[64 * 66 + 63] = 0xff;
+        let result = g1_subgroup_check(&points, "bn254", false).unwrap();
+        assert_eq!(
+            (result.valid, result.first_bad_index, result.count_bad),
+            (false, Some(5), Some(2))
+        );
+        let err = g1_subgroup_check(&points, "secp256k1", false).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "curve: unknown curve \"secp256k1\", expected bn254 or bls12-381"
+        );
+    }
+}
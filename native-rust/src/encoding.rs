@@ -0,0 +1,460 @@
+//! Conversions between BN254 scalar field encodings
+//!
+//! Field elements are 32-byte big-endian canonical values, as everywhere in
+//! [`crate::bn254`]. They convert to and from JavaScript `BigInt`s, hex
+//! strings with or without a `0x` prefix, and decimal strings. Every parser
+//! rejects values that are not below the field prime instead of reducing
+//! them. Hex output is always `0x` followed by 64 lowercase digits.
+//!
+//! [`field_elements_from_bigints`] and [`field_elements_to_bigints`] convert
+//! whole arrays of `BigInt`s for any field `random_field_elements` supports,
+//! to and from its packed little-endian Buffers.
+
+use napi::bindgen_prelude::{BigInt, Buffer};
+use napi::{Env, Error, JsBigInt, Result, Status};
+use napi_derive::napi;
+
+use crate::bigint::{geq, mac};
+use crate::bn254::{parse_fr, Fr, FrConfig};
+use crate::montgomery::MontConfig;
+use crate::random::FieldName;
+use crate::validate::below;
+
+fn invalid(name: &str, reason: String) -> Error {
+    Error::new(Status::InvalidArg, format!("{name}: {reason}"))
+}
+
+/// Range-check little-endian limbs, naming `shown` in the error
+fn canonical(limbs: [u64; 4], name: &str, shown: &str) -> Result<Fr> {
+    Fr::from_canonical(limbs).ok_or_else(|| {
+        invalid(
+            name,
+            format!("{shown} is not below the BN254 scalar field modulus"),
+        )
+    })
+}
+
+/// Element with the value of a `BigInt`'s sign and little-endian words
+pub fn from_words(sign_bit: bool, words: &[u64]) -> Result<Fr> {
+    let len = words.iter().rposition(|&w| w != 0).map_or(0, |i| i + 1);
+    if sign_bit && len > 0 {
+        return Err(invalid("n", "expected a non-negative BigInt".to_string()));
+    }
+    if len > 4 {
+        return Err(invalid(
+            "n",
+            "value is not below the BN254 scalar field modulus".to_string(),
+        ));
+    }
+    let mut limbs = [0u64; 4];
+    limbs[..len].copy_from_slice(&words[..len]);
+    canonical(limbs, "n", "value")
+}
+
+/// Parse hex digits, with or without a `0x` prefix
+pub fn from_hex(hex: &str) -> Result<Fr> {
+    let digits = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    if digits.is_empty() {
+        return Err(invalid(
+            "hex",
+            "expected at least one hex digit".to_string(),
+        ));
+    }
+    let mut limbs = [0u64; 4];
+    for (i, c) in digits.chars().enumerate() {
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| invalid("hex", format!("invalid hex digit {c:?} at position {i}")))?;
+        if limbs[3] >> 60 != 0 {
+            return Err(invalid(
+                "hex",
+                format!("0x{digits} is not below the BN254 scalar field modulus"),
+            ));
+        }
+        for k in (1..4).rev() {
+            limbs[k] = (limbs[k] << 4) | (limbs[k - 1] >> 60);
+        }
+        limbs[0] = (limbs[0] << 4) | u64::from(digit);
+    }
+    canonical(limbs, "hex", &format!("0x{digits}"))
+}
+
+/// Parse a non-negative decimal integer
+pub fn from_decimal(dec: &str) -> Result<Fr> {
+    if dec.is_empty() {
+        return Err(invalid("dec", "expected at least one digit".to_string()));
+    }
+    let too_large = || {
+        invalid(
+            "dec",
+            format!("{dec} is not below the BN254 scalar field modulus"),
+        )
+    };
+    let mut limbs = [0u64; 4];
+    for (i, c) in dec.chars().enumerate() {
+        let digit = c.to_digit(10).ok_or_else(|| {
+            invalid(
+                "dec",
+                format!("invalid decimal digit {c:?} at position {i}"),
+            )
+        })?;
+        // limbs = limbs * 10 + digit
+        let mut carry = u64::from(digit);
+        for limb in limbs.iter_mut() {
+            (*limb, carry) = mac(0, *limb, 10, carry);
+        }
+        if carry != 0 || geq(&limbs, &FrConfig::MODULUS) {
+            return Err(too_large());
+        }
+    }
+    canonical(limbs, "dec", dec)
+}
+
+/// `0x` and 64 lowercase hex digits
+pub fn to_hex(value: Fr) -> String {
+    let digits: String = value
+        .to_be_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("0x{digits}")
+}
+
+/// Decimal digits without leading zeros
+pub fn to_decimal(value: Fr) -> String {
+    let mut limbs = value.to_canonical();
+    let mut digits = Vec::new();
+    loop {
+        // limbs /= 10, most significant limb first
+        let mut rem = 0u64;
+        for limb in limbs.iter_mut().rev() {
+            let acc = (u128::from(rem) << 64) | u128::from(*limb);
+            *limb = (acc / 10) as u64;
+            rem = (acc % 10) as u64;
+        }
+        digits.push(b'0' + rem as u8);
+        if limbs == [0; 4] {
+            break;
+        }
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// 32-byte big-endian field element from a non-negative `BigInt` below `p`
+#[napi]
+pub fn field_elem_from_bigint(mut n: JsBigInt) -> Result<Vec<u8>> {
+    let (sign_bit, words) = n.get_words()?;
+    Ok(from_words(sign_bit, &words)?.to_be_bytes())
+}
+
+/// `BigInt` value of a 32-byte big-endian field element
+#[napi]
+pub fn field_elem_to_bigint(env: Env, elem: Vec<u8>) -> Result<JsBigInt> {
+    let value = parse_fr(&elem, "elem")?;
+    env.create_bigint_from_words(false, value.to_canonical().to_vec())
+}
+
+/// 32-byte big-endian field element from hex, with or without `0x`
+#[napi]
+pub fn field_elem_from_hex(hex: String) -> Result<Vec<u8>> {
+    Ok(from_hex(&hex)?.to_be_bytes())
+}
+
+/// `0x`-prefixed, 64-digit lowercase hex of a 32-byte big-endian field element
+#[napi]
+pub fn field_elem_to_hex(elem: Vec<u8>) -> Result<String> {
+    Ok(to_hex(parse_fr(&elem, "elem")?))
+}
+
+/// 32-byte big-endian field element from a decimal string
+#[napi]
+pub fn field_elem_from_decimal(dec: String) -> Result<Vec<u8>> {
+    Ok(from_decimal(&dec)?.to_be_bytes())
+}
+
+/// Decimal string of a 32-byte big-endian field element
+#[napi]
+pub fn field_elem_to_decimal(elem: Vec<u8>) -> Result<String> {
+    Ok(to_decimal(parse_fr(&elem, "elem")?))
+}
+
+/// Little-endian encoding of a non-negative `BigInt` below the modulus of `field`
+fn element_from_words(
+    sign_bit: bool,
+    words: &[u64],
+    field: FieldName,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let len = words.iter().rposition(|&w| w != 0).map_or(0, |i| i + 1);
+    if sign_bit && len > 0 {
+        return Err(invalid(name, "expected a non-negative BigInt".to_string()));
+    }
+    let modulus = field.modulus_le_bytes();
+    let mut bytes: Vec<u8> = words[..len].iter().flat_map(|w| w.to_le_bytes()).collect();
+    let fits = bytes.iter().skip(modulus.len()).all(|&b| b == 0);
+    bytes.resize(modulus.len(), 0);
+    if !fits || !below(&bytes, &modulus) {
+        return Err(invalid(
+            name,
+            format!("value is not below the {} modulus", field.name()),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Pack `BigInt`s into little-endian elements of `field`
+pub fn elements_from_bigints(values: &[BigInt], field: FieldName) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(values.len() * field.element_bytes());
+    for (i, value) in values.iter().enumerate() {
+        out.extend(element_from_words(
+            value.sign_bit,
+            &value.words,
+            field,
+            &format!("values[{i}]"),
+        )?);
+    }
+    Ok(out)
+}
+
+/// Unpack little-endian elements of `field` into `BigInt`s
+pub fn elements_to_bigints(data: &[u8], field: FieldName) -> Result<Vec<BigInt>> {
+    let result = crate::validate::validate(data, field)?;
+    if let Some(i) = result.first_bad_index {
+        return Err(invalid(
+            "data",
+            format!("element {i} is not below the {} modulus", field.name()),
+        ));
+    }
+    Ok(data
+        .chunks_exact(field.element_bytes())
+        .map(|element| BigInt {
+            sign_bit: false,
+            words: element
+                .chunks(8)
+                .map(|word| {
+                    let mut le = [0u8; 8];
+                    le[..word.len()].copy_from_slice(word);
+                    u64::from_le_bytes(le)
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Packed little-endian elements of `field` from non-negative `BigInt`s
+///
+/// `field` is any name `random_field_elements` accepts, and the result uses
+/// its element size. Negative values and values `>= p` throw, naming the
+/// offending index.
+#[napi]
+pub fn field_elements_from_bigints(values: Vec<BigInt>, field: String) -> Result<Buffer> {
+    elements_from_bigints(&values, FieldName::parse(&field)?).map(Buffer::from)
+}
+
+/// `BigInt`s of packed little-endian elements of `field`
+///
+/// The inverse of [`field_elements_from_bigints`]. A length that is not a
+/// multiple of the element size throws, as does any element `>= p`.
+#[napi]
+pub fn field_elements_to_bigints(data: Buffer, field: String) -> Result<Vec<BigInt>> {
+    elements_to_bigints(&data, FieldName::parse(&field)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_elements, FieldName, SEED_BYTES};
+
+    const P_MINUS_ONE_DEC: &str =
+        "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+    const P_MINUS_ONE_HEX: &str =
+        "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000";
+
+    /// Every representation of `value` parses back to it
+    fn round_trip(value: Fr) {
+        let bytes = value.to_be_bytes();
+        let hex = field_elem_to_hex(bytes.clone()).unwrap();
+        assert_eq!(hex.len(), 66);
+        assert_eq!(field_elem_from_hex(hex.clone()).unwrap(), bytes);
+        assert_eq!(field_elem_from_hex(hex[2..].to_uppercase()).unwrap(), bytes);
+        let dec = field_elem_to_decimal(bytes.clone()).unwrap();
+        assert_eq!(field_elem_from_decimal(dec).unwrap(), bytes);
+        assert_eq!(from_words(false, &value.to_canonical()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let p_minus_one = -Fr::one();
+        for value in [Fr::zero(), Fr::one(), p_minus_one] {
+            round_trip(value);
+        }
+        let random = random_elements(FieldName::Bn254Fr, 200, Some(&[9; SEED_BYTES])).unwrap();
+        for chunk in random.chunks_exact(32) {
+            round_trip(Fr::from_le_bytes(chunk).unwrap());
+        }
+
+        assert_eq!(to_hex(Fr::zero()), format!("0x{}", "0".repeat(64)));
+        assert_eq!(to_decimal(Fr::zero()), "0");
+        assert_eq!(to_hex(p_minus_one), P_MINUS_ONE_HEX);
+        assert_eq!(to_decimal(p_minus_one), P_MINUS_ONE_DEC);
+        assert_eq!(from_decimal(P_MINUS_ONE_DEC).unwrap(), p_minus_one);
+        assert_eq!(from_hex(P_MINUS_ONE_HEX).unwrap(), p_minus_one);
+        // Leading zeros and surplus zero words are not part of the value
+        assert_eq!(
+            from_hex(&format!("0x{}1", "0".repeat(80))).unwrap(),
+            Fr::one()
+        );
+        assert_eq!(from_decimal("000042").unwrap(), Fr::from_u64(42));
+        assert_eq!(from_words(false, &[]).unwrap(), Fr::zero());
+        assert_eq!(from_words(true, &[0, 0]).unwrap(), Fr::zero());
+        assert_eq!(
+            from_words(false, &[5, 0, 0, 0, 0, 0]).unwrap(),
+            Fr::from_u64(5)
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_values() {
+        let p = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let err = from_decimal(p).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(
+            err.reason,
+            format!("dec: {p} is not below the BN254 scalar field modulus")
+        );
+        assert!(from_decimal(&"9".repeat(100)).is_err());
+        let err = from_hex("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+            .unwrap_err();
+        assert!(err
+            .reason
+            .ends_with("is not below the BN254 scalar field modulus"));
+        assert!(from_hex(&"f".repeat(65)).is_err());
+        assert!(from_words(false, &FrConfig::MODULUS).is_err());
+        assert!(from_words(false, &[0, 0, 0, 0, 1]).is_err());
+        assert_eq!(
+            from_words(true, &[1]).unwrap_err().reason,
+            "n: expected a non-negative BigInt"
+        );
+    }
+
+    fn big(words: &[u64]) -> BigInt {
+        BigInt {
+            sign_bit: false,
+            words: words.to_vec(),
+        }
+    }
+
+    /// Value of a little-endian byte string as `BigInt` words
+    fn words_of(le: &[u8]) -> Vec<u64> {
+        le.chunks(8)
+            .map(|w| {
+                let mut b = [0u8; 8];
+                b[..w.len()].copy_from_slice(w);
+                u64::from_le_bytes(b)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bigint_arrays_round_trip() {
+        for (name, field) in FieldName::ALL {
+            let p = field.modulus_le_bytes();
+            let mut p_minus_one = p.clone();
+            p_minus_one[0] -= 1;
+            let random = random_elements(field, 50, Some(&[3; SEED_BYTES])).unwrap();
+            let data = [vec![0; p.len()], p_minus_one.clone(), random].concat();
+
+            let values = elements_to_bigints(&data, field).unwrap();
+            assert_eq!(values.len(), 52, "{name}");
+            assert_eq!(values[1].words, words_of(&p_minus_one), "{name}");
+            assert!(values.iter().all(|v| !v.sign_bit));
+            assert_eq!(
+                elements_from_bigints(&values, field).unwrap(),
+                data,
+                "{name}"
+            );
+            let parsed = FieldName::parse(&name.to_uppercase()).unwrap();
+            assert_eq!(elements_from_bigints(&values, parsed).unwrap(), data);
+
+            let err = elements_from_bigints(&[big(&[1]), big(&words_of(&p))], field).unwrap_err();
+            assert_eq!(
+                err.reason,
+                format!("values[1]: value is not below the {name} modulus")
+            );
+            let mut bad = data.clone();
+            bad[2 * p.len()..3 * p.len()].copy_from_slice(&p);
+            assert_eq!(
+                elements_to_bigints(&bad, field).unwrap_err().reason,
+                format!("data: element 2 is not below the {name} modulus")
+            );
+        }
+    }
+
+    #[test]
+    fn test_bigint_words_beyond_64_bits() {
+        let field = FieldName::Bn254Fr;
+        // 2^64 + 5 and 2^200 + 2^130 + 1, one and several high words
+        let data = elements_from_bigints(&[big(&[5, 1]), big(&[1, 0, 4, 0x100])], field).unwrap();
+        assert_eq!(data.len(), 64);
+        assert_eq!(data[0], 5);
+        assert_eq!(data[8], 1);
+        assert_eq!(data[32], 1);
+        assert_eq!(data[32 + 16], 4);
+        assert_eq!(data[32 + 25], 1);
+        let back = elements_to_bigints(&data, field).unwrap();
+        assert_eq!(back[0].words, [5, 1, 0, 0]);
+        assert_eq!(back[1].words, [1, 0, 4, 0x100]);
+
+        // Surplus zero words and the sign of zero are not part of the value
+        let padded = elements_from_bigints(&[big(&[7, 0, 0, 0, 0, 0])], field).unwrap();
+        assert_eq!(padded, elements_from_bigints(&[big(&[7])], field).unwrap());
+        let neg_zero = BigInt {
+            sign_bit: true,
+            words: vec![0],
+        };
+        assert_eq!(
+            elements_from_bigints(&[neg_zero], field).unwrap(),
+            vec![0; 32]
+        );
+        assert!(elements_from_bigints(&[big(&[])], field).is_ok());
+
+        let negative = BigInt {
+            sign_bit: true,
+            words: vec![0, 1],
+        };
+        assert_eq!(
+            elements_from_bigints(&[big(&[1]), negative], field)
+                .unwrap_err()
+                .reason,
+            "values[1]: expected a non-negative BigInt"
+        );
+        // A nonzero fifth word, and a 2^32 value in a 4-byte field
+        assert!(elements_from_bigints(&[big(&[0, 0, 0, 0, 1])], field).is_err());
+        assert!(elements_from_bigints(&[big(&[1 << 32])], FieldName::M31).is_err());
+        assert!(elements_to_bigints(&[0; 33], field).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_strings() {
+        assert_eq!(
+            from_hex("0x12g4").unwrap_err().reason,
+            "hex: invalid hex digit 'g' at position 2"
+        );
+        assert_eq!(
+            from_hex("0x").unwrap_err().reason,
+            "hex: expected at least one hex digit"
+        );
+        assert_eq!(
+            from_decimal("-1").unwrap_err().reason,
+            "dec: invalid decimal digit '-' at position 0"
+        );
+        assert!(from_decimal("").is_err());
+        assert!(from_decimal("1e3").is_err());
+        assert!(field_elem_to_hex(vec![0; 31]).is_err());
+        assert!(field_elem_to_hex(vec![0xff; 32]).is_err());
+    }
+}
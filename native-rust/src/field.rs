@@ -0,0 +1,484 @@
+//! Element-wise arithmetic over packed little-endian field element arrays
+//!
+//! Shared by the `bn254_fr_*` and `bls12_381_fr_*` bindings, which take
+//! Buffers of concatenated little-endian elements. Elements are canonical by
+//! default. With `in_montgomery_form` set they are the raw Montgomery-form
+//! limbs instead, and results are returned the same way, so a pipeline of
+//! calls converts once at each end rather than on every call. Extension
+//! field elements are packed as their coefficients in order, each encoded
+//! like a base field element. The add and mul bindings also take a
+//! `reduction` option selecting Barrett reduction of canonical values
+//! instead of Montgomery multiplication.
+
+use std::sync::Mutex;
+
+use napi::bindgen_prelude::{Buffer, Uint8Array};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use rayon::prelude::*;
+
+use crate::barrett::Barrett;
+use crate::montgomery::{geq, ConstantTimeField, Field, Fp, MontConfig};
+use crate::tower::{Fp2, TowerConfig};
+
+/// Below this many elements the array functions run on the calling thread
+pub(crate) const ELEMENTWISE_PARALLEL_THRESHOLD: usize = 1 << 12;
+
+/// How elements of a packed array are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    /// The value itself
+    Canonical,
+    /// The limbs of `value * R mod p`
+    Montgomery,
+}
+
+impl Form {
+    /// `Montgomery` when the caller set `in_montgomery_form`
+    pub fn from_flag(in_montgomery_form: Option<bool>) -> Self {
+        if in_montgomery_form.unwrap_or(false) {
+            Form::Montgomery
+        } else {
+            Form::Canonical
+        }
+    }
+}
+
+/// A field element the packed array functions can decode and encode
+pub(crate) trait Packed: Field {
+    /// Size of one packed element in bytes
+    const BYTES: usize;
+
+    /// Decode one element in `form`, `None` if a coefficient is `>= p` and
+    /// `reduce` is not set
+    fn decode(bytes: &[u8], reduce: bool, form: Form) -> Option<Self>;
+
+    /// Encode one element in `form`
+    fn encode(&self, form: Form) -> Vec<u8>;
+}
+
+impl<C: MontConfig<N>, const N: usize> Packed for Fp<C, N> {
+    const BYTES: usize = 8 * N;
+
+    fn decode(bytes: &[u8], reduce: bool, form: Form) -> Option<Self> {
+        match (form, reduce) {
+            (Form::Canonical, false) => Fp::from_le_bytes(bytes),
+            (Form::Canonical, true) => Fp::from_le_bytes_reduced(bytes),
+            (Form::Montgomery, false) => Fp::from_montgomery_le_bytes(bytes),
+            (Form::Montgomery, true) => Fp::from_montgomery_le_bytes_reduced(bytes),
+        }
+    }
+
+    fn encode(&self, form: Form) -> Vec<u8> {
+        match form {
+            Form::Canonical => self.to_le_bytes(),
+            Form::Montgomery => self.to_montgomery_le_bytes(),
+        }
+    }
+}
+
+/// `c0 || c1`
+impl<T: TowerConfig<N>, const N: usize> Packed for Fp2<T, N> {
+    const BYTES: usize = 16 * N;
+
+    fn decode(bytes: &[u8], reduce: bool, form: Form) -> Option<Self> {
+        let (c0, c1) = bytes.split_at(8 * N);
+        Some(Fp2::new(
+            Packed::decode(c0, reduce, form)?,
+            Packed::decode(c1, reduce, form)?,
+        ))
+    }
+
+    fn encode(&self, form: Form) -> Vec<u8> {
+        [self.c0.encode(form), self.c1.encode(form)].concat()
+    }
+}
+
+/// Elements per input the Montgomery spot check samples
+const MONTGOMERY_SPOT_CHECKS: usize = 8;
+
+/// Reject Montgomery-form input that looks canonical; debug builds only
+///
+/// Montgomery encodings of real data spread over the whole field, so a
+/// non-zero sample with every limb but the lowest zero is almost certainly a
+/// small canonical integer passed by mistake. This only catches that common
+/// case: a canonical value of full width cannot be told apart.
+fn spot_check_montgomery(bytes: &[u8], size: usize, name: &str) -> Result<()> {
+    let count = bytes.len() / size;
+    let samples = count.min(MONTGOMERY_SPOT_CHECKS);
+    for k in 0..samples {
+        // Evenly spaced, always including the first and last element
+        let index = if samples == 1 {
+            0
+        } else {
+            k * (count - 1) / (samples - 1)
+        };
+        let element = &bytes[index * size..(index + 1) * size];
+        if element[8..].iter().all(|&b| b == 0) && element[..8].iter().any(|&b| b != 0) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "{name}[{index}]: looks like a canonical value, but in_montgomery_form is set"
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// How the batched add/mul bindings reduce products
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    /// Montgomery multiplication, converting canonical input on the way
+    Montgomery,
+    /// Barrett reduction of canonical limbs; see [`crate::barrett`]
+    Barrett,
+}
+
+impl Reduction {
+    /// Parse the `reduction` option, `Montgomery` when absent
+    ///
+    /// Barrett works on canonical values, so it cannot be combined with
+    /// Montgomery-form input.
+    pub fn parse(reduction: Option<&str>, form: Form) -> Result<Self> {
+        let parsed = match reduction {
+            None | Some("montgomery") => Reduction::Montgomery,
+            Some("barrett") => Reduction::Barrett,
+            Some(other) => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("reduction: expected \"montgomery\" or \"barrett\", got {other:?}"),
+                ))
+            }
+        };
+        if parsed == Reduction::Barrett && form == Form::Montgomery {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "reduction: \"barrett\" cannot be combined with in_montgomery_form".to_string(),
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Check that all inputs have the same length, a multiple of `size`
+fn check_lengths<const K: usize>(inputs: [(&[u8], &str); K], size: usize) -> Result<()> {
+    let len = inputs[0].0.len();
+    for (bytes, name) in inputs {
+        if !bytes.len().is_multiple_of(size) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "{name}: length {} is not a multiple of {size} bytes",
+                    bytes.len()
+                ),
+            ));
+        }
+        if bytes.len() != len {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "{} and {name} must have the same length, got {len} and {} bytes",
+                    inputs[0].1,
+                    bytes.len()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Decode each element of the length-checked `inputs`, apply `op` and
+/// write its `size`-byte result, reporting the lowest undecodable index
+fn map_elements<T, const K: usize>(
+    inputs: [(&[u8], &str); K],
+    size: usize,
+    field: &str,
+    decode: impl Fn(&[u8]) -> Option<T> + Sync,
+    op: impl Fn([T; K], &mut [u8]) + Sync,
+) -> Result<Vec<u8>> {
+    // Lowest failing element index across all workers
+    let first_error: Mutex<Option<(usize, Error)>> = Mutex::new(None);
+    let mut out = vec![0u8; inputs[0].0.len()];
+    crate::parallel::par_chunks_mut(
+        &mut out,
+        size,
+        ELEMENTWISE_PARALLEL_THRESHOLD * size,
+        |offset, chunk| {
+            for (j, dst) in chunk.chunks_exact_mut(size).enumerate() {
+                let start = offset + j * size;
+                let index = start / size;
+                let decoded =
+                    inputs.map(|(bytes, name)| decode(&bytes[start..start + size]).ok_or(name));
+                if let Some(name) = decoded.iter().find_map(|v| v.as_ref().err()) {
+                    let mut slot = first_error.lock().unwrap();
+                    if slot.as_ref().is_none_or(|(i, _)| index < *i) {
+                        let err = Error::new(
+                            Status::InvalidArg,
+                            format!("{name}[{index}]: value is not below the {field} modulus"),
+                        );
+                        *slot = Some((index, err));
+                    }
+                    return;
+                }
+                op(decoded.map(|v| v.ok().unwrap()), dst);
+            }
+        },
+    );
+    match first_error.into_inner().unwrap() {
+        Some((_, err)) => Err(err),
+        None => Ok(out),
+    }
+}
+
+/// Apply `op` element-wise across packed little-endian arrays
+///
+/// All inputs must be the same length, a multiple of the element size.
+/// Inputs are decoded from `input` form and results encoded in `output`
+/// form. Elements `>= p` are rejected, naming the first offending index, or
+/// reduced modulo `p` when `reduce` is set. `field` names the field in
+/// errors, e.g. "BN254 scalar field".
+pub(crate) fn elementwise<F: Packed, const K: usize>(
+    inputs: [(&[u8], &str); K],
+    reduce: bool,
+    (input, output): (Form, Form),
+    field: &str,
+    op: impl Fn([F; K]) -> F + Sync,
+) -> Result<Vec<u8>> {
+    let size = F::BYTES;
+    check_lengths(inputs, size)?;
+    if cfg!(debug_assertions) && input == Form::Montgomery {
+        for (bytes, name) in inputs {
+            spot_check_montgomery(bytes, size, name)?;
+        }
+    }
+    map_elements(
+        inputs,
+        size,
+        field,
+        |bytes| F::decode(bytes, reduce, input),
+        |values, dst| dst.copy_from_slice(&op(values).encode(output)),
+    )
+}
+
+/// [`elementwise`] over canonical 256-bit elements with Barrett reduction
+///
+/// `μ` is computed once for the call and shared by every element. Inputs
+/// and results are canonical little-endian; `reduce` and the errors behave
+/// as in [`elementwise`].
+pub(crate) fn barrett_elementwise<C: MontConfig<4>, const K: usize>(
+    inputs: [(&[u8], &str); K],
+    reduce: bool,
+    field: &str,
+    op: impl Fn(&Barrett, [[u64; 4]; K]) -> [u64; 4] + Sync,
+) -> Result<Vec<u8>> {
+    check_lengths(inputs, 32)?;
+    let barrett = Barrett::new(C::MODULUS);
+    map_elements(
+        inputs,
+        32,
+        field,
+        |bytes| {
+            let limbs: [u64; 4] = std::array::from_fn(|i| {
+                u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap())
+            });
+            if reduce {
+                Some(barrett.reduce_narrow(&limbs))
+            } else {
+                (!geq(&limbs, &C::MODULUS)).then_some(limbs)
+            }
+        },
+        |values, dst| {
+            for (limb, bytes) in op(&barrett, values).iter().zip(dst.chunks_exact_mut(8)) {
+                bytes.copy_from_slice(&limb.to_le_bytes());
+            }
+        },
+    )
+}
+
+/// Up to this many elements batch inversion runs on the calling thread
+const INV_BATCH_PARALLEL_THRESHOLD: usize = 1 << 10;
+
+/// Below this many elements constant-time inversion runs on the calling
+/// thread; each element costs a full exponentiation
+const INV_CT_PARALLEL_THRESHOLD: usize = 1 << 6;
+
+/// Invert every element in place with Montgomery's trick; zeros stay zero
+///
+/// Inputs longer than 1024 elements are split into one chunk per Rayon
+/// worker, each running its own prefix-product scan, single inversion and
+/// backfill.
+pub fn batch_invert<F: Field>(values: &mut [F]) {
+    let workers = crate::parallel::num_threads();
+    if workers <= 1 || values.len() <= INV_BATCH_PARALLEL_THRESHOLD {
+        batch_invert_serial(values);
+        return;
+    }
+    values
+        .par_chunks_mut(values.len().div_ceil(workers))
+        .for_each(batch_invert_serial);
+}
+
+/// [`batch_invert`] on the calling thread
+pub(crate) fn batch_invert_serial<F: Field>(values: &mut [F]) {
+    // prefix[i] is the product of the non-zero elements before i
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::one();
+    for v in values.iter() {
+        prefix.push(acc);
+        if !v.is_zero() {
+            acc = acc * *v;
+        }
+    }
+    let mut inv = acc
+        .inverse()
+        .expect("a product of non-zero elements is non-zero");
+    for (v, p) in values.iter_mut().zip(prefix).rev() {
+        if v.is_zero() {
+            continue;
+        }
+        let next = inv * *v;
+        *v = inv * p;
+        inv = next;
+    }
+}
+
+/// Invert every element of a packed little-endian array in `form`
+///
+/// A zero element is an error naming its index, unless `allow_zero` is set,
+/// in which case zeros map to zero. Elements `>= p` are rejected. With
+/// `constant_time` set each element gets its own Fermat inversion instead of
+/// sharing one through Montgomery's trick.
+pub(crate) fn inv_batch<F: Packed + ConstantTimeField>(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+    field: &str,
+) -> Result<Vec<u8>> {
+    let size = F::BYTES;
+    // Validates and converts into Montgomery limbs in parallel
+    let limbs = elementwise(
+        [(values, "values")],
+        false,
+        (form, Form::Montgomery),
+        field,
+        |[x]: [F; 1]| x,
+    )?;
+    let mut elements: Vec<F> = limbs
+        .chunks_exact(size)
+        .map(|c| F::decode(c, false, Form::Montgomery).expect("validated above"))
+        .collect();
+    if !allow_zero {
+        if let Some(i) = elements.iter().position(|v| v.is_zero()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("values[{i}]: zero has no inverse"),
+            ));
+        }
+    }
+    if constant_time {
+        crate::parallel::par_chunks_mut(&mut elements, 1, INV_CT_PARALLEL_THRESHOLD, |_, chunk| {
+            for v in chunk {
+                *v = v.invert_ct();
+            }
+        });
+    } else {
+        batch_invert(&mut elements);
+    }
+    let mut out = vec![0u8; values.len()];
+    crate::parallel::par_chunks_mut(
+        &mut out,
+        size,
+        ELEMENTWISE_PARALLEL_THRESHOLD * size,
+        |offset, chunk| {
+            for (j, dst) in chunk.chunks_exact_mut(size).enumerate() {
+                dst.copy_from_slice(&elements[offset / size + j].encode(form));
+            }
+        },
+    );
+    Ok(out)
+}
+
+/// `x^e` for every element of a packed little-endian array in `form`
+///
+/// `exponent` is a big-endian integer of any length; an empty or zero
+/// exponent maps every element to one. With `constant_time` set the
+/// exponentiation's timing depends only on the exponent's length, but
+/// canonical-form decoding and encoding stay variable time, so secret bases
+/// should also be passed in Montgomery form.
+pub(crate) fn pow_batch<C: MontConfig<N>, const N: usize>(
+    bases: &[u8],
+    exponent: &[u8],
+    constant_time: bool,
+    form: Form,
+    field: &str,
+) -> Result<Vec<u8>> {
+    // Little-endian limbs, from the least significant end of the bytes
+    let mut limbs: Vec<u64> = exponent
+        .rchunks(8)
+        .map(|chunk| {
+            let mut word = [0u8; 8];
+            word[8 - chunk.len()..].copy_from_slice(chunk);
+            u64::from_be_bytes(word)
+        })
+        .collect();
+    if !constant_time {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+    }
+    elementwise(
+        [(bases, "bases")],
+        false,
+        (form, form),
+        field,
+        |[x]: [Fp<C, N>; 1]| {
+            if constant_time {
+                x.pow_ct(&limbs)
+            } else {
+                x.pow_vartime(&limbs)
+            }
+        },
+    )
+}
+
+/// Square roots of a packed array, with a flag per element
+#[napi(object)]
+pub struct SqrtBatchResult {
+    /// One root per element, zero where the element has none
+    pub roots: Buffer,
+    /// 1 where the element is a square and its root was returned, else 0
+    pub flags: Uint8Array,
+}
+
+/// Square roots of a packed little-endian array of canonical elements
+///
+/// Returns the roots, each the smaller of the two as an integer, and one
+/// flag byte per element: 1 for a square (zero included), 0 for a
+/// non-residue, whose root slot is left zero. `root_of_unity` is a
+/// primitive `2^TWO_ADICITY`-th root of unity. Elements `>= p` are rejected.
+pub(crate) fn sqrt_batch<C: MontConfig<N>, const N: usize>(
+    values: &[u8],
+    root_of_unity: Fp<C, N>,
+    field: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let roots = elementwise(
+        [(values, "values")],
+        false,
+        (Form::Canonical, Form::Canonical),
+        field,
+        |[x]: [Fp<C, N>; 1]| match x.sqrt(root_of_unity) {
+            Some(r) if geq(&r.to_canonical(), &(-r).to_canonical()) => -r,
+            Some(r) => r,
+            None => Fp::zero(),
+        },
+    )?;
+    let size = Fp::<C, N>::BYTES;
+    let flags = values
+        .chunks_exact(size)
+        .zip(roots.chunks_exact(size))
+        .map(|(x, r)| u8::from(r.iter().any(|&b| b != 0) || x.iter().all(|&b| b == 0)))
+        .collect();
+    Ok((roots, flags))
+}
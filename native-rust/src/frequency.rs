@@ -0,0 +1,146 @@
+//! CPU clock frequency detection
+//!
+//! macOS reads the `hw.cpufrequency*` sysctls, which Apple Silicon does not
+//! publish. Linux reads cpu0's `cpufreq` directory in sysfs, falling back to
+//! the `cpu MHz` line of `/proc/cpuinfo` for the current clock on machines
+//! without a cpufreq driver (most VMs). Windows reads the per-processor
+//! `CallNtPowerInformation` records, which carry the base and current clock
+//! but not the boost ceiling. Values that cannot be determined are 0.
+
+use napi_derive::napi;
+
+/// CPU clock frequencies in MHz, 0 where unknown
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuFrequencyInfo {
+    /// Nominal (non-boost) clock
+    pub base_mhz: u32,
+    /// Highest single-core boost clock
+    pub boost_mhz: u32,
+    /// Clock of the first core at the time of the call
+    pub current_mhz: u32,
+}
+
+/// Detect the base, boost and current clock of the first CPU core
+pub fn detect_cpu_frequency() -> CpuFrequencyInfo {
+    detect()
+}
+
+/// Read the CPU base, boost and current clock frequencies
+///
+/// The current clock is re-read on every call; on Apple Silicon all three
+/// are 0.
+#[napi]
+pub fn get_cpu_frequency() -> CpuFrequencyInfo {
+    detect_cpu_frequency()
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> CpuFrequencyInfo {
+    use crate::sysctl::read_u64;
+
+    let mhz = |name: &str| read_u64(name).map_or(0, |hz| (hz / 1_000_000) as u32);
+    CpuFrequencyInfo {
+        base_mhz: mhz("hw.cpufrequency"),
+        boost_mhz: mhz("hw.cpufrequency_max"),
+        current_mhz: mhz("hw.cpufrequency"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect() -> CpuFrequencyInfo {
+    let dir = std::path::Path::new("/sys/devices/system/cpu/cpu0/cpufreq");
+    // intel_pstate names the base clock `base_frequency`; `cpuinfo_cur_freq`
+    // is root-only on many kernels, `scaling_cur_freq` is not
+    let mhz = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| parse_khz(&std::fs::read_to_string(dir.join(name)).ok()?))
+            .unwrap_or(0)
+    };
+    let current = mhz(&["cpuinfo_cur_freq", "scaling_cur_freq"]);
+    CpuFrequencyInfo {
+        base_mhz: mhz(&["scaling_base_frequency", "base_frequency"]),
+        boost_mhz: mhz(&["cpuinfo_max_freq"]),
+        current_mhz: if current > 0 {
+            current
+        } else {
+            std::fs::read_to_string("/proc/cpuinfo")
+                .ok()
+                .and_then(|text| parse_cpuinfo_mhz(&text))
+                .unwrap_or(0)
+        },
+    }
+}
+
+#[cfg(windows)]
+fn detect() -> CpuFrequencyInfo {
+    crate::win32::processor_clock_mhz()
+        .map(|(base_mhz, current_mhz)| CpuFrequencyInfo {
+            base_mhz,
+            boost_mhz: 0,
+            current_mhz,
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn detect() -> CpuFrequencyInfo {
+    CpuFrequencyInfo::default()
+}
+
+/// Parse a sysfs frequency in kHz, such as `3600000`, into MHz
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_khz(s: &str) -> Option<u32> {
+    let khz: u64 = s.trim().parse().ok()?;
+    u32::try_from(khz / 1000).ok().filter(|&mhz| mhz > 0)
+}
+
+/// The `cpu MHz` of the first processor in `/proc/cpuinfo` text
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpuinfo_mhz(cpuinfo: &str) -> Option<u32> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() != "cpu MHz" {
+            return None;
+        }
+        let mhz: f64 = value.trim().parse().ok()?;
+        (mhz >= 1.0).then_some(mhz.round() as u32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_khz() {
+        assert_eq!(parse_khz("3600000\n"), Some(3600));
+        assert_eq!(parse_khz("800000"), Some(800));
+        assert_eq!(parse_khz("0"), None);
+        assert_eq!(parse_khz("<unknown>"), None);
+    }
+
+    #[test]
+    fn test_parse_cpuinfo_mhz() {
+        let text =
+            "processor\t: 0\ncpu MHz\t\t: 2099.998\n\nprocessor\t: 1\ncpu MHz\t\t: 800.000\n";
+        assert_eq!(parse_cpuinfo_mhz(text), Some(2100));
+        // aarch64 kernels do not report a clock
+        assert_eq!(
+            parse_cpuinfo_mhz("processor\t: 0\nBogoMIPS\t: 50.00\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_cpu_frequency() {
+        let info = get_cpu_frequency();
+        if info.base_mhz > 0 && info.boost_mhz > 0 {
+            assert!(info.boost_mhz >= info.base_mhz);
+        }
+        if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            assert_eq!(info, CpuFrequencyInfo::default());
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! FRI low-degree test over the BN254 scalar field
+//!
+//! A polynomial with fewer than `d` coefficients (`d` a power of two) is
+//! evaluated on the coset `5·<ω>` of size `d · blowup`. Each layer is
+//! committed in a Poseidon Merkle tree whose leaf `j` hashes the pair
+//! `(f(x), f(-x))` for `x = s·ω^j`, then folded with a transcript challenge
+//! `β` into
+//!
+//! ```text
+//! f'(x²) = (f(x) + f(-x)) / 2 + β · (f(x) - f(-x)) / (2x)
+//! ```
+//!
+//! on the squared coset, which has half the size. After `log2(d)` folds an
+//! honest layer is constant and only that value is sent. Query positions
+//! are drawn from the transcript once every root is absorbed; for each one
+//! the pair and its Merkle path is opened in every layer.
+//!
+//! Tree nodes use circomlib's two-input Poseidon, as in [`crate::merkle`].
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{parse_fr, Fr, FR_BYTES};
+use crate::merkle::{hash_pair, verify_proof, MerkleProof};
+use crate::ntt::{log2_size, ntt_in_place, root_of_unity, TWO_ADICITY};
+use crate::parallel::par_map;
+use crate::poseidon;
+use crate::transcript::Transcript;
+
+/// Supported evaluation domain expansion factors
+pub const BLOWUPS: [u32; 4] = [2, 4, 8, 16];
+
+/// Multiplicative generator of the scalar field, used as the coset shift
+const COSET_SHIFT: u64 = 5;
+
+const TRANSCRIPT_LABEL: &str = "zk-accelerate fri";
+
+/// Proof that committed evaluations come from a low-degree polynomial
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FriCommitment {
+    /// Root of the first layer, the evaluations of the polynomial itself
+    pub merkle_root: Vec<u8>,
+    /// Roots of the folded layers that follow the first
+    pub layers: Vec<Vec<u8>>,
+    /// Merkle paths of the opened pairs, `layers.len() + 1` per query
+    pub query_proofs: Vec<MerkleProof>,
+    /// The two opened evaluations of every entry of `query_proofs`
+    pub query_values: Vec<Vec<u8>>,
+    /// Pair index of every query in the first layer
+    pub query_indices: Vec<u32>,
+    /// Constant the last fold collapses to
+    pub final_value: Vec<u8>,
+    /// Committed coefficient count bound, a power of two
+    pub degree_bound: u32,
+    /// Evaluation domain size over `degree_bound`
+    pub blowup: u32,
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(Status::InvalidArg, msg)
+}
+
+fn check_blowup(blowup: u32) -> Result<()> {
+    if !BLOWUPS.contains(&blowup) {
+        return Err(invalid(format!(
+            "blowup must be one of {BLOWUPS:?}, got {blowup}"
+        )));
+    }
+    Ok(())
+}
+
+/// Leaf hash of an opened pair
+fn leaf(a: &Fr, b: &Fr) -> Vec<u8> {
+    poseidon::hash(&[*a, *b])
+        .expect("two inputs are always a valid circomlib width")
+        .to_be_bytes()
+}
+
+/// Every level of a complete Merkle tree, leaves first
+fn build_tree(leaves: Vec<Vec<u8>>) -> Vec<Vec<Vec<u8>>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let pairs: Vec<_> = levels.last().unwrap().chunks_exact(2).collect();
+        let next = par_map(&pairs, |p| hash_pair(&p[0], &p[1]));
+        levels.push(next);
+    }
+    levels
+}
+
+fn open(levels: &[Vec<Vec<u8>>], index: usize) -> MerkleProof {
+    let depth = levels.len() - 1;
+    MerkleProof {
+        siblings: (0..depth)
+            .map(|level| levels[level][(index >> level) ^ 1].clone())
+            .collect(),
+        path_bits: (0..depth).map(|level| index >> level & 1 == 1).collect(),
+    }
+}
+
+/// Absorb the parameters every proof is bound to
+fn start_transcript(degree_bound: u32, blowup: u32, num_queries: u32) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL.to_string());
+    let params = [degree_bound, blowup, num_queries].map(u32::to_be_bytes);
+    transcript.append_message("params".to_string(), params.concat());
+    transcript
+}
+
+/// Draw `count` pair indices below `half` (a power of two)
+fn draw_queries(transcript: &mut Transcript, count: u32, half: usize) -> Vec<u32> {
+    (0..count)
+        .map(|_| {
+            let bytes = transcript
+                .challenge_bytes("query".to_string(), 4)
+                .expect("four bytes is a valid challenge length");
+            u32::from_be_bytes(bytes.try_into().unwrap()) & (half as u32 - 1)
+        })
+        .collect()
+}
+
+/// Fold one layer over the coset `shift·<ω>` with challenge `beta`
+fn fold(evals: &[Fr], shift: Fr, beta: Fr) -> Vec<Fr> {
+    let half = evals.len() / 2;
+    let log_n = evals.len().trailing_zeros();
+    let two_inv = Fr::from_u64(2).inverse().unwrap();
+    let omega_inv = root_of_unity(log_n).inverse().unwrap();
+    let mut x_inv = shift.inverse().unwrap();
+    let mut out = Vec::with_capacity(half);
+    for j in 0..half {
+        out.push(fold_pair(evals[j], evals[j + half], x_inv, beta, two_inv));
+        x_inv *= omega_inv;
+    }
+    out
+}
+
+fn fold_pair(a: Fr, b: Fr, x_inv: Fr, beta: Fr, two_inv: Fr) -> Fr {
+    ((a + b) + beta * (a - b) * x_inv) * two_inv
+}
+
+/// Evaluations of `coeffs` on the coset `COSET_SHIFT·<ω_n>`
+fn evaluate_on_coset(coeffs: &[Fr], n: usize) -> Result<Vec<Fr>> {
+    let shift = Fr::from_u64(COSET_SHIFT);
+    let mut power = Fr::one();
+    let mut values = vec![Fr::zero(); n];
+    for (v, c) in values.iter_mut().zip(coeffs) {
+        *v = *c * power;
+        power *= shift;
+    }
+    ntt_in_place(&mut values, false)?;
+    Ok(values)
+}
+
+/// Commit to first-layer evaluations claimed to have degree below `degree_bound`
+fn prove(evals: Vec<Fr>, degree_bound: usize, blowup: u32, num_queries: u32) -> FriCommitment {
+    let rounds = degree_bound.trailing_zeros() as usize;
+    let mut transcript = start_transcript(degree_bound as u32, blowup, num_queries);
+    let mut shift = Fr::from_u64(COSET_SHIFT);
+    let mut layers = Vec::with_capacity(rounds);
+    let mut trees = Vec::with_capacity(rounds);
+    let mut current = evals;
+    for _ in 0..rounds {
+        let half = current.len() / 2;
+        let pairs: Vec<_> = (0..half).map(|j| (current[j], current[j + half])).collect();
+        let tree = build_tree(par_map(&pairs, |(a, b)| leaf(a, b)));
+        let root = tree.last().unwrap()[0].clone();
+        transcript.append_message("layer".to_string(), root);
+        let beta = transcript.challenge_fr("fold");
+        let next = fold(&current, shift, beta);
+        layers.push(current);
+        trees.push(tree);
+        current = next;
+        shift = shift.square();
+    }
+    let final_value = current[0].to_be_bytes();
+    transcript.append_message("final".to_string(), final_value.clone());
+    let query_indices = draw_queries(&mut transcript, num_queries, layers[0].len() / 2);
+
+    let mut query_proofs = Vec::with_capacity(query_indices.len() * rounds);
+    let mut query_values = Vec::with_capacity(2 * query_indices.len() * rounds);
+    for &q in &query_indices {
+        for (evals, tree) in layers.iter().zip(&trees) {
+            let half = evals.len() / 2;
+            let j = q as usize % half;
+            query_proofs.push(open(tree, j));
+            query_values.push(evals[j].to_be_bytes());
+            query_values.push(evals[j + half].to_be_bytes());
+        }
+    }
+    let mut roots = trees.iter().map(|t| t.last().unwrap()[0].clone());
+    FriCommitment {
+        merkle_root: roots.next().unwrap(),
+        layers: roots.collect(),
+        query_proofs,
+        query_values,
+        query_indices,
+        final_value,
+        degree_bound: degree_bound as u32,
+        blowup,
+    }
+}
+
+/// Commit to a polynomial with a FRI low-degree proof
+///
+/// `poly` holds 32-byte big-endian coefficients, lowest degree first. The
+/// degree bound is the coefficient count rounded up to a power of two (at
+/// least 2), and the evaluation domain is `blowup` times larger; `blowup`
+/// must be 2, 4, 8 or 16.
+#[napi]
+pub fn fri_commit(poly: Vec<Vec<u8>>, blowup: u32, num_queries: u32) -> Result<FriCommitment> {
+    check_blowup(blowup)?;
+    if poly.is_empty() {
+        return Err(invalid(
+            "poly must have at least one coefficient".to_string(),
+        ));
+    }
+    if num_queries == 0 {
+        return Err(invalid("num_queries must be positive".to_string()));
+    }
+    let coeffs = poly
+        .iter()
+        .enumerate()
+        .map(|(i, c)| parse_fr(c, &format!("poly[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let degree_bound = coeffs.len().next_power_of_two().max(2);
+    let n = degree_bound * blowup as usize;
+    log2_size(n)?;
+    Ok(prove(
+        evaluate_on_coset(&coeffs, n)?,
+        degree_bound,
+        blowup,
+        num_queries,
+    ))
+}
+
+/// Check a FRI proof and that it opens every pair index in `queries`
+///
+/// `degree_bound`, `blowup` and `num_queries` are the verifier's own
+/// parameters; the degree bound is rounded up as in [`fri_commit`]. A proof
+/// made with any other parameters is rejected, as is a `queries` list whose
+/// length is not `num_queries`. Query positions are re-derived from the
+/// transcript, so a proof only verifies at the positions its roots commit
+/// to. Returns false for malformed commitments as well as for failed checks.
+#[napi]
+pub fn fri_verify(
+    commitment: FriCommitment,
+    queries: Vec<u32>,
+    degree_bound: u32,
+    blowup: u32,
+    num_queries: u32,
+) -> bool {
+    let pinned = degree_bound.max(2).checked_next_power_of_two();
+    if pinned != Some(commitment.degree_bound)
+        || blowup != commitment.blowup
+        || num_queries == 0
+        || num_queries as usize != commitment.query_indices.len()
+        || queries.len() != commitment.query_indices.len()
+    {
+        return false;
+    }
+    verify(&commitment, &queries).unwrap_or(false)
+}
+
+fn verify(c: &FriCommitment, queries: &[u32]) -> Option<bool> {
+    if !BLOWUPS.contains(&c.blowup) || c.degree_bound < 2 || !c.degree_bound.is_power_of_two() {
+        return None;
+    }
+    let rounds = c.degree_bound.trailing_zeros() as usize;
+    let log_n = (c.degree_bound as u64 * c.blowup as u64).trailing_zeros();
+    let num_queries = c.query_indices.len();
+    if log_n > TWO_ADICITY
+        || c.layers.len() + 1 != rounds
+        || c.query_proofs.len() != num_queries * rounds
+        || c.query_values.len() != 2 * c.query_proofs.len()
+    {
+        return None;
+    }
+    let fr = |bytes: &[u8]| {
+        (bytes.len() == FR_BYTES)
+            .then(|| Fr::from_be_bytes(bytes))
+            .flatten()
+    };
+    let final_value = fr(&c.final_value)?;
+    let values = c
+        .query_values
+        .iter()
+        .map(|v| fr(v))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut transcript = start_transcript(c.degree_bound, c.blowup, num_queries as u32);
+    let roots: Vec<&Vec<u8>> = std::iter::once(&c.merkle_root).chain(&c.layers).collect();
+    let betas: Vec<Fr> = roots
+        .iter()
+        .map(|root| {
+            transcript.append_message("layer".to_string(), root.to_vec());
+            transcript.challenge_fr("fold")
+        })
+        .collect();
+    transcript.append_message("final".to_string(), c.final_value.clone());
+    let expected = draw_queries(&mut transcript, num_queries as u32, 1 << (log_n - 1));
+    if expected != c.query_indices || !queries.iter().all(|q| expected.contains(q)) {
+        return Some(false);
+    }
+
+    let two_inv = Fr::from_u64(2).inverse().unwrap();
+    let shifts: Vec<Fr> = (0..rounds)
+        .scan(Fr::from_u64(COSET_SHIFT), |s, _| {
+            let current = *s;
+            *s = s.square();
+            Some(current)
+        })
+        .collect();
+    for (q, &index) in c.query_indices.iter().enumerate() {
+        // Position and value the previous fold predicts in this layer
+        let mut predicted: Option<(usize, Fr)> = None;
+        for layer in 0..rounds {
+            let entry = q * rounds + layer;
+            let (a, b) = (values[2 * entry], values[2 * entry + 1]);
+            let half = 1usize << (log_n as usize - 1 - layer);
+            let j = index as usize % half;
+            // The previous layer's pair index is this layer's position
+            if let Some((position, value)) = predicted {
+                let opened = if position < half { a } else { b };
+                if opened != value {
+                    return Some(false);
+                }
+            }
+            let proof = &c.query_proofs[entry];
+            let bits_match = proof.path_bits.len() == half.trailing_zeros() as usize
+                && proof
+                    .path_bits
+                    .iter()
+                    .enumerate()
+                    .all(|(level, &bit)| bit == (j >> level & 1 == 1));
+            if !bits_match || !verify_proof(roots[layer].clone(), leaf(&a, &b), proof.clone()) {
+                return Some(false);
+            }
+            let omega = root_of_unity(log_n - layer as u32);
+            let x = shifts[layer] * omega.pow(&[j as u64]);
+            let folded = fold_pair(a, b, x.inverse()?, betas[layer], two_inv);
+            predicted = Some((j, folded));
+        }
+        if predicted.map(|(_, v)| v) != Some(final_value) {
+            return Some(false);
+        }
+    }
+    Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coeffs(values: &[u64]) -> Vec<Vec<u8>> {
+        values
+            .iter()
+            .map(|&v| Fr::from_u64(v).to_be_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_coset_evaluation() {
+        let poly: Vec<Fr> = [3u64, 1, 4, 1].iter().map(|&v| Fr::from_u64(v)).collect();
+        let evals = evaluate_on_coset(&poly, 8).unwrap();
+        let omega = root_of_unity(3);
+        for (j, e) in evals.iter().enumerate() {
+            let x = Fr::from_u64(COSET_SHIFT) * omega.pow(&[j as u64]);
+            let expected = poly.iter().rev().fold(Fr::zero(), |acc, c| acc * x + *c);
+            assert_eq!(*e, expected);
+        }
+    }
+
+    #[test]
+    fn test_degree_three_verifies() {
+        for blowup in BLOWUPS {
+            let c = fri_commit(coeffs(&[7, 0, 2, 9]), blowup, 6).unwrap();
+            assert_eq!(c.degree_bound, 4);
+            assert_eq!(c.layers.len(), 1);
+            assert_eq!(c.query_proofs.len(), 12);
+            assert!(c.query_indices.iter().all(|&q| q < 2 * blowup));
+            assert!(
+                fri_verify(c.clone(), c.query_indices.clone(), 4, blowup, 6),
+                "blowup {blowup}"
+            );
+            assert!(!fri_verify(c.clone(), vec![], 4, blowup, 6));
+        }
+        // A constant is still folded once
+        let c = fri_commit(coeffs(&[42]), 2, 3).unwrap();
+        assert_eq!(c.final_value, Fr::from_u64(42).to_be_bytes());
+        assert!(fri_verify(c.clone(), c.query_indices, 1, 2, 3));
+    }
+
+    #[test]
+    fn test_cheating_polynomial_fails() {
+        // Degree 7 evaluations passed off as degree below 4
+        let poly: Vec<Fr> = (1..=8u64).map(|v| Fr::from_u64(v * v + 1)).collect();
+        let evals = evaluate_on_coset(&poly, 16).unwrap();
+        let c = prove(evals, 4, 4, 8);
+        assert!(!fri_verify(c.clone(), c.query_indices, 4, 4, 8));
+        // An honest proof of the same polynomial claims a larger bound
+        let c = fri_commit(poly.iter().map(|v| v.to_be_bytes()).collect(), 2, 8).unwrap();
+        assert!(fri_verify(c.clone(), c.query_indices.clone(), 8, 2, 8));
+        assert!(!fri_verify(c.clone(), c.query_indices, 4, 2, 8));
+    }
+
+    #[test]
+    fn test_verifier_pins_parameters() {
+        let c = fri_commit(coeffs(&[1, 2, 3]), 4, 5).unwrap();
+        let queries = c.query_indices.clone();
+        assert!(fri_verify(c.clone(), queries.clone(), 3, 4, 5));
+        assert!(!fri_verify(c.clone(), queries.clone(), 8, 4, 5));
+        assert!(!fri_verify(c.clone(), queries.clone(), 4, 8, 5));
+        assert!(!fri_verify(c.clone(), queries.clone(), 4, 4, 6));
+        assert!(!fri_verify(c.clone(), queries.clone(), 4, 4, 0));
+        assert!(!fri_verify(c.clone(), queries[..4].to_vec(), 4, 4, 5));
+        assert!(!fri_verify(c.clone(), queries.clone(), u32::MAX, 4, 5));
+        // A single query the prover chose to open is not enough
+        let few = fri_commit(coeffs(&[1, 2, 3]), 4, 1).unwrap();
+        assert!(!fri_verify(few.clone(), few.query_indices, 4, 4, 5));
+    }
+
+    #[test]
+    fn test_tampering_fails() {
+        let c = fri_commit(coeffs(&[1, 2, 3, 4, 5, 6, 7]), 4, 5).unwrap();
+        let queries = c.query_indices.clone();
+        let verify = |c: FriCommitment, queries: Vec<u32>| fri_verify(c, queries, 8, 4, 5);
+        assert!(verify(c.clone(), queries.clone()));
+
+        let mut bad = c.clone();
+        bad.query_values[3] = Fr::from_u64(1).to_be_bytes();
+        assert!(!verify(bad, queries.clone()));
+        let mut bad = c.clone();
+        bad.final_value = Fr::from_u64(1).to_be_bytes();
+        assert!(!verify(bad, queries.clone()));
+        let mut bad = c.clone();
+        bad.query_indices[0] ^= 1;
+        assert!(!verify(bad, queries.clone()));
+        let mut bad = c.clone();
+        bad.layers.pop();
+        assert!(!verify(bad, queries.clone()));
+        // Positions the proof does not open
+        let unopened = (0..32).find(|q| !queries.contains(q)).unwrap();
+        let mut shifted = queries.clone();
+        shifted[0] = unopened;
+        assert!(!verify(c, shifted));
+    }
+
+    #[test]
+    fn test_rejects_invalid_parameters() {
+        for blowup in [0, 1, 3, 32] {
+            let err = fri_commit(coeffs(&[1, 2]), blowup, 4).unwrap_err();
+            assert_eq!(err.status, Status::InvalidArg);
+        }
+        assert!(fri_commit(vec![], 2, 4).is_err());
+        assert!(fri_commit(coeffs(&[1, 2]), 2, 0).is_err());
+        assert!(fri_commit(vec![vec![0xff; 32]], 2, 4).is_err());
+    }
+}
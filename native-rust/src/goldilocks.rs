@@ -0,0 +1,432 @@
+//! Goldilocks field `p = 2^64 - 2^32 + 1` batch arithmetic
+//!
+//! Elements cross the NAPI boundary as `BigUint64Array`s. Any `u64` is
+//! accepted and read modulo `p`, so inputs in `[p, 2^64)` are valid; results
+//! are always canonical. Since `2^64 ≡ 2^32 - 1 (mod p)`, every wraparound
+//! is corrected by adding or subtracting `ε = 2^32 - 1`, and 128-bit
+//! products reduce without division.
+//!
+//! The portable path uses `u128` products. On aarch64 the add, sub and mul
+//! batches run two lanes per NEON instruction (see [`crate::neon`]), with
+//! the 64x64-bit product assembled from four `umull` digit products.
+
+use napi::bindgen_prelude::BigUint64Array;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::neon::U64x2;
+
+/// The Goldilocks prime `2^64 - 2^32 + 1`
+pub const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// `2^64 mod p`
+const EPSILON: u64 = 0xffff_ffff;
+
+/// Below this many elements the batch functions run on the calling thread
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// Reduce any `u64` to `[0, p)`; one subtraction suffices since `2p > 2^64`
+#[inline(always)]
+pub fn canonical(x: u64) -> u64 {
+    if x >= MODULUS {
+        x - MODULUS
+    } else {
+        x
+    }
+}
+
+/// `a + b mod p`
+#[inline]
+pub fn add(a: u64, b: u64) -> u64 {
+    let (a, b) = (canonical(a), canonical(b));
+    let (sum, over) = a.overflowing_add(b);
+    // a + b < 2p, so after a wraparound sum + ε is already below p
+    if over {
+        sum + EPSILON
+    } else {
+        canonical(sum)
+    }
+}
+
+/// `a - b mod p`
+#[inline]
+pub fn sub(a: u64, b: u64) -> u64 {
+    let (a, b) = (canonical(a), canonical(b));
+    let (diff, under) = a.overflowing_sub(b);
+    // Adding p to the wrapped difference is subtracting ε modulo 2^64
+    if under {
+        diff - EPSILON
+    } else {
+        diff
+    }
+}
+
+/// `a * b mod p`
+#[inline]
+pub fn mul(a: u64, b: u64) -> u64 {
+    reduce128(a as u128 * b as u128)
+}
+
+/// Reduce a 128-bit value: `x_hi·2^64 + x_lo` with `2^64 ≡ ε` and `2^96 ≡ -1`
+#[inline]
+fn reduce128(x: u128) -> u64 {
+    let (x_lo, x_hi) = (x as u64, (x >> 64) as u64);
+    let (hi_hi, hi_lo) = (x_hi >> 32, x_hi & EPSILON);
+    let (mut t0, borrow) = x_lo.overflowing_sub(hi_hi);
+    if borrow {
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+    let t1 = hi_lo * EPSILON;
+    let (t2, carry) = t0.overflowing_add(t1);
+    // t1 <= (2^32 - 1)^2, so a carry leaves t2 small enough for + ε
+    canonical(if carry { t2 + EPSILON } else { t2 })
+}
+
+/// `a^exp mod p`
+pub fn pow(a: u64, mut exp: u64) -> u64 {
+    let (mut base, mut acc) = (canonical(a), 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul(acc, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+/// Multiplicative inverse `a^(p-2)`, `None` for zero
+pub fn inverse(a: u64) -> Option<u64> {
+    (canonical(a) != 0).then(|| pow(a, MODULUS - 2))
+}
+
+/// Two-lane `canonical`
+#[inline(always)]
+fn canonical_x2(x: U64x2) -> U64x2 {
+    let p = U64x2::splat(MODULUS);
+    // Subtract p, then add it back in lanes that were already below p
+    let reduced = x.wrapping_sub(p);
+    let keep = x.lt_mask(p);
+    reduced.wrapping_add(keep.and(p))
+}
+
+#[inline(always)]
+fn add_x2(a: U64x2, b: U64x2) -> U64x2 {
+    let (a, b) = (canonical_x2(a), canonical_x2(b));
+    let sum = a.wrapping_add(b);
+    let over = sum.lt_mask(a);
+    // After a wraparound sum + ε < p, and canonical_x2 leaves it alone
+    canonical_x2(sum.wrapping_add(over.and(U64x2::splat(EPSILON))))
+}
+
+#[inline(always)]
+fn sub_x2(a: U64x2, b: U64x2) -> U64x2 {
+    let (a, b) = (canonical_x2(a), canonical_x2(b));
+    let under = a.lt_mask(b);
+    a.wrapping_sub(b)
+        .wrapping_sub(under.and(U64x2::splat(EPSILON)))
+}
+
+/// Two-lane `mul`: the 128-bit product from four 32x32-bit digit products
+#[inline(always)]
+fn mul_x2(a: U64x2, b: U64x2) -> U64x2 {
+    let zero = U64x2::zero();
+    let (a0, a1) = (a.low32(), a.high32().low32());
+    let (b0, b1) = (b.low32(), b.high32().low32());
+    let lo = zero.mul_add(a0, b0);
+    let hi = zero.mul_add(a1, b1);
+    // Neither sum can overflow: (2^32 - 1)^2 + 2 (2^32 - 1) < 2^64
+    let mid1 = zero.mul_add(a0, b1).wrapping_add(lo.high32());
+    let mid2 = mid1.low32().widen().mul_add(a1, b0);
+    let x_lo = lo.low32().widen().wrapping_add(mid2.shift_left32());
+    let x_hi = hi.wrapping_add(mid1.high32()).wrapping_add(mid2.high32());
+    reduce128_x2(x_lo, x_hi)
+}
+
+/// Two-lane `reduce128`, with masks in place of the branches
+#[inline(always)]
+fn reduce128_x2(x_lo: U64x2, x_hi: U64x2) -> U64x2 {
+    let epsilon = U64x2::splat(EPSILON);
+    let hi_hi = x_hi.high32();
+    let hi_lo = x_hi.and(epsilon);
+    let borrow = x_lo.lt_mask(hi_hi);
+    let t0 = x_lo.wrapping_sub(hi_hi).wrapping_sub(borrow.and(epsilon));
+    // hi_lo * ε = hi_lo * 2^32 - hi_lo
+    let t1 = hi_lo.shift_left32().wrapping_sub(hi_lo);
+    let t2 = t0.wrapping_add(t1);
+    let carry = t2.lt_mask(t1);
+    canonical_x2(t2.wrapping_add(carry.and(epsilon)))
+}
+
+/// `out[i] = f(a[i], b[i])`, two lanes at a time when `neon` is set
+fn map_pairs(
+    a: &[u64],
+    b: &[u64],
+    neon: bool,
+    scalar: fn(u64, u64) -> u64,
+    vector: fn(U64x2, U64x2) -> U64x2,
+) -> Vec<u64> {
+    debug_assert_eq!(a.len(), b.len());
+    let mut out = vec![0u64; a.len()];
+    crate::parallel::par_chunks_mut(&mut out, 2, PARALLEL_THRESHOLD, |offset, chunk| {
+        let (a, b) = (&a[offset..], &b[offset..]);
+        let paired = if neon { chunk.len() & !1 } else { 0 };
+        for (i, o) in chunk[..paired].chunks_exact_mut(2).enumerate() {
+            let j = 2 * i;
+            let lanes = vector(U64x2::new(a[j], a[j + 1]), U64x2::new(b[j], b[j + 1])).lanes();
+            o.copy_from_slice(&lanes);
+        }
+        for (i, o) in chunk.iter_mut().enumerate().skip(paired) {
+            *o = scalar(a[i], b[i]);
+        }
+    });
+    out
+}
+
+/// Element-wise `a + b`
+pub fn add_batch(a: &[u64], b: &[u64]) -> Vec<u64> {
+    map_pairs(a, b, crate::neon::enabled(), add, add_x2)
+}
+
+/// Element-wise `a - b`
+pub fn sub_batch(a: &[u64], b: &[u64]) -> Vec<u64> {
+    map_pairs(a, b, crate::neon::enabled(), sub, sub_x2)
+}
+
+/// Element-wise `a * b`
+pub fn mul_batch(a: &[u64], b: &[u64]) -> Vec<u64> {
+    map_pairs(a, b, crate::neon::enabled(), mul, mul_x2)
+}
+
+/// Invert every element with Montgomery's trick; zeros stay zero
+pub fn inv_batch(values: &[u64]) -> Vec<u64> {
+    let mut out: Vec<u64> = values.iter().map(|&v| canonical(v)).collect();
+    crate::parallel::par_chunks_mut(&mut out, 1, PARALLEL_THRESHOLD, |_, chunk| {
+        // prefix[i] is the product of the non-zero elements before i
+        let mut prefix = Vec::with_capacity(chunk.len());
+        let mut acc = 1;
+        for &v in chunk.iter() {
+            prefix.push(acc);
+            if v != 0 {
+                acc = mul(acc, v);
+            }
+        }
+        let mut inv = inverse(acc).expect("a product of non-zero elements is non-zero");
+        for (v, p) in chunk.iter_mut().zip(prefix).rev() {
+            if *v == 0 {
+                continue;
+            }
+            let next = mul(inv, *v);
+            *v = mul(inv, p);
+            inv = next;
+        }
+    });
+    out
+}
+
+fn check_lengths(a: &[u64], b: &[u64]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same length, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Element-wise Goldilocks addition; inputs may be any `u64`
+#[napi]
+pub fn goldilocks_add(a: BigUint64Array, b: BigUint64Array) -> Result<BigUint64Array> {
+    check_lengths(&a, &b)?;
+    Ok(add_batch(&a, &b).into())
+}
+
+/// Element-wise Goldilocks subtraction `a - b`
+#[napi]
+pub fn goldilocks_sub(a: BigUint64Array, b: BigUint64Array) -> Result<BigUint64Array> {
+    check_lengths(&a, &b)?;
+    Ok(sub_batch(&a, &b).into())
+}
+
+/// Element-wise Goldilocks multiplication
+#[napi]
+pub fn goldilocks_mul(a: BigUint64Array, b: BigUint64Array) -> Result<BigUint64Array> {
+    check_lengths(&a, &b)?;
+    Ok(mul_batch(&a, &b).into())
+}
+
+/// Reject the first element congruent to zero
+pub fn check_invertible(values: &[u64]) -> Result<()> {
+    match values.iter().position(|&v| canonical(v) == 0) {
+        Some(i) => Err(Error::new(
+            Status::InvalidArg,
+            format!("values[{i}]: zero has no inverse"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Invert every element with a single exponentiation per worker chunk
+///
+/// A zero element (including `p`) is an error naming its index unless
+/// `allow_zero` is set, in which case zeros map to zero.
+#[napi]
+pub fn goldilocks_inv_batch(
+    values: BigUint64Array,
+    allow_zero: Option<bool>,
+) -> Result<BigUint64Array> {
+    crate::constant_time::require_variable_time("goldilocks_inv_batch")?;
+    if !allow_zero.unwrap_or(false) {
+        check_invertible(&values)?;
+    }
+    Ok(inv_batch(&values).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: u128 = MODULUS as u128;
+
+    /// Values around every wraparound: 0, ε, 2^32, p, 2^64
+    const BOUNDARY: [u64; 12] = [
+        0,
+        1,
+        2,
+        EPSILON - 1,
+        EPSILON,
+        1 << 32,
+        MODULUS - 2,
+        MODULUS - 1,
+        MODULUS,
+        MODULUS + 1,
+        u64::MAX - 1,
+        u64::MAX,
+    ];
+
+    fn samples() -> Vec<u64> {
+        let mut values = BOUNDARY.to_vec();
+        let mut x = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..52 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            values.push(x);
+        }
+        values
+    }
+
+    fn pairs() -> (Vec<u64>, Vec<u64>) {
+        let values = samples();
+        values
+            .iter()
+            .flat_map(|&a| values.iter().map(move |&b| (a, b)))
+            .unzip()
+    }
+
+    #[test]
+    fn test_scalar_matches_u128() {
+        for &a in &samples() {
+            for &b in &samples() {
+                let (x, y) = (a as u128 % P, b as u128 % P);
+                assert_eq!(add(a, b) as u128, (x + y) % P, "{a:#x} + {b:#x}");
+                assert_eq!(sub(a, b) as u128, (x + P - y) % P, "{a:#x} - {b:#x}");
+                assert_eq!(mul(a, b) as u128, x * y % P, "{a:#x} * {b:#x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_boundaries() {
+        // Non-canonical inputs are read modulo p
+        assert_eq!(canonical(MODULUS), 0);
+        assert_eq!(canonical(u64::MAX), EPSILON - 1);
+        assert_eq!(add(MODULUS - 1, 1), 0);
+        assert_eq!(add(u64::MAX, u64::MAX), 2 * (EPSILON - 1));
+        assert_eq!(add(MODULUS - 1, MODULUS - 1), MODULUS - 2);
+        assert_eq!(sub(0, 1), MODULUS - 1);
+        assert_eq!(sub(MODULUS, 1), MODULUS - 1);
+        assert_eq!(sub(1, u64::MAX), MODULUS - EPSILON + 2);
+        assert_eq!(mul(MODULUS - 1, MODULUS - 1), 1);
+        assert_eq!(mul(u64::MAX, u64::MAX), (EPSILON - 1) * (EPSILON - 1));
+        // 2^96 ≡ -1
+        assert_eq!(mul(1 << 32, 1 << 32), EPSILON);
+        assert_eq!(mul(1 << 48, 1 << 48), MODULUS - 1);
+    }
+
+    #[test]
+    fn test_vector_matches_scalar() {
+        let (a, b) = pairs();
+        for (neon, name) in [(true, "vector"), (false, "scalar")] {
+            let check = |got: Vec<u64>, f: fn(u64, u64) -> u64| {
+                for i in 0..a.len() {
+                    assert_eq!(got[i], f(a[i], b[i]), "{name} {:#x} {:#x}", a[i], b[i]);
+                }
+            };
+            check(map_pairs(&a, &b, neon, add, add_x2), add);
+            check(map_pairs(&a, &b, neon, sub, sub_x2), sub);
+            check(map_pairs(&a, &b, neon, mul, mul_x2), mul);
+        }
+        // Odd lengths finish on the scalar path
+        let odd = map_pairs(&a[..3], &b[..3], true, mul, mul_x2);
+        assert_eq!(odd, [mul(a[0], b[0]), mul(a[1], b[1]), mul(a[2], b[2])]);
+    }
+
+    #[test]
+    fn test_batches_across_threads() {
+        let n = 3 * PARALLEL_THRESHOLD + 5;
+        let a: Vec<u64> = (0..n as u64)
+            .map(|i| u64::MAX - i * 0x1_0000_0001)
+            .collect();
+        let b: Vec<u64> = (0..n as u64).map(|i| i.wrapping_mul(EPSILON) ^ 7).collect();
+        let products = mul_batch(&a, &b);
+        let sums = add_batch(&a, &b);
+        let diffs = sub_batch(&a, &b);
+        for i in (0..n).step_by(997) {
+            assert_eq!(products[i], mul(a[i], b[i]));
+            assert_eq!(sums[i], add(a[i], b[i]));
+            assert_eq!(diffs[i], sub(a[i], b[i]));
+        }
+        let inverses = inv_batch(&a);
+        for i in (0..n).step_by(997) {
+            assert_eq!(mul(inverses[i], a[i]), 1);
+        }
+    }
+
+    #[test]
+    fn test_inv_batch_zeros() {
+        let inverses = inv_batch(&[0, 1, MODULUS, MODULUS - 1, 2, u64::MAX]);
+        assert_eq!(inverses[..4], [0, 1, 0, MODULUS - 1]);
+        assert_eq!(mul(inverses[4], 2), 1);
+        assert_eq!(mul(inverses[5], u64::MAX), 1);
+        assert!(inv_batch(&[]).is_empty());
+        assert_eq!(inverse(MODULUS), None);
+
+        assert!(check_invertible(&[1, MODULUS - 1, u64::MAX]).is_ok());
+        let err = check_invertible(&[1, 2, MODULUS]).unwrap_err();
+        assert_eq!(err.reason, "values[2]: zero has no inverse");
+    }
+
+    #[test]
+    fn test_inv_batch_random() {
+        // x * inv(x) == 1 across batches large enough to split across workers
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        for len in [1, 7, 1000, 3 * PARALLEL_THRESHOLD + 5] {
+            let values: Vec<u64> = (0..len)
+                .map(|_| {
+                    x ^= x << 13;
+                    x ^= x >> 7;
+                    x ^= x << 17;
+                    x
+                })
+                .filter(|&v| canonical(v) != 0)
+                .collect();
+            let inverses = inv_batch(&values);
+            assert!(values.iter().zip(&inverses).all(|(&v, &i)| mul(v, i) == 1));
+        }
+    }
+}
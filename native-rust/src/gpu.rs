@@ -0,0 +1,126 @@
+//! Metal GPU device discovery
+//!
+//! On macOS the system default Metal device is queried through the `metal`
+//! crate. Every other platform reports an unavailable GPU instead of failing,
+//! so JavaScript schedulers can branch on `available` alone.
+
+use napi_derive::napi;
+
+/// GPU capabilities structure exposed to JavaScript
+#[napi(object)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RustGpuCapabilities {
+    /// Whether a usable Metal device was found
+    pub available: bool,
+    /// Device name reported by Metal (e.g. "Apple M2 Pro")
+    pub device_name: String,
+    /// Whether the GPU shares memory with the CPU (true on Apple Silicon)
+    pub is_unified_memory: bool,
+    /// Maximum threads per threadgroup (width dimension)
+    pub max_threads_per_threadgroup: u32,
+    /// Largest buffer the device can allocate, in bytes
+    pub max_buffer_length: i64,
+    /// Recommended upper bound for the working set, in bytes
+    pub recommended_working_set_size: i64,
+    /// Highest supported GPU family (e.g. "apple8", "mac2"), empty if unknown
+    pub gpu_family: String,
+}
+
+/// Detect the system default Metal GPU
+///
+/// Reports an unavailable GPU when `ZK_ACCEL_DISABLE_GPU` is set.
+#[napi]
+pub fn detect_gpu_capabilities() -> RustGpuCapabilities {
+    if crate::overrides::get().disable_gpu {
+        return RustGpuCapabilities::default();
+    }
+    detect()
+}
+
+/// Whether batch kernels can be dispatched to the GPU through Metal
+///
+/// Requires a build with the `metal` feature on macOS, a Metal device whose
+/// kernels compiled, and `ZK_ACCEL_DISABLE_GPU` unset.
+#[napi]
+pub fn metal_available() -> bool {
+    if crate::overrides::get().disable_gpu {
+        return false;
+    }
+    #[cfg(all(feature = "metal", target_os = "macos"))]
+    {
+        crate::metal_kernels::mont_mul_kernel().is_some()
+    }
+    #[cfg(not(all(feature = "metal", target_os = "macos")))]
+    {
+        false
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> RustGpuCapabilities {
+    use metal::{Device, MTLGPUFamily};
+
+    let Some(device) = Device::system_default() else {
+        return RustGpuCapabilities::default();
+    };
+
+    // Highest family first
+    let families = [
+        (MTLGPUFamily::Apple8, "apple8"),
+        (MTLGPUFamily::Apple7, "apple7"),
+        (MTLGPUFamily::Apple6, "apple6"),
+        (MTLGPUFamily::Mac2, "mac2"),
+    ];
+    let gpu_family = families
+        .iter()
+        .find(|(family, _)| device.supports_family(*family))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_default();
+
+    RustGpuCapabilities {
+        available: true,
+        device_name: device.name().to_string(),
+        is_unified_memory: device.has_unified_memory(),
+        max_threads_per_threadgroup: device.max_threads_per_threadgroup().width as u32,
+        max_buffer_length: device.max_buffer_length() as i64,
+        recommended_working_set_size: device.recommended_max_working_set_size() as i64,
+        gpu_family,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn detect() -> RustGpuCapabilities {
+    RustGpuCapabilities::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_gpu_capabilities() {
+        let gpu = detect_gpu_capabilities();
+        if gpu.available {
+            assert!(!gpu.device_name.is_empty());
+            assert!(gpu.max_threads_per_threadgroup > 0);
+            assert!(gpu.max_buffer_length > 0);
+        } else {
+            assert_eq!(gpu, RustGpuCapabilities::default());
+        }
+        if cfg!(not(target_os = "macos")) {
+            assert!(!gpu.available);
+        }
+    }
+
+    #[test]
+    fn test_metal_available() {
+        let metal = metal_available();
+        if metal {
+            assert!(detect_gpu_capabilities().available);
+        }
+        if cfg!(not(all(feature = "metal", target_os = "macos"))) {
+            assert!(!metal);
+        }
+        assert_eq!(crate::detect_rust_capabilities().has_metal, metal);
+    }
+}
@@ -0,0 +1,1142 @@
+//! Groth16 verification over BLS12-381, and proving and verification over
+//! BN254
+//!
+//! Keys and proofs use the uncompressed point encodings of
+//! [`crate::bls12_381`]: 96-byte G1 and 192-byte G2. Public inputs are
+//! 32-byte little-endian scalars, one per `ic` entry after the first.
+//!
+//! A proof `(A, B, C)` is accepted when
+//! `e(A, B) == e(α, β) · e(L, γ) · e(C, δ)` with
+//! `L = ic[0] + Σ x_i ic[i]`. `e(α, β)` is fixed by the key, so the check is a
+//! three-pair Miller loop and one final exponentiation.
+//!
+//! `groth16_simulate_proof` forges proofs from a key's toxic waste, the
+//! setup scalars `α, β, γ, δ` behind its points, working backwards from the
+//! equation: for random `A = [a]` and `B = [b]`,
+//! `C = [(ab - αβ) / δ] - (γ / δ)·L` makes both sides `e(g1, g2)^ab`. The
+//! toxic waste is a separate argument and never part of a
+//! `VerificationKey`; without it no such proof can be made.
+//!
+//! `prove_groth16` proves circom circuits with snarkjs proving keys (see
+//! [`crate::circom`]), producing the same proofs as `snarkjs groth16 prove`.
+//! The quotient is evaluated the way snarkjs lays out its `H` points: the A,
+//! B and C evaluations are interpolated, moved to the coset `ω_{2n} · D` of
+//! the domain `D`, and `h = a · b - c` is taken pointwise there. Proofs and
+//! public signals use the [`crate::bn254`] encodings, so they can be passed
+//! straight to the Ethereum pairing precompile. `verify_groth16` checks them
+//! against the key `groth16_verification_key` extracts from the `.zkey`,
+//! using the same equation as a four-pair product under BN254's ate pairing.
+
+#[cfg(not(feature = "wasm"))]
+use napi::bindgen_prelude::AsyncTask;
+#[cfg(not(feature = "wasm"))]
+use napi::{Env, JsFunction, Task};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bls12_381::{
+    decode_g1, decode_g1_subgroup, decode_g2, decode_g2_subgroup, decode_scalar_le, encode_g1,
+    encode_g2, g1_generator, g2_generator, Bls12Pairing, Fp12, Fr, G1Affine, G2Affine,
+};
+use crate::bn254;
+use crate::circom::{Matrix, R1cs, Zkey};
+use crate::msm::pippenger;
+use crate::ntt::{ntt_in_place, root_of_unity};
+use crate::pairing::{final_exponentiation, multi_miller_loop, pairing, pairing_product_is_one};
+use crate::random::{random_elements, FieldName};
+#[cfg(not(feature = "wasm"))]
+use crate::tasks::{progress_sink, reject_error, CancellationToken};
+use crate::tasks::{register_circuit, CancelFlag, PhaseProgress, Progress, ProofSystem};
+
+/// Groth16 verification key
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct VerificationKey {
+    pub alpha_g1: Vec<u8>,
+    pub beta_g2: Vec<u8>,
+    pub gamma_g2: Vec<u8>,
+    pub delta_g2: Vec<u8>,
+    /// `ic[0]` plus one G1 point per public input
+    pub ic: Vec<Vec<u8>>,
+}
+
+/// The secret setup scalars of a verification key, each 32-byte
+/// little-endian
+///
+/// `vk.alpha_g1 = [alpha] g1`, and `beta`, `gamma` and `delta` likewise give
+/// the G2 points. Anyone holding them can forge proofs for the key.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ToxicWaste {
+    pub alpha: Vec<u8>,
+    pub beta: Vec<u8>,
+    pub gamma: Vec<u8>,
+    pub delta: Vec<u8>,
+}
+
+/// A random verification key together with its toxic waste
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SimulationSetup {
+    pub vk: VerificationKey,
+    pub toxic_waste: ToxicWaste,
+}
+
+/// Groth16 proof
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Proof {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub c: Vec<u8>,
+    /// Set by `groth16_simulate_proof`; verification ignores it
+    pub simulated: Option<bool>,
+}
+
+/// Verification key with `e(α, β)` precomputed
+pub(crate) struct PreparedVerifyingKey {
+    alpha_beta: Fp12,
+    gamma: G2Affine,
+    delta: G2Affine,
+    ic: Vec<G1Affine>,
+}
+
+impl PreparedVerifyingKey {
+    pub(crate) fn new(
+        alpha: &G1Affine,
+        beta: &G2Affine,
+        gamma: G2Affine,
+        delta: G2Affine,
+        ic: Vec<G1Affine>,
+    ) -> Self {
+        PreparedVerifyingKey {
+            alpha_beta: pairing::<Bls12Pairing, 6>(alpha, beta),
+            gamma,
+            delta,
+            ic,
+        }
+    }
+
+    fn decode(vk: &VerificationKey) -> Result<Self> {
+        if vk.ic.is_empty() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "vk.ic: expected at least one point".to_string(),
+            ));
+        }
+        let ic = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(i, p)| decode_g1_subgroup(p, &format!("vk.ic[{i}]")))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(
+            &decode_g1_subgroup(&vk.alpha_g1, "vk.alpha_g1")?,
+            &decode_g2_subgroup(&vk.beta_g2, "vk.beta_g2")?,
+            decode_g2_subgroup(&vk.gamma_g2, "vk.gamma_g2")?,
+            decode_g2_subgroup(&vk.delta_g2, "vk.delta_g2")?,
+            ic,
+        ))
+    }
+
+    /// `ic[0] + Σ x_i ic[i]`
+    fn prepare_inputs(&self, inputs: &[Fr]) -> G1Affine {
+        let scalars: Vec<[u64; 4]> = inputs.iter().map(|x| x.to_canonical()).collect();
+        pippenger(&self.ic[1..], &scalars, true)
+            .add_affine(&self.ic[0])
+            .to_affine()
+    }
+
+    /// Check `e(A, B) · e(-L, γ) · e(-C, δ) == e(α, β)`
+    pub(crate) fn verify(
+        &self,
+        a: &G1Affine,
+        b: &G2Affine,
+        c: &G1Affine,
+        inputs: &[Fr],
+    ) -> Result<bool> {
+        if inputs.len() + 1 != self.ic.len() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "public_inputs: key expects {} inputs, got {}",
+                    self.ic.len() - 1,
+                    inputs.len()
+                ),
+            ));
+        }
+        let l = self.prepare_inputs(inputs);
+        let f = multi_miller_loop::<Bls12Pairing, 6>(&[
+            (*a, *b),
+            (l.neg(), self.gamma),
+            (c.neg(), self.delta),
+        ]);
+        Ok(final_exponentiation::<Bls12Pairing, 6>(&f) == self.alpha_beta)
+    }
+}
+
+/// Decode 32-byte little-endian public inputs, one per `ic` point after the
+/// first
+fn decode_inputs(pvk: &PreparedVerifyingKey, public_inputs: &[Vec<u8>]) -> Result<Vec<Fr>> {
+    if public_inputs.len() + 1 != pvk.ic.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "public_inputs: key expects {} inputs, got {}",
+                pvk.ic.len() - 1,
+                public_inputs.len()
+            ),
+        ));
+    }
+    public_inputs
+        .iter()
+        .enumerate()
+        .map(|(i, x)| decode_scalar_le(x, &format!("public_inputs[{i}]")))
+        .collect()
+}
+
+/// Verify a Groth16 proof against a verification key and public inputs
+///
+/// Every proof and key point must lie in its prime-order subgroup; points
+/// outside them throw rather than verify false.
+#[napi]
+pub fn groth16_verify(
+    vk: VerificationKey,
+    proof: Proof,
+    public_inputs: Vec<Vec<u8>>,
+) -> Result<bool> {
+    let pvk = PreparedVerifyingKey::decode(&vk)?;
+    let inputs = decode_inputs(&pvk, &public_inputs)?;
+    let a = decode_g1_subgroup(&proof.a, "proof.a")?;
+    let b = decode_g2_subgroup(&proof.b, "proof.b")?;
+    let c = decode_g1_subgroup(&proof.c, "proof.c")?;
+    pvk.verify(&a, &b, &c, &inputs)
+}
+
+/// Nonzero scalars from the OS random number generator
+fn random_scalars(n: usize) -> Result<Vec<Fr>> {
+    let bytes = random_elements(FieldName::Bls12_381Fr, n, None)?;
+    let scalars: Vec<Fr> = bytes
+        .chunks_exact(32)
+        .map(|b| decode_scalar_le(b, "random").expect("sampled below r"))
+        .collect();
+    if scalars.iter().any(Fr::is_zero) {
+        // Probability about n / 2^255
+        return random_scalars(n);
+    }
+    Ok(scalars)
+}
+
+fn g1_mul(s: Fr) -> G1Affine {
+    g1_generator()
+        .to_jacobian()
+        .mul_limbs(&s.to_canonical())
+        .to_affine()
+}
+
+fn g2_mul(s: Fr) -> G2Affine {
+    g2_generator()
+        .to_jacobian()
+        .mul_limbs(&s.to_canonical())
+        .to_affine()
+}
+
+/// Generate a random verification key for simulated proofs with
+/// `n_public` inputs
+///
+/// FOR TESTING ONLY. The key corresponds to no circuit, and its toxic waste
+/// is returned alongside it for [`groth16_simulate_proof`]. Never trust such
+/// a key to verify anything.
+#[napi]
+pub fn groth16_simulation_setup(n_public: u32) -> Result<SimulationSetup> {
+    let n = n_public as usize;
+    let s = random_scalars(5 + n)?;
+    let (alpha, beta, gamma, delta) = (s[0], s[1], s[2], s[3]);
+    Ok(SimulationSetup {
+        vk: VerificationKey {
+            alpha_g1: encode_g1(&g1_mul(alpha)),
+            beta_g2: encode_g2(&g2_mul(beta)),
+            gamma_g2: encode_g2(&g2_mul(gamma)),
+            delta_g2: encode_g2(&g2_mul(delta)),
+            ic: s[4..].iter().map(|&x| encode_g1(&g1_mul(x))).collect(),
+        },
+        toxic_waste: ToxicWaste {
+            alpha: alpha.to_le_bytes(),
+            beta: beta.to_le_bytes(),
+            gamma: gamma.to_le_bytes(),
+            delta: delta.to_le_bytes(),
+        },
+    })
+}
+
+/// Forge a proof that `groth16_verify` accepts for `vk` and `public_inputs`
+///
+/// FOR TESTING ONLY, to exercise verification pipelines without a proving
+/// key. `toxic_waste` must be the setup scalars of `vk`, from a ceremony you
+/// ran yourself or from [`groth16_simulation_setup`]. The proof is valid
+/// for that key alone and proves nothing about any circuit. It is
+/// rerandomized on every call and has `simulated` set.
+#[napi]
+pub fn groth16_simulate_proof(
+    vk: VerificationKey,
+    public_inputs: Vec<Vec<u8>>,
+    toxic_waste: ToxicWaste,
+) -> Result<Proof> {
+    let pvk = PreparedVerifyingKey::decode(&vk)?;
+    let inputs = decode_inputs(&pvk, &public_inputs)?;
+    let scalar = |bytes: &[u8], name: &str| decode_scalar_le(bytes, &format!("toxic_waste.{name}"));
+    let alpha = scalar(&toxic_waste.alpha, "alpha")?;
+    let beta = scalar(&toxic_waste.beta, "beta")?;
+    let gamma = scalar(&toxic_waste.gamma, "gamma")?;
+    let delta = scalar(&toxic_waste.delta, "delta")?;
+    let mismatch = |name: &str, point: &str| {
+        Error::new(
+            Status::InvalidArg,
+            format!("toxic_waste.{name}: does not match vk.{point}"),
+        )
+    };
+    let keyed = [
+        (
+            g1_mul(alpha) == decode_g1(&vk.alpha_g1, "vk.alpha_g1")?,
+            "alpha",
+            "alpha_g1",
+        ),
+        (
+            g2_mul(beta) == decode_g2(&vk.beta_g2, "vk.beta_g2")?,
+            "beta",
+            "beta_g2",
+        ),
+        (g2_mul(gamma) == pvk.gamma, "gamma", "gamma_g2"),
+        (g2_mul(delta) == pvk.delta, "delta", "delta_g2"),
+    ];
+    if let Some(&(_, name, point)) = keyed.iter().find(|(matches, ..)| !matches) {
+        return Err(mismatch(name, point));
+    }
+    let delta_inv = delta.inverse().ok_or_else(|| {
+        Error::new(
+            Status::InvalidArg,
+            "toxic_waste.delta: must be nonzero".to_string(),
+        )
+    })?;
+
+    let s = random_scalars(2)?;
+    let (a, b) = (s[0], s[1]);
+    let l = pvk.prepare_inputs(&inputs);
+    let c = g1_mul((a * b - alpha * beta) * delta_inv)
+        .to_jacobian()
+        .add(
+            &l.to_jacobian()
+                .mul_limbs(&(gamma * delta_inv).to_canonical())
+                .neg(),
+        );
+    Ok(Proof {
+        a: encode_g1(&g1_mul(a)),
+        b: encode_g2(&g2_mul(b)),
+        c: encode_g1(&c.to_affine()),
+        simulated: Some(true),
+    })
+}
+
+/// BN254 Groth16 proof from `prove_groth16`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct GrothProof {
+    /// 64-byte G1 point
+    pub pi_a: Vec<u8>,
+    /// 128-byte G2 point
+    pub pi_b: Vec<u8>,
+    /// 64-byte G1 point
+    pub pi_c: Vec<u8>,
+    /// 32-byte big-endian public signals, outputs before inputs
+    pub public_signals: Vec<Vec<u8>>,
+}
+
+/// BN254 Groth16 verification key, the points of `snarkjs zkey export
+/// verificationkey`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct GrothVerificationKey {
+    /// 64-byte G1 point
+    pub alpha_g1: Vec<u8>,
+    /// 128-byte G2 point
+    pub beta_g2: Vec<u8>,
+    /// 128-byte G2 point
+    pub gamma_g2: Vec<u8>,
+    /// 128-byte G2 point
+    pub delta_g2: Vec<u8>,
+    /// `ic[0]` plus one 64-byte G1 point per public signal
+    pub ic: Vec<Vec<u8>>,
+}
+
+/// Extract the verification key from a snarkjs `.zkey` proving key
+#[napi]
+pub fn groth16_verification_key(proving_key: Vec<u8>) -> Result<GrothVerificationKey> {
+    let zkey = Zkey::parse(&proving_key, "proving_key")?;
+    Ok(verification_key(&zkey))
+}
+
+fn verification_key(zkey: &Zkey) -> GrothVerificationKey {
+    GrothVerificationKey {
+        alpha_g1: bn254::encode_g1(&zkey.alpha_g1),
+        beta_g2: bn254::encode_g2(&zkey.beta_g2),
+        gamma_g2: bn254::encode_g2(&zkey.gamma_g2),
+        delta_g2: bn254::encode_g2(&zkey.delta_g2),
+        ic: zkey.ic.iter().map(bn254::encode_g1).collect(),
+    }
+}
+
+/// Verify a `prove_groth16` proof for its `public_signals`
+///
+/// Every proof and key point must lie in its prime-order subgroup; points
+/// outside them throw rather than verify false.
+#[napi]
+pub fn verify_groth16(vk: GrothVerificationKey, proof: GrothProof) -> Result<bool> {
+    if proof.public_signals.len() + 1 != vk.ic.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "proof.public_signals: key expects {} signals, got {}",
+                vk.ic.len().saturating_sub(1),
+                proof.public_signals.len()
+            ),
+        ));
+    }
+    let ic = vk
+        .ic
+        .iter()
+        .enumerate()
+        .map(|(i, p)| bn254::decode_g1(p, &format!("vk.ic[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let signals = proof
+        .public_signals
+        .iter()
+        .enumerate()
+        .map(|(i, v)| bn254::parse_fr(v, &format!("proof.public_signals[{i}]")))
+        .map(|v| v.map(|v| v.to_canonical()))
+        .collect::<Result<Vec<_>>>()?;
+    let l = pippenger(&ic[1..], &signals, true)
+        .add_affine(&ic[0])
+        .to_affine();
+    // G1 has cofactor 1, so points on the curve are in the subgroup
+    Ok(pairing_product_is_one::<bn254::Bn254Pairing, 4>(&[
+        (
+            bn254::decode_g1(&proof.pi_a, "proof.pi_a")?,
+            bn254::decode_g2_subgroup(&proof.pi_b, "proof.pi_b")?,
+        ),
+        (
+            bn254::decode_g1(&vk.alpha_g1, "vk.alpha_g1")?.neg(),
+            bn254::decode_g2_subgroup(&vk.beta_g2, "vk.beta_g2")?,
+        ),
+        (
+            l.neg(),
+            bn254::decode_g2_subgroup(&vk.gamma_g2, "vk.gamma_g2")?,
+        ),
+        (
+            bn254::decode_g1(&proof.pi_c, "proof.pi_c")?.neg(),
+            bn254::decode_g2_subgroup(&vk.delta_g2, "vk.delta_g2")?,
+        ),
+    ]))
+}
+
+/// Evaluations of `a · b - c` on the coset `ω_{2n} · D`
+fn quotient_evaluations(
+    zkey: &Zkey,
+    witness: &[bn254::Fr],
+    ntt: &PhaseProgress,
+) -> Result<Vec<bn254::Fr>> {
+    let n = zkey.domain_size;
+    let mut a = vec![bn254::Fr::zero(); n];
+    let mut b = vec![bn254::Fr::zero(); n];
+    for coeff in &zkey.coefficients {
+        let evals = match coeff.matrix {
+            Matrix::A => &mut a,
+            Matrix::B => &mut b,
+        };
+        evals[coeff.constraint as usize] += coeff.value * witness[coeff.signal as usize];
+    }
+    let mut c: Vec<bn254::Fr> = a.iter().zip(&b).map(|(&x, &y)| x * y).collect();
+
+    let shift = root_of_unity(n.trailing_zeros() + 1);
+    for evals in [&mut a, &mut b, &mut c] {
+        ntt_in_place(evals, true)?;
+        ntt.advance(1);
+        let mut power = bn254::Fr::one();
+        for coeff in evals.iter_mut() {
+            *coeff *= power;
+            power *= shift;
+        }
+        ntt_in_place(evals, false)?;
+        ntt.advance(1);
+    }
+    Ok(a.iter()
+        .zip(&b)
+        .zip(&c)
+        .map(|((&x, &y), &z)| x * y - z)
+        .collect())
+}
+
+/// Check that `zkey` was set up for the wires of `r1cs`
+fn check_key_fits(r1cs: &R1cs, zkey: &Zkey) -> Result<()> {
+    if r1cs.n_wires != zkey.n_vars || r1cs.n_public() != zkey.n_public {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "proving_key: built for {} wires and {} public signals, the r1cs has {} and {}",
+                zkey.n_vars,
+                zkey.n_public,
+                r1cs.n_wires,
+                r1cs.n_public()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Prove `r1cs` for a full `witness` with blinding factors `r` and `s`
+///
+/// The witness is checked against every constraint first, so an invalid
+/// assignment is reported instead of yielding a proof that fails to verify.
+/// `progress` moves through the phases "witness" (the constraint check, to
+/// 10%), "ntt" (the six transforms of the quotient, to 40%) and "msm" (by
+/// points multiplied, to 95%); the caller reports "final".
+pub fn prove_bn254(
+    r1cs: &R1cs,
+    zkey: &Zkey,
+    witness: &[bn254::Fr],
+    r: bn254::Fr,
+    s: bn254::Fr,
+    cancel: &CancelFlag,
+    progress: &Progress,
+) -> Result<GrothProof> {
+    check_key_fits(r1cs, zkey)?;
+    progress.report("witness", 0.0);
+    if let Some(i) = crate::r1cs::first_unsatisfied(r1cs, witness)? {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("witness: constraint {i} is not satisfied"),
+        ));
+    }
+    progress.report("witness", 10.0);
+    cancel.check()?;
+
+    let h = quotient_evaluations(zkey, witness, &progress.phase("ntt", 10.0, 40.0, 6))?;
+    cancel.check()?;
+
+    let canonical = |values: &[bn254::Fr]| -> Vec<[u64; 4]> {
+        values.iter().map(|v| v.to_canonical()).collect()
+    };
+    let w = canonical(witness);
+    let (r_limbs, s_limbs) = (r.to_canonical(), s.to_canonical());
+    let delta_g1 = zkey.delta_g1.to_jacobian();
+    let points = [
+        zkey.a.len(),
+        zkey.b_g2.len(),
+        zkey.b_g1.len(),
+        zkey.c.len(),
+        zkey.h.len(),
+    ];
+    let msm = progress.phase("msm", 40.0, 95.0, points.iter().sum::<usize>() as u64);
+    let tracked = |points: &[bn254::G1Affine], scalars: &[[u64; 4]]| {
+        let sum = pippenger(points, scalars, true);
+        msm.advance(points.len() as u64);
+        sum
+    };
+
+    let pi_a = tracked(&zkey.a, &w)
+        .add_affine(&zkey.alpha_g1)
+        .add(&delta_g1.mul_limbs(&r_limbs));
+    let pi_b = pippenger(&zkey.b_g2, &w, true)
+        .add_affine(&zkey.beta_g2)
+        .add(&zkey.delta_g2.to_jacobian().mul_limbs(&s_limbs));
+    msm.advance(zkey.b_g2.len() as u64);
+    let pi_b1 = tracked(&zkey.b_g1, &w)
+        .add_affine(&zkey.beta_g1)
+        .add(&delta_g1.mul_limbs(&s_limbs));
+    cancel.check()?;
+    let pi_c = tracked(&zkey.c, &w[zkey.n_public + 1..])
+        .add(&tracked(&zkey.h, &canonical(&h)))
+        .add(&pi_a.mul_limbs(&s_limbs))
+        .add(&pi_b1.mul_limbs(&r_limbs))
+        .add(&delta_g1.mul_limbs(&(r * s).to_canonical()).neg());
+
+    Ok(GrothProof {
+        pi_a: bn254::encode_g1(&pi_a.to_affine()),
+        pi_b: bn254::encode_g2(&pi_b.to_affine()),
+        pi_c: bn254::encode_g1(&pi_c.to_affine()),
+        public_signals: witness[1..=zkey.n_public]
+            .iter()
+            .map(|v| v.to_be_bytes())
+            .collect(),
+    })
+}
+
+/// [`prove_bn254`] with fresh random blinding factors
+fn prove_blinded(
+    r1cs: &R1cs,
+    zkey: &Zkey,
+    witness: &[bn254::Fr],
+    cancel: &CancelFlag,
+    progress: &Progress,
+) -> Result<GrothProof> {
+    let blinding = random_elements(FieldName::Bn254Fr, 2, None)?;
+    let scalar = |bytes: &[u8]| bn254::Fr::from_le_bytes(bytes).unwrap();
+    prove_bn254(
+        r1cs,
+        zkey,
+        witness,
+        scalar(&blinding[..32]),
+        scalar(&blinding[32..]),
+        cancel,
+        progress,
+    )
+}
+
+/// Size in bytes of a `prove_async` Groth16 proof body, `pi_a || pi_b || pi_c`
+const PROOF_BODY_BYTES: usize = 2 * bn254::G1_BYTES + bn254::G2_BYTES;
+
+/// A circom circuit and its proving key, served by `prove_async`
+struct CircomGroth16 {
+    r1cs: R1cs,
+    zkey: Zkey,
+    vk: GrothVerificationKey,
+}
+
+impl ProofSystem for CircomGroth16 {
+    fn prove(&self, witness: &[u8], cancel: &CancelFlag, progress: &Progress) -> Result<Vec<u8>> {
+        crate::constant_time::require_variable_time("prove_async")?;
+        if !witness.len().is_multiple_of(bn254::FR_BYTES) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "witness: expected packed {}-byte values, got {} bytes",
+                    bn254::FR_BYTES,
+                    witness.len()
+                ),
+            ));
+        }
+        let witness = witness
+            .chunks_exact(bn254::FR_BYTES)
+            .enumerate()
+            .map(|(i, v)| bn254::parse_fr(v, &format!("witness[{i}]")))
+            .collect::<Result<Vec<_>>>()?;
+        let proof = prove_blinded(&self.r1cs, &self.zkey, &witness, cancel, progress)?;
+        Ok([proof.pi_a, proof.pi_b, proof.pi_c].concat())
+    }
+
+    fn verify(&self, proof: &[u8], public_inputs: &[Vec<u8>], _: &CancelFlag) -> Result<bool> {
+        if proof.len() != PROOF_BODY_BYTES {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "proof: expected a {PROOF_BODY_BYTES}-byte Groth16 proof, got {} bytes",
+                    proof.len()
+                ),
+            ));
+        }
+        let (pi_a, rest) = proof.split_at(bn254::G1_BYTES);
+        let (pi_b, pi_c) = rest.split_at(bn254::G2_BYTES);
+        verify_groth16(
+            self.vk.clone(),
+            GrothProof {
+                pi_a: pi_a.to_vec(),
+                pi_b: pi_b.to_vec(),
+                pi_c: pi_c.to_vec(),
+                public_signals: public_inputs.to_vec(),
+            },
+        )
+    }
+}
+
+/// Register a circom circuit under `circuit_id` for `prove_async` and
+/// `verify_async`
+///
+/// `r1cs` and `proving_key` are as for `prove_groth16`, and are parsed once
+/// here. `prove_async` then takes every wire packed as 32-byte big-endian
+/// values, starting with the constant 1, and resolves with the framed
+/// `pi_a || pi_b || pi_c`. `verify_async` checks that proof against the
+/// public signals as 32-byte big-endian values. Registering an id again
+/// replaces the circuit.
+#[napi]
+pub fn register_groth16_circuit(
+    circuit_id: String,
+    r1cs: Vec<u8>,
+    proving_key: Vec<u8>,
+) -> Result<()> {
+    crate::tasks::check_circuit_id(&circuit_id)?;
+    let r1cs = R1cs::parse(&r1cs, "r1cs")?;
+    let zkey = Zkey::parse(&proving_key, "proving_key")?;
+    check_key_fits(&r1cs, &zkey)?;
+    let vk = verification_key(&zkey);
+    register_circuit(
+        &circuit_id,
+        std::sync::Arc::new(CircomGroth16 { r1cs, zkey, vk }),
+    )
+}
+
+/// Background task behind `prove_groth16`
+#[cfg(not(feature = "wasm"))]
+pub struct ProveGroth16Task {
+    r1cs: Vec<u8>,
+    witness: Vec<Vec<u8>>,
+    proving_key: Vec<u8>,
+    cancel: CancelFlag,
+    progress: Progress,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl Task for ProveGroth16Task {
+    type Output = GrothProof;
+    type JsValue = GrothProof;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.cancel.check()?;
+        crate::constant_time::require_variable_time("prove_groth16")?;
+        let r1cs = R1cs::parse(&self.r1cs, "r1cs")?;
+        let zkey = Zkey::parse(&self.proving_key, "proving_key")?;
+        let witness = self
+            .witness
+            .iter()
+            .enumerate()
+            .map(|(i, v)| bn254::parse_fr(v, &format!("witness[{i}]")))
+            .collect::<Result<Vec<_>>>()?;
+        let proof = prove_blinded(&r1cs, &zkey, &witness, &self.cancel, &self.progress)?;
+        self.cancel.check()?;
+        self.progress.report("final", 100.0);
+        Ok(proof)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(reject_error(env, err))
+    }
+}
+
+/// Prove a circom circuit off the main thread
+///
+/// `r1cs` is the circuit's `.r1cs` file and `proving_key` its snarkjs
+/// `.zkey`, both over BN254. `witness` holds every wire as a 32-byte
+/// big-endian scalar, starting with the constant 1, as `snarkjs wtns export
+/// json` lists them. Rejects with `code: "CANCELLED"` if `token` is
+/// cancelled before the proof is returned. `on_progress` is called as for
+/// `prove_async`. Not in the `wasm` build.
+#[cfg(not(feature = "wasm"))]
+#[napi(ts_return_type = "Promise<GrothProof>")]
+pub fn prove_groth16(
+    r1cs: Vec<u8>,
+    witness: Vec<Vec<u8>>,
+    proving_key: Vec<u8>,
+    token: Option<&CancellationToken>,
+    #[napi(ts_arg_type = "(progress: ProofProgress) => void")] on_progress: Option<JsFunction>,
+) -> Result<AsyncTask<ProveGroth16Task>> {
+    Ok(AsyncTask::new(ProveGroth16Task {
+        r1cs,
+        witness,
+        proving_key,
+        cancel: token.map(CancellationToken::flag).unwrap_or_default(),
+        progress: Progress::new(progress_sink(on_progress)?),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A key and proof for two public inputs built from known trapdoors
+    ///
+    /// With `A = [a]_1`, `B = [b]_2` and `C = [c]_1`, the verification
+    /// equation reduces to `ab = αβ + lγ + cδ` in the exponent, so `c` is
+    /// solved for directly.
+    fn fixture(inputs: &[Fr]) -> (VerificationKey, Proof) {
+        let s = |v: u64| Fr::from_u64(v);
+        let (alpha, beta, gamma, delta) = (s(11), s(13), s(17), s(19));
+        let ic_scalars = [s(23), s(29), s(31)];
+        let (a, b) = (s(37), s(41));
+        let l = ic_scalars[0] + ic_scalars[1] * inputs[0] + ic_scalars[2] * inputs[1];
+        let c = (a * b - alpha * beta - l * gamma) * delta.inverse().unwrap();
+        let vk = VerificationKey {
+            alpha_g1: encode_g1(&g1_mul(alpha)),
+            beta_g2: encode_g2(&g2_mul(beta)),
+            gamma_g2: encode_g2(&g2_mul(gamma)),
+            delta_g2: encode_g2(&g2_mul(delta)),
+            ic: ic_scalars.iter().map(|&x| encode_g1(&g1_mul(x))).collect(),
+        };
+        let proof = Proof {
+            a: encode_g1(&g1_mul(a)),
+            b: encode_g2(&g2_mul(b)),
+            c: encode_g1(&g1_mul(c)),
+            simulated: None,
+        };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_verify_two_inputs() {
+        let inputs = [Fr::from_u64(3), Fr::from_u64(35)];
+        let (vk, proof) = fixture(&inputs);
+        let encoded: Vec<Vec<u8>> = inputs.iter().map(|x| x.to_le_bytes()).collect();
+        assert!(groth16_verify(vk.clone(), proof.clone(), encoded.clone()).unwrap());
+
+        let mut wrong = encoded.clone();
+        wrong[1] = Fr::from_u64(36).to_le_bytes();
+        assert!(!groth16_verify(vk.clone(), proof.clone(), wrong).unwrap());
+
+        let mut tampered = proof;
+        tampered.c = encode_g1(&g1_mul(Fr::from_u64(5)));
+        assert!(!groth16_verify(vk, tampered, encoded).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_malformed_inputs() {
+        let inputs = [Fr::from_u64(1), Fr::from_u64(2)];
+        let (vk, proof) = fixture(&inputs);
+        assert!(groth16_verify(vk.clone(), proof.clone(), vec![inputs[0].to_le_bytes()]).is_err());
+        let mut bad = proof;
+        bad.b = vec![0u8; 96];
+        let encoded = inputs.iter().map(|x| x.to_le_bytes()).collect();
+        assert!(groth16_verify(vk.clone(), bad, encoded).is_err());
+
+        // The prepared key checks the input count itself instead of panicking
+        let pvk = PreparedVerifyingKey::decode(&vk).unwrap();
+        let g1 = g1_generator();
+        let err = pvk
+            .verify(&g1, &g2_generator(), &g1, &inputs[..1])
+            .unwrap_err();
+        assert_eq!(err.reason, "public_inputs: key expects 2 inputs, got 1");
+    }
+
+    #[test]
+    fn test_rejects_points_outside_subgroups() {
+        let inputs = [Fr::from_u64(1), Fr::from_u64(2)];
+        let encoded: Vec<Vec<u8>> = inputs.iter().map(|x| x.to_le_bytes()).collect();
+        let (vk, proof) = fixture(&inputs);
+        let reason = |vk: VerificationKey, proof: Proof| {
+            groth16_verify(vk, proof, encoded.clone())
+                .unwrap_err()
+                .reason
+        };
+
+        // An order-13 B used to panic inside the Miller loop
+        let q13 = encode_g2(&crate::bls12_381::g2_point_of_order_13());
+        let mut bad = proof.clone();
+        bad.b = q13.clone();
+        assert_eq!(
+            reason(vk.clone(), bad),
+            "proof.b: point is not in the BLS12-381 G2 subgroup"
+        );
+        let mut bad_vk = vk.clone();
+        bad_vk.delta_g2 = q13;
+        assert_eq!(
+            reason(bad_vk, proof.clone()),
+            "vk.delta_g2: point is not in the BLS12-381 G2 subgroup"
+        );
+
+        // A point on the G1 curve with cofactor torsion
+        use crate::bls12_381::Fp;
+        let outside = (1u64..)
+            .find_map(|i| {
+                let x = Fp::from_u64(i);
+                let y = (x.square() * x + Fp::from_u64(4)).sqrt(-Fp::one())?;
+                Some(G1Affine::new(x, y))
+            })
+            .unwrap();
+        let mut bad = proof.clone();
+        bad.a = encode_g1(&outside);
+        assert_eq!(
+            reason(vk.clone(), bad),
+            "proof.a: point is not in the BLS12-381 G1 subgroup"
+        );
+        let mut bad = proof.clone();
+        bad.c = encode_g1(&outside);
+        assert_eq!(
+            reason(vk.clone(), bad),
+            "proof.c: point is not in the BLS12-381 G1 subgroup"
+        );
+
+        // (0, 2) has order 3 on y^2 = x^3 + 4
+        let order_3 = G1Affine::new(Fp::zero(), Fp::from_u64(2));
+        assert!(order_3.to_jacobian().mul_limbs(&[3]).is_identity());
+        let mut bad_vk = vk.clone();
+        bad_vk.alpha_g1 = encode_g1(&order_3);
+        assert_eq!(
+            reason(bad_vk, proof.clone()),
+            "vk.alpha_g1: point is not in the BLS12-381 G1 subgroup"
+        );
+        for i in 0..vk.ic.len() {
+            let mut bad_vk = vk.clone();
+            bad_vk.ic[i] = encode_g1(&order_3);
+            assert_eq!(
+                reason(bad_vk, proof.clone()),
+                format!("vk.ic[{i}]: point is not in the BLS12-381 G1 subgroup")
+            );
+        }
+        assert!(groth16_verify(vk, proof, encoded).unwrap());
+    }
+
+    #[test]
+    fn test_simulated_proofs_verify() {
+        let SimulationSetup { vk, toxic_waste } = groth16_simulation_setup(3).unwrap();
+        assert_eq!(vk.ic.len(), 4);
+        let inputs: Vec<Vec<u8>> = [5, 0, 1 << 40]
+            .map(|v| Fr::from_u64(v).to_le_bytes())
+            .to_vec();
+        let simulate = |vk: &VerificationKey, inputs: &[Vec<u8>], tw: &ToxicWaste| {
+            groth16_simulate_proof(vk.clone(), inputs.to_vec(), tw.clone()).unwrap()
+        };
+        let proof = simulate(&vk, &inputs, &toxic_waste);
+        assert_eq!(proof.simulated, Some(true));
+        assert!(groth16_verify(vk.clone(), proof.clone(), inputs.clone()).unwrap());
+
+        // Rerandomized each time, and bound to the inputs and the key
+        let again = simulate(&vk, &inputs, &toxic_waste);
+        assert_ne!(again.a, proof.a);
+        assert!(groth16_verify(vk.clone(), again, inputs.clone()).unwrap());
+        let mut other_inputs = inputs.clone();
+        other_inputs[1] = Fr::from_u64(1).to_le_bytes();
+        assert!(!groth16_verify(vk.clone(), proof.clone(), other_inputs).unwrap());
+        let other = groth16_simulation_setup(3).unwrap();
+        assert!(!groth16_verify(other.vk, proof, inputs.clone()).unwrap());
+
+        let setup = groth16_simulation_setup(0).unwrap();
+        let proof = simulate(&setup.vk, &[], &setup.toxic_waste);
+        assert!(groth16_verify(setup.vk, proof, vec![]).unwrap());
+    }
+
+    #[test]
+    fn test_simulate_ordinary_key() {
+        // The fixture key is built directly from its setup scalars
+        let inputs = [Fr::from_u64(3), Fr::from_u64(35)];
+        let encoded: Vec<Vec<u8>> = inputs.iter().map(|x| x.to_le_bytes()).collect();
+        let (vk, _) = fixture(&inputs);
+        let s = |v: u64| Fr::from_u64(v).to_le_bytes();
+        let toxic_waste = ToxicWaste {
+            alpha: s(11),
+            beta: s(13),
+            gamma: s(17),
+            delta: s(19),
+        };
+        let proof =
+            groth16_simulate_proof(vk.clone(), encoded.clone(), toxic_waste.clone()).unwrap();
+        assert!(groth16_verify(vk.clone(), proof, encoded.clone()).unwrap());
+
+        let reason = |tw: ToxicWaste, inputs: Vec<Vec<u8>>| {
+            groth16_simulate_proof(vk.clone(), inputs, tw)
+                .unwrap_err()
+                .reason
+        };
+        let mut wrong = toxic_waste.clone();
+        wrong.gamma = s(18);
+        assert_eq!(
+            reason(wrong, encoded.clone()),
+            "toxic_waste.gamma: does not match vk.gamma_g2"
+        );
+        let mut wrong = toxic_waste.clone();
+        wrong.alpha = s(12);
+        assert_eq!(
+            reason(wrong, encoded.clone()),
+            "toxic_waste.alpha: does not match vk.alpha_g1"
+        );
+        assert_eq!(
+            reason(toxic_waste, vec![]),
+            "public_inputs: key expects 2 inputs, got 0"
+        );
+    }
+
+    /// circom's multiplier (`c <== a * b`, `c` public) with its snarkjs key
+    const MULTIPLIER_R1CS: &[u8] = include_bytes!("../tests/fixtures/multiplier.r1cs");
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../tests/fixtures/multiplier.zkey");
+
+    #[cfg(not(feature = "wasm"))]
+    fn multiplier_witness(c: u64) -> Vec<Vec<u8>> {
+        [1, c, 3, 11]
+            .iter()
+            .map(|&v| bn254::Fr::from_u64(v).to_be_bytes())
+            .collect()
+    }
+
+    fn ark_g1(bytes: &[u8]) -> ark_bn254::G1Affine {
+        use ark_ff::PrimeField;
+        let fq = |b: &[u8]| ark_bn254::Fq::from_be_bytes_mod_order(b);
+        ark_bn254::G1Affine::new(fq(&bytes[..32]), fq(&bytes[32..]))
+    }
+
+    fn ark_g2(bytes: &[u8]) -> ark_bn254::G2Affine {
+        use ark_ff::PrimeField;
+        let fq = |i: usize| ark_bn254::Fq::from_be_bytes_mod_order(&bytes[i * 32..(i + 1) * 32]);
+        ark_bn254::G2Affine::new(
+            ark_bn254::Fq2::new(fq(1), fq(0)),
+            ark_bn254::Fq2::new(fq(3), fq(2)),
+        )
+    }
+
+    /// Check a proof against the zkey's verification key with arkworks
+    fn ark_verify(zkey: &Zkey, proof: &GrothProof) -> bool {
+        use ark_ec::pairing::Pairing;
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_ff::Zero;
+        let inputs: Vec<bn254::Fr> = proof
+            .public_signals
+            .iter()
+            .map(|v| bn254::Fr::from_be_bytes(v).unwrap())
+            .collect();
+        let l = pippenger(
+            &zkey.ic[1..],
+            &inputs.iter().map(|x| x.to_canonical()).collect::<Vec<_>>(),
+            false,
+        )
+        .add_affine(&zkey.ic[0])
+        .to_affine();
+        let g1 = |p: &bn254::G1Affine| ark_g1(&bn254::encode_g1(p));
+        let g2 = |p: &bn254::G2Affine| ark_g2(&bn254::encode_g2(p));
+        let (a, b, c) = (
+            ark_g1(&proof.pi_a),
+            ark_g2(&proof.pi_b),
+            ark_g1(&proof.pi_c),
+        );
+        let neg = |p: ark_bn254::G1Affine| (-p.into_group()).into_affine();
+        ark_bn254::Bn254::multi_pairing(
+            [a, neg(g1(&zkey.alpha_g1)), neg(g1(&l)), neg(c)],
+            [b, g2(&zkey.beta_g2), g2(&zkey.gamma_g2), g2(&zkey.delta_g2)],
+        )
+        .is_zero()
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn prove_multiplier(witness: Vec<Vec<u8>>) -> Result<GrothProof> {
+        ProveGroth16Task {
+            r1cs: MULTIPLIER_R1CS.to_vec(),
+            witness,
+            proving_key: MULTIPLIER_ZKEY.to_vec(),
+            cancel: CancelFlag::default(),
+            progress: Progress::default(),
+        }
+        .compute()
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_prove_multiplier() {
+        let zkey = Zkey::parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+        let proof = prove_multiplier(multiplier_witness(33)).unwrap();
+        assert_eq!(proof.pi_a.len(), bn254::G1_BYTES);
+        assert_eq!(proof.pi_b.len(), bn254::G2_BYTES);
+        assert_eq!(
+            proof.public_signals,
+            vec![bn254::Fr::from_u64(33).to_be_bytes()]
+        );
+        assert!(ark_verify(&zkey, &proof));
+
+        // Fresh blinding every time
+        let again = prove_multiplier(multiplier_witness(33)).unwrap();
+        assert_ne!(again.pi_a, proof.pi_a);
+        assert!(ark_verify(&zkey, &again));
+
+        let mut claimed = proof;
+        claimed.public_signals = vec![bn254::Fr::from_u64(34).to_be_bytes()];
+        assert!(!ark_verify(&zkey, &claimed));
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_verify_groth16_round_trip() {
+        let vk = groth16_verification_key(MULTIPLIER_ZKEY.to_vec()).unwrap();
+        assert_eq!(vk.ic.len(), 2);
+        let proof = prove_multiplier(multiplier_witness(33)).unwrap();
+        assert!(verify_groth16(vk.clone(), proof.clone()).unwrap());
+
+        let mut claimed = proof.clone();
+        claimed.public_signals = vec![bn254::Fr::from_u64(34).to_be_bytes()];
+        assert!(!verify_groth16(vk.clone(), claimed).unwrap());
+        let mut tampered = proof.clone();
+        tampered.pi_c = proof.pi_a.clone();
+        assert!(!verify_groth16(vk.clone(), tampered).unwrap());
+
+        let mut missing = proof.clone();
+        missing.public_signals.clear();
+        let err = verify_groth16(vk.clone(), missing).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "proof.public_signals: key expects 1 signals, got 0"
+        );
+
+        // A twist point with cofactor torsion
+        use crate::ec::SwCurve;
+        use crate::montgomery::Field;
+        let outside = (1u64..)
+            .find_map(|i| {
+                let x = bn254::Fq2::new(bn254::Fq::from_u64(i), bn254::Fq::zero());
+                let y = (x.square() * x + bn254::G2Config::coeff_b()).sqrt()?;
+                let p = bn254::G2Affine::new(x, y);
+                (!bn254::g2_in_subgroup(&p)).then_some(p)
+            })
+            .unwrap();
+        let mut bad = proof.clone();
+        bad.pi_b = bn254::encode_g2(&outside);
+        let err = verify_groth16(vk.clone(), bad).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "proof.pi_b: point is not in the BN254 G2 subgroup"
+        );
+        let mut bad_vk = vk;
+        bad_vk.delta_g2 = bn254::encode_g2(&outside);
+        let err = verify_groth16(bad_vk, proof).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "vk.delta_g2: point is not in the BN254 G2 subgroup"
+        );
+    }
+
+    #[test]
+    fn test_prove_without_blinding() {
+        // r = s = 0 leaves only the key and witness terms
+        let r1cs = R1cs::parse(MULTIPLIER_R1CS, "r1cs").unwrap();
+        let zkey = Zkey::parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+        let witness: Vec<bn254::Fr> = [1, 33, 3, 11].map(bn254::Fr::from_u64).to_vec();
+        let zero = bn254::Fr::zero();
+        let proof = prove_bn254(
+            &r1cs,
+            &zkey,
+            &witness,
+            zero,
+            zero,
+            &CancelFlag::default(),
+            &Progress::default(),
+        );
+        assert!(ark_verify(&zkey, &proof.unwrap()));
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_prove_rejects_bad_inputs() {
+        let err = prove_multiplier(multiplier_witness(34)).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(err.reason, "witness: constraint 0 is not satisfied");
+
+        let mut short = multiplier_witness(33);
+        short.pop();
+        let err = prove_multiplier(short).unwrap_err();
+        assert_eq!(err.reason, "witness: expected 4 values, got 3");
+
+        let mut unscaled = multiplier_witness(33);
+        unscaled[0] = bn254::Fr::from_u64(2).to_be_bytes();
+        let err = prove_multiplier(unscaled).unwrap_err();
+        assert_eq!(err.reason, "witness: the first value must be 1");
+
+        let mut task = ProveGroth16Task {
+            r1cs: MULTIPLIER_ZKEY.to_vec(),
+            witness: multiplier_witness(33),
+            proving_key: MULTIPLIER_ZKEY.to_vec(),
+            cancel: CancelFlag::default(),
+            progress: Progress::default(),
+        };
+        let err = task.compute().unwrap_err();
+        assert_eq!(err.reason, "r1cs: missing the \"r1cs\" file magic");
+
+        let token = CancellationToken::new();
+        token.cancel();
+        task.cancel = token.flag();
+        assert_eq!(task.compute().unwrap_err().status, Status::Cancelled);
+    }
+}
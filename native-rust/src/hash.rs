@@ -0,0 +1,878 @@
+//! Keccak-256, SHA-256 and BLAKE3 for proof transcripts
+//!
+//! `keccak256` is the original Keccak padding used by Ethereum (domain byte
+//! `0x01`), not FIPS 202 SHA3-256. On aarch64 CPUs with FEAT_SHA3 the
+//! Keccak-f[1600] permutation runs on the EOR3/RAX1/XAR/BCAX instructions
+//! with two states per vector, so batches hash messages in pairs.
+//!
+//! SHA-256 uses the SHA extensions on x86_64 and FEAT_SHA256 on aarch64. On
+//! x86_64 CPUs with AVX2 but without the SHA extensions, batches hash eight
+//! messages at a time, one per 32-bit lane. Every path falls back to
+//! portable code and produces identical digests.
+//!
+//! BLAKE3 comes from the `blake3` crate, which picks its SSE4.1, AVX2 or
+//! AVX-512 backend at runtime on x86_64 and uses NEON on aarch64. Batches
+//! hash one input per worker item, like the other batch functions.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::parallel::par_map;
+
+/// Size in bytes of a Keccak-256 or SHA-256 digest
+pub const DIGEST_BYTES: usize = 32;
+
+/// Inputs handed to one worker by the batch functions
+const BATCH_CHUNK: usize = 64;
+
+/// Keccak-256 rate in bytes (1088 bits)
+const KECCAK_RATE: usize = 136;
+/// Keccak-256 rate in 64-bit lanes
+const KECCAK_RATE_LANES: usize = KECCAK_RATE / 8;
+
+/// Keccak-f[1600] round constants
+const RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// ρ rotation of lane `x + 5y`
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Whether the CPU has the ARMv8.2 SHA-3 instructions (FEAT_SHA3)
+///
+/// std's cached detection reads `HWCAP_SHA3` from `AT_HWCAP` on Linux and
+/// `hw.optional.arm.FEAT_SHA3` on macOS, the same sources as
+/// `RustHardwareCapabilities::has_sha3`. Always false off aarch64.
+pub fn detect_sha3_ext() -> bool {
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("sha3")
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        false
+    }
+}
+
+/// Keccak-f[1600] on one state, lane `x + 5y` at index `x + 5y`
+fn keccak_f1600(a: &mut [u64; 25]) {
+    for rc in RC {
+        // θ
+        let c: [u64; 5] =
+            std::array::from_fn(|x| a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20]);
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+        // ρ and π
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotate_left(RHO[x + 5 * y]);
+            }
+        }
+        // χ
+        for y in 0..5 {
+            for x in 0..5 {
+                a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        // ι
+        a[0] ^= rc;
+    }
+}
+
+/// Number of rate-sized blocks a message of `len` bytes pads to
+fn keccak_blocks(len: usize) -> usize {
+    len / KECCAK_RATE + 1
+}
+
+/// Block `i` of the padded message as little-endian lanes
+fn keccak_block(data: &[u8], i: usize) -> [u64; KECCAK_RATE_LANES] {
+    let start = i * KECCAK_RATE;
+    let tail = &data[start.min(data.len())..data.len().min(start + KECCAK_RATE)];
+    let mut bytes = [0u8; KECCAK_RATE];
+    bytes[..tail.len()].copy_from_slice(tail);
+    if start + KECCAK_RATE > data.len() {
+        bytes[tail.len()] ^= 0x01;
+        bytes[KECCAK_RATE - 1] ^= 0x80;
+    }
+    std::array::from_fn(|j| u64::from_le_bytes(bytes[8 * j..8 * j + 8].try_into().unwrap()))
+}
+
+fn keccak256_portable(data: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut state = [0u64; 25];
+    for i in 0..keccak_blocks(data.len()) {
+        for (lane, word) in state.iter_mut().zip(keccak_block(data, i)) {
+            *lane ^= word;
+        }
+        keccak_f1600(&mut state);
+    }
+    let mut out = [0u8; DIGEST_BYTES];
+    for (chunk, lane) in out.chunks_exact_mut(8).zip(state) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+/// Keccak-f[1600] with the FEAT_SHA3 instructions, two states per vector
+#[cfg(target_arch = "aarch64")]
+mod keccak_sha3 {
+    use std::arch::aarch64::*;
+
+    use super::{keccak_block, keccak_blocks, DIGEST_BYTES, KECCAK_RATE_LANES, RC};
+
+    /// `b[dst] = rotl(a[src] ^ d[src % 5], rot)` for every lane, as XAR
+    /// (XOR then rotate right)
+    macro_rules! rho_pi {
+        ($a:ident, $d:ident, $b:ident; $($src:literal => $dst:literal, $rot:literal;)*) => {
+            $( $b[$dst] = vxarq_u64::<{ (64 - $rot) % 64 }>($a[$src], $d[$src % 5]); )*
+        };
+    }
+
+    #[target_feature(enable = "sha3")]
+    unsafe fn keccak_f1600_x2(a: &mut [uint64x2_t; 25]) {
+        for rc in RC {
+            // θ: D[x] = C[x - 1] ^ rotl(C[x + 1], 1), applied inside XAR below
+            let mut c = [vdupq_n_u64(0); 5];
+            for (x, cx) in c.iter_mut().enumerate() {
+                *cx = veor3q_u64(veor3q_u64(a[x], a[x + 5], a[x + 10]), a[x + 15], a[x + 20]);
+            }
+            let mut d = [vdupq_n_u64(0); 5];
+            for (x, dx) in d.iter_mut().enumerate() {
+                *dx = vrax1q_u64(c[(x + 4) % 5], c[(x + 1) % 5]);
+            }
+            // θ, ρ and π
+            let mut b = [vdupq_n_u64(0); 25];
+            rho_pi!(a, d, b;
+                0 => 0, 0; 1 => 10, 1; 2 => 20, 62; 3 => 5, 28; 4 => 15, 27;
+                5 => 16, 36; 6 => 1, 44; 7 => 11, 6; 8 => 21, 55; 9 => 6, 20;
+                10 => 7, 3; 11 => 17, 10; 12 => 2, 43; 13 => 12, 25; 14 => 22, 39;
+                15 => 23, 41; 16 => 8, 45; 17 => 18, 15; 18 => 3, 21; 19 => 13, 8;
+                20 => 14, 18; 21 => 24, 2; 22 => 9, 61; 23 => 19, 56; 24 => 4, 14;
+            );
+            // χ: BCAX computes b[x] ^ (b[x + 2] & !b[x + 1])
+            for y in 0..5 {
+                for x in 0..5 {
+                    a[x + 5 * y] =
+                        vbcaxq_u64(b[x + 5 * y], b[(x + 2) % 5 + 5 * y], b[(x + 1) % 5 + 5 * y]);
+                }
+            }
+            // ι
+            a[0] = veorq_u64(a[0], vdupq_n_u64(rc));
+        }
+    }
+
+    /// Keccak-256 of two messages, one per vector lane
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support FEAT_SHA3 (see [`super::detect_sha3_ext`]).
+    #[target_feature(enable = "sha3")]
+    pub unsafe fn keccak256_x2(messages: [&[u8]; 2]) -> [[u8; DIGEST_BYTES]; 2] {
+        let blocks = messages.map(|m| keccak_blocks(m.len()));
+        let mut state = [vdupq_n_u64(0); 25];
+        let mut out = [[0u8; DIGEST_BYTES]; 2];
+        for i in 0..blocks[0].max(blocks[1]) {
+            // A lane that has finished absorbs zeros; its digest is already out
+            let [lo, hi] = [0, 1].map(|lane| {
+                if i < blocks[lane] {
+                    keccak_block(messages[lane], i)
+                } else {
+                    [0; KECCAK_RATE_LANES]
+                }
+            });
+            for j in 0..KECCAK_RATE_LANES {
+                let words = [lo[j], hi[j]];
+                state[j] = veorq_u64(state[j], vld1q_u64(words.as_ptr()));
+            }
+            keccak_f1600_x2(&mut state);
+            for (lane, digest) in out.iter_mut().enumerate() {
+                if i + 1 == blocks[lane] {
+                    for (chunk, v) in digest.chunks_exact_mut(8).zip(&state) {
+                        let mut words = [0u64; 2];
+                        vst1q_u64(words.as_mut_ptr(), *v);
+                        chunk.copy_from_slice(&words[lane].to_le_bytes());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Keccak-256 digest of `data`
+pub fn keccak256_digest(data: &[u8]) -> [u8; DIGEST_BYTES] {
+    #[cfg(target_arch = "aarch64")]
+    if detect_sha3_ext() {
+        // SAFETY: FEAT_SHA3 was detected at runtime
+        return unsafe { keccak_sha3::keccak256_x2([data, &[]]) }[0];
+    }
+    keccak256_portable(data)
+}
+
+/// Keccak-256 digests of every input, in order, hashed on worker threads
+pub fn keccak256_digests<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<[u8; DIGEST_BYTES]> {
+    let chunks: Vec<&[T]> = inputs.chunks(BATCH_CHUNK).collect();
+    let sha3 = detect_sha3_ext();
+    par_map(&chunks, |chunk| {
+        if !sha3 {
+            return chunk
+                .iter()
+                .map(|m| keccak256_portable(m.as_ref()))
+                .collect::<Vec<_>>();
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            chunk
+                .chunks(2)
+                .flat_map(|pair| {
+                    let second = pair.get(1).map_or(&[][..], |m| m.as_ref());
+                    // SAFETY: FEAT_SHA3 was detected at runtime
+                    let digests = unsafe { keccak_sha3::keccak256_x2([pair[0].as_ref(), second]) };
+                    digests.into_iter().take(pair.len())
+                })
+                .collect()
+        }
+        #[cfg(not(target_arch = "aarch64"))]
+        unreachable!("FEAT_SHA3 is only detected on aarch64")
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// SHA-256 initial hash value
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 round constants
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Number of 64-byte blocks a message of `len` bytes pads to
+fn sha256_blocks(len: usize) -> usize {
+    (len + 9).div_ceil(64)
+}
+
+/// Block `i` of the padded message: data, `0x80`, zeros, bit length big-endian
+fn sha256_block(data: &[u8], i: usize) -> [u8; 64] {
+    let start = i * 64;
+    let tail = &data[start.min(data.len())..data.len().min(start + 64)];
+    let mut block = [0u8; 64];
+    block[..tail.len()].copy_from_slice(tail);
+    if (start..start + 64).contains(&data.len()) {
+        block[data.len() - start] = 0x80;
+    }
+    if i + 1 == sha256_blocks(data.len()) {
+        block[56..].copy_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    }
+    block
+}
+
+fn sha256_compress_portable(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w = [0u32; 64];
+    for (t, word) in block.chunks_exact(4).enumerate() {
+        w[t] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = w[t - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in K.iter().zip(w) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// SHA-256 with the x86 SHA extensions
+#[cfg(target_arch = "x86_64")]
+mod sha_ni {
+    use std::arch::x86_64::*;
+
+    use super::K;
+
+    /// Whether the CPU has the SHA extensions and the SSE4.1 shuffles they need
+    pub fn enabled() -> bool {
+        std::arch::is_x86_feature_detected!("sha") && std::arch::is_x86_feature_detected!("sse4.1")
+    }
+
+    /// Four rounds with message words `w` and constants `K[4i..4i + 4]`
+    #[inline]
+    #[target_feature(enable = "sha,sse4.1")]
+    unsafe fn rounds4(abef: &mut __m128i, cdgh: &mut __m128i, w: __m128i, i: usize) {
+        let k = _mm_loadu_si128(K[4 * i..].as_ptr().cast());
+        let wk = _mm_add_epi32(w, k);
+        *cdgh = _mm_sha256rnds2_epu32(*cdgh, *abef, wk);
+        *abef = _mm_sha256rnds2_epu32(*abef, *cdgh, _mm_shuffle_epi32(wk, 0x0e));
+    }
+
+    /// Compress one block into `state`
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support the SHA extensions and SSE4.1 (see [`enabled`]).
+    #[target_feature(enable = "sha,sse4.1")]
+    pub unsafe fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        // Byte-swap each 32-bit word of the big-endian message
+        let bswap = _mm_set_epi64x(0x0c0d0e0f08090a0b, 0x0405060700010203);
+
+        // The rounds instruction wants the state as ABEF and CDGH
+        let dcba = _mm_loadu_si128(state.as_ptr().cast());
+        let hgfe = _mm_loadu_si128(state[4..].as_ptr().cast());
+        let cdab = _mm_shuffle_epi32(dcba, 0xb1);
+        let efgh = _mm_shuffle_epi32(hgfe, 0x1b);
+        let mut abef = _mm_alignr_epi8(cdab, efgh, 8);
+        let mut cdgh = _mm_blend_epi16(efgh, cdab, 0xf0);
+        let (abef_in, cdgh_in) = (abef, cdgh);
+
+        let mut w = [_mm_setzero_si128(); 4];
+        for (i, wi) in w.iter_mut().enumerate() {
+            *wi = _mm_shuffle_epi8(_mm_loadu_si128(block[16 * i..].as_ptr().cast()), bswap);
+            rounds4(&mut abef, &mut cdgh, *wi, i);
+        }
+        // w[i % 4] holds words 4(i - 4) .. 4(i - 4) + 4 until replaced
+        for i in 4..16 {
+            let t = _mm_sha256msg1_epu32(w[i % 4], w[(i + 1) % 4]);
+            let t = _mm_add_epi32(t, _mm_alignr_epi8(w[(i + 3) % 4], w[(i + 2) % 4], 4));
+            w[i % 4] = _mm_sha256msg2_epu32(t, w[(i + 3) % 4]);
+            rounds4(&mut abef, &mut cdgh, w[i % 4], i);
+        }
+
+        abef = _mm_add_epi32(abef, abef_in);
+        cdgh = _mm_add_epi32(cdgh, cdgh_in);
+        let feba = _mm_shuffle_epi32(abef, 0x1b);
+        let dchg = _mm_shuffle_epi32(cdgh, 0xb1);
+        _mm_storeu_si128(state.as_mut_ptr().cast(), _mm_blend_epi16(feba, dchg, 0xf0));
+        _mm_storeu_si128(
+            state[4..].as_mut_ptr().cast(),
+            _mm_alignr_epi8(dchg, feba, 8),
+        );
+    }
+}
+
+/// Eight-message SHA-256 in AVX2, one message per 32-bit lane
+#[cfg(target_arch = "x86_64")]
+mod sha256_avx2 {
+    use std::arch::x86_64::*;
+
+    use super::{sha256_block, sha256_blocks, DIGEST_BYTES, K, SHA256_IV};
+
+    macro_rules! rotr {
+        ($x:expr, $n:literal) => {
+            _mm256_or_si256(
+                _mm256_srli_epi32::<$n>($x),
+                _mm256_slli_epi32::<{ 32 - $n }>($x),
+            )
+        };
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn add(a: __m256i, b: __m256i) -> __m256i {
+        _mm256_add_epi32(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn xor3(a: __m256i, b: __m256i, c: __m256i) -> __m256i {
+        _mm256_xor_si256(_mm256_xor_si256(a, b), c)
+    }
+
+    /// Compress one block per lane into the transposed state
+    #[target_feature(enable = "avx2")]
+    unsafe fn compress(state: &mut [__m256i; 8], blocks: &[[u8; 64]; 8]) {
+        let mut w = [_mm256_setzero_si256(); 16];
+        for (t, wt) in w.iter_mut().enumerate() {
+            let word = |lane: usize| {
+                u32::from_be_bytes(blocks[lane][4 * t..4 * t + 4].try_into().unwrap()) as i32
+            };
+            *wt = _mm256_setr_epi32(
+                word(0),
+                word(1),
+                word(2),
+                word(3),
+                word(4),
+                word(5),
+                word(6),
+                word(7),
+            );
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (t, k) in K.iter().enumerate() {
+            // w[t % 16] holds word t - 16 until replaced
+            if t >= 16 {
+                let w15 = w[(t + 1) % 16];
+                let w2 = w[(t + 14) % 16];
+                let s0 = xor3(rotr!(w15, 7), rotr!(w15, 18), _mm256_srli_epi32::<3>(w15));
+                let s1 = xor3(rotr!(w2, 17), rotr!(w2, 19), _mm256_srli_epi32::<10>(w2));
+                w[t % 16] = add(add(w[t % 16], s0), add(w[(t + 9) % 16], s1));
+            }
+            let s1 = xor3(rotr!(e, 6), rotr!(e, 11), rotr!(e, 25));
+            let ch = _mm256_xor_si256(_mm256_and_si256(e, f), _mm256_andnot_si256(e, g));
+            let t1 = add(
+                add(h, s1),
+                add(ch, add(_mm256_set1_epi32(*k as i32), w[t % 16])),
+            );
+            let s0 = xor3(rotr!(a, 2), rotr!(a, 13), rotr!(a, 22));
+            let maj = xor3(
+                _mm256_and_si256(a, b),
+                _mm256_and_si256(a, c),
+                _mm256_and_si256(b, c),
+            );
+            h = g;
+            g = f;
+            f = e;
+            e = add(d, t1);
+            d = c;
+            c = b;
+            b = a;
+            a = add(t1, add(s0, maj));
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = add(*s, v);
+        }
+    }
+
+    /// SHA-256 of up to eight messages
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sha256_x8(messages: &[&[u8]]) -> Vec<[u8; DIGEST_BYTES]> {
+        debug_assert!(messages.len() <= 8);
+        // Missing lanes have no blocks and only ever absorb zeros
+        let blocks: [usize; 8] =
+            std::array::from_fn(|lane| messages.get(lane).map_or(0, |m| sha256_blocks(m.len())));
+        let mut state = SHA256_IV.map(|v| _mm256_set1_epi32(v as i32));
+        let mut out = vec![[0u8; DIGEST_BYTES]; messages.len()];
+        for i in 0..blocks.iter().copied().max().unwrap_or(0) {
+            let input: [[u8; 64]; 8] = std::array::from_fn(|lane| {
+                if i < blocks[lane] {
+                    sha256_block(messages[lane], i)
+                } else {
+                    [0; 64]
+                }
+            });
+            compress(&mut state, &input);
+            for (lane, digest) in out.iter_mut().enumerate() {
+                if i + 1 == blocks[lane] {
+                    for (chunk, v) in digest.chunks_exact_mut(4).zip(&state) {
+                        let mut words = [0u32; 8];
+                        _mm256_storeu_si256(words.as_mut_ptr().cast(), *v);
+                        chunk.copy_from_slice(&words[lane].to_be_bytes());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// SHA-256 with the ARMv8 SHA-256 instructions (FEAT_SHA256)
+#[cfg(target_arch = "aarch64")]
+mod sha256_arm {
+    use std::arch::aarch64::*;
+
+    use super::K;
+
+    /// Whether the CPU has FEAT_SHA256
+    pub fn enabled() -> bool {
+        std::arch::is_aarch64_feature_detected!("sha2")
+    }
+
+    /// Compress one block into `state`
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support FEAT_SHA256 (see [`enabled`]).
+    #[target_feature(enable = "sha2")]
+    pub unsafe fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        let mut abcd = vld1q_u32(state.as_ptr());
+        let mut efgh = vld1q_u32(state[4..].as_ptr());
+        let (abcd_in, efgh_in) = (abcd, efgh);
+
+        let mut w = [vdupq_n_u32(0); 4];
+        for (i, wi) in w.iter_mut().enumerate() {
+            *wi = vreinterpretq_u32_u8(vrev32q_u8(vld1q_u8(block[16 * i..].as_ptr())));
+        }
+        for i in 0..16 {
+            // w[i % 4] holds words 4(i - 4) .. 4(i - 4) + 4 until replaced
+            if i >= 4 {
+                let t = vsha256su0q_u32(w[i % 4], w[(i + 1) % 4]);
+                w[i % 4] = vsha256su1q_u32(t, w[(i + 2) % 4], w[(i + 3) % 4]);
+            }
+            let wk = vaddq_u32(w[i % 4], vld1q_u32(K[4 * i..].as_ptr()));
+            let abcd_prev = abcd;
+            abcd = vsha256hq_u32(abcd_prev, efgh, wk);
+            efgh = vsha256h2q_u32(efgh, abcd_prev, wk);
+        }
+
+        vst1q_u32(state.as_mut_ptr(), vaddq_u32(abcd, abcd_in));
+        vst1q_u32(state[4..].as_mut_ptr(), vaddq_u32(efgh, efgh_in));
+    }
+}
+
+/// Compress one block with the fastest single-stream implementation
+fn sha256_compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    #[cfg(target_arch = "x86_64")]
+    if sha_ni::enabled() {
+        // SAFETY: the SHA extensions and SSE4.1 were detected at runtime
+        return unsafe { sha_ni::compress(state, block) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if sha256_arm::enabled() {
+        // SAFETY: FEAT_SHA256 was detected at runtime
+        return unsafe { sha256_arm::compress(state, block) };
+    }
+    sha256_compress_portable(state, block)
+}
+
+fn sha256_with(data: &[u8], compress: fn(&mut [u32; 8], &[u8; 64])) -> [u8; DIGEST_BYTES] {
+    let mut state = SHA256_IV;
+    for i in 0..sha256_blocks(data.len()) {
+        compress(&mut state, &sha256_block(data, i));
+    }
+    let mut out = [0u8; DIGEST_BYTES];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+/// SHA-256 digest of `data`
+pub fn sha256_digest(data: &[u8]) -> [u8; DIGEST_BYTES] {
+    sha256_with(data, sha256_compress)
+}
+
+/// SHA-256 digests of every input, in order, hashed on worker threads
+pub fn sha256_digests<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<[u8; DIGEST_BYTES]> {
+    let chunks: Vec<&[T]> = inputs.chunks(BATCH_CHUNK).collect();
+    #[cfg(target_arch = "x86_64")]
+    let multi_buffer = !sha_ni::enabled() && std::arch::is_x86_feature_detected!("avx2");
+    #[cfg(not(target_arch = "x86_64"))]
+    let multi_buffer = false;
+    par_map(&chunks, |chunk| {
+        if !multi_buffer {
+            return chunk
+                .iter()
+                .map(|m| sha256_digest(m.as_ref()))
+                .collect::<Vec<_>>();
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            chunk
+                .chunks(8)
+                .flat_map(|group| {
+                    let messages: Vec<&[u8]> = group.iter().map(|m| m.as_ref()).collect();
+                    // SAFETY: AVX2 was detected at runtime
+                    unsafe { sha256_avx2::sha256_x8(&messages) }
+                })
+                .collect()
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        unreachable!("multi-buffer SHA-256 is only enabled on x86_64")
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Keccak-256 (Ethereum's `keccak256`) of `data`
+#[napi]
+pub fn keccak256(data: Vec<u8>) -> Vec<u8> {
+    keccak256_digest(&data).to_vec()
+}
+
+/// Keccak-256 of each input, hashed in parallel
+#[napi]
+pub fn keccak256_batch(inputs: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    keccak256_digests(&inputs)
+        .into_iter()
+        .map(|d| d.to_vec())
+        .collect()
+}
+
+/// SHA-256 of `data`
+#[napi]
+pub fn sha256(data: Vec<u8>) -> Vec<u8> {
+    sha256_digest(&data).to_vec()
+}
+
+/// SHA-256 of each input, hashed in parallel
+#[napi]
+pub fn sha256_batch(inputs: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    sha256_digests(&inputs)
+        .into_iter()
+        .map(|d| d.to_vec())
+        .collect()
+}
+
+/// BLAKE3 digests of every input, in order, hashed on worker threads
+pub fn blake3_digests<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<[u8; DIGEST_BYTES]> {
+    let chunks: Vec<&[T]> = inputs.chunks(BATCH_CHUNK).collect();
+    par_map(&chunks, |chunk| {
+        chunk
+            .iter()
+            .map(|m| *blake3::hash(m.as_ref()).as_bytes())
+            .collect::<Vec<_>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// BLAKE3 of `data`, 32 bytes
+#[napi]
+pub fn blake3_hash(data: Vec<u8>) -> Vec<u8> {
+    blake3::hash(&data).as_bytes().to_vec()
+}
+
+/// BLAKE3 in keyed mode, a MAC under a 32-byte `key`
+#[napi]
+pub fn blake3_hash_keyed(key: Vec<u8>, data: Vec<u8>) -> Result<Vec<u8>> {
+    let key: [u8; blake3::KEY_LEN] = key.as_slice().try_into().map_err(|_| {
+        Error::new(
+            Status::InvalidArg,
+            format!("key: expected {} bytes, got {}", blake3::KEY_LEN, key.len()),
+        )
+    })?;
+    Ok(blake3::keyed_hash(&key, &data).as_bytes().to_vec())
+}
+
+/// BLAKE3 of each input, hashed in parallel
+#[napi]
+pub fn blake3_hash_batch(inputs: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    blake3_digests(&inputs)
+        .into_iter()
+        .map(|d| d.to_vec())
+        .collect()
+}
+
+/// Derive a 32-byte key from `material` with BLAKE3's key derivation mode
+///
+/// `context` should be a hardcoded, globally unique string naming the
+/// application and purpose, e.g. "my-protocol 2024-01-01 transcript seed";
+/// the same context and material always give the same key.
+#[napi]
+pub fn blake3_keyed_derive(context: String, material: Vec<u8>) -> Vec<u8> {
+    blake3::derive_key(&context, &material).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Digest;
+    use tiny_keccak::Hasher;
+
+    fn reference_keccak256(data: &[u8]) -> [u8; DIGEST_BYTES] {
+        let mut hasher = tiny_keccak::Keccak::v256();
+        hasher.update(data);
+        let mut out = [0u8; DIGEST_BYTES];
+        hasher.finalize(&mut out);
+        out
+    }
+
+    fn reference_sha256(data: &[u8]) -> [u8; DIGEST_BYTES] {
+        sha2::Sha256::digest(data).into()
+    }
+
+    /// Messages of every length up to three blocks, from a byte counter
+    fn messages(max_len: usize) -> Vec<Vec<u8>> {
+        (0..=max_len)
+            .map(|len| (0..len).map(|i| (i * 31 + len) as u8).collect())
+            .collect()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_keccak256_known_answers() {
+        assert_eq!(
+            hex(&keccak256_digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // Solidity `keccak256("transfer(address,uint256)")`
+        assert_eq!(
+            hex(&keccak256_digest(b"transfer(address,uint256)")[..4]),
+            "a9059cbb"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_matches_tiny_keccak() {
+        // Covers the 135/136/137-byte padding boundaries
+        let inputs = messages(3 * KECCAK_RATE + 1);
+        let expected: Vec<[u8; DIGEST_BYTES]> =
+            inputs.iter().map(|m| reference_keccak256(m)).collect();
+        for (m, e) in inputs.iter().zip(&expected) {
+            assert_eq!(keccak256_portable(m), *e, "len {}", m.len());
+            assert_eq!(keccak256_digest(m), *e, "len {}", m.len());
+        }
+        assert_eq!(keccak256_digests(&inputs), expected);
+        // Reversed, so pairs mix long and short messages
+        let reversed: Vec<&Vec<u8>> = inputs.iter().rev().collect();
+        let digests = keccak256_digests(&reversed);
+        assert!(digests.iter().eq(expected.iter().rev()));
+        assert!(keccak256_digests::<Vec<u8>>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_sha256_known_answers() {
+        assert_eq!(
+            hex(&sha256_digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256_digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_sha256_matches_sha2() {
+        // Covers the 55/56/64-byte padding boundaries
+        let inputs = messages(200);
+        let expected: Vec<[u8; DIGEST_BYTES]> =
+            inputs.iter().map(|m| reference_sha256(m)).collect();
+        for (m, e) in inputs.iter().zip(&expected) {
+            assert_eq!(
+                sha256_with(m, sha256_compress_portable),
+                *e,
+                "len {}",
+                m.len()
+            );
+            assert_eq!(sha256_digest(m), *e, "len {}", m.len());
+        }
+        assert_eq!(sha256_digests(&inputs), expected);
+        assert!(sha256_digests::<Vec<u8>>(&[]).is_empty());
+
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("avx2") {
+            // Lanes finishing at different blocks, and a partial group
+            for group in inputs.rchunks(8).chain([&inputs[3..6]]) {
+                let messages: Vec<&[u8]> = group.iter().map(|m| m.as_slice()).collect();
+                let digests = unsafe { sha256_avx2::sha256_x8(&messages) };
+                let expected: Vec<_> = messages.iter().map(|m| reference_sha256(m)).collect();
+                assert_eq!(digests, expected);
+            }
+        }
+    }
+
+    /// The official BLAKE3 test vectors' input: byte `i` is `i % 251`
+    fn blake3_vector_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_blake3_known_answers() {
+        // test_vectors.json from the BLAKE3 repository, first 32 bytes
+        for (len, digest) in [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+            ),
+            (
+                1023,
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+            ),
+        ] {
+            assert_eq!(
+                hex(&blake3_hash(blake3_vector_input(len))),
+                digest,
+                "len {len}"
+            );
+        }
+        let key = b"whats the Elvish word for friend".to_vec();
+        assert_eq!(
+            hex(&blake3_hash_keyed(key, vec![]).unwrap()),
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26"
+        );
+        let context = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+        assert_eq!(
+            hex(&blake3_keyed_derive(context.to_string(), vec![])),
+            "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d"
+        );
+
+        let err = blake3_hash_keyed(vec![0; 31], vec![]).unwrap_err();
+        assert_eq!(err.reason, "key: expected 32 bytes, got 31");
+    }
+
+    #[test]
+    fn test_blake3_batch() {
+        let inputs = messages(2 * BATCH_CHUNK + 3);
+        let batch = blake3_hash_batch(inputs.clone());
+        assert_eq!(batch.len(), inputs.len());
+        for (m, d) in inputs.into_iter().zip(batch) {
+            assert_eq!(blake3_hash(m), d);
+        }
+        assert!(blake3_hash_batch(vec![]).is_empty());
+    }
+}
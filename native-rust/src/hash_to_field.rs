@@ -0,0 +1,359 @@
+//! `hash_to_field` from RFC 9380 (hashing to elliptic curves), section 5
+//!
+//! Messages expand with `expand_message_xmd` over SHA-256. Each element
+//! reduces `L = ceil((ceil(log2(p)) + 128) / 8)` uniform bytes modulo `p`,
+//! so the bias is below `2^-128`: 64 bytes for the BLS12-381 base field, 48
+//! for the 254- and 255-bit fields. Only prime fields (`m = 1`) are covered;
+//! the BLS12-381 G2 suite hashes to `Fp2`, which takes two BLS12-381 base
+//! field elements per output.
+//!
+//! Elements are encoded big-endian, matching the RFC's `I2OSP` and the
+//! `u` values in its test vectors, at [`FieldName::element_bytes`] each.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::hash::{sha256_digest, DIGEST_BYTES};
+use crate::montgomery::{Fp, MontConfig};
+use crate::random::FieldName;
+
+/// SHA-256 input block size, `r_in_bytes` in the RFC
+const BLOCK_BYTES: usize = 64;
+
+/// Longest DST used as is; longer ones are hashed first
+pub const MAX_DST_BYTES: usize = 255;
+
+/// Longest `expand_message_xmd` output with SHA-256 (255 blocks)
+pub const MAX_EXPAND_BYTES: usize = 255 * DIGEST_BYTES;
+
+/// Computational security level `k` in bits
+const SECURITY_BITS: usize = 128;
+
+/// `expand_message_xmd` with SHA-256: `len` uniform bytes from `msg`
+///
+/// A DST longer than [`MAX_DST_BYTES`] is replaced by
+/// `SHA-256("H2C-OVERSIZE-DST-" || dst)` (RFC 9380 section 5.3.3).
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>> {
+    if dst.is_empty() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "dst: must not be empty".to_string(),
+        ));
+    }
+    if len == 0 || len > MAX_EXPAND_BYTES {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("expand_message_xmd: length must be 1 to {MAX_EXPAND_BYTES} bytes, got {len}"),
+        ));
+    }
+    let long_dst;
+    let dst = if dst.len() > MAX_DST_BYTES {
+        long_dst = sha256_digest(&[b"H2C-OVERSIZE-DST-".as_slice(), dst].concat());
+        &long_dst[..]
+    } else {
+        dst
+    };
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+
+    let mut msg_prime = vec![0u8; BLOCK_BYTES];
+    msg_prime.extend_from_slice(msg);
+    msg_prime.extend_from_slice(&(len as u16).to_be_bytes());
+    msg_prime.push(0);
+    msg_prime.extend_from_slice(&dst_prime);
+    let b0 = sha256_digest(&msg_prime);
+
+    let ell = len.div_ceil(DIGEST_BYTES);
+    let mut out = Vec::with_capacity(ell * DIGEST_BYTES);
+    let mut block = [0u8; DIGEST_BYTES];
+    let mut input = Vec::with_capacity(DIGEST_BYTES + 1 + dst_prime.len());
+    for i in 1..=ell {
+        input.clear();
+        // b_1 = H(b_0 || 1 || DST'), b_i = H((b_0 ^ b_(i-1)) || i || DST')
+        input.extend(b0.iter().zip(block).map(|(x, y)| x ^ y));
+        input.push(i as u8);
+        input.extend_from_slice(&dst_prime);
+        block = sha256_digest(&input);
+        out.extend_from_slice(&block);
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Bit length of a little-endian multi-limb modulus
+fn modulus_bits(modulus: &[u64]) -> usize {
+    let top = modulus.iter().rposition(|&l| l != 0).unwrap_or(0);
+    64 * top + 64 - modulus[top].leading_zeros() as usize
+}
+
+/// Uniform bytes consumed per element of a field with a `bits`-bit modulus
+fn bytes_per_element(bits: usize) -> usize {
+    (bits + SECURITY_BITS).div_ceil(8)
+}
+
+/// Uniform bytes `hash_to_field` consumes per element of `field`
+pub fn expansion_bytes(field: FieldName) -> usize {
+    let modulus: &[u64] = match field {
+        FieldName::Bn254Fr => &crate::bn254::FrConfig::MODULUS,
+        FieldName::Bn254Fq => &crate::bn254::FqConfig::MODULUS,
+        FieldName::Bls12_381Fr => &crate::bls12_381::FrConfig::MODULUS,
+        FieldName::Bls12_381Fp => &crate::bls12_381::FpConfig::MODULUS,
+        FieldName::PallasFp => &crate::pasta::FpConfig::MODULUS,
+        FieldName::VestaFp => &crate::pasta::FqConfig::MODULUS,
+        FieldName::Goldilocks => &[crate::goldilocks::MODULUS],
+        FieldName::BabyBear => &[crate::babybear::MODULUS as u64],
+        FieldName::M31 => &[crate::m31::MODULUS as u64],
+    };
+    bytes_per_element(modulus_bits(modulus))
+}
+
+/// Big-endian `bytes` (at most `2 * 8N` of them) reduced modulo `p`
+fn reduce_mont<C: MontConfig<N>, const N: usize>(bytes: &[u8]) -> Fp<C, N> {
+    let width = Fp::<C, N>::BYTES;
+    debug_assert!(bytes.len() <= 2 * width);
+    let (hi, lo) = bytes.split_at(bytes.len().saturating_sub(width));
+    let le_word = |be: &[u8]| {
+        let mut le = vec![0u8; width];
+        le[..be.len()].copy_from_slice(be);
+        le[..be.len()].reverse();
+        Fp::<C, N>::from_le_bytes_reduced(&le).expect("padded to the element width")
+    };
+    // R = 2^(64N) mod p, whose Montgomery form is R^2 mod p
+    let r = Fp::<C, N>::from_montgomery_limbs(C::R2).expect("R^2 mod p is reduced");
+    le_word(hi) * r + le_word(lo)
+}
+
+/// Big-endian `bytes` reduced modulo a word-sized `modulus`
+fn reduce_word(bytes: &[u8], modulus: u64) -> u64 {
+    bytes.iter().fold(0u64, |acc, &b| {
+        (((acc as u128) << 8 | b as u128) % modulus as u128) as u64
+    })
+}
+
+/// `count` elements of `field` hashed from `msg` under `dst`, packed big-endian
+pub fn hash_to_field_elements(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+    field: FieldName,
+) -> Result<Vec<u8>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let l = expansion_bytes(field);
+    let max_count = MAX_EXPAND_BYTES / l;
+    if count > max_count {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("count: at most {max_count} elements fit one expansion, got {count}"),
+        ));
+    }
+    let uniform = expand_message_xmd(msg, dst, count * l)?;
+    let mut out = Vec::with_capacity(count * field.element_bytes());
+    for chunk in uniform.chunks_exact(l) {
+        match field {
+            FieldName::Bn254Fr => {
+                out.extend(reduce_mont::<crate::bn254::FrConfig, 4>(chunk).to_be_bytes())
+            }
+            FieldName::Bn254Fq => {
+                out.extend(reduce_mont::<crate::bn254::FqConfig, 4>(chunk).to_be_bytes())
+            }
+            FieldName::Bls12_381Fr => {
+                out.extend(reduce_mont::<crate::bls12_381::FrConfig, 4>(chunk).to_be_bytes())
+            }
+            FieldName::Bls12_381Fp => {
+                out.extend(reduce_mont::<crate::bls12_381::FpConfig, 6>(chunk).to_be_bytes())
+            }
+            FieldName::PallasFp => {
+                out.extend(reduce_mont::<crate::pasta::FpConfig, 4>(chunk).to_be_bytes())
+            }
+            FieldName::VestaFp => {
+                out.extend(reduce_mont::<crate::pasta::FqConfig, 4>(chunk).to_be_bytes())
+            }
+            FieldName::Goldilocks => {
+                out.extend(reduce_word(chunk, crate::goldilocks::MODULUS).to_be_bytes())
+            }
+            FieldName::BabyBear => {
+                let v = reduce_word(chunk, crate::babybear::MODULUS.into()) as u32;
+                out.extend(v.to_be_bytes())
+            }
+            FieldName::M31 => {
+                let v = reduce_word(chunk, crate::m31::MODULUS.into()) as u32;
+                out.extend(v.to_be_bytes())
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// RFC 9380 `hash_to_field` with `expand_message_xmd` over SHA-256
+///
+/// Returns `count` canonical elements of `field`, packed big-endian; the
+/// field names are those of `random_field_elements`. A `dst` longer than
+/// 255 bytes is hashed down as the RFC requires.
+#[napi]
+pub fn hash_to_field(msg: Buffer, dst: Buffer, count: u32, field: String) -> Result<Buffer> {
+    let field = FieldName::parse(&field)?;
+    hash_to_field_elements(&msg, &dst, count as usize, field).map(Buffer::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    fn long_dst() -> Vec<u8> {
+        let mut dst = b"QUUX-V01-CS02-with-expander-SHA256-128-long-DST-".to_vec();
+        dst.resize(256, b'1');
+        dst
+    }
+
+    #[test]
+    fn test_expand_message_xmd_vectors() {
+        // RFC 9380 appendix K.1
+        let q128 = format!("q128_{}", "q".repeat(128));
+        let a512 = format!("a512_{}", "a".repeat(512));
+        let cases: [(&[u8], &[u8], usize, &str); 8] = [
+            (DST, b"", 0x20, "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"),
+            (DST, b"abc", 0x20, "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"),
+            (DST, q128.as_bytes(), 0x20, "b23a1d2b4d97b2ef7785562a7e8bac7eed54ed6e97e29aa51bfe3f12ddad1ff9"),
+            (DST, a512.as_bytes(), 0x20, "4623227bcc01293b8c130bf771da8c298dede7383243dc0993d2d94823958c4c"),
+            (DST, b"", 0x80, "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"),
+            (DST, b"abcdef0123456789", 0x80, "ef904a29bffc4cf9ee82832451c946ac3c8f8058ae97d8d629831a74c6572bd9ebd0df635cd1f208e2038e760c4994984ce73f0d55ea9f22af83ba4734569d4bc95e18350f740c07eef653cbb9f87910d833751825f0ebefa1abe5420bb52be14cf489b37fe1a72f7de2d10be453b2c9d9eb20c7e3f6edc5a60629178d9478df"),
+            (&long_dst(), b"abc", 0x20, "52dbf4f36cf560fca57dedec2ad924ee9c266341d8f3d6afe5171733b16bbb12"),
+            (&long_dst(), a512.as_bytes(), 0x80, "78b53f2413f3c688f07732c10e5ced29a17c6a16f717179ffbe38d92d6c9ec296502eb9889af83a1928cd162e845b0d3c5424e83280fed3d10cffb2f8431f14e7a23f4c68819d40617589e4c41169d0b56e0e3535be1fd71fbb08bb70c5b5ffed953d6c14bf7618b35fc1f4c4b30538236b4b08c9fbf90462447a8ada60be495"),
+        ];
+        for (dst, msg, len, expected) in cases {
+            assert_eq!(
+                expand_message_xmd(msg, dst, len).unwrap(),
+                hex(expected),
+                "{len} bytes of {:?}",
+                String::from_utf8_lossy(&msg[..msg.len().min(8)])
+            );
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_g1_suite_vectors() {
+        // `u` values of BLS12381G1_XMD:SHA-256_SSWU_RO_, RFC 9380 appendix J.9.1
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let cases: [(&[u8], [&str; 2]); 3] = [
+            (b"", [
+                "0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f",
+                "019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9",
+            ]),
+            (b"abc", [
+                "0d921c33f2bad966478a03ca35d05719bdf92d347557ea166e5bba579eea9b83e9afa5c088573c2281410369fbd32951",
+                "003574a00b109ada2f26a37a91f9d1e740dffd8d69ec0c35e1e9f4652c7dba61123e9dd2e76c655d956e2b3462611139",
+            ]),
+            (b"abcdef0123456789", [
+                "062d1865eb80ebfa73dcfc45db1ad4266b9f3a93219976a3790ab8d52d3e5f1e62f3b01795e36834b17b70e7b76246d4",
+                "0cdc3e2f271f29c4ff75020857ce6c5d36008c9b48385ea2f2bf6f96f428a3deb798aa033cd482d1cdc8b30178b08e3a",
+            ]),
+        ];
+        for (msg, u) in cases {
+            let out = hash_to_field_elements(msg, dst, 2, FieldName::Bls12_381Fp).unwrap();
+            assert_eq!(out, [hex(u[0]), hex(u[1])].concat());
+        }
+    }
+
+    #[test]
+    fn test_reduction_matches_byte_horner() {
+        // OS2IP(bytes) mod p, one byte at a time
+        fn horner<C: MontConfig<N>, const N: usize>(bytes: &[u8]) -> Vec<u8> {
+            let base = Fp::<C, N>::from_u64(256);
+            bytes
+                .iter()
+                .fold(Fp::<C, N>::zero(), |acc, &b| {
+                    acc * base + Fp::from_u64(b as u64)
+                })
+                .to_be_bytes()
+        }
+        assert_eq!(expansion_bytes(FieldName::Bls12_381Fp), 64);
+        assert_eq!(expansion_bytes(FieldName::Bls12_381Fr), 48);
+        assert_eq!(expansion_bytes(FieldName::Bn254Fr), 48);
+        assert_eq!(expansion_bytes(FieldName::M31), 20);
+        for (name, field) in FieldName::ALL {
+            let out = hash_to_field_elements(b"abc", DST, 5, field).unwrap();
+            let l = expansion_bytes(field);
+            let uniform = expand_message_xmd(b"abc", DST, 5 * l).unwrap();
+            let expected: Vec<u8> = uniform
+                .chunks_exact(l)
+                .flat_map(|chunk| match field {
+                    FieldName::Bn254Fr => horner::<crate::bn254::FrConfig, 4>(chunk),
+                    FieldName::Bn254Fq => horner::<crate::bn254::FqConfig, 4>(chunk),
+                    FieldName::Bls12_381Fr => horner::<crate::bls12_381::FrConfig, 4>(chunk),
+                    FieldName::Bls12_381Fp => horner::<crate::bls12_381::FpConfig, 6>(chunk),
+                    FieldName::PallasFp => horner::<crate::pasta::FpConfig, 4>(chunk),
+                    FieldName::VestaFp => horner::<crate::pasta::FqConfig, 4>(chunk),
+                    FieldName::Goldilocks => {
+                        let p = crate::goldilocks::MODULUS as u128;
+                        let v = chunk
+                            .iter()
+                            .fold(0u128, |acc, &b| (acc * 256 + b as u128) % p);
+                        (v as u64).to_be_bytes().to_vec()
+                    }
+                    FieldName::BabyBear | FieldName::M31 => {
+                        let p = match field {
+                            FieldName::BabyBear => crate::babybear::MODULUS,
+                            _ => crate::m31::MODULUS,
+                        } as u64;
+                        let v = chunk
+                            .iter()
+                            .fold(0u64, |acc, &b| (acc * 256 + b as u64) % p);
+                        (v as u32).to_be_bytes().to_vec()
+                    }
+                })
+                .collect();
+            assert_eq!(out, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let err = expand_message_xmd(b"", b"", 32).unwrap_err();
+        assert_eq!(err.reason, "dst: must not be empty");
+        assert!(expand_message_xmd(b"", DST, MAX_EXPAND_BYTES).is_ok());
+        let err = expand_message_xmd(b"", DST, MAX_EXPAND_BYTES + 1).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "expand_message_xmd: length must be 1 to 8160 bytes, got 8161"
+        );
+        // Exactly 255 bytes is used as is; 256 is hashed down
+        let dst255 = vec![b'x'; 255];
+        let dst256 = vec![b'x'; 256];
+        let hashed = sha256_digest(&[b"H2C-OVERSIZE-DST-".as_slice(), &dst256].concat());
+        assert_eq!(
+            expand_message_xmd(b"m", &dst256, 32).unwrap(),
+            expand_message_xmd(b"m", &hashed, 32).unwrap()
+        );
+        assert_ne!(
+            expand_message_xmd(b"m", &dst255, 32).unwrap(),
+            expand_message_xmd(
+                b"m",
+                &sha256_digest(&[b"H2C-OVERSIZE-DST-".as_slice(), &dst255].concat()),
+                32
+            )
+            .unwrap()
+        );
+
+        assert!(hash_to_field_elements(b"", DST, 0, FieldName::Bn254Fr)
+            .unwrap()
+            .is_empty());
+        assert!(hash_to_field_elements(b"", DST, 127, FieldName::Bls12_381Fp).is_ok());
+        let err = hash_to_field_elements(b"", DST, 128, FieldName::Bls12_381Fp).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(
+            err.reason,
+            "count: at most 127 elements fit one expansion, got 128"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Linux aarch64 feature detection via the ELF auxiliary vector
+//!
+//! The kernel publishes CPU features in `AT_HWCAP` / `AT_HWCAP2`. Decoding is
+//! kept separate from reading so it can be exercised with recorded bitmasks
+//! on any host.
+
+/// `AT_HWCAP` bits (arch/arm64/include/uapi/asm/hwcap.h)
+pub const HWCAP_ASIMD: u64 = 1 << 1;
+pub const HWCAP_AES: u64 = 1 << 3;
+pub const HWCAP_PMULL: u64 = 1 << 4;
+pub const HWCAP_SHA2: u64 = 1 << 6;
+pub const HWCAP_SHA3: u64 = 1 << 17;
+pub const HWCAP_SVE: u64 = 1 << 22;
+
+/// `AT_HWCAP2` bits
+pub const HWCAP2_SVE2: u64 = 1 << 1;
+
+/// Features decoded from the auxiliary vector
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HwcapFeatures {
+    pub neon: bool,
+    pub sve: bool,
+    pub sve2: bool,
+    pub aes_pmull: bool,
+    pub aes: bool,
+    pub pmull: bool,
+    pub sha2: bool,
+    pub sha3: bool,
+}
+
+/// Decode raw `AT_HWCAP` / `AT_HWCAP2` values
+pub fn decode(hwcap: u64, hwcap2: u64) -> HwcapFeatures {
+    let sve = hwcap & HWCAP_SVE != 0;
+    HwcapFeatures {
+        neon: hwcap & HWCAP_ASIMD != 0,
+        sve,
+        // SVE2 is only meaningful when the base SVE bit is also set
+        sve2: sve && hwcap2 & HWCAP2_SVE2 != 0,
+        aes_pmull: hwcap & HWCAP_AES != 0 && hwcap & HWCAP_PMULL != 0,
+        aes: hwcap & HWCAP_AES != 0,
+        pmull: hwcap & HWCAP_PMULL != 0,
+        sha2: hwcap & HWCAP_SHA2 != 0,
+        sha3: hwcap & HWCAP_SHA3 != 0,
+    }
+}
+
+/// Read and decode the running kernel's auxiliary vector
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+pub fn read() -> HwcapFeatures {
+    // SAFETY: getauxval has no preconditions and returns 0 for unknown keys
+    let (hwcap, hwcap2) = unsafe {
+        (
+            libc::getauxval(libc::AT_HWCAP) as u64,
+            libc::getauxval(libc::AT_HWCAP2) as u64,
+        )
+    };
+    decode(hwcap, hwcap2)
+}
+
+/// Decode a `prctl(PR_SVE_GET_VL)` result into a vector length in bits
+///
+/// The low 16 bits hold the length in bytes; higher bits carry flags such as
+/// `PR_SVE_VL_INHERIT`. Negative results (EINVAL without SVE) decode to 0.
+pub fn decode_sve_vl(ret: i32) -> u32 {
+    const PR_SVE_VL_LEN_MASK: i32 = 0xffff;
+    if ret < 0 {
+        0
+    } else {
+        ((ret & PR_SVE_VL_LEN_MASK) as u32) * 8
+    }
+}
+
+/// Current SVE vector length in bits, or 0 when SVE is unavailable
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+pub fn sve_vector_length_bits() -> u32 {
+    const PR_SVE_GET_VL: libc::c_int = 51;
+    // SAFETY: PR_SVE_GET_VL takes no further arguments and returns -1 (EINVAL)
+    // on kernels or CPUs without SVE
+    decode_sve_vl(unsafe { libc::prctl(PR_SVE_GET_VL, 0, 0, 0, 0) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_graviton3_like() {
+        // NEON, AES, PMULL, SHA2, SHA3, SVE set; SVE2 not set (Neoverse V1)
+        let caps = decode(
+            HWCAP_ASIMD | HWCAP_AES | HWCAP_PMULL | HWCAP_SHA2 | HWCAP_SHA3 | HWCAP_SVE | 0x1,
+            0,
+        );
+        assert_eq!(
+            caps,
+            HwcapFeatures {
+                neon: true,
+                sve: true,
+                sve2: false,
+                aes_pmull: true,
+                aes: true,
+                pmull: true,
+                sha2: true,
+                sha3: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_sve2_requires_sve() {
+        assert!(decode(HWCAP_SVE, HWCAP2_SVE2).sve2);
+        assert!(!decode(0, HWCAP2_SVE2).sve2);
+        // AES without PMULL does not count as the combined extension
+        assert!(!decode(HWCAP_AES, 0).aes_pmull);
+        assert!(decode(HWCAP_AES, 0).aes);
+        // Neoverse N1 style: SHA2 without SHA3
+        let n1 = decode(HWCAP_ASIMD | HWCAP_SHA2, 0);
+        assert!(n1.sha2 && !n1.sha3);
+        assert_eq!(decode(0, 0), HwcapFeatures::default());
+    }
+
+    #[test]
+    fn test_decode_sve_vl() {
+        // Neoverse V2 (16 bytes), Neoverse V1 / A64FX (32 / 64 bytes)
+        assert_eq!(decode_sve_vl(16), 128);
+        assert_eq!(decode_sve_vl(32), 256);
+        assert_eq!(decode_sve_vl(64), 512);
+        // PR_SVE_VL_INHERIT (1 << 17) is a flag, not part of the length
+        assert_eq!(decode_sve_vl(32 | 1 << 17), 256);
+        assert_eq!(decode_sve_vl(-1), 0);
+    }
+}
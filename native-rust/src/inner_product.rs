@@ -0,0 +1,275 @@
+//! Inner products of packed field element vectors
+//!
+//! `fr_inner_product` computes `Σ a[i] · b[i]` without materializing the
+//! products. The vectors are split into fixed-size blocks, each summed on a
+//! worker thread, and the block sums are added in block order, so the work
+//! done does not depend on the thread count. Inputs use the encoding of
+//! `random_field_elements`: canonical, little-endian, at the field's element
+//! size.
+//!
+//! For the Montgomery-backed fields the canonical limbs are multiplied as if
+//! they were Montgomery form, which yields `a · b / R` per term, and the sum
+//! is scaled by `R` once at the end. This skips converting each element.
+
+use std::ops::Range;
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::montgomery::{Fp, MontConfig};
+use crate::random::FieldName;
+use crate::validate::check_canonical;
+
+/// Elements per block; each block's sum is one parallel work item
+const BLOCK: usize = 1 << 13;
+
+/// `Σ term(i)` over `0..n`, summed per [`BLOCK`] on worker threads and then
+/// across blocks in order
+fn block_sum<T: Copy + Send + Sync>(
+    n: usize,
+    zero: T,
+    add: impl Fn(T, T) -> T + Sync,
+    term: impl Fn(usize) -> T + Sync,
+) -> T {
+    let blocks: Vec<Range<usize>> = (0..n)
+        .step_by(BLOCK)
+        .map(|start| start..(start + BLOCK).min(n))
+        .collect();
+    crate::parallel::par_map(&blocks, |block| {
+        block.clone().fold(zero, |acc, i| add(acc, term(i)))
+    })
+    .into_iter()
+    .fold(zero, &add)
+}
+
+fn inner_mont<C: MontConfig<N>, const N: usize>(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let size = 8 * N;
+    // Range checked by the caller, so decoding cannot fail
+    let raw = |bytes: &[u8], i: usize| {
+        Fp::<C, N>::from_montgomery_le_bytes(&bytes[i * size..(i + 1) * size])
+            .expect("element below the modulus")
+    };
+    let sum = block_sum(
+        a.len() / size,
+        Fp::zero(),
+        |x, y| x + y,
+        |i| raw(a, i) * raw(b, i),
+    );
+    // Each term is a · b / R, read as Montgomery form a · b / R^2
+    let r2 = Fp::<C, N>::from_canonical(C::R2).expect("R^2 mod p is reduced");
+    (sum * r2).to_le_bytes()
+}
+
+fn word<const B: usize>(bytes: &[u8], i: usize) -> [u8; B] {
+    bytes[i * B..(i + 1) * B].try_into().unwrap()
+}
+
+fn inner_goldilocks(a: &[u8], b: &[u8]) -> Vec<u8> {
+    use crate::goldilocks::{add, mul};
+    let value = |bytes: &[u8], i| u64::from_le_bytes(word(bytes, i));
+    block_sum(a.len() / 8, 0, add, |i| mul(value(a, i), value(b, i)))
+        .to_le_bytes()
+        .to_vec()
+}
+
+fn inner_babybear(a: &[u8], b: &[u8]) -> Vec<u8> {
+    use crate::babybear::{add, mont_mul, to_montgomery};
+    let value = |bytes: &[u8], i| u32::from_le_bytes(word(bytes, i));
+    // Terms are a · b / R; to_montgomery multiplies the sum back by R
+    let sum = block_sum(a.len() / 4, 0, add, |i| mont_mul(value(a, i), value(b, i)));
+    to_montgomery(sum).to_le_bytes().to_vec()
+}
+
+fn inner_m31(a: &[u8], b: &[u8]) -> Vec<u8> {
+    use crate::m31::{add, mul};
+    let value = |bytes: &[u8], i| u32::from_le_bytes(word(bytes, i));
+    block_sum(a.len() / 4, 0, add, |i| mul(value(a, i), value(b, i)))
+        .to_le_bytes()
+        .to_vec()
+}
+
+/// `Σ a[i] · b[i]` over packed little-endian elements of `field`
+pub fn inner_product(a: &[u8], b: &[u8], field: FieldName) -> Result<Vec<u8>> {
+    let size = field.element_bytes();
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "b: expected {} elements to match a, got {}",
+                a.len() / size,
+                b.len() / size
+            ),
+        ));
+    }
+    check_canonical(a, field, "a")?;
+    check_canonical(b, field, "b")?;
+    Ok(match field {
+        FieldName::Bn254Fr => inner_mont::<crate::bn254::FrConfig, 4>(a, b),
+        FieldName::Bn254Fq => inner_mont::<crate::bn254::FqConfig, 4>(a, b),
+        FieldName::Bls12_381Fr => inner_mont::<crate::bls12_381::FrConfig, 4>(a, b),
+        FieldName::Bls12_381Fp => inner_mont::<crate::bls12_381::FpConfig, 6>(a, b),
+        FieldName::PallasFp => inner_mont::<crate::pasta::FpConfig, 4>(a, b),
+        FieldName::VestaFp => inner_mont::<crate::pasta::FqConfig, 4>(a, b),
+        FieldName::Goldilocks => inner_goldilocks(a, b),
+        FieldName::BabyBear => inner_babybear(a, b),
+        FieldName::M31 => inner_m31(a, b),
+    })
+}
+
+/// Inner product `Σ a[i] · b[i]` of two packed vectors, as one element
+///
+/// `field` is any name `random_field_elements` accepts, and `a`, `b` and the
+/// result are packed little-endian canonical elements of that field. Empty
+/// vectors give zero. Vectors of different lengths throw, as do lengths
+/// that are not a multiple of the element size and elements `>= p`.
+/// Large vectors are summed in parallel, in a fixed block order.
+#[napi]
+pub fn fr_inner_product(a: Buffer, b: Buffer, field: String) -> Result<Buffer> {
+    inner_product(&a, &b, FieldName::parse(&field)?).map(Buffer::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_elements, SEED_BYTES};
+
+    /// Schoolbook `Σ a[i] · b[i]` over little-endian integers, reduced once
+    fn naive(a: &[u8], b: &[u8], field: FieldName) -> Vec<u8> {
+        let size = field.element_bytes();
+        let to_big = |bytes: &[u8]| {
+            let mut limbs: Vec<u64> = bytes
+                .chunks(8)
+                .map(|w| {
+                    let mut le = [0u8; 8];
+                    le[..w.len()].copy_from_slice(w);
+                    u64::from_le_bytes(le)
+                })
+                .collect();
+            limbs.resize(2 * limbs.len() + 1, 0);
+            limbs
+        };
+        let p = to_big(&field.modulus_le_bytes());
+        let mut sum = vec![0u64; p.len()];
+        for (x, y) in a.chunks_exact(size).zip(b.chunks_exact(size)) {
+            let (x, y) = (to_big(x), to_big(y));
+            let half = x.len() / 2;
+            // x · y fits the doubled width; add it into the running sum
+            for i in 0..half {
+                let mut carry = 0u128;
+                for j in 0..half {
+                    let t = sum[i + j] as u128 + x[i] as u128 * y[j] as u128 + carry;
+                    sum[i + j] = t as u64;
+                    carry = t >> 64;
+                }
+                let mut k = i + half;
+                while carry != 0 {
+                    let t = sum[k] as u128 + carry;
+                    sum[k] = t as u64;
+                    carry = t >> 64;
+                    k += 1;
+                }
+            }
+            // Keep the sum below p so it cannot overflow across terms
+            sum = rem(&sum, &p);
+        }
+        sum.iter()
+            .flat_map(|l| l.to_le_bytes())
+            .take(size)
+            .collect()
+    }
+
+    /// `x mod p` by shift-and-subtract
+    fn rem(x: &[u64], p: &[u64]) -> Vec<u64> {
+        let bits = 64 * x.len();
+        let mut r = vec![0u64; x.len()];
+        for bit in (0..bits).rev() {
+            let mut carry = (x[bit / 64] >> (bit % 64)) & 1;
+            for limb in r.iter_mut() {
+                let next = *limb >> 63;
+                *limb = (*limb << 1) | carry;
+                carry = next;
+            }
+            if r.iter().rev().ge(p.iter().rev()) {
+                let mut borrow = 0u64;
+                for (limb, &q) in r.iter_mut().zip(p) {
+                    let (d, b1) = limb.overflowing_sub(q);
+                    let (d, b2) = d.overflowing_sub(borrow);
+                    *limb = d;
+                    borrow = (b1 || b2) as u64;
+                }
+            }
+        }
+        r
+    }
+
+    #[test]
+    fn test_matches_naive_for_every_field() {
+        for (name, field) in FieldName::ALL {
+            let size = field.element_bytes();
+            for n in [0, 1, 7, BLOCK + 3] {
+                let a = random_elements(field, n, Some(&[1; SEED_BYTES])).unwrap();
+                let b = random_elements(field, n, Some(&[2; SEED_BYTES])).unwrap();
+                let result = inner_product(&a, &b, field).unwrap();
+                assert_eq!(result.len(), size, "{name}");
+                assert_eq!(result, naive(&a, &b, field), "{name} n={n}");
+            }
+            // p - 1 squared, n times, is n
+            let mut p_minus_one = field.modulus_le_bytes();
+            p_minus_one[0] -= 1;
+            let a = p_minus_one.repeat(5);
+            let mut five = vec![0; size];
+            five[0] = 5;
+            assert_eq!(inner_product(&a, &a, field).unwrap(), five, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let field = FieldName::Bn254Fr;
+        let a = random_elements(field, 4, Some(&[3; SEED_BYTES])).unwrap();
+        let err = inner_product(&a, &a[..96], field).unwrap_err();
+        assert_eq!(err.reason, "b: expected 4 elements to match a, got 3");
+        let err = inner_product(&a[1..], &a[1..], field).unwrap_err();
+        assert_eq!(err.reason, "a: length 127 is not a multiple of 32 bytes");
+        let mut b = a.clone();
+        b[64..96].copy_from_slice(&field.modulus_le_bytes());
+        let err = inner_product(&a, &b, field).unwrap_err();
+        assert_eq!(err.reason, "b[2]: value is not below the bn254-fr modulus");
+    }
+
+    /// `cargo test --release bench_inner_product -- --ignored --nocapture`
+    ///
+    /// Fused inner product against separate element-wise mul and sum.
+    #[test]
+    #[ignore]
+    fn bench_inner_product() {
+        use crate::bn254::{fr_mul, Fr};
+        use crate::field::Form;
+        use std::time::Instant;
+
+        let field = FieldName::Bn254Fr;
+        for log_n in [20, 24] {
+            let n = 1 << log_n;
+            let a = random_elements(field, n, Some(&[5; SEED_BYTES])).unwrap();
+            let b = random_elements(field, n, Some(&[6; SEED_BYTES])).unwrap();
+
+            let start = Instant::now();
+            let fused = inner_product(&a, &b, field).unwrap();
+            let fused_time = start.elapsed();
+
+            let start = Instant::now();
+            let products = fr_mul(&a, &b, false, Form::Canonical).unwrap();
+            let sum = products
+                .chunks_exact(32)
+                .fold(Fr::zero(), |acc, p| acc + Fr::from_le_bytes(p).unwrap());
+            let separate_time = start.elapsed();
+
+            assert_eq!(fused, sum.to_le_bytes());
+            println!(
+                "2^{log_n} elements: fused {fused_time:?}, mul then sum {separate_time:?} ({:.1}x)",
+                separate_time.as_secs_f64() / fused_time.as_secs_f64()
+            );
+        }
+    }
+}
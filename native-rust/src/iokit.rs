@@ -0,0 +1,125 @@
+//! Thin wrappers over the macOS IORegistry
+//!
+//! Like [`crate::sysctl`], readers return `None` when the entry or property
+//! does not exist, e.g. on Intel Macs without an Apple GPU. Only public
+//! registry lookups are used, so no entitlements are required.
+
+use std::os::raw::{c_char, c_void};
+
+use core_foundation::base::{CFType, TCFType};
+use core_foundation::dictionary::CFDictionary;
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
+
+#[allow(non_camel_case_types)]
+type io_object_t = u32;
+
+/// `kIOMainPortDefault`
+const MAIN_PORT_DEFAULT: u32 = 0;
+
+extern "C" {
+    fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+    fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> io_object_t;
+    fn IORegistryEntryCreateCFProperty(
+        entry: io_object_t,
+        key: *const c_void,
+        allocator: *const c_void,
+        options: u32,
+    ) -> *const c_void;
+    fn IOObjectRelease(object: io_object_t) -> i32;
+    fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+    fn IOPSGetProvidingPowerSourceType(snapshot: *const c_void) -> *const c_void;
+    fn IOPMCopyCPUPowerStatus(status: *mut *const c_void) -> i32;
+}
+
+/// First registered service of IOKit class `class`, released by the caller
+fn matching_service(class: &std::ffi::CStr) -> Option<io_object_t> {
+    // SAFETY: IOServiceMatching returns a dictionary that
+    // IOServiceGetMatchingService consumes
+    let service = unsafe {
+        let matching = IOServiceMatching(class.as_ptr());
+        if matching.is_null() {
+            return None;
+        }
+        IOServiceGetMatchingService(MAIN_PORT_DEFAULT, matching)
+    };
+    (service != 0).then_some(service)
+}
+
+/// Whether any service of IOKit class `class` is registered
+pub fn service_exists(class: &std::ffi::CStr) -> bool {
+    let Some(service) = matching_service(class) else {
+        return false;
+    };
+    // SAFETY: the service came from IOServiceGetMatchingService
+    unsafe { IOObjectRelease(service) };
+    true
+}
+
+/// Read an integer property of the first `AGXAccelerator` (Apple GPU) service
+pub fn accelerator_property_u64(key: &str) -> Option<u64> {
+    let service = matching_service(c"AGXAccelerator")?;
+    let key = CFString::new(key);
+    // SAFETY: the property follows the Create rule and is wrapped (and
+    // released) by CFType; a null result means the key is absent
+    let value = unsafe {
+        let property = IORegistryEntryCreateCFProperty(
+            service,
+            key.as_concrete_TypeRef() as *const c_void,
+            std::ptr::null(),
+            0,
+        );
+        IOObjectRelease(service);
+        if property.is_null() {
+            return None;
+        }
+        CFType::wrap_under_create_rule(property as _)
+    };
+    value
+        .downcast::<CFNumber>()?
+        .to_i64()
+        .and_then(|v| u64::try_from(v).ok())
+}
+
+/// Source currently powering the machine: "AC Power", "Battery Power" or
+/// "UPS Power" (`kIOPS*Value`)
+pub fn providing_power_source() -> Option<String> {
+    // SAFETY: the snapshot follows the Create rule and is wrapped (and
+    // released) by CFType; the source type follows the Get rule and is
+    // retained before the snapshot is dropped
+    unsafe {
+        let snapshot = IOPSCopyPowerSourcesInfo();
+        if snapshot.is_null() {
+            return None;
+        }
+        let snapshot = CFType::wrap_under_create_rule(snapshot as _);
+        let source = IOPSGetProvidingPowerSourceType(snapshot.as_CFTypeRef());
+        if source.is_null() {
+            return None;
+        }
+        Some(CFString::wrap_under_get_rule(source as _).to_string())
+    }
+}
+
+/// Percentage of full CPU speed the power manager currently allows
+/// (`kIOPMCPUPowerLimitProcessorSpeedKey`), 100 when unthrottled
+///
+/// `None` where the power manager does not publish CPU power status, which
+/// includes most Apple Silicon machines.
+pub fn cpu_speed_limit() -> Option<u64> {
+    let mut status: *const c_void = std::ptr::null();
+    // SAFETY: on success the dictionary follows the Create rule and is
+    // wrapped (and released) by CFDictionary
+    let status = unsafe {
+        if IOPMCopyCPUPowerStatus(&mut status) != 0 || status.is_null() {
+            return None;
+        }
+        CFDictionary::<CFType, CFType>::wrap_under_create_rule(status as _)
+    };
+    let key = CFString::new("CPU_Speed_Limit").as_CFType();
+    status
+        .find(&key)?
+        .downcast::<CFNumber>()?
+        .to_i64()
+        .and_then(|v| u64::try_from(v).ok())
+}
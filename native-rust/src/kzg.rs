@@ -0,0 +1,259 @@
+//! KZG polynomial commitments over BLS12-381
+//!
+//! Polynomials are coefficient vectors (lowest degree first) of 32-byte
+//! little-endian scalars. The structured reference string is passed in by
+//! the caller: `srs_g1[i] = [τ^i]_1` and `srs_g2 = [G2, [τ]_2, ...]`, using
+//! the uncompressed point encodings of [`crate::bls12_381`].
+//!
+//! A commitment is `C = [p(τ)]_1`. An opening at `z` is the evaluation
+//! `y = p(z)` together with `π = [q(τ)]_1` for `q(X) = (p(X) - y) / (X - z)`,
+//! checked with `e(π, [τ]_2 - z G2) == e(C - y G1, G2)`.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bls12_381::{
+    decode_g1, decode_g2_subgroup, decode_scalar_le, encode_g1, Bls12Pairing, Fr, G1Affine,
+    G2Affine,
+};
+use crate::msm::pippenger;
+use crate::pairing::pairing_product_is_one;
+
+/// Result of opening a polynomial at a point
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KzgProof {
+    /// 96-byte G1 commitment to the polynomial
+    pub commitment: Vec<u8>,
+    /// `p(z)` as a 32-byte little-endian scalar
+    pub evaluation: Vec<u8>,
+    /// 96-byte G1 opening proof
+    pub proof: Vec<u8>,
+}
+
+fn parse_poly(poly: &[Vec<u8>]) -> Result<Vec<Fr>> {
+    poly.iter()
+        .enumerate()
+        .map(|(i, c)| decode_scalar_le(c, &format!("poly[{i}]")))
+        .collect()
+}
+
+fn parse_srs_g1(srs_g1: &[Vec<u8>], needed: usize) -> Result<Vec<G1Affine>> {
+    if srs_g1.len() < needed {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "srs_g1: polynomial needs {needed} powers of tau, got {}",
+                srs_g1.len()
+            ),
+        ));
+    }
+    srs_g1[..needed]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| decode_g1(p, &format!("srs_g1[{i}]")))
+        .collect()
+}
+
+/// `[p(τ)]_1` for coefficients `coeffs`
+pub fn commit(coeffs: &[Fr], srs: &[G1Affine]) -> G1Affine {
+    let scalars: Vec<[u64; 4]> = coeffs.iter().map(|c| c.to_canonical()).collect();
+    pippenger(&srs[..coeffs.len()], &scalars, true).to_affine()
+}
+
+/// Evaluate `p(z)` and the quotient `(p(X) - p(z)) / (X - z)`
+pub fn divide_by_linear(coeffs: &[Fr], z: Fr) -> (Fr, Vec<Fr>) {
+    let mut quotient = vec![Fr::zero(); coeffs.len().saturating_sub(1)];
+    let mut acc = Fr::zero();
+    for (i, c) in coeffs.iter().enumerate().rev() {
+        if i < quotient.len() {
+            quotient[i] = acc;
+        }
+        acc = acc * z + *c;
+    }
+    (acc, quotient)
+}
+
+/// Pairing check `e(π, [τ]_2 - z G2) == e(C - y G1, G2)`
+pub fn verify(
+    commitment: &G1Affine,
+    z: Fr,
+    y: Fr,
+    proof: &G1Affine,
+    g1: &G1Affine,
+    g2: &G2Affine,
+    tau_g2: &G2Affine,
+) -> bool {
+    let z_g2 = g2.to_jacobian().mul_limbs(&z.to_canonical());
+    let shifted_g2 = tau_g2.to_jacobian().add(&z_g2.neg()).to_affine();
+    let y_g1 = g1.to_jacobian().mul_limbs(&y.to_canonical());
+    let lhs_g1 = commitment.to_jacobian().add(&y_g1.neg()).to_affine();
+    // e(π, [τ - z]_2) · e(-(C - y G1), G2) == 1
+    pairing_product_is_one::<Bls12Pairing, 6>(&[(*proof, shifted_g2), (lhs_g1.neg(), *g2)])
+}
+
+/// Commit to a polynomial given in little-endian scalar coefficients
+#[napi]
+pub fn kzg_commit(poly: Vec<Vec<u8>>, srs_g1: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    crate::constant_time::require_variable_time("kzg_commit")?;
+    let coeffs = parse_poly(&poly)?;
+    let srs = parse_srs_g1(&srs_g1, coeffs.len())?;
+    Ok(encode_g1(&commit(&coeffs, &srs)))
+}
+
+/// Open a polynomial at `z`, returning commitment, evaluation and proof
+#[napi]
+pub fn kzg_open(poly: Vec<Vec<u8>>, z: Vec<u8>, srs_g1: Vec<Vec<u8>>) -> Result<KzgProof> {
+    crate::constant_time::require_variable_time("kzg_open")?;
+    let coeffs = parse_poly(&poly)?;
+    let z = decode_scalar_le(&z, "z")?;
+    let srs = parse_srs_g1(&srs_g1, coeffs.len())?;
+    let (y, quotient) = divide_by_linear(&coeffs, z);
+    Ok(KzgProof {
+        commitment: encode_g1(&commit(&coeffs, &srs)),
+        evaluation: y.to_le_bytes(),
+        proof: encode_g1(&commit(&quotient, &srs)),
+    })
+}
+
+/// Verify a KZG opening with the pairing check
+///
+/// `srs_g1[0]` must be the G1 generator, `srs_g2[0]` the G2 generator and
+/// `srs_g2[1]` the point `[τ]_2`. SRS G2 points outside the prime-order
+/// subgroup throw.
+#[napi]
+pub fn kzg_verify(
+    commitment: Vec<u8>,
+    z: Vec<u8>,
+    y: Vec<u8>,
+    proof: Vec<u8>,
+    srs_g1: Vec<Vec<u8>>,
+    srs_g2: Vec<Vec<u8>>,
+) -> Result<bool> {
+    let commitment = decode_g1(&commitment, "commitment")?;
+    let z = decode_scalar_le(&z, "z")?;
+    let y = decode_scalar_le(&y, "y")?;
+    let proof = decode_g1(&proof, "proof")?;
+    let g1 = parse_srs_g1(&srs_g1, 1)?[0];
+    if srs_g2.len() < 2 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("srs_g2: expected at least 2 points, got {}", srs_g2.len()),
+        ));
+    }
+    let g2 = decode_g2_subgroup(&srs_g2[0], "srs_g2[0]")?;
+    let tau_g2 = decode_g2_subgroup(&srs_g2[1], "srs_g2[1]")?;
+    Ok(verify(&commitment, z, y, &proof, &g1, &g2, &tau_g2))
+}
+
+/// Powers-of-tau setup for a known `τ`; only for tests and benchmarks
+#[cfg(test)]
+pub(crate) fn insecure_setup(tau: Fr, n: usize) -> (Vec<G1Affine>, Vec<G2Affine>) {
+    use crate::bls12_381::{g1_generator, g2_generator};
+
+    let g1 = g1_generator().to_jacobian();
+    let mut power = Fr::one();
+    let mut srs_g1 = Vec::with_capacity(n);
+    for _ in 0..n {
+        srs_g1.push(g1.mul_limbs(&power.to_canonical()).to_affine());
+        power *= tau;
+    }
+    let g2 = g2_generator();
+    let tau_g2 = g2.to_jacobian().mul_limbs(&tau.to_canonical());
+    (srs_g1, vec![g2, tau_g2.to_affine()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::{encode_g2, g1_generator};
+
+    fn le(v: Fr) -> Vec<u8> {
+        v.to_le_bytes()
+    }
+
+    fn setup(n: usize) -> (Vec<Vec<u8>>, Vec<Vec<u8>>, Fr) {
+        let tau = Fr::from_u64(0x1234_5678_9abc_def1);
+        let (g1, g2) = insecure_setup(tau, n);
+        (
+            g1.iter().map(encode_g1).collect(),
+            g2.iter().map(encode_g2).collect(),
+            tau,
+        )
+    }
+
+    #[test]
+    fn test_divide_by_linear() {
+        // p(X) = 3 + 2X + X^2, p(5) = 38, q(X) = 7 + X
+        let p: Vec<Fr> = [3, 2, 1].iter().map(|&v| Fr::from_u64(v)).collect();
+        let (y, q) = divide_by_linear(&p, Fr::from_u64(5));
+        assert_eq!(y, Fr::from_u64(38));
+        assert_eq!(q, vec![Fr::from_u64(7), Fr::one()]);
+    }
+
+    #[test]
+    fn test_commit_matches_evaluation_at_tau() {
+        let (srs_g1, _, tau) = setup(4);
+        let p: Vec<Fr> = [9, 0, 4, 11].iter().map(|&v| Fr::from_u64(v)).collect();
+        let expected = divide_by_linear(&p, tau).0;
+        let commitment = kzg_commit(p.iter().map(|&c| le(c)).collect(), srs_g1).unwrap();
+        let direct = g1_generator()
+            .to_jacobian()
+            .mul_limbs(&expected.to_canonical())
+            .to_affine();
+        assert_eq!(commitment, encode_g1(&direct));
+    }
+
+    #[test]
+    fn test_open_and_verify() {
+        let (srs_g1, srs_g2, _) = setup(8);
+        let poly: Vec<Vec<u8>> = (0..8u64)
+            .map(|i| le(Fr::from_u64(i * i + 1) * -Fr::one()))
+            .collect();
+        let z = le(Fr::from_u64(77));
+        let opened = kzg_open(poly, z.clone(), srs_g1.clone()).unwrap();
+        let check = |y: &Vec<u8>, z: &Vec<u8>| {
+            kzg_verify(
+                opened.commitment.clone(),
+                z.clone(),
+                y.clone(),
+                opened.proof.clone(),
+                srs_g1.clone(),
+                srs_g2.clone(),
+            )
+            .unwrap()
+        };
+        assert!(check(&opened.evaluation, &z));
+        assert!(!check(&le(Fr::from_u64(1)), &z));
+        assert!(!check(&opened.evaluation, &le(Fr::from_u64(78))));
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let (srs_g1, _, _) = setup(2);
+        let poly = vec![le(Fr::one()); 3];
+        assert!(kzg_commit(poly, srs_g1.clone()).is_err());
+        assert!(kzg_commit(vec![vec![0xff; 32]], srs_g1).is_err());
+
+        let (srs_g1, srs_g2, _) = setup(4);
+        let opened = kzg_open(vec![le(Fr::one()); 4], le(Fr::from_u64(3)), srs_g1.clone()).unwrap();
+        let q13 = encode_g2(&crate::bls12_381::g2_point_of_order_13());
+        for i in 0..2 {
+            let mut bad_g2 = srs_g2.clone();
+            bad_g2[i] = q13.clone();
+            let err = kzg_verify(
+                opened.commitment.clone(),
+                le(Fr::from_u64(3)),
+                opened.evaluation.clone(),
+                opened.proof.clone(),
+                srs_g1.clone(),
+                bad_g2,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.reason,
+                format!("srs_g2[{i}]: point is not in the BLS12-381 G2 subgroup")
+            );
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Batched Legendre symbols of packed field elements
+//!
+//! `fr_legendre_batch` evaluates Euler's criterion `x^((p - 1) / 2)` for
+//! every element, which is 1 for a non-zero square, `p - 1` for a
+//! non-residue and 0 for zero. Every element shares the exponent, which
+//! the Montgomery fields raise with [`Fp::pow_vartime`]'s fixed 4-bit
+//! window. Inputs use the encoding of `random_field_elements`:
+//! canonical, little-endian, at the field's element size.
+
+use napi::bindgen_prelude::{Buffer, Int8Array};
+use napi::Result;
+use napi_derive::napi;
+
+use crate::field::ELEMENTWISE_PARALLEL_THRESHOLD;
+use crate::montgomery::{Fp, MontConfig};
+use crate::random::FieldName;
+use crate::validate::check_canonical;
+
+/// Map Euler's criterion to the Legendre symbol
+fn symbol(is_zero: bool, is_one: bool) -> i8 {
+    match (is_zero, is_one) {
+        (true, _) => 0,
+        (_, true) => 1,
+        _ => -1,
+    }
+}
+
+fn legendre_mont<C: MontConfig<N>, const N: usize>(x: &[u8]) -> i8 {
+    // p is odd, so (p - 1) / 2 = p >> 1
+    let mut half = C::MODULUS;
+    for i in 0..N {
+        half[i] = (half[i] >> 1) | half.get(i + 1).map_or(0, |h| h << 63);
+    }
+    // Range checked by the caller, so decoding cannot fail
+    let x = Fp::<C, N>::from_le_bytes(x).expect("element below the modulus");
+    symbol(x.is_zero(), x.pow_vartime(&half) == Fp::one())
+}
+
+fn legendre_goldilocks(x: &[u8]) -> i8 {
+    use crate::goldilocks::{pow, MODULUS};
+    let x = u64::from_le_bytes(x.try_into().unwrap());
+    symbol(x == 0, pow(x, (MODULUS - 1) / 2) == 1)
+}
+
+fn legendre_babybear(x: &[u8]) -> i8 {
+    use crate::babybear::{mont_mul, to_montgomery, MODULUS};
+    let x = u32::from_le_bytes(x.try_into().unwrap());
+    let one = to_montgomery(1);
+    let (mut acc, mut base, mut exp) = (one, to_montgomery(x), (MODULUS - 1) / 2);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mont_mul(acc, base);
+        }
+        base = mont_mul(base, base);
+        exp >>= 1;
+    }
+    symbol(x == 0, acc == one)
+}
+
+fn legendre_m31(x: &[u8]) -> i8 {
+    use crate::m31::{pow, MODULUS};
+    let x = u32::from_le_bytes(x.try_into().unwrap());
+    symbol(x == 0, pow(x, (MODULUS - 1) / 2) == 1)
+}
+
+/// Legendre symbol of each packed little-endian element of `field`
+pub fn legendre_batch(values: &[u8], field: FieldName) -> Result<Vec<i8>> {
+    check_canonical(values, field, "values")?;
+    let symbol_of: fn(&[u8]) -> i8 = match field {
+        FieldName::Bn254Fr => legendre_mont::<crate::bn254::FrConfig, 4>,
+        FieldName::Bn254Fq => legendre_mont::<crate::bn254::FqConfig, 4>,
+        FieldName::Bls12_381Fr => legendre_mont::<crate::bls12_381::FrConfig, 4>,
+        FieldName::Bls12_381Fp => legendre_mont::<crate::bls12_381::FpConfig, 6>,
+        FieldName::PallasFp => legendre_mont::<crate::pasta::FpConfig, 4>,
+        FieldName::VestaFp => legendre_mont::<crate::pasta::FqConfig, 4>,
+        FieldName::Goldilocks => legendre_goldilocks,
+        FieldName::BabyBear => legendre_babybear,
+        FieldName::M31 => legendre_m31,
+    };
+    let size = field.element_bytes();
+    let mut out = vec![0i8; values.len() / size];
+    crate::parallel::par_chunks_mut(
+        &mut out,
+        1,
+        ELEMENTWISE_PARALLEL_THRESHOLD,
+        |offset, chunk| {
+            let inputs = values[offset * size..].chunks_exact(size);
+            for (s, x) in chunk.iter_mut().zip(inputs) {
+                *s = symbol_of(x);
+            }
+        },
+    );
+    Ok(out)
+}
+
+/// Legendre symbol of every element of a packed array: 1, 0 or -1
+///
+/// `field` is any name `random_field_elements` accepts and `values` packed
+/// little-endian canonical elements of it. Gives 1 for a non-zero square,
+/// -1 for a non-residue and 0 for zero. Lengths that are not a multiple of
+/// the element size and elements `>= p` throw. Large arrays are split
+/// across worker threads.
+#[napi]
+pub fn fr_legendre_batch(values: Buffer, field: String) -> Result<Int8Array> {
+    legendre_batch(&values, FieldName::parse(&field)?).map(Int8Array::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_elements, SEED_BYTES};
+
+    /// Little-endian encoding of a small integer in `field`
+    fn small(field: FieldName, v: u64) -> Vec<u8> {
+        let mut bytes = v.to_le_bytes().to_vec();
+        bytes.resize(field.element_bytes(), 0);
+        bytes
+    }
+
+    #[test]
+    fn test_known_symbols() {
+        // The non-residue each field's square root or NTT generator is
+        // built from: 5 for BN254 Fr, -1 for BLS12-381 Fp (p = 3 mod 4),
+        // 7 for Goldilocks
+        let mut minus_one = FieldName::Bls12_381Fp.modulus_le_bytes();
+        minus_one[0] -= 1;
+        for (field, non_residue) in [
+            (FieldName::Bn254Fr, small(FieldName::Bn254Fr, 5)),
+            (FieldName::Bls12_381Fp, minus_one),
+            (FieldName::Goldilocks, small(FieldName::Goldilocks, 7)),
+        ] {
+            let mut values = non_residue;
+            for v in [0, 1, 4, 9] {
+                values.extend(small(field, v));
+            }
+            assert_eq!(
+                legendre_batch(&values, field).unwrap(),
+                [-1, 0, 1, 1, 1],
+                "{}",
+                field.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_squares_and_non_residues() {
+        for (name, field) in FieldName::ALL {
+            let n = ELEMENTWISE_PARALLEL_THRESHOLD + 5;
+            let values = random_elements(field, n, Some(&[8; SEED_BYTES])).unwrap();
+            let symbols = legendre_batch(&values, field).unwrap();
+            assert_eq!(symbols.len(), n, "{name}");
+            // About half the random elements are squares
+            let residues = symbols.iter().filter(|&&s| s == 1).count();
+            assert!(
+                n / 3 < residues && residues < 2 * n / 3,
+                "{name} {residues}"
+            );
+
+            // Every square is a residue, and a residue times a
+            // non-residue is not
+            let squares = crate::vector::hadamard(&values, &values, field).unwrap();
+            let symbols_of_squares = legendre_batch(&squares, field).unwrap();
+            assert!(symbols_of_squares.iter().all(|&s| s == 1), "{name}");
+            let size = field.element_bytes();
+            let qr = symbols.iter().position(|&s| s == 1).unwrap();
+            let nr = symbols.iter().position(|&s| s == -1).unwrap();
+            let product = crate::vector::hadamard(
+                &values[qr * size..(qr + 1) * size],
+                &values[nr * size..(nr + 1) * size],
+                field,
+            )
+            .unwrap();
+            assert_eq!(legendre_batch(&product, field).unwrap(), [-1], "{name}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let field = FieldName::Goldilocks;
+        let err = legendre_batch(&[0; 9], field).unwrap_err();
+        assert_eq!(err.reason, "values: length 9 is not a multiple of 8 bytes");
+        let err = legendre_batch(&field.modulus_le_bytes(), field).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "values[0]: value is not below the goldilocks modulus"
+        );
+        assert!(legendre_batch(&[], field).unwrap().is_empty());
+    }
+}
@@ -4,45 +4,431 @@
 //! This module provides Rust-based native bindings for high-performance
 //! ZK proof operations, leveraging Apple Silicon hardware acceleration.
 
+#[cfg(not(feature = "wasm"))]
+use napi::bindgen_prelude::AsyncTask;
+use napi::Result;
+#[cfg(not(feature = "wasm"))]
+use napi::{Env, Task};
 use napi_derive::napi;
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+
+#[cfg(target_arch = "x86_64")]
+pub mod avx2;
+pub mod avx512;
+pub mod babybear;
+pub mod backend;
+pub mod barrett;
+pub mod benchmark;
+pub(crate) mod bigint;
+pub mod bls12_381;
+pub mod bls_sig;
+pub mod bn254;
+pub mod bulletproofs;
+pub mod cache;
+pub mod chip;
+pub mod circom;
+pub mod constant_time;
+pub mod cpuinfo;
+pub mod decompose;
+pub mod ec;
+pub mod encoding;
+pub mod field;
+pub mod frequency;
+pub mod fri;
+pub mod goldilocks;
+pub mod gpu;
+pub mod groth16;
+pub mod hash;
+pub mod hash_to_field;
+pub mod hwcap;
+pub mod inner_product;
+#[cfg(target_os = "macos")]
+pub(crate) mod iokit;
+pub mod kzg;
+pub mod legendre;
+pub mod m31;
+pub mod memory;
+pub mod merkle;
+#[cfg(all(feature = "metal", target_os = "macos"))]
+pub(crate) mod metal_kernels;
+pub mod montgomery;
+pub mod msm;
+pub mod neon;
+pub mod ntt;
+pub mod overrides;
+pub mod pairing;
+pub mod parallel;
+pub mod pasta;
+pub mod pedersen;
+pub mod plonk;
+pub mod plookup;
+pub mod poseidon;
+pub mod power;
+pub mod r1cs;
+pub mod random;
+pub mod rescue;
+pub mod selftest;
+pub mod serialization;
+pub mod sme;
+#[cfg(target_os = "macos")]
+pub(crate) mod sysctl;
+pub mod tasks;
+pub mod thermal;
+pub mod threading;
+pub mod topology;
+pub mod tower;
+pub mod transcript;
+pub mod tuning;
+pub mod validate;
+pub mod vector;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(windows)]
+pub(crate) mod win32;
+pub mod witness;
+pub mod zero_copy;
+pub mod zkey;
 
 /// Hardware capabilities structure exposed to JavaScript
+///
+/// Detection runs once per process; `detect_rust_capabilities()` and
+/// `get_binding_status()` return clones of the cached result. Call
+/// `refresh_capabilities()` to re-detect, e.g. after changing the
+/// `ZK_ACCEL_DISABLE_*` environment variables.
 #[napi(object)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RustHardwareCapabilities {
-    /// Whether NEON SIMD is available
+    /// Whether NEON SIMD is available (runtime check on Linux and Windows)
     pub has_neon: bool,
     /// Whether AMX (Apple Matrix Coprocessor) is available
     pub has_amx: bool,
     /// Whether SME (Scalable Matrix Extension) is available (M4+)
     pub has_sme: bool,
+    /// SME version: 0 = none, 1 = SME, 2 = SME2
+    pub sme_version: u32,
+    /// Maximum SME streaming vector length in bits from
+    /// `hw.optional.arm.sme_max_svl_b` (512 on M4), 0 without SME or if unknown
+    pub sme_streaming_vector_length_bits: u32,
+    /// Whether batch kernels can run on the GPU through Metal (`metal` feature)
+    pub has_metal: bool,
+    /// Whether AVX2 is available (x86_64, runtime CPUID check)
+    pub has_avx2: bool,
+    /// Whether AVX-512 Foundation is available (x86_64, runtime CPUID check)
+    pub has_avx512f: bool,
+    /// Whether AVX-512 BW (byte/word instructions) is available
+    pub has_avx512bw: bool,
+    /// Whether AVX-512 VBMI (vector byte permutes) is available
+    pub has_avx512vbmi: bool,
+    /// Whether AVX-512 IFMA (52-bit integer multiply-add) is available
+    pub has_avx512ifma: bool,
+    /// Whether BMI2 (MULX/ADOX/ADCX-friendly bit manipulation) is available
+    pub has_bmi2: bool,
+    /// Whether SVE is available (Linux `AT_HWCAP`, Windows `IsProcessorFeaturePresent`)
+    pub has_sve: bool,
+    /// Whether SVE2 is available (Linux `AT_HWCAP2`, Windows `IsProcessorFeaturePresent`)
+    pub has_sve2: bool,
+    /// SVE vector length in bits from Linux `PR_SVE_GET_VL` (128 on Neoverse
+    /// V2, 256 on V1); 0 without SVE, on macOS, or when it cannot be queried
+    pub sve_vector_length_bits: u32,
+    /// Whether both the AES and PMULL crypto extensions are available
+    pub has_aes_pmull: bool,
+    /// Whether the ARMv8 AES instructions (FEAT_AES) are available
+    pub has_aes: bool,
+    /// Whether 64-bit polynomial multiply (FEAT_PMULL) is available
+    pub has_pmull: bool,
+    /// Whether the SHA-256 instructions (FEAT_SHA256) are available
+    pub has_sha2: bool,
+    /// Whether the SHA-3 instructions EOR3/RAX1/XAR/BCAX (FEAT_SHA3) are available
+    pub has_sha3: bool,
+    /// Same as `has_sha3`, under the `has_arm_*` name older callers probe for
+    pub has_arm_sha3: bool,
     /// Number of CPU cores
     pub cpu_cores: u32,
+    /// Number of physical cores, excluding SMT siblings (at most `cpu_cores`)
+    pub physical_cores: u32,
+    /// Number of performance cores (all cores on non-hybrid CPUs)
+    pub performance_cores: u32,
+    /// Number of efficiency cores, 0 on non-hybrid CPUs
+    pub efficiency_cores: u32,
+    /// Total physical (unified on Apple Silicon) memory in bytes, 0 if unknown
+    pub total_memory_bytes: i64,
+    /// L1 data cache size in bytes, 0 if unknown
+    pub l1_cache_bytes: i64,
+    /// L2 cache size in bytes, 0 if unknown
+    pub l2_cache_bytes: i64,
+    /// Cache line size in bytes, 0 if unknown
+    pub cache_line_bytes: u32,
+    /// CPU model name (e.g. "Apple M3 Pro", "ARM Neoverse-V1"), empty if unknown
+    pub cpu_model: String,
+    /// Apple Silicon generation parsed from the brand string (e.g. "M1",
+    /// "M4"), empty on other hardware
+    pub chip_family: String,
+    /// Apple Silicon variant: "base", "Pro", "Max" or "Ultra", empty on
+    /// other hardware
+    pub chip_variant: String,
+    /// GPU core count from the IORegistry (Apple Silicon), 0 if unknown
+    pub gpu_core_count: u32,
+    /// Whether the IORegistry lists an Apple Neural Engine (not used by any
+    /// kernel yet), false if unknown
+    pub has_neural_engine: bool,
     /// Target architecture
     pub arch: String,
     /// Target OS
     pub os: String,
+    /// Whether `ZK_ACCEL_DISABLE_*` environment variables forced any feature off
+    pub overridden: bool,
+}
+
+/// Runtime x86_64 feature check; always false on other architectures
+///
+/// std queries CPUID and XGETBV directly, so AVX state enabled by the OS is
+/// honoured the same way on Linux, macOS and Windows.
+macro_rules! x86_feature {
+    ($feature:tt) => {{
+        #[cfg(target_arch = "x86_64")]
+        {
+            std::arch::is_x86_feature_detected!($feature)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            false
+        }
+    }};
+}
+
+static CAPABILITIES: OnceLock<RwLock<RustHardwareCapabilities>> = OnceLock::new();
+
+fn capabilities() -> &'static RwLock<RustHardwareCapabilities> {
+    CAPABILITIES.get_or_init(|| RwLock::new(detect_uncached(overrides::get())))
 }
 
 /// Detect hardware capabilities from Rust
 ///
 /// Returns a structure containing information about available
-/// hardware acceleration features on the current system.
+/// hardware acceleration features on the current system. The first call
+/// runs detection; later calls return the cached result.
 #[napi]
 pub fn detect_rust_capabilities() -> RustHardwareCapabilities {
-    RustHardwareCapabilities {
-        has_neon: detect_neon(),
+    capabilities()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Re-run hardware detection, re-reading the environment overrides
+///
+/// The result replaces the cached capabilities returned by
+/// `detect_rust_capabilities()` and `get_binding_status()`.
+#[napi]
+pub fn refresh_capabilities() -> RustHardwareCapabilities {
+    let mut cached = capabilities().write().unwrap_or_else(|e| e.into_inner());
+    *cached = detect_uncached(overrides::reload());
+    cached.clone()
+}
+
+/// Capability detection run on the libuv thread pool
+pub struct DetectCapabilitiesTask;
+
+#[cfg(not(feature = "wasm"))]
+impl Task for DetectCapabilitiesTask {
+    type Output = RustHardwareCapabilities;
+    type JsValue = RustHardwareCapabilities;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Ok(detect_rust_capabilities())
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Promise-returning `detect_rust_capabilities()`
+///
+/// The first detection (sysctls, Metal device query) runs off the main
+/// thread; later calls resolve with the cached result.
+#[cfg(not(feature = "wasm"))]
+#[napi]
+pub fn detect_rust_capabilities_async() -> AsyncTask<DetectCapabilitiesTask> {
+    AsyncTask::new(DetectCapabilitiesTask)
+}
+
+/// Run every detector, ignoring the cache
+fn detect_uncached(overrides: overrides::Overrides) -> RustHardwareCapabilities {
+    let hwcaps = detect_hwcaps();
+    let geometry = topology::detect_memory_geometry();
+    let cores = topology::detect_core_counts();
+    let crypto = detect_crypto_extensions(&hwcaps);
+    let sme = detect_sme();
+    let cpu_model = cpuinfo::detect_cpu_model();
+    let chip = chip::parse_brand_string(&cpu_model).unwrap_or_default();
+    let mut caps = RustHardwareCapabilities {
+        has_neon: detect_neon(&hwcaps),
         has_amx: detect_amx(),
-        has_sme: detect_sme(),
+        has_sme: sme.version > 0,
+        sme_version: sme.version,
+        sme_streaming_vector_length_bits: sme.streaming_vector_length_bits,
+        has_metal: gpu::metal_available(),
+        has_avx2: x86_feature!("avx2"),
+        has_avx512f: x86_feature!("avx512f"),
+        has_avx512bw: x86_feature!("avx512bw"),
+        has_avx512vbmi: x86_feature!("avx512vbmi"),
+        has_avx512ifma: x86_feature!("avx512ifma"),
+        has_bmi2: x86_feature!("bmi2"),
+        has_sve: hwcaps.sve,
+        has_sve2: hwcaps.sve2,
+        sve_vector_length_bits: sve_vector_length(&hwcaps, detect_sve_vector_length_bits),
+        has_aes_pmull: crypto.aes && crypto.pmull,
+        has_aes: crypto.aes,
+        has_pmull: crypto.pmull,
+        has_sha2: crypto.sha2,
+        has_sha3: crypto.sha3,
+        has_arm_sha3: crypto.sha3,
         cpu_cores: get_cpu_count(),
+        physical_cores: topology::detect_physical_cores(),
+        performance_cores: cores.performance,
+        efficiency_cores: cores.efficiency,
+        total_memory_bytes: geometry.total_memory_bytes as i64,
+        l1_cache_bytes: geometry.l1_cache_bytes as i64,
+        l2_cache_bytes: geometry.l2_cache_bytes as i64,
+        cache_line_bytes: geometry.cache_line_bytes as u32,
+        cpu_model,
+        chip_family: chip.family,
+        chip_variant: chip.variant,
+        gpu_core_count: chip::detect_gpu_core_count(),
+        has_neural_engine: chip::detect_neural_engine(),
         arch: get_arch(),
         os: get_os(),
+        overridden: false,
+    };
+    apply_overrides(&mut caps, overrides);
+    caps
+}
+
+/// Clamp capability flags forced off by the environment
+fn apply_overrides(caps: &mut RustHardwareCapabilities, overrides: overrides::Overrides) {
+    caps.has_sme &= !overrides.disable_sme;
+    if !caps.has_sme {
+        caps.sme_version = 0;
+        caps.sme_streaming_vector_length_bits = 0;
+    }
+    caps.has_amx &= !overrides.disable_amx;
+    caps.has_neon &= !overrides.disable_neon;
+    caps.has_metal &= !overrides.disable_gpu;
+    caps.overridden = overrides.any();
+}
+
+/// Read the runtime aarch64 feature bits
+///
+/// Linux decodes the auxiliary vector; Windows on ARM queries
+/// `IsProcessorFeaturePresent`. Everything else reports no features.
+fn detect_hwcaps() -> hwcap::HwcapFeatures {
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        hwcap::read()
+    }
+    #[cfg(all(windows, target_arch = "aarch64"))]
+    {
+        use win32::*;
+
+        let crypto = processor_feature(PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE);
+        let sve = processor_feature(PF_ARM_SVE_INSTRUCTIONS_AVAILABLE);
+        hwcap::HwcapFeatures {
+            neon: processor_feature(PF_ARM_NEON_INSTRUCTIONS_AVAILABLE),
+            sve,
+            sve2: sve && processor_feature(PF_ARM_SVE2_INSTRUCTIONS_AVAILABLE),
+            aes_pmull: crypto,
+            aes: crypto,
+            pmull: crypto,
+            sha2: crypto,
+            sha3: false,
+        }
+    }
+    #[cfg(not(any(
+        all(target_os = "linux", target_arch = "aarch64"),
+        all(windows, target_arch = "aarch64")
+    )))]
+    {
+        hwcap::HwcapFeatures::default()
+    }
+}
+
+/// SVE vector length for dispatch decisions, 0 when SVE cannot be used
+///
+/// Reads the cached capabilities, so it is cheap enough for hot paths.
+pub fn sve_vector_length_bits() -> u32 {
+    detect_rust_capabilities().sve_vector_length_bits
+}
+
+/// Vector length to report for the decoded features; only queried with SVE
+fn sve_vector_length(hwcaps: &hwcap::HwcapFeatures, query: impl FnOnce() -> u32) -> u32 {
+    if hwcaps.sve {
+        query()
+    } else {
+        0
+    }
+}
+
+/// Detect the SVE vector length in bits (Linux aarch64 only)
+fn detect_sve_vector_length_bits() -> u32 {
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        hwcap::sve_vector_length_bits()
+    }
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    {
+        0
+    }
+}
+
+/// ARMv8 cryptographic extensions
+struct CryptoExtensions {
+    aes: bool,
+    pmull: bool,
+    sha2: bool,
+    sha3: bool,
+}
+
+/// Detect ARMv8 crypto extensions
+///
+/// macOS publishes them as `hw.optional.arm.FEAT_*` sysctls; Linux aarch64
+/// reports them in `AT_HWCAP`, already decoded into `hwcaps`.
+fn detect_crypto_extensions(hwcaps: &hwcap::HwcapFeatures) -> CryptoExtensions {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = hwcaps;
+        let feature = |name: &str| sysctl::read_u64(name).is_some_and(|v| v != 0);
+        CryptoExtensions {
+            aes: feature("hw.optional.arm.FEAT_AES"),
+            pmull: feature("hw.optional.arm.FEAT_PMULL"),
+            sha2: feature("hw.optional.arm.FEAT_SHA256"),
+            sha3: feature("hw.optional.arm.FEAT_SHA3"),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        CryptoExtensions {
+            aes: hwcaps.aes,
+            pmull: hwcaps.pmull,
+            sha2: hwcaps.sha2,
+            sha3: hwcaps.sha3,
+        }
     }
 }
 
 /// Detect NEON SIMD support
-fn detect_neon() -> bool {
-    cfg!(target_arch = "aarch64")
+///
+/// Linux and Windows report it at runtime; on macOS every aarch64 CPU has it.
+fn detect_neon(hwcaps: &hwcap::HwcapFeatures) -> bool {
+    if cfg!(all(
+        target_arch = "aarch64",
+        any(target_os = "linux", windows)
+    )) {
+        hwcaps.neon
+    } else {
+        cfg!(target_arch = "aarch64")
+    }
 }
 
 /// Detect AMX support (Apple Silicon via Accelerate framework)
@@ -58,81 +444,20 @@ fn detect_amx() -> bool {
     }
 }
 
-/// Detect SME support (M4+)
-fn detect_sme() -> bool {
+/// Detect SME support from the `hw.optional.arm` sysctls (M4+)
+fn detect_sme() -> sme::SmeFeatures {
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     {
-        // SME detection requires runtime checks
-        // This is a conservative default - actual detection
-        // would require sysctl calls
-        detect_sme_runtime()
+        sme::read()
     }
     #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
     {
-        false
+        sme::SmeFeatures::default()
     }
 }
 
-/// Runtime SME detection for macOS
-#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-fn detect_sme_runtime() -> bool {
-    use std::ffi::CStr;
-    use std::os::raw::{c_char, c_int, c_void};
-    
-    extern "C" {
-        fn sysctlbyname(
-            name: *const c_char,
-            oldp: *mut c_void,
-            oldlenp: *mut usize,
-            newp: *mut c_void,
-            newlen: usize,
-        ) -> c_int;
-    }
-    
-    let name = b"hw.optional.arm.FEAT_SME\0";
-    let mut value: i64 = 0;
-    let mut size = std::mem::size_of::<i64>();
-    
-    unsafe {
-        let result = sysctlbyname(
-            name.as_ptr() as *const c_char,
-            &mut value as *mut i64 as *mut c_void,
-            &mut size,
-            std::ptr::null_mut(),
-            0,
-        );
-        
-        if result == 0 {
-            return value != 0;
-        }
-    }
-    
-    // Fallback: check CPU brand string for M4
-    let brand_name = b"machdep.cpu.brand_string\0";
-    let mut brand: [u8; 256] = [0; 256];
-    let mut brand_size = 256usize;
-    
-    unsafe {
-        let result = sysctlbyname(
-            brand_name.as_ptr() as *const c_char,
-            brand.as_mut_ptr() as *mut c_void,
-            &mut brand_size,
-            std::ptr::null_mut(),
-            0,
-        );
-        
-        if result == 0 {
-            if let Ok(brand_str) = CStr::from_ptr(brand.as_ptr() as *const c_char).to_str() {
-                return brand_str.contains("M4");
-            }
-        }
-    }
-    
-    false
-}
-
 /// Get CPU core count
-fn get_cpu_count() -> u32 {
+pub(crate) fn get_cpu_count() -> u32 {
     std::thread::available_parallelism()
         .map(|p| p.get() as u32)
         .unwrap_or(1)
@@ -141,23 +466,37 @@ fn get_cpu_count() -> u32 {
 /// Get target architecture string
 fn get_arch() -> String {
     #[cfg(target_arch = "aarch64")]
-    { "aarch64".to_string() }
+    {
+        "aarch64".to_string()
+    }
     #[cfg(target_arch = "x86_64")]
-    { "x86_64".to_string() }
+    {
+        "x86_64".to_string()
+    }
     #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
-    { "unknown".to_string() }
+    {
+        "unknown".to_string()
+    }
 }
 
 /// Get target OS string
 fn get_os() -> String {
     #[cfg(target_os = "macos")]
-    { "macos".to_string() }
+    {
+        "macos".to_string()
+    }
     #[cfg(target_os = "linux")]
-    { "linux".to_string() }
+    {
+        "linux".to_string()
+    }
     #[cfg(target_os = "windows")]
-    { "windows".to_string() }
+    {
+        "windows".to_string()
+    }
     #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
-    { "unknown".to_string() }
+    {
+        "unknown".to_string()
+    }
 }
 
 /// Get the Rust component version
@@ -173,8 +512,8 @@ pub fn is_apple_silicon() -> bool {
 }
 
 /// Native binding status information
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NativeBindingStatus {
     /// Whether the Rust binding is loaded
     pub rust_loaded: bool,
@@ -184,6 +523,9 @@ pub struct NativeBindingStatus {
     pub apple_silicon: bool,
     /// Hardware capabilities
     pub capabilities: RustHardwareCapabilities,
+    /// Resident set size of the process in bytes (a `bigint` in JavaScript),
+    /// 0 if unknown
+    pub memory_usage_bytes: u64,
 }
 
 /// Get the status of native bindings
@@ -194,9 +536,82 @@ pub fn get_binding_status() -> NativeBindingStatus {
         rust_version: rust_version(),
         apple_silicon: is_apple_silicon(),
         capabilities: detect_rust_capabilities(),
+        memory_usage_bytes: memory::get_memory_usage(),
     }
 }
 
+/// Version of the `binding_status_json()` layout
+///
+/// Bump whenever a field is added to, renamed in or removed from
+/// `NativeBindingStatus` or `RustHardwareCapabilities`.
+pub const BINDING_STATUS_SCHEMA_VERSION: u32 = 5;
+
+/// Cargo features this binary was compiled with
+pub fn compiled_features() -> Vec<String> {
+    env!("ZK_ACCELERATE_FEATURES")
+        .split(',')
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// How this binary was built, for bug reports
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BuildInfo {
+    /// Enabled cargo features, sorted
+    pub features: Vec<String>,
+    /// `rustc --version` of the compiler, empty if it could not be run
+    pub rustc_version: String,
+    /// Target triple, e.g. "aarch64-apple-darwin"
+    pub target: String,
+    /// Cargo profile, "debug" or "release"
+    pub profile: String,
+    /// Commit the binary was built from, if built from a git checkout
+    pub git_commit: Option<String>,
+    /// Build time in seconds since the Unix epoch (`SOURCE_DATE_EPOCH` if set)
+    pub build_timestamp: i64,
+}
+
+/// Compiled features, compiler, target, profile, commit and build time
+#[napi]
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        features: compiled_features(),
+        rustc_version: env!("ZK_ACCELERATE_RUSTC_VERSION").to_string(),
+        target: env!("ZK_ACCELERATE_TARGET").to_string(),
+        profile: env!("ZK_ACCELERATE_PROFILE").to_string(),
+        git_commit: option_env!("ZK_ACCELERATE_GIT_COMMIT").map(str::to_string),
+        build_timestamp: env!("ZK_ACCELERATE_BUILD_TIMESTAMP").parse().unwrap_or(0),
+    }
+}
+
+/// `binding_status_json()` payload; field names are part of the schema
+#[derive(Serialize)]
+struct BindingStatusExport {
+    schema_version: u32,
+    features: Vec<String>,
+    #[serde(flatten)]
+    status: NativeBindingStatus,
+}
+
+/// `get_binding_status()` as a versioned JSON document
+///
+/// Top-level keys are `schema_version`, `features` (compiled cargo
+/// features) and the `NativeBindingStatus` fields; keys keep their
+/// snake_case Rust names, so the shape only changes together with
+/// `schema_version`.
+#[napi]
+pub fn binding_status_json() -> Result<String> {
+    let export = BindingStatusExport {
+        schema_version: BINDING_STATUS_SCHEMA_VERSION,
+        features: compiled_features(),
+        status: get_binding_status(),
+    };
+    serde_json::to_string(&export)
+        .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,16 +620,268 @@ mod tests {
     fn test_detect_capabilities() {
         let caps = detect_rust_capabilities();
         assert!(caps.cpu_cores >= 1);
+        assert!(caps.physical_cores >= 1 && caps.physical_cores <= caps.cpu_cores);
+        assert!(caps.performance_cores >= 1);
+        assert!(caps.performance_cores + caps.efficiency_cores <= caps.cpu_cores * 2);
         assert!(!caps.arch.is_empty());
         assert!(!caps.os.is_empty());
     }
 
+    #[test]
+    fn test_x86_features_coherent() {
+        let caps = detect_rust_capabilities();
+        if caps.has_avx512f {
+            assert!(caps.has_avx2);
+        }
+        if caps.has_avx512ifma || caps.has_avx512bw || caps.has_avx512vbmi {
+            assert!(caps.has_avx512f);
+        }
+        if !cfg!(target_arch = "x86_64") {
+            assert!(!caps.has_avx2 && !caps.has_avx512f && !caps.has_avx512ifma && !caps.has_bmi2);
+            assert!(!caps.has_avx512bw && !caps.has_avx512vbmi);
+        }
+    }
+
+    #[test]
+    fn test_sve_fields_coherent() {
+        let caps = detect_rust_capabilities();
+        if caps.has_sve2 {
+            assert!(caps.has_sve);
+        }
+        if !caps.has_sve {
+            assert_eq!(caps.sve_vector_length_bits, 0);
+        }
+        if caps.has_sve {
+            assert!(caps.has_neon);
+        }
+        if !cfg!(target_arch = "aarch64") {
+            assert!(!caps.has_neon && !caps.has_sve && !caps.has_sve2);
+        }
+    }
+
+    #[test]
+    fn test_sve_vector_length_mocked() {
+        let neoverse_v2 = hwcap::decode(hwcap::HWCAP_ASIMD | hwcap::HWCAP_SVE, hwcap::HWCAP2_SVE2);
+        assert_eq!(
+            sve_vector_length(&neoverse_v2, || hwcap::decode_sve_vl(16)),
+            128
+        );
+        let neoverse_n1 = hwcap::decode(hwcap::HWCAP_ASIMD, 0);
+        assert_eq!(
+            sve_vector_length(&neoverse_n1, || panic!("VL queried without SVE")),
+            0
+        );
+        let caps = detect_rust_capabilities();
+        assert_eq!(sve_vector_length_bits(), caps.sve_vector_length_bits);
+        if cfg!(target_os = "macos") {
+            assert!(!caps.has_sve && !caps.has_sve2);
+            assert_eq!(caps.sve_vector_length_bits, 0);
+        }
+        if caps.sve_vector_length_bits != 0 {
+            // Architecturally a multiple of 128 between 128 and 2048
+            assert!(caps.sve_vector_length_bits.is_multiple_of(128));
+            assert!((128..=2048).contains(&caps.sve_vector_length_bits));
+        }
+    }
+
+    #[test]
+    fn test_sme_fields_coherent() {
+        let caps = detect_rust_capabilities();
+        assert_eq!(caps.has_sme, caps.sme_version > 0);
+        assert!(caps.sme_version <= 2);
+        if caps.sme_version == 0 {
+            assert_eq!(caps.sme_streaming_vector_length_bits, 0);
+        }
+        if !cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            assert!(!caps.has_sme);
+        }
+    }
+
+    #[test]
+    fn test_crypto_extensions_coherent() {
+        let caps = detect_rust_capabilities();
+        assert_eq!(caps.has_aes_pmull, caps.has_aes && caps.has_pmull);
+        if !cfg!(target_arch = "aarch64") {
+            assert!(!caps.has_aes && !caps.has_pmull && !caps.has_sha2 && !caps.has_sha3);
+        }
+        if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            // Every Apple Silicon generation implements all four
+            assert!(caps.has_aes && caps.has_pmull && caps.has_sha2 && caps.has_sha3);
+        }
+        let status = get_binding_status();
+        assert_eq!(caps.has_arm_sha3, caps.has_sha3);
+        assert_eq!(status.capabilities.has_sha3, caps.has_sha3);
+    }
+
+    #[test]
+    fn test_env_overrides_clamp() {
+        use overrides::{Overrides, DISABLE_NEON, DISABLE_SME};
+
+        let mut caps = detect_rust_capabilities();
+        caps.has_sme = true;
+        caps.sme_version = 2;
+        caps.sme_streaming_vector_length_bits = 512;
+        caps.has_amx = true;
+        caps.has_neon = true;
+        let forced = Overrides::from_lookup(|name| {
+            [DISABLE_SME, DISABLE_NEON]
+                .contains(&name)
+                .then(|| "1".to_string())
+        });
+        apply_overrides(&mut caps, forced);
+        assert!(!caps.has_sme && !caps.has_neon);
+        assert_eq!(caps.sme_version, 0);
+        assert_eq!(caps.sme_streaming_vector_length_bits, 0);
+        assert!(caps.has_amx);
+        assert!(caps.overridden);
+
+        apply_overrides(&mut caps, Overrides::default());
+        assert!(!caps.overridden);
+        assert_eq!(
+            detect_rust_capabilities().overridden,
+            overrides::get().any()
+        );
+    }
+
+    #[test]
+    fn test_capabilities_cached() {
+        let first = detect_rust_capabilities();
+        let second = detect_rust_capabilities();
+        assert_eq!(format!("{first:?}"), format!("{second:?}"));
+
+        let refreshed = refresh_capabilities();
+        assert_eq!(refreshed.cpu_cores, first.cpu_cores);
+        assert_eq!(refreshed.cpu_model, first.cpu_model);
+        assert_eq!(
+            format!("{refreshed:?}"),
+            format!("{:?}", get_binding_status().capabilities)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_async_detection_matches_sync() {
+        let from_task = DetectCapabilitiesTask.compute().unwrap();
+        assert_eq!(
+            format!("{from_task:?}"),
+            format!("{:?}", detect_rust_capabilities())
+        );
+    }
+
+    #[test]
+    fn test_chip_fields_coherent() {
+        let caps = detect_rust_capabilities();
+        if !caps.chip_variant.is_empty() {
+            assert!(!caps.chip_family.is_empty());
+        }
+        if !caps.chip_family.is_empty() {
+            assert!(caps.cpu_model.starts_with("Apple M"));
+        }
+        if !cfg!(target_os = "macos") {
+            assert!(caps.chip_family.is_empty());
+            assert_eq!(caps.gpu_core_count, 0);
+            assert!(!caps.has_neural_engine);
+        }
+    }
+
     #[test]
     fn test_rust_version() {
         let version = rust_version();
         assert!(!version.is_empty());
     }
 
+    #[test]
+    fn test_binding_status_json_schema() {
+        let json: serde_json::Value =
+            serde_json::from_str(&binding_status_json().unwrap()).unwrap();
+        assert_eq!(json["schema_version"], BINDING_STATUS_SCHEMA_VERSION);
+        assert_eq!(json["rust_loaded"], true);
+        assert_eq!(json["rust_version"], rust_version());
+        assert!(json["features"].is_array());
+
+        // Schema 5 key snapshot: adding or renaming a field must bump the version
+        let keys = |v: &serde_json::Value| {
+            let mut keys: Vec<String> = v.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(
+            keys(&json),
+            [
+                "apple_silicon",
+                "capabilities",
+                "features",
+                "memory_usage_bytes",
+                "rust_loaded",
+                "rust_version",
+                "schema_version"
+            ]
+        );
+        let capability_keys = keys(&json["capabilities"]);
+        for required in [
+            "has_neon",
+            "has_amx",
+            "has_sme",
+            "sme_version",
+            "has_metal",
+            "has_avx2",
+            "has_avx512f",
+            "has_sve",
+            "has_sha3",
+            "has_arm_sha3",
+            "cpu_cores",
+            "physical_cores",
+            "performance_cores",
+            "efficiency_cores",
+            "total_memory_bytes",
+            "cpu_model",
+            "chip_family",
+            "chip_variant",
+            "gpu_core_count",
+            "has_neural_engine",
+            "arch",
+            "os",
+            "overridden",
+        ] {
+            assert!(
+                capability_keys.iter().any(|k| k == required),
+                "missing {required}"
+            );
+        }
+        assert_eq!(capability_keys.len(), 37);
+    }
+
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+        // Triples are arch-vendor-os[-env]; Apple's OS is "darwin"
+        let os = match std::env::consts::OS {
+            "macos" => "darwin",
+            os => os,
+        };
+        assert!(
+            info.target.starts_with(std::env::consts::ARCH),
+            "{}",
+            info.target
+        );
+        assert!(info.target.contains(os), "{}", info.target);
+        assert_eq!(
+            info.profile,
+            if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            }
+        );
+        assert!(info.rustc_version.starts_with("rustc "));
+        assert!(info.build_timestamp > 0);
+        assert_eq!(info.features, compiled_features());
+        assert_eq!(
+            info.features.iter().any(|f| f == "metal"),
+            cfg!(feature = "metal")
+        );
+    }
+
     #[test]
     fn test_binding_status() {
         let status = get_binding_status();
@@ -0,0 +1,427 @@
+//! Mersenne31 field `p = 2^31 - 1` batch arithmetic
+//!
+//! Elements cross the NAPI boundary as `Uint32Array`s. Inputs may be any
+//! value below `2^31`, so zero can arrive as either `0` or `p`; every result
+//! is canonical in `[0, p)`, so the two encodings of zero never reach the
+//! caller. Reduction uses `2^31 ≡ 1`: fold the high bits onto the low ones.
+//!
+//! On aarch64 add, sub and mul run four lanes per NEON instruction (see
+//! [`crate::neon::U32x4`]). [`m31_cfft_twiddles`] provides the circle group
+//! twiddles a circle FFT over this field needs.
+
+use napi::bindgen_prelude::Uint32Array;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::neon::U32x4;
+
+/// The Mersenne prime `2^31 - 1`
+pub const MODULUS: u32 = 0x7fff_ffff;
+
+/// Generator of the circle group `x^2 + y^2 = 1`, of order `2^31`
+const CIRCLE_GENERATOR: CirclePoint = CirclePoint {
+    x: 2,
+    y: 1_268_011_823,
+};
+
+/// Largest circle FFT size: the domain needs a point of order `2n <= 2^31`
+pub const MAX_CFFT_LOG_SIZE: u32 = 30;
+
+/// Below this many elements the batch functions run on the calling thread
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// Map `p`, the second encoding of zero, to `0`; `x` must be below `2^31`
+#[inline(always)]
+pub fn canonical(x: u32) -> u32 {
+    if x == MODULUS {
+        0
+    } else {
+        x
+    }
+}
+
+/// Reduce a value below `2^32` to `[0, p]` by folding bit 31 onto bit 0
+#[inline(always)]
+fn fold(x: u32) -> u32 {
+    (x & MODULUS) + (x >> 31)
+}
+
+/// `a + b mod p`
+#[inline]
+pub fn add(a: u32, b: u32) -> u32 {
+    canonical(fold(canonical(a) + canonical(b)))
+}
+
+/// `a - b mod p`
+#[inline]
+pub fn sub(a: u32, b: u32) -> u32 {
+    canonical(fold(canonical(a) + (MODULUS - canonical(b))))
+}
+
+/// `a * b mod p`
+#[inline]
+pub fn mul(a: u32, b: u32) -> u32 {
+    let t = canonical(a) as u64 * canonical(b) as u64;
+    // t < 2^62, so the high part is below 2^31 and the sum below 2^32
+    let r = (t as u32 & MODULUS) + (t >> 31) as u32;
+    canonical(fold(r))
+}
+
+/// `a^exp mod p`
+pub fn pow(a: u32, mut exp: u32) -> u32 {
+    let (mut base, mut acc) = (canonical(a), 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul(acc, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+/// Multiplicative inverse `a^(p-2)`, `None` for either encoding of zero
+pub fn inverse(a: u32) -> Option<u32> {
+    (canonical(a) != 0).then(|| pow(a, MODULUS - 2))
+}
+
+/// Four-lane `canonical` for lanes in `[0, p]`: `p - p` is the smaller one
+#[inline(always)]
+fn canonical_x4(x: U32x4) -> U32x4 {
+    x.min(x.wrapping_sub(U32x4::splat(MODULUS)))
+}
+
+#[inline(always)]
+fn add_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let sum = canonical_x4(a).wrapping_add(canonical_x4(b));
+    // sum <= 2p - 2, so one conditional subtraction lands in [0, p)
+    canonical_x4(sum)
+}
+
+#[inline(always)]
+fn sub_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let diff = canonical_x4(a).wrapping_sub(canonical_x4(b));
+    // After a borrow diff + p wraps back below p, otherwise it is larger
+    diff.min(diff.wrapping_add(U32x4::splat(MODULUS)))
+}
+
+#[inline(always)]
+fn mul_x4(a: U32x4, b: U32x4) -> U32x4 {
+    let (a, b) = (canonical_x4(a), canonical_x4(b));
+    let (lo, hi) = (a.mul_lo(b), a.mul_hi(b));
+    // t >> 31 = (hi << 1) + (lo >> 31); the product is below 2^62, so both
+    // parts stay below 2^31 and their sum below 2p
+    let high = hi.shift_left(1).wrapping_add(lo.shift_right(31));
+    canonical_x4(high.wrapping_add(lo.and(U32x4::splat(MODULUS))))
+}
+
+/// `out[i] = f(a[i], b[i])`, four lanes at a time when `neon` is set
+fn map_quads(
+    a: &[u32],
+    b: &[u32],
+    neon: bool,
+    scalar: fn(u32, u32) -> u32,
+    vector: fn(U32x4, U32x4) -> U32x4,
+) -> Vec<u32> {
+    debug_assert_eq!(a.len(), b.len());
+    let mut out = vec![0u32; a.len()];
+    crate::parallel::par_chunks_mut(&mut out, 4, PARALLEL_THRESHOLD, |offset, chunk| {
+        let (a, b) = (&a[offset..], &b[offset..]);
+        let quads = if neon { chunk.len() & !3 } else { 0 };
+        for (q, o) in chunk[..quads].chunks_exact_mut(4).enumerate() {
+            let i = 4 * q;
+            let x = U32x4::new(a[i..i + 4].try_into().unwrap());
+            let y = U32x4::new(b[i..i + 4].try_into().unwrap());
+            o.copy_from_slice(&vector(x, y).lanes());
+        }
+        for (i, o) in chunk.iter_mut().enumerate().skip(quads) {
+            *o = scalar(a[i], b[i]);
+        }
+    });
+    out
+}
+
+/// Element-wise `a + b`
+pub fn add_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, b, crate::neon::enabled(), add, add_x4)
+}
+
+/// Element-wise `a - b`
+pub fn sub_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, b, crate::neon::enabled(), sub, sub_x4)
+}
+
+/// Element-wise `a * b`
+pub fn mul_batch(a: &[u32], b: &[u32]) -> Vec<u32> {
+    map_quads(a, b, crate::neon::enabled(), mul, mul_x4)
+}
+
+/// Invert every element with Montgomery's trick; zeros stay zero
+pub fn inv_batch(values: &[u32]) -> Vec<u32> {
+    let mut out: Vec<u32> = values.iter().map(|&v| canonical(v)).collect();
+    crate::parallel::par_chunks_mut(&mut out, 1, PARALLEL_THRESHOLD, |_, chunk| {
+        // prefix[i] is the product of the non-zero elements before i
+        let mut prefix = Vec::with_capacity(chunk.len());
+        let mut acc = 1;
+        for &v in chunk.iter() {
+            prefix.push(acc);
+            if v != 0 {
+                acc = mul(acc, v);
+            }
+        }
+        let mut inv = inverse(acc).expect("a product of non-zero elements is non-zero");
+        for (v, p) in chunk.iter_mut().zip(prefix).rev() {
+            if *v == 0 {
+                continue;
+            }
+            let next = mul(inv, *v);
+            *v = mul(inv, p);
+            inv = next;
+        }
+    });
+    out
+}
+
+/// Point on the circle `x^2 + y^2 = 1` over the field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CirclePoint {
+    x: u32,
+    y: u32,
+}
+
+impl CirclePoint {
+    /// Group law `(x1 x2 - y1 y2, x1 y2 + x2 y1)`
+    fn mul(self, rhs: Self) -> Self {
+        CirclePoint {
+            x: sub(mul(self.x, rhs.x), mul(self.y, rhs.y)),
+            y: add(mul(self.x, rhs.y), mul(rhs.x, self.y)),
+        }
+    }
+
+    /// Generator of the subgroup of order `2^log_order`
+    fn subgroup_generator(log_order: u32) -> Self {
+        let mut g = CIRCLE_GENERATOR;
+        for _ in log_order..31 {
+            g = g.mul(g);
+        }
+        g
+    }
+}
+
+/// Twiddles for a circle FFT over `2^log_n` points, one layer per butterfly
+/// round
+///
+/// The domain is the coset `P_i = G_2n · G_n^i`, where `G_k` generates the
+/// circle subgroup of order `k`. Layer 0 holds `y(P_i)` for `i < n/2`, the
+/// twiddles of the first round that pairs each point with its conjugate.
+/// Layer `k >= 1` holds `x(2^(k-1) P_i)` for `i < n/2^(k+1)`, the
+/// x-coordinates the later rounds fold with the doubling map `2x^2 - 1`.
+/// All values are canonical.
+#[napi]
+pub fn m31_cfft_twiddles(log_n: u32) -> Result<Vec<Vec<u32>>> {
+    if log_n > MAX_CFFT_LOG_SIZE {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("circle FFT size 2^{log_n} exceeds the maximum of 2^{MAX_CFFT_LOG_SIZE}"),
+        ));
+    }
+    if log_n == 0 {
+        return Ok(Vec::new());
+    }
+    let n = 1usize << log_n;
+    let step = CirclePoint::subgroup_generator(log_n);
+    let mut point = CirclePoint::subgroup_generator(log_n + 1);
+    let mut points = Vec::with_capacity(n / 2);
+    for _ in 0..n / 2 {
+        points.push(point);
+        point = point.mul(step);
+    }
+
+    let mut layers = vec![points.iter().map(|p| p.y).collect::<Vec<_>>()];
+    let mut xs: Vec<u32> = points.iter().map(|p| p.x).collect();
+    for _ in 1..log_n {
+        xs.truncate(xs.len() / 2);
+        layers.push(xs.clone());
+        for x in xs.iter_mut() {
+            *x = sub(mul(2, mul(*x, *x)), 1);
+        }
+    }
+    Ok(layers)
+}
+
+/// Reject the first element that is not below `2^31`
+fn check_range(values: &[u32], name: &str) -> Result<()> {
+    match values.iter().position(|&v| v > MODULUS) {
+        Some(i) => Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}[{i}]: value {} does not fit in 31 bits", values[i]),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_pair(a: &[u32], b: &[u32]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a and b must have the same length, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ));
+    }
+    check_range(a, "a")?;
+    check_range(b, "b")
+}
+
+/// Element-wise Mersenne31 addition; results are canonical
+#[napi]
+pub fn m31_add_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(add_batch(&a, &b).into())
+}
+
+/// Element-wise Mersenne31 subtraction `a - b`
+#[napi]
+pub fn m31_sub_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(sub_batch(&a, &b).into())
+}
+
+/// Element-wise Mersenne31 multiplication
+#[napi]
+pub fn m31_mul_batch(a: Uint32Array, b: Uint32Array) -> Result<Uint32Array> {
+    check_pair(&a, &b)?;
+    Ok(mul_batch(&a, &b).into())
+}
+
+/// Invert every element with a single exponentiation; zeros map to zero
+#[napi]
+pub fn m31_inv_batch(values: Uint32Array) -> Result<Uint32Array> {
+    crate::constant_time::require_variable_time("m31_inv_batch")?;
+    check_range(&values, "values")?;
+    Ok(inv_batch(&values).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: u64 = MODULUS as u64;
+
+    fn samples() -> Vec<u32> {
+        let mut values = vec![0, 1, 2, 1 << 30, MODULUS - 2, MODULUS - 1, MODULUS];
+        let mut x = 0x2545_f491u32;
+        for _ in 0..57 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            values.push(x & MODULUS);
+        }
+        values
+    }
+
+    #[test]
+    fn test_scalar_matches_u64() {
+        for &a in &samples() {
+            for &b in &samples() {
+                let (x, y) = (a as u64 % P, b as u64 % P);
+                assert_eq!(add(a, b) as u64, (x + y) % P, "{a} + {b}");
+                assert_eq!(sub(a, b) as u64, (x + P - y) % P, "{a} - {b}");
+                assert_eq!(mul(a, b) as u64, x * y % P, "{a} * {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_encodings() {
+        // p and 0 both mean zero and always come back as 0
+        assert_eq!(add(MODULUS, 0), 0);
+        assert_eq!(add(MODULUS, MODULUS), 0);
+        assert_eq!(sub(MODULUS, 0), 0);
+        assert_eq!(sub(0, MODULUS), 0);
+        assert_eq!(mul(MODULUS, 5), 0);
+        assert_eq!(add(MODULUS - 1, 1), 0);
+        assert_eq!(sub(0, 1), MODULUS - 1);
+        // (p - 1)^2 = 1, and (p - 1) (p - 1) wraps around the full product range
+        assert_eq!(mul(MODULUS - 1, MODULUS - 1), 1);
+        assert_eq!(mul(MODULUS - 1, 2), MODULUS - 2);
+        assert_eq!(
+            inv_batch(&[MODULUS, 0, 1, MODULUS - 1]),
+            [0, 0, 1, MODULUS - 1]
+        );
+        assert_eq!(inverse(MODULUS), None);
+
+        let zeros = [0, MODULUS, MODULUS, 0];
+        let ones = [1, 1, 1, 1];
+        for neon in [false, true] {
+            assert_eq!(map_quads(&zeros, &zeros, neon, add, add_x4), [0; 4]);
+            assert_eq!(map_quads(&zeros, &zeros, neon, sub, sub_x4), [0; 4]);
+            assert_eq!(map_quads(&zeros, &ones, neon, mul, mul_x4), [0; 4]);
+            assert_eq!(map_quads(&ones, &zeros, neon, mul, mul_x4), [0; 4]);
+        }
+        let err = check_pair(&[0, 1 << 31], &[0, 0]).unwrap_err();
+        assert_eq!(err.reason, "a[1]: value 2147483648 does not fit in 31 bits");
+    }
+
+    #[test]
+    fn test_vector_matches_scalar() {
+        let values = samples();
+        let (a, b): (Vec<u32>, Vec<u32>) = values
+            .iter()
+            .flat_map(|&x| values.iter().map(move |&y| (x, y)))
+            .unzip();
+        // Three extra elements leave a scalar remainder after the quads
+        let (a, b) = (&a[..a.len() - 1], &b[..b.len() - 1]);
+        type Op = (fn(u32, u32) -> u32, fn(U32x4, U32x4) -> U32x4);
+        let ops: [Op; 3] = [(add, add_x4), (sub, sub_x4), (mul, mul_x4)];
+        for (scalar, vector) in ops {
+            let expected = map_quads(a, b, false, scalar, vector);
+            assert_eq!(map_quads(a, b, true, scalar, vector), expected);
+        }
+        let n = 2 * PARALLEL_THRESHOLD + 3;
+        let big: Vec<u32> = (0..n as u32).map(|i| MODULUS - i).collect();
+        let inverses = inv_batch(&big);
+        let products = mul_batch(&big, &inverses);
+        assert_eq!(products[0], 0);
+        assert!(products[1..].iter().all(|&v| v == 1));
+    }
+
+    #[test]
+    fn test_cfft_twiddles() {
+        assert!(m31_cfft_twiddles(0).unwrap().is_empty());
+        assert!(m31_cfft_twiddles(MAX_CFFT_LOG_SIZE + 1).is_err());
+        // The domain of two points is {G_4, G_4^-1} = {(0, y), (0, -y)}
+        let two = m31_cfft_twiddles(1).unwrap();
+        assert_eq!(two.len(), 1);
+        assert!(two[0] == [1] || two[0] == [MODULUS - 1]);
+
+        let log_n = 6;
+        let layers = m31_cfft_twiddles(log_n).unwrap();
+        let sizes: Vec<usize> = layers.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [32, 16, 8, 4, 2, 1]);
+        // Each layer 0 y comes with the layer 1 x of the same point
+        for (i, &x) in layers[1].iter().enumerate() {
+            let y = layers[0][i];
+            assert_eq!(add(mul(x, x), mul(y, y)), 1);
+        }
+        // Later layers are the doubling map applied to the previous one
+        for k in 2..log_n as usize {
+            for (i, &x) in layers[k].iter().enumerate() {
+                let prev = layers[k - 1][i];
+                assert_eq!(x, sub(mul(2, mul(prev, prev)), 1));
+            }
+        }
+        // The generator has order exactly 2^31
+        let half = CirclePoint::subgroup_generator(1);
+        assert_eq!(
+            half,
+            CirclePoint {
+                x: MODULUS - 1,
+                y: 0
+            }
+        );
+        assert_eq!(half.mul(half), CirclePoint { x: 1, y: 0 });
+    }
+}
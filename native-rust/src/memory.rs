@@ -0,0 +1,212 @@
+//! Process memory usage and Rust heap accounting
+//!
+//! `get_memory_usage()` reports the resident set size of the whole process,
+//! V8 heap included: `task_info(TASK_VM_INFO)` on macOS, `VmRSS` from
+//! `/proc/self/status` on Linux and `GetProcessMemoryInfo` on Windows.
+//! `global_allocator_stats()` counts only allocations made through Rust's
+//! global allocator, which this module wraps to keep running totals.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use napi_derive::napi;
+
+/// Bytes currently allocated through the Rust global allocator
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+/// Highest value `ALLOCATED` has reached
+static PEAK: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting live bytes and the high-water mark
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn grew(size: usize) {
+        let now = ALLOCATED.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+        PEAK.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn shrank(size: usize) {
+        ALLOCATED.fetch_sub(size as u64, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: every call is forwarded unchanged to `System`; the counters are
+// only updated after a successful allocation
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrank(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            if new_size >= layout.size() {
+                Self::grew(new_size - layout.size());
+            } else {
+                Self::shrank(layout.size() - new_size);
+            }
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+/// Rust heap usage, separate from the V8 heap
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocatorStats {
+    /// Bytes currently allocated
+    pub bytes_allocated: u64,
+    /// Most bytes allocated at once since the module was loaded
+    pub peak_bytes: u64,
+}
+
+/// Current and peak bytes allocated through the Rust global allocator
+#[napi]
+pub fn global_allocator_stats() -> AllocatorStats {
+    let bytes_allocated = ALLOCATED.load(Ordering::Relaxed);
+    // `grew` raises `PEAK` just after `ALLOCATED`, so it can briefly lag
+    AllocatorStats {
+        bytes_allocated,
+        peak_bytes: PEAK.load(Ordering::Relaxed).max(bytes_allocated),
+    }
+}
+
+/// Resident set size of the process in bytes, 0 if unknown
+#[napi]
+pub fn get_memory_usage() -> u64 {
+    resident_bytes().unwrap_or(0)
+}
+
+#[cfg(target_os = "macos")]
+fn resident_bytes() -> Option<u64> {
+    mach::task_resident_size()
+}
+
+#[cfg(target_os = "linux")]
+fn resident_bytes() -> Option<u64> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+#[cfg(windows)]
+fn resident_bytes() -> Option<u64> {
+    crate::win32::process_working_set_bytes()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn resident_bytes() -> Option<u64> {
+    None
+}
+
+/// The `VmRSS` line of `/proc/self/status` text, in bytes
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb = line["VmRSS:".len()..].trim().strip_suffix("kB")?;
+    kb.trim().parse::<u64>().ok().map(|kb| kb * 1024)
+}
+
+#[cfg(target_os = "macos")]
+mod mach {
+    /// `TASK_VM_INFO`
+    const TASK_VM_INFO: u32 = 22;
+
+    /// Revision 0 of `task_vm_info_data_t`, declared `#pragma pack(4)`
+    #[repr(C, packed(4))]
+    #[derive(Default)]
+    struct TaskVmInfo {
+        virtual_size: u64,
+        region_count: i32,
+        page_size: i32,
+        resident_size: u64,
+        /// `resident_size_peak` through `compressed_lifetime`
+        rest: [u64; 15],
+    }
+
+    extern "C" {
+        static mach_task_self_: u32;
+        fn task_info(task: u32, flavor: u32, info: *mut i32, count: *mut u32) -> i32;
+    }
+
+    /// `resident_size` of the calling task
+    pub fn task_resident_size() -> Option<u64> {
+        let mut info = TaskVmInfo::default();
+        let mut count = (std::mem::size_of::<TaskVmInfo>() / 4) as u32;
+        // SAFETY: `count` is the size of `info` in natural_t units, and the
+        // kernel writes at most that many
+        let status = unsafe {
+            task_info(
+                mach_task_self_,
+                TASK_VM_INFO,
+                (&mut info as *mut TaskVmInfo).cast(),
+                &mut count,
+            )
+        };
+        (status == 0).then_some(info.resident_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::Fr;
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\tnode\nVmPeak:\t  812344 kB\nVmRSS:\t   52340 kB\nThreads:\t11\n";
+        assert_eq!(parse_vm_rss(status), Some(52340 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tnode\n"), None);
+    }
+
+    #[test]
+    fn test_stats_cover_live_allocation() {
+        const LEN: usize = 1 << 20;
+        // Live blocks are never subtracted, whatever other tests free
+        let held = vec![1u8; LEN];
+        let stats = global_allocator_stats();
+        assert!(stats.bytes_allocated >= LEN as u64);
+        assert!(stats.peak_bytes >= stats.bytes_allocated);
+        assert_eq!(held[LEN - 1], 1);
+    }
+
+    /// Reads process-wide counters, so it needs the test binary to itself:
+    /// `cargo test test_usage_grows_with_ntt_domain -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn test_usage_grows_with_ntt_domain() {
+        const LOG_N: u32 = 20;
+        let bytes = (1u64 << LOG_N) * std::mem::size_of::<Fr>() as u64;
+        let heap_before = global_allocator_stats();
+        let rss_before = get_memory_usage();
+
+        let mut domain: Vec<Fr> = (0..1u64 << LOG_N).map(Fr::from_u64).collect();
+        crate::ntt::ntt_in_place(&mut domain, false).unwrap();
+
+        let heap = global_allocator_stats();
+        assert!(heap.bytes_allocated >= heap_before.bytes_allocated + bytes / 2);
+        assert!(heap.peak_bytes >= heap.bytes_allocated);
+        if cfg!(any(target_os = "macos", target_os = "linux", windows)) {
+            assert!(get_memory_usage() >= rss_before + bytes / 2);
+        }
+        assert!(!domain[1].is_zero());
+    }
+}
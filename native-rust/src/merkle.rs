@@ -0,0 +1,763 @@
+//! Incremental (append-only) and sparse binary Merkle trees with Poseidon
+//! hashing
+//!
+//! Nodes are hashed with circomlib's two-input Poseidon (`t = 3`), the
+//! `poseidon2` used by Semaphore and `@zk-kit/incremental-merkle-tree`, and
+//! empty leaves are zero, so roots match a Semaphore group of the same depth.
+//!
+//! Only nodes that cover at least one inserted leaf are stored; missing
+//! siblings are the roots of empty subtrees, computed on first use and shared
+//! by every tree.
+//!
+//! [`SparseMerkleTree`] is a key-value map over 256-bit keys: the key's bits,
+//! most significant first, are the path from the root, so every key has its
+//! own leaf and a proof for an absent key shows that leaf is empty.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::bn254::{parse_fr, Fr, FR_BYTES};
+use crate::poseidon;
+
+/// Largest supported tree depth (Semaphore allows 16 to 32)
+pub const MAX_DEPTH: u32 = 32;
+
+/// Roots of empty subtrees by height: `zeros[0] = 0`, `zeros[i + 1] = H(zeros[i], zeros[i])`
+static ZEROS: Lazy<Mutex<Vec<Vec<u8>>>> = Lazy::new(|| Mutex::new(vec![Fr::zero().to_be_bytes()]));
+
+/// Root of an empty subtree of the given height
+fn zero(height: usize) -> Vec<u8> {
+    let mut zeros = ZEROS.lock().unwrap();
+    while zeros.len() <= height {
+        let below = zeros.last().unwrap();
+        let next = hash_pair(below, below);
+        zeros.push(next);
+    }
+    zeros[height].clone()
+}
+
+/// Poseidon hash of two stored (already validated) nodes
+pub(crate) fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let node = |bytes: &[u8]| Fr::from_be_bytes(bytes).expect("stored nodes are canonical");
+    poseidon::hash(&[node(left), node(right)])
+        .expect("two inputs are always a valid circomlib width")
+        .to_be_bytes()
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(Status::InvalidArg, msg)
+}
+
+fn check_depth(depth: u32) -> Result<()> {
+    if !(1..=MAX_DEPTH).contains(&depth) {
+        return Err(invalid(format!(
+            "depth must be between 1 and {MAX_DEPTH}, got {depth}"
+        )));
+    }
+    Ok(())
+}
+
+/// Inclusion proof for one leaf
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MerkleProof {
+    /// Sibling of the path node at each level, leaf level first
+    pub siblings: Vec<Vec<u8>>,
+    /// Whether the path node at each level is a right child (Semaphore's
+    /// `pathIndices`, i.e. the bits of the leaf index, least significant first)
+    pub path_bits: Vec<bool>,
+}
+
+/// Append-only Poseidon Merkle tree
+///
+/// Leaves are 32-byte big-endian BN254 scalar field elements.
+#[napi]
+#[derive(Debug, Clone)]
+pub struct IncrementalMerkleTree {
+    depth: u32,
+    /// `nodes[level][i]`; level 0 holds the leaves, level `depth` the root
+    nodes: Vec<Vec<Vec<u8>>>,
+}
+
+#[napi]
+impl IncrementalMerkleTree {
+    /// Create an empty tree with `2^depth` leaf slots
+    #[napi(constructor)]
+    pub fn new(depth: u32) -> Result<Self> {
+        check_depth(depth)?;
+        Ok(IncrementalMerkleTree {
+            depth,
+            nodes: vec![Vec::new(); depth as usize + 1],
+        })
+    }
+
+    /// Tree depth
+    #[napi(getter)]
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Number of inserted leaves
+    #[napi(getter)]
+    pub fn leaf_count(&self) -> u32 {
+        self.nodes[0].len() as u32
+    }
+
+    /// Append a leaf and return its index
+    #[napi]
+    pub fn insert(&mut self, leaf: Vec<u8>) -> Result<u32> {
+        parse_fr(&leaf, "leaf")?;
+        let index = self.nodes[0].len();
+        if index as u64 >= 1u64 << self.depth {
+            return Err(invalid(format!(
+                "tree of depth {} is full ({} leaves)",
+                self.depth,
+                1u64 << self.depth
+            )));
+        }
+        self.nodes[0].push(leaf);
+
+        let mut i = index;
+        for level in 0..self.depth as usize {
+            let parent = if i.is_multiple_of(2) {
+                hash_pair(&self.nodes[level][i], &zero(level))
+            } else {
+                hash_pair(&self.nodes[level][i - 1], &self.nodes[level][i])
+            };
+            i /= 2;
+            let above = &mut self.nodes[level + 1];
+            if i < above.len() {
+                above[i] = parent;
+            } else {
+                above.push(parent);
+            }
+        }
+        Ok(index as u32)
+    }
+
+    /// Current root (the empty-subtree root before any insertion)
+    #[napi]
+    pub fn root(&self) -> Vec<u8> {
+        let depth = self.depth as usize;
+        self.nodes[depth]
+            .first()
+            .cloned()
+            .unwrap_or_else(|| zero(depth))
+    }
+
+    /// Inclusion proof for the leaf at `index`
+    #[napi]
+    pub fn proof(&self, index: u32) -> Result<MerkleProof> {
+        if index >= self.leaf_count() {
+            return Err(invalid(format!(
+                "leaf index {index} out of range for {} leaves",
+                self.leaf_count()
+            )));
+        }
+        let mut siblings = Vec::with_capacity(self.depth as usize);
+        let mut path_bits = Vec::with_capacity(self.depth as usize);
+        let mut i = index as usize;
+        for level in 0..self.depth as usize {
+            let sibling = i ^ 1;
+            siblings.push(
+                self.nodes[level]
+                    .get(sibling)
+                    .cloned()
+                    .unwrap_or_else(|| zero(level)),
+            );
+            path_bits.push(i % 2 == 1);
+            i /= 2;
+        }
+        Ok(MerkleProof {
+            siblings,
+            path_bits,
+        })
+    }
+
+    /// Serialize for persistence
+    ///
+    /// Layout: depth and leaf count as `u32` big-endian, then every stored
+    /// node level by level from the leaves up, 32 bytes each.
+    #[napi]
+    pub fn serialize(&self) -> Vec<u8> {
+        let stored: usize = self.nodes.iter().map(Vec::len).sum();
+        let mut out = Vec::with_capacity(8 + stored * FR_BYTES);
+        out.extend(self.depth.to_be_bytes());
+        out.extend(self.leaf_count().to_be_bytes());
+        for node in self.nodes.iter().flatten() {
+            out.extend(node);
+        }
+        out
+    }
+
+    /// Restore a tree written by `serialize()`
+    #[napi(factory)]
+    pub fn deserialize(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() < 8 {
+            return Err(invalid(format!(
+                "serialized tree: expected at least 8 header bytes, got {}",
+                bytes.len()
+            )));
+        }
+        let depth = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+        let leaves = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as u64;
+        check_depth(depth)?;
+        if leaves > 1u64 << depth {
+            return Err(invalid(format!(
+                "serialized tree: {leaves} leaves do not fit in depth {depth}"
+            )));
+        }
+        // A level holds one node per started pair of the level below
+        let counts: Vec<usize> = (0..=depth)
+            .map(|level| leaves.div_ceil(1u64 << level) as usize)
+            .collect();
+        let expected = 8 + counts.iter().sum::<usize>() * FR_BYTES;
+        if bytes.len() != expected {
+            return Err(invalid(format!(
+                "serialized tree: expected {expected} bytes for {leaves} leaves at depth {depth}, got {}",
+                bytes.len()
+            )));
+        }
+        let mut chunks = bytes[8..].chunks_exact(FR_BYTES);
+        let mut nodes = Vec::with_capacity(counts.len());
+        for (level, count) in counts.into_iter().enumerate() {
+            let row = (0..count)
+                .map(|i| {
+                    let node = chunks.next().unwrap();
+                    parse_fr(node, &format!("serialized tree: nodes[{level}][{i}]"))?;
+                    Ok(node.to_vec())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            nodes.push(row);
+        }
+        Ok(IncrementalMerkleTree { depth, nodes })
+    }
+}
+
+/// Check that `leaf` is included under `root` along `proof`
+///
+/// Returns false for malformed inputs as well as for failed proofs.
+#[napi]
+pub fn verify_proof(root: Vec<u8>, leaf: Vec<u8>, proof: MerkleProof) -> bool {
+    if proof.siblings.len() != proof.path_bits.len() || proof.siblings.is_empty() {
+        return false;
+    }
+    let valid = |bytes: &[u8]| bytes.len() == FR_BYTES && Fr::from_be_bytes(bytes).is_some();
+    if !valid(&root) || !valid(&leaf) || !proof.siblings.iter().all(|s| valid(s)) {
+        return false;
+    }
+    let computed =
+        proof
+            .siblings
+            .iter()
+            .zip(&proof.path_bits)
+            .fold(leaf, |node, (sibling, &is_right)| {
+                if is_right {
+                    hash_pair(sibling, &node)
+                } else {
+                    hash_pair(&node, sibling)
+                }
+            });
+    computed == root
+}
+
+/// Depth of a [`SparseMerkleTree`], one level per key bit
+pub const SPARSE_DEPTH: usize = 256;
+
+/// Size in bytes of a [`SparseMerkleTree`] key
+pub const SPARSE_KEY_BYTES: usize = SPARSE_DEPTH / 8;
+
+/// Proof that a key holds a value, or is empty, in a [`SparseMerkleTree`]
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SparseMerkleProof {
+    /// The siblings that are not empty-subtree roots, leaf level first
+    pub siblings: Vec<Vec<u8>>,
+    /// 32 bytes, bit `i % 8` of byte `i / 8` set when the sibling at height
+    /// `i` (0 at the leaves) is in `siblings` rather than an empty subtree
+    pub bitmap: Vec<u8>,
+}
+
+/// Hash of the leaf holding `value`; empty leaves are zero
+fn sparse_leaf(value: &[u8]) -> [u8; 32] {
+    to_node(&hash_pair(value, &Fr::one().to_be_bytes()))
+}
+
+fn to_node(bytes: &[u8]) -> [u8; 32] {
+    bytes.try_into().expect("nodes are 32 bytes")
+}
+
+/// Bit `height` of the key counting from the least significant, which picks
+/// the right child at that height
+fn key_bit(key: &[u8; 32], height: usize) -> bool {
+    (key[31 - height / 8] >> (height % 8)) & 1 == 1
+}
+
+/// The key with its lowest `height` bits cleared: the path to its ancestor
+/// at `height`
+fn key_prefix(key: &[u8; 32], height: usize) -> [u8; 32] {
+    let mut prefix = *key;
+    for (i, byte) in prefix.iter_mut().rev().enumerate() {
+        let low = height.saturating_sub(8 * i).min(8);
+        *byte &= !((1u16 << low) - 1) as u8;
+    }
+    prefix
+}
+
+/// The key of the sibling subtree at `height`
+fn sibling_prefix(key: &[u8; 32], height: usize) -> [u8; 32] {
+    let mut sibling = key_prefix(key, height);
+    sibling[31 - height / 8] ^= 1 << (height % 8);
+    sibling
+}
+
+fn parse_key(key: &[u8]) -> Result<[u8; 32]> {
+    key.try_into().map_err(|_| {
+        invalid(format!(
+            "key: expected {SPARSE_KEY_BYTES} bytes, got {}",
+            key.len()
+        ))
+    })
+}
+
+/// Serialized form of a [`SparseMerkleTree`]: its entries, from which
+/// `load` rebuilds the nodes
+#[derive(Serialize, Deserialize)]
+struct SavedSparseTree {
+    entries: Vec<([u8; 32], [u8; 32])>,
+}
+
+/// Poseidon sparse Merkle tree of depth 256
+///
+/// Keys are any 32 bytes, such as a hashed or zero-padded Ethereum address;
+/// values are 32-byte big-endian BN254 scalar field elements. A key holding
+/// a value has leaf `H(value, 1)` and every other leaf is zero.
+#[napi]
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree {
+    entries: HashMap<[u8; 32], [u8; 32]>,
+    /// `nodes[height]` maps the key prefix of every non-empty subtree at
+    /// that height to its root; `nodes[256]` holds the tree root
+    nodes: Vec<HashMap<[u8; 32], [u8; 32]>>,
+}
+
+#[napi]
+impl SparseMerkleTree {
+    /// Create an empty tree
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        SparseMerkleTree {
+            entries: HashMap::new(),
+            nodes: vec![HashMap::new(); SPARSE_DEPTH + 1],
+        }
+    }
+
+    /// Number of keys holding a value
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.entries.len() as u32
+    }
+
+    /// Current root (the empty-subtree root of height 256 for an empty tree)
+    #[napi]
+    pub fn root(&self) -> Vec<u8> {
+        match self.nodes[SPARSE_DEPTH].get(&[0; 32]) {
+            Some(root) => root.to_vec(),
+            None => zero(SPARSE_DEPTH),
+        }
+    }
+
+    /// Set the value of `key` and return the new root
+    ///
+    /// Rehashes the 256 nodes on the key's path.
+    #[napi]
+    pub fn update(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<Vec<u8>> {
+        let key = parse_key(&key)?;
+        parse_fr(&value, "value")?;
+        self.entries.insert(key, to_node(&value));
+        let mut node = sparse_leaf(&value);
+        for height in 0..SPARSE_DEPTH {
+            self.nodes[height].insert(key_prefix(&key, height), node);
+            let sibling = self.sibling(&key, height);
+            let sibling = sibling.map_or_else(|| zero(height), |s| s.to_vec());
+            node = to_node(&if key_bit(&key, height) {
+                hash_pair(&sibling, &node)
+            } else {
+                hash_pair(&node, &sibling)
+            });
+        }
+        self.nodes[SPARSE_DEPTH].insert([0; 32], node);
+        Ok(node.to_vec())
+    }
+
+    /// Value of `key`, or `null` if it holds none
+    #[napi]
+    pub fn get(&self, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let key = parse_key(&key)?;
+        Ok(self.entries.get(&key).map(|v| v.to_vec()))
+    }
+
+    /// Membership proof for a key holding a value, or non-membership proof
+    /// for an empty one
+    #[napi]
+    pub fn prove(&self, key: Vec<u8>) -> Result<SparseMerkleProof> {
+        let key = parse_key(&key)?;
+        let mut siblings = Vec::new();
+        let mut bitmap = vec![0u8; SPARSE_KEY_BYTES];
+        for height in 0..SPARSE_DEPTH {
+            if let Some(sibling) = self.sibling(&key, height) {
+                siblings.push(sibling.to_vec());
+                bitmap[height / 8] |= 1 << (height % 8);
+            }
+        }
+        Ok(SparseMerkleProof { siblings, bitmap })
+    }
+
+    /// Check `proof` for `key` under `root`: that it holds `value`, or with
+    /// `value` null that it is empty
+    ///
+    /// Returns false for malformed inputs as well as for failed proofs.
+    #[napi]
+    pub fn verify_proof(
+        root: Vec<u8>,
+        key: Vec<u8>,
+        value: Option<Vec<u8>>,
+        proof: SparseMerkleProof,
+    ) -> bool {
+        let valid = |bytes: &[u8]| bytes.len() == FR_BYTES && Fr::from_be_bytes(bytes).is_some();
+        let Ok(key) = parse_key(&key) else {
+            return false;
+        };
+        if proof.bitmap.len() != SPARSE_KEY_BYTES
+            || !valid(&root)
+            || !proof.siblings.iter().all(|s| valid(s))
+        {
+            return false;
+        }
+        let mut node = match value {
+            Some(value) if valid(&value) => sparse_leaf(&value).to_vec(),
+            Some(_) => return false,
+            None => Fr::zero().to_be_bytes(),
+        };
+        let mut siblings = proof.siblings.iter();
+        for height in 0..SPARSE_DEPTH {
+            let sibling = if (proof.bitmap[height / 8] >> (height % 8)) & 1 == 1 {
+                match siblings.next() {
+                    Some(s) => s.clone(),
+                    None => return false,
+                }
+            } else {
+                zero(height)
+            };
+            node = if key_bit(&key, height) {
+                hash_pair(&sibling, &node)
+            } else {
+                hash_pair(&node, &sibling)
+            };
+        }
+        siblings.next().is_none() && node == root
+    }
+
+    /// Serialize the entries as JSON for persistence
+    #[napi]
+    pub fn save(&self) -> Result<String> {
+        let saved = SavedSparseTree {
+            entries: self.entries.iter().map(|(k, v)| (*k, *v)).collect(),
+        };
+        serde_json::to_string(&saved).map_err(|e| invalid(format!("serialized tree: {e}")))
+    }
+
+    /// Rebuild a tree written by `save()`
+    ///
+    /// Rehashes the path of every entry, so loading costs as much as the
+    /// original updates.
+    #[napi(factory)]
+    pub fn load(json: String) -> Result<Self> {
+        let saved: SavedSparseTree = serde_json::from_str(&json)
+            .map_err(|e| invalid(format!("serialized tree: invalid JSON: {e}")))?;
+        let mut tree = SparseMerkleTree::new();
+        for (key, value) in saved.entries {
+            tree.update(key.to_vec(), value.to_vec())?;
+        }
+        Ok(tree)
+    }
+}
+
+impl Default for SparseMerkleTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SparseMerkleTree {
+    /// Root of the non-empty sibling subtree of `key`'s path at `height`
+    fn sibling(&self, key: &[u8; 32], height: usize) -> Option<&[u8; 32]> {
+        self.nodes[height].get(&sibling_prefix(key, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr(v: u64) -> Vec<u8> {
+        Fr::from_u64(v).to_be_bytes()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_zero_subtrees_match_zk_kit() {
+        // `zeros` of @zk-kit/incremental-merkle-tree with poseidon2 and zero value 0
+        assert_eq!(zero(0), fr(0));
+        assert_eq!(
+            zero(1),
+            hex("2098f5fb9e239eab3ceac3f27b81e481dc3124d55ffed523a839ee8446b64864")
+        );
+        assert_eq!(
+            zero(2),
+            hex("1069673dcdb12263df301a6ff584a7ec261a44cb9dc68df067a4774460b1f1e1")
+        );
+        assert_eq!(
+            zero(3),
+            hex("18f43331537ee2af2e3d758d50f72106467c6eea50371dd528d57eb2b856d238")
+        );
+        assert_eq!(IncrementalMerkleTree::new(3).unwrap().root(), zero(3));
+    }
+
+    #[test]
+    fn test_insert_matches_full_recomputation() {
+        let mut tree = IncrementalMerkleTree::new(3).unwrap();
+        for v in 1..=5 {
+            assert_eq!(tree.insert(fr(v)).unwrap(), v as u32 - 1);
+        }
+        let mut level: Vec<Vec<u8>> = (1..=8)
+            .map(|v| if v <= 5 { fr(v) } else { fr(0) })
+            .collect();
+        while level.len() > 1 {
+            level = level.chunks(2).map(|p| hash_pair(&p[0], &p[1])).collect();
+        }
+        assert_eq!(tree.root(), level[0]);
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        let mut tree = IncrementalMerkleTree::new(4).unwrap();
+        for v in 0..11 {
+            tree.insert(fr(100 + v)).unwrap();
+        }
+        let root = tree.root();
+        for index in 0..11 {
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(proof.siblings.len(), 4);
+            assert_eq!(proof.path_bits[0], index % 2 == 1);
+            assert!(verify_proof(
+                root.clone(),
+                fr(100 + index as u64),
+                proof.clone()
+            ));
+            assert!(!verify_proof(root.clone(), fr(999), proof));
+        }
+        let mut bad = tree.proof(3).unwrap();
+        bad.path_bits.pop();
+        assert!(!verify_proof(root.clone(), fr(103), bad));
+        assert!(tree.proof(11).is_err());
+    }
+
+    #[test]
+    fn test_full_tree_and_invalid_input() {
+        let mut tree = IncrementalMerkleTree::new(1).unwrap();
+        tree.insert(fr(1)).unwrap();
+        tree.insert(fr(2)).unwrap();
+        assert!(tree.insert(fr(3)).is_err());
+        assert!(tree.insert(vec![0xff; 32]).is_err());
+        assert!(IncrementalMerkleTree::new(0).is_err());
+        assert!(IncrementalMerkleTree::new(MAX_DEPTH + 1).is_err());
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let mut tree = IncrementalMerkleTree::new(5).unwrap();
+        for v in 0..7 {
+            tree.insert(fr(v * 3)).unwrap();
+        }
+        let bytes = tree.serialize();
+        let mut restored = IncrementalMerkleTree::deserialize(bytes.clone()).unwrap();
+        assert_eq!(restored.root(), tree.root());
+        assert_eq!(restored.leaf_count(), 7);
+        tree.insert(fr(42)).unwrap();
+        restored.insert(fr(42)).unwrap();
+        assert_eq!(restored.root(), tree.root());
+
+        assert!(IncrementalMerkleTree::deserialize(bytes[..bytes.len() - 1].to_vec()).is_err());
+        let mut corrupt = bytes;
+        corrupt[8] = 0xff;
+        assert!(IncrementalMerkleTree::deserialize(corrupt).is_err());
+        let empty = IncrementalMerkleTree::new(2).unwrap().serialize();
+        assert_eq!(
+            IncrementalMerkleTree::deserialize(empty).unwrap().root(),
+            zero(2)
+        );
+    }
+
+    fn key(last: u8, first: u8) -> Vec<u8> {
+        let mut k = vec![0; SPARSE_KEY_BYTES];
+        k[0] = first;
+        k[31] = last;
+        k
+    }
+
+    #[test]
+    fn test_sparse_membership_and_non_membership() {
+        let mut tree = SparseMerkleTree::new();
+        assert_eq!(tree.root(), zero(SPARSE_DEPTH));
+        // Keys differing in the lowest bit are leaf-level siblings; in the
+        // highest, they split at the root
+        let keys = [key(0, 0), key(1, 0), key(0, 0x80), key(7, 0x13)];
+        let mut root = tree.root();
+        for (i, k) in keys.iter().enumerate() {
+            let updated = tree.update(k.clone(), fr(10 + i as u64)).unwrap();
+            assert_ne!(updated, root);
+            root = updated;
+        }
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.size(), 4);
+
+        for (i, k) in keys.iter().enumerate() {
+            let value = fr(10 + i as u64);
+            assert_eq!(tree.get(k.clone()).unwrap(), Some(value.clone()));
+            let proof = tree.prove(k.clone()).unwrap();
+            let set_bits: u32 = proof.bitmap.iter().map(|b| b.count_ones()).sum();
+            assert_eq!(set_bits as usize, proof.siblings.len());
+            let verify = |v: Option<Vec<u8>>| {
+                SparseMerkleTree::verify_proof(root.clone(), k.clone(), v, proof.clone())
+            };
+            assert!(verify(Some(value)));
+            assert!(!verify(Some(fr(99))));
+            assert!(!verify(None));
+        }
+
+        // Absent keys, including a leaf-level sibling of a present one
+        for absent in [key(2, 0), key(6, 0x13), key(0, 0xff)] {
+            assert_eq!(tree.get(absent.clone()).unwrap(), None);
+            let proof = tree.prove(absent.clone()).unwrap();
+            assert!(SparseMerkleTree::verify_proof(
+                root.clone(),
+                absent.clone(),
+                None,
+                proof.clone()
+            ));
+            assert!(!SparseMerkleTree::verify_proof(
+                root.clone(),
+                absent,
+                Some(fr(0)),
+                proof
+            ));
+        }
+    }
+
+    #[test]
+    fn test_sparse_root_is_order_independent() {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = (0..6u8)
+            .map(|i| (key(i * 37, i * 11), fr(i as u64)))
+            .collect();
+        let mut forward = SparseMerkleTree::new();
+        for (k, v) in &entries {
+            forward.update(k.clone(), v.clone()).unwrap();
+        }
+        let mut backward = SparseMerkleTree::default();
+        for (k, v) in entries.iter().rev() {
+            backward.update(k.clone(), v.clone()).unwrap();
+        }
+        assert_eq!(forward.root(), backward.root());
+
+        // A single entry needs no siblings: every other subtree is empty
+        let mut single = SparseMerkleTree::new();
+        single.update(key(5, 5), fr(1)).unwrap();
+        let proof = single.prove(key(5, 5)).unwrap();
+        assert!(proof.siblings.is_empty());
+        assert_eq!(proof.bitmap, vec![0; SPARSE_KEY_BYTES]);
+    }
+
+    #[test]
+    fn test_sparse_overwrite_and_tampering() {
+        let mut tree = SparseMerkleTree::new();
+        tree.update(key(1, 1), fr(1)).unwrap();
+        tree.update(key(2, 2), fr(2)).unwrap();
+        let old_root = tree.root();
+        let old_proof = tree.prove(key(1, 1)).unwrap();
+        let new_root = tree.update(key(1, 1), fr(3)).unwrap();
+        assert_eq!(tree.size(), 2);
+        assert!(!SparseMerkleTree::verify_proof(
+            new_root.clone(),
+            key(1, 1),
+            Some(fr(1)),
+            old_proof.clone()
+        ));
+        assert!(SparseMerkleTree::verify_proof(
+            old_root,
+            key(1, 1),
+            Some(fr(1)),
+            old_proof.clone()
+        ));
+
+        let proof = tree.prove(key(1, 1)).unwrap();
+        let verify = |p: SparseMerkleProof| {
+            SparseMerkleTree::verify_proof(new_root.clone(), key(1, 1), Some(fr(3)), p)
+        };
+        assert!(verify(proof.clone()));
+        let mut extra = proof.clone();
+        extra.siblings.push(fr(0));
+        assert!(!verify(extra));
+        let mut short_bitmap = proof.clone();
+        short_bitmap.bitmap.pop();
+        assert!(!verify(short_bitmap));
+        let mut flipped = proof;
+        flipped.bitmap[0] ^= 1;
+        assert!(!verify(flipped));
+
+        assert!(tree.update(vec![0; 20], fr(1)).is_err());
+        assert!(tree.update(key(0, 0), vec![0xff; 32]).is_err());
+        let err = tree.get(vec![0; 33]).unwrap_err();
+        assert_eq!(err.reason, "key: expected 32 bytes, got 33");
+        assert!(!SparseMerkleTree::verify_proof(
+            tree.root(),
+            vec![0; 31],
+            None,
+            tree.prove(key(0, 0)).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_sparse_save_load() {
+        let mut tree = SparseMerkleTree::new();
+        for i in 0..5u8 {
+            tree.update(key(i, 200 - i), fr(i as u64 * 1000)).unwrap();
+        }
+        let json = tree.save().unwrap();
+        let mut restored = SparseMerkleTree::load(json).unwrap();
+        assert_eq!(restored.root(), tree.root());
+        assert_eq!(restored.get(key(3, 197)).unwrap(), Some(fr(3000)));
+        assert_eq!(
+            restored.update(key(9, 9), fr(9)).unwrap(),
+            tree.update(key(9, 9), fr(9)).unwrap()
+        );
+        assert_eq!(
+            SparseMerkleTree::load(SparseMerkleTree::new().save().unwrap())
+                .unwrap()
+                .root(),
+            zero(SPARSE_DEPTH)
+        );
+        assert!(SparseMerkleTree::load("{\"entries\": 3}".to_string()).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Metal compute kernels for batch field arithmetic
+//!
+//! The shader source in `shaders/mont_mul.metal` is compiled at runtime the
+//! first time a kernel is requested. Buffers use shared storage, which on
+//! Apple Silicon's unified memory avoids any copy between CPU and GPU.
+
+use std::ffi::c_void;
+
+use metal::{
+    CommandQueue, CompileOptions, ComputePipelineState, Device, MTLResourceOptions, MTLSize,
+};
+use once_cell::sync::Lazy;
+
+use crate::montgomery::{Fp, MontConfig};
+use crate::neon::digits;
+
+const MONT_MUL_SOURCE: &str = include_str!("shaders/mont_mul.metal");
+
+/// Mirrors `MontParams` in the shader
+#[repr(C)]
+struct MontParams {
+    modulus: [u32; 8],
+    inv: u32,
+    count: u32,
+}
+
+/// Compiled element-wise Montgomery multiplication pipeline
+pub(crate) struct MontMulKernel {
+    device: Device,
+    queue: CommandQueue,
+    pipeline: ComputePipelineState,
+}
+
+static MONT_MUL: Lazy<Option<MontMulKernel>> = Lazy::new(MontMulKernel::new);
+
+/// The shared kernel, or `None` if no device is present or compilation failed
+pub(crate) fn mont_mul_kernel() -> Option<&'static MontMulKernel> {
+    MONT_MUL.as_ref()
+}
+
+/// Prefer the integrated unified-memory GPU, then the system default
+fn select_device() -> Option<Device> {
+    Device::all()
+        .into_iter()
+        .find(|d| d.has_unified_memory())
+        .or_else(Device::system_default)
+}
+
+impl MontMulKernel {
+    fn new() -> Option<Self> {
+        let device = select_device()?;
+        let library = device
+            .new_library_with_source(MONT_MUL_SOURCE, &CompileOptions::new())
+            .ok()?;
+        let function = library.get_function("mont_mul", None).ok()?;
+        let pipeline = device
+            .new_compute_pipeline_state_with_function(&function)
+            .ok()?;
+        let queue = device.new_command_queue();
+        Some(MontMulKernel {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Element-wise `a[i] * b[i]` on the GPU
+    pub(crate) fn mul<C: MontConfig<4>>(&self, a: &[Fp<C, 4>], b: &[Fp<C, 4>]) -> Vec<Fp<C, 4>> {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let pack = |v: &[Fp<C, 4>]| -> Vec<u32> {
+            v.iter()
+                .flat_map(|x| digits(x.to_montgomery_limbs()))
+                .collect()
+        };
+        let (a_digits, b_digits) = (pack(a), pack(b));
+        let bytes = (a_digits.len() * std::mem::size_of::<u32>()) as u64;
+        let params = MontParams {
+            modulus: digits(C::MODULUS),
+            inv: C::INV as u32,
+            count: n as u32,
+        };
+
+        objc::rc::autoreleasepool(|| {
+            let shared = MTLResourceOptions::StorageModeShared;
+            let buf_a =
+                self.device
+                    .new_buffer_with_data(a_digits.as_ptr() as *const c_void, bytes, shared);
+            let buf_b =
+                self.device
+                    .new_buffer_with_data(b_digits.as_ptr() as *const c_void, bytes, shared);
+            let buf_out = self.device.new_buffer(bytes, shared);
+
+            let command = self.queue.new_command_buffer();
+            let encoder = command.new_compute_command_encoder();
+            encoder.set_compute_pipeline_state(&self.pipeline);
+            encoder.set_buffer(0, Some(&buf_a), 0);
+            encoder.set_buffer(1, Some(&buf_b), 0);
+            encoder.set_buffer(2, Some(&buf_out), 0);
+            encoder.set_bytes(
+                3,
+                std::mem::size_of::<MontParams>() as u64,
+                &params as *const MontParams as *const c_void,
+            );
+            let width = self
+                .pipeline
+                .thread_execution_width()
+                .min(self.pipeline.max_total_threads_per_threadgroup());
+            encoder.dispatch_threads(MTLSize::new(n as u64, 1, 1), MTLSize::new(width, 1, 1));
+            encoder.end_encoding();
+            command.commit();
+            command.wait_until_completed();
+
+            // SAFETY: the command buffer has completed, and `buf_out` holds
+            // exactly `8 * n` u32 digits written by the kernel
+            let out =
+                unsafe { std::slice::from_raw_parts(buf_out.contents() as *const u32, 8 * n) };
+            out.chunks_exact(8)
+                .map(|d| {
+                    let limbs: [u64; 4] =
+                        std::array::from_fn(|i| d[2 * i] as u64 | ((d[2 * i + 1] as u64) << 32));
+                    Fp::from_montgomery_reduced(limbs, false)
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,833 @@
+//! Generic Montgomery-form prime field arithmetic
+//!
+//! Field elements are stored as `N` little-endian 64-bit limbs in Montgomery
+//! form (`a * R mod p` with `R = 2^(64 * N)`). Each concrete field only has to
+//! provide its modulus; the Montgomery constants are derived at compile time.
+//! The limb arithmetic, including the 256-bit multiplication kernel, lives in
+//! [`crate::bigint`].
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use crate::bigint::{add_limbs, add_mod, mont_mul_generic, sub_mod};
+pub(crate) use crate::bigint::{geq, sub_limbs};
+
+/// `-m^-1 mod 2^64` via Newton iteration (valid for odd `m0`)
+pub(crate) const fn compute_inv(m0: u64) -> u64 {
+    let mut inv: u64 = 1;
+    let mut i = 0;
+    while i < 6 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(m0.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+/// `(2 * a) mod m` for `a < m`
+const fn double_mod<const N: usize>(a: &[u64; N], m: &[u64; N]) -> [u64; N] {
+    let (sum, carry) = add_limbs(a, a);
+    if carry != 0 || geq(&sum, m) {
+        sub_limbs(&sum, m).0
+    } else {
+        sum
+    }
+}
+
+/// `2^bits mod m`, by repeated doubling
+const fn pow2_mod<const N: usize>(m: &[u64; N], bits: usize) -> [u64; N] {
+    let mut acc = [0u64; N];
+    acc[0] = 1;
+    let mut i = 0;
+    while i < bits {
+        acc = double_mod(&acc, m);
+        i += 1;
+    }
+    acc
+}
+
+/// Largest `s` such that `2^s` divides `m - 1`, for odd `m`
+const fn two_adicity<const N: usize>(m: &[u64; N]) -> u32 {
+    let mut s = 0;
+    let mut i = 0;
+    while i < N {
+        let limb = if i == 0 { m[0] - 1 } else { m[i] };
+        if limb != 0 {
+            return s + limb.trailing_zeros();
+        }
+        s += 64;
+        i += 1;
+    }
+    s
+}
+
+/// `a >> bits`
+const fn shr_limbs<const N: usize>(a: &[u64; N], bits: u32) -> [u64; N] {
+    let words = (bits / 64) as usize;
+    let shift = bits % 64;
+    let mut out = [0u64; N];
+    let mut i = 0;
+    while i + words < N {
+        out[i] = a[i + words] >> shift;
+        if shift > 0 && i + words + 1 < N {
+            out[i] |= a[i + words + 1] << (64 - shift);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Parameters of a prime field used in Montgomery form
+///
+/// Only `MODULUS` has to be supplied; `INV`, `R` and `R2` default to values
+/// derived from it at compile time.
+pub trait MontConfig<const N: usize>: 'static + Copy + Send + Sync + fmt::Debug {
+    /// The field prime, little-endian limbs
+    const MODULUS: [u64; N];
+    /// `-p^-1 mod 2^64`
+    const INV: u64 = compute_inv(Self::MODULUS[0]);
+    /// `R mod p`, i.e. one in Montgomery form
+    const R: [u64; N] = pow2_mod(&Self::MODULUS, 64 * N);
+    /// `R^2 mod p`, used to convert into Montgomery form
+    const R2: [u64; N] = pow2_mod(&Self::MODULUS, 128 * N);
+    /// `s` in `p - 1 = 2^s t` with `t` odd
+    const TWO_ADICITY: u32 = two_adicity(&Self::MODULUS);
+    /// `(t - 1) / 2`, the Tonelli-Shanks exponent
+    const TRACE_MINUS_ONE_DIV_TWO: [u64; N] = shr_limbs(&Self::MODULUS, Self::TWO_ADICITY + 1);
+}
+
+/// Operations shared by every field type curve arithmetic is generic over
+pub trait Field:
+    Copy
+    + Clone
+    + PartialEq
+    + Eq
+    + fmt::Debug
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    fn square(&self) -> Self;
+    fn double(&self) -> Self;
+    /// Multiplicative inverse, `None` for zero
+    fn inverse(&self) -> Option<Self>;
+}
+
+/// An element of the prime field described by `C`, in Montgomery form
+pub struct Fp<C: MontConfig<N>, const N: usize> {
+    limbs: [u64; N],
+    _config: PhantomData<C>,
+}
+
+impl<C: MontConfig<N>, const N: usize> Fp<C, N> {
+    /// Size of the canonical byte encoding
+    pub const BYTES: usize = 8 * N;
+
+    #[inline(always)]
+    const fn from_mont_limbs(limbs: [u64; N]) -> Self {
+        Fp {
+            limbs,
+            _config: PhantomData,
+        }
+    }
+
+    /// The additive identity
+    pub const fn zero() -> Self {
+        Self::from_mont_limbs([0u64; N])
+    }
+
+    /// The multiplicative identity
+    pub const fn one() -> Self {
+        Self::from_mont_limbs(C::R)
+    }
+
+    /// Whether this element is zero
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&l| l == 0)
+    }
+
+    /// Build an element from a small integer
+    pub fn from_u64(v: u64) -> Self {
+        let mut limbs = [0u64; N];
+        limbs[0] = v;
+        // Multi-limb moduli are always larger than a u64, single-limb ones may not be
+        if N == 1 && geq(&limbs, &C::MODULUS) {
+            limbs[0] = v % C::MODULUS[0];
+        }
+        Self::from_mont_limbs(limbs).mont_mul(&Self::from_mont_limbs(C::R2))
+    }
+
+    /// Build an element from canonical little-endian limbs, rejecting values `>= p`
+    pub fn from_canonical(limbs: [u64; N]) -> Option<Self> {
+        if geq(&limbs, &C::MODULUS) {
+            return None;
+        }
+        Some(Self::from_mont_limbs(limbs).mont_mul(&Self::from_mont_limbs(C::R2)))
+    }
+
+    /// The canonical (non-Montgomery) little-endian limbs of this element
+    pub fn to_canonical(self) -> [u64; N] {
+        let mut one = [0u64; N];
+        one[0] = 1;
+        self.mont_mul(&Self::from_mont_limbs(one)).limbs
+    }
+
+    /// Raw Montgomery-form limbs
+    pub fn to_montgomery_limbs(self) -> [u64; N] {
+        self.limbs
+    }
+
+    /// Build an element directly from Montgomery-form limbs, rejecting values `>= p`
+    pub fn from_montgomery_limbs(limbs: [u64; N]) -> Option<Self> {
+        if geq(&limbs, &C::MODULUS) {
+            None
+        } else {
+            Some(Self::from_mont_limbs(limbs))
+        }
+    }
+
+    /// Final step of a Montgomery reduction computed outside this type: `limbs`
+    /// (plus `overflow * 2^(64N)`) must be below `2p`
+    #[inline]
+    pub(crate) fn from_montgomery_reduced(limbs: [u64; N], overflow: bool) -> Self {
+        if overflow || geq(&limbs, &C::MODULUS) {
+            Self::from_mont_limbs(sub_limbs(&limbs, &C::MODULUS).0)
+        } else {
+            Self::from_mont_limbs(limbs)
+        }
+    }
+
+    /// Decode a canonical big-endian encoding of exactly `8 * N` bytes
+    pub fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; N];
+        for (i, chunk) in bytes.rchunks(8).enumerate() {
+            limbs[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Self::from_canonical(limbs)
+    }
+
+    /// Decode a canonical little-endian encoding of exactly `8 * N` bytes
+    pub fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; N];
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            limbs[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self::from_canonical(limbs)
+    }
+
+    /// Decode exactly `8 * N` little-endian bytes, reducing values `>= p`
+    pub fn from_le_bytes_reduced(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; N];
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            limbs[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        // Any value below 2^(64N) times R^2 mod p stays within the CIOS bound
+        Some(Self::from_mont_limbs(limbs).mont_mul(&Self::from_mont_limbs(C::R2)))
+    }
+
+    /// Canonical big-endian encoding
+    pub fn to_be_bytes(self) -> Vec<u8> {
+        self.to_canonical()
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect()
+    }
+
+    /// Canonical little-endian encoding
+    pub fn to_le_bytes(self) -> Vec<u8> {
+        self.to_canonical()
+            .iter()
+            .flat_map(|l| l.to_le_bytes())
+            .collect()
+    }
+
+    /// Decode raw Montgomery-form little-endian limbs, rejecting values `>= p`
+    pub fn from_montgomery_le_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; N];
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            limbs[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self::from_montgomery_limbs(limbs)
+    }
+
+    /// Decode raw Montgomery-form little-endian limbs, reducing values `>= p`
+    pub fn from_montgomery_le_bytes_reduced(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let mut limbs = [0u64; N];
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            limbs[i] = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        // Multiplying by R cancels the R^-1 of the reduction, leaving limbs mod p
+        Some(Self::from_mont_limbs(limbs).mont_mul(&Self::one()))
+    }
+
+    /// Raw Montgomery-form limbs, little-endian
+    pub fn to_montgomery_le_bytes(self) -> Vec<u8> {
+        self.limbs.iter().flat_map(|l| l.to_le_bytes()).collect()
+    }
+
+    /// Montgomery multiplication (CIOS): `a * b * R^-1 mod p`
+    #[inline]
+    fn mont_mul(&self, rhs: &Self) -> Self {
+        let (mut t, t_hi) = self.mont_mul_unreduced(rhs);
+        if t_hi != 0 || geq(&t, &C::MODULUS) {
+            t = sub_limbs(&t, &C::MODULUS).0;
+        }
+        Self::from_mont_limbs(t)
+    }
+
+    /// CIOS product before the final subtraction: `(t, t_hi)` with
+    /// `t + t_hi * 2^(64N) < 2p`
+    ///
+    /// 256-bit fields take [`crate::bigint::mont_mul`] and 384-bit fields
+    /// [`crate::bigint::mont_mul_384`]; `N` is a constant, so the checks
+    /// compile away.
+    #[inline]
+    fn mont_mul_unreduced(&self, rhs: &Self) -> ([u64; N], u64) {
+        if N == 4 {
+            let limbs = |x: &[u64; N]| -> [u64; 4] { x[..].try_into().unwrap() };
+            let (t, t_hi) = crate::bigint::mont_mul(
+                &limbs(&self.limbs),
+                &limbs(&rhs.limbs),
+                &limbs(&C::MODULUS),
+                C::INV,
+            );
+            return (t[..].try_into().unwrap(), t_hi);
+        }
+        if N == 6 {
+            let limbs = |x: &[u64; N]| -> [u64; 6] { x[..].try_into().unwrap() };
+            let (t, t_hi) = crate::bigint::mont_mul_384(
+                &limbs(&self.limbs),
+                &limbs(&rhs.limbs),
+                &limbs(&C::MODULUS),
+                C::INV,
+            );
+            return (t[..].try_into().unwrap(), t_hi);
+        }
+        mont_mul_generic(&self.limbs, &rhs.limbs, &C::MODULUS, C::INV)
+    }
+
+    /// Montgomery multiplication with a branch-free final subtraction
+    ///
+    /// `mont_mul` skips the subtraction of `p` when the product is already
+    /// reduced, so its timing depends on the operands. Here `t - p` is always
+    /// computed and the result picked with `subtle`'s constant-time select.
+    /// Audit changes with a dudect-style timing test
+    /// (<https://github.com/oreparaz/dudect>): time fixed versus random
+    /// operands and check Welch's t-statistic stays small.
+    pub fn mul_ct(&self, rhs: &Self) -> Self {
+        let (t, t_hi) = self.mont_mul_unreduced(rhs);
+        let (reduced, borrow) = sub_limbs(&t, &C::MODULUS);
+        // Keep `t` only when it is below p: no overflow word and `t - p` borrowed
+        let keep = Choice::from((borrow & (t_hi ^ 1)) as u8);
+        let mut out = [0u64; N];
+        for i in 0..N {
+            out[i] = u64::conditional_select(&reduced[i], &t[i], keep);
+        }
+        Self::from_mont_limbs(out)
+    }
+
+    /// `self + rhs` with a branch-free final subtraction
+    pub fn add_ct(&self, rhs: &Self) -> Self {
+        let (sum, carry) = add_limbs(&self.limbs, &rhs.limbs);
+        let (reduced, borrow) = sub_limbs(&sum, &C::MODULUS);
+        let keep = Choice::from((borrow & (carry ^ 1)) as u8);
+        Self::conditional_select(
+            &Self::from_mont_limbs(reduced),
+            &Self::from_mont_limbs(sum),
+            keep,
+        )
+    }
+
+    /// `self - rhs`, adding back `p` under a mask instead of a branch
+    pub fn sub_ct(&self, rhs: &Self) -> Self {
+        let (diff, borrow) = sub_limbs(&self.limbs, &rhs.limbs);
+        let mask = borrow.wrapping_neg();
+        Self::from_mont_limbs(add_limbs(&diff, &C::MODULUS.map(|l| l & mask)).0)
+    }
+
+    /// [`Fp::from_canonical`] without data-dependent branches, except on
+    /// whether `limbs` is below `p`
+    pub fn from_canonical_ct(limbs: [u64; N]) -> Option<Self> {
+        let below_p = sub_limbs(&limbs, &C::MODULUS).1 == 1;
+        let value = Self::from_mont_limbs(limbs).mul_ct(&Self::from_mont_limbs(C::R2));
+        below_p.then_some(value)
+    }
+
+    /// [`Fp::to_canonical`] without data-dependent branches
+    pub fn to_canonical_ct(self) -> [u64; N] {
+        let mut one = [0u64; N];
+        one[0] = 1;
+        self.mul_ct(&Self::from_mont_limbs(one)).limbs
+    }
+
+    /// `self^exp` for an exponent given as little-endian limbs
+    pub fn pow(&self, exp: &[u64]) -> Self {
+        let mut acc = Self::one();
+        for &limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc.square();
+                if (limb >> bit) & 1 == 1 {
+                    acc = acc.mont_mul(self);
+                }
+            }
+        }
+        acc
+    }
+
+    /// Powers `self^0 ..= self^15` for the 4-bit window exponentiations
+    fn window_table(&self, mul: impl Fn(&Self, &Self) -> Self) -> [Self; 16] {
+        let mut table = [Self::one(); 16];
+        for i in 1..16 {
+            table[i] = mul(&table[i - 1], self);
+        }
+        table
+    }
+
+    /// `self^exp` with a fixed 4-bit window over little-endian limbs
+    ///
+    /// Four squarings and at most one table multiplication per window, the
+    /// multiplication skipped for zero windows. Variable time.
+    pub fn pow_vartime(&self, exp: &[u64]) -> Self {
+        let table = self.window_table(Self::mont_mul);
+        let mut acc = Self::one();
+        for &limb in exp.iter().rev() {
+            for shift in (0..64).step_by(4).rev() {
+                for _ in 0..4 {
+                    acc = acc.square();
+                }
+                let window = ((limb >> shift) & 0xf) as usize;
+                if window != 0 {
+                    acc = acc.mont_mul(&table[window]);
+                }
+            }
+        }
+        acc
+    }
+
+    /// [`Fp::pow_vartime`] in constant time for a given exponent length
+    ///
+    /// Every window multiplies, by `self^0 = 1` for zero windows, and the
+    /// table entry is read with a constant-time scan of the whole table.
+    pub fn pow_ct(&self, exp: &[u64]) -> Self {
+        let table = self.window_table(Self::mul_ct);
+        let mut acc = Self::one();
+        for &limb in exp.iter().rev() {
+            for shift in (0..64).step_by(4).rev() {
+                for _ in 0..4 {
+                    acc = acc.mul_ct(&acc);
+                }
+                let window = (limb >> shift) & 0xf;
+                let mut entry = [0u64; N];
+                for (i, t) in table.iter().enumerate() {
+                    let hit = (i as u64).ct_eq(&window);
+                    for (e, l) in entry.iter_mut().zip(&t.limbs) {
+                        e.conditional_assign(l, hit);
+                    }
+                }
+                acc = acc.mul_ct(&Self::from_mont_limbs(entry));
+            }
+        }
+        acc
+    }
+
+    /// `2 * self`
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// `self^2`
+    pub fn square(&self) -> Self {
+        self.mont_mul(self)
+    }
+
+    /// Multiplicative inverse via the binary extended Euclidean algorithm
+    ///
+    /// This is **variable time**: the number of iterations depends on the
+    /// value being inverted. Use [`Fp::inverse_ct`] for secret inputs.
+    pub fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let p = C::MODULUS;
+        let mut u = self.to_canonical();
+        let mut v = p;
+        let mut one = [0u64; N];
+        one[0] = 1;
+        let mut x1 = one;
+        let mut x2 = [0u64; N];
+
+        while u != one && v != one {
+            while u[0] & 1 == 0 {
+                shr1(&mut u, 0);
+                halve_mod(&mut x1, &p);
+            }
+            while v[0] & 1 == 0 {
+                shr1(&mut v, 0);
+                halve_mod(&mut x2, &p);
+            }
+            if geq(&u, &v) {
+                u = sub_limbs(&u, &v).0;
+                x1 = sub_mod(&x1, &x2, &p);
+            } else {
+                v = sub_limbs(&v, &u).0;
+                x2 = sub_mod(&x2, &x1, &p);
+            }
+        }
+        let inv = if u == one { x1 } else { x2 };
+        Self::from_canonical(inv)
+    }
+
+    /// Square root by Tonelli-Shanks, `None` for a non-residue
+    ///
+    /// `root_of_unity` must be a primitive `2^TWO_ADICITY`-th root of unity.
+    /// Either of the two roots may be returned. Variable time.
+    pub fn sqrt(&self, root_of_unity: Self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(*self);
+        }
+        let one = Self::one();
+        let w = self.pow(&C::TRACE_MINUS_ONE_DIV_TWO);
+        // x = self^((t + 1) / 2) and b = self^t, so x^2 = self * b
+        let mut x = w.mont_mul(self);
+        let mut b = x.mont_mul(&w);
+        let mut z = root_of_unity;
+        let mut v = C::TWO_ADICITY;
+        while b != one {
+            // Order of b is 2^k
+            let mut k = 0;
+            let mut b2k = b;
+            while b2k != one {
+                b2k = b2k.square();
+                k += 1;
+            }
+            if k == v {
+                return None;
+            }
+            let mut w = z;
+            for _ in 0..v - k - 1 {
+                w = w.square();
+            }
+            z = w.square();
+            b = b.mont_mul(&z);
+            x = x.mont_mul(&w);
+            v = k;
+        }
+        Some(x)
+    }
+
+    /// Multiplicative inverse via Fermat's little theorem, `self^(p - 2)`
+    ///
+    /// The exponent is the public constant `p - 2`, so the sequence of
+    /// squarings and multiplications is independent of the input. Returns
+    /// `None` for zero, which is the only branch on the input.
+    pub fn inverse_ct(&self) -> Option<Self> {
+        let inv = self.invert_or_zero_ct();
+        (!self.is_zero()).then_some(inv)
+    }
+
+    /// `self^(p - 2)` with [`Fp::pow_ct`], so zero maps to zero
+    fn invert_or_zero_ct(&self) -> Self {
+        let mut two = [0u64; N];
+        two[0] = 2;
+        self.pow_ct(&sub_limbs(&C::MODULUS, &two).0)
+    }
+}
+
+/// Shift right by one bit, shifting `top` in as the new most significant bit
+#[inline]
+fn shr1<const N: usize>(a: &mut [u64; N], top: u64) {
+    let mut carry = top;
+    for limb in a.iter_mut().rev() {
+        let next = *limb & 1;
+        *limb = (*limb >> 1) | (carry << 63);
+        carry = next;
+    }
+}
+
+/// `a / 2 mod p` for odd `p`
+#[inline]
+fn halve_mod<const N: usize>(a: &mut [u64; N], p: &[u64; N]) {
+    if a[0] & 1 == 0 {
+        shr1(a, 0);
+    } else {
+        let (sum, carry) = add_limbs(a, p);
+        *a = sum;
+        shr1(a, carry);
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Clone for Fp<C, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Copy for Fp<C, N> {}
+
+impl<C: MontConfig<N>, const N: usize> PartialEq for Fp<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.limbs == other.limbs
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Eq for Fp<C, N> {}
+
+impl<C: MontConfig<N>, const N: usize> Hash for Fp<C, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.limbs.hash(state);
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> ConditionallySelectable for Fp<C, N> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self::from_mont_limbs(std::array::from_fn(|i| {
+            u64::conditional_select(&a.limbs[i], &b.limbs[i], choice)
+        }))
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Default for Fp<C, N> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> fmt::Debug for Fp<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.to_canonical().iter().rev() {
+            write!(f, "{limb:016x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Add for Fp<C, N> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::from_mont_limbs(add_mod(&self.limbs, &rhs.limbs, &C::MODULUS))
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Sub for Fp<C, N> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::from_mont_limbs(sub_mod(&self.limbs, &rhs.limbs, &C::MODULUS))
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Mul for Fp<C, N> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.mont_mul(&rhs)
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Neg for Fp<C, N> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            self
+        } else {
+            Self::from_mont_limbs(sub_limbs(&C::MODULUS, &self.limbs).0)
+        }
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> AddAssign for Fp<C, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> SubAssign for Fp<C, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> MulAssign for Fp<C, N> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Field for Fp<C, N> {
+    fn zero() -> Self {
+        Fp::zero()
+    }
+
+    fn one() -> Self {
+        Fp::one()
+    }
+
+    fn is_zero(&self) -> bool {
+        Fp::is_zero(self)
+    }
+
+    fn square(&self) -> Self {
+        Fp::square(self)
+    }
+
+    fn double(&self) -> Self {
+        Fp::double(self)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Fp::inverse(self)
+    }
+}
+
+/// Field arithmetic without data-dependent branches or memory accesses
+///
+/// Backs the constant-time mode of [`crate::constant_time`]: complete-formula
+/// scalar multiplication in [`crate::ec`] and Fermat inversion. Only the
+/// arithmetic itself is covered; encoding and validation stay variable time.
+pub trait ConstantTimeField: Field + ConditionallySelectable {
+    fn add_ct(&self, rhs: &Self) -> Self;
+    fn sub_ct(&self, rhs: &Self) -> Self;
+    fn mul_ct(&self, rhs: &Self) -> Self;
+    /// Multiplicative inverse, zero for zero
+    fn invert_ct(&self) -> Self;
+}
+
+impl<C: MontConfig<N>, const N: usize> ConstantTimeField for Fp<C, N> {
+    fn add_ct(&self, rhs: &Self) -> Self {
+        Fp::add_ct(self, rhs)
+    }
+
+    fn sub_ct(&self, rhs: &Self) -> Self {
+        Fp::sub_ct(self, rhs)
+    }
+
+    fn mul_ct(&self, rhs: &Self) -> Self {
+        Fp::mul_ct(self, rhs)
+    }
+
+    fn invert_ct(&self) -> Self {
+        self.invert_or_zero_ct()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct F101;
+
+    impl MontConfig<1> for F101 {
+        const MODULUS: [u64; 1] = [101];
+    }
+
+    type Small = Fp<F101, 1>;
+
+    #[test]
+    fn test_small_field_matches_integer_arithmetic() {
+        for a in 0..101u64 {
+            for b in [0u64, 1, 2, 50, 99, 100] {
+                let fa = Small::from_u64(a);
+                let fb = Small::from_u64(b);
+                assert_eq!((fa + fb).to_canonical()[0], (a + b) % 101);
+                assert_eq!((fa - fb).to_canonical()[0], (a + 101 - b) % 101);
+                assert_eq!((fa * fb).to_canonical()[0], (a * b) % 101);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ct_arithmetic_matches() {
+        for a in 0..101u64 {
+            let fa = Small::from_u64(a);
+            for b in 0..101u64 {
+                let fb = Small::from_u64(b);
+                assert_eq!(fa.add_ct(&fb), fa + fb);
+                assert_eq!(fa.sub_ct(&fb), fa - fb);
+                let choice = Choice::from((a & 1) as u8);
+                let picked = Small::conditional_select(&fa, &fb, choice);
+                assert_eq!(picked, if a & 1 == 1 { fb } else { fa });
+            }
+        }
+        assert_eq!(ConstantTimeField::invert_ct(&Small::zero()), Small::zero());
+
+        // 2^64 - 59: sums of large elements overflow the limb
+        #[derive(Debug, Clone, Copy)]
+        struct F64;
+
+        impl MontConfig<1> for F64 {
+            const MODULUS: [u64; 1] = [0xffff_ffff_ffff_ffc5];
+        }
+
+        let big = [0u64, 1, 58, 1 << 63, u64::MAX - 59, u64::MAX - 60];
+        for a in big.map(Fp::<F64, 1>::from_u64) {
+            for b in big.map(Fp::<F64, 1>::from_u64) {
+                assert_eq!(a.add_ct(&b), a + b);
+                assert_eq!(a.sub_ct(&b), a - b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse_paths_agree() {
+        for a in 1..101u64 {
+            let fa = Small::from_u64(a);
+            let inv = fa.inverse().unwrap();
+            assert_eq!(inv, fa.inverse_ct().unwrap());
+            assert_eq!(fa * inv, Small::one());
+        }
+        assert!(Small::zero().inverse().is_none());
+        assert!(Small::zero().inverse_ct().is_none());
+    }
+
+    #[test]
+    fn test_window_pow_matches_square_and_multiply() {
+        let exponents: [&[u64]; 5] = [
+            &[],
+            &[0],
+            &[1],
+            &[0xf0f0_0000_0000_000f],
+            &[0x0123_4567_89ab_cdef, 0, 0xffff_ffff_ffff_ffff],
+        ];
+        for a in [0u64, 1, 2, 3, 57, 100] {
+            let fa = Small::from_u64(a);
+            for exp in exponents {
+                assert_eq!(fa.pow_vartime(exp), fa.pow(exp), "{a}^{exp:?}");
+                assert_eq!(fa.pow_ct(exp), fa.pow(exp), "{a}^{exp:?}");
+            }
+        }
+    }
+}
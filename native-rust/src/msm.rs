@@ -0,0 +1,354 @@
+//! Multi-scalar multiplication with Pippenger's bucket method
+//!
+//! Scalars are split into `c`-bit windows. For each window every point is
+//! added into the bucket selected by its digit, and the buckets are combined
+//! with a running sum so that bucket `d` contributes `d` times. Windows are
+//! independent and are processed as separate Rayon tasks. The window is
+//! narrowed when its bucket array would not fit in the L2 cache.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, JsTypedArray, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+use crate::bn254::{self, G1Affine};
+use crate::ec::{Affine, Jacobian, SwCurve};
+use crate::parallel::par_map;
+use crate::zero_copy;
+
+/// Scalar width in bits for every supported curve
+pub(crate) const SCALAR_BITS: usize = 256;
+
+/// Window size in bits for an MSM of `n` terms: `max(1, floor(log2 n) - 2)`
+pub fn window_bits(n: usize) -> usize {
+    let log2 = n.max(1).ilog2() as usize;
+    log2.saturating_sub(2).max(1)
+}
+
+/// Detected L2 cache size, 0 if unknown
+static L2_CACHE_BYTES: Lazy<u64> =
+    Lazy::new(|| crate::topology::detect_memory_geometry().l2_cache_bytes);
+
+/// [`window_bits`] narrowed until the `2^c - 1` buckets of `bucket_bytes`
+/// each fit in `cache_bytes`; unlimited when `cache_bytes` is 0
+pub fn window_bits_within(n: usize, bucket_bytes: usize, cache_bytes: u64) -> usize {
+    let mut c = window_bits(n);
+    if cache_bytes > 0 {
+        while c > 1 && ((1u64 << c) - 1) * bucket_bytes as u64 > cache_bytes {
+            c -= 1;
+        }
+    }
+    c
+}
+
+/// Extract the `c`-bit digit of `scalar` starting at bit `offset`
+fn digit(scalar: &[u64; 4], offset: usize, c: usize) -> usize {
+    let limb = offset / 64;
+    let shift = offset % 64;
+    let mut bits = scalar[limb] >> shift;
+    if shift + c > 64 && limb + 1 < 4 {
+        bits |= scalar[limb + 1] << (64 - shift);
+    }
+    (bits & ((1u64 << c) - 1)) as usize
+}
+
+/// Sum of `digit_i * P_i` over one window
+fn window_sum<C: SwCurve>(
+    points: &[Affine<C>],
+    scalars: &[[u64; 4]],
+    offset: usize,
+    c: usize,
+) -> Jacobian<C> {
+    let mut buckets = vec![Jacobian::<C>::identity(); (1 << c) - 1];
+    for (point, scalar) in points.iter().zip(scalars) {
+        let d = digit(scalar, offset, c);
+        if d != 0 {
+            buckets[d - 1] = buckets[d - 1].add_affine(point);
+        }
+    }
+    // sum_d d * B_d via running suffix sums
+    let mut running = Jacobian::identity();
+    let mut acc = Jacobian::identity();
+    for bucket in buckets.iter().rev() {
+        running = running.add(bucket);
+        acc = acc.add(&running);
+    }
+    acc
+}
+
+/// `sum scalars[i] * points[i]`, scalars given as little-endian 64-bit limbs
+///
+/// With `parallel` set, windows are spread across worker threads; otherwise
+/// the whole MSM runs on the calling thread.
+pub fn pippenger<C: SwCurve>(
+    points: &[Affine<C>],
+    scalars: &[[u64; 4]],
+    parallel: bool,
+) -> Jacobian<C> {
+    debug_assert_eq!(points.len(), scalars.len());
+    let bucket_bytes = std::mem::size_of::<Jacobian<C>>();
+    let c = window_bits_within(points.len(), bucket_bytes, *L2_CACHE_BYTES);
+    let offsets: Vec<usize> = (0..SCALAR_BITS).step_by(c).collect();
+    let sums = if parallel {
+        par_map(&offsets, |&offset| window_sum(points, scalars, offset, c))
+    } else {
+        offsets
+            .iter()
+            .map(|&offset| window_sum(points, scalars, offset, c))
+            .collect()
+    };
+    // Horner over windows, most significant first
+    sums.iter().rev().fold(Jacobian::identity(), |acc, sum| {
+        let mut acc = acc;
+        for _ in 0..c {
+            acc = acc.double();
+        }
+        acc.add(sum)
+    })
+}
+
+/// Decode a 32-byte little-endian scalar into limbs
+fn parse_scalar_le(bytes: &[u8], name: &str) -> Result<[u64; 4]> {
+    if bytes.len() != 32 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected a 32-byte little-endian scalar, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(std::array::from_fn(|i| {
+        u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap())
+    }))
+}
+
+type DecodedMsm = (Vec<G1Affine>, Vec<[u64; 4]>);
+
+fn decode_bn254_inputs(
+    points: &[Vec<u8>],
+    scalars: &[Vec<u8>],
+    prefix: &str,
+) -> Result<DecodedMsm> {
+    if points.len() != scalars.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{prefix}got {} points but {} scalars",
+                points.len(),
+                scalars.len()
+            ),
+        ));
+    }
+    let points = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| bn254::decode_g1(p, &format!("{prefix}points[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let scalars = scalars
+        .iter()
+        .enumerate()
+        .map(|(i, s)| parse_scalar_le(s, &format!("{prefix}scalars[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    Ok((points, scalars))
+}
+
+/// Multi-scalar multiplication on BN254 G1
+///
+/// Points are 64-byte uncompressed affine encodings (`x || y`, big-endian
+/// coordinates) and scalars are 32-byte little-endian integers. Returns the
+/// 64-byte affine result; an empty input yields the point at infinity.
+#[napi]
+pub fn msm_bn254_g1(points: Vec<Vec<u8>>, scalars: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    crate::constant_time::require_variable_time("msm_bn254_g1")?;
+    let (points, scalars) = decode_bn254_inputs(&points, &scalars, "")?;
+    Ok(bn254::encode_g1(
+        &pippenger(&points, &scalars, true).to_affine(),
+    ))
+}
+
+/// MSM over packed 64-byte points and 32-byte little-endian scalars
+pub fn msm_packed(points: &[u8], scalars: &[u8]) -> Result<Vec<u8>> {
+    let points = zero_copy::elements(points, bn254::G1_BYTES, "points")?
+        .enumerate()
+        .map(|(i, p)| bn254::decode_g1(p, &format!("points[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let scalars = zero_copy::elements(scalars, zero_copy::FR_BYTES, "scalars")?
+        .enumerate()
+        .map(|(i, s)| parse_scalar_le(s, &format!("scalars[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    if points.len() != scalars.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("got {} points but {} scalars", points.len(), scalars.len()),
+        ));
+    }
+    Ok(bn254::encode_g1(
+        &pippenger(&points, &scalars, true).to_affine(),
+    ))
+}
+
+/// [`msm_bn254_g1`] over `Uint8Array`s of packed points and scalars, read in
+/// place
+///
+/// See [`crate::zero_copy`] for the layout and the safety contract.
+#[napi]
+pub fn msm_bn254_g1_packed(points: JsTypedArray, scalars: JsTypedArray) -> Result<Buffer> {
+    crate::constant_time::require_variable_time("msm_bn254_g1_packed")?;
+    let (points, scalars) = (points.into_value()?, scalars.into_value()?);
+    Ok(msm_packed(
+        zero_copy::bytes(&points, "points")?,
+        zero_copy::bytes(&scalars, "scalars")?,
+    )?
+    .into())
+}
+
+/// [`msm_bn254_g1_packed`] writing the 64-byte result into `output`
+#[napi]
+pub fn msm_bn254_g1_into(
+    points: JsTypedArray,
+    scalars: JsTypedArray,
+    output: JsTypedArray,
+) -> Result<()> {
+    crate::constant_time::require_variable_time("msm_bn254_g1_into")?;
+    let result = {
+        let (points, scalars) = (points.into_value()?, scalars.into_value()?);
+        msm_packed(
+            zero_copy::bytes(&points, "points")?,
+            zero_copy::bytes(&scalars, "scalars")?,
+        )?
+    };
+    let mut output = output.into_value()?;
+    let output = zero_copy::bytes_mut(&mut output, "output")?;
+    zero_copy::check_output_len(output, result.len(), "output")?;
+    output.copy_from_slice(&result);
+    Ok(())
+}
+
+/// One independent MSM in a batch
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MsmJob {
+    /// 64-byte uncompressed affine BN254 G1 points
+    pub points: Vec<Vec<u8>>,
+    /// 32-byte little-endian scalars, one per point
+    pub scalars: Vec<Vec<u8>>,
+}
+
+/// Run several independent BN254 G1 MSMs
+///
+/// Jobs and the windows within each job are all scheduled on Rayon's
+/// work-stealing pool, so a mix of large and small MSMs keeps every core
+/// busy. Results are returned in job order.
+#[napi]
+pub fn msm_bn254_g1_batch(jobs: Vec<MsmJob>) -> Result<Vec<Vec<u8>>> {
+    crate::constant_time::require_variable_time("msm_bn254_g1_batch")?;
+    let decoded = jobs
+        .iter()
+        .enumerate()
+        .map(|(i, job)| decode_bn254_inputs(&job.points, &job.scalars, &format!("jobs[{i}].")))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(par_map(&decoded, |(points, scalars)| {
+        bn254::encode_g1(&pippenger(points, scalars, true).to_affine())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::g1_generator;
+
+    fn naive(points: &[G1Affine], scalars: &[[u64; 4]]) -> G1Affine {
+        points
+            .iter()
+            .zip(scalars)
+            .fold(Jacobian::identity(), |acc, (p, s)| {
+                acc.add(&p.to_jacobian().mul_limbs(s))
+            })
+            .to_affine()
+    }
+
+    fn inputs(n: usize) -> (Vec<G1Affine>, Vec<[u64; 4]>) {
+        let g = g1_generator().to_jacobian();
+        let mut seed = 0x9e3779b97f4a7c15u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let points = (0..n).map(|_| g.mul_limbs(&[next()]).to_affine()).collect();
+        let scalars = (0..n)
+            .map(|_| [next(), next(), next(), next() >> 3])
+            .collect();
+        (points, scalars)
+    }
+
+    fn to_bytes(scalar: &[u64; 4]) -> Vec<u8> {
+        scalar.iter().flat_map(|l| l.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_window_bits() {
+        assert_eq!(window_bits(0), 1);
+        assert_eq!(window_bits(1), 1);
+        assert_eq!(window_bits(8), 1);
+        assert_eq!(window_bits(1024), 8);
+
+        // 96-byte BN254 buckets
+        assert_eq!(window_bits_within(1 << 20, 96, 0), 18);
+        assert_eq!(window_bits_within(1 << 20, 96, 16 << 20), 17);
+        assert_eq!(window_bits_within(1 << 20, 96, 2 << 20), 14);
+        assert_eq!(window_bits_within(1024, 96, 2 << 20), 8);
+        assert_eq!(window_bits_within(1024, 96, 1), 1);
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for n in [1, 2, 7, 64, 300] {
+            let (points, scalars) = inputs(n);
+            let expected = naive(&points, &scalars);
+            assert_eq!(pippenger(&points, &scalars, true).to_affine(), expected);
+            assert_eq!(pippenger(&points, &scalars, false).to_affine(), expected);
+        }
+    }
+
+    #[test]
+    fn test_napi_known_values() {
+        // 2 * G, encoded as in the Ethereum precompiles
+        let two_g = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+        let g = bn254::encode_g1(&g1_generator());
+        let out =
+            msm_bn254_g1(vec![g.clone(), g.clone()], vec![to_bytes(&[1, 0, 0, 0]); 2]).unwrap();
+        let hex: String = out.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(hex, two_g);
+        assert_eq!(msm_bn254_g1(vec![], vec![]).unwrap(), vec![0u8; 64]);
+        assert!(msm_bn254_g1(vec![g.clone()], vec![]).is_err());
+        assert!(msm_bn254_g1(vec![g.clone()], vec![vec![1u8; 31]]).is_err());
+
+        let packed_points = [g.clone(), g.clone()].concat();
+        let packed_scalars = to_bytes(&[1, 0, 0, 0]).repeat(2);
+        assert_eq!(msm_packed(&packed_points, &packed_scalars).unwrap(), out);
+        assert!(msm_packed(&packed_points[..63], &packed_scalars).is_err());
+        assert!(msm_packed(&g, &packed_scalars).is_err());
+    }
+
+    #[test]
+    fn test_batch() {
+        let jobs: Vec<MsmJob> = [3usize, 40, 0, 17]
+            .iter()
+            .map(|&n| {
+                let (points, scalars) = inputs(n);
+                MsmJob {
+                    points: points.iter().map(bn254::encode_g1).collect(),
+                    scalars: scalars.iter().map(to_bytes).collect(),
+                }
+            })
+            .collect();
+        let results = msm_bn254_g1_batch(jobs.clone()).unwrap();
+        assert_eq!(results.len(), jobs.len());
+        for (job, result) in jobs.into_iter().zip(results) {
+            assert_eq!(msm_bn254_g1(job.points, job.scalars).unwrap(), result);
+        }
+    }
+}
@@ -0,0 +1,694 @@
+//! NEON vector helpers
+//!
+//! `U32x2` / `U64x2` / `U32x4` wrap `uint32x2_t` / `uint64x2_t` /
+//! `uint32x4_t` on aarch64, and `v128` in wasm32 builds compiled with the
+//! `simd128` target feature. Other targets get a lane-by-lane emulation with
+//! identical semantics, so the vectorized algorithms built on top are
+//! compiled and tested on every host while only aarch64 and SIMD wasm
+//! builds run them on real vector registers.
+//!
+//! NEON has no 64x64-bit multiply, so Montgomery products are computed on
+//! 32-bit digits with `umlal` (`vmlal_u32`), two independent products per
+//! instruction.
+
+use crate::montgomery::{Fp, MontConfig};
+
+#[cfg(target_arch = "aarch64")]
+mod imp {
+    use std::arch::aarch64::*;
+
+    #[derive(Clone, Copy)]
+    pub struct U32x2(uint32x2_t);
+
+    #[derive(Clone, Copy)]
+    pub struct U64x2(uint64x2_t);
+
+    #[derive(Clone, Copy)]
+    pub struct U32x4(uint32x4_t);
+
+    // NEON is a baseline aarch64 feature; depending on the toolchain the
+    // register-only intrinsics are either safe or `unsafe` to call.
+    #[allow(unused_unsafe)]
+    impl U32x2 {
+        #[inline(always)]
+        pub fn new(a: u32, b: u32) -> Self {
+            let lanes = [a, b];
+            U32x2(unsafe { vld1_u32(lanes.as_ptr()) })
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x2(unsafe { vdup_n_u32(v) })
+        }
+
+        /// Lane-wise `a * b mod 2^32`
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x2(unsafe { vmul_u32(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn widen(self) -> U64x2 {
+            U64x2(unsafe { vmovl_u32(self.0) })
+        }
+    }
+
+    #[allow(unused_unsafe)]
+    impl U64x2 {
+        #[inline(always)]
+        pub fn new(a: u64, b: u64) -> Self {
+            let lanes = [a, b];
+            U64x2(unsafe { vld1q_u64(lanes.as_ptr()) })
+        }
+
+        #[inline(always)]
+        pub fn zero() -> Self {
+            U64x2(unsafe { vdupq_n_u64(0) })
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u64; 2] {
+            let mut out = [0u64; 2];
+            unsafe { vst1q_u64(out.as_mut_ptr(), self.0) };
+            out
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U64x2(unsafe { vaddq_u64(self.0, rhs.0) })
+        }
+
+        /// Lane-wise `self + a * b` with 32x32 -> 64-bit products (`umlal`)
+        #[inline(always)]
+        pub fn mul_add(self, a: U32x2, b: U32x2) -> Self {
+            U64x2(unsafe { vmlal_u32(self.0, a.0, b.0) })
+        }
+
+        /// Low 32 bits of each lane
+        #[inline(always)]
+        pub fn low32(self) -> U32x2 {
+            U32x2(unsafe { vmovn_u64(self.0) })
+        }
+
+        /// Each lane shifted right by 32
+        #[inline(always)]
+        pub fn high32(self) -> Self {
+            U64x2(unsafe { vshrq_n_u64::<32>(self.0) })
+        }
+
+        /// Each lane shifted right by `n` (`ushl` by a negative amount)
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U64x2(unsafe { vshlq_u64(self.0, vdupq_n_s64(-(n as i64))) })
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u64) -> Self {
+            U64x2(unsafe { vdupq_n_u64(v) })
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U64x2(unsafe { vsubq_u64(self.0, rhs.0) })
+        }
+
+        /// All ones in each lane where `self < rhs`, zero elsewhere
+        #[inline(always)]
+        pub fn lt_mask(self, rhs: Self) -> Self {
+            U64x2(unsafe { vcltq_u64(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U64x2(unsafe { vandq_u64(self.0, rhs.0) })
+        }
+
+        /// Each lane shifted left by 32
+        #[inline(always)]
+        pub fn shift_left32(self) -> Self {
+            U64x2(unsafe { vshlq_n_u64::<32>(self.0) })
+        }
+    }
+
+    #[allow(unused_unsafe)]
+    impl U32x4 {
+        #[inline(always)]
+        pub fn new(lanes: [u32; 4]) -> Self {
+            U32x4(unsafe { vld1q_u32(lanes.as_ptr()) })
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x4(unsafe { vdupq_n_u32(v) })
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u32; 4] {
+            let mut out = [0u32; 4];
+            unsafe { vst1q_u32(out.as_mut_ptr(), self.0) };
+            out
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U32x4(unsafe { vaddq_u32(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U32x4(unsafe { vsubq_u32(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn min(self, rhs: Self) -> Self {
+            U32x4(unsafe { vminq_u32(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U32x4(unsafe { vandq_u32(self.0, rhs.0) })
+        }
+
+        #[inline(always)]
+        pub fn shift_left(self, n: u32) -> Self {
+            U32x4(unsafe { vshlq_u32(self.0, vdupq_n_s32(n as i32)) })
+        }
+
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U32x4(unsafe { vshlq_u32(self.0, vdupq_n_s32(-(n as i32))) })
+        }
+
+        /// Lane-wise `a * b mod 2^32`
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x4(unsafe { vmulq_u32(self.0, rhs.0) })
+        }
+
+        /// Lane-wise `(a * b) >> 32`
+        #[inline(always)]
+        pub fn mul_hi(self, rhs: Self) -> Self {
+            unsafe {
+                let lo = vmull_u32(vget_low_u32(self.0), vget_low_u32(rhs.0));
+                let hi = vmull_high_u32(self.0, rhs.0);
+                U32x4(vuzp2q_u32(
+                    vreinterpretq_u32_u64(lo),
+                    vreinterpretq_u32_u64(hi),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod imp {
+    use std::arch::wasm32::*;
+
+    /// Lanes 0 and 1 of a `u32x4`; lanes 2 and 3 are ignored
+    #[derive(Clone, Copy)]
+    pub struct U32x2(v128);
+
+    #[derive(Clone, Copy)]
+    pub struct U64x2(v128);
+
+    #[derive(Clone, Copy)]
+    pub struct U32x4(v128);
+
+    impl U32x2 {
+        #[inline(always)]
+        pub fn new(a: u32, b: u32) -> Self {
+            U32x2(u32x4(a, b, 0, 0))
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x2(u32x4_splat(v))
+        }
+
+        /// Lane-wise `a * b mod 2^32`
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x2(u32x4_mul(self.0, rhs.0))
+        }
+
+        #[inline(always)]
+        pub fn widen(self) -> U64x2 {
+            U64x2(u64x2_extend_low_u32x4(self.0))
+        }
+    }
+
+    impl U64x2 {
+        #[inline(always)]
+        pub fn new(a: u64, b: u64) -> Self {
+            U64x2(u64x2(a, b))
+        }
+
+        #[inline(always)]
+        pub fn zero() -> Self {
+            U64x2(u64x2_splat(0))
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u64; 2] {
+            [
+                u64x2_extract_lane::<0>(self.0),
+                u64x2_extract_lane::<1>(self.0),
+            ]
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U64x2(u64x2_add(self.0, rhs.0))
+        }
+
+        /// Lane-wise `self + a * b` with 32x32 -> 64-bit products
+        #[inline(always)]
+        pub fn mul_add(self, a: U32x2, b: U32x2) -> Self {
+            U64x2(u64x2_add(self.0, u64x2_extmul_low_u32x4(a.0, b.0)))
+        }
+
+        /// Low 32 bits of each lane
+        #[inline(always)]
+        pub fn low32(self) -> U32x2 {
+            U32x2(u32x4_shuffle::<0, 2, 0, 2>(self.0, self.0))
+        }
+
+        /// Each lane shifted right by 32
+        #[inline(always)]
+        pub fn high32(self) -> Self {
+            U64x2(u64x2_shr(self.0, 32))
+        }
+
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U64x2(u64x2_shr(self.0, n))
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u64) -> Self {
+            U64x2(u64x2_splat(v))
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U64x2(u64x2_sub(self.0, rhs.0))
+        }
+
+        /// All ones in each lane where `self < rhs`, zero elsewhere
+        ///
+        /// wasm only compares signed 64-bit lanes, so both sides are biased
+        /// by `2^63` first.
+        #[inline(always)]
+        pub fn lt_mask(self, rhs: Self) -> Self {
+            let bias = u64x2_splat(1 << 63);
+            U64x2(i64x2_lt(v128_xor(self.0, bias), v128_xor(rhs.0, bias)))
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U64x2(v128_and(self.0, rhs.0))
+        }
+
+        /// Each lane shifted left by 32
+        #[inline(always)]
+        pub fn shift_left32(self) -> Self {
+            U64x2(u64x2_shl(self.0, 32))
+        }
+    }
+
+    impl U32x4 {
+        #[inline(always)]
+        pub fn new(lanes: [u32; 4]) -> Self {
+            U32x4(u32x4(lanes[0], lanes[1], lanes[2], lanes[3]))
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x4(u32x4_splat(v))
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u32; 4] {
+            [
+                u32x4_extract_lane::<0>(self.0),
+                u32x4_extract_lane::<1>(self.0),
+                u32x4_extract_lane::<2>(self.0),
+                u32x4_extract_lane::<3>(self.0),
+            ]
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U32x4(u32x4_add(self.0, rhs.0))
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U32x4(u32x4_sub(self.0, rhs.0))
+        }
+
+        #[inline(always)]
+        pub fn min(self, rhs: Self) -> Self {
+            U32x4(u32x4_min(self.0, rhs.0))
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U32x4(v128_and(self.0, rhs.0))
+        }
+
+        #[inline(always)]
+        pub fn shift_left(self, n: u32) -> Self {
+            U32x4(u32x4_shl(self.0, n))
+        }
+
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U32x4(u32x4_shr(self.0, n))
+        }
+
+        /// Lane-wise `a * b mod 2^32`
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x4(u32x4_mul(self.0, rhs.0))
+        }
+
+        /// Lane-wise `(a * b) >> 32`: the odd halves of the widened products
+        #[inline(always)]
+        pub fn mul_hi(self, rhs: Self) -> Self {
+            let lo = u64x2_extmul_low_u32x4(self.0, rhs.0);
+            let hi = u64x2_extmul_high_u32x4(self.0, rhs.0);
+            U32x4(u32x4_shuffle::<1, 3, 5, 7>(lo, hi))
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+mod imp {
+    #[derive(Clone, Copy)]
+    pub struct U32x2([u32; 2]);
+
+    #[derive(Clone, Copy)]
+    pub struct U64x2([u64; 2]);
+
+    #[derive(Clone, Copy)]
+    pub struct U32x4([u32; 4]);
+
+    impl U32x2 {
+        #[inline(always)]
+        pub fn new(a: u32, b: u32) -> Self {
+            U32x2([a, b])
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x2([v, v])
+        }
+
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x2([
+                self.0[0].wrapping_mul(rhs.0[0]),
+                self.0[1].wrapping_mul(rhs.0[1]),
+            ])
+        }
+
+        #[inline(always)]
+        pub fn widen(self) -> U64x2 {
+            U64x2([self.0[0] as u64, self.0[1] as u64])
+        }
+    }
+
+    impl U64x2 {
+        #[inline(always)]
+        pub fn new(a: u64, b: u64) -> Self {
+            U64x2([a, b])
+        }
+
+        #[inline(always)]
+        pub fn zero() -> Self {
+            U64x2([0, 0])
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u64; 2] {
+            self.0
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U64x2([
+                self.0[0].wrapping_add(rhs.0[0]),
+                self.0[1].wrapping_add(rhs.0[1]),
+            ])
+        }
+
+        #[inline(always)]
+        pub fn mul_add(self, a: U32x2, b: U32x2) -> Self {
+            U64x2([
+                self.0[0].wrapping_add(a.0[0] as u64 * b.0[0] as u64),
+                self.0[1].wrapping_add(a.0[1] as u64 * b.0[1] as u64),
+            ])
+        }
+
+        #[inline(always)]
+        pub fn low32(self) -> U32x2 {
+            U32x2([self.0[0] as u32, self.0[1] as u32])
+        }
+
+        #[inline(always)]
+        pub fn high32(self) -> Self {
+            U64x2([self.0[0] >> 32, self.0[1] >> 32])
+        }
+
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U64x2([self.0[0] >> n, self.0[1] >> n])
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u64) -> Self {
+            U64x2([v, v])
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U64x2([
+                self.0[0].wrapping_sub(rhs.0[0]),
+                self.0[1].wrapping_sub(rhs.0[1]),
+            ])
+        }
+
+        #[inline(always)]
+        pub fn lt_mask(self, rhs: Self) -> Self {
+            let mask = |a: u64, b: u64| if a < b { u64::MAX } else { 0 };
+            U64x2([mask(self.0[0], rhs.0[0]), mask(self.0[1], rhs.0[1])])
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U64x2([self.0[0] & rhs.0[0], self.0[1] & rhs.0[1]])
+        }
+
+        #[inline(always)]
+        pub fn shift_left32(self) -> Self {
+            U64x2([self.0[0] << 32, self.0[1] << 32])
+        }
+    }
+
+    impl U32x4 {
+        #[inline(always)]
+        pub fn new(lanes: [u32; 4]) -> Self {
+            U32x4(lanes)
+        }
+
+        #[inline(always)]
+        pub fn splat(v: u32) -> Self {
+            U32x4([v; 4])
+        }
+
+        #[inline(always)]
+        pub fn lanes(self) -> [u32; 4] {
+            self.0
+        }
+
+        #[inline(always)]
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| self.0[i].wrapping_add(rhs.0[i])))
+        }
+
+        #[inline(always)]
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| self.0[i].wrapping_sub(rhs.0[i])))
+        }
+
+        #[inline(always)]
+        pub fn min(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| self.0[i].min(rhs.0[i])))
+        }
+
+        #[inline(always)]
+        pub fn and(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| self.0[i] & rhs.0[i]))
+        }
+
+        #[inline(always)]
+        pub fn shift_left(self, n: u32) -> Self {
+            U32x4(self.0.map(|v| v << n))
+        }
+
+        #[inline(always)]
+        pub fn shift_right(self, n: u32) -> Self {
+            U32x4(self.0.map(|v| v >> n))
+        }
+
+        #[inline(always)]
+        pub fn mul_lo(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| self.0[i].wrapping_mul(rhs.0[i])))
+        }
+
+        #[inline(always)]
+        pub fn mul_hi(self, rhs: Self) -> Self {
+            U32x4(std::array::from_fn(|i| {
+                ((self.0[i] as u64 * rhs.0[i] as u64) >> 32) as u32
+            }))
+        }
+    }
+}
+
+pub use imp::{U32x2, U32x4, U64x2};
+
+/// Split four 64-bit limbs into eight 32-bit digits
+#[inline(always)]
+pub(crate) fn digits(limbs: [u64; 4]) -> [u32; 8] {
+    let mut out = [0u32; 8];
+    for (i, l) in limbs.iter().enumerate() {
+        out[2 * i] = *l as u32;
+        out[2 * i + 1] = (*l >> 32) as u32;
+    }
+    out
+}
+
+/// Two independent Montgomery products `a[k] * b[k] * 2^-256 mod p`
+///
+/// CIOS over eight 32-bit digits with both products sharing each vector
+/// instruction. `R = 2^256` for both the 4x64 and 8x32 digit layouts, so the
+/// result is in the same Montgomery form as [`Fp`]'s scalar multiplication.
+#[inline]
+pub fn mont_mul_pair<C: MontConfig<4>>(a: [Fp<C, 4>; 2], b: [Fp<C, 4>; 2]) -> [Fp<C, 4>; 2] {
+    let p = digits(C::MODULUS);
+    let inv = U32x2::splat(C::INV as u32);
+    let a0 = digits(a[0].to_montgomery_limbs());
+    let a1 = digits(a[1].to_montgomery_limbs());
+    let b0 = digits(b[0].to_montgomery_limbs());
+    let b1 = digits(b[1].to_montgomery_limbs());
+    let av: [U32x2; 8] = std::array::from_fn(|j| U32x2::new(a0[j], a1[j]));
+
+    // Each lane of t[j] holds a 32-bit digit; t[8], t[9] collect the overflow
+    let mut t = [U64x2::zero(); 10];
+    for i in 0..8 {
+        let bi = U32x2::new(b0[i], b1[i]);
+        let mut carry = U64x2::zero();
+        for j in 0..8 {
+            let sum = t[j].wrapping_add(carry).mul_add(av[j], bi);
+            t[j] = sum.low32().widen();
+            carry = sum.high32();
+        }
+        let sum = t[8].wrapping_add(carry);
+        t[8] = sum.low32().widen();
+        t[9] = sum.high32();
+
+        let m = t[0].low32().mul_lo(inv);
+        let mut carry = t[0].mul_add(m, U32x2::splat(p[0])).high32();
+        for j in 1..8 {
+            let sum = t[j].wrapping_add(carry).mul_add(m, U32x2::splat(p[j]));
+            t[j - 1] = sum.low32().widen();
+            carry = sum.high32();
+        }
+        let sum = t[8].wrapping_add(carry);
+        t[7] = sum.low32().widen();
+        t[8] = t[9].wrapping_add(sum.high32());
+    }
+
+    std::array::from_fn(|lane| {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = t[2 * i].lanes()[lane] | (t[2 * i + 1].lanes()[lane] << 32);
+        }
+        Fp::from_montgomery_reduced(limbs, t[8].lanes()[lane] != 0)
+    })
+}
+
+/// Whether this machine can run the vectorized kernels
+///
+/// True on aarch64 and in wasm32 builds with `simd128`, unless
+/// `ZK_ACCEL_DISABLE_NEON` is set.
+pub fn supported() -> bool {
+    cfg!(any(
+        target_arch = "aarch64",
+        all(target_arch = "wasm32", target_feature = "simd128")
+    )) && !crate::overrides::get().disable_neon
+}
+
+/// Whether dispatch should use the vectorized kernels: supported, and not
+/// excluded by a pinned backend (see [`crate::backend`])
+pub fn enabled() -> bool {
+    supported() && crate::backend::allows(crate::backend::Backend::Neon)
+}
+
+/// Element-wise `out[i] = a[i] * b[i]`, two products per kernel call
+pub fn mul_batch<C: MontConfig<4>>(a: &[Fp<C, 4>], b: &[Fp<C, 4>], out: &mut [Fp<C, 4>]) {
+    debug_assert!(a.len() == b.len() && a.len() == out.len());
+    let paired = a.len() & !1;
+    for ((x, y), o) in a[..paired]
+        .chunks_exact(2)
+        .zip(b[..paired].chunks_exact(2))
+        .zip(out[..paired].chunks_exact_mut(2))
+    {
+        let [p0, p1] = mont_mul_pair([x[0], x[1]], [y[0], y[1]]);
+        o[0] = p0;
+        o[1] = p1;
+    }
+    for ((x, y), o) in a[paired..].iter().zip(&b[paired..]).zip(&mut out[paired..]) {
+        *o = *x * *y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::Fr;
+
+    #[test]
+    fn test_mont_mul_pair_matches_scalar() {
+        let mut x = Fr::from_u64(0x1234_5678_9abc_def0);
+        let mut y = -Fr::from_u64(3);
+        for _ in 0..200 {
+            let z = x * y + Fr::one();
+            let [p0, p1] = mont_mul_pair([x, z], [y, x]);
+            assert_eq!(p0, x * y);
+            assert_eq!(p1, z * x);
+            x = z;
+            y = y.square() + z;
+        }
+        let [m0, m1] = mont_mul_pair([-Fr::one(), Fr::zero()], [-Fr::one(), -Fr::one()]);
+        assert_eq!(m0, Fr::one());
+        assert_eq!(m1, Fr::zero());
+    }
+
+    #[test]
+    fn test_mul_batch_odd_length() {
+        let a: Vec<Fr> = (1..=7u64)
+            .map(|v| Fr::from_u64(v) - Fr::from_u64(1 << 40))
+            .collect();
+        let b: Vec<Fr> = (1..=7u64).map(|v| Fr::from_u64(v * v + 9)).collect();
+        let mut out = vec![Fr::zero(); 7];
+        mul_batch(&a, &b, &mut out);
+        for i in 0..7 {
+            assert_eq!(out[i], a[i] * b[i]);
+        }
+    }
+}
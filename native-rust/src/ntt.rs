@@ -0,0 +1,741 @@
+//! Number-theoretic transform over the BN254 scalar field
+//!
+//! Iterative radix-2 Cooley-Tukey on a bit-reversed input. BN254's scalar
+//! field has 2-adicity 28, so transforms of up to 2^28 points are supported.
+//! Large stages are split across worker threads. The twiddle multiplications
+//! are vectorized where the CPU allows: two at a time with the NEON
+//! Montgomery kernel on aarch64, and four at a time with AVX2 on x86_64 CPUs
+//! where that measures faster than the scalar path.
+//!
+//! [`NttStream`] covers inputs too large to hold in memory at once: chunks
+//! are staged in a memory-mapped scratch file and transformed with the
+//! four-step algorithm, one `sqrt(n)`-point column or row at a time.
+
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use memmap2::MmapMut;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, JsTypedArray, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+use crate::bn254::{parse_fr, Fr};
+use crate::parallel::{num_threads, par_chunks_mut, par_zip_chunks_mut};
+use crate::zero_copy;
+
+/// Largest `k` such that `2^k` divides `r - 1`
+pub const TWO_ADICITY: u32 = 28;
+
+/// Primitive `2^28`-th root of unity, `5^((r - 1) / 2^28)`
+static ROOT_OF_UNITY: Lazy<Fr> = Lazy::new(|| {
+    Fr::from_canonical([
+        0x9bd61b6e725b19f0,
+        0x402d111e41112ed4,
+        0x00e0a7eb8ef62abc,
+        0x2a3c09f0a58a7e85,
+    ])
+    .unwrap()
+});
+
+/// Below this many elements a stage runs on the calling thread
+pub(crate) const PARALLEL_THRESHOLD: usize = 1 << 12;
+
+/// Conservative single-core butterfly throughput used to size `ntt_supported_sizes`
+const BUTTERFLIES_PER_CORE_PER_SEC: f64 = 20e6;
+
+/// Primitive `2^log_n`-th root of unity
+pub fn root_of_unity(log_n: u32) -> Fr {
+    debug_assert!(log_n <= TWO_ADICITY);
+    let mut w = *ROOT_OF_UNITY;
+    for _ in log_n..TWO_ADICITY {
+        w = w.square();
+    }
+    w
+}
+
+/// Validate a transform length and return its base-2 logarithm
+pub fn log2_size(n: usize) -> Result<u32> {
+    if n == 0 || !n.is_power_of_two() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("NTT length must be a power of two, got {n}"),
+        ));
+    }
+    let log_n = n.trailing_zeros();
+    if log_n > TWO_ADICITY {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("NTT length 2^{log_n} exceeds the BN254 maximum of 2^{TWO_ADICITY}"),
+        ));
+    }
+    Ok(log_n)
+}
+
+/// Butterfly implementation used for a transform
+///
+/// Public, with the functions taking it, only for `benches/ntt.rs`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Scalar,
+    Neon,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+/// Whether the AVX2 butterflies beat the scalar ones on this CPU
+///
+/// Scalar code gets full 64x64-bit multiplies while AVX2 works on 32-bit
+/// digits, so which one wins depends on the core. Both are timed once on a
+/// small stage and the faster is kept for the life of the process.
+#[cfg(target_arch = "x86_64")]
+static AVX2_FASTER: Lazy<bool> = Lazy::new(|| crate::avx2::enabled() && calibrate_avx2());
+
+#[cfg(target_arch = "x86_64")]
+fn calibrate_avx2() -> bool {
+    use std::time::{Duration, Instant};
+
+    const HALF: usize = 1 << 10;
+    let twiddles = powers(root_of_unity(11), HALF);
+    let mut lo = twiddles.clone();
+    let mut hi: Vec<Fr> = twiddles.iter().map(|w| w.square()).collect();
+    let mut best = [Duration::MAX; 2];
+    for _ in 0..8 {
+        for (i, kernel) in [Kernel::Scalar, Kernel::Avx2].into_iter().enumerate() {
+            let start = Instant::now();
+            butterflies(&mut lo, &mut hi, &twiddles, kernel);
+            best[i] = best[i].min(start.elapsed());
+        }
+    }
+    best[1] < best[0]
+}
+
+/// Fastest kernel the running CPU supports and the backend selection allows
+pub(crate) fn kernel() -> Kernel {
+    if crate::neon::enabled() {
+        return Kernel::Neon;
+    }
+    #[cfg(target_arch = "x86_64")]
+    if crate::backend::allows(crate::backend::Backend::Avx2) && *AVX2_FASTER {
+        return Kernel::Avx2;
+    }
+    Kernel::Scalar
+}
+
+impl Kernel {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Scalar => "scalar",
+            Kernel::Neon => "neon",
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => "avx2",
+        }
+    }
+}
+
+/// Every kernel the running CPU can execute, scalar first
+///
+/// Ignores the backend selection, so the self-test covers pinned-out kernels.
+#[doc(hidden)]
+pub fn available_kernels() -> Vec<Kernel> {
+    let mut kernels = vec![Kernel::Scalar];
+    if crate::neon::supported() {
+        kernels.push(Kernel::Neon);
+    }
+    #[cfg(target_arch = "x86_64")]
+    if crate::avx2::enabled() {
+        kernels.push(Kernel::Avx2);
+    }
+    kernels
+}
+
+/// [`ntt_in_place`] with an explicit kernel, bypassing dispatch
+#[doc(hidden)]
+pub fn ntt_in_place_with(values: &mut [Fr], invert: bool, kernel: Kernel) -> Result<()> {
+    let log_n = log2_size(values.len())?;
+    transform(values, log_n, invert, kernel);
+    Ok(())
+}
+
+/// In-place forward (or inverse, scaled by `1/n`) NTT
+pub fn ntt_in_place(values: &mut [Fr], invert: bool) -> Result<()> {
+    ntt_in_place_with(values, invert, kernel())
+}
+
+fn transform(values: &mut [Fr], log_n: u32, invert: bool, kernel: Kernel) {
+    let n = values.len();
+    if n == 1 {
+        return;
+    }
+    bit_reverse_permute(values, log_n);
+
+    let mut omega = root_of_unity(log_n);
+    if invert {
+        omega = omega.inverse().unwrap();
+    }
+    let mut len = 2;
+    while len <= n {
+        let w = omega.pow(&[(n / len) as u64]);
+        let twiddles = powers(w, len / 2);
+        stage(values, len, &twiddles, kernel);
+        len <<= 1;
+    }
+
+    if invert {
+        let n_inv = Fr::from_u64(n as u64).inverse().unwrap();
+        par_chunks_mut(values, 1, PARALLEL_THRESHOLD, |_, chunk| {
+            chunk.iter_mut().for_each(|v| *v *= n_inv);
+        });
+    }
+}
+
+fn powers(w: Fr, count: usize) -> Vec<Fr> {
+    let mut out = Vec::with_capacity(count);
+    let mut acc = Fr::one();
+    for _ in 0..count {
+        out.push(acc);
+        acc *= w;
+    }
+    out
+}
+
+fn bit_reverse_permute(values: &mut [Fr], log_n: u32) {
+    for i in 0..values.len() {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+/// One radix-2 stage over blocks of `len` elements
+fn stage(values: &mut [Fr], len: usize, twiddles: &[Fr], kernel: Kernel) {
+    let half = len / 2;
+    if values.len() / len >= num_threads() {
+        // Enough independent blocks to give every worker whole blocks
+        par_chunks_mut(values, len, PARALLEL_THRESHOLD, |_, chunk| {
+            for block in chunk.chunks_exact_mut(len) {
+                let (lo, hi) = block.split_at_mut(half);
+                butterflies(lo, hi, twiddles, kernel);
+            }
+        });
+    } else {
+        // Few large blocks: split each block's butterflies across workers
+        for block in values.chunks_exact_mut(len) {
+            let (lo, hi) = block.split_at_mut(half);
+            par_zip_chunks_mut(lo, hi, PARALLEL_THRESHOLD, |offset, lo, hi| {
+                butterflies(lo, hi, &twiddles[offset..offset + lo.len()], kernel);
+            });
+        }
+    }
+}
+
+#[inline]
+fn butterflies(lo: &mut [Fr], hi: &mut [Fr], twiddles: &[Fr], kernel: Kernel) {
+    match kernel {
+        Kernel::Scalar => butterflies_scalar(lo, hi, twiddles),
+        Kernel::Neon => butterflies_neon(lo, hi, twiddles),
+        // SAFETY: `Kernel::Avx2` is only selected after CPUID reports AVX2
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx2 => unsafe { butterflies_avx2(lo, hi, twiddles) },
+    }
+}
+
+fn butterflies_scalar(lo: &mut [Fr], hi: &mut [Fr], twiddles: &[Fr]) {
+    for ((a, b), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles) {
+        let t = *b * *w;
+        *b = *a - t;
+        *a += t;
+    }
+}
+
+/// Butterflies with the twiddle products computed pairwise in NEON lanes
+fn butterflies_neon(lo: &mut [Fr], hi: &mut [Fr], twiddles: &[Fr]) {
+    let paired = lo.len() & !1;
+    let (lo_pairs, lo_rest) = lo.split_at_mut(paired);
+    let (hi_pairs, hi_rest) = hi.split_at_mut(paired);
+    for ((a, b), w) in lo_pairs
+        .chunks_exact_mut(2)
+        .zip(hi_pairs.chunks_exact_mut(2))
+        .zip(twiddles.chunks_exact(2))
+    {
+        let [t0, t1] = crate::neon::mont_mul_pair([b[0], b[1]], [w[0], w[1]]);
+        b[0] = a[0] - t0;
+        b[1] = a[1] - t1;
+        a[0] += t0;
+        a[1] += t1;
+    }
+    butterflies_scalar(lo_rest, hi_rest, &twiddles[paired..]);
+}
+
+/// Butterflies with the twiddle products computed four at a time in AVX2 lanes
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn butterflies_avx2(lo: &mut [Fr], hi: &mut [Fr], twiddles: &[Fr]) {
+    let quads = lo.len() & !3;
+    let (lo_quads, lo_rest) = lo.split_at_mut(quads);
+    let (hi_quads, hi_rest) = hi.split_at_mut(quads);
+    for ((a, b), w) in lo_quads
+        .chunks_exact_mut(4)
+        .zip(hi_quads.chunks_exact_mut(4))
+        .zip(twiddles.chunks_exact(4))
+    {
+        let t = crate::avx2::mont_mul_x4([b[0], b[1], b[2], b[3]], [w[0], w[1], w[2], w[3]]);
+        for k in 0..4 {
+            b[k] = a[k] - t[k];
+            a[k] += t[k];
+        }
+    }
+    butterflies_scalar(lo_rest, hi_rest, &twiddles[quads..]);
+}
+
+/// Forward or inverse NTT of BN254 scalar field elements
+///
+/// Coefficients are 32-byte big-endian canonical encodings and the length
+/// must be a power of two (at most 2^28). The inverse transform includes
+/// the `1/n` scaling, so `ntt(ntt(a), true) == a`.
+#[napi]
+pub fn ntt_bn254(coeffs: Vec<Vec<u8>>, invert: bool) -> Result<Vec<Vec<u8>>> {
+    log2_size(coeffs.len())?;
+    let mut values = coeffs
+        .iter()
+        .enumerate()
+        .map(|(i, c)| parse_fr(c, &format!("coeffs[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    ntt_in_place(&mut values, invert)?;
+    Ok(values.iter().map(|v| v.to_be_bytes()).collect())
+}
+
+/// Transform of packed 32-byte big-endian coefficients
+pub fn ntt_packed(coeffs: &[u8], invert: bool) -> Result<Vec<Fr>> {
+    let mut values = zero_copy::parse_packed_fr(coeffs, "coeffs")?;
+    ntt_in_place(&mut values, invert)?;
+    Ok(values)
+}
+
+/// [`ntt_bn254`] over a `Uint8Array` of packed coefficients, read in place
+///
+/// See [`crate::zero_copy`] for the layout and the safety contract.
+#[napi]
+pub fn ntt_bn254_packed(coeffs: JsTypedArray, invert: bool) -> Result<Buffer> {
+    let coeffs = coeffs.into_value()?;
+    let values = ntt_packed(zero_copy::bytes(&coeffs, "coeffs")?, invert)?;
+    Ok(zero_copy::encode_packed_fr(&values).into())
+}
+
+/// [`ntt_bn254_packed`] writing the packed result into `output`
+///
+/// `output` must be exactly as long as `coeffs` and may be the same array.
+#[napi]
+pub fn ntt_bn254_into(coeffs: JsTypedArray, invert: bool, output: JsTypedArray) -> Result<()> {
+    let values = {
+        let coeffs = coeffs.into_value()?;
+        ntt_packed(zero_copy::bytes(&coeffs, "coeffs")?, invert)?
+    };
+    let mut output = output.into_value()?;
+    zero_copy::write_packed_fr(
+        zero_copy::bytes_mut(&mut output, "output")?,
+        &values,
+        "output",
+    )
+}
+
+/// Power-of-two NTT sizes this machine should finish in under a second
+///
+/// Estimated from a conservative per-core butterfly rate scaled by the
+/// performance core count; always capped at the field's 2-adicity.
+#[napi]
+pub fn ntt_supported_sizes() -> Vec<u32> {
+    supported_sizes(num_threads() as u32)
+}
+
+fn supported_sizes(cores: u32) -> Vec<u32> {
+    let budget = BUTTERFLIES_PER_CORE_PER_SEC * cores.max(1) as f64;
+    (0..=TWO_ADICITY)
+        .take_while(|&k| {
+            // k stages of n/2 butterflies each
+            let butterflies = k as f64 * (1u64 << k) as f64 / 2.0;
+            butterflies < budget
+        })
+        .map(|k| 1u32 << k)
+        .collect()
+}
+
+/// Bytes per element in the scratch file: Montgomery limbs, little-endian
+const SCRATCH_ELEMENT_BYTES: usize = 32;
+
+/// Columns gathered per pass of the four-step column transforms, so each
+/// row contributes one contiguous run instead of a single element
+const COLUMN_BLOCK: usize = 64;
+
+/// Distinguishes default scratch files of streams in the same process
+static SCRATCH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn read_slot(scratch: &[u8], i: usize) -> Fr {
+    let bytes = &scratch[i * SCRATCH_ELEMENT_BYTES..(i + 1) * SCRATCH_ELEMENT_BYTES];
+    let limbs =
+        std::array::from_fn(|k| u64::from_le_bytes(bytes[8 * k..8 * k + 8].try_into().unwrap()));
+    Fr::from_montgomery_limbs(limbs).expect("scratch holds reduced elements")
+}
+
+fn write_slot(scratch: &mut [u8], i: usize, value: Fr) {
+    let bytes = &mut scratch[i * SCRATCH_ELEMENT_BYTES..(i + 1) * SCRATCH_ELEMENT_BYTES];
+    for (k, limb) in value.to_montgomery_limbs().iter().enumerate() {
+        bytes[8 * k..8 * k + 8].copy_from_slice(&limb.to_le_bytes());
+    }
+}
+
+/// Four-step forward NTT of the `2^log_n` elements in `scratch`
+///
+/// The input is read as an `n1 x n2` row-major matrix. After `n2` column
+/// transforms of length `n1`, a twiddle by `ω^(row·col)` and `n1` row
+/// transforms of length `n2`, output `k1 + n1·k2` sits in slot `k1·n2 + k2`.
+fn four_step(scratch: &mut [u8], log_n: u32) {
+    let log_n1 = log_n / 2;
+    let (n1, n2) = (1usize << log_n1, 1usize << (log_n - log_n1));
+    let omega = root_of_unity(log_n);
+
+    for first in (0..n2).step_by(COLUMN_BLOCK) {
+        let width = COLUMN_BLOCK.min(n2 - first);
+        let mut columns = vec![Vec::with_capacity(n1); width];
+        for row in 0..n1 {
+            for (c, column) in columns.iter_mut().enumerate() {
+                column.push(read_slot(scratch, row * n2 + first + c));
+            }
+        }
+        for (c, column) in columns.iter_mut().enumerate() {
+            ntt_in_place(column, false).expect("column length is a power of two");
+            let twiddles = powers(omega.pow(&[(first + c) as u64]), n1);
+            for (row, (value, twiddle)) in column.iter().zip(twiddles).enumerate() {
+                write_slot(scratch, row * n2 + first + c, *value * twiddle);
+            }
+        }
+    }
+
+    for row in 0..n1 {
+        let mut values: Vec<Fr> = (0..n2).map(|c| read_slot(scratch, row * n2 + c)).collect();
+        ntt_in_place(&mut values, false).expect("row length is a power of two");
+        for (c, value) in values.into_iter().enumerate() {
+            write_slot(scratch, row * n2 + c, value);
+        }
+    }
+}
+
+/// Memory-mapped scratch file, removed when dropped
+struct Scratch {
+    path: PathBuf,
+    map: Option<MmapMut>,
+}
+
+impl Scratch {
+    fn create(path: PathBuf, len: usize) -> Result<Self> {
+        let io_error = |e: std::io::Error| {
+            Error::new(
+                Status::GenericFailure,
+                format!("NTT scratch file {}: {e}", path.display()),
+            )
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(io_error)?;
+        file.set_len(len as u64).map_err(io_error)?;
+        // SAFETY: the file was just created for this stream and is not
+        // shared; it is only resized or removed after the map is dropped
+        let map = unsafe { MmapMut::map_mut(&file) }.map_err(io_error)?;
+        Ok(Scratch {
+            path,
+            map: Some(map),
+        })
+    }
+
+    fn bytes(&mut self) -> &mut [u8] {
+        self.map.as_mut().expect("map is only taken on drop")
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        // Unmap first so the removal also succeeds on Windows
+        self.map.take();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Forward NTT over coefficients pushed in chunks
+///
+/// Chunks are staged in a memory-mapped scratch file (in the system temp
+/// directory unless `set_scratch_path` is called before the first chunk)
+/// that is removed once the stream is finalized or dropped. Output matches
+/// `ntt_bn254(coeffs, false)` on the concatenated chunks.
+#[napi]
+pub struct NttStream {
+    total_size: usize,
+    chunk_size: usize,
+    received: usize,
+    scratch_path: Option<PathBuf>,
+    scratch: Option<Scratch>,
+    finalized: bool,
+}
+
+#[napi]
+impl NttStream {
+    /// Expect `total_size` coefficients in chunks of `chunk_size`
+    #[napi(constructor)]
+    pub fn new(total_size: u32, chunk_size: u32) -> Result<Self> {
+        log2_size(total_size as usize)?;
+        if chunk_size == 0 || !total_size.is_multiple_of(chunk_size) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("chunk size {chunk_size} must divide the NTT length {total_size}"),
+            ));
+        }
+        Ok(NttStream {
+            total_size: total_size as usize,
+            chunk_size: chunk_size as usize,
+            received: 0,
+            scratch_path: None,
+            scratch: None,
+            finalized: false,
+        })
+    }
+
+    /// Create the scratch file at `path` instead of the temp directory
+    ///
+    /// Any existing file there is overwritten, then removed when the stream
+    /// finishes.
+    #[napi]
+    pub fn set_scratch_path(&mut self, path: String) -> Result<()> {
+        if self.scratch.is_some() || self.finalized {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "scratch path must be set before the first chunk".to_string(),
+            ));
+        }
+        self.scratch_path = Some(PathBuf::from(path));
+        Ok(())
+    }
+
+    /// Append the next `chunk_size` coefficients, 32-byte big-endian each
+    #[napi]
+    pub fn push_chunk(&mut self, coeffs: Vec<Vec<u8>>) -> Result<()> {
+        self.check_open()?;
+        if self.received == self.total_size {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("all {} coefficients were already pushed", self.total_size),
+            ));
+        }
+        if coeffs.len() != self.chunk_size {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "expected a chunk of {} coefficients, got {}",
+                    self.chunk_size,
+                    coeffs.len()
+                ),
+            ));
+        }
+        let values = coeffs
+            .iter()
+            .enumerate()
+            .map(|(i, c)| parse_fr(c, &format!("coeffs[{}]", self.received + i)))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.scratch.is_none() {
+            let path = self.scratch_path.clone().unwrap_or_else(|| {
+                std::env::temp_dir().join(format!(
+                    "zk-accelerate-ntt-{}-{}.scratch",
+                    std::process::id(),
+                    SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed)
+                ))
+            });
+            self.scratch = Some(Scratch::create(
+                path,
+                self.total_size * SCRATCH_ELEMENT_BYTES,
+            )?);
+        }
+        let scratch = self.scratch.as_mut().unwrap().bytes();
+        for (i, value) in values.into_iter().enumerate() {
+            write_slot(scratch, self.received + i, value);
+        }
+        self.received += self.chunk_size;
+        Ok(())
+    }
+
+    /// Run the transform once every chunk has arrived and return its output
+    #[napi]
+    pub fn finalize(&mut self) -> Result<Vec<Vec<u8>>> {
+        self.check_open()?;
+        if self.received != self.total_size {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "received {} of {} coefficients",
+                    self.received, self.total_size
+                ),
+            ));
+        }
+        self.finalized = true;
+        let mut scratch = self.scratch.take().expect("a full stream has scratch");
+        let log_n = self.total_size.trailing_zeros();
+        let bytes = scratch.bytes();
+        four_step(bytes, log_n);
+
+        let (n1, n2) = (1usize << (log_n / 2), 1usize << (log_n - log_n / 2));
+        Ok((0..self.total_size)
+            .map(|k| read_slot(bytes, (k % n1) * n2 + k / n1).to_be_bytes())
+            .collect())
+    }
+
+    fn check_open(&self) -> Result<()> {
+        if self.finalized {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "NTT stream was already finalized".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(n: usize, seed: u64) -> Vec<Fr> {
+        let mut x = Fr::from_u64(seed);
+        (0..n)
+            .map(|i| {
+                x = x.square() + Fr::from_u64(i as u64 + 7);
+                x
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_known_transform() {
+        // NTT of [1, 2, 3, 4], computed with a Python reference
+        let input: Vec<Vec<u8>> = (1..=4).map(|v| Fr::from_u64(v).to_be_bytes()).collect();
+        let out = ntt_bn254(input.clone(), false).unwrap();
+        assert_eq!(out[0], Fr::from_u64(10).to_be_bytes());
+        assert_eq!(out[2], (-Fr::from_u64(2)).to_be_bytes());
+        assert_eq!(
+            format!("{:?}", parse_fr(&out[1], "out").unwrap()),
+            "0x00000000000000016789af3a83522eb1969386a2f88c094a419fe246c11f9394"
+        );
+        assert_eq!(ntt_bn254(out, true).unwrap(), input);
+    }
+
+    #[test]
+    fn test_round_trip_parallel_sizes() {
+        for log_n in [1u32, 5, 13] {
+            let input = pseudo_random(1 << log_n, log_n as u64);
+            let mut values = input.clone();
+            ntt_in_place(&mut values, false).unwrap();
+            ntt_in_place(&mut values, true).unwrap();
+            assert_eq!(values, input);
+        }
+    }
+
+    #[test]
+    fn test_vector_and_scalar_paths_agree() {
+        let _guard = crate::backend::tests::lock_dispatch();
+        // 2^9 exercises the vector loops; 2^1 leaves only scalar remainders
+        for log_n in [1, 9] {
+            let input = pseudo_random(1 << log_n, 99);
+            let mut scalar = input.clone();
+            transform(&mut scalar, log_n, false, Kernel::Scalar);
+
+            let mut paired = input.clone();
+            transform(&mut paired, log_n, false, Kernel::Neon);
+            assert_eq!(scalar, paired);
+
+            #[cfg(target_arch = "x86_64")]
+            if crate::avx2::enabled() {
+                let mut quads = input;
+                transform(&mut quads, log_n, false, Kernel::Avx2);
+                assert_eq!(scalar, quads);
+            }
+        }
+        #[cfg(target_arch = "x86_64")]
+        {
+            assert_eq!(kernel() == Kernel::Avx2, *AVX2_FASTER);
+            if !crate::detect_rust_capabilities().has_avx2 {
+                assert_eq!(kernel(), Kernel::Scalar);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_power_of_two() {
+        let input: Vec<Vec<u8>> = (0..3).map(|v| Fr::from_u64(v).to_be_bytes()).collect();
+        assert!(ntt_bn254(input, false).is_err());
+        assert!(ntt_bn254(vec![], false).is_err());
+    }
+
+    #[test]
+    fn test_supported_sizes() {
+        let sizes = ntt_supported_sizes();
+        assert_eq!(sizes[0], 1);
+        assert!(sizes.windows(2).all(|w| w[1] == 2 * w[0]));
+        assert!(supported_sizes(64).len() >= supported_sizes(1).len());
+        assert!(*supported_sizes(u32::MAX).last().unwrap() == 1 << TWO_ADICITY);
+    }
+
+    #[test]
+    fn test_stream_matches_in_memory() {
+        let dir = std::env::temp_dir();
+        for (log_n, chunk) in [(0u32, 1u32), (3, 2), (10, 1 << 7), (13, 1 << 13)] {
+            let input = pseudo_random(1 << log_n, 7 + log_n as u64);
+            let mut expected = input.clone();
+            ntt_in_place(&mut expected, false).unwrap();
+
+            let mut stream = NttStream::new(1 << log_n, chunk).unwrap();
+            let path = dir.join(format!("ntt-stream-test-{}-{log_n}", std::process::id()));
+            stream
+                .set_scratch_path(path.to_string_lossy().into_owned())
+                .unwrap();
+            for part in input.chunks(chunk as usize) {
+                stream
+                    .push_chunk(part.iter().map(|v| v.to_be_bytes()).collect())
+                    .unwrap();
+                assert!(path.exists());
+            }
+            let out = stream.finalize().unwrap();
+            assert!(!path.exists());
+            let expected: Vec<Vec<u8>> = expected.iter().map(|v| v.to_be_bytes()).collect();
+            assert_eq!(out, expected, "2^{log_n}");
+            assert!(stream.finalize().is_err());
+        }
+
+        assert!(NttStream::new(12, 4).is_err());
+        assert!(NttStream::new(16, 3).is_err());
+        assert!(NttStream::new(16, 0).is_err());
+        let mut stream = NttStream::new(4, 2).unwrap();
+        assert!(stream.push_chunk(vec![Fr::one().to_be_bytes()]).is_err());
+        stream.push_chunk(vec![vec![0; 32]; 2]).unwrap();
+        assert!(stream.set_scratch_path("elsewhere".to_string()).is_err());
+        assert!(stream.finalize().is_err());
+    }
+
+    #[test]
+    fn test_packed_matches_array_api() {
+        let input = pseudo_random(16, 3);
+        let coeffs: Vec<Vec<u8>> = input.iter().map(|v| v.to_be_bytes()).collect();
+        let expected = ntt_bn254(coeffs.clone(), false).unwrap();
+        let packed = ntt_packed(&coeffs.concat(), false).unwrap();
+        assert_eq!(zero_copy::encode_packed_fr(&packed), expected.concat());
+        assert!(ntt_packed(&coeffs.concat()[..31 * 16], false).is_err());
+        assert!(ntt_packed(&coeffs[..3].concat(), false).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Environment-variable overrides that force hardware features off
+//!
+//! `ZK_ACCEL_DISABLE_SME`, `ZK_ACCEL_DISABLE_AMX`, `ZK_ACCEL_DISABLE_NEON`
+//! and `ZK_ACCEL_DISABLE_GPU` are read the first time any detection or
+//! dispatch code asks for them, and stay fixed until [`reload`] is called
+//! (which `refresh_capabilities` does). `1`, `true`, `yes` and `on` (any
+//! case) enable an override.
+
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+pub const DISABLE_SME: &str = "ZK_ACCEL_DISABLE_SME";
+pub const DISABLE_AMX: &str = "ZK_ACCEL_DISABLE_AMX";
+pub const DISABLE_NEON: &str = "ZK_ACCEL_DISABLE_NEON";
+pub const DISABLE_GPU: &str = "ZK_ACCEL_DISABLE_GPU";
+
+/// Features forced off by the environment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub disable_sme: bool,
+    pub disable_amx: bool,
+    pub disable_neon: bool,
+    pub disable_gpu: bool,
+}
+
+static OVERRIDES: Lazy<RwLock<Overrides>> = Lazy::new(|| RwLock::new(Overrides::from_env()));
+
+/// Overrides captured from the process environment
+pub fn get() -> Overrides {
+    *OVERRIDES.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Re-read the environment and make the result current
+pub fn reload() -> Overrides {
+    let overrides = Overrides::from_env();
+    *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = overrides;
+    overrides
+}
+
+/// Whether an environment value switches an override on
+pub fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+impl Overrides {
+    /// Read the current environment (uncached)
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Build from an arbitrary variable lookup
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |name| lookup(name).is_some_and(|v| is_truthy(&v));
+        Overrides {
+            disable_sme: flag(DISABLE_SME),
+            disable_amx: flag(DISABLE_AMX),
+            disable_neon: flag(DISABLE_NEON),
+            disable_gpu: flag(DISABLE_GPU),
+        }
+    }
+
+    /// Whether any feature is forced off
+    pub fn any(&self) -> bool {
+        self.disable_sme || self.disable_amx || self.disable_neon || self.disable_gpu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truthy_values() {
+        for v in ["1", "true", "TRUE", "Yes", " on "] {
+            assert!(is_truthy(v), "{v}");
+        }
+        for v in ["", "0", "false", "no", "off", "2"] {
+            assert!(!is_truthy(v), "{v}");
+        }
+    }
+
+    #[test]
+    fn test_from_lookup() {
+        let overrides = Overrides::from_lookup(|name| match name {
+            DISABLE_SME => Some("yes".to_string()),
+            DISABLE_NEON => Some("0".to_string()),
+            DISABLE_GPU => Some("1".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            overrides,
+            Overrides {
+                disable_sme: true,
+                disable_amx: false,
+                disable_neon: false,
+                disable_gpu: true,
+            }
+        );
+        assert!(overrides.any());
+        assert!(!Overrides::from_lookup(|_| None).any());
+    }
+
+    #[test]
+    fn test_from_env() {
+        std::env::set_var(DISABLE_AMX, "true");
+        let overrides = Overrides::from_env();
+        std::env::remove_var(DISABLE_AMX);
+        assert!(overrides.disable_amx);
+    }
+}
@@ -0,0 +1,190 @@
+//! Ate pairings on BLS12 and BN curves with a sextic twist
+//!
+//! BLS12 curves use the optimal ate pairing, whose Miller loop runs over the
+//! curve parameter `x`. BN curves use the plain ate pairing with loop count
+//! `t - 1 = 6x^2`: twice as long as their optimal ate loop, but with no
+//! Frobenius correction steps at the end.
+//!
+//! G2 points live on the twist `E'(Fp2)`. The Miller loop runs in affine
+//! twist coordinates, so each step costs one Fp2 inversion, and every line
+//! is evaluated directly as an Fp12 element. Lines are scaled by constants of
+//! proper subfields, which the final exponentiation maps to one.
+//!
+//! The final exponentiation splits `(p^12 - 1) / r` into the easy part
+//! `(p^6 - 1)(p^2 + 1)`, done with Frobenius maps and one inversion, and the
+//! hard part `(p^4 - p^2 + 1) / r` (or a fixed multiple of it coprime to `r`,
+//! which is still a non-degenerate bilinear pairing), done by
+//! exponentiation with cyclotomic squarings.
+
+use crate::ec::{Affine, SwCurve};
+use crate::montgomery::{Field, Fp};
+use crate::tower::{Fp12, Fp2, TowerConfig};
+
+/// Which way the sextic twist maps onto `E(Fp12)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Twist {
+    /// `E': y^2 = x^3 + b ξ`, untwisted by `(x / w^2, y / w^3)`
+    M,
+    /// `E': y^2 = x^3 + b / ξ`, untwisted by `(x w^2, y w^3)`
+    D,
+}
+
+/// Curve-specific pairing parameters
+pub trait PairingConfig<const N: usize>: 'static {
+    type Tower: TowerConfig<N>;
+    type G1: SwCurve<Base = Fp<<Self::Tower as TowerConfig<N>>::Base, N>>;
+    type G2: SwCurve<Base = Fp2<Self::Tower, N>>;
+
+    const TWIST: Twist;
+    /// Absolute value of the Miller loop count as little-endian 64-bit limbs
+    const LOOP_COUNT: &'static [u64];
+    /// Whether the loop count is negative
+    const LOOP_IS_NEGATIVE: bool;
+    /// `(p^4 - p^2 + 1) / r`, or a multiple of it coprime to `r`, as
+    /// little-endian 64-bit limbs
+    const FINAL_EXP_HARD: &'static [u64];
+}
+
+type Gt<P, const N: usize> = Fp12<<P as PairingConfig<N>>::Tower, N>;
+
+/// A `(G1, G2)` pair fed to the Miller loop
+pub type PairingInput<P, const N: usize> = (
+    Affine<<P as PairingConfig<N>>::G1>,
+    Affine<<P as PairingConfig<N>>::G2>,
+);
+
+/// Line through `t` with twist slope `lambda`, evaluated at `p`
+fn line<P: PairingConfig<N>, const N: usize>(
+    t: &Affine<P::G2>,
+    lambda: Fp2<P::Tower, N>,
+    p: &Affine<P::G1>,
+) -> Gt<P, N> {
+    let xp = Fp2::from_base(p.x);
+    let yp = Fp2::from_base(p.y);
+    let constant = lambda * t.x - t.y;
+    let zero = Fp2::zero();
+    // Coefficients of 1, w, ..., w^5 (M-type lines are scaled by w^3)
+    match P::TWIST {
+        Twist::M => Fp12::from_w_coeffs([constant, zero, -(lambda * xp), yp, zero, zero]),
+        Twist::D => Fp12::from_w_coeffs([yp, -(lambda * xp), zero, constant, zero, zero]),
+    }
+}
+
+/// Double `t`, returning the tangent line evaluated at `p`
+///
+/// Only points outside the prime-order subgroup reach `T = O` or a
+/// vertical tangent. Vertical lines lie in Fp6, which the final
+/// exponentiation sends to one, so those steps contribute one.
+fn double_step<P: PairingConfig<N>, const N: usize>(
+    t: &mut Affine<P::G2>,
+    p: &Affine<P::G1>,
+) -> Gt<P, N> {
+    if t.infinity || t.y.is_zero() {
+        *t = Affine::identity();
+        return Gt::<P, N>::one();
+    }
+    let x2 = t.x.square();
+    let lambda = (x2.double() + x2) * t.y.double().inverse().expect("y is non-zero");
+    let l = line::<P, N>(t, lambda, p);
+    let x3 = lambda.square() - t.x.double();
+    let y3 = lambda * (t.x - x3) - t.y;
+    *t = Affine::new(x3, y3);
+    l
+}
+
+/// Add `q` to `t`, returning the chord evaluated at `p`
+///
+/// `T = ±Q` and `T = O` only happen outside the prime-order subgroup; as
+/// in [`double_step`], their vertical lines contribute one.
+fn add_step<P: PairingConfig<N>, const N: usize>(
+    t: &mut Affine<P::G2>,
+    q: &Affine<P::G2>,
+    p: &Affine<P::G1>,
+) -> Gt<P, N> {
+    if t.infinity {
+        *t = *q;
+        return Gt::<P, N>::one();
+    }
+    if t.x == q.x {
+        if t.y == q.y {
+            return double_step::<P, N>(t, p);
+        }
+        *t = Affine::identity();
+        return Gt::<P, N>::one();
+    }
+    let lambda = (q.y - t.y) * (q.x - t.x).inverse().expect("T != ±Q");
+    let l = line::<P, N>(t, lambda, p);
+    let x3 = lambda.square() - t.x - q.x;
+    let y3 = lambda * (t.x - x3) - t.y;
+    *t = Affine::new(x3, y3);
+    l
+}
+
+/// Product of Miller loops `prod f_{x,Q_i}(P_i)`; pairs with infinity are skipped
+pub fn multi_miller_loop<P: PairingConfig<N>, const N: usize>(
+    pairs: &[PairingInput<P, N>],
+) -> Gt<P, N> {
+    let pairs: Vec<_> = pairs
+        .iter()
+        .filter(|(p, q)| !p.infinity && !q.infinity)
+        .collect();
+    let mut ts: Vec<Affine<P::G2>> = pairs.iter().map(|(_, q)| *q).collect();
+    let mut f = Gt::<P, N>::one();
+    let bit_len = P::LOOP_COUNT.len() * 64
+        - P::LOOP_COUNT
+            .last()
+            .map_or(64, |l| l.leading_zeros() as usize);
+    let bit = |i: usize| (P::LOOP_COUNT[i / 64] >> (i % 64)) & 1 == 1;
+    for i in (0..bit_len.saturating_sub(1)).rev() {
+        f = f.square();
+        for ((p, _), t) in pairs.iter().zip(ts.iter_mut()) {
+            f *= double_step::<P, N>(t, p);
+        }
+        if bit(i) {
+            for ((p, q), t) in pairs.iter().zip(ts.iter_mut()) {
+                f *= add_step::<P, N>(t, q, p);
+            }
+        }
+    }
+    if P::LOOP_IS_NEGATIVE {
+        f = f.conjugate();
+    }
+    f
+}
+
+/// Raise a Miller loop output to `(p^12 - 1) / r`
+pub fn final_exponentiation<P: PairingConfig<N>, const N: usize>(f: &Gt<P, N>) -> Gt<P, N> {
+    final_exponentiation_with(f, P::FINAL_EXP_HARD)
+}
+
+/// Raise `f` to `(p^6 - 1)(p^2 + 1) hard`, given the hard part's
+/// little-endian limbs
+///
+/// Zero maps to zero; every other element lands in the cyclotomic subgroup.
+pub fn final_exponentiation_with<T: TowerConfig<N>, const N: usize>(
+    f: &Fp12<T, N>,
+    hard: &[u64],
+) -> Fp12<T, N> {
+    let Some(inv) = f.inverse() else {
+        return Fp12::zero();
+    };
+    // f^(p^6 - 1), then ^(p^2 + 1)
+    let f1 = f.conjugate() * inv;
+    let f2 = f1.frobenius().frobenius() * f1;
+    f2.cyclotomic_pow(hard)
+}
+
+/// The reduced pairing `e(p, q)`
+pub fn pairing<P: PairingConfig<N>, const N: usize>(
+    p: &Affine<P::G1>,
+    q: &Affine<P::G2>,
+) -> Gt<P, N> {
+    final_exponentiation::<P, N>(&multi_miller_loop::<P, N>(&[(*p, *q)]))
+}
+
+/// Whether `prod e(P_i, Q_i) == 1`, sharing one final exponentiation
+pub fn pairing_product_is_one<P: PairingConfig<N>, const N: usize>(
+    pairs: &[PairingInput<P, N>],
+) -> bool {
+    final_exponentiation::<P, N>(&multi_miller_loop::<P, N>(pairs)) == Gt::<P, N>::one()
+}
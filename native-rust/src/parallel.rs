@@ -0,0 +1,174 @@
+//! Data-parallel helpers on the Rayon global pool
+//!
+//! Kernels split their input into at most one chunk per worker and hand the
+//! chunks to Rayon, falling back to the calling thread for small inputs
+//! where splitting would cost more than it saves.
+//!
+//! Workers are sized to the performance cores: on hybrid CPUs, splitting a
+//! kernel evenly across efficiency cores leaves every other chunk waiting on
+//! the slowest one. The base clock adjusts that count. Fast cores run out
+//! of memory bandwidth and all-core boost headroom before they run out of
+//! cores, while on slow cores every extra worker still pays off.
+//! `configure_thread_pool()` in `threading` overrides the count and sets the
+//! worker stack size.
+
+use rayon::prelude::*;
+
+use crate::topology::CoreCounts;
+
+/// Base clock at or above which a quarter of the performance cores are left idle
+const FAST_CORE_MHZ: u32 = 3500;
+/// Base clock below which efficiency cores are used as workers too
+const SLOW_CORE_MHZ: u32 = 2000;
+
+/// Worker count from the detected core counts and base clock
+pub(crate) fn detected_threads() -> usize {
+    threads_for(
+        crate::topology::detect_core_counts(),
+        crate::frequency::detect_cpu_frequency().base_mhz,
+    )
+}
+
+/// Worker count for the given cores and base clock (0 when unknown)
+fn threads_for(cores: CoreCounts, base_mhz: u32) -> usize {
+    let performance = cores.performance.max(1) as usize;
+    match base_mhz {
+        0 => performance,
+        mhz if mhz >= FAST_CORE_MHZ => (performance - performance / 4).max(1),
+        mhz if mhz < SLOW_CORE_MHZ => performance + cores.efficiency as usize,
+        _ => performance,
+    }
+}
+
+/// Number of worker threads used by parallel kernels
+///
+/// The performance core count, adjusted for the base clock where it is
+/// known, unless configured otherwise. Always 1 on wasm32, where std
+/// cannot spawn threads.
+pub fn num_threads() -> usize {
+    if cfg!(target_arch = "wasm32") {
+        return 1;
+    }
+    crate::threading::pool_threads()
+}
+
+/// Chunk length giving at most `num_threads()` chunks, rounded up to `align`
+fn chunk_len(len: usize, align: usize) -> usize {
+    len.div_ceil(num_threads()).div_ceil(align).max(1) * align
+}
+
+/// Run `f(offset, chunk)` over `data` split into contiguous chunks
+///
+/// Every chunk except possibly the last is a multiple of `align` elements.
+/// Inputs shorter than `min_len` are processed inline.
+pub fn par_chunks_mut<T, F>(data: &mut [T], align: usize, min_len: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    let align = align.max(1);
+    if num_threads() <= 1 || data.len() < min_len || data.len() <= align {
+        f(0, data);
+        return;
+    }
+    let per = chunk_len(data.len(), align);
+    data.par_chunks_mut(per)
+        .enumerate()
+        .for_each(|(i, chunk)| f(i * per, chunk));
+}
+
+/// Run `f(offset, a_chunk, b_chunk)` over two equal-length slices in lockstep
+///
+/// Inputs shorter than `min_len` are processed inline.
+pub fn par_zip_chunks_mut<T, F>(a: &mut [T], b: &mut [T], min_len: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T], &mut [T]) + Sync,
+{
+    debug_assert_eq!(a.len(), b.len());
+    if num_threads() <= 1 || a.len() < min_len {
+        f(0, a, b);
+        return;
+    }
+    let per = chunk_len(a.len(), 1);
+    a.par_chunks_mut(per)
+        .zip(b.par_chunks_mut(per))
+        .enumerate()
+        .for_each(|(i, (ca, cb))| f(i * per, ca, cb));
+}
+
+/// Map `f` over `items` on worker threads, returning results in input order
+///
+/// Items are split one at a time, so idle workers steal uneven items
+/// rather than waiting on a fixed up-front split.
+pub fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if num_threads() <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+    items.par_iter().with_max_len(1).map(&f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_chunks_mut_covers_input() {
+        let mut data = vec![0usize; 10_000];
+        par_chunks_mut(&mut data, 8, 0, |offset, chunk| {
+            assert_eq!(offset % 8, 0);
+            for (i, v) in chunk.iter_mut().enumerate() {
+                *v = offset + i;
+            }
+        });
+        assert!(data.iter().enumerate().all(|(i, &v)| i == v));
+    }
+
+    #[test]
+    fn test_par_zip_chunks_mut_in_lockstep() {
+        let mut a: Vec<u64> = (0..5000).collect();
+        let mut b: Vec<u64> = (0..5000).map(|v| v * 2).collect();
+        par_zip_chunks_mut(&mut a, &mut b, 0, |_, ca, cb| {
+            for (x, y) in ca.iter_mut().zip(cb.iter_mut()) {
+                std::mem::swap(x, y);
+            }
+        });
+        assert!(a.iter().enumerate().all(|(i, &v)| v == 2 * i as u64));
+        assert!(b.iter().enumerate().all(|(i, &v)| v == i as u64));
+    }
+
+    #[test]
+    fn test_threads_for_frequency() {
+        let cores = |performance, efficiency| CoreCounts {
+            performance,
+            efficiency,
+        };
+        // Unknown and mid-range clocks use the performance cores
+        assert_eq!(threads_for(cores(8, 4), 0), 8);
+        assert_eq!(threads_for(cores(8, 4), 3000), 8);
+        // Fast cores leave a quarter idle, never dropping below one worker
+        assert_eq!(threads_for(cores(8, 4), 4200), 6);
+        assert_eq!(threads_for(cores(1, 0), 5000), 1);
+        assert_eq!(threads_for(cores(3, 0), 3500), 3);
+        // Slow cores bring the efficiency cores in
+        assert_eq!(threads_for(cores(8, 4), 1800), 12);
+        assert_eq!(threads_for(cores(0, 0), 1200), 1);
+        assert!(num_threads() >= 1);
+    }
+
+    #[test]
+    fn test_par_map_preserves_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        let squares = par_map(&items, |&v| v * v);
+        assert!(squares
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == (i * i) as u64));
+        assert!(par_map(&[] as &[u64], |&v| v).is_empty());
+    }
+}
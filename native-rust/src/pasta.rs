@@ -0,0 +1,445 @@
+//! Pallas and Vesta base field arithmetic
+//!
+//! The Pasta curves form a cycle: Pallas is defined over `Fp` and has
+//! scalar field `Fq`, Vesta the other way round. The `pallas_fp_*`
+//! functions work in Pallas's base field `p`, the `vesta_fp_*` functions in
+//! Vesta's base field `q`. Both take Buffers of packed 32-byte little-endian
+//! elements like the `bn254_fr_*` functions and run on the same Montgomery
+//! backend. Both fields have 2-adicity 32.
+
+use napi::bindgen_prelude::Buffer;
+use napi::Result;
+use napi_derive::napi;
+
+use crate::constant_time;
+use crate::field::{elementwise, inv_batch, pow_batch, Form};
+use crate::montgomery::{Fp as PrimeField, MontConfig};
+
+/// A Pasta base field
+pub trait PastaConfig: MontConfig<4> {
+    /// Field name used in errors
+    const NAME: &'static str;
+    /// Primitive `2^32`-th root of unity `5^t`, canonical limbs
+    const ROOT_OF_UNITY: [u64; 4];
+}
+
+/// Pallas base field `p`
+/// = 0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001
+#[derive(Debug, Clone, Copy)]
+pub struct FpConfig;
+
+impl MontConfig<4> for FpConfig {
+    const MODULUS: [u64; 4] = [
+        0x992d30ed00000001,
+        0x224698fc094cf91b,
+        0x0000000000000000,
+        0x4000000000000000,
+    ];
+}
+
+impl PastaConfig for FpConfig {
+    const NAME: &'static str = "Pallas base field";
+    const ROOT_OF_UNITY: [u64; 4] = [
+        0xbdad6fabd87ea32f,
+        0xea322bf2b7bb7584,
+        0x362120830561f81a,
+        0x2bce74deac30ebda,
+    ];
+}
+
+/// Element of the Pallas base field (the Vesta scalar field)
+pub type Fp = PrimeField<FpConfig, 4>;
+
+/// Vesta base field `q`
+/// = 0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001
+#[derive(Debug, Clone, Copy)]
+pub struct FqConfig;
+
+impl MontConfig<4> for FqConfig {
+    const MODULUS: [u64; 4] = [
+        0x8c46eb2100000001,
+        0x224698fc0994a8dd,
+        0x0000000000000000,
+        0x4000000000000000,
+    ];
+}
+
+impl PastaConfig for FqConfig {
+    const NAME: &'static str = "Vesta base field";
+    const ROOT_OF_UNITY: [u64; 4] = [
+        0xa70e2c1102b6d05f,
+        0x9bb97ea3c106f049,
+        0x9e5c4dfd492ae26e,
+        0x2de6a9b8746d3f58,
+    ];
+}
+
+/// Element of the Vesta base field (the Pallas scalar field)
+pub type Fq = PrimeField<FqConfig, 4>;
+
+/// Size in bytes of an encoded Pasta field element
+pub const ELEMENT_BYTES: usize = 32;
+
+/// Primitive `2^log_n`-th root of unity of a Pasta field
+pub fn root_of_unity<C: PastaConfig>(log_n: u32) -> PrimeField<C, 4> {
+    debug_assert!(log_n <= C::TWO_ADICITY);
+    let mut w = PrimeField::from_canonical(C::ROOT_OF_UNITY).expect("canonical constant");
+    for _ in log_n..C::TWO_ADICITY {
+        w = w.square();
+    }
+    w
+}
+
+/// Element-wise `a + b` over packed little-endian elements in `form`
+pub fn add<C: PastaConfig>(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        C::NAME,
+        |[x, y]: [PrimeField<C, 4>; 2]| x + y,
+    )
+}
+
+/// Element-wise `a - b` over packed little-endian elements in `form`
+pub fn sub<C: PastaConfig>(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        C::NAME,
+        |[x, y]: [PrimeField<C, 4>; 2]| x - y,
+    )
+}
+
+/// Element-wise `a * b` over packed little-endian elements in `form`
+pub fn mul<C: PastaConfig>(a: &[u8], b: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a"), (b, "b")],
+        reduce,
+        (form, form),
+        C::NAME,
+        |[x, y]: [PrimeField<C, 4>; 2]| x * y,
+    )
+}
+
+/// Element-wise `-a` over packed little-endian elements in `form`
+pub fn neg<C: PastaConfig>(a: &[u8], reduce: bool, form: Form) -> Result<Vec<u8>> {
+    elementwise(
+        [(a, "a")],
+        reduce,
+        (form, form),
+        C::NAME,
+        |[x]: [PrimeField<C, 4>; 1]| -x,
+    )
+}
+
+/// Element-wise inverse of packed little-endian elements in `form`
+pub fn inv<C: PastaConfig>(
+    values: &[u8],
+    allow_zero: bool,
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    inv_batch::<PrimeField<C, 4>>(values, allow_zero, constant_time, form, C::NAME)
+}
+
+/// `x^exponent` over packed little-endian elements in `form`
+pub fn pow<C: PastaConfig>(
+    bases: &[u8],
+    exponent: &[u8],
+    constant_time: bool,
+    form: Form,
+) -> Result<Vec<u8>> {
+    pow_batch::<C, 4>(bases, exponent, constant_time, form, C::NAME)
+}
+
+/// Element-wise `a + b` over packed 32-byte little-endian Pallas base field elements
+///
+/// `a` and `b` must be the same length. Values `>= p` are rejected unless
+/// `reduce` is set, in which case they are reduced modulo `p` first. With
+/// `in_montgomery_form` set, inputs and result are Montgomery-form limbs.
+#[napi]
+pub fn pallas_fp_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    add::<FpConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed 32-byte little-endian Pallas base field elements
+#[napi]
+pub fn pallas_fp_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    sub::<FpConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed 32-byte little-endian Pallas base field elements
+#[napi]
+pub fn pallas_fp_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    mul::<FpConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `-a` over packed 32-byte little-endian Pallas base field elements
+#[napi]
+pub fn pallas_fp_neg(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    neg::<FpConfig>(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Invert packed 32-byte little-endian Pallas base field elements
+///
+/// A zero element is an error naming its index unless `allow_zero` is set,
+/// in which case zeros map to zero. See `bn254_fr_inv_batch` for
+/// `constant_time`.
+#[napi]
+pub fn pallas_fp_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    inv::<FpConfig>(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// `x^exponent` for packed 32-byte little-endian Pallas base field elements
+///
+/// See `bn254_fr_pow_batch`.
+#[napi]
+pub fn pallas_fp_pow_batch(
+    bases: Buffer,
+    exponent: Buffer,
+    constant_time: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    pow::<FpConfig>(
+        &bases,
+        &exponent,
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// Element-wise `a + b` over packed 32-byte little-endian Vesta base field elements
+///
+/// As `pallas_fp_add`, modulo `q`.
+#[napi]
+pub fn vesta_fp_add(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    add::<FqConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a - b` over packed 32-byte little-endian Vesta base field elements
+#[napi]
+pub fn vesta_fp_sub(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    sub::<FqConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `a * b` over packed 32-byte little-endian Vesta base field elements
+#[napi]
+pub fn vesta_fp_mul(
+    a: Buffer,
+    b: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    mul::<FqConfig>(&a, &b, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Element-wise `-a` over packed 32-byte little-endian Vesta base field elements
+#[napi]
+pub fn vesta_fp_neg(
+    a: Buffer,
+    reduce: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    neg::<FqConfig>(&a, reduce.unwrap_or(false), form).map(Buffer::from)
+}
+
+/// Invert packed 32-byte little-endian Vesta base field elements
+///
+/// See `pallas_fp_inv_batch`.
+#[napi]
+pub fn vesta_fp_inv_batch(
+    values: Buffer,
+    allow_zero: Option<bool>,
+    in_montgomery_form: Option<bool>,
+    constant_time: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    inv::<FqConfig>(
+        &values,
+        allow_zero.unwrap_or(false),
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+/// `x^exponent` for packed 32-byte little-endian Vesta base field elements
+#[napi]
+pub fn vesta_fp_pow_batch(
+    bases: Buffer,
+    exponent: Buffer,
+    constant_time: Option<bool>,
+    in_montgomery_form: Option<bool>,
+) -> Result<Buffer> {
+    let form = Form::from_flag(in_montgomery_form);
+    pow::<FqConfig>(
+        &bases,
+        &exponent,
+        constant_time::resolve(constant_time),
+        form,
+    )
+    .map(Buffer::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// `[a, b, a + b, a - b, a * b, -a, 1/a, 1/b, ROOT_OF_UNITY]` from
+    /// pasta_curves 0.5, little-endian `to_repr()` encodings
+    fn check_vectors<C: PastaConfig>(vectors: [&str; 9]) {
+        let [a, b, sum, diff, product, negated, inv_a, inv_b, root] = vectors.map(hex);
+        let form = Form::Canonical;
+        assert_eq!(add::<C>(&a, &b, false, form).unwrap(), sum);
+        assert_eq!(sub::<C>(&a, &b, false, form).unwrap(), diff);
+        assert_eq!(mul::<C>(&a, &b, false, form).unwrap(), product);
+        assert_eq!(neg::<C>(&a, false, form).unwrap(), negated);
+        assert_eq!(
+            inv::<C>(&[a, b].concat(), false, false, form).unwrap(),
+            [inv_a, inv_b].concat()
+        );
+        assert_eq!(root_of_unity::<C>(32).to_le_bytes(), root);
+    }
+
+    #[test]
+    fn test_pallas_fp_pasta_curves_vectors() {
+        check_vectors::<FpConfig>([
+            "ef2f0240f5eefc82f59823e7d7bd3ef864b130b708b251d0427b6fe551863611",
+            "8990a1b2b0051390bc9bcd2384b9efb2ffffffffffffffffffffffffffffff3f",
+            "77c0a3f2b8c3e279963ba40160dee78864b130b708b251d0427b6fe551863611",
+            "679f608d311a178c54f6a2cc4f9d956765b130b708b251d0427b6fe551863611",
+            "d580611331e0dc7a93a8ef147da0f5ba88d561870bea5320dfbf9cf16a504830",
+            "12d0fdbff74130162660292224db072a9b4ecf48f74dae2fbd84901aae79c92e",
+            "24f712c7df2b815027ac7179b026486702c43b3f1b46a8c0eab33a7a44cd9804",
+            "1faa862dffa09f6d32eb3ba8e91088e20edc62bbb388672c3429f343ab6b9027",
+            "2fa37ed8ab6fadbd8475bbb7f22b32ea1af8610583202136daeb30acde74ce2b",
+        ]);
+    }
+
+    #[test]
+    fn test_vesta_fp_pasta_curves_vectors() {
+        check_vectors::<FqConfig>([
+            "d180c423d6434b55929c03832e175fe9cab90d4721599901202f8ebffc8f311b",
+            "8990a1b2e4bf2c837e4b152484b9efb2ffffffffffffffffffffffffffffff3f",
+            "591166d69918314c333f849db637087acab90d4721599901202f8ebffc8f311b",
+            "49f02271126f655ef1f98268a6f6b558cbb90d4721599901202f8ebffc8f311b",
+            "a6a966985f125f9d4ba9a3ace493e8db55353bd94e72fb5263e6f3eb52ebc409",
+            "307f3bdc4aa7fb364b0c9186cd81e7383546f2b8dea666fedfd071400370ce24",
+            "e66ed3042053b31d7fc20d39cbfef48dd063d1a7677bc07c12719e9987dca60f",
+            "52c8ecd9207d3f70af9389cecba45e3966c8fe36415bfda99edb93f506a58d37",
+            "5fd0b602112c0ea749f006c1a37eb99b6ee22a49fd4d5c9e583f6d74b8a9e62d",
+        ]);
+    }
+
+    fn check_root_of_unity<C: PastaConfig>() {
+        assert_eq!(C::TWO_ADICITY, 32);
+        // ROOT_OF_UNITY = 5^t with t = (p - 1) / 2^32
+        let t = [
+            C::MODULUS[0] >> 32 | C::MODULUS[1] << 32,
+            C::MODULUS[1] >> 32 | C::MODULUS[2] << 32,
+            C::MODULUS[2] >> 32 | C::MODULUS[3] << 32,
+            C::MODULUS[3] >> 32,
+        ];
+        let w = root_of_unity::<C>(32);
+        assert_eq!(w, PrimeField::from_u64(5).pow(&t));
+        let five = PrimeField::<C, 4>::from_u64(5).to_le_bytes();
+        let t_be: Vec<u8> = t.iter().rev().flat_map(|l| l.to_be_bytes()).collect();
+        for constant_time in [false, true] {
+            let powers = pow::<C>(&five, &t_be, constant_time, Form::Canonical).unwrap();
+            assert_eq!(powers, w.to_le_bytes());
+        }
+        let one = PrimeField::<C, 4>::one();
+        assert_ne!(root_of_unity::<C>(1), one);
+        assert_eq!(root_of_unity::<C>(1).square(), one);
+        assert_eq!(root_of_unity::<C>(0), one);
+    }
+
+    #[test]
+    fn test_roots_of_unity() {
+        check_root_of_unity::<FpConfig>();
+        check_root_of_unity::<FqConfig>();
+    }
+
+    #[test]
+    fn test_errors_name_the_field() {
+        let p = FpConfig::MODULUS
+            .iter()
+            .flat_map(|l| l.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let err = add::<FpConfig>(&p, &p, false, Form::Canonical).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "a[0]: value is not below the Pallas base field modulus"
+        );
+        // p is below q, so it is a valid Vesta element
+        assert!(add::<FqConfig>(&p, &p, false, Form::Canonical).is_ok());
+        assert_eq!(
+            neg::<FpConfig>(&p, true, Form::Canonical).unwrap(),
+            [0u8; ELEMENT_BYTES]
+        );
+        let err = inv::<FqConfig>(&[0; 64], false, false, Form::Canonical).unwrap_err();
+        assert_eq!(err.reason, "values[0]: zero has no inverse");
+        let err = mul::<FqConfig>(&[0; 32], &[0; 31], false, Form::Canonical).unwrap_err();
+        assert!(err.reason.contains("multiple of 32"), "{}", err.reason);
+    }
+}
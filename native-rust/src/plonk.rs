@@ -0,0 +1,1243 @@
+//! PLONK proof verification over BLS12-381 with KZG commitments
+//!
+//! Circuits have `W ≥ 2` advice columns `w_i` over the domain `H = <ω>` of
+//! size `n`. Every row satisfies the gate
+//!
+//! ```text
+//! Σ q_i·w_i + q_M·w_0·w_1 + q_C + PI = 0
+//! ```
+//!
+//! with `PI(X) = -Σ x_j L_j(X)` placing the instances in the first rows.
+//! Copy constraints use the PLONK permutation argument with column cosets
+//! `k_i = 7^i`. Circuits may add a lookup of `q_K·w_0` into a fixed table
+//! column `T` (which must contain 0), proven with Halo2's variant of
+//! plookup: permuted columns `A'`, `S'` and a grand product `Z_L`.
+//!
+//! The prover sends the evaluations at `ζ` of every polynomial the
+//! constraint reads, instead of a linearization polynomial; the verifier
+//! recombines them into the quotient identity and checks all of them in one
+//! batched KZG opening at `ζ`, `ζω` and, with a lookup, `ζ/ω`.
+//!
+//! Challenges come from a [`Transcript`], reduced from 64 bytes modulo the
+//! scalar field order. Points use the 96-byte uncompressed G1 and 192-byte
+//! G2 encodings of [`crate::bls12_381`] and scalars are 32-byte
+//! little-endian. The proof is the concatenation, in order, of the G1
+//! points
+//!
+//! ```text
+//! w_0..w_{W-1}, [A', S'], Z, [Z_L], t_0..t_{m-1}, W_ζ, W_ζω, [W_ζ/ω]
+//! ```
+//!
+//! and the scalars
+//!
+//! ```text
+//! at ζ:   w_i, q_i, q_M, q_C, σ_i, Z, [q_K, T, A', S', Z_L], t_j
+//! at ζω:  Z, [Z_L]
+//! at ζ/ω: [A']
+//! ```
+//!
+//! where the bracketed entries are present only with a lookup and the
+//! quotient has `m = max(W, 3)` chunks with a lookup, `W` without.
+
+use std::ops::{Add, Mul, Sub};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bls12_381::{
+    decode_g1, decode_g2_subgroup, decode_scalar_le, encode_g1, g1_generator, Bls12Pairing, Fr,
+    G1Affine, G1Projective, G2Affine, G1_BYTES, SCALAR_BYTES,
+};
+use crate::pairing::pairing_product_is_one;
+use crate::transcript::Transcript;
+
+const TRANSCRIPT_LABEL: &str = "zk-accelerate plonk";
+
+/// Multiplicative generator of the scalar field
+const GENERATOR: u64 = 7;
+
+/// Largest `k` such that `2^k` divides `r - 1`
+const TWO_ADICITY: u32 = 32;
+
+/// Most advice columns a key may declare
+pub const MAX_ADVICE_COLUMNS: u32 = 16;
+
+/// Bytes hashed into a challenge, reduced modulo `r`
+const CHALLENGE_BYTES: u32 = 64;
+
+/// PLONK verification key
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PlonkVk {
+    /// Number of rows `n`, a power of two
+    pub domain_size: u32,
+    /// Number of advice columns `W`
+    pub num_advice_columns: u32,
+    /// Gate selector commitments `q_0..q_{W-1}, q_M, q_C`
+    pub gate_commitments: Vec<Vec<u8>>,
+    /// Permutation commitments `σ_0..σ_{W-1}`
+    pub permutation_commitments: Vec<Vec<u8>>,
+    /// Lookup selector `q_K`, present together with `lookup_table_commitment`
+    pub lookup_selector_commitment: Option<Vec<u8>>,
+    /// Lookup table column `T`
+    pub lookup_table_commitment: Option<Vec<u8>>,
+    /// `[G2, [τ]_2]` from the setup
+    pub srs_g2: Vec<Vec<u8>>,
+}
+
+/// Decoded verification key
+pub(crate) struct Key {
+    n: usize,
+    omega: Fr,
+    wires: usize,
+    gates: Vec<G1Affine>,
+    sigmas: Vec<G1Affine>,
+    /// `(q_K, T)`
+    lookup: Option<(G1Affine, G1Affine)>,
+    g2: G2Affine,
+    tau_g2: G2Affine,
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(Status::InvalidArg, msg)
+}
+
+fn rejected(check: &str) -> Error {
+    Error::new(
+        Status::GenericFailure,
+        format!("proof verification failed: {check}"),
+    )
+}
+
+/// Primitive `2^log_n`-th root of unity, `7^((r - 1) / 2^log_n)`
+pub fn root_of_unity(log_n: u32) -> Fr {
+    debug_assert!(log_n <= TWO_ADICITY);
+    // (r - 1) >> 32, the odd part of r - 1
+    let odd = [
+        0xfffe5bfeffffffff,
+        0x09a1d80553bda402,
+        0x299d7d483339d808,
+        0x0000000073eda753,
+    ];
+    let mut w = Fr::from_u64(GENERATOR).pow(&odd);
+    for _ in log_n..TWO_ADICITY {
+        w = w.square();
+    }
+    w
+}
+
+/// Column coset shifts `k_i = 7^i`
+fn coset_shifts(wires: usize) -> Vec<Fr> {
+    let g = Fr::from_u64(GENERATOR);
+    (0..wires)
+        .scan(Fr::one(), |k, _| {
+            let current = *k;
+            *k *= g;
+            Some(current)
+        })
+        .collect()
+}
+
+/// Number of quotient chunks for the given shape
+fn quotient_chunks(wires: usize, lookup: bool) -> usize {
+    if lookup {
+        wires.max(3)
+    } else {
+        wires
+    }
+}
+
+impl Key {
+    pub(crate) fn decode(vk: &PlonkVk) -> Result<Self> {
+        let n = vk.domain_size as usize;
+        if n < 2 || !n.is_power_of_two() {
+            return Err(invalid(format!(
+                "vk.domain_size: expected a power of two of at least 2, got {n}"
+            )));
+        }
+        let wires = vk.num_advice_columns as usize;
+        if !(2..=MAX_ADVICE_COLUMNS as usize).contains(&wires) {
+            return Err(invalid(format!(
+                "vk.num_advice_columns: expected 2 to {MAX_ADVICE_COLUMNS}, got {wires}"
+            )));
+        }
+        let points = |list: &[Vec<u8>], name: &str, expected: usize| {
+            if list.len() != expected {
+                return Err(invalid(format!(
+                    "{name}: expected {expected} commitments for {wires} advice columns, got {}",
+                    list.len()
+                )));
+            }
+            list.iter()
+                .enumerate()
+                .map(|(i, p)| decode_g1(p, &format!("{name}[{i}]")))
+                .collect::<Result<Vec<_>>>()
+        };
+        let lookup = match (&vk.lookup_selector_commitment, &vk.lookup_table_commitment) {
+            (None, None) => None,
+            (Some(selector), Some(table)) => Some((
+                decode_g1(selector, "vk.lookup_selector_commitment")?,
+                decode_g1(table, "vk.lookup_table_commitment")?,
+            )),
+            _ => {
+                return Err(invalid(
+                    "vk: lookup selector and table commitments must be given together".to_string(),
+                ))
+            }
+        };
+        if vk.srs_g2.len() < 2 {
+            return Err(invalid(format!(
+                "vk.srs_g2: expected at least 2 points, got {}",
+                vk.srs_g2.len()
+            )));
+        }
+        Ok(Key {
+            n,
+            omega: root_of_unity(n.trailing_zeros()),
+            wires,
+            gates: points(&vk.gate_commitments, "vk.gate_commitments", wires + 2)?,
+            sigmas: points(
+                &vk.permutation_commitments,
+                "vk.permutation_commitments",
+                wires,
+            )?,
+            lookup,
+            // Both enter the Miller loop, so must lie in the G2 subgroup
+            g2: decode_g2_subgroup(&vk.srs_g2[0], "vk.srs_g2[0]")?,
+            tau_g2: decode_g2_subgroup(&vk.srs_g2[1], "vk.srs_g2[1]")?,
+        })
+    }
+
+    fn quotient_chunks(&self) -> usize {
+        quotient_chunks(self.wires, self.lookup.is_some())
+    }
+}
+
+/// Challenges of one proof, in transcript order
+pub(crate) struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    zeta: Fr,
+    v: Fr,
+    u: Fr,
+}
+
+fn challenge(transcript: &mut Transcript, label: &str) -> Fr {
+    let radix = Fr::from_u64(256);
+    transcript
+        .challenge_bytes(label.to_string(), CHALLENGE_BYTES)
+        .expect("challenge length is positive")
+        .iter()
+        .fold(Fr::zero(), |acc, &b| acc * radix + Fr::from_u64(b as u64))
+}
+
+fn absorb_points(transcript: &mut Transcript, label: &str, points: &[G1Affine]) {
+    let bytes: Vec<u8> = points.iter().flat_map(encode_g1).collect();
+    transcript.append_message(label.to_string(), bytes);
+}
+
+fn absorb_scalars(transcript: &mut Transcript, label: &str, scalars: &[Fr]) {
+    let bytes: Vec<u8> = scalars.iter().flat_map(|s| s.to_le_bytes()).collect();
+    transcript.append_message(label.to_string(), bytes);
+}
+
+/// Transcript bound to the key and the instances
+fn statement_transcript(key: &Key, instances: &[Fr]) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL.to_string());
+    let mut shape = (key.n as u32).to_be_bytes().to_vec();
+    shape.extend((key.wires as u32).to_be_bytes());
+    shape.push(key.lookup.is_some() as u8);
+    transcript.append_message("shape".to_string(), shape);
+    let mut fixed = key.gates.clone();
+    fixed.extend(&key.sigmas);
+    if let Some((selector, table)) = key.lookup {
+        fixed.extend([selector, table]);
+    }
+    absorb_points(&mut transcript, "vk", &fixed);
+    absorb_scalars(&mut transcript, "instances", instances);
+    transcript
+}
+
+/// Commitments to the permuted lookup columns and their grand product
+#[derive(Debug, Clone)]
+pub(crate) struct LookupCommitments {
+    permuted_input: G1Affine,
+    permuted_table: G1Affine,
+    z: G1Affine,
+}
+
+/// Lookup evaluations carried by a proof
+#[derive(Debug, Clone)]
+pub(crate) struct LookupEvaluations {
+    selector: Fr,
+    table: Fr,
+    permuted_input: Fr,
+    permuted_table: Fr,
+    z: Fr,
+    z_next: Fr,
+    permuted_input_prev: Fr,
+}
+
+/// Decoded proof
+#[derive(Debug, Clone)]
+pub(crate) struct Proof {
+    wires: Vec<G1Affine>,
+    z: G1Affine,
+    lookup: Option<LookupCommitments>,
+    quotient: Vec<G1Affine>,
+    /// Opening witnesses at `ζ`, `ζω` and, with a lookup, `ζ/ω`
+    openings: Vec<G1Affine>,
+    wire_evals: Vec<Fr>,
+    gate_evals: Vec<Fr>,
+    sigma_evals: Vec<Fr>,
+    z_eval: Fr,
+    lookup_evals: Option<LookupEvaluations>,
+    quotient_evals: Vec<Fr>,
+    z_next_eval: Fr,
+}
+
+/// Sequential reader over the proof bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize, what: &str) -> Result<&[u8]> {
+        let end = self.offset + len;
+        if end > self.bytes.len() {
+            return Err(invalid(format!(
+                "invalid proof encoding: truncated at {what} (offset {}, {} bytes)",
+                self.offset,
+                self.bytes.len()
+            )));
+        }
+        let chunk = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(chunk)
+    }
+
+    fn point(&mut self, what: &str) -> Result<G1Affine> {
+        let bytes = self.take(G1_BYTES, what)?;
+        decode_g1(bytes, what).map_err(|e| invalid(format!("invalid proof encoding: {}", e.reason)))
+    }
+
+    fn points(&mut self, what: &str, count: usize) -> Result<Vec<G1Affine>> {
+        (0..count)
+            .map(|i| self.point(&format!("{what}[{i}]")))
+            .collect()
+    }
+
+    fn scalar(&mut self, what: &str) -> Result<Fr> {
+        let bytes = self.take(SCALAR_BYTES, what)?;
+        decode_scalar_le(bytes, what)
+            .map_err(|e| invalid(format!("invalid proof encoding: {}", e.reason)))
+    }
+
+    fn scalars(&mut self, what: &str, count: usize) -> Result<Vec<Fr>> {
+        (0..count)
+            .map(|i| self.scalar(&format!("{what}[{i}]")))
+            .collect()
+    }
+}
+
+impl Proof {
+    pub(crate) fn decode(key: &Key, bytes: &[u8]) -> Result<Self> {
+        let w = key.wires;
+        let lookup = key.lookup.is_some();
+        let mut r = Reader { bytes, offset: 0 };
+        let wires = r.points("wire commitment", w)?;
+        let permuted = if lookup {
+            Some((r.point("permuted input")?, r.point("permuted table")?))
+        } else {
+            None
+        };
+        let z = r.point("permutation product")?;
+        let lookup_commitments = match permuted {
+            Some((permuted_input, permuted_table)) => Some(LookupCommitments {
+                permuted_input,
+                permuted_table,
+                z: r.point("lookup product")?,
+            }),
+            None => None,
+        };
+        let quotient = r.points("quotient commitment", key.quotient_chunks())?;
+        let openings = r.points("opening witness", if lookup { 3 } else { 2 })?;
+
+        let wire_evals = r.scalars("wire evaluation", w)?;
+        let gate_evals = r.scalars("gate evaluation", w + 2)?;
+        let sigma_evals = r.scalars("permutation evaluation", w)?;
+        let z_eval = r.scalar("permutation product evaluation")?;
+        let lookup_head = if lookup {
+            Some(r.scalars("lookup evaluation", 5)?)
+        } else {
+            None
+        };
+        let quotient_evals = r.scalars("quotient evaluation", key.quotient_chunks())?;
+        let z_next_eval = r.scalar("shifted permutation product evaluation")?;
+        let lookup_evals = match lookup_head {
+            Some(head) => Some(LookupEvaluations {
+                selector: head[0],
+                table: head[1],
+                permuted_input: head[2],
+                permuted_table: head[3],
+                z: head[4],
+                z_next: r.scalar("shifted lookup product evaluation")?,
+                permuted_input_prev: r.scalar("previous permuted input evaluation")?,
+            }),
+            None => None,
+        };
+        if r.offset != bytes.len() {
+            return Err(invalid(format!(
+                "invalid proof encoding: expected {} bytes, got {}",
+                r.offset,
+                bytes.len()
+            )));
+        }
+        Ok(Proof {
+            wires,
+            z,
+            lookup: lookup_commitments,
+            quotient,
+            openings,
+            wire_evals,
+            gate_evals,
+            sigma_evals,
+            z_eval,
+            lookup_evals,
+            quotient_evals,
+            z_next_eval,
+        })
+    }
+
+    /// Every evaluation in proof order
+    fn evaluations(&self) -> Vec<Fr> {
+        let mut out = self.wire_evals.clone();
+        out.extend(&self.gate_evals);
+        out.extend(&self.sigma_evals);
+        out.push(self.z_eval);
+        if let Some(l) = &self.lookup_evals {
+            out.extend([l.selector, l.table, l.permuted_input, l.permuted_table, l.z]);
+        }
+        out.extend(&self.quotient_evals);
+        out.push(self.z_next_eval);
+        if let Some(l) = &self.lookup_evals {
+            out.extend([l.z_next, l.permuted_input_prev]);
+        }
+        out
+    }
+
+    /// Replay the transcript to derive the challenges
+    fn challenges(&self, key: &Key, instances: &[Fr]) -> Challenges {
+        let mut t = statement_transcript(key, instances);
+        absorb_points(&mut t, "wires", &self.wires);
+        if let Some(l) = &self.lookup {
+            absorb_points(&mut t, "permuted", &[l.permuted_input, l.permuted_table]);
+        }
+        let beta = challenge(&mut t, "beta");
+        let gamma = challenge(&mut t, "gamma");
+        let mut products = vec![self.z];
+        products.extend(self.lookup.as_ref().map(|l| l.z));
+        absorb_points(&mut t, "products", &products);
+        let alpha = challenge(&mut t, "alpha");
+        absorb_points(&mut t, "quotient", &self.quotient);
+        let zeta = challenge(&mut t, "zeta");
+        absorb_scalars(&mut t, "evaluations", &self.evaluations());
+        let v = challenge(&mut t, "v");
+        absorb_points(&mut t, "openings", &self.openings);
+        let u = challenge(&mut t, "u");
+        Challenges {
+            beta,
+            gamma,
+            alpha,
+            zeta,
+            v,
+            u,
+        }
+    }
+}
+
+/// Arithmetic the constraint is written over: field elements for the
+/// verifier, polynomials for a prover
+pub(crate) trait Ring:
+    Clone + From<Fr> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+}
+
+impl<T> Ring for T where T: Clone + From<Fr> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> {}
+
+/// Lookup values the constraint reads
+pub(crate) struct LookupRow<T> {
+    selector: T,
+    table: T,
+    permuted_input: T,
+    permuted_table: T,
+    z: T,
+    z_next: T,
+    permuted_input_prev: T,
+}
+
+/// Values of every polynomial the constraint reads, at one point
+pub(crate) struct Row<T> {
+    x: T,
+    wires: Vec<T>,
+    /// `q_0..q_{W-1}, q_M, q_C`
+    gates: Vec<T>,
+    sigmas: Vec<T>,
+    z: T,
+    z_next: T,
+    pi: T,
+    l0: T,
+    lookup: Option<LookupRow<T>>,
+}
+
+/// `Σ α^i c_i` over the gate, permutation and lookup constraints
+///
+/// Vanishes on `H` exactly when every constraint holds on every row.
+fn constraint<T: Ring>(row: &Row<T>, beta: Fr, gamma: Fr, alpha: Fr) -> T {
+    let w = row.wires.len();
+    let one = T::from(Fr::one());
+    let (beta_t, gamma_t) = (T::from(beta), T::from(gamma));
+
+    let mut gate = row.pi.clone()
+        + row.gates[w + 1].clone()
+        + row.gates[w].clone() * row.wires[0].clone() * row.wires[1].clone();
+    for (q, wire) in row.gates.iter().zip(&row.wires) {
+        gate = gate + q.clone() * wire.clone();
+    }
+
+    let mut identity = row.z.clone();
+    let mut permuted = row.z_next.clone();
+    for ((wire, sigma), k) in row.wires.iter().zip(&row.sigmas).zip(coset_shifts(w)) {
+        identity = identity * (wire.clone() + T::from(beta * k) * row.x.clone() + gamma_t.clone());
+        permuted = permuted * (wire.clone() + beta_t.clone() * sigma.clone() + gamma_t.clone());
+    }
+    let mut terms = vec![
+        gate,
+        identity - permuted,
+        row.l0.clone() * (row.z.clone() - one.clone()),
+    ];
+
+    if let Some(l) = &row.lookup {
+        let input = l.selector.clone() * row.wires[0].clone();
+        let diff = l.permuted_input.clone() - l.permuted_table.clone();
+        terms.push(
+            l.z_next.clone()
+                * (l.permuted_input.clone() + beta_t.clone())
+                * (l.permuted_table.clone() + gamma_t.clone())
+                - l.z.clone() * (input + beta_t) * (l.table.clone() + gamma_t),
+        );
+        terms.push(row.l0.clone() * (l.z.clone() - one));
+        terms.push(row.l0.clone() * diff.clone());
+        terms.push(diff * (l.permuted_input.clone() - l.permuted_input_prev.clone()));
+    }
+    let alpha = T::from(alpha);
+    terms
+        .into_iter()
+        .rev()
+        .fold(T::from(Fr::zero()), |acc, term| acc * alpha.clone() + term)
+}
+
+/// `(commitment, evaluation)` claims opened at `ζ`, `ζω` and `ζ/ω`
+fn opening_claims(key: &Key, proof: &Proof) -> Vec<Vec<(G1Affine, Fr)>> {
+    let mut at_zeta: Vec<(G1Affine, Fr)> = Vec::new();
+    at_zeta.extend(
+        proof
+            .wires
+            .iter()
+            .copied()
+            .zip(proof.wire_evals.iter().copied()),
+    );
+    at_zeta.extend(
+        key.gates
+            .iter()
+            .copied()
+            .zip(proof.gate_evals.iter().copied()),
+    );
+    at_zeta.extend(
+        key.sigmas
+            .iter()
+            .copied()
+            .zip(proof.sigma_evals.iter().copied()),
+    );
+    at_zeta.push((proof.z, proof.z_eval));
+    let mut at_next = vec![(proof.z, proof.z_next_eval)];
+    let mut sets = Vec::with_capacity(3);
+    match (key.lookup, &proof.lookup, &proof.lookup_evals) {
+        (Some((selector, table)), Some(c), Some(e)) => {
+            at_zeta.extend([
+                (selector, e.selector),
+                (table, e.table),
+                (c.permuted_input, e.permuted_input),
+                (c.permuted_table, e.permuted_table),
+                (c.z, e.z),
+            ]);
+            at_zeta.extend(
+                proof
+                    .quotient
+                    .iter()
+                    .copied()
+                    .zip(proof.quotient_evals.iter().copied()),
+            );
+            at_next.push((c.z, e.z_next));
+            sets.extend([
+                at_zeta,
+                at_next,
+                vec![(c.permuted_input, e.permuted_input_prev)],
+            ]);
+        }
+        _ => {
+            at_zeta.extend(
+                proof
+                    .quotient
+                    .iter()
+                    .copied()
+                    .zip(proof.quotient_evals.iter().copied()),
+            );
+            sets.extend([at_zeta, at_next]);
+        }
+    }
+    sets
+}
+
+/// Batched KZG check of every opening claim
+///
+/// Claims at each point are combined with powers of `v` and the points with
+/// powers of `u`, giving
+/// `e(Σ u^j W_j, [τ]_2) == e(Σ u^j (F_j - y_j G1 + z_j W_j), G2)`.
+fn check_openings(key: &Key, proof: &Proof, ch: &Challenges) -> bool {
+    let points = [
+        ch.zeta,
+        ch.zeta * key.omega,
+        ch.zeta * key.omega.inverse().unwrap(),
+    ];
+    let g1 = g1_generator().to_jacobian();
+    let mut witnesses = G1Projective::identity();
+    let mut combined = G1Projective::identity();
+    let mut u_pow = Fr::one();
+    for ((claims, witness), point) in opening_claims(key, proof)
+        .iter()
+        .zip(&proof.openings)
+        .zip(points)
+    {
+        let mut f = G1Projective::identity();
+        let mut y = Fr::zero();
+        let mut v_pow = Fr::one();
+        for (commitment, eval) in claims {
+            f = f.add(&commitment.to_jacobian().mul_limbs(&v_pow.to_canonical()));
+            y += v_pow * *eval;
+            v_pow *= ch.v;
+        }
+        let witness = witness.to_jacobian();
+        let term = f
+            .add(&g1.mul_limbs(&y.to_canonical()).neg())
+            .add(&witness.mul_limbs(&point.to_canonical()));
+        witnesses = witnesses.add(&witness.mul_limbs(&u_pow.to_canonical()));
+        combined = combined.add(&term.mul_limbs(&u_pow.to_canonical()));
+        u_pow *= ch.u;
+    }
+    pairing_product_is_one::<Bls12Pairing, 6>(&[
+        (witnesses.to_affine(), key.tau_g2),
+        (combined.to_affine().neg(), key.g2),
+    ])
+}
+
+/// Verify a decoded proof, naming the failed check on rejection
+pub(crate) fn verify(key: &Key, proof: &Proof, instances: &[Fr]) -> Result<()> {
+    let ch = proof.challenges(key, instances);
+    let n_fr = Fr::from_u64(key.n as u64);
+    let zeta_n = ch.zeta.pow(&[key.n as u64]);
+    let vanishing = zeta_n - Fr::one();
+    if vanishing.is_zero() {
+        return Err(rejected("evaluation point lies in the domain"));
+    }
+    // L_j(ζ) = ω^j (ζ^n - 1) / (n (ζ - ω^j))
+    let lagrange =
+        |omega_j: Fr| omega_j * vanishing * (n_fr * (ch.zeta - omega_j)).inverse().unwrap();
+    let mut pi = Fr::zero();
+    let mut omega_j = Fr::one();
+    for x in instances {
+        pi -= *x * lagrange(omega_j);
+        omega_j *= key.omega;
+    }
+    let row = Row {
+        x: ch.zeta,
+        wires: proof.wire_evals.clone(),
+        gates: proof.gate_evals.clone(),
+        sigmas: proof.sigma_evals.clone(),
+        z: proof.z_eval,
+        z_next: proof.z_next_eval,
+        pi,
+        l0: lagrange(Fr::one()),
+        lookup: proof.lookup_evals.as_ref().map(|l| LookupRow {
+            selector: l.selector,
+            table: l.table,
+            permuted_input: l.permuted_input,
+            permuted_table: l.permuted_table,
+            z: l.z,
+            z_next: l.z_next,
+            permuted_input_prev: l.permuted_input_prev,
+        }),
+    };
+    let quotient = proof
+        .quotient_evals
+        .iter()
+        .rev()
+        .fold(Fr::zero(), |acc, t| acc * zeta_n + *t);
+    if constraint(&row, ch.beta, ch.gamma, ch.alpha) != quotient * vanishing {
+        return Err(rejected("constraints do not match the quotient at ζ"));
+    }
+    if !check_openings(key, proof, &ch) {
+        return Err(rejected("KZG opening check"));
+    }
+    Ok(())
+}
+
+/// Verify a PLONK proof against a verification key and instances
+///
+/// `instances` are 32-byte little-endian scalars placed in the first rows
+/// of the public input polynomial. Resolves to true for a valid proof.
+/// Malformed proofs are rejected with an `InvalidArg` error starting with
+/// "invalid proof encoding", and well-formed proofs that fail a check with a
+/// `GenericFailure` error starting with "proof verification failed".
+#[napi]
+pub fn plonk_verify(vk: PlonkVk, proof: Vec<u8>, instances: Vec<Vec<u8>>) -> Result<bool> {
+    let key = Key::decode(&vk)?;
+    if instances.len() > key.n {
+        return Err(invalid(format!(
+            "instances: domain of {} rows cannot hold {} instances",
+            key.n,
+            instances.len()
+        )));
+    }
+    let instances = instances
+        .iter()
+        .enumerate()
+        .map(|(i, x)| decode_scalar_le(x, &format!("instances[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    let proof = Proof::decode(&key, &proof)?;
+    verify(&key, &proof, &instances)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::encode_g2;
+    use crate::kzg::{commit, divide_by_linear, insecure_setup};
+
+    /// Dense polynomial, lowest degree first
+    #[derive(Debug, Clone)]
+    struct Poly(Vec<Fr>);
+
+    impl From<Fr> for Poly {
+        fn from(c: Fr) -> Self {
+            Poly(vec![c])
+        }
+    }
+
+    impl Add for Poly {
+        type Output = Poly;
+        fn add(self, other: Poly) -> Poly {
+            let (mut long, short) = if self.0.len() >= other.0.len() {
+                (self.0, other.0)
+            } else {
+                (other.0, self.0)
+            };
+            for (a, b) in long.iter_mut().zip(short) {
+                *a += b;
+            }
+            Poly(long)
+        }
+    }
+
+    impl Sub for Poly {
+        type Output = Poly;
+        fn sub(self, other: Poly) -> Poly {
+            let mut out = self.0;
+            out.resize(out.len().max(other.0.len()), Fr::zero());
+            for (a, b) in out.iter_mut().zip(other.0) {
+                *a -= b;
+            }
+            Poly(out)
+        }
+    }
+
+    impl Mul for Poly {
+        type Output = Poly;
+        fn mul(self, other: Poly) -> Poly {
+            let mut out = vec![Fr::zero(); self.0.len() + other.0.len() - 1];
+            for (i, a) in self.0.iter().enumerate() {
+                for (j, b) in other.0.iter().enumerate() {
+                    out[i + j] += *a * *b;
+                }
+            }
+            Poly(out)
+        }
+    }
+
+    impl Poly {
+        /// Interpolate `values[i] = p(ω^i)` with an inverse DFT
+        fn interpolate(values: &[Fr], omega: Fr) -> Poly {
+            let n = values.len();
+            let n_inv = Fr::from_u64(n as u64).inverse().unwrap();
+            let omega_inv = omega.inverse().unwrap();
+            Poly(
+                (0..n)
+                    .map(|k| {
+                        let step = omega_inv.pow(&[k as u64]);
+                        let mut x = Fr::one();
+                        let mut acc = Fr::zero();
+                        for v in values {
+                            acc += *v * x;
+                            x *= step;
+                        }
+                        acc * n_inv
+                    })
+                    .collect(),
+            )
+        }
+
+        fn eval(&self, x: Fr) -> Fr {
+            self.0.iter().rev().fold(Fr::zero(), |acc, c| acc * x + *c)
+        }
+
+        /// `p(s·X)`
+        fn scale(&self, s: Fr) -> Poly {
+            let mut power = Fr::one();
+            Poly(
+                self.0
+                    .iter()
+                    .map(|c| {
+                        let out = *c * power;
+                        power *= s;
+                        out
+                    })
+                    .collect(),
+            )
+        }
+
+        /// Exact division by `X^n - 1`
+        fn div_vanishing(&self, n: usize) -> Poly {
+            let mut rem = self.0.clone();
+            let mut quotient = vec![Fr::zero(); rem.len().saturating_sub(n)];
+            for i in (n..rem.len()).rev() {
+                let c = rem[i];
+                quotient[i - n] += c;
+                rem[i - n] += c;
+                rem[i] = Fr::zero();
+            }
+            assert!(
+                rem.iter().all(|c| c.is_zero()),
+                "constraint does not vanish on H"
+            );
+            Poly(quotient)
+        }
+    }
+
+    /// Circuit proving knowledge of `x` with `x^3 + x + 5 = 35`
+    struct Circuit {
+        n: usize,
+        wires: Vec<Vec<Fr>>,
+        /// `q_0, q_1, q_2, q_M, q_C`
+        gates: Vec<Vec<Fr>>,
+        /// Cells `(column, row)` that must hold equal values
+        copies: Vec<Vec<(usize, usize)>>,
+        /// `(q_K, T)`
+        lookup: Option<(Vec<Fr>, Vec<Fr>)>,
+        instances: Vec<Fr>,
+    }
+
+    fn fr(v: i64) -> Fr {
+        if v < 0 {
+            -Fr::from_u64(v.unsigned_abs())
+        } else {
+            Fr::from_u64(v as u64)
+        }
+    }
+
+    fn cubic(x: i64, lookup: bool) -> Circuit {
+        let n = 8;
+        let rows: [([i64; 3], [i64; 5]); 5] = [
+            // w_0 = 35, the instance
+            ([35, 0, 0], [1, 0, 0, 0, 0]),
+            // x · x = x^2
+            ([x, x, x * x], [0, 0, -1, 1, 0]),
+            // x^2 · x = x^3
+            ([x * x, x, x * x * x], [0, 0, -1, 1, 0]),
+            // x^3 + x = x^3 + x
+            ([x * x * x, x, x * x * x + x], [1, 1, -1, 0, 0]),
+            // (x^3 + x) + 5 = 35
+            ([x * x * x + x, 0, 35], [1, 0, -1, 0, 5]),
+        ];
+        let mut wires = vec![vec![Fr::zero(); n]; 3];
+        let mut gates = vec![vec![Fr::zero(); n]; 5];
+        for (r, (w, q)) in rows.iter().enumerate() {
+            for (c, v) in w.iter().enumerate() {
+                wires[c][r] = fr(*v);
+            }
+            for (c, v) in q.iter().enumerate() {
+                gates[c][r] = fr(*v);
+            }
+        }
+        let lookup = lookup.then(|| {
+            // x must be one of 0..8
+            let mut selector = vec![Fr::zero(); n];
+            selector[1] = Fr::one();
+            (selector, (0..n as u64).map(Fr::from_u64).collect())
+        });
+        Circuit {
+            n,
+            wires,
+            gates,
+            copies: vec![
+                vec![(0, 1), (1, 1), (1, 2), (1, 3)],
+                vec![(2, 1), (0, 2)],
+                vec![(2, 2), (0, 3)],
+                vec![(2, 3), (0, 4)],
+                vec![(2, 4), (0, 0)],
+            ],
+            lookup,
+            instances: vec![fr(35)],
+        }
+    }
+
+    struct Prover {
+        srs: Vec<G1Affine>,
+        vk: PlonkVk,
+        omega: Fr,
+        gates: Vec<Poly>,
+        sigmas: Vec<Poly>,
+        sigma_values: Vec<Vec<Fr>>,
+        lookup: Option<(Poly, Poly)>,
+    }
+
+    fn setup(circuit: &Circuit) -> Prover {
+        let n = circuit.n;
+        let (srs, srs_g2) = insecure_setup(Fr::from_u64(0xfeed_beef_cafe), n);
+        let omega = root_of_unity(n.trailing_zeros());
+        let ks = coset_shifts(3);
+        let label = |c: usize, r: usize| ks[c] * omega.pow(&[r as u64]);
+        let mut sigma_values: Vec<Vec<Fr>> = (0..3)
+            .map(|c| (0..n).map(|r| label(c, r)).collect())
+            .collect();
+        for cycle in &circuit.copies {
+            for (i, &(c, r)) in cycle.iter().enumerate() {
+                let (nc, nr) = cycle[(i + 1) % cycle.len()];
+                sigma_values[c][r] = label(nc, nr);
+            }
+        }
+        let gates: Vec<Poly> = circuit
+            .gates
+            .iter()
+            .map(|q| Poly::interpolate(q, omega))
+            .collect();
+        let sigmas: Vec<Poly> = sigma_values
+            .iter()
+            .map(|s| Poly::interpolate(s, omega))
+            .collect();
+        let lookup = circuit.lookup.as_ref().map(|(selector, table)| {
+            (
+                Poly::interpolate(selector, omega),
+                Poly::interpolate(table, omega),
+            )
+        });
+        let c = |p: &Poly| encode_g1(&commit(&p.0, &srs));
+        let vk = PlonkVk {
+            domain_size: n as u32,
+            num_advice_columns: 3,
+            gate_commitments: gates.iter().map(c).collect(),
+            permutation_commitments: sigmas.iter().map(c).collect(),
+            lookup_selector_commitment: lookup.as_ref().map(|(s, _)| c(s)),
+            lookup_table_commitment: lookup.as_ref().map(|(_, t)| c(t)),
+            srs_g2: srs_g2.iter().map(encode_g2).collect(),
+        };
+        Prover {
+            srs,
+            vk,
+            omega,
+            gates,
+            sigmas,
+            sigma_values,
+            lookup,
+        }
+    }
+
+    /// Halo2's permuted lookup columns: equal inputs grouped, each group
+    /// starting on a matching table row
+    fn permute_lookup(input: &[Fr], table: &[Fr]) -> (Vec<Fr>, Vec<Fr>) {
+        let mut permuted_input = input.to_vec();
+        permuted_input.sort_by_key(|v| v.to_canonical().iter().rev().copied().collect::<Vec<_>>());
+        let mut remaining = table.to_vec();
+        let mut permuted_table = vec![None; input.len()];
+        for i in 0..input.len() {
+            if i == 0 || permuted_input[i] != permuted_input[i - 1] {
+                let at = remaining
+                    .iter()
+                    .position(|t| *t == permuted_input[i])
+                    .expect("lookup input is not in the table");
+                permuted_table[i] = Some(remaining.swap_remove(at));
+            }
+        }
+        let mut rest = remaining.into_iter();
+        let permuted_table = permuted_table
+            .into_iter()
+            .map(|t| t.unwrap_or_else(|| rest.next().unwrap()))
+            .collect();
+        (permuted_input, permuted_table)
+    }
+
+    /// Grand product `Z_0 = 1`, `Z_{i+1} = Z_i · num_i / den_i`
+    fn grand_product(num: &[Fr], den: &[Fr]) -> Vec<Fr> {
+        let mut z = vec![Fr::one()];
+        for i in 0..num.len() - 1 {
+            let next = z[i] * num[i] * den[i].inverse().unwrap();
+            z.push(next);
+        }
+        assert_eq!(z[num.len() - 1] * num[num.len() - 1], den[num.len() - 1]);
+        z
+    }
+
+    fn prove(prover: &Prover, circuit: &Circuit, instances: &[Fr]) -> Vec<u8> {
+        let n = circuit.n;
+        let omega = prover.omega;
+        let key = Key::decode(&prover.vk).unwrap();
+        let c = |p: &Poly| commit(&p.0, &prover.srs);
+        let interpolate = |v: &[Fr]| Poly::interpolate(v, omega);
+        let mut t = statement_transcript(&key, instances);
+
+        let wires: Vec<Poly> = circuit.wires.iter().map(|w| interpolate(w)).collect();
+        let wire_commitments: Vec<G1Affine> = wires.iter().map(c).collect();
+        absorb_points(&mut t, "wires", &wire_commitments);
+        let lookup_columns = circuit.lookup.as_ref().map(|(selector, table)| {
+            let input: Vec<Fr> = (0..n).map(|i| selector[i] * circuit.wires[0][i]).collect();
+            let (a, s) = permute_lookup(&input, table);
+            (input, table.clone(), a, s)
+        });
+        let permuted = lookup_columns
+            .as_ref()
+            .map(|(_, _, a, s)| (interpolate(a), interpolate(s)));
+        if let Some((a, s)) = &permuted {
+            absorb_points(&mut t, "permuted", &[c(a), c(s)]);
+        }
+        let beta = challenge(&mut t, "beta");
+        let gamma = challenge(&mut t, "gamma");
+
+        let ks = coset_shifts(3);
+        let (num, den): (Vec<Fr>, Vec<Fr>) = (0..n)
+            .map(|i| {
+                let x = omega.pow(&[i as u64]);
+                (0..3).fold((Fr::one(), Fr::one()), |(num, den), col| {
+                    let w = circuit.wires[col][i];
+                    (
+                        num * (w + beta * ks[col] * x + gamma),
+                        den * (w + beta * prover.sigma_values[col][i] + gamma),
+                    )
+                })
+            })
+            .unzip();
+        let z = interpolate(&grand_product(&num, &den));
+        let z_lookup = lookup_columns.as_ref().map(|(input, table, a, s)| {
+            let num: Vec<Fr> = (0..n)
+                .map(|i| (input[i] + beta) * (table[i] + gamma))
+                .collect();
+            let den: Vec<Fr> = (0..n).map(|i| (a[i] + beta) * (s[i] + gamma)).collect();
+            interpolate(&grand_product(&num, &den))
+        });
+        let mut products = vec![c(&z)];
+        products.extend(z_lookup.as_ref().map(c));
+        absorb_points(&mut t, "products", &products);
+        let alpha = challenge(&mut t, "alpha");
+
+        let mut pi_values = vec![Fr::zero(); n];
+        for (v, x) in pi_values.iter_mut().zip(instances) {
+            *v = -*x;
+        }
+        let mut l0_values = vec![Fr::zero(); n];
+        l0_values[0] = Fr::one();
+        let row = Row {
+            x: Poly(vec![Fr::zero(), Fr::one()]),
+            wires: wires.clone(),
+            gates: prover.gates.clone(),
+            sigmas: prover.sigmas.clone(),
+            z: z.clone(),
+            z_next: z.scale(omega),
+            pi: interpolate(&pi_values),
+            l0: interpolate(&l0_values),
+            lookup: prover
+                .lookup
+                .as_ref()
+                .zip(permuted.as_ref())
+                .zip(z_lookup.as_ref())
+                .map(|(((selector, table), (a, s)), zl)| LookupRow {
+                    selector: selector.clone(),
+                    table: table.clone(),
+                    permuted_input: a.clone(),
+                    permuted_table: s.clone(),
+                    z: zl.clone(),
+                    z_next: zl.scale(omega),
+                    permuted_input_prev: a.scale(omega.inverse().unwrap()),
+                }),
+        };
+        let quotient = constraint(&row, beta, gamma, alpha).div_vanishing(n);
+        let chunks = key.quotient_chunks();
+        assert!(quotient.0.len() <= chunks * n);
+        let mut padded = quotient.0.clone();
+        padded.resize(chunks * n, Fr::zero());
+        let quotient: Vec<Poly> = padded.chunks(n).map(|c| Poly(c.to_vec())).collect();
+        let quotient_commitments: Vec<G1Affine> = quotient.iter().map(c).collect();
+        absorb_points(&mut t, "quotient", &quotient_commitments);
+        let zeta = challenge(&mut t, "zeta");
+
+        // Opening sets in the order of `opening_claims`
+        let mut at_zeta: Vec<Poly> = wires.clone();
+        at_zeta.extend(prover.gates.iter().cloned());
+        at_zeta.extend(prover.sigmas.iter().cloned());
+        at_zeta.push(z.clone());
+        let mut at_next = vec![z.clone()];
+        let mut at_prev = vec![];
+        if let (Some((selector, table)), Some((a, s)), Some(zl)) =
+            (&prover.lookup, &permuted, &z_lookup)
+        {
+            at_zeta.extend([selector, table, a, s, zl].map(Poly::clone));
+            at_next.push(zl.clone());
+            at_prev.push(a.clone());
+        }
+        at_zeta.extend(quotient.iter().cloned());
+        let zeta_next = zeta * omega;
+        let zeta_prev = zeta * omega.inverse().unwrap();
+        let mut evaluations: Vec<Fr> = at_zeta.iter().map(|p| p.eval(zeta)).collect();
+        evaluations.extend(at_next.iter().map(|p| p.eval(zeta_next)));
+        evaluations.extend(at_prev.iter().map(|p| p.eval(zeta_prev)));
+        absorb_scalars(&mut t, "evaluations", &evaluations);
+        let v = challenge(&mut t, "v");
+
+        let mut sets = vec![(at_zeta, zeta), (at_next, zeta_next)];
+        if !at_prev.is_empty() {
+            sets.push((at_prev, zeta_prev));
+        }
+        let openings: Vec<G1Affine> = sets
+            .iter()
+            .map(|(polys, point)| {
+                let mut v_pow = Fr::one();
+                let combined = polys.iter().fold(Poly(vec![Fr::zero()]), |acc, p| {
+                    let term = p.clone() * Poly::from(v_pow);
+                    v_pow *= v;
+                    acc + term
+                });
+                c(&Poly(divide_by_linear(&combined.0, *point).1))
+            })
+            .collect();
+
+        let mut g1: Vec<G1Affine> = wire_commitments;
+        if let Some((a, s)) = &permuted {
+            g1.extend([c(a), c(s)]);
+        }
+        g1.extend(products);
+        g1.extend(quotient_commitments);
+        g1.extend(openings);
+        let mut bytes: Vec<u8> = g1.iter().flat_map(encode_g1).collect();
+        bytes.extend(evaluations.iter().flat_map(|e| e.to_le_bytes()));
+        bytes
+    }
+
+    fn le(values: &[Fr]) -> Vec<Vec<u8>> {
+        values.iter().map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        let w = root_of_unity(TWO_ADICITY);
+        assert_eq!(w.pow(&[1 << 31]), -Fr::one());
+        assert_eq!(root_of_unity(3).pow(&[8]), Fr::one());
+        assert_eq!(root_of_unity(3).pow(&[4]), -Fr::one());
+    }
+
+    #[test]
+    fn test_vanilla_proof_verifies() {
+        let circuit = cubic(3, false);
+        let prover = setup(&circuit);
+        let proof = prove(&prover, &circuit, &circuit.instances);
+        assert!(plonk_verify(prover.vk.clone(), proof.clone(), le(&circuit.instances)).unwrap());
+
+        // The same proof does not verify another statement
+        let err = plonk_verify(prover.vk.clone(), proof, le(&[fr(36)])).unwrap_err();
+        assert_eq!(err.status, Status::GenericFailure);
+        assert!(err.reason.starts_with("proof verification failed"));
+    }
+
+    #[test]
+    fn test_lookup_proof_verifies() {
+        let circuit = cubic(3, true);
+        let prover = setup(&circuit);
+        let proof = prove(&prover, &circuit, &circuit.instances);
+        assert!(plonk_verify(prover.vk.clone(), proof, le(&circuit.instances)).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_proofs_fail_verification() {
+        let circuit = cubic(3, true);
+        let prover = setup(&circuit);
+        let proof = prove(&prover, &circuit, &circuit.instances);
+        let instances = le(&circuit.instances);
+        let fails = |bytes: Vec<u8>| {
+            let err = plonk_verify(prover.vk.clone(), bytes, instances.clone()).unwrap_err();
+            assert_eq!(err.status, Status::GenericFailure, "{}", err.reason);
+        };
+        // Flip the last evaluation (A'(ζ/ω)): the identity no longer holds
+        let mut bad = proof.clone();
+        let last = bad.len() - SCALAR_BYTES;
+        bad[last] ^= 1;
+        fails(bad);
+        // Swap two opening witnesses: the identity holds, the pairing does not
+        let openings = (3 + 2 + 1 + 1 + 3) * G1_BYTES;
+        let mut bad = proof.clone();
+        bad.copy_within(openings..openings + G1_BYTES, openings + G1_BYTES);
+        fails(bad);
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        let circuit = cubic(3, false);
+        let prover = setup(&circuit);
+        let proof = prove(&prover, &circuit, &circuit.instances);
+        let instances = le(&circuit.instances);
+        let encoding_error = |bytes: Vec<u8>| {
+            let err = plonk_verify(prover.vk.clone(), bytes, instances.clone()).unwrap_err();
+            assert_eq!(err.status, Status::InvalidArg);
+            assert!(
+                err.reason.starts_with("invalid proof encoding"),
+                "{}",
+                err.reason
+            );
+        };
+        encoding_error(proof[..proof.len() - 1].to_vec());
+        encoding_error([proof.clone(), vec![0]].concat());
+        let mut off_curve = proof.clone();
+        off_curve[G1_BYTES - 1] ^= 1;
+        encoding_error(off_curve);
+        let mut unreduced = proof.clone();
+        let last = unreduced.len() - SCALAR_BYTES;
+        unreduced[last..].fill(0xff);
+        encoding_error(unreduced);
+
+        let mut vk = prover.vk.clone();
+        vk.lookup_table_commitment = Some(vk.gate_commitments[0].clone());
+        assert!(plonk_verify(vk, proof.clone(), instances.clone()).is_err());
+        let mut vk = prover.vk.clone();
+        vk.domain_size = 6;
+        assert!(plonk_verify(vk, proof.clone(), instances.clone()).is_err());
+
+        let q13 = encode_g2(&crate::bls12_381::g2_point_of_order_13());
+        for i in 0..2 {
+            let mut vk = prover.vk.clone();
+            vk.srs_g2[i] = q13.clone();
+            let err = plonk_verify(vk, proof.clone(), instances.clone()).unwrap_err();
+            assert_eq!(
+                err.reason,
+                format!("vk.srs_g2[{i}]: point is not in the BLS12-381 G2 subgroup")
+            );
+        }
+    }
+}
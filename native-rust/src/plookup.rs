@@ -0,0 +1,176 @@
+//! Lookup checks over BN254 scalar field tables
+//!
+//! [`verify_lookup`] checks that every witness value appears in a table with
+//! the logarithmic-derivative form of the lookup argument,
+//!
+//! ```text
+//! Σ_j 1 / (w_j + γ) = Σ_i m_i / (t_i + γ)
+//! ```
+//!
+//! where `m_i` counts the witnesses equal to `t_i` (only the first copy of a
+//! repeated table value is counted) and `γ` is drawn from a [`Transcript`]
+//! over the table and the witnesses. Both sides are equal as rational
+//! functions of `γ` exactly when every witness is in the table, so a
+//! witness outside it makes them differ except with probability about
+//! `(|t| + |w|) / r`. Values are 32-byte big-endian canonical elements.
+
+use std::collections::{HashMap, HashSet};
+
+use napi::Result;
+use napi_derive::napi;
+
+use crate::bn254::{parse_fr, Fr};
+use crate::field::batch_invert;
+use crate::transcript::Transcript;
+
+const TRANSCRIPT_LABEL: &str = "zk-accelerate lookup";
+
+fn parse_all(values: &[Vec<u8>], name: &str) -> Result<Vec<Fr>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| parse_fr(v, &format!("{name}[{i}]")))
+        .collect()
+}
+
+fn absorb(transcript: &mut Transcript, label: &str, values: &[Fr]) {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    transcript.append_message(label.to_string(), bytes);
+}
+
+/// `Σ weight_i / (value_i + γ)`, or `None` if some `value_i + γ` is zero
+fn weighted_inverse_sum(values: &[Fr], weights: impl Fn(usize) -> Fr, gamma: Fr) -> Option<Fr> {
+    let mut denominators: Vec<Fr> = values.iter().map(|&v| v + gamma).collect();
+    if denominators.iter().any(Fr::is_zero) {
+        return None;
+    }
+    batch_invert(&mut denominators);
+    Some(
+        denominators
+            .iter()
+            .enumerate()
+            .fold(Fr::zero(), |acc, (i, &d)| acc + weights(i) * d),
+    )
+}
+
+/// Whether every value of `witnesses` appears in `table`
+pub fn lookup(table: &[Fr], witnesses: &[Fr]) -> bool {
+    let mut counts: HashMap<[u64; 4], u64> = HashMap::new();
+    for w in witnesses {
+        *counts.entry(w.to_canonical()).or_default() += 1;
+    }
+    let multiplicities: Vec<Fr> = table
+        .iter()
+        .map(|t| Fr::from_u64(counts.remove(&t.to_canonical()).unwrap_or(0)))
+        .collect();
+
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL.to_string());
+    absorb(&mut transcript, "table", table);
+    absorb(&mut transcript, "witnesses", witnesses);
+    loop {
+        // A challenge that zeroes a denominator is redrawn
+        let gamma = transcript.challenge_fr("gamma");
+        let lhs = weighted_inverse_sum(witnesses, |_| Fr::one(), gamma);
+        let rhs = weighted_inverse_sum(table, |i| multiplicities[i], gamma);
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            return lhs == rhs;
+        }
+    }
+}
+
+/// Check that every witness value appears in `table`
+///
+/// Both lists hold 32-byte big-endian BN254 scalar field elements. Runs
+/// the logarithmic-derivative lookup argument with a Fiat-Shamir challenge,
+/// so it is the check a lookup prover commits to rather than a set lookup.
+/// Duplicates are allowed in either list, and an empty witness list passes.
+#[napi]
+pub fn verify_lookup(table: Vec<Vec<u8>>, witnesses: Vec<Vec<u8>>) -> Result<bool> {
+    let table = parse_all(&table, "table")?;
+    let witnesses = parse_all(&witnesses, "witnesses")?;
+    Ok(lookup(&table, &witnesses))
+}
+
+/// Deduplicate 32-byte big-endian BN254 scalars into a lookup table
+///
+/// Keeps the first copy of each value in input order, or with `sort` set
+/// returns the values in ascending order.
+#[napi]
+pub fn build_lookup_table(elements: Vec<Vec<u8>>, sort: bool) -> Result<Vec<Vec<u8>>> {
+    parse_all(&elements, "elements")?;
+    if sort {
+        // Canonical big-endian bytes order as the integers do
+        let mut sorted = elements;
+        sorted.sort_unstable();
+        sorted.dedup();
+        return Ok(sorted);
+    }
+    let mut seen = HashSet::new();
+    Ok(elements
+        .into_iter()
+        .filter(|e| seen.insert(e.clone()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr(v: u64) -> Vec<u8> {
+        Fr::from_u64(v).to_be_bytes()
+    }
+
+    fn range_table() -> Vec<Vec<u8>> {
+        (0..16).map(fr).collect()
+    }
+
+    #[test]
+    fn test_range_check() {
+        let witnesses = [3, 15, 0, 7, 7, 7].map(fr).to_vec();
+        assert!(verify_lookup(range_table(), witnesses.clone()).unwrap());
+        assert!(verify_lookup(range_table(), vec![]).unwrap());
+
+        let mut out_of_range = witnesses.clone();
+        out_of_range.push(fr(16));
+        assert!(!verify_lookup(range_table(), out_of_range).unwrap());
+        let negative = (-Fr::one()).to_be_bytes();
+        assert!(!verify_lookup(range_table(), vec![negative]).unwrap());
+        assert!(!verify_lookup(vec![], vec![fr(0)]).unwrap());
+
+        // Repeated table values neither help nor hurt
+        let mut doubled = range_table();
+        doubled.extend(range_table());
+        assert!(verify_lookup(doubled.clone(), witnesses).unwrap());
+        assert!(!verify_lookup(doubled, vec![fr(99)]).unwrap());
+    }
+
+    #[test]
+    fn test_build_lookup_table() {
+        let elements = [9, 2, 9, 0, 2, 5].map(fr).to_vec();
+        assert_eq!(
+            build_lookup_table(elements.clone(), false).unwrap(),
+            [9, 2, 0, 5].map(fr).to_vec()
+        );
+        assert_eq!(
+            build_lookup_table(elements, true).unwrap(),
+            [0, 2, 5, 9].map(fr).to_vec()
+        );
+        let large = (-Fr::one()).to_be_bytes();
+        let sorted = build_lookup_table(vec![large.clone(), fr(256), fr(1)], true).unwrap();
+        assert_eq!(sorted, vec![fr(1), fr(256), large]);
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let err = verify_lookup(range_table(), vec![vec![0; 31]]).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "witnesses[0]: expected a 32-byte big-endian field element, got 31 bytes"
+        );
+        let err = build_lookup_table(vec![fr(1), vec![0xff; 32]], true).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "elements[1]: value is not below the BN254 scalar field modulus"
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Poseidon hash over the BN254 scalar field
+//!
+//! Round constants and the MDS matrix are generated with the Grain LFSR from
+//! the reference `generate_parameters_grain.sage` script (prime field, `x^5`
+//! S-box, 254-bit field size), which is also how circomlib's constants were
+//! produced. Up to [`MAX_CACHED_PARAMS`] generated parameter sets are cached
+//! per `(t, rf, rp)`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+use crate::bn254::{parse_fr, Fr, FrConfig};
+use crate::montgomery::{geq, sub_limbs, MontConfig};
+
+/// Smallest and largest supported state widths
+pub const MIN_WIDTH: u32 = 2;
+pub const MAX_WIDTH: u32 = 8;
+
+/// Number of full rounds used by circomlib for every width
+pub const CIRCOMLIB_FULL_ROUNDS: u32 = 8;
+
+/// Largest `rf` or `rp`: Grain encodes each in a 10-bit field
+pub const MAX_ROUNDS: u32 = 1023;
+
+/// Largest `rf + rp`, bounding the constants one parameter set generates
+pub const MAX_TOTAL_ROUNDS: u32 = 1024;
+
+/// Parameter sets kept in the cache; sets beyond it are generated per call
+pub const MAX_CACHED_PARAMS: usize = 64;
+
+/// circomlib's partial round counts, indexed by `t - 2`
+const CIRCOMLIB_PARTIAL_ROUNDS: [u32; 7] = [56, 57, 56, 60, 60, 63, 64];
+
+/// Bit size of the BN254 scalar field, as fed to the Grain LFSR
+const FIELD_BITS: u32 = 254;
+
+/// Round constants and MDS matrix for one `(t, rf, rp)` instance
+#[derive(Debug)]
+pub struct PoseidonParams {
+    pub t: usize,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    /// `(rf + rp) * t` constants, consumed `t` per round
+    pub round_constants: Vec<Fr>,
+    /// `t x t` Cauchy matrix, row-major
+    pub mds: Vec<Vec<Fr>>,
+}
+
+type ParamsCache = HashMap<(u32, u32, u32), Arc<PoseidonParams>>;
+
+static PARAMS_CACHE: Lazy<Mutex<ParamsCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Grain LFSR in self-shrinking mode, as specified in the Poseidon paper
+struct Grain {
+    /// 80-bit register; bit `i` is element `i` of the reference bit list
+    state: u128,
+}
+
+impl Grain {
+    fn new(t: u32, rf: u32, rp: u32) -> Self {
+        // field = 1 (prime), sbox = 0 (x^alpha), n, t, R_F, R_P, then 30 one bits
+        let fields: [(u32, u32); 6] = [
+            (1, 2),
+            (0, 4),
+            (FIELD_BITS, 12),
+            (t, 12),
+            (rf, 10),
+            (rp, 10),
+        ];
+        let mut state = 0u128;
+        let mut pos = 0;
+        for (value, width) in fields {
+            for i in (0..width).rev() {
+                state |= (((value >> i) & 1) as u128) << pos;
+                pos += 1;
+            }
+        }
+        for _ in 0..30 {
+            state |= 1u128 << pos;
+            pos += 1;
+        }
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+
+    fn clock(&mut self) -> u128 {
+        let s = self.state;
+        let bit = (s ^ (s >> 13) ^ (s >> 23) ^ (s >> 38) ^ (s >> 51) ^ (s >> 62)) & 1;
+        self.state = (s >> 1) | (bit << 79);
+        bit
+    }
+
+    /// Next output bit: bits are consumed in pairs and the second one is kept
+    /// only when the first is set
+    fn next_bit(&mut self) -> u64 {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep == 1 {
+                return bit as u64;
+            }
+        }
+    }
+
+    /// Next `FIELD_BITS`-bit integer, most significant bit first
+    fn next_integer(&mut self) -> [u64; 4] {
+        let mut limbs = [0u64; 4];
+        for _ in 0..FIELD_BITS {
+            let bit = self.next_bit();
+            for i in (1..4).rev() {
+                limbs[i] = (limbs[i] << 1) | (limbs[i - 1] >> 63);
+            }
+            limbs[0] = (limbs[0] << 1) | bit;
+        }
+        limbs
+    }
+
+    /// Next field element by rejection sampling
+    fn next_field_element(&mut self) -> Fr {
+        loop {
+            if let Some(v) = Fr::from_canonical(self.next_integer()) {
+                return v;
+            }
+        }
+    }
+
+    /// Next field element reduced modulo `r` (used for the MDS seeds)
+    fn next_reduced(&mut self) -> Fr {
+        let mut v = self.next_integer();
+        while geq(&v, &FrConfig::MODULUS) {
+            v = sub_limbs(&v, &FrConfig::MODULUS).0;
+        }
+        Fr::from_canonical(v).unwrap()
+    }
+}
+
+impl PoseidonParams {
+    /// Generate the parameters for a width and round configuration
+    ///
+    /// Panics if the constant count overflows; [`params`] bounds the
+    /// arguments first.
+    pub fn generate(t: u32, rf: u32, rp: u32) -> Self {
+        let mut grain = Grain::new(t, rf, rp);
+        let width = t as usize;
+        let constants = (rf as usize)
+            .checked_add(rp as usize)
+            .and_then(|rounds| rounds.checked_mul(width))
+            .expect("round constant count overflows usize");
+        let round_constants = (0..constants).map(|_| grain.next_field_element()).collect();
+
+        let mds = loop {
+            let mut seeds: Vec<Fr> = (0..2 * width).map(|_| grain.next_reduced()).collect();
+            while has_duplicates(&seeds) {
+                seeds = (0..2 * width).map(|_| grain.next_reduced()).collect();
+            }
+            let (xs, ys) = seeds.split_at(width);
+            let matrix: Option<Vec<Vec<Fr>>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x + *y).inverse()).collect())
+                .collect();
+            if let Some(matrix) = matrix {
+                break matrix;
+            }
+        };
+
+        PoseidonParams {
+            t: width,
+            full_rounds: rf as usize,
+            partial_rounds: rp as usize,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Apply the Poseidon permutation to `state` in place
+    pub fn permute(&self, state: &mut [Fr]) {
+        debug_assert_eq!(state.len(), self.t);
+        let half_full = self.full_rounds / 2;
+        let mut scratch = vec![Fr::zero(); self.t];
+        for (round, constants) in self.round_constants.chunks(self.t).enumerate() {
+            for (s, c) in state.iter_mut().zip(constants) {
+                *s += *c;
+            }
+            if round < half_full || round >= half_full + self.partial_rounds {
+                state.iter_mut().for_each(|s| *s = sbox(*s));
+            } else {
+                state[0] = sbox(state[0]);
+            }
+            for (out, row) in scratch.iter_mut().zip(&self.mds) {
+                *out = row
+                    .iter()
+                    .zip(state.iter())
+                    .fold(Fr::zero(), |acc, (m, s)| acc + *m * *s);
+            }
+            state.copy_from_slice(&scratch);
+        }
+    }
+}
+
+fn has_duplicates(values: &[Fr]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .any(|(i, a)| values[i + 1..].contains(a))
+}
+
+#[inline]
+fn sbox(x: Fr) -> Fr {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// Fetch (or generate and cache) the parameters for `(t, rf, rp)`
+pub fn params(t: u32, rf: u32, rp: u32) -> Result<Arc<PoseidonParams>> {
+    if !(MIN_WIDTH..=MAX_WIDTH).contains(&t) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("t must be between {MIN_WIDTH} and {MAX_WIDTH}, got {t}"),
+        ));
+    }
+    if rf == 0 || !rf.is_multiple_of(2) || rf > MAX_ROUNDS {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "rf must be a positive even number of full rounds up to {MAX_ROUNDS}, got {rf}"
+            ),
+        ));
+    }
+    if rp > MAX_ROUNDS {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("rp must be at most {MAX_ROUNDS}, got {rp}"),
+        ));
+    }
+    if rf + rp > MAX_TOTAL_ROUNDS {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "rf + rp must be at most {MAX_TOTAL_ROUNDS}, got {}",
+                rf + rp
+            ),
+        ));
+    }
+    let key = (t, rf, rp);
+    if let Some(cached) = PARAMS_CACHE.lock().unwrap().get(&key) {
+        return Ok(cached.clone());
+    }
+    // Generated without the lock, so other widths stay available meanwhile
+    let generated = Arc::new(PoseidonParams::generate(t, rf, rp));
+    let mut cache = PARAMS_CACHE.lock().unwrap();
+    if let Some(cached) = cache.get(&key) {
+        return Ok(cached.clone());
+    }
+    if cache.len() < MAX_CACHED_PARAMS {
+        cache.insert(key, generated.clone());
+    }
+    Ok(generated)
+}
+
+/// Hash `t - 1` field elements: the state is `[0, inputs...]` and the output
+/// is the first state element after the permutation
+pub fn hash_with(inputs: &[Fr], t: u32, rf: u32, rp: u32) -> Result<Fr> {
+    let params = params(t, rf, rp)?;
+    if inputs.len() + 1 != params.t {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "a width-{t} Poseidon instance hashes {} inputs, got {}",
+                t - 1,
+                inputs.len()
+            ),
+        ));
+    }
+    let mut state = Vec::with_capacity(params.t);
+    state.push(Fr::zero());
+    state.extend_from_slice(inputs);
+    params.permute(&mut state);
+    Ok(state[0])
+}
+
+/// Hash with circomlib's parameters for the given number of inputs (1 to 7)
+pub fn hash(inputs: &[Fr]) -> Result<Fr> {
+    let t = inputs.len() as u32 + 1;
+    if !(MIN_WIDTH..=MAX_WIDTH).contains(&t) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "circomlib Poseidon takes 1 to {} inputs, got {}",
+                MAX_WIDTH - 1,
+                inputs.len()
+            ),
+        ));
+    }
+    let rp = CIRCOMLIB_PARTIAL_ROUNDS[(t - MIN_WIDTH) as usize];
+    hash_with(inputs, t, CIRCOMLIB_FULL_ROUNDS, rp)
+}
+
+fn parse_inputs(inputs: &[Vec<u8>]) -> Result<Vec<Fr>> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, bytes)| parse_fr(bytes, &format!("inputs[{i}]")))
+        .collect()
+}
+
+/// Poseidon hash with an explicit width and round configuration
+///
+/// Inputs are 32-byte big-endian BN254 scalar field elements; exactly
+/// `t - 1` of them are required. Returns the 32-byte big-endian digest.
+#[napi]
+pub fn poseidon_hash(inputs: Vec<Vec<u8>>, t: u32, rf: u32, rp: u32) -> Result<Vec<u8>> {
+    Ok(hash_with(&parse_inputs(&inputs)?, t, rf, rp)?.to_be_bytes())
+}
+
+/// Poseidon hash with circomlib-compatible parameters
+///
+/// Two inputs use `t = 3, rf = 8, rp = 57`; other input counts follow
+/// circomlib's table for the matching width.
+#[napi]
+pub fn poseidon_hash_default(inputs: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    Ok(hash(&parse_inputs(&inputs)?)?.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr(v: u64) -> Vec<u8> {
+        Fr::from_u64(v).to_be_bytes()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_circomlib_vectors() {
+        // Reference outputs of circomlibjs `poseidon([...])`
+        assert_eq!(
+            poseidon_hash_default(vec![fr(1), fr(2)]).unwrap(),
+            hex("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+        );
+        assert_eq!(
+            poseidon_hash_default(vec![fr(1)]).unwrap(),
+            hex("29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133")
+        );
+        assert_eq!(
+            poseidon_hash_default(vec![fr(1), fr(2), fr(3), fr(4)]).unwrap(),
+            hex("299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465")
+        );
+    }
+
+    #[test]
+    fn test_circomlib_constants() {
+        let params = params(3, 8, 57).unwrap();
+        assert_eq!(params.round_constants.len(), 65 * 3);
+        assert_eq!(
+            params.round_constants[0].to_be_bytes(),
+            hex("0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e")
+        );
+        assert_eq!(
+            params.mds[0][0].to_be_bytes(),
+            hex("109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b")
+        );
+    }
+
+    #[test]
+    fn test_explicit_parameters_match_default() {
+        let inputs = vec![fr(7), fr(11)];
+        assert_eq!(
+            poseidon_hash(inputs.clone(), 3, 8, 57).unwrap(),
+            poseidon_hash_default(inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(poseidon_hash(vec![fr(1)], 1, 8, 57).is_err());
+        assert!(poseidon_hash(vec![fr(1); 8], 9, 8, 57).is_err());
+        assert!(poseidon_hash(vec![fr(1), fr(2)], 3, 7, 57).is_err());
+        // Grain's 10-bit round fields would alias 1024 with 0
+        let err = params(3, 8, 1024).unwrap_err();
+        assert_eq!(err.reason, "rp must be at most 1023, got 1024");
+        let err = params(3, 1024, 0).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "rf must be a positive even number of full rounds up to 1023, got 1024"
+        );
+        let err = params(3, 8, u32::MAX).unwrap_err();
+        assert_eq!(
+            err.reason,
+            format!("rp must be at most 1023, got {}", u32::MAX)
+        );
+        let err = params(3, 2, 1023).unwrap_err();
+        assert_eq!(err.reason, "rf + rp must be at most 1024, got 1025");
+        assert_eq!(params(2, 2, 1022).unwrap().round_constants.len(), 2048);
+        // Input count must match the width
+        assert!(poseidon_hash(vec![fr(1)], 3, 8, 57).is_err());
+        assert!(poseidon_hash_default(vec![]).is_err());
+    }
+}
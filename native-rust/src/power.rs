@@ -0,0 +1,117 @@
+//! Thermal pressure and power source
+//!
+//! macOS reports thermal pressure and Low Power Mode through `NSProcessInfo`
+//! and the power source through IOKit's power-source snapshot. Other
+//! platforms report the thermal state as "unknown" and both flags as false.
+//! Nothing here needs entitlements, and every value is re-read on each call.
+
+use napi_derive::napi;
+
+/// Thermal pressure names by `NSProcessInfoThermalState` value
+const THERMAL_STATES: [&str; 4] = ["nominal", "fair", "serious", "critical"];
+
+/// Thermal state reported where it cannot be read
+pub const UNKNOWN: &str = "unknown";
+
+/// Power and thermal signals for shedding work on hot or battery-constrained machines
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerState {
+    /// "nominal", "fair", "serious", "critical", or "unknown" off macOS
+    pub thermal_pressure: String,
+    /// Whether Low Power Mode is enabled, false if unknown
+    pub low_power_mode: bool,
+    /// Whether the machine is running on battery, false if unknown
+    pub on_battery: bool,
+}
+
+/// Name of an `NSProcessInfoThermalState` value
+pub fn thermal_state_name(state: i64) -> &'static str {
+    usize::try_from(state)
+        .ok()
+        .and_then(|i| THERMAL_STATES.get(i))
+        .copied()
+        .unwrap_or(UNKNOWN)
+}
+
+/// Current `NSProcessInfo` thermal pressure name, "unknown" off macOS
+pub fn thermal_pressure() -> &'static str {
+    #[cfg(target_os = "macos")]
+    {
+        use objc::runtime::Object;
+        use objc::{class, msg_send, sel, sel_impl};
+
+        objc::rc::autoreleasepool(|| {
+            // SAFETY: `processInfo` returns the shared instance; `thermalState`
+            // exists from macOS 10.10
+            let state: isize = unsafe {
+                let info: *mut Object = msg_send![class!(NSProcessInfo), processInfo];
+                msg_send![info, thermalState]
+            };
+            thermal_state_name(state as i64)
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        UNKNOWN
+    }
+}
+
+/// Read the current thermal pressure, Low Power Mode and power source
+#[napi]
+pub fn get_power_state() -> PowerState {
+    #[cfg(target_os = "macos")]
+    {
+        use objc::runtime::{Object, BOOL, NO};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        objc::rc::autoreleasepool(|| {
+            // SAFETY: `processInfo` returns the shared instance;
+            // `isLowPowerModeEnabled` exists from macOS 12, the deployment target
+            let low_power: BOOL = unsafe {
+                let info: *mut Object = msg_send![class!(NSProcessInfo), processInfo];
+                msg_send![info, isLowPowerModeEnabled]
+            };
+            PowerState {
+                thermal_pressure: thermal_pressure().to_string(),
+                low_power_mode: low_power != NO,
+                on_battery: crate::iokit::providing_power_source()
+                    .is_some_and(|source| source == "Battery Power"),
+            }
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        PowerState {
+            thermal_pressure: UNKNOWN.to_string(),
+            low_power_mode: false,
+            on_battery: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thermal_state_names() {
+        assert_eq!(thermal_state_name(0), "nominal");
+        assert_eq!(thermal_state_name(1), "fair");
+        assert_eq!(thermal_state_name(2), "serious");
+        assert_eq!(thermal_state_name(3), "critical");
+        assert_eq!(thermal_state_name(4), UNKNOWN);
+        assert_eq!(thermal_state_name(-1), UNKNOWN);
+    }
+
+    #[test]
+    fn test_power_state_populated() {
+        let state = get_power_state();
+        if cfg!(target_os = "macos") {
+            assert!(THERMAL_STATES.contains(&state.thermal_pressure.as_str()));
+        } else {
+            assert_eq!(state.thermal_pressure, UNKNOWN);
+            assert!(!state.low_power_mode && !state.on_battery);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Witness checks against circom R1CS constraint systems
+//!
+//! [`check_r1cs`] evaluates every constraint `<a, w> · <b, w> = <c, w>` of a
+//! `.r1cs` file over BN254 and reports the first one a witness violates,
+//! which is much cheaper to debug than a proof that fails to verify. Large
+//! systems are checked in parallel. [`count_constraints`] and
+//! [`count_wires`] read only the header.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{parse_fr, Fr};
+use crate::circom::{Constraint, R1cs, R1csHeader};
+use crate::encoding::to_decimal;
+use crate::field::ELEMENTWISE_PARALLEL_THRESHOLD;
+
+/// Outcome of [`check_r1cs`]
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csCheckResult {
+    /// Whether the witness satisfies every constraint
+    pub satisfied: bool,
+    /// Index of the first violated constraint, absent when satisfied
+    pub failed_constraint_index: Option<u32>,
+    /// The violated constraint and its values, empty when satisfied
+    pub failed_constraint_description: String,
+}
+
+/// `<lc, w>`
+fn eval(lc: &[(u32, Fr)], witness: &[Fr]) -> Fr {
+    lc.iter().fold(Fr::zero(), |acc, &(wire, coeff)| {
+        acc + coeff * witness[wire as usize]
+    })
+}
+
+fn is_satisfied(k: &Constraint, witness: &[Fr]) -> bool {
+    eval(&k.a, witness) * eval(&k.b, witness) == eval(&k.c, witness)
+}
+
+/// Index of the first constraint `witness` violates
+///
+/// The witness must have one value per wire and start with the constant 1.
+pub fn first_unsatisfied(r1cs: &R1cs, witness: &[Fr]) -> Result<Option<usize>> {
+    if witness.len() != r1cs.n_wires {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "witness: expected {} values, got {}",
+                r1cs.n_wires,
+                witness.len()
+            ),
+        ));
+    }
+    if witness[0] != Fr::one() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "witness: the first value must be 1".to_string(),
+        ));
+    }
+    let per = r1cs
+        .constraints
+        .len()
+        .div_ceil(crate::parallel::num_threads())
+        .max(ELEMENTWISE_PARALLEL_THRESHOLD);
+    let chunks: Vec<&[Constraint]> = r1cs.constraints.chunks(per).collect();
+    Ok(crate::parallel::par_map(&chunks, |chunk| {
+        chunk.iter().position(|k| !is_satisfied(k, witness))
+    })
+    .into_iter()
+    .enumerate()
+    .find_map(|(i, first)| first.map(|j| i * per + j)))
+}
+
+/// Decimal value, written negative when that is shorter
+fn signed(value: Fr) -> String {
+    let (pos, neg) = (to_decimal(value), to_decimal(-value));
+    if neg.len() < pos.len() {
+        format!("-{neg}")
+    } else {
+        pos
+    }
+}
+
+/// A linear combination as `3*w1 - w2 + ...`, or `0` when empty
+fn describe_lc(lc: &[(u32, Fr)]) -> String {
+    let mut out = String::new();
+    for (i, &(wire, coeff)) in lc.iter().enumerate() {
+        let value = signed(coeff);
+        let (negative, magnitude) = match value.strip_prefix('-') {
+            Some(m) => (true, m),
+            None => (false, value.as_str()),
+        };
+        let sign = match (i, negative) {
+            (0, true) => "-",
+            (0, false) => "",
+            (_, true) => " - ",
+            (_, false) => " + ",
+        };
+        let term = if magnitude == "1" {
+            format!("w{wire}")
+        } else {
+            format!("{magnitude}*w{wire}")
+        };
+        out.push_str(sign);
+        out.push_str(&term);
+    }
+    if out.is_empty() {
+        out.push('0');
+    }
+    out
+}
+
+/// A violated constraint with the values it took
+fn describe_failure(k: &Constraint, witness: &[Fr]) -> String {
+    let (a, b, c) = (
+        eval(&k.a, witness),
+        eval(&k.b, witness),
+        eval(&k.c, witness),
+    );
+    format!(
+        "({}) * ({}) = ({}) fails: {} * {} = {}, not {}",
+        describe_lc(&k.a),
+        describe_lc(&k.b),
+        describe_lc(&k.c),
+        signed(a),
+        signed(b),
+        signed(a * b),
+        signed(c)
+    )
+}
+
+/// Check a witness against a parsed constraint system
+pub fn check(r1cs: &R1cs, witness: &[Fr]) -> Result<R1csCheckResult> {
+    Ok(match first_unsatisfied(r1cs, witness)? {
+        None => R1csCheckResult {
+            satisfied: true,
+            failed_constraint_index: None,
+            failed_constraint_description: String::new(),
+        },
+        Some(i) => R1csCheckResult {
+            satisfied: false,
+            failed_constraint_index: Some(i as u32),
+            failed_constraint_description: describe_failure(&r1cs.constraints[i], witness),
+        },
+    })
+}
+
+/// Check whether `witness` satisfies every constraint of a circom `.r1cs`
+///
+/// `witness` holds one 32-byte big-endian BN254 scalar per wire, starting
+/// with the constant 1. A witness that violates a constraint is not an
+/// error: the result names the first such constraint, e.g.
+/// `"(-w2) * (w3) = (-w1) fails: -3 * 11 = -33, not -34"`. Malformed files,
+/// non-BN254 circuits and witnesses of the wrong length throw.
+#[napi]
+pub fn check_r1cs(r1cs: Vec<u8>, witness: Vec<Vec<u8>>) -> Result<R1csCheckResult> {
+    let r1cs = R1cs::parse(&r1cs, "r1cs")?;
+    let witness = witness
+        .iter()
+        .enumerate()
+        .map(|(i, v)| parse_fr(v, &format!("witness[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    check(&r1cs, &witness)
+}
+
+/// Number of constraints in a `.r1cs` file, from its header alone
+#[napi]
+pub fn count_constraints(r1cs: Vec<u8>) -> Result<u32> {
+    Ok(R1csHeader::parse(&r1cs, "r1cs")?.n_constraints as u32)
+}
+
+/// Number of wires in a `.r1cs` file, including the constant 1, from its
+/// header alone
+#[napi]
+pub fn count_wires(r1cs: Vec<u8>) -> Result<u32> {
+    Ok(R1csHeader::parse(&r1cs, "r1cs")?.n_wires as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLIER_R1CS: &[u8] = include_bytes!("../tests/fixtures/multiplier.r1cs");
+
+    /// `[1, c, a, b]` for the circuit `c <== a * b`
+    fn witness(c: u64, a: u64, b: u64) -> Vec<Vec<u8>> {
+        [1, c, a, b].map(|v| Fr::from_u64(v).to_be_bytes()).to_vec()
+    }
+
+    #[test]
+    fn test_multiplier() {
+        let ok = check_r1cs(MULTIPLIER_R1CS.to_vec(), witness(33, 3, 11)).unwrap();
+        assert_eq!(
+            ok,
+            R1csCheckResult {
+                satisfied: true,
+                failed_constraint_index: None,
+                failed_constraint_description: String::new(),
+            }
+        );
+        let bad = check_r1cs(MULTIPLIER_R1CS.to_vec(), witness(34, 3, 11)).unwrap();
+        assert!(!bad.satisfied);
+        assert_eq!(bad.failed_constraint_index, Some(0));
+        assert_eq!(
+            bad.failed_constraint_description,
+            "(-w2) * (w3) = (-w1) fails: -3 * 11 = -33, not -34"
+        );
+
+        assert_eq!(count_constraints(MULTIPLIER_R1CS.to_vec()).unwrap(), 1);
+        assert_eq!(count_wires(MULTIPLIER_R1CS.to_vec()).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let mut short = witness(33, 3, 11);
+        short.pop();
+        let err = check_r1cs(MULTIPLIER_R1CS.to_vec(), short).unwrap_err();
+        assert_eq!(err.reason, "witness: expected 4 values, got 3");
+        let mut unscaled = witness(33, 3, 11);
+        unscaled[0] = Fr::from_u64(2).to_be_bytes();
+        let err = check_r1cs(MULTIPLIER_R1CS.to_vec(), unscaled).unwrap_err();
+        assert_eq!(err.reason, "witness: the first value must be 1");
+        let mut wide = witness(33, 3, 11);
+        wide[2] = vec![0xff; 32];
+        assert!(check_r1cs(MULTIPLIER_R1CS.to_vec(), wide).is_err());
+        assert!(count_wires(MULTIPLIER_R1CS[..20].to_vec()).is_err());
+        assert!(count_constraints(b"zkey".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_first_failure_across_workers() {
+        // Many copies of the multiplier constraint, so the scan splits
+        let base = R1cs::parse(MULTIPLIER_R1CS, "r1cs").unwrap();
+        let n = 3 * ELEMENTWISE_PARALLEL_THRESHOLD + 5;
+        let mut r1cs = base.clone();
+        r1cs.constraints = vec![base.constraints[0].clone(); n];
+        let broken = 2 * ELEMENTWISE_PARALLEL_THRESHOLD + 1;
+        // The copy at `broken` and every later one expects w1 = -a * b
+        for k in &mut r1cs.constraints[broken..] {
+            k.c[0].1 = Fr::one();
+        }
+        let w: Vec<Fr> = [1, 33, 3, 11].map(Fr::from_u64).to_vec();
+        let result = check(&r1cs, &w).unwrap();
+        assert_eq!(result.failed_constraint_index, Some(broken as u32));
+        assert_eq!(
+            result.failed_constraint_description,
+            "(-w2) * (w3) = (w1) fails: -3 * 11 = -33, not 33"
+        );
+        assert!(check(&base, &w).unwrap().satisfied);
+    }
+
+    #[test]
+    fn test_describe_lc() {
+        let lc = [(0, Fr::from_u64(5)), (4, -Fr::from_u64(2)), (7, Fr::one())];
+        assert_eq!(describe_lc(&lc), "5*w0 - 2*w4 + w7");
+        assert_eq!(describe_lc(&[]), "0");
+    }
+}
@@ -0,0 +1,293 @@
+//! Uniformly random field elements for test fixtures and blinding factors
+//!
+//! Elements come from ChaCha20, seeded with the caller's 32-byte seed for
+//! reproducible output or from the OS RNG otherwise. Each draw fills the
+//! modulus's bit length and is rejected if it is `>= p`, so every output is
+//! canonical and uniform modulo `p`. Every draw is accepted with
+//! probability above one half.
+//!
+//! Output is packed little-endian in the encoding the field's own functions
+//! take: 32 bytes per element for the 256-bit fields, 48 for the BLS12-381
+//! base field, 8 for Goldilocks and 4 for BabyBear and Mersenne31. BabyBear
+//! values are canonical; since the Montgomery map is a bijection they are
+//! equally uniform when read as Montgomery-form values.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, RngCore, SeedableRng};
+
+use crate::montgomery::{geq, MontConfig};
+
+/// Size in bytes of a `random_field_elements` seed
+pub const SEED_BYTES: usize = 32;
+
+/// Fields `random_field_elements` can sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldName {
+    Bn254Fr,
+    Bn254Fq,
+    Bls12_381Fr,
+    Bls12_381Fp,
+    PallasFp,
+    VestaFp,
+    Goldilocks,
+    BabyBear,
+    M31,
+}
+
+impl FieldName {
+    /// Every field with the name callers pass
+    pub const ALL: [(&'static str, FieldName); 9] = [
+        ("bn254-fr", FieldName::Bn254Fr),
+        ("bn254-fq", FieldName::Bn254Fq),
+        ("bls12-381-fr", FieldName::Bls12_381Fr),
+        ("bls12-381-fp", FieldName::Bls12_381Fp),
+        ("pallas-fp", FieldName::PallasFp),
+        ("vesta-fp", FieldName::VestaFp),
+        ("goldilocks", FieldName::Goldilocks),
+        ("babybear", FieldName::BabyBear),
+        ("m31", FieldName::M31),
+    ];
+
+    /// Look up a field by name, ignoring ASCII case
+    pub fn parse(name: &str) -> Result<Self> {
+        let lower = name.to_ascii_lowercase();
+        Self::ALL
+            .iter()
+            .find(|(n, _)| *n == lower)
+            .map(|&(_, field)| field)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|(n, _)| *n).collect();
+                Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "field: unknown field \"{name}\", expected one of {}",
+                        known.join(", ")
+                    ),
+                )
+            })
+    }
+
+    /// The name callers pass for this field
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|&&(_, field)| field == self)
+            .map(|&(name, _)| name)
+            .expect("every field is listed")
+    }
+
+    /// Size in bytes of one encoded element
+    pub fn element_bytes(self) -> usize {
+        match self {
+            FieldName::Bls12_381Fp => 48,
+            FieldName::Goldilocks => 8,
+            FieldName::BabyBear | FieldName::M31 => 4,
+            _ => 32,
+        }
+    }
+
+    /// The modulus, little-endian in [`Self::element_bytes`] bytes
+    pub fn modulus_le_bytes(self) -> Vec<u8> {
+        fn limbs<C: MontConfig<N>, const N: usize>() -> Vec<u8> {
+            C::MODULUS.iter().flat_map(|l| l.to_le_bytes()).collect()
+        }
+        match self {
+            FieldName::Bn254Fr => limbs::<crate::bn254::FrConfig, 4>(),
+            FieldName::Bn254Fq => limbs::<crate::bn254::FqConfig, 4>(),
+            FieldName::Bls12_381Fr => limbs::<crate::bls12_381::FrConfig, 4>(),
+            FieldName::Bls12_381Fp => limbs::<crate::bls12_381::FpConfig, 6>(),
+            FieldName::PallasFp => limbs::<crate::pasta::FpConfig, 4>(),
+            FieldName::VestaFp => limbs::<crate::pasta::FqConfig, 4>(),
+            FieldName::Goldilocks => crate::goldilocks::MODULUS.to_le_bytes().to_vec(),
+            FieldName::BabyBear => crate::babybear::MODULUS.to_le_bytes().to_vec(),
+            FieldName::M31 => crate::m31::MODULUS.to_le_bytes().to_vec(),
+        }
+    }
+}
+
+/// Mask keeping the bit length of `top`, the most significant modulus limb
+fn top_mask(top: u64) -> u64 {
+    u64::MAX >> top.leading_zeros()
+}
+
+/// Append one uniform element of a Montgomery-backend field
+fn sample_mont<C: MontConfig<N>, const N: usize>(rng: &mut ChaCha20Rng, out: &mut Vec<u8>) {
+    let mask = top_mask(C::MODULUS[N - 1]);
+    loop {
+        let mut limbs = [0u64; N];
+        for limb in limbs.iter_mut() {
+            *limb = rng.next_u64();
+        }
+        limbs[N - 1] &= mask;
+        if !geq(&limbs, &C::MODULUS) {
+            out.extend(limbs.iter().flat_map(|l| l.to_le_bytes()));
+            return;
+        }
+    }
+}
+
+/// One uniform value below a word-sized modulus
+fn sample_word(rng: &mut ChaCha20Rng, modulus: u64) -> u64 {
+    let mask = top_mask(modulus);
+    loop {
+        let v = rng.next_u64() & mask;
+        if v < modulus {
+            return v;
+        }
+    }
+}
+
+/// `count` packed little-endian uniform elements of `field`
+///
+/// `seed`, when given, must be [`SEED_BYTES`] long; the same seed always
+/// yields the same elements.
+pub fn random_elements(field: FieldName, count: usize, seed: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut rng = match seed {
+        Some(seed) => {
+            let seed: [u8; SEED_BYTES] = seed.try_into().map_err(|_| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "seed: expected {SEED_BYTES} bytes, got {} bytes",
+                        seed.len()
+                    ),
+                )
+            })?;
+            ChaCha20Rng::from_seed(seed)
+        }
+        None => ChaCha20Rng::from_rng(OsRng).map_err(|e| {
+            Error::new(
+                Status::GenericFailure,
+                format!("failed to seed from the OS RNG: {e}"),
+            )
+        })?,
+    };
+    let mut out = Vec::with_capacity(count * field.element_bytes());
+    for _ in 0..count {
+        match field {
+            FieldName::Bn254Fr => sample_mont::<crate::bn254::FrConfig, 4>(&mut rng, &mut out),
+            FieldName::Bn254Fq => sample_mont::<crate::bn254::FqConfig, 4>(&mut rng, &mut out),
+            FieldName::Bls12_381Fr => {
+                sample_mont::<crate::bls12_381::FrConfig, 4>(&mut rng, &mut out)
+            }
+            FieldName::Bls12_381Fp => {
+                sample_mont::<crate::bls12_381::FpConfig, 6>(&mut rng, &mut out)
+            }
+            FieldName::PallasFp => sample_mont::<crate::pasta::FpConfig, 4>(&mut rng, &mut out),
+            FieldName::VestaFp => sample_mont::<crate::pasta::FqConfig, 4>(&mut rng, &mut out),
+            FieldName::Goldilocks => {
+                let v = sample_word(&mut rng, crate::goldilocks::MODULUS);
+                out.extend(v.to_le_bytes());
+            }
+            FieldName::BabyBear => {
+                let v = sample_word(&mut rng, crate::babybear::MODULUS.into()) as u32;
+                out.extend(v.to_le_bytes());
+            }
+            FieldName::M31 => {
+                let v = sample_word(&mut rng, crate::m31::MODULUS.into()) as u32;
+                out.extend(v.to_le_bytes());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// `count` uniformly random elements of `field`, packed little-endian
+///
+/// `field` is one of "bn254-fr", "bn254-fq", "bls12-381-fr",
+/// "bls12-381-fp", "pallas-fp", "vesta-fp", "goldilocks", "babybear" or
+/// "m31". With a 32-byte `seed` the output is reproducible (ChaCha20);
+/// without one it is seeded from the OS RNG.
+#[napi]
+pub fn random_field_elements(field: String, count: u32, seed: Option<Buffer>) -> Result<Buffer> {
+    let field = FieldName::parse(&field)?;
+    random_elements(field, count as usize, seed.as_deref()).map(Buffer::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether every element of `bytes` is below the field modulus
+    fn all_canonical(field: FieldName, bytes: &[u8]) -> bool {
+        let word = |chunk: &[u8]| {
+            let mut le = [0u8; 8];
+            le[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(le)
+        };
+        bytes
+            .chunks_exact(field.element_bytes())
+            .all(|chunk| match field {
+                FieldName::Bn254Fr => crate::bn254::Fr::from_le_bytes(chunk).is_some(),
+                FieldName::Bn254Fq => crate::bn254::Fq::from_le_bytes(chunk).is_some(),
+                FieldName::Bls12_381Fr => crate::bls12_381::Fr::from_le_bytes(chunk).is_some(),
+                FieldName::Bls12_381Fp => crate::bls12_381::Fp::from_le_bytes(chunk).is_some(),
+                FieldName::PallasFp => crate::pasta::Fp::from_le_bytes(chunk).is_some(),
+                FieldName::VestaFp => crate::pasta::Fq::from_le_bytes(chunk).is_some(),
+                FieldName::Goldilocks => word(chunk) < crate::goldilocks::MODULUS,
+                FieldName::BabyBear => word(chunk) < crate::babybear::MODULUS.into(),
+                FieldName::M31 => word(chunk) < crate::m31::MODULUS.into(),
+            })
+    }
+
+    #[test]
+    fn test_seeded_output_is_reproducible_and_canonical() {
+        let seed = [7u8; SEED_BYTES];
+        for (name, field) in FieldName::ALL {
+            let a = random_elements(field, 1000, Some(&seed)).unwrap();
+            assert_eq!(a.len(), 1000 * field.element_bytes(), "{name}");
+            assert_eq!(
+                random_elements(field, 1000, Some(&seed)).unwrap(),
+                a,
+                "{name}"
+            );
+            assert!(all_canonical(field, &a), "{name}");
+            // A prefix of a longer draw
+            let b = random_elements(field, 10, Some(&seed)).unwrap();
+            assert_eq!(b, a[..b.len()], "{name}");
+
+            let other = random_elements(field, 1000, Some(&[8u8; SEED_BYTES])).unwrap();
+            assert_ne!(other, a, "{name}");
+            let unseeded = random_elements(field, 1000, None).unwrap();
+            assert!(all_canonical(field, &unseeded), "{name}");
+            assert_ne!(
+                unseeded,
+                random_elements(field, 1000, None).unwrap(),
+                "{name}"
+            );
+        }
+        assert!(random_elements(FieldName::Bn254Fr, 0, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_high_bits_are_used() {
+        // Pasta's p is just above 2^254, so about half of all elements have
+        // bit 253 set
+        let values = random_elements(FieldName::PallasFp, 4000, Some(&[1; SEED_BYTES])).unwrap();
+        let high = values
+            .chunks_exact(32)
+            .filter(|v| v[31] & 0x20 != 0)
+            .count();
+        assert!((1000..3000).contains(&high), "{high}");
+    }
+
+    #[test]
+    fn test_names_and_seed_length() {
+        assert_eq!(FieldName::parse("BN254-Fr").unwrap(), FieldName::Bn254Fr);
+        let err = FieldName::parse("bn256").unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(
+            err.reason
+                .starts_with("field: unknown field \"bn256\", expected one of bn254-fr"),
+            "{}",
+            err.reason
+        );
+        let err = random_elements(FieldName::M31, 1, Some(&[0; 16])).unwrap_err();
+        assert_eq!(err.reason, "seed: expected 32 bytes, got 16 bytes");
+    }
+}
@@ -0,0 +1,333 @@
+//! Rescue-Prime hash over the BN254 scalar field
+//!
+//! Follows the Rescue-Prime reference implementation (`rescue_prime.py`).
+//!
+//! - The S-box is `x^alpha` followed by `x^(1/alpha)` with `alpha = 5`: the
+//!   smallest exponent coprime to `r - 1`, since 3 divides `r - 1` and `x^3`
+//!   is not a permutation of this field.
+//! - Round constants are `2 * m * num_rounds` little-endian 33-byte integers
+//!   reduced modulo `r`, read from SHAKE-256 seeded with
+//!   `"Rescue-XLIX(r,m,capacity,128)"`.
+//! - The MDS matrix comes from the `m x 2m` Vandermonde matrix
+//!   `V[i][j] = g^(i*j)` over the smallest primitive element `g = 5`.
+//!   After row reduction to `[I | A]`, the matrix is `A` transposed, i.e.
+//!   the redundancy part of a systematic Reed-Solomon generator matrix.
+//!
+//! Generated parameter sets are cached per `(m, capacity, num_rounds)`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+use crate::bn254::{parse_fr, Fr};
+
+/// Smallest and largest supported state widths
+pub const MIN_WIDTH: u32 = 2;
+pub const MAX_WIDTH: u32 = 16;
+
+/// Largest supported number of rounds
+pub const MAX_ROUNDS: u32 = 64;
+
+/// Security level written into the round constant seed, as in the reference
+const SECURITY_LEVEL: u32 = 128;
+
+/// `alpha^-1 mod (r - 1)`, the inverse S-box exponent
+const ALPHA_INV: [u64; 4] = [
+    0xcfe7f7a98ccccccd,
+    0x535cb9d394945a0d,
+    0x93736af8679aad17,
+    0x26b6a528b427b354,
+];
+
+/// Smallest primitive element of the BN254 scalar field
+const GENERATOR: u64 = 5;
+
+/// Bytes of SHAKE-256 output per round constant: one more than `r` needs
+const BYTES_PER_CONSTANT: usize = 33;
+
+/// Decimal `r`, as formatted into the round constant seed
+const MODULUS_DECIMAL: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// Round constants and MDS matrix for one `(m, capacity, num_rounds)` instance
+#[derive(Debug)]
+pub struct RescueParams {
+    pub m: usize,
+    pub capacity: usize,
+    pub rounds: usize,
+    /// `2 * m * rounds` constants, `m` after each half-round
+    pub round_constants: Vec<Fr>,
+    /// `m x m` matrix, row-major
+    pub mds: Vec<Vec<Fr>>,
+}
+
+type ParamsCache = HashMap<(u32, u32, u32), Arc<RescueParams>>;
+
+static PARAMS_CACHE: Lazy<Mutex<ParamsCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+impl RescueParams {
+    /// Generate the parameters for a width, capacity and round count
+    pub fn generate(m: u32, capacity: u32, rounds: u32) -> Self {
+        RescueParams {
+            m: m as usize,
+            capacity: capacity as usize,
+            rounds: rounds as usize,
+            round_constants: round_constants(m, capacity, rounds),
+            mds: mds_matrix(m as usize),
+        }
+    }
+
+    /// Apply the Rescue-XLIX permutation to `state` in place
+    pub fn permute(&self, state: &mut [Fr]) {
+        debug_assert_eq!(state.len(), self.m);
+        let mut scratch = vec![Fr::zero(); self.m];
+        for (half, constants) in self.round_constants.chunks(self.m).enumerate() {
+            if half % 2 == 0 {
+                state.iter_mut().for_each(|s| *s = sbox(*s));
+            } else {
+                state.iter_mut().for_each(|s| *s = s.pow(&ALPHA_INV));
+            }
+            for (out, row) in scratch.iter_mut().zip(&self.mds) {
+                *out = row
+                    .iter()
+                    .zip(state.iter())
+                    .fold(Fr::zero(), |acc, (m, s)| acc + *m * *s);
+            }
+            for ((s, x), c) in state.iter_mut().zip(&scratch).zip(constants) {
+                *s = *x + *c;
+            }
+        }
+    }
+}
+
+#[inline]
+fn sbox(x: Fr) -> Fr {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// Read the round constants from the seeded SHAKE-256 stream
+fn round_constants(m: u32, capacity: u32, rounds: u32) -> Vec<Fr> {
+    let seed = format!("Rescue-XLIX({MODULUS_DECIMAL},{m},{capacity},{SECURITY_LEVEL})");
+    let mut shake = Shake256::default();
+    shake.update(seed.as_bytes());
+    let mut reader = shake.finalize_xof();
+    let two_256 = Fr::from_u64(2).pow(&[256]);
+    (0..2 * m * rounds)
+        .map(|_| {
+            let mut bytes = [0u8; BYTES_PER_CONSTANT];
+            reader.read(&mut bytes);
+            let low = Fr::from_le_bytes_reduced(&bytes[..32]).unwrap();
+            low + Fr::from_u64(bytes[32] as u64) * two_256
+        })
+        .collect()
+}
+
+/// Transpose of the right half of the row-reduced `m x 2m` Vandermonde matrix
+fn mds_matrix(m: usize) -> Vec<Vec<Fr>> {
+    let g = Fr::from_u64(GENERATOR);
+    let mut v: Vec<Vec<Fr>> = (0..m)
+        .map(|i| (0..2 * m).map(|j| g.pow(&[(i * j) as u64])).collect())
+        .collect();
+    // The left half is a Vandermonde matrix over distinct powers of g, so
+    // every pivot exists without row swaps
+    for col in 0..m {
+        let inv = v[col][col]
+            .inverse()
+            .expect("Vandermonde pivots are non-zero");
+        v[col].iter_mut().for_each(|x| *x *= inv);
+        let pivot = v[col].clone();
+        for (i, row) in v.iter_mut().enumerate() {
+            if i == col || row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col];
+            for (x, p) in row.iter_mut().zip(&pivot) {
+                *x -= factor * *p;
+            }
+        }
+    }
+    (0..m)
+        .map(|i| (0..m).map(|j| v[j][m + i]).collect())
+        .collect()
+}
+
+/// Fetch (or generate and cache) the parameters for `(m, capacity, num_rounds)`
+pub fn params(m: u32, capacity: u32, num_rounds: u32) -> Result<Arc<RescueParams>> {
+    if !(MIN_WIDTH..=MAX_WIDTH).contains(&m) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("m must be between {MIN_WIDTH} and {MAX_WIDTH}, got {m}"),
+        ));
+    }
+    if capacity >= m {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "capacity must leave a rate of at least 1, got capacity {capacity} for m = {m}"
+            ),
+        ));
+    }
+    if !(1..=MAX_ROUNDS).contains(&num_rounds) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("num_rounds must be between 1 and {MAX_ROUNDS}, got {num_rounds}"),
+        ));
+    }
+    let mut cache = PARAMS_CACHE.lock().unwrap();
+    Ok(cache
+        .entry((m, capacity, num_rounds))
+        .or_insert_with(|| Arc::new(RescueParams::generate(m, capacity, num_rounds)))
+        .clone())
+}
+
+/// Sponge hash: absorb `inputs` padded with a one and then zeros to a
+/// multiple of the rate, and squeeze `rate` elements
+pub fn hash(inputs: &[Fr], m: u32, capacity: u32, num_rounds: u32) -> Result<Vec<Fr>> {
+    let params = params(m, capacity, num_rounds)?;
+    let rate = params.m - params.capacity;
+    let mut padded = inputs.to_vec();
+    padded.push(Fr::one());
+    padded.resize(padded.len().next_multiple_of(rate), Fr::zero());
+
+    let mut state = vec![Fr::zero(); params.m];
+    for block in padded.chunks(rate) {
+        for (s, x) in state.iter_mut().zip(block) {
+            *s += *x;
+        }
+        params.permute(&mut state);
+    }
+    state.truncate(rate);
+    Ok(state)
+}
+
+fn parse_elements(values: &[Vec<u8>], name: &str) -> Result<Vec<Fr>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, bytes)| parse_fr(bytes, &format!("{name}[{i}]")))
+        .collect()
+}
+
+/// Rescue-Prime hash of any number of BN254 scalar field elements
+///
+/// Inputs are 32-byte big-endian; returns the `m - capacity` output
+/// elements in the same encoding.
+#[napi]
+pub fn rescue_prime_hash(
+    inputs: Vec<Vec<u8>>,
+    m: u32,
+    capacity: u32,
+    num_rounds: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let digest = hash(&parse_elements(&inputs, "inputs")?, m, capacity, num_rounds)?;
+    Ok(digest.iter().map(|v| v.to_be_bytes()).collect())
+}
+
+/// Apply the Rescue-Prime permutation to a state of exactly `m` elements
+///
+/// For building custom sponge or compression modes; elements are 32-byte
+/// big-endian.
+#[napi]
+pub fn rescue_prime_permutation(
+    state: Vec<Vec<u8>>,
+    m: u32,
+    capacity: u32,
+    num_rounds: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let params = params(m, capacity, num_rounds)?;
+    if state.len() != params.m {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("state must hold m = {m} elements, got {}", state.len()),
+        ));
+    }
+    let mut state = parse_elements(&state, "state")?;
+    params.permute(&mut state);
+    Ok(state.iter().map(|v| v.to_be_bytes()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr(v: u64) -> Vec<u8> {
+        Fr::from_u64(v).to_be_bytes()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Expected values below come from a Python port of the reference
+    // `rescue_prime.py` with the same p, alpha, g and seed
+
+    #[test]
+    fn test_parameters_match_reference() {
+        let params = params(3, 1, 8).unwrap();
+        assert_eq!(params.round_constants.len(), 48);
+        assert_eq!(
+            params.round_constants[0].to_be_bytes(),
+            hex("241214b64e37a42dddc49216b6433fe75e4af3533a8c8961def18b459420ce96")
+        );
+        let small = |v: u64| Fr::from_u64(v);
+        assert_eq!(params.mds[0], [small(125), -small(155), small(31)]);
+        assert_eq!(params.mds[1], [small(3875), -small(4680), small(806)]);
+        assert_eq!(params.mds[2], [small(100750), -small(121055), small(20306)]);
+
+        let x = Fr::from_u64(0x1234_5678_9abc_def0);
+        assert_eq!(sbox(x).pow(&ALPHA_INV), x);
+    }
+
+    #[test]
+    fn test_permutation_known_answer() {
+        let out = rescue_prime_permutation(vec![fr(0), fr(1), fr(2)], 3, 1, 8).unwrap();
+        assert_eq!(
+            out,
+            [
+                hex("2db6ecb0d66fb514aa6e76f6a94147844325e3031904f4b07a35446caf4f0376"),
+                hex("0dc68190241cc0c87095bd0015a9dc229d7668dc24960f1d3542661567bb780e"),
+                hex("19b75a34ad2f4de1f4f79e1ea87326fc3d94111dcf534bb87be8729936329ad7"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_known_answers() {
+        assert_eq!(
+            rescue_prime_hash(vec![fr(1), fr(2), fr(3)], 3, 1, 8).unwrap(),
+            [
+                hex("155af835dd1d747be2698af3c8fd23270eb9a85dd13e3000e3fb32f9431c0774"),
+                hex("10da94f8a0ec34ea610e34be233af07fd7aad9195707baef6e4486157a53e0a2"),
+            ]
+        );
+        assert_eq!(
+            rescue_prime_hash(vec![fr(5)], 4, 2, 10).unwrap(),
+            [
+                hex("00896c0b4c6e4c2152985887403e206f4040dd58c9301791dafedf178e7c6ac6"),
+                hex("2830d1f269beb2775a91ca96c02c6a98e4a845fdab2479fda5a7de0f54cdda0d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_parameters() {
+        let err = rescue_prime_hash(vec![], 3, 3, 8).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("rate of at least 1"), "{}", err.reason);
+        assert!(rescue_prime_hash(vec![], 1, 0, 8).is_err());
+        assert!(rescue_prime_hash(vec![], 3, 1, 0).is_err());
+        let err = rescue_prime_permutation(vec![fr(0); 2], 3, 1, 8).unwrap_err();
+        assert!(err.reason.contains("m = 3"), "{}", err.reason);
+        let err = rescue_prime_hash(vec![vec![0xff; 32]], 3, 1, 8).unwrap_err();
+        assert!(err.reason.starts_with("inputs[0]"), "{}", err.reason);
+    }
+}
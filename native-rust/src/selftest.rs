@@ -0,0 +1,451 @@
+//! Built-in self-test
+//!
+//! `run_self_test()` runs every accelerated code path the machine can
+//! execute against known-answer vectors and the scalar reference, and
+//! reports the outcome of each check instead of throwing. The report is
+//! meant to be pasted into bug reports about wrong proofs on unusual
+//! hardware, so it also carries the capability snapshot.
+
+use std::time::Instant;
+
+use napi_derive::napi;
+
+use crate::bigint;
+use crate::bn254::{self, Fr};
+use crate::montgomery::{sub_limbs, MontConfig};
+use crate::ntt;
+use crate::random::{random_elements, FieldName};
+
+/// Outcome of one self-test check
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SelfTestResult {
+    /// Component name, e.g. "bn254.field.neon" or "ntt.avx2"
+    pub name: String,
+    /// Whether the check ran and matched its expected values
+    pub passed: bool,
+    /// Whether the path is unavailable on this machine (not a failure)
+    pub skipped: bool,
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: f64,
+    /// Mismatch description or skip reason, empty on success
+    pub detail: String,
+}
+
+/// Result of the whole self-test battery
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SelfTestReport {
+    /// Whether every check that ran passed
+    pub passed: bool,
+    /// Per-component results in execution order
+    pub results: Vec<SelfTestResult>,
+    /// Total wall-clock duration in milliseconds
+    pub total_duration_ms: f64,
+    /// Rust component version
+    pub rust_version: String,
+    /// Capabilities the run was dispatched on
+    pub capabilities: crate::RustHardwareCapabilities,
+}
+
+/// Outcome of a check: `Err` describes a mismatch, `Ok(Some(_))` a skip
+type CheckOutcome = Result<Option<String>, String>;
+
+/// `a * b` operands and product, computed with Python big integers
+const FIELD_A: &str = "2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a2b1a";
+const FIELD_B: &str = "0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed0fed";
+const FIELD_PRODUCT: &str = "1a03c5ff3105f8f56baf98b9506058cbfe6f107a78f5eb443a38fb520f8b53b9";
+
+/// Second coefficient of the NTT of `[1, 2, 3, 4]`
+const NTT_OUT_1: &str = "00000000000000016789af3a83522eb1969386a2f88c094a419fe246c11f9394";
+
+/// circomlibjs `poseidon([1, 2])`
+const POSEIDON_1_2: &str = "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a";
+
+fn fr_hex(hex: &str) -> Fr {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    Fr::from_be_bytes(&bytes).expect("constant must be a canonical field element")
+}
+
+/// Deterministic operands that hit the top limbs and the reduction
+fn operands(n: usize, seed: u64) -> Vec<Fr> {
+    let mut x = fr_hex(FIELD_A) + Fr::from_u64(seed);
+    (0..n)
+        .map(|i| {
+            x = x.square() + Fr::from_u64(i as u64 + 3);
+            x
+        })
+        .collect()
+}
+
+fn expect_eq<T: PartialEq + std::fmt::Debug>(
+    what: &str,
+    got: T,
+    expected: T,
+) -> Result<(), String> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(format!("{what}: got {got:?}, expected {expected:?}"))
+    }
+}
+
+/// Products of [`operands`] computed one at a time with scalar Montgomery
+fn scalar_products(a: &[Fr], b: &[Fr]) -> Vec<Fr> {
+    a.iter().zip(b).map(|(x, y)| *x * *y).collect()
+}
+
+fn check_field_scalar() -> CheckOutcome {
+    expect_eq(
+        "a * b",
+        fr_hex(FIELD_A) * fr_hex(FIELD_B),
+        fr_hex(FIELD_PRODUCT),
+    )?;
+    expect_eq("(-1)^2", (-Fr::one()).square(), Fr::one())?;
+    let two = Fr::from_u64(2);
+    expect_eq("2 * 2^-1", two * two.inverse().unwrap(), Fr::one())?;
+    Ok(None)
+}
+
+fn check_field_neon() -> CheckOutcome {
+    if !crate::neon::supported() {
+        return Ok(Some("NEON unavailable or disabled".to_string()));
+    }
+    let (a, b) = (operands(33, 1), operands(33, 2));
+    let mut out = vec![Fr::zero(); a.len()];
+    crate::neon::mul_batch(&a, &b, &mut out);
+    expect_eq("NEON products", out, scalar_products(&a, &b))?;
+    let [product, _] =
+        crate::neon::mont_mul_pair([fr_hex(FIELD_A), Fr::one()], [fr_hex(FIELD_B), Fr::one()]);
+    expect_eq("NEON a * b", product, fr_hex(FIELD_PRODUCT))?;
+    Ok(None)
+}
+
+fn check_field_avx2() -> CheckOutcome {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if !crate::avx2::enabled() {
+            return Ok(Some("AVX2 unavailable".to_string()));
+        }
+        let (a, b) = (operands(32, 3), operands(32, 4));
+        let mut out = Vec::with_capacity(a.len());
+        for (x, y) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+            // SAFETY: AVX2 support was checked above
+            let quad = unsafe {
+                crate::avx2::mont_mul_x4([x[0], x[1], x[2], x[3]], [y[0], y[1], y[2], y[3]])
+            };
+            out.extend(quad);
+        }
+        expect_eq("AVX2 products", out, scalar_products(&a, &b))?;
+        Ok(None)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        Ok(Some("not an x86_64 build".to_string()))
+    }
+}
+
+fn check_field_metal() -> CheckOutcome {
+    #[cfg(all(feature = "metal", target_os = "macos"))]
+    {
+        let Some(kernel) = crate::gpu::metal_available()
+            .then(crate::metal_kernels::mont_mul_kernel)
+            .flatten()
+        else {
+            return Ok(Some("no usable Metal device".to_string()));
+        };
+        let (a, b) = (operands(257, 5), operands(257, 6));
+        expect_eq(
+            "Metal products",
+            kernel.mul(&a, &b),
+            scalar_products(&a, &b),
+        )?;
+        Ok(None)
+    }
+    #[cfg(not(all(feature = "metal", target_os = "macos")))]
+    {
+        Ok(Some("built without the metal feature".to_string()))
+    }
+}
+
+/// Random operands per field in the Montgomery kernel check
+const MONT_MUL_SAMPLES: usize = 1000;
+
+/// Modulus and `-p^-1 mod 2^64` of a 256-bit field
+fn mont_params<C: MontConfig<4>>() -> ([u64; 4], u64) {
+    (C::MODULUS, C::INV)
+}
+
+/// Compare the dispatched 256-bit Montgomery kernel with the portable one
+/// on fresh random operands of every 256-bit field, plus 0, 1 and `p - 1`
+fn check_mont_mul() -> CheckOutcome {
+    let fields = [
+        (
+            "bn254-fr",
+            FieldName::Bn254Fr,
+            mont_params::<bn254::FrConfig>(),
+        ),
+        (
+            "bn254-fq",
+            FieldName::Bn254Fq,
+            mont_params::<bn254::FqConfig>(),
+        ),
+        (
+            "bls12-381-fr",
+            FieldName::Bls12_381Fr,
+            mont_params::<crate::bls12_381::FrConfig>(),
+        ),
+        (
+            "pallas-fp",
+            FieldName::PallasFp,
+            mont_params::<crate::pasta::FpConfig>(),
+        ),
+        (
+            "vesta-fp",
+            FieldName::VestaFp,
+            mont_params::<crate::pasta::FqConfig>(),
+        ),
+    ];
+    for (name, field, (p, inv)) in fields {
+        let random = random_elements(field, MONT_MUL_SAMPLES, None).map_err(|e| e.reason)?;
+        let mut values = vec![[0; 4], [1, 0, 0, 0], sub_limbs(&p, &[1, 0, 0, 0]).0];
+        values.extend(random.chunks_exact(32).map(|chunk| {
+            std::array::from_fn(|i| u64::from_le_bytes(chunk[8 * i..8 * i + 8].try_into().unwrap()))
+        }));
+        for (a, b) in values.iter().zip(values.iter().rev()) {
+            for b in [a, b] {
+                expect_eq(
+                    &format!("{name}: {a:x?} * {b:x?}"),
+                    bigint::mont_mul(a, b, &p, inv),
+                    bigint::mont_mul_portable(a, b, &p, inv),
+                )?;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Cross-check the Montgomery multiplication kernel against the portable one
+///
+/// Runs on fresh random operands, so repeated calls cover new inputs. The
+/// result is named after the kernel, e.g. "bigint.mont_mul.aarch64".
+#[napi]
+pub fn mont_mul_selftest() -> SelfTestResult {
+    run(
+        &format!("bigint.mont_mul.{}", bigint::mont_mul_kernel()),
+        check_mont_mul,
+    )
+}
+
+/// Compare the dispatched 384-bit Montgomery kernel with the schoolbook one
+/// on fresh random BLS12-381 base field operands, plus 0, 1 and `p - 1`
+fn check_mont_mul_384() -> CheckOutcome {
+    use crate::bls12_381::FpConfig;
+
+    let (p, inv) = (FpConfig::MODULUS, FpConfig::INV);
+    let random =
+        random_elements(FieldName::Bls12_381Fp, MONT_MUL_SAMPLES, None).map_err(|e| e.reason)?;
+    let mut values = vec![
+        [0; 6],
+        [1, 0, 0, 0, 0, 0],
+        sub_limbs(&p, &[1, 0, 0, 0, 0, 0]).0,
+    ];
+    values.extend(random.chunks_exact(48).map(|chunk| {
+        std::array::from_fn(|i| u64::from_le_bytes(chunk[8 * i..8 * i + 8].try_into().unwrap()))
+    }));
+    for (a, b) in values.iter().zip(values.iter().rev()) {
+        for b in [a, b] {
+            expect_eq(
+                &format!("bls12-381-fp: {a:x?} * {b:x?}"),
+                bigint::mont_mul_384(a, b, &p, inv),
+                bigint::mont_mul_generic(a, b, &p, inv),
+            )?;
+        }
+    }
+    Ok(None)
+}
+
+fn check_ntt(kernel: ntt::Kernel) -> CheckOutcome {
+    let input: Vec<Fr> = (1..=4).map(Fr::from_u64).collect();
+    let mut values = input.clone();
+    ntt::ntt_in_place_with(&mut values, false, kernel).map_err(|e| e.reason)?;
+    expect_eq("NTT([1, 2, 3, 4])[0]", values[0], Fr::from_u64(10))?;
+    expect_eq("NTT([1, 2, 3, 4])[1]", values[1], fr_hex(NTT_OUT_1))?;
+    expect_eq("NTT([1, 2, 3, 4])[2]", values[2], -Fr::from_u64(2))?;
+
+    // A size that reaches the vector loops, against the scalar reference
+    let input = operands(1 << 9, 7);
+    let mut reference = input.clone();
+    let mut values = input.clone();
+    ntt::ntt_in_place_with(&mut reference, false, ntt::Kernel::Scalar).map_err(|e| e.reason)?;
+    ntt::ntt_in_place_with(&mut values, false, kernel).map_err(|e| e.reason)?;
+    expect_eq("forward transform of 512 elements", &values, &reference)?;
+    ntt::ntt_in_place_with(&mut values, true, kernel).map_err(|e| e.reason)?;
+    expect_eq("inverse round trip", values, input)?;
+    Ok(None)
+}
+
+fn check_poseidon() -> CheckOutcome {
+    let digest =
+        crate::poseidon::hash(&[Fr::from_u64(1), Fr::from_u64(2)]).map_err(|e| e.reason)?;
+    expect_eq("poseidon([1, 2])", digest, fr_hex(POSEIDON_1_2))?;
+    Ok(None)
+}
+
+fn check_msm() -> CheckOutcome {
+    let g = bn254::g1_generator().to_jacobian();
+    let points: Vec<_> = (1..=24u64).map(|k| g.mul_limbs(&[k]).to_affine()).collect();
+    let scalars: Vec<[u64; 4]> = operands(points.len(), 8)
+        .iter()
+        .map(|s| s.to_canonical())
+        .collect();
+    let expected = points
+        .iter()
+        .zip(&scalars)
+        .fold(bn254::G1Projective::identity(), |acc, (p, s)| {
+            acc.add(&p.to_jacobian().mul_limbs(s))
+        });
+    expect_eq(
+        "serial Pippenger",
+        crate::msm::pippenger(&points, &scalars, false),
+        expected,
+    )?;
+    expect_eq(
+        "parallel Pippenger",
+        crate::msm::pippenger(&points, &scalars, true),
+        expected,
+    )?;
+    Ok(None)
+}
+
+fn check_bls12_381_g1() -> CheckOutcome {
+    let g = crate::bls12_381::g1_generator();
+    if !g.is_on_curve() {
+        return Err("generator is not on the curve".to_string());
+    }
+    let g = g.to_jacobian();
+    expect_eq("G + G", g.add(&g), g.double())?;
+    expect_eq("3G", g.mul_limbs(&[3]), g.double().add(&g))?;
+    Ok(None)
+}
+
+fn run(name: &str, check: impl FnOnce() -> CheckOutcome) -> SelfTestResult {
+    let start = Instant::now();
+    let outcome = check();
+    let duration_ms = start.elapsed().as_secs_f64() * 1e3;
+    let (passed, skipped, detail) = match outcome {
+        Ok(None) => (true, false, String::new()),
+        Ok(Some(reason)) => (true, true, reason),
+        Err(mismatch) => (false, false, mismatch),
+    };
+    SelfTestResult {
+        name: name.to_string(),
+        passed,
+        skipped,
+        duration_ms,
+        detail,
+    }
+}
+
+/// Run every available accelerated path against known answers
+///
+/// Never throws: each failure is recorded in its result and the remaining
+/// checks still run. Paths the machine cannot execute are reported as
+/// skipped.
+#[napi]
+pub fn run_self_test() -> SelfTestReport {
+    let start = Instant::now();
+    let mut results = vec![
+        mont_mul_selftest(),
+        run(
+            &format!("bigint.mont_mul_384.{}", bigint::mont_mul_384_kernel()),
+            check_mont_mul_384,
+        ),
+        run("bn254.field.scalar", check_field_scalar),
+        run("bn254.field.neon", check_field_neon),
+        run("bn254.field.avx2", check_field_avx2),
+        run("bn254.field.metal", check_field_metal),
+    ];
+    for kernel in ntt::available_kernels() {
+        results.push(run(&format!("ntt.{}", kernel.name()), || check_ntt(kernel)));
+    }
+    results.push(run("poseidon.bn254", check_poseidon));
+    results.push(run("msm.bn254.g1", check_msm));
+    results.push(run("bls12_381.g1", check_bls12_381_g1));
+
+    SelfTestReport {
+        passed: results.iter().all(|r| r.passed),
+        results,
+        total_duration_ms: start.elapsed().as_secs_f64() * 1e3,
+        rust_version: crate::rust_version(),
+        capabilities: crate::detect_rust_capabilities(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        let report = run_self_test();
+        let failures: Vec<_> = report.results.iter().filter(|r| !r.passed).collect();
+        assert!(failures.is_empty(), "{failures:?}");
+        assert!(report.passed);
+        assert!(report.total_duration_ms >= 0.0);
+        assert_eq!(report.capabilities.cpu_cores, crate::get_cpu_count());
+    }
+
+    #[test]
+    fn test_scalar_paths_always_run() {
+        let report = run_self_test();
+        for name in [
+            "bn254.field.scalar",
+            "ntt.scalar",
+            "poseidon.bn254",
+            "msm.bn254.g1",
+            "bls12_381.g1",
+        ] {
+            let result = report.results.iter().find(|r| r.name == name).unwrap();
+            assert!(result.passed && !result.skipped, "{result:?}");
+        }
+        let neon = report
+            .results
+            .iter()
+            .find(|r| r.name == "bn254.field.neon")
+            .unwrap();
+        assert_eq!(neon.skipped, !crate::neon::supported());
+    }
+
+    #[test]
+    fn test_mont_mul_selftest() {
+        let result = mont_mul_selftest();
+        assert!(result.passed && !result.skipped, "{result:?}");
+        assert_eq!(
+            result.name,
+            format!("bigint.mont_mul.{}", bigint::mont_mul_kernel())
+        );
+    }
+
+    #[test]
+    fn test_mont_mul_384_check() {
+        assert_eq!(check_mont_mul_384(), Ok(None));
+        let name = format!("bigint.mont_mul_384.{}", bigint::mont_mul_384_kernel());
+        let report = run_self_test();
+        let result = report.results.iter().find(|r| r.name == name).unwrap();
+        assert!(result.passed && !result.skipped, "{result:?}");
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let result = run("broken", || {
+            expect_eq("1 + 1", Fr::one() + Fr::one(), Fr::from_u64(3))?;
+            Ok(None)
+        });
+        assert!(!result.passed && !result.skipped);
+        assert!(result.detail.starts_with("1 + 1: got"));
+    }
+}
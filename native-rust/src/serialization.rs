@@ -0,0 +1,468 @@
+//! snarkjs JSON import and export for Groth16 proofs and verification keys
+//!
+//! snarkjs writes points in projective form as decimal strings: G1 as
+//! `[x, y, z]` and G2 as `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]`, with
+//! `z = 1` for affine points and `z = 0` for infinity. The `curve` key picks
+//! the base field: `bn128` (the default when absent) or `bls12381`.
+//!
+//! Points are converted to the crate's uncompressed big-endian encodings:
+//! each coordinate is 32 bytes on BN254 and 48 bytes on BLS12-381, G2
+//! coordinates are written `c1 || c0`, and infinity is all zeros. Only affine
+//! and infinity points are accepted; other `z` values are rejected rather
+//! than normalized.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde_json::{json, Value};
+
+use crate::groth16::{Proof, VerificationKey};
+use crate::montgomery::MontConfig;
+
+/// Curves snarkjs can emit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    Bn254,
+    Bls12_381,
+}
+
+impl Curve {
+    fn from_json(value: &Value) -> Result<Self> {
+        match value.get("curve") {
+            None => Ok(Curve::Bn254),
+            Some(Value::String(name)) => match name.to_ascii_lowercase().as_str() {
+                "bn128" | "bn254" | "altbn128" => Ok(Curve::Bn254),
+                "bls12381" | "bls12_381" => Ok(Curve::Bls12_381),
+                _ => Err(invalid(format!("curve: unsupported curve \"{name}\""))),
+            },
+            Some(_) => Err(invalid("curve: expected a string".to_string())),
+        }
+    }
+
+    /// Infer the curve from the width of an encoded G1 point
+    fn from_g1_len(len: usize) -> Result<Self> {
+        match len {
+            crate::bn254::G1_BYTES => Ok(Curve::Bn254),
+            crate::bls12_381::G1_BYTES => Ok(Curve::Bls12_381),
+            _ => Err(invalid(format!(
+                "proof.a: expected a {}- or {}-byte G1 point, got {len} bytes",
+                crate::bn254::G1_BYTES,
+                crate::bls12_381::G1_BYTES
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Curve::Bn254 => "bn128",
+            Curve::Bls12_381 => "bls12381",
+        }
+    }
+
+    /// Base field modulus as little-endian limbs
+    fn modulus(self) -> &'static [u64] {
+        match self {
+            Curve::Bn254 => &crate::bn254::FqConfig::MODULUS,
+            Curve::Bls12_381 => &crate::bls12_381::FpConfig::MODULUS,
+        }
+    }
+
+    fn coord_bytes(self) -> usize {
+        8 * self.modulus().len()
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(Status::InvalidArg, msg)
+}
+
+/// Parse a decimal string into `limbs.len()` little-endian limbs
+fn parse_decimal(s: &str, limbs: usize) -> Option<Vec<u64>> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut out = vec![0u64; limbs];
+    for digit in s.bytes().map(|b| (b - b'0') as u128) {
+        let mut carry = digit;
+        for limb in out.iter_mut() {
+            let v = (*limb as u128) * 10 + carry;
+            *limb = v as u64;
+            carry = v >> 64;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(out)
+}
+
+/// Format little-endian limbs as a decimal string
+fn format_decimal(limbs: &[u64]) -> String {
+    let mut value = limbs.to_vec();
+    let mut digits = Vec::new();
+    while value.iter().any(|&l| l != 0) {
+        let mut rem = 0u128;
+        for limb in value.iter_mut().rev() {
+            let cur = (rem << 64) | *limb as u128;
+            *limb = (cur / 10) as u64;
+            rem = cur % 10;
+        }
+        digits.push(b'0' + rem as u8);
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+/// Whether little-endian `a < b`
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x < y;
+        }
+    }
+    false
+}
+
+/// Decode one coordinate string into big-endian bytes below the modulus
+fn coordinate(value: &Value, curve: Curve, name: &str) -> Result<Vec<u8>> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| invalid(format!("{name}: expected a decimal string")))?;
+    let limbs = parse_decimal(s, curve.modulus().len())
+        .filter(|l| less_than(l, curve.modulus()))
+        .ok_or_else(|| {
+            invalid(format!(
+                "{name}: \"{s}\" is not a decimal integer below the {} base field modulus",
+                curve.name()
+            ))
+        })?;
+    Ok(limbs.iter().rev().flat_map(|l| l.to_be_bytes()).collect())
+}
+
+/// Encode big-endian coordinate bytes as a decimal string
+fn coordinate_to_decimal(bytes: &[u8]) -> String {
+    let limbs: Vec<u64> = bytes
+        .rchunks(8)
+        .map(|c| u64::from_be_bytes(c.try_into().unwrap()))
+        .collect();
+    format_decimal(&limbs)
+}
+
+fn array<'a>(value: &'a Value, len: usize, name: &str) -> Result<&'a [Value]> {
+    match value.as_array() {
+        Some(items) if items.len() == len => Ok(items),
+        Some(items) => Err(invalid(format!(
+            "{name}: expected {len} elements, got {}",
+            items.len()
+        ))),
+        None => Err(invalid(format!("{name}: expected an array"))),
+    }
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value> {
+    value
+        .get(key)
+        .ok_or_else(|| invalid(format!("{key}: missing")))
+}
+
+fn is_decimal(value: &Value, expected: &str) -> bool {
+    value
+        .as_str()
+        .and_then(|s| parse_decimal(s, 1))
+        .is_some_and(|l| l[0].to_string() == expected)
+}
+
+/// `[x, y, z]` to an uncompressed G1 encoding
+fn g1_from_json(value: &Value, curve: Curve, name: &str) -> Result<Vec<u8>> {
+    let items = array(value, 3, name)?;
+    if is_decimal(&items[2], "0") {
+        return Ok(vec![0u8; 2 * curve.coord_bytes()]);
+    }
+    if !is_decimal(&items[2], "1") {
+        return Err(invalid(format!(
+            "{name}: point is not affine (z must be 1)"
+        )));
+    }
+    let mut out = coordinate(&items[0], curve, &format!("{name}[0]"))?;
+    out.extend(coordinate(&items[1], curve, &format!("{name}[1]"))?);
+    Ok(out)
+}
+
+/// `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]` to an uncompressed G2 encoding
+fn g2_from_json(value: &Value, curve: Curve, name: &str) -> Result<Vec<u8>> {
+    let items = array(value, 3, name)?;
+    let z = array(&items[2], 2, &format!("{name}[2]"))?;
+    if is_decimal(&z[1], "0") && is_decimal(&z[0], "0") {
+        return Ok(vec![0u8; 4 * curve.coord_bytes()]);
+    }
+    if !(is_decimal(&z[0], "1") && is_decimal(&z[1], "0")) {
+        return Err(invalid(format!(
+            "{name}: point is not affine (z must be 1)"
+        )));
+    }
+    let mut out = Vec::with_capacity(4 * curve.coord_bytes());
+    for (i, coord) in items[..2].iter().enumerate() {
+        let c = array(coord, 2, &format!("{name}[{i}]"))?;
+        out.extend(coordinate(&c[1], curve, &format!("{name}[{i}][1]"))?);
+        out.extend(coordinate(&c[0], curve, &format!("{name}[{i}][0]"))?);
+    }
+    Ok(out)
+}
+
+fn g1_to_json(bytes: &[u8], curve: Curve, name: &str) -> Result<Value> {
+    let width = curve.coord_bytes();
+    if bytes.len() != 2 * width {
+        return Err(invalid(format!(
+            "{name}: expected a {}-byte G1 point, got {} bytes",
+            2 * width,
+            bytes.len()
+        )));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(json!(["0", "1", "0"]));
+    }
+    let (x, y) = bytes.split_at(width);
+    Ok(json!([
+        coordinate_to_decimal(x),
+        coordinate_to_decimal(y),
+        "1"
+    ]))
+}
+
+fn g2_to_json(bytes: &[u8], curve: Curve, name: &str) -> Result<Value> {
+    let width = curve.coord_bytes();
+    if bytes.len() != 4 * width {
+        return Err(invalid(format!(
+            "{name}: expected a {}-byte G2 point, got {} bytes",
+            4 * width,
+            bytes.len()
+        )));
+    }
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(json!([["0", "0"], ["1", "0"], ["0", "0"]]));
+    }
+    let c: Vec<String> = bytes.chunks(width).map(coordinate_to_decimal).collect();
+    // Encoded as x.c1, x.c0, y.c1, y.c0
+    Ok(json!([[c[1], c[0]], [c[3], c[2]], ["1", "0"]]))
+}
+
+fn parse_json(json: &str) -> Result<Value> {
+    serde_json::from_str(json).map_err(|e| invalid(format!("invalid JSON: {e}")))
+}
+
+/// Parse a snarkjs `proof.json`
+#[napi]
+pub fn proof_from_snarkjs_json(json: String) -> Result<Proof> {
+    let value = parse_json(&json)?;
+    let curve = Curve::from_json(&value)?;
+    Ok(Proof {
+        a: g1_from_json(field(&value, "pi_a")?, curve, "pi_a")?,
+        b: g2_from_json(field(&value, "pi_b")?, curve, "pi_b")?,
+        c: g1_from_json(field(&value, "pi_c")?, curve, "pi_c")?,
+        simulated: None,
+    })
+}
+
+/// Serialize a proof in the snarkjs `proof.json` layout
+///
+/// The curve is inferred from the point widths.
+#[napi]
+pub fn proof_to_snarkjs_json(proof: Proof) -> Result<String> {
+    let curve = Curve::from_g1_len(proof.a.len())?;
+    let value = json!({
+        "pi_a": g1_to_json(&proof.a, curve, "proof.a")?,
+        "pi_b": g2_to_json(&proof.b, curve, "proof.b")?,
+        "pi_c": g1_to_json(&proof.c, curve, "proof.c")?,
+        "protocol": "groth16",
+        "curve": curve.name(),
+    });
+    Ok(value.to_string())
+}
+
+/// Parse a snarkjs `verification_key.json`
+#[napi]
+pub fn vk_from_snarkjs_json(json: String) -> Result<VerificationKey> {
+    let value = parse_json(&json)?;
+    let curve = Curve::from_json(&value)?;
+    let ic = field(&value, "IC")?
+        .as_array()
+        .ok_or_else(|| invalid("IC: expected an array".to_string()))?;
+    if ic.is_empty() {
+        return Err(invalid("IC: expected at least one point".to_string()));
+    }
+    if let Some(n) = value.get("nPublic").and_then(Value::as_u64) {
+        if n as usize + 1 != ic.len() {
+            return Err(invalid(format!(
+                "IC: nPublic is {n} but IC has {} points",
+                ic.len()
+            )));
+        }
+    }
+    Ok(VerificationKey {
+        alpha_g1: g1_from_json(field(&value, "vk_alpha_1")?, curve, "vk_alpha_1")?,
+        beta_g2: g2_from_json(field(&value, "vk_beta_2")?, curve, "vk_beta_2")?,
+        gamma_g2: g2_from_json(field(&value, "vk_gamma_2")?, curve, "vk_gamma_2")?,
+        delta_g2: g2_from_json(field(&value, "vk_delta_2")?, curve, "vk_delta_2")?,
+        ic: ic
+            .iter()
+            .enumerate()
+            .map(|(i, p)| g1_from_json(p, curve, &format!("IC[{i}]")))
+            .collect::<Result<_>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::{encode_g1, encode_g2, g1_generator, g2_generator};
+
+    // BN254 G1 generator, 2·G1 and 3·G1, and the EIP-197 G2 generator
+    const G1: [&str; 2] = ["1", "2"];
+    const G1_X2: [&str; 2] = [
+        "1368015179489954701390400359078579693043519447331113978918064868415326638035",
+        "9918110051302171585080402603319702774565515993150576347155970296011118125764",
+    ];
+    const G1_X3: [&str; 2] = [
+        "3353031288059533942658390886683067124040920775575537747144343083137631628272",
+        "19321533766552368860946552437480515441416830039777911637913418824951667761761",
+    ];
+    const G2: [[&str; 2]; 2] = [
+        [
+            "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+            "11559732032986387107991004021392285783925812861821192530917403151452391805634",
+        ],
+        [
+            "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+            "4082367875863433681332203403145435568316851327593401208105741076214120093531",
+        ],
+    ];
+    const FQ_MODULUS: &str =
+        "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+
+    /// A proof in the exact layout `snarkjs groth16 prove` writes
+    fn bn254_proof_json() -> Value {
+        json!({
+            "pi_a": [G1_X2[0], G1_X2[1], "1"],
+            "pi_b": [G2[0], G2[1], ["1", "0"]],
+            "pi_c": [G1_X3[0], G1_X3[1], "1"],
+            "protocol": "groth16",
+            "curve": "bn128"
+        })
+    }
+
+    #[test]
+    fn test_decimal_roundtrip() {
+        assert_eq!(parse_decimal("0", 4), Some(vec![0; 4]));
+        assert_eq!(parse_decimal("18446744073709551616", 2), Some(vec![0, 1]));
+        assert_eq!(parse_decimal("18446744073709551616", 1), None);
+        assert_eq!(parse_decimal("12a", 4), None);
+        assert_eq!(parse_decimal("-1", 4), None);
+        assert_eq!(parse_decimal("", 4), None);
+        for s in ["0", "1", "12345678901234567890123", FQ_MODULUS] {
+            assert_eq!(format_decimal(&parse_decimal(s, 4).unwrap()), s);
+        }
+    }
+
+    #[test]
+    fn test_bn254_proof_roundtrip() {
+        let json = bn254_proof_json();
+        let proof = proof_from_snarkjs_json(json.to_string()).unwrap();
+        assert_eq!(proof.a.len(), 64);
+        assert_eq!(proof.b.len(), 128);
+        let mut expected_a = [0u8; 64];
+        expected_a[31] = 1;
+        expected_a[63] = 2;
+        let c = proof_from_snarkjs_json(
+            json!({"pi_a": [G1[0], G1[1], "1"], "pi_b": json["pi_b"], "pi_c": json["pi_c"]})
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(c.a, expected_a);
+
+        let back: Value =
+            serde_json::from_str(&proof_to_snarkjs_json(proof.clone()).unwrap()).unwrap();
+        assert_eq!(back, json);
+        assert_eq!(
+            proof_from_snarkjs_json(back.to_string()).unwrap().b,
+            proof.b
+        );
+    }
+
+    #[test]
+    fn test_bls12_381_proof_roundtrip() {
+        let proof = Proof {
+            a: encode_g1(&g1_generator()),
+            b: encode_g2(&g2_generator()),
+            c: vec![0u8; 96],
+            simulated: None,
+        };
+        let json = proof_to_snarkjs_json(proof.clone()).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["curve"], "bls12381");
+        assert_eq!(value["pi_c"], json!(["0", "1", "0"]));
+        let back = proof_from_snarkjs_json(json).unwrap();
+        assert_eq!(back.a, proof.a);
+        assert_eq!(back.b, proof.b);
+        assert_eq!(back.c, proof.c);
+    }
+
+    #[test]
+    fn test_rejects_malformed_proofs() {
+        let reject = |edit: &dyn Fn(&mut Value)| {
+            let mut json = bn254_proof_json();
+            edit(&mut json);
+            proof_from_snarkjs_json(json.to_string())
+                .unwrap_err()
+                .reason
+        };
+        assert!(reject(&|j| j["pi_a"] = json!(["1", "2"])).contains("expected 3 elements"));
+        assert!(reject(&|j| j["pi_a"][0] = json!(FQ_MODULUS)).contains("below the bn128"));
+        assert!(reject(&|j| j["pi_a"][2] = json!("2")).contains("not affine"));
+        assert!(reject(&|j| j["pi_b"][2] = json!(["1", "1"])).contains("not affine"));
+        assert!(reject(&|j| j["pi_b"][0] = json!(["1"])).contains("pi_b[0]"));
+        assert!(reject(&|j| j["pi_c"][1] = json!(7)).contains("decimal string"));
+        assert!(reject(&|j| j["curve"] = json!("mnt4")).contains("unsupported curve"));
+        assert!(reject(&|j| {
+            j.as_object_mut().unwrap().remove("pi_c");
+        })
+        .contains("pi_c: missing"));
+        assert!(proof_from_snarkjs_json("{".to_string()).is_err());
+
+        let short = Proof {
+            a: vec![0; 10],
+            b: vec![],
+            c: vec![],
+            simulated: None,
+        };
+        assert!(proof_to_snarkjs_json(short).is_err());
+    }
+
+    #[test]
+    fn test_vk_from_json() {
+        let g1 = json!([G1[0], G1[1], "1"]);
+        let g2 = json!([G2[0], G2[1], ["1", "0"]]);
+        let vk_json = json!({
+            "protocol": "groth16",
+            "curve": "bn128",
+            "nPublic": 2,
+            "vk_alpha_1": g1,
+            "vk_beta_2": g2,
+            "vk_gamma_2": g2,
+            "vk_delta_2": g2,
+            "IC": [g1, [G1_X2[0], G1_X2[1], "1"], [G1_X3[0], G1_X3[1], "1"]]
+        });
+        let vk = vk_from_snarkjs_json(vk_json.to_string()).unwrap();
+        assert_eq!(vk.ic.len(), 3);
+        assert_eq!(vk.beta_g2, vk.delta_g2);
+        assert_eq!(vk.beta_g2.len(), 128);
+
+        let mut mismatched = vk_json.clone();
+        mismatched["nPublic"] = json!(1);
+        assert!(vk_from_snarkjs_json(mismatched.to_string()).is_err());
+        let mut empty = vk_json;
+        empty["IC"] = json!([]);
+        assert!(vk_from_snarkjs_json(empty.to_string()).is_err());
+    }
+}
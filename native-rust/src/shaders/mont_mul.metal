@@ -0,0 +1,69 @@
+// Element-wise Montgomery multiplication over a 256-bit prime field
+//
+// Inputs and outputs are eight little-endian 32-bit digits per element, in
+// Montgomery form with R = 2^256. The modulus and -p^-1 mod 2^32 come from
+// the host so the same kernel serves any 4-limb field.
+
+#include <metal_stdlib>
+using namespace metal;
+
+struct MontParams {
+    uint modulus[8];
+    uint inv;
+    uint count;
+};
+
+kernel void mont_mul(device const uint *a [[buffer(0)]],
+                     device const uint *b [[buffer(1)]],
+                     device uint *out [[buffer(2)]],
+                     constant MontParams &params [[buffer(3)]],
+                     uint gid [[thread_position_in_grid]]) {
+    if (gid >= params.count) {
+        return;
+    }
+    uint x[8];
+    uint y[8];
+    for (uint j = 0; j < 8; j++) {
+        x[j] = a[gid * 8 + j];
+        y[j] = b[gid * 8 + j];
+    }
+
+    // CIOS: t[8], t[9] collect the overflow of each outer iteration
+    uint t[10] = {0, 0, 0, 0, 0, 0, 0, 0, 0, 0};
+    for (uint i = 0; i < 8; i++) {
+        ulong carry = 0;
+        for (uint j = 0; j < 8; j++) {
+            ulong s = ulong(t[j]) + ulong(x[j]) * ulong(y[i]) + carry;
+            t[j] = uint(s);
+            carry = s >> 32;
+        }
+        ulong s = ulong(t[8]) + carry;
+        t[8] = uint(s);
+        t[9] = uint(s >> 32);
+
+        uint m = t[0] * params.inv;
+        s = ulong(t[0]) + ulong(m) * ulong(params.modulus[0]);
+        carry = s >> 32;
+        for (uint j = 1; j < 8; j++) {
+            s = ulong(t[j]) + ulong(m) * ulong(params.modulus[j]) + carry;
+            t[j - 1] = uint(s);
+            carry = s >> 32;
+        }
+        s = ulong(t[8]) + carry;
+        t[7] = uint(s);
+        t[8] = t[9] + uint(s >> 32);
+    }
+
+    // Final conditional subtraction of p
+    uint r[8];
+    ulong borrow = 0;
+    for (uint j = 0; j < 8; j++) {
+        ulong d = ulong(t[j]) - ulong(params.modulus[j]) - borrow;
+        r[j] = uint(d);
+        borrow = (d >> 32) & 1;
+    }
+    bool reduce = t[8] != 0 || borrow == 0;
+    for (uint j = 0; j < 8; j++) {
+        out[gid * 8 + j] = reduce ? r[j] : t[j];
+    }
+}
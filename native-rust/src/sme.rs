@@ -0,0 +1,136 @@
+//! SME (Scalable Matrix Extension) detection on Apple Silicon
+//!
+//! macOS publishes SME support as `hw.optional.arm.FEAT_SME*` and
+//! `hw.optional.arm.SME_*` sysctls, plus the maximum streaming vector length
+//! in `hw.optional.arm.sme_max_svl_b`. Decoding is kept separate from the
+//! sysctl reads so it can be exercised with recorded outputs on any host.
+
+pub const FEAT_SME: &str = "hw.optional.arm.FEAT_SME";
+pub const FEAT_SME2: &str = "hw.optional.arm.FEAT_SME2";
+pub const SME_MAX_SVL_B: &str = "hw.optional.arm.sme_max_svl_b";
+
+/// Per-instruction-group `SME_*` sysctls; any of them implies base SME
+pub const SME_INSTRUCTION_GROUPS: [&str; 6] = [
+    "hw.optional.arm.SME_F32F32",
+    "hw.optional.arm.SME_BI32I32",
+    "hw.optional.arm.SME_B16F32",
+    "hw.optional.arm.SME_F16F32",
+    "hw.optional.arm.SME_I8I32",
+    "hw.optional.arm.SME_I16I32",
+];
+
+/// Decoded SME support
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SmeFeatures {
+    /// 0 = none, 1 = SME, 2 = SME2
+    pub version: u32,
+    /// Maximum streaming vector length in bits, 0 without SME or if unknown
+    pub streaming_vector_length_bits: u32,
+}
+
+impl SmeFeatures {
+    /// Decode from an arbitrary integer-sysctl lookup
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<u64>) -> Self {
+        let feature = |name: &str| lookup(name).is_some_and(|v| v != 0);
+        let version = if feature(FEAT_SME2) {
+            2
+        } else if feature(FEAT_SME) || SME_INSTRUCTION_GROUPS.iter().any(|name| feature(name)) {
+            1
+        } else {
+            0
+        };
+        let streaming_vector_length_bits = if version == 0 {
+            0
+        } else {
+            lookup(SME_MAX_SVL_B).map_or(0, |bytes| (bytes as u32).saturating_mul(8))
+        };
+        SmeFeatures {
+            version,
+            streaming_vector_length_bits,
+        }
+    }
+}
+
+/// Read SME support from the running kernel's sysctls
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+pub fn read() -> SmeFeatures {
+    SmeFeatures::from_lookup(crate::sysctl::read_u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `sysctl hw.optional.arm` on an M4 Pro, macOS 15.1 (SME lines only)
+    const M4_SYSCTL: &str = "\
+hw.optional.arm.FEAT_SME: 1
+hw.optional.arm.FEAT_SME2: 1
+hw.optional.arm.SME_F32F32: 1
+hw.optional.arm.SME_BI32I32: 1
+hw.optional.arm.SME_B16F32: 1
+hw.optional.arm.SME_F16F32: 1
+hw.optional.arm.SME_I8I32: 1
+hw.optional.arm.SME_I16I32: 1
+hw.optional.arm.FEAT_SME_F64F64: 1
+hw.optional.arm.FEAT_SME_I16I64: 1
+hw.optional.arm.sme_max_svl_b: 64
+";
+
+    /// `sysctl hw.optional.arm` on an M1, macOS 15.1 (SME lines only)
+    const M1_SYSCTL: &str = "\
+hw.optional.arm.FEAT_SME: 0
+hw.optional.arm.FEAT_SME2: 0
+hw.optional.arm.SME_F32F32: 0
+hw.optional.arm.SME_BI32I32: 0
+hw.optional.arm.SME_B16F32: 0
+hw.optional.arm.SME_F16F32: 0
+hw.optional.arm.SME_I8I32: 0
+hw.optional.arm.SME_I16I32: 0
+hw.optional.arm.FEAT_SME_F64F64: 0
+hw.optional.arm.FEAT_SME_I16I64: 0
+";
+
+    fn lookup(output: &str) -> impl Fn(&str) -> Option<u64> + '_ {
+        move |name| {
+            output.lines().find_map(|line| {
+                let (key, value) = line.split_once(": ")?;
+                (key == name).then(|| value.trim().parse().ok())?
+            })
+        }
+    }
+
+    #[test]
+    fn test_decode_m4() {
+        assert_eq!(
+            SmeFeatures::from_lookup(lookup(M4_SYSCTL)),
+            SmeFeatures {
+                version: 2,
+                streaming_vector_length_bits: 512,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_m1() {
+        assert_eq!(
+            SmeFeatures::from_lookup(lookup(M1_SYSCTL)),
+            SmeFeatures::default()
+        );
+        // Older kernels publish none of the keys at all
+        assert_eq!(SmeFeatures::from_lookup(|_| None), SmeFeatures::default());
+    }
+
+    #[test]
+    fn test_decode_partial() {
+        // SME without SME2, streaming length unpublished
+        let sme1 = SmeFeatures::from_lookup(lookup("hw.optional.arm.FEAT_SME: 1\n"));
+        assert_eq!(sme1.version, 1);
+        assert_eq!(sme1.streaming_vector_length_bits, 0);
+        // Instruction-group sysctls alone imply base SME
+        let groups = SmeFeatures::from_lookup(lookup("hw.optional.arm.SME_I8I32: 1\n"));
+        assert_eq!(groups.version, 1);
+        // A stray streaming length is ignored without SME
+        let stray = SmeFeatures::from_lookup(lookup("hw.optional.arm.sme_max_svl_b: 64\n"));
+        assert_eq!(stray, SmeFeatures::default());
+    }
+}
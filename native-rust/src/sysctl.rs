@@ -0,0 +1,63 @@
+//! Thin wrappers over macOS `sysctlbyname`
+//!
+//! Every reader returns `None` when the key is unknown on the running kernel,
+//! so callers can fall back to defaults without special-casing OS versions.
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+
+extern "C" {
+    fn sysctlbyname(
+        name: *const c_char,
+        oldp: *mut c_void,
+        oldlenp: *mut usize,
+        newp: *mut c_void,
+        newlen: usize,
+    ) -> c_int;
+}
+
+/// Read an integer sysctl (32- or 64-bit)
+pub fn read_u64(name: &str) -> Option<u64> {
+    let name = CString::new(name).ok()?;
+    let mut buf = [0u8; 8];
+    let mut size = buf.len();
+    let result = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr() as *mut c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    match size {
+        4 => Some(u32::from_ne_bytes(buf[..4].try_into().unwrap()) as u64),
+        8 => Some(u64::from_ne_bytes(buf)),
+        _ => None,
+    }
+}
+
+/// Read a string sysctl
+pub fn read_string(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut buf = [0u8; 256];
+    let mut size = buf.len();
+    let result = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr() as *mut c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    let bytes = &buf[..size];
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8(bytes[..end].to_vec()).ok()
+}
@@ -0,0 +1,607 @@
+//! Asynchronous proving and verification
+//!
+//! `prove_async` / `verify_async` run on the libuv worker pool through
+//! `napi::Task`, so long computations resolve a Promise instead of blocking
+//! the event loop. The kernels they call spread their own work across the
+//! crate's worker threads (see [`crate::parallel`]).
+//!
+//! Proof systems register themselves under a circuit id; from JavaScript,
+//! `register_groth16_circuit` (see [`crate::groth16`]) registers a circom
+//! circuit with its snarkjs proving key. Proofs returned by `prove_async` are
+//! framed with that id (`u16` big-endian length, id bytes, proof body) so
+//! `verify_async` can route them back to the same system.
+//!
+//! Cancellation is cooperative: a [`CancellationToken`] passed from
+//! JavaScript is checked before the task starts, by provers between phases,
+//! and once more before resolving. A cancelled task rejects with an `Error`
+//! whose `code` is `"CANCELLED"`.
+//!
+//! Progress is reported through an optional callback. Provers advance a
+//! [`Progress`] per phase, from hot loops if need be, since counting is a
+//! single atomic add. Each whole percent crossed queues a non-blocking call
+//! to the callback on the main thread. Reported percentages never decrease.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use napi::bindgen_prelude::AsyncTask;
+#[cfg(not(feature = "wasm"))]
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+#[cfg(not(feature = "wasm"))]
+use napi::JsFunction;
+use napi::{Env, Error, JsError, JsUnknown, Result, Status, Task};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+
+/// `code` of the error a cancelled task rejects with
+pub const CANCELLED_CODE: &str = "CANCELLED";
+
+/// Token JavaScript callers use to cancel an in-flight task
+#[napi]
+#[derive(Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[napi]
+impl CancellationToken {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every task holding this token
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Whether `cancel()` has been called
+    #[napi(getter)]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Flag shared with the worker thread
+    pub fn flag(&self) -> CancelFlag {
+        CancelFlag(Some(self.cancelled.clone()))
+    }
+}
+
+/// Worker-side view of an optional cancellation token
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Option<Arc<AtomicBool>>);
+
+impl CancelFlag {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.as_ref().is_some_and(|f| f.load(Ordering::Acquire))
+    }
+
+    /// Return a `Status::Cancelled` error if cancellation was requested
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::new(
+                Status::Cancelled,
+                "operation was cancelled".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Progress update passed to the `prove_async` callback
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofProgress {
+    /// Current phase, e.g. "witness", "ntt", "msm" or "final"
+    pub phase: String,
+    /// Overall completion from 0 to 100, never decreasing
+    pub percent: f64,
+    /// Milliseconds since the task started computing
+    pub elapsed_ms: i64,
+}
+
+pub(crate) type ProgressSink = Arc<dyn Fn(ProofProgress) + Send + Sync>;
+
+/// Worker-side progress reporter for one proving task
+///
+/// A reporter without a sink ignores every update, so provers can report
+/// unconditionally.
+pub struct Progress {
+    start: Instant,
+    sink: Option<ProgressSink>,
+    /// Last reported percentage; held while calling the sink so reports
+    /// are delivered in order
+    last: Mutex<f64>,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Progress {
+    pub(crate) fn new(sink: Option<ProgressSink>) -> Self {
+        Progress {
+            start: Instant::now(),
+            sink,
+            last: Mutex::new(0.0),
+        }
+    }
+
+    /// Report `percent` overall completion, clamped to never decrease
+    pub fn report(&self, phase: &str, percent: f64) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let mut last = self.last.lock().unwrap();
+        *last = percent.clamp(*last, 100.0);
+        sink(ProofProgress {
+            phase: phase.to_string(),
+            percent: *last,
+            elapsed_ms: self.start.elapsed().as_millis() as i64,
+        });
+    }
+
+    /// Counter for a phase of `total` work items spanning `from..to` percent
+    pub fn phase(&self, name: &'static str, from: f64, to: f64, total: u64) -> PhaseProgress<'_> {
+        PhaseProgress {
+            progress: self,
+            name,
+            from,
+            to,
+            total: total.max(1),
+            done: AtomicU64::new(0),
+            reported: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Shared counter for one phase, safe to advance from worker threads
+pub struct PhaseProgress<'a> {
+    progress: &'a Progress,
+    name: &'static str,
+    from: f64,
+    to: f64,
+    total: u64,
+    done: AtomicU64,
+    /// Whole percent of this phase last reported
+    reported: AtomicU64,
+}
+
+impl PhaseProgress<'_> {
+    /// Record `n` more completed items, reporting each whole percent crossed
+    pub fn advance(&self, n: u64) {
+        let done = (self.done.fetch_add(n, Ordering::Relaxed) + n).min(self.total);
+        let step = done * 100 / self.total;
+        if self.reported.fetch_max(step, Ordering::Relaxed) < step {
+            let fraction = done as f64 / self.total as f64;
+            self.progress
+                .report(self.name, self.from + (self.to - self.from) * fraction);
+        }
+    }
+}
+
+/// Forward progress updates to a JavaScript callback on the main thread
+///
+/// Calls are queued without blocking the worker, in the order reported.
+#[cfg(not(feature = "wasm"))]
+pub(crate) fn progress_sink(on_progress: Option<JsFunction>) -> Result<Option<ProgressSink>> {
+    let Some(callback) = on_progress else {
+        return Ok(None);
+    };
+    let tsfn: ThreadsafeFunction<ProofProgress, ErrorStrategy::Fatal> =
+        callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+    Ok(Some(Arc::new(move |update| {
+        tsfn.call(update, ThreadsafeFunctionCallMode::NonBlocking);
+    })))
+}
+
+/// A proof system reachable through `prove_async` / `verify_async`
+///
+/// Long-running implementations should call `cancel.check()` between phases
+/// and report how far they are through `progress`.
+pub trait ProofSystem: Send + Sync {
+    fn prove(&self, witness: &[u8], cancel: &CancelFlag, progress: &Progress) -> Result<Vec<u8>>;
+
+    fn verify(&self, proof: &[u8], public_inputs: &[Vec<u8>], cancel: &CancelFlag) -> Result<bool>;
+}
+
+static REGISTRY: Lazy<RwLock<HashMap<String, Arc<dyn ProofSystem>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Check that `circuit_id` fits the `u16` length of the proof header
+pub(crate) fn check_circuit_id(circuit_id: &str) -> Result<()> {
+    if circuit_id.len() > u16::MAX as usize {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "circuit_id: must be at most {} bytes, got {}",
+                u16::MAX,
+                circuit_id.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Register (or replace) the proof system handling `circuit_id`
+///
+/// Rejects ids too long for the proof header.
+pub fn register_circuit(circuit_id: &str, system: Arc<dyn ProofSystem>) -> Result<()> {
+    check_circuit_id(circuit_id)?;
+    REGISTRY
+        .write()
+        .unwrap()
+        .insert(circuit_id.to_string(), system);
+    Ok(())
+}
+
+fn lookup(circuit_id: &str) -> Result<Arc<dyn ProofSystem>> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .get(circuit_id)
+        .cloned()
+        .ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                format!("circuit_id: no proof system registered for {circuit_id:?}"),
+            )
+        })
+}
+
+fn frame_proof(circuit_id: &str, body: &[u8]) -> Result<Vec<u8>> {
+    check_circuit_id(circuit_id)?;
+    let mut out = Vec::with_capacity(2 + circuit_id.len() + body.len());
+    out.extend((circuit_id.len() as u16).to_be_bytes());
+    out.extend(circuit_id.as_bytes());
+    out.extend(body);
+    Ok(out)
+}
+
+fn unframe_proof(proof: &[u8]) -> Result<(&str, &[u8])> {
+    let malformed = || {
+        Error::new(
+            Status::InvalidArg,
+            "proof: missing or truncated circuit id header".to_string(),
+        )
+    };
+    let len =
+        u16::from_be_bytes(proof.get(..2).ok_or_else(malformed)?.try_into().unwrap()) as usize;
+    let id = proof.get(2..2 + len).ok_or_else(malformed)?;
+    let id = std::str::from_utf8(id).map_err(|_| malformed())?;
+    Ok((id, &proof[2 + len..]))
+}
+
+/// Reject with `code: "CANCELLED"` for cancellations, otherwise pass through
+pub(crate) fn reject_error(env: Env, err: Error) -> Error {
+    if err.status != Status::Cancelled {
+        return err;
+    }
+    let with_code = || -> Result<JsUnknown> {
+        let mut obj = JsError::from(Error::new(Status::Cancelled, err.reason.clone()))
+            .into_unknown(env)
+            .coerce_to_object()?;
+        obj.set_named_property("code", env.create_string(CANCELLED_CODE)?)?;
+        Ok(obj.into_unknown())
+    };
+    match with_code() {
+        Ok(value) => Error::from(value),
+        Err(e) => e,
+    }
+}
+
+/// Background task behind `prove_async`
+pub struct ProveTask {
+    circuit_id: String,
+    witness: Vec<u8>,
+    cancel: CancelFlag,
+    progress: Progress,
+}
+
+impl Task for ProveTask {
+    type Output = Vec<u8>;
+    type JsValue = Vec<u8>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.cancel.check()?;
+        let system = lookup(&self.circuit_id)?;
+        let body = system.prove(&self.witness, &self.cancel, &self.progress)?;
+        self.cancel.check()?;
+        self.progress.report("final", 100.0);
+        frame_proof(&self.circuit_id, &body)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(reject_error(env, err))
+    }
+}
+
+/// Background task behind `verify_async`
+pub struct VerifyTask {
+    proof: Vec<u8>,
+    public_inputs: Vec<Vec<u8>>,
+    cancel: CancelFlag,
+}
+
+impl Task for VerifyTask {
+    type Output = bool;
+    type JsValue = bool;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.cancel.check()?;
+        let (circuit_id, body) = unframe_proof(&self.proof)?;
+        let system = lookup(circuit_id)?;
+        let valid = system.verify(body, &self.public_inputs, &self.cancel)?;
+        self.cancel.check()?;
+        Ok(valid)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(reject_error(env, err))
+    }
+}
+
+/// Generate a proof for `circuit_id` off the main thread
+///
+/// Resolves with the framed proof bytes. Rejects with `code: "CANCELLED"`
+/// if `token` is cancelled before the proof is returned. `on_progress`, if
+/// given, is called on the main thread with a `ProofProgress` as the
+/// prover advances, ending with phase "final" at 100 percent. Not in the
+/// `wasm` build, which has no main-thread callbacks.
+#[cfg(not(feature = "wasm"))]
+#[napi(ts_return_type = "Promise<Array<number>>")]
+pub fn prove_async(
+    circuit_id: String,
+    witness: Vec<u8>,
+    token: Option<&CancellationToken>,
+    #[napi(ts_arg_type = "(progress: ProofProgress) => void")] on_progress: Option<JsFunction>,
+) -> Result<AsyncTask<ProveTask>> {
+    Ok(AsyncTask::new(ProveTask {
+        circuit_id,
+        witness,
+        cancel: token.map(CancellationToken::flag).unwrap_or_default(),
+        progress: Progress::new(progress_sink(on_progress)?),
+    }))
+}
+
+/// Verify a proof produced by `prove_async` off the main thread
+#[napi(ts_return_type = "Promise<boolean>")]
+pub fn verify_async(
+    proof: Vec<u8>,
+    public_inputs: Vec<Vec<u8>>,
+    token: Option<&CancellationToken>,
+) -> AsyncTask<VerifyTask> {
+    AsyncTask::new(VerifyTask {
+        proof,
+        public_inputs,
+        cancel: token.map(CancellationToken::flag).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Toy system: the "proof" is the witness reversed, verified against
+    /// the concatenated public inputs
+    struct Reverse;
+
+    impl ProofSystem for Reverse {
+        fn prove(&self, witness: &[u8], cancel: &CancelFlag, _: &Progress) -> Result<Vec<u8>> {
+            cancel.check()?;
+            Ok(witness.iter().rev().copied().collect())
+        }
+
+        fn verify(&self, proof: &[u8], inputs: &[Vec<u8>], _: &CancelFlag) -> Result<bool> {
+            let expected: Vec<u8> = inputs.concat().into_iter().rev().collect();
+            Ok(proof == expected)
+        }
+    }
+
+    /// Toy system counting over the witness bytes in two parallel phases
+    struct Counting;
+
+    impl ProofSystem for Counting {
+        fn prove(&self, witness: &[u8], _: &CancelFlag, progress: &Progress) -> Result<Vec<u8>> {
+            let mut out = witness.to_vec();
+            for (name, from, to) in [("ntt", 0.0, 40.0), ("msm", 40.0, 90.0)] {
+                let phase = progress.phase(name, from, to, out.len() as u64);
+                crate::parallel::par_chunks_mut(&mut out, 1, 0, |_, chunk| {
+                    for byte in chunk {
+                        *byte = byte.wrapping_add(1);
+                        phase.advance(1);
+                    }
+                });
+            }
+            Ok(out)
+        }
+
+        fn verify(&self, _: &[u8], _: &[Vec<u8>], _: &CancelFlag) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    fn prove_with_progress(
+        circuit_id: &str,
+        witness: &[u8],
+        token: Option<&CancellationToken>,
+        sink: Option<ProgressSink>,
+    ) -> Result<Vec<u8>> {
+        let mut task = ProveTask {
+            circuit_id: circuit_id.to_string(),
+            witness: witness.to_vec(),
+            cancel: token.map(CancellationToken::flag).unwrap_or_default(),
+            progress: Progress::new(sink),
+        };
+        task.compute()
+    }
+
+    fn prove(
+        circuit_id: &str,
+        witness: &[u8],
+        token: Option<&CancellationToken>,
+    ) -> Result<Vec<u8>> {
+        prove_with_progress(circuit_id, witness, token, None)
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        register_circuit("test-counting", Arc::new(Counting)).unwrap();
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let updates = updates.clone();
+            Arc::new(move |update: ProofProgress| updates.lock().unwrap().push(update))
+        };
+        let proof =
+            prove_with_progress("test-counting", &vec![0u8; 10_000], None, Some(sink)).unwrap();
+        assert!(unframe_proof(&proof).unwrap().1.iter().all(|&b| b == 2));
+
+        let updates = updates.lock().unwrap();
+        assert!(updates.len() > 2);
+        assert!(updates
+            .windows(2)
+            .all(|w| w[0].percent <= w[1].percent && w[0].elapsed_ms <= w[1].elapsed_ms));
+        assert!(updates.iter().any(|u| u.phase == "ntt"));
+        assert!(updates
+            .iter()
+            .any(|u| u.phase == "msm" && u.percent > 40.0 && u.percent <= 90.0));
+        let last = updates.last().unwrap();
+        assert_eq!((last.phase.as_str(), last.percent), ("final", 100.0));
+        // One report per whole percent per phase, plus the final one
+        assert!(updates.len() <= 201);
+    }
+
+    #[test]
+    fn test_progress_clamped() {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let progress = Progress::new(Some({
+            let updates = updates.clone();
+            Arc::new(move |update: ProofProgress| updates.lock().unwrap().push(update.percent))
+        }));
+        progress.report("witness", 30.0);
+        progress.report("witness", 20.0);
+        progress.report("final", 150.0);
+        assert_eq!(*updates.lock().unwrap(), [30.0, 30.0, 100.0]);
+        // Without a sink, reports are dropped
+        Progress::default().phase("msm", 0.0, 100.0, 0).advance(5);
+    }
+
+    #[test]
+    fn test_prove_then_verify() {
+        register_circuit("test-reverse", Arc::new(Reverse)).unwrap();
+        let proof = prove("test-reverse", &[1, 2, 3], None).unwrap();
+        assert_eq!(
+            unframe_proof(&proof).unwrap(),
+            ("test-reverse", &[3u8, 2, 1][..])
+        );
+
+        let mut verify = VerifyTask {
+            proof: proof.clone(),
+            public_inputs: vec![vec![1], vec![2, 3]],
+            cancel: CancelFlag::default(),
+        };
+        assert!(verify.compute().unwrap());
+        verify.public_inputs = vec![vec![9]];
+        assert!(!verify.compute().unwrap());
+    }
+
+    #[test]
+    fn test_registered_groth16_circuit() {
+        use crate::bn254::Fr;
+        crate::groth16::register_groth16_circuit(
+            "test-multiplier".to_string(),
+            include_bytes!("../tests/fixtures/multiplier.r1cs").to_vec(),
+            include_bytes!("../tests/fixtures/multiplier.zkey").to_vec(),
+        )
+        .unwrap();
+        let witness: Vec<u8> = [1, 33, 3, 11]
+            .iter()
+            .flat_map(|&v| Fr::from_u64(v).to_be_bytes())
+            .collect();
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let updates = updates.clone();
+            Arc::new(move |update: ProofProgress| updates.lock().unwrap().push(update))
+        };
+        let proof = prove_with_progress("test-multiplier", &witness, None, Some(sink)).unwrap();
+        assert_eq!(unframe_proof(&proof).unwrap().1.len(), 256);
+
+        // The prover walks through every phase in order
+        let updates = updates.lock().unwrap();
+        let mut phases: Vec<&str> = updates.iter().map(|u| u.phase.as_str()).collect();
+        phases.dedup();
+        assert_eq!(phases, ["witness", "ntt", "msm", "final"]);
+        assert!(updates.windows(2).all(|w| w[0].percent <= w[1].percent));
+        assert_eq!(updates.last().unwrap().percent, 100.0);
+
+        let mut verify = VerifyTask {
+            proof,
+            public_inputs: vec![Fr::from_u64(33).to_be_bytes()],
+            cancel: CancelFlag::default(),
+        };
+        assert!(verify.compute().unwrap());
+        verify.public_inputs = vec![Fr::from_u64(34).to_be_bytes()];
+        assert!(!verify.compute().unwrap());
+
+        let err = prove("test-multiplier", &witness[..40], None).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "witness: expected packed 32-byte values, got 40 bytes"
+        );
+        let mut bad = witness.clone();
+        bad[63] ^= 1;
+        let err = prove("test-multiplier", &bad, None).unwrap_err();
+        assert_eq!(err.reason, "witness: constraint 0 is not satisfied");
+    }
+
+    #[test]
+    fn test_cancellation() {
+        register_circuit("test-reverse", Arc::new(Reverse)).unwrap();
+        let token = CancellationToken::new();
+        assert!(prove("test-reverse", &[1], Some(&token)).is_ok());
+        token.cancel();
+        assert!(token.is_cancelled());
+        let err = prove("test-reverse", &[1], Some(&token)).unwrap_err();
+        assert_eq!(err.status, Status::Cancelled);
+    }
+
+    #[test]
+    fn test_unknown_circuit_and_bad_proof() {
+        assert_eq!(
+            prove("no-such-circuit", &[], None).unwrap_err().status,
+            Status::InvalidArg
+        );
+        assert!(unframe_proof(&[0]).is_err());
+        assert!(unframe_proof(&[0, 5, b'a']).is_err());
+        assert_eq!(unframe_proof(&[0, 0]).unwrap(), ("", &[][..]));
+
+        // Ids whose length overflows the u16 header are refused up front
+        let long = "x".repeat(u16::MAX as usize + 1);
+        let err = register_circuit(&long, Arc::new(Reverse)).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(
+            err.reason,
+            "circuit_id: must be at most 65535 bytes, got 65536"
+        );
+        assert!(frame_proof(&long, &[]).is_err());
+        let longest = "x".repeat(u16::MAX as usize);
+        let framed = frame_proof(&longest, &[7]).unwrap();
+        assert_eq!(
+            unframe_proof(&framed).unwrap(),
+            (longest.as_str(), &[7u8][..])
+        );
+    }
+}
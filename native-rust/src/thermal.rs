@@ -0,0 +1,105 @@
+//! Thermal throttling checks before long-running work
+//!
+//! A multi-minute proof started on a machine that is already throttling runs
+//! at a fraction of its usual speed and heats it further. Callers should
+//! poll [`get_thermal_state`] (or just [`thermal_ok`]) before starting one
+//! and, if it reports serious pressure, wait and poll again before
+//! starting.
+//!
+//! The pressure level is the `NSProcessInfo` thermal state shared with
+//! [`crate::power`]. Throttling comes from the IOKit CPU power status
+//! (`IOPMCopyCPUPowerStatus`): a processor speed limit below 100%. Machines
+//! that do not publish that status, most Apple Silicon among them, count as
+//! throttling at "serious" pressure and above. Off macOS the level is
+//! "unknown" and nothing is reported as throttling.
+
+use napi_derive::napi;
+
+use crate::power::thermal_pressure;
+
+/// Thermal pressure and whether the CPU is being slowed down for it
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThermalState {
+    /// "nominal", "fair", "serious", "critical", or "unknown" off macOS
+    pub level: String,
+    /// Whether CPU performance is currently limited for thermal reasons
+    pub throttling: bool,
+}
+
+/// Levels at which long-running work should not be started
+fn is_hot(level: &str) -> bool {
+    matches!(level, "serious" | "critical")
+}
+
+/// Combine a pressure level with the CPU speed limit percentage, if known
+fn thermal_state(level: &str, speed_limit: Option<u64>) -> ThermalState {
+    ThermalState {
+        level: level.to_string(),
+        throttling: match speed_limit {
+            Some(limit) => limit < 100,
+            None => is_hot(level),
+        },
+    }
+}
+
+/// Read the current thermal pressure and CPU throttling state
+#[napi]
+pub fn get_thermal_state() -> ThermalState {
+    #[cfg(target_os = "macos")]
+    {
+        thermal_state(thermal_pressure(), crate::iokit::cpu_speed_limit())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        thermal_state(thermal_pressure(), None)
+    }
+}
+
+/// Whether thermal pressure is low enough to start a long proof
+///
+/// False at "serious" or "critical" pressure; true when the level is unknown.
+#[napi]
+pub fn thermal_ok() -> bool {
+    !is_hot(&get_thermal_state().level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::power::UNKNOWN;
+
+    #[test]
+    fn test_thermal_state_levels() {
+        for (level, hot) in [
+            ("nominal", false),
+            ("fair", false),
+            ("serious", true),
+            ("critical", true),
+            (UNKNOWN, false),
+        ] {
+            assert_eq!(is_hot(level), hot, "{level}");
+            assert_eq!(thermal_state(level, None).throttling, hot, "{level}");
+        }
+        // A published speed limit takes precedence over the level
+        assert!(thermal_state("nominal", Some(80)).throttling);
+        assert!(!thermal_state("serious", Some(100)).throttling);
+    }
+
+    #[test]
+    fn test_get_thermal_state() {
+        let state = get_thermal_state();
+        if cfg!(target_os = "macos") {
+            assert_ne!(state.level, UNKNOWN);
+        } else {
+            assert_eq!(
+                state,
+                ThermalState {
+                    level: UNKNOWN.to_string(),
+                    throttling: false,
+                }
+            );
+            assert!(thermal_ok());
+        }
+    }
+}
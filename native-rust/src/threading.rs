@@ -0,0 +1,134 @@
+//! Rayon thread pool configuration for the parallel kernels
+//!
+//! Parallel kernels run on Rayon's global pool, which is built the first
+//! time a kernel asks for its size: either by an earlier
+//! `configure_thread_pool()` call, or with the detected defaults. Rayon's
+//! global pool cannot change once built, so `configure_thread_pool()` must
+//! run before any parallel computation and fails on every later call.
+
+use std::sync::Once;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Upper bound on the configurable worker count
+const MAX_THREADS: u32 = 1024;
+
+/// Build the global pool with the detected defaults unless it exists
+fn ensure_global_pool() {
+    static DEFAULT: Once = Once::new();
+    DEFAULT.call_once(|| {
+        // Fails when `configure_thread_pool` already built it
+        let _ = rayon::ThreadPoolBuilder::new()
+            .num_threads(crate::parallel::detected_threads())
+            .build_global();
+    });
+}
+
+/// Worker count of the pool parallel kernels started here run on
+///
+/// Inside [`with_thread_pool`], including on its workers, that pool's size.
+pub(crate) fn pool_threads() -> usize {
+    ensure_global_pool();
+    rayon::current_num_threads()
+}
+
+/// Build Rayon's global pool with the given worker count and stack size
+///
+/// `num_threads` of 0 keeps the detected default, as does a
+/// `stack_size_kb` of 0 for the stack size. Must be called once, before any
+/// parallel computation; later calls return an error because the global
+/// pool is immutable once built.
+#[napi]
+pub fn configure_thread_pool(num_threads: u32, stack_size_kb: u32) -> Result<()> {
+    if num_threads > MAX_THREADS {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("num_threads must be at most {MAX_THREADS}, got {num_threads}"),
+        ));
+    }
+    let threads = match num_threads {
+        0 => crate::parallel::detected_threads(),
+        n => n as usize,
+    };
+    let mut builder = rayon::ThreadPoolBuilder::new().num_threads(threads);
+    if stack_size_kb > 0 {
+        builder = builder.stack_size(stack_size_kb as usize * 1024);
+    }
+    builder.build_global().map_err(|_| {
+        Error::new(
+            Status::GenericFailure,
+            "the thread pool is already configured; configure_thread_pool must be called \
+             once, before any parallel computation"
+                .to_string(),
+        )
+    })
+}
+
+/// Number of worker threads in Rayon's current pool
+#[napi]
+pub fn get_thread_pool_size() -> u32 {
+    pool_threads() as u32
+}
+
+/// Run `f` on a dedicated pool of `num_threads` workers, leaving the global
+/// pool untouched
+///
+/// Every parallel kernel `f` starts runs on the dedicated pool, including
+/// kernels nested inside another kernel's workers.
+pub fn with_thread_pool<R: Send>(num_threads: u32, f: impl FnOnce() -> R + Send) -> R {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.max(1) as usize)
+        .build()
+        .expect("failed to build a thread pool")
+        .install(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::Fr;
+
+    #[test]
+    fn test_configure_after_first_use_fails() {
+        // Other tests have run or will run kernels, so fix the pool first
+        let _ = get_thread_pool_size();
+        let err = configure_thread_pool(2, 0).unwrap_err();
+        assert!(err.reason.contains("already configured"), "{}", err.reason);
+        let err = configure_thread_pool(MAX_THREADS + 1, 0).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+
+    #[test]
+    fn test_with_thread_pool() {
+        let global = get_thread_pool_size();
+        let values: Vec<Fr> = (0..1u64 << 14).map(Fr::from_u64).collect();
+        let expected = {
+            let mut v = values.clone();
+            crate::ntt::ntt_in_place(&mut v, false).unwrap();
+            v
+        };
+        for threads in [1, 2, 7] {
+            let mut v = values.clone();
+            with_thread_pool(threads, || {
+                assert_eq!(get_thread_pool_size(), threads);
+                crate::ntt::ntt_in_place(&mut v, false).unwrap();
+            });
+            assert_eq!(v, expected);
+        }
+        assert_eq!(get_thread_pool_size(), global);
+
+        // Kernels nested inside workers see the dedicated pool too
+        let nested = with_thread_pool(3, || {
+            crate::parallel::par_map(&[0u8; 8], |_| {
+                crate::parallel::par_map(&[0u8; 4], |_| get_thread_pool_size())
+            })
+        });
+        assert!(nested.concat().iter().all(|&n| n == 3));
+
+        // The global pool is untouched after a panic too
+        let result = std::panic::catch_unwind(|| with_thread_pool(3, || panic!("boom")));
+        assert!(result.is_err());
+        assert_eq!(get_thread_pool_size(), global);
+    }
+}
@@ -0,0 +1,465 @@
+//! Memory size, cache geometry and core-type detection
+//!
+//! macOS reads the `hw.*` sysctls; Linux reads `sysconf` and the
+//! `/sys/devices/system/cpu/cpu0/cache` hierarchy; Windows walks the
+//! `GetLogicalProcessorInformationEx` records. Values that cannot be
+//! determined are reported as 0.
+
+use napi_derive::napi;
+
+/// Total memory and CPU cache geometry, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryGeometry {
+    pub total_memory_bytes: u64,
+    pub l1_cache_bytes: u64,
+    pub l2_cache_bytes: u64,
+    pub cache_line_bytes: u64,
+}
+
+/// Detect memory and cache geometry for the current machine
+pub fn detect_memory_geometry() -> MemoryGeometry {
+    detect()
+}
+
+/// CPU cache sizes exposed to JavaScript, 0 where unknown
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheSizes {
+    /// L1 data cache size in bytes (unified L1 where there is no split)
+    pub l1d_cache_bytes: u32,
+    /// L2 cache size in bytes
+    pub l2_cache_bytes: u32,
+    /// Cache line size in bytes
+    pub cache_line_bytes: u32,
+}
+
+impl From<MemoryGeometry> for CacheSizes {
+    fn from(geometry: MemoryGeometry) -> Self {
+        let clamp = |v: u64| u32::try_from(v).unwrap_or(u32::MAX);
+        CacheSizes {
+            l1d_cache_bytes: clamp(geometry.l1_cache_bytes),
+            l2_cache_bytes: clamp(geometry.l2_cache_bytes),
+            cache_line_bytes: clamp(geometry.cache_line_bytes),
+        }
+    }
+}
+
+/// Detect the L1 data, L2 and cache line sizes
+#[napi]
+pub fn get_cache_sizes() -> CacheSizes {
+    detect_memory_geometry().into()
+}
+
+/// Performance / efficiency core split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoreCounts {
+    pub performance: u32,
+    pub efficiency: u32,
+}
+
+/// Detect performance and efficiency core counts
+///
+/// Apple Silicon reports each perflevel separately (`perflevel0` is the
+/// performance cluster). Windows reports physical cores with an efficiency
+/// class, and cores in the highest class count as performance cores.
+/// Everywhere else, and on Macs without perflevels, every logical core is
+/// treated as a performance core.
+pub fn detect_core_counts() -> CoreCounts {
+    #[cfg(target_os = "macos")]
+    {
+        use crate::sysctl::read_u64;
+
+        if let Some(performance) = read_u64("hw.perflevel0.physicalcpu").filter(|&n| n > 0) {
+            return CoreCounts {
+                performance: performance as u32,
+                efficiency: read_u64("hw.perflevel1.physicalcpu").unwrap_or(0) as u32,
+            };
+        }
+    }
+    #[cfg(windows)]
+    {
+        let topology = windows_topology();
+        if topology.performance_cores > 0 {
+            return CoreCounts {
+                performance: topology.performance_cores,
+                efficiency: topology.efficiency_cores,
+            };
+        }
+    }
+    CoreCounts {
+        performance: crate::get_cpu_count(),
+        efficiency: 0,
+    }
+}
+
+/// Detect the number of physical cores, not counting SMT siblings
+///
+/// macOS reads `hw.physicalcpu`; Linux counts distinct
+/// `(physical id, core id)` pairs in `/proc/cpuinfo`; Windows counts
+/// processor-core records. Falls back to the logical count where the
+/// topology is not reported (e.g. aarch64 kernels without `core id`), and
+/// never exceeds the logical cores available to this process.
+pub fn detect_physical_cores() -> u32 {
+    #[cfg(target_os = "macos")]
+    let physical = crate::sysctl::read_u64("hw.physicalcpu").map(|n| n as u32);
+    #[cfg(target_os = "linux")]
+    let physical = std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|text| parse_physical_cores(&text));
+    #[cfg(windows)]
+    let physical = {
+        let topology = windows_topology();
+        Some(topology.performance_cores + topology.efficiency_cores)
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+    let physical: Option<u32> = None;
+
+    let logical = crate::get_cpu_count();
+    physical
+        .filter(|&n| n > 0)
+        .map_or(logical, |n| n.min(logical))
+}
+
+/// Number of physical CPU cores, excluding SMT siblings
+#[napi]
+pub fn get_physical_core_count() -> u32 {
+    detect_physical_cores()
+}
+
+/// Count distinct `(physical id, core id)` pairs in `/proc/cpuinfo` text
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_physical_cores(cpuinfo: &str) -> Option<u32> {
+    let mut cores = std::collections::HashSet::new();
+    // Processor blocks are separated by blank lines
+    for block in cpuinfo.split("\n\n") {
+        let field = |key: &str| {
+            block.lines().find_map(|line| {
+                let (k, v) = line.split_once(':')?;
+                (k.trim() == key).then(|| v.trim())
+            })
+        };
+        if let Some(core) = field("core id") {
+            cores.insert((field("physical id").unwrap_or("0"), core));
+        }
+    }
+    (!cores.is_empty()).then_some(cores.len() as u32)
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> MemoryGeometry {
+    use crate::sysctl::read_u64;
+
+    MemoryGeometry {
+        total_memory_bytes: read_u64("hw.memsize").unwrap_or(0),
+        l1_cache_bytes: read_u64("hw.l1dcachesize").unwrap_or(0),
+        l2_cache_bytes: read_u64("hw.l2cachesize").unwrap_or(0),
+        cache_line_bytes: read_u64("hw.cachelinesize").unwrap_or(0),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect() -> MemoryGeometry {
+    let mut geometry = MemoryGeometry {
+        total_memory_bytes: linux_total_memory(),
+        ..Default::default()
+    };
+    let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+        return geometry;
+    };
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let read = |name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        let Some(size) = parse_cache_size(&read("size")) else {
+            continue;
+        };
+        match (read("level").as_str(), read("type").as_str()) {
+            ("1", "Data" | "Unified") => geometry.l1_cache_bytes = size,
+            ("2", "Data" | "Unified") => geometry.l2_cache_bytes = size,
+            _ => continue,
+        }
+        if geometry.cache_line_bytes == 0 {
+            geometry.cache_line_bytes = read("coherency_line_size").parse().unwrap_or(0);
+        }
+    }
+    geometry
+}
+
+#[cfg(target_os = "linux")]
+fn linux_total_memory() -> u64 {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if pages <= 0 || page_size <= 0 {
+        return 0;
+    }
+    pages as u64 * page_size as u64
+}
+
+#[cfg(windows)]
+fn detect() -> MemoryGeometry {
+    let topology = windows_topology();
+    MemoryGeometry {
+        total_memory_bytes: crate::win32::total_physical_memory().unwrap_or(0),
+        l1_cache_bytes: topology.l1_cache_bytes,
+        l2_cache_bytes: topology.l2_cache_bytes,
+        cache_line_bytes: topology.cache_line_bytes,
+    }
+}
+
+#[cfg(windows)]
+fn windows_topology() -> ProcessorTopology {
+    crate::win32::logical_processor_information()
+        .map(|buffer| parse_processor_information(&buffer))
+        .unwrap_or_default()
+}
+
+/// Core and cache summary decoded from `GetLogicalProcessorInformationEx`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+struct ProcessorTopology {
+    performance_cores: u32,
+    efficiency_cores: u32,
+    l1_cache_bytes: u64,
+    l2_cache_bytes: u64,
+    cache_line_bytes: u64,
+}
+
+/// Walk packed `SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX` records
+///
+/// Each record starts with its relationship and total size. Processor-core
+/// records carry the efficiency class at offset 9; cache records carry
+/// level, line size, cache size and type at offsets 8, 10, 12 and 16. The
+/// first L1/L2 data or unified cache seen is reported, matching the
+/// per-core figures of the other platforms.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_processor_information(buffer: &[u8]) -> ProcessorTopology {
+    const RELATION_PROCESSOR_CORE: u32 = 0;
+    const RELATION_CACHE: u32 = 2;
+    const CACHE_UNIFIED: u32 = 0;
+    const CACHE_DATA: u32 = 2;
+
+    let u16_at = |r: &[u8], i: usize| u16::from_le_bytes([r[i], r[i + 1]]);
+    let u32_at = |r: &[u8], i: usize| u32::from_le_bytes(r[i..i + 4].try_into().unwrap());
+
+    let mut topology = ProcessorTopology::default();
+    let mut classes = Vec::new();
+    let mut rest = buffer;
+    while rest.len() >= 8 {
+        let size = u32_at(rest, 4) as usize;
+        if size < 8 || size > rest.len() {
+            break;
+        }
+        let record = &rest[..size];
+        match u32_at(record, 0) {
+            RELATION_PROCESSOR_CORE if size >= 10 => classes.push(record[9]),
+            RELATION_CACHE if size >= 20 => {
+                let level = record[8];
+                let line = u16_at(record, 10) as u64;
+                let bytes = u32_at(record, 12) as u64;
+                let data = matches!(u32_at(record, 16), CACHE_UNIFIED | CACHE_DATA);
+                let slot = match (level, data) {
+                    (1, true) => Some(&mut topology.l1_cache_bytes),
+                    (2, true) => Some(&mut topology.l2_cache_bytes),
+                    _ => None,
+                };
+                if let Some(slot) = slot.filter(|s| **s == 0) {
+                    *slot = bytes;
+                    if topology.cache_line_bytes == 0 {
+                        topology.cache_line_bytes = line;
+                    }
+                }
+            }
+            _ => {}
+        }
+        rest = &rest[size..];
+    }
+    if let Some(&top) = classes.iter().max() {
+        let performance = classes.iter().filter(|&&c| c == top).count() as u32;
+        topology.performance_cores = performance;
+        topology.efficiency_cores = classes.len() as u32 - performance;
+    }
+    topology
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn detect() -> MemoryGeometry {
+    MemoryGeometry::default()
+}
+
+/// Parse a sysfs cache size such as `48K`, `2048K` or `8M`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cache_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, scale) = match s.chars().last()? {
+        'K' => (&s[..s.len() - 1], 1 << 10),
+        'M' => (&s[..s.len() - 1], 1 << 20),
+        'G' => (&s[..s.len() - 1], 1 << 30),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok().map(|v| v * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cache_size() {
+        assert_eq!(parse_cache_size("48K"), Some(48 * 1024));
+        assert_eq!(parse_cache_size("8M"), Some(8 << 20));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size(""), None);
+        assert_eq!(parse_cache_size("xK"), None);
+    }
+
+    #[test]
+    fn test_detect_memory_geometry() {
+        let geometry = detect_memory_geometry();
+        if cfg!(any(target_os = "macos", target_os = "linux")) {
+            assert!(geometry.total_memory_bytes > 0);
+        }
+        if geometry.cache_line_bytes != 0 {
+            assert!(geometry.cache_line_bytes.is_power_of_two());
+        }
+        let sizes = get_cache_sizes();
+        assert_eq!(sizes.l1d_cache_bytes as u64, geometry.l1_cache_bytes);
+        assert_eq!(sizes.l2_cache_bytes as u64, geometry.l2_cache_bytes);
+    }
+
+    #[test]
+    fn test_core_counts() {
+        let cores = detect_core_counts();
+        assert!(cores.performance >= 1);
+        assert!(cores.performance + cores.efficiency <= crate::get_cpu_count() * 2);
+        if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            assert!(cores.efficiency > 0);
+        }
+    }
+
+    #[test]
+    fn test_parse_physical_cores() {
+        let block = |cpu: u32, package: u32, core: u32| {
+            format!("processor\t: {cpu}\nphysical id\t: {package}\ncore id\t\t: {core}\n")
+        };
+        // Two packages of two cores, each with two SMT siblings
+        let mut text = Vec::new();
+        for cpu in 0..8 {
+            text.push(block(cpu, cpu / 4, cpu % 2));
+        }
+        assert_eq!(parse_physical_cores(&text.join("\n")), Some(4));
+
+        // aarch64 kernels list processors without topology fields
+        let arm = "processor\t: 0\nBogoMIPS\t: 50.00\n\nprocessor\t: 1\nBogoMIPS\t: 50.00\n";
+        assert_eq!(parse_physical_cores(arm), None);
+    }
+
+    #[test]
+    fn test_physical_cores() {
+        let physical = get_physical_core_count();
+        assert!(physical >= 1);
+        assert!(physical <= crate::get_cpu_count());
+    }
+
+    /// Build a packed record with the given relationship and payload
+    fn record(relationship: u32, payload: &[u8]) -> Vec<u8> {
+        let mut r = relationship.to_le_bytes().to_vec();
+        r.extend_from_slice(&((payload.len() + 8) as u32).to_le_bytes());
+        r.extend_from_slice(payload);
+        r
+    }
+
+    fn core(efficiency_class: u8) -> Vec<u8> {
+        let mut payload = vec![0u8; 40];
+        payload[1] = efficiency_class;
+        record(0, &payload)
+    }
+
+    fn cache(level: u8, line: u16, size: u32, kind: u32) -> Vec<u8> {
+        let mut payload = vec![0u8; 40];
+        payload[0] = level;
+        payload[2..4].copy_from_slice(&line.to_le_bytes());
+        payload[4..8].copy_from_slice(&size.to_le_bytes());
+        payload[8..12].copy_from_slice(&kind.to_le_bytes());
+        record(2, &payload)
+    }
+
+    #[test]
+    fn test_parse_processor_information() {
+        // Two P-cores, three E-cores, split L1, unified L2, and a NUMA record
+        let mut buffer = Vec::new();
+        for class in [1, 1, 0, 0, 0] {
+            buffer.extend(core(class));
+        }
+        buffer.extend(cache(1, 64, 32 << 10, 1));
+        buffer.extend(cache(1, 64, 48 << 10, 2));
+        buffer.extend(cache(2, 64, 1280 << 10, 0));
+        buffer.extend(cache(2, 64, 2 << 20, 0));
+        buffer.extend(record(1, &[0u8; 24]));
+        assert_eq!(
+            parse_processor_information(&buffer),
+            ProcessorTopology {
+                performance_cores: 2,
+                efficiency_cores: 3,
+                l1_cache_bytes: 48 << 10,
+                l2_cache_bytes: 1280 << 10,
+                cache_line_bytes: 64,
+            }
+        );
+
+        // Homogeneous cores all count as performance cores
+        let uniform: Vec<u8> = (0..4).flat_map(|_| core(0)).collect();
+        let topology = parse_processor_information(&uniform);
+        assert_eq!(
+            (topology.performance_cores, topology.efficiency_cores),
+            (4, 0)
+        );
+
+        // Truncated records stop the walk instead of reading past the end
+        let mut truncated = core(0);
+        truncated[4] = 200;
+        assert_eq!(
+            parse_processor_information(&truncated),
+            ProcessorTopology::default()
+        );
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    #[test]
+    fn test_apple_silicon_geometry() {
+        let geometry = detect_memory_geometry();
+        assert!(geometry.total_memory_bytes >= 8 << 30);
+        assert!(geometry.l1_cache_bytes > 0);
+        assert!(geometry.l2_cache_bytes > geometry.l1_cache_bytes);
+        assert_eq!(geometry.cache_line_bytes, 128);
+    }
+}
+
+#[cfg(all(test, windows))]
+mod windows_tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_topology() {
+        let topology = windows_topology();
+        assert!(topology.performance_cores >= 1);
+        assert!(topology.performance_cores + topology.efficiency_cores <= crate::get_cpu_count());
+        assert!(topology.l1_cache_bytes > 0);
+        assert!(topology.l2_cache_bytes >= topology.l1_cache_bytes);
+        assert!(topology.cache_line_bytes.is_power_of_two());
+    }
+
+    #[test]
+    fn test_windows_capabilities() {
+        let caps = crate::detect_rust_capabilities();
+        assert_eq!(caps.os, "windows");
+        assert!(caps.total_memory_bytes > 0);
+        assert!(caps.performance_cores >= 1);
+        assert!(!caps.cpu_model.is_empty());
+        if cfg!(target_arch = "x86_64") {
+            assert!(!caps.has_neon && !caps.has_sve);
+        }
+    }
+}
@@ -0,0 +1,595 @@
+//! Extension field tower `Fp2 -> Fp6 -> Fp12` for pairing-friendly curves
+//!
+//! - `Fp2 = Fp[u] / (u^2 + 1)`
+//! - `Fp6 = Fp2[v] / (v^3 - ξ)`
+//! - `Fp12 = Fp6[w] / (w^2 - v)`
+//!
+//! BLS12-381 and BN254 share this shape and differ only in the base field and
+//! the non-residue `ξ` (`1 + u` and `9 + u` respectively). Frobenius maps use
+//! the coefficients `ξ^(i (p - 1) / 6)`, computed once per curve.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::montgomery::{sub_limbs, ConstantTimeField, Field, Fp, MontConfig};
+
+/// Parameters of a `u^2 = -1` tower
+pub trait TowerConfig<const N: usize>: 'static + Copy + Send + Sync + fmt::Debug {
+    /// Base prime field
+    type Base: MontConfig<N>;
+
+    /// `ξ = XI[0] + XI[1] u`, the cubic and sextic non-residue of Fp2
+    const XI: [u64; 2];
+
+    /// `ξ^(i (p - 1) / 6)` for `i = 0..6`, usually cached via [`frobenius_coeffs`]
+    fn frobenius_coeffs() -> &'static [Fp2<Self, N>; 6];
+}
+
+/// Compute `ξ^(i (p - 1) / 6)` for `i = 0..6`
+pub fn frobenius_coeffs<T: TowerConfig<N>, const N: usize>() -> [Fp2<T, N>; 6] {
+    let mut exp = sub_limbs(&T::Base::MODULUS, &one_limbs::<N>()).0;
+    let mut rem = 0u128;
+    for limb in exp.iter_mut().rev() {
+        let cur = (rem << 64) | *limb as u128;
+        *limb = (cur / 6) as u64;
+        rem = cur % 6;
+    }
+    debug_assert_eq!(rem, 0, "p - 1 must be divisible by 6");
+    let g = Fp2::<T, N>::xi().pow(&exp);
+    let mut out = [Fp2::one(); 6];
+    for i in 1..6 {
+        out[i] = out[i - 1] * g;
+    }
+    out
+}
+
+fn one_limbs<const N: usize>() -> [u64; N] {
+    let mut one = [0u64; N];
+    one[0] = 1;
+    one
+}
+
+/// Square-and-multiply over little-endian 64-bit limbs
+fn pow_limbs<F: Field>(base: &F, exp: &[u64]) -> F {
+    let mut acc = F::one();
+    for limb in exp.iter().rev() {
+        for bit in (0..64).rev() {
+            acc = acc.square();
+            if (limb >> bit) & 1 == 1 {
+                acc = acc * *base;
+            }
+        }
+    }
+    acc
+}
+
+/// Element `c0 + c1 u` of Fp2
+pub struct Fp2<T: TowerConfig<N>, const N: usize> {
+    pub c0: Fp<T::Base, N>,
+    pub c1: Fp<T::Base, N>,
+}
+
+/// Element `c0 + c1 v + c2 v^2` of Fp6
+pub struct Fp6<T: TowerConfig<N>, const N: usize> {
+    pub c0: Fp2<T, N>,
+    pub c1: Fp2<T, N>,
+    pub c2: Fp2<T, N>,
+}
+
+/// Element `c0 + c1 w` of Fp12
+pub struct Fp12<T: TowerConfig<N>, const N: usize> {
+    pub c0: Fp6<T, N>,
+    pub c1: Fp6<T, N>,
+}
+
+impl<T: TowerConfig<N>, const N: usize> Fp2<T, N> {
+    pub fn new(c0: Fp<T::Base, N>, c1: Fp<T::Base, N>) -> Self {
+        Fp2 { c0, c1 }
+    }
+
+    /// Embed a base field element
+    pub fn from_base(c0: Fp<T::Base, N>) -> Self {
+        Fp2 { c0, c1: Fp::zero() }
+    }
+
+    /// The non-residue `ξ`
+    pub fn xi() -> Self {
+        Fp2::new(Fp::from_u64(T::XI[0]), Fp::from_u64(T::XI[1]))
+    }
+
+    /// `c0 - c1 u`, which is also the Frobenius map `x^p`
+    pub fn conjugate(&self) -> Self {
+        Fp2::new(self.c0, -self.c1)
+    }
+
+    pub fn mul_by_base(&self, k: Fp<T::Base, N>) -> Self {
+        Fp2::new(self.c0 * k, self.c1 * k)
+    }
+
+    /// Multiply by `ξ = x0 + x1 u`
+    pub fn mul_by_xi(&self) -> Self {
+        let x0 = Fp::from_u64(T::XI[0]);
+        let (c0, c1) = if T::XI[1] == 1 {
+            (self.c0 * x0 - self.c1, self.c0 + self.c1 * x0)
+        } else {
+            let x1 = Fp::from_u64(T::XI[1]);
+            (self.c0 * x0 - self.c1 * x1, self.c0 * x1 + self.c1 * x0)
+        };
+        Fp2::new(c0, c1)
+    }
+
+    pub fn pow(&self, exp: &[u64]) -> Self {
+        pow_limbs(self, exp)
+    }
+
+    /// Square root for `p ≡ 3 (mod 4)`, `None` for a non-residue
+    ///
+    /// Algorithm 9 of "Square root computation over even extension fields"
+    /// (ePrint 2012/685). Either of the two roots may be returned. Variable
+    /// time.
+    pub fn sqrt(&self) -> Option<Self> {
+        assert_eq!(T::Base::TWO_ADICITY, 1, "Fp2::sqrt needs p = 3 mod 4");
+        if self.is_zero() {
+            return Some(*self);
+        }
+        // (p - 3) / 4 and (p - 1) / 2
+        let quarter = T::Base::TRACE_MINUS_ONE_DIV_TWO;
+        let mut half = T::Base::MODULUS;
+        for i in 0..N {
+            half[i] = (half[i] >> 1) | half.get(i + 1).map_or(0, |h| h << 63);
+        }
+        let a1 = self.pow(&quarter);
+        let alpha = a1.square() * *self;
+        let x0 = a1 * *self;
+        let x = if alpha == -Self::one() {
+            Fp2::new(-x0.c1, x0.c0)
+        } else {
+            (alpha + Self::one()).pow(&half) * x0
+        };
+        (x.square() == *self).then_some(x)
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Fp6<T, N> {
+    pub fn new(c0: Fp2<T, N>, c1: Fp2<T, N>, c2: Fp2<T, N>) -> Self {
+        Fp6 { c0, c1, c2 }
+    }
+
+    /// Multiply by `v`: `(c0, c1, c2) -> (ξ c2, c0, c1)`
+    pub fn mul_by_v(&self) -> Self {
+        Fp6::new(self.c2.mul_by_xi(), self.c0, self.c1)
+    }
+
+    /// Frobenius map `x^p`
+    pub fn frobenius(&self) -> Self {
+        let g = T::frobenius_coeffs();
+        Fp6::new(
+            self.c0.conjugate(),
+            self.c1.conjugate() * g[2],
+            self.c2.conjugate() * g[4],
+        )
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Fp12<T, N> {
+    pub fn new(c0: Fp6<T, N>, c1: Fp6<T, N>) -> Self {
+        Fp12 { c0, c1 }
+    }
+
+    /// Build from the coefficients of `1, w, ..., w^5`
+    pub fn from_w_coeffs(g: [Fp2<T, N>; 6]) -> Self {
+        Fp12::new(Fp6::new(g[0], g[2], g[4]), Fp6::new(g[1], g[3], g[5]))
+    }
+
+    /// `c0 - c1 w`, the `p^6` Frobenius and the inverse on the cyclotomic subgroup
+    pub fn conjugate(&self) -> Self {
+        Fp12::new(self.c0, -self.c1)
+    }
+
+    /// Frobenius map `x^p`
+    pub fn frobenius(&self) -> Self {
+        let g = T::frobenius_coeffs();
+        Fp12::from_w_coeffs([
+            self.c0.c0.conjugate(),
+            self.c1.c0.conjugate() * g[1],
+            self.c0.c1.conjugate() * g[2],
+            self.c1.c1.conjugate() * g[3],
+            self.c0.c2.conjugate() * g[4],
+            self.c1.c2.conjugate() * g[5],
+        ])
+    }
+
+    pub fn pow(&self, exp: &[u64]) -> Self {
+        pow_limbs(self, exp)
+    }
+
+    /// Granger-Scott squaring, valid only in the cyclotomic subgroup
+    ///
+    /// Elements of order dividing `p^4 - p^2 + 1`, such as the output of the
+    /// easy part of the final exponentiation, square in Fp4 components with
+    /// three Fp2 squarings' worth of work instead of a full Fp12 square.
+    pub fn cyclotomic_square(&self) -> Self {
+        // (z0 + z1 t)^2 over Fp4 = Fp2[t] / (t^2 - ξ)
+        let fp4_square = |z0: Fp2<T, N>, z1: Fp2<T, N>| {
+            let t = z0 * z1;
+            let c0 = (z0 + z1) * (z0 + z1.mul_by_xi()) - t - t.mul_by_xi();
+            (c0, t.double())
+        };
+        let (t0, t1) = fp4_square(self.c0.c0, self.c1.c1);
+        let (t2, t3) = fp4_square(self.c1.c0, self.c0.c2);
+        let (t4, t5) = fp4_square(self.c0.c1, self.c1.c2);
+        let t5 = t5.mul_by_xi();
+        // 3 t - 2 z for the real parts, 3 t + 2 z for the imaginary ones
+        let minus = |t: Fp2<T, N>, z: Fp2<T, N>| (t - z).double() + t;
+        let plus = |t: Fp2<T, N>, z: Fp2<T, N>| (t + z).double() + t;
+        Fp12::new(
+            Fp6::new(
+                minus(t0, self.c0.c0),
+                minus(t2, self.c0.c1),
+                minus(t4, self.c0.c2),
+            ),
+            Fp6::new(
+                plus(t5, self.c1.c0),
+                plus(t1, self.c1.c1),
+                plus(t3, self.c1.c2),
+            ),
+        )
+    }
+
+    /// [`Fp12::pow`] with [`Fp12::cyclotomic_square`], for cyclotomic
+    /// subgroup elements only
+    pub fn cyclotomic_pow(&self, exp: &[u64]) -> Self {
+        let mut acc = Self::one();
+        for limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = acc.cyclotomic_square();
+                if (limb >> bit) & 1 == 1 {
+                    acc *= *self;
+                }
+            }
+        }
+        acc
+    }
+}
+
+/// Clone/Copy/Eq/Debug/assign-op boilerplate shared by the tower types
+macro_rules! tower_boilerplate {
+    ($ty:ident, $($field:ident),+) => {
+        impl<T: TowerConfig<N>, const N: usize> Clone for $ty<T, N> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> Copy for $ty<T, N> {}
+
+        impl<T: TowerConfig<N>, const N: usize> PartialEq for $ty<T, N> {
+            fn eq(&self, other: &Self) -> bool {
+                $(self.$field == other.$field)&&+
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> Eq for $ty<T, N> {}
+
+        impl<T: TowerConfig<N>, const N: usize> Default for $ty<T, N> {
+            fn default() -> Self {
+                <Self as Field>::zero()
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> fmt::Debug for $ty<T, N> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($ty))
+                    $(.field(stringify!($field), &self.$field))+
+                    .finish()
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> Add for $ty<T, N> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                $ty { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> Sub for $ty<T, N> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                $ty { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> Neg for $ty<T, N> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $ty { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> AddAssign for $ty<T, N> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> SubAssign for $ty<T, N> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T: TowerConfig<N>, const N: usize> MulAssign for $ty<T, N> {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+    };
+}
+
+tower_boilerplate!(Fp2, c0, c1);
+tower_boilerplate!(Fp6, c0, c1, c2);
+tower_boilerplate!(Fp12, c0, c1);
+
+impl<T: TowerConfig<N>, const N: usize> Mul for Fp2<T, N> {
+    type Output = Self;
+
+    /// Karatsuba: three base multiplications
+    fn mul(self, rhs: Self) -> Self {
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        let cross = (self.c0 + self.c1) * (rhs.c0 + rhs.c1);
+        Fp2::new(aa - bb, cross - aa - bb)
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Mul for Fp6<T, N> {
+    type Output = Self;
+
+    /// Karatsuba over the cubic extension: six Fp2 multiplications
+    fn mul(self, rhs: Self) -> Self {
+        let (a0, a1, a2) = (self.c0, self.c1, self.c2);
+        let (b0, b1, b2) = (rhs.c0, rhs.c1, rhs.c2);
+        let t0 = a0 * b0;
+        let t1 = a1 * b1;
+        let t2 = a2 * b2;
+        let c0 = ((a1 + a2) * (b1 + b2) - t1 - t2).mul_by_xi() + t0;
+        let c1 = (a0 + a1) * (b0 + b1) - t0 - t1 + t2.mul_by_xi();
+        let c2 = (a0 + a2) * (b0 + b2) - t0 - t2 + t1;
+        Fp6::new(c0, c1, c2)
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Mul for Fp12<T, N> {
+    type Output = Self;
+
+    /// Karatsuba over the quadratic extension: three Fp6 multiplications
+    fn mul(self, rhs: Self) -> Self {
+        let aa = self.c0 * rhs.c0;
+        let bb = self.c1 * rhs.c1;
+        let cross = (self.c0 + self.c1) * (rhs.c0 + rhs.c1);
+        Fp12::new(aa + bb.mul_by_v(), cross - aa - bb)
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Field for Fp2<T, N> {
+    fn zero() -> Self {
+        Fp2::new(Fp::zero(), Fp::zero())
+    }
+
+    fn one() -> Self {
+        Fp2::new(Fp::one(), Fp::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    /// `(a + bu)^2 = (a + b)(a - b) + 2ab u`
+    fn square(&self) -> Self {
+        let ab = self.c0 * self.c1;
+        Fp2::new((self.c0 + self.c1) * (self.c0 - self.c1), ab.double())
+    }
+
+    fn double(&self) -> Self {
+        Fp2::new(self.c0.double(), self.c1.double())
+    }
+
+    /// `(a - bu) / (a^2 + b^2)`
+    fn inverse(&self) -> Option<Self> {
+        let norm = self.c0.square() + self.c1.square();
+        let inv = norm.inverse()?;
+        Some(Fp2::new(self.c0 * inv, -(self.c1 * inv)))
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> ConditionallySelectable for Fp2<T, N> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Fp2::new(
+            Fp::conditional_select(&a.c0, &b.c0, choice),
+            Fp::conditional_select(&a.c1, &b.c1, choice),
+        )
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> ConstantTimeField for Fp2<T, N> {
+    fn add_ct(&self, rhs: &Self) -> Self {
+        Fp2::new(self.c0.add_ct(&rhs.c0), self.c1.add_ct(&rhs.c1))
+    }
+
+    fn sub_ct(&self, rhs: &Self) -> Self {
+        Fp2::new(self.c0.sub_ct(&rhs.c0), self.c1.sub_ct(&rhs.c1))
+    }
+
+    /// Karatsuba, as for `*`
+    fn mul_ct(&self, rhs: &Self) -> Self {
+        let aa = self.c0.mul_ct(&rhs.c0);
+        let bb = self.c1.mul_ct(&rhs.c1);
+        let cross = self.c0.add_ct(&self.c1).mul_ct(&rhs.c0.add_ct(&rhs.c1));
+        Fp2::new(aa.sub_ct(&bb), cross.sub_ct(&aa).sub_ct(&bb))
+    }
+
+    /// `(a - bu) / (a^2 + b^2)` with a Fermat inversion of the norm
+    fn invert_ct(&self) -> Self {
+        let norm = self.c0.mul_ct(&self.c0).add_ct(&self.c1.mul_ct(&self.c1));
+        let inv = ConstantTimeField::invert_ct(&norm);
+        Fp2::new(
+            self.c0.mul_ct(&inv),
+            Fp::zero().sub_ct(&self.c1.mul_ct(&inv)),
+        )
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Field for Fp6<T, N> {
+    fn zero() -> Self {
+        Fp6::new(Fp2::zero(), Fp2::zero(), Fp2::zero())
+    }
+
+    fn one() -> Self {
+        Fp6::new(Fp2::one(), Fp2::zero(), Fp2::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero() && self.c2.is_zero()
+    }
+
+    fn square(&self) -> Self {
+        *self * *self
+    }
+
+    fn double(&self) -> Self {
+        Fp6::new(self.c0.double(), self.c1.double(), self.c2.double())
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let (a0, a1, a2) = (self.c0, self.c1, self.c2);
+        let t0 = a0.square() - (a1 * a2).mul_by_xi();
+        let t1 = a2.square().mul_by_xi() - a0 * a1;
+        let t2 = a1.square() - a0 * a2;
+        let norm = a0 * t0 + ((a2 * t1) + (a1 * t2)).mul_by_xi();
+        let inv = norm.inverse()?;
+        Some(Fp6::new(t0 * inv, t1 * inv, t2 * inv))
+    }
+}
+
+impl<T: TowerConfig<N>, const N: usize> Field for Fp12<T, N> {
+    fn zero() -> Self {
+        Fp12::new(Fp6::zero(), Fp6::zero())
+    }
+
+    fn one() -> Self {
+        Fp12::new(Fp6::one(), Fp6::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    /// Complex squaring: two Fp6 multiplications
+    fn square(&self) -> Self {
+        let ab = self.c0 * self.c1;
+        let c0 = (self.c0 + self.c1) * (self.c0 + self.c1.mul_by_v()) - ab - ab.mul_by_v();
+        Fp12::new(c0, ab.double())
+    }
+
+    fn double(&self) -> Self {
+        Fp12::new(self.c0.double(), self.c1.double())
+    }
+
+    /// `(a - bw) / (a^2 - b^2 v)`
+    fn inverse(&self) -> Option<Self> {
+        let norm = self.c0.square() - self.c1.square().mul_by_v();
+        let inv = norm.inverse()?;
+        Some(Fp12::new(self.c0 * inv, -(self.c1 * inv)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls12_381::Bls12Tower;
+
+    type F2 = Fp2<Bls12Tower, 6>;
+    type F12 = Fp12<Bls12Tower, 6>;
+
+    fn sample12(seed: u64) -> F12 {
+        let f2 = |k: u64| F2::new(Fp::from_u64(seed * 31 + k), Fp::from_u64(seed ^ (k << 7)));
+        F12::from_w_coeffs(std::array::from_fn(|i| f2(i as u64 + 1)))
+    }
+
+    #[test]
+    fn test_fp2_arithmetic() {
+        // u^2 = -1
+        let u = F2::new(Fp::zero(), Fp::one());
+        assert_eq!(u * u, -F2::one());
+        let a = F2::new(Fp::from_u64(3), Fp::from_u64(5));
+        assert_eq!(a.square(), a * a);
+        assert_eq!(a * a.inverse().unwrap(), F2::one());
+        assert_eq!(a.mul_by_xi(), a * F2::xi());
+        assert!(F2::zero().inverse().is_none());
+
+        let b = -F2::new(Fp::from_u64(7), Fp::from_u64(11)).square();
+        assert_eq!(a.add_ct(&b), a + b);
+        assert_eq!(a.sub_ct(&b), a - b);
+        assert_eq!(b.sub_ct(&a), b - a);
+        assert_eq!(a.mul_ct(&b), a * b);
+        assert_eq!(b.invert_ct(), b.inverse().unwrap());
+        assert_eq!(F2::zero().invert_ct(), F2::zero());
+    }
+
+    #[test]
+    fn test_fp2_sqrt() {
+        let u = F2::new(Fp::zero(), Fp::one());
+        for a in [
+            F2::new(Fp::from_u64(3), Fp::from_u64(5)),
+            F2::from_base(Fp::from_u64(7)),
+            u,
+            -F2::one(),
+        ] {
+            let square = a.square();
+            let root = square.sqrt().unwrap();
+            assert!(root == a || root == -a, "{a:?}");
+        }
+        assert_eq!(F2::zero().sqrt(), Some(F2::zero()));
+        // ξ = 1 + u is a non-residue
+        assert_eq!(F2::xi().sqrt(), None);
+    }
+
+    #[test]
+    fn test_fp12_field_laws() {
+        let a = sample12(1);
+        let b = sample12(2);
+        let c = sample12(3);
+        assert_eq!(a * b, b * a);
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!(a.square(), a * a);
+        assert_eq!(a * a.inverse().unwrap(), F12::one());
+        let a6 = a.c0;
+        assert_eq!(a6 * a6.inverse().unwrap(), Fp6::one());
+    }
+
+    #[test]
+    fn test_frobenius_is_p_power() {
+        let a = sample12(4);
+        // x^(p^12) = x, and x^p matches exponentiation by p
+        let mut f = a;
+        for _ in 0..12 {
+            f = f.frobenius();
+        }
+        assert_eq!(f, a);
+        let p = <Bls12Tower as TowerConfig<6>>::Base::MODULUS;
+        assert_eq!(a.frobenius(), a.pow(&p));
+        assert_eq!(a.c0.frobenius(), pow_limbs(&a.c0, &p));
+        // p^6 Frobenius is conjugation
+        let mut f6 = a;
+        for _ in 0..6 {
+            f6 = f6.frobenius();
+        }
+        assert_eq!(f6, a.conjugate());
+    }
+}
@@ -0,0 +1,225 @@
+//! Fiat-Shamir transcripts over BLAKE2b-512
+//!
+//! Every append absorbs `len(label) || label || len(data) || data` (`u32` and
+//! `u64` big-endian lengths) into a running BLAKE2b-512 state, so labels
+//! separate domains and no two append sequences share an encoding. The
+//! transcript label itself is absorbed first under the tag
+//! `"zk-accelerate transcript"`.
+//!
+//! A challenge absorbs its label and requested length, then expands the
+//! digest of the state so far as `BLAKE2b-512(seed || u32 counter)` blocks.
+//! Since the request itself is absorbed, repeating a challenge label yields
+//! a fresh value.
+
+use blake2::{Blake2b512, Digest};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{decode_g1, parse_fr, Fr};
+
+const DOMAIN_TAG: &[u8] = b"zk-accelerate transcript";
+
+/// Bytes hashed into a scalar challenge, reduced modulo `r`
+const SCALAR_CHALLENGE_BYTES: u32 = 64;
+
+/// Largest `challenge_bytes` request (1 MiB)
+pub const MAX_CHALLENGE_BYTES: u32 = 1 << 20;
+
+/// Hash-based Fiat-Shamir transcript
+///
+/// Scalars are 32-byte big-endian BN254 scalar field elements and points
+/// 64-byte uncompressed BN254 G1 points; both are validated before being
+/// absorbed.
+#[napi]
+#[derive(Clone)]
+pub struct Transcript {
+    state: Blake2b512,
+}
+
+impl Transcript {
+    fn absorb(&mut self, tag: &[u8], data: &[u8]) {
+        self.state.update((tag.len() as u32).to_be_bytes());
+        self.state.update(tag);
+        self.state.update((data.len() as u64).to_be_bytes());
+        self.state.update(data);
+    }
+
+    /// Challenge scalar as a field element
+    pub fn challenge_fr(&mut self, label: &str) -> Fr {
+        let radix = Fr::from_u64(256);
+        self.challenge(label, SCALAR_CHALLENGE_BYTES)
+            .iter()
+            .fold(Fr::zero(), |acc, &b| acc * radix + Fr::from_u64(b as u64))
+    }
+
+    fn challenge(&mut self, label: &str, n_bytes: u32) -> Vec<u8> {
+        self.absorb(label.as_bytes(), &n_bytes.to_be_bytes());
+        let seed = self.state.clone().finalize();
+        let mut out = Vec::with_capacity(n_bytes as usize);
+        let mut counter = 0u32;
+        while out.len() < n_bytes as usize {
+            let mut block = Blake2b512::new();
+            block.update(seed);
+            block.update(counter.to_be_bytes());
+            out.extend_from_slice(&block.finalize());
+            counter += 1;
+        }
+        out.truncate(n_bytes as usize);
+        out
+    }
+}
+
+#[napi]
+impl Transcript {
+    /// Start a transcript for the protocol named by `label`
+    #[napi(constructor)]
+    pub fn new(label: String) -> Self {
+        let mut transcript = Transcript {
+            state: Blake2b512::new(),
+        };
+        transcript.absorb(DOMAIN_TAG, label.as_bytes());
+        transcript
+    }
+
+    /// Absorb arbitrary bytes
+    #[napi]
+    pub fn append_message(&mut self, label: String, message: Vec<u8>) {
+        self.absorb(label.as_bytes(), &message);
+    }
+
+    /// Absorb a 32-byte big-endian BN254 scalar field element
+    #[napi]
+    pub fn append_scalar(&mut self, label: String, scalar: Vec<u8>) -> Result<()> {
+        parse_fr(&scalar, &label)?;
+        self.absorb(label.as_bytes(), &scalar);
+        Ok(())
+    }
+
+    /// Absorb a 64-byte uncompressed BN254 G1 point
+    #[napi]
+    pub fn append_point(&mut self, label: String, point: Vec<u8>) -> Result<()> {
+        decode_g1(&point, &label)?;
+        self.absorb(label.as_bytes(), &point);
+        Ok(())
+    }
+
+    /// Squeeze a BN254 scalar challenge (32 bytes, big-endian)
+    ///
+    /// 64 challenge bytes are read as a big-endian integer and reduced
+    /// modulo `r`, which keeps the bias below 2^-250.
+    #[napi]
+    pub fn challenge_scalar(&mut self, label: String) -> Vec<u8> {
+        self.challenge_fr(&label).to_be_bytes()
+    }
+
+    /// Squeeze `n_bytes` challenge bytes, at most [`MAX_CHALLENGE_BYTES`]
+    #[napi]
+    pub fn challenge_bytes(&mut self, label: String, n_bytes: u32) -> Result<Vec<u8>> {
+        if !(1..=MAX_CHALLENGE_BYTES).contains(&n_bytes) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("n_bytes must be between 1 and {MAX_CHALLENGE_BYTES}, got {n_bytes}"),
+            ));
+        }
+        Ok(self.challenge(&label, n_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn example() -> Transcript {
+        let mut t = Transcript::new("example".to_string());
+        t.append_message("msg".to_string(), b"hello".to_vec());
+        t.append_scalar("x".to_string(), Fr::from_u64(5).to_be_bytes())
+            .unwrap();
+        let g = crate::bn254::encode_g1(&crate::bn254::g1_generator());
+        t.append_point("g".to_string(), g).unwrap();
+        t
+    }
+
+    #[test]
+    fn test_python_reference() {
+        // Same framing with Python's hashlib.blake2b
+        let mut t = example();
+        assert_eq!(
+            t.challenge_scalar("alpha".to_string()),
+            hex("2256e35446b115d9af9d97506363043abd212d721350e28463be3ebe54bd37d8")
+        );
+        assert_eq!(
+            t.challenge_bytes("beta".to_string(), 80).unwrap(),
+            hex(concat!(
+                "8a55e4f85039d285ee63a8349d02f7e41bed389c05327b1eaac532f47714a1be",
+                "d11968fe6d03a6dfd045719381fc9e27ef5816561af9a600fdb4484cec9057df",
+                "ba4a6974f8f30db5dfbec4e6ef7e8013"
+            ))
+        );
+        // Repeating a label still gives a fresh challenge
+        assert_eq!(
+            t.challenge_scalar("alpha".to_string()),
+            hex("0e57f6bbf1f3b3fc71e35c76208cd184b6872500f752cca8a48bb18f9c1c255c")
+        );
+    }
+
+    #[test]
+    fn test_deterministic_and_order_sensitive() {
+        let (mut a, mut b) = (example(), example());
+        assert_eq!(
+            a.challenge_scalar("c".to_string()),
+            b.challenge_scalar("c".to_string())
+        );
+
+        let mut swapped = Transcript::new("example".to_string());
+        swapped.append_message("msg".to_string(), b"hell".to_vec());
+        swapped.append_message("o".to_string(), vec![]);
+        let mut plain = Transcript::new("example".to_string());
+        plain.append_message("msg".to_string(), b"hello".to_vec());
+        assert_ne!(
+            swapped.challenge_bytes("c".to_string(), 32).unwrap(),
+            plain.challenge_bytes("c".to_string(), 32).unwrap()
+        );
+
+        let mut other = Transcript::new("other".to_string());
+        let mut same = Transcript::new("example".to_string());
+        assert_ne!(
+            other.challenge_bytes("c".to_string(), 16).unwrap(),
+            same.challenge_bytes("c".to_string(), 16).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_inputs() {
+        let mut t = Transcript::new("t".to_string());
+        assert!(t.append_scalar("s".to_string(), vec![0xff; 32]).is_err());
+        assert!(t.append_point("p".to_string(), vec![1; 64]).is_err());
+        assert!(t.challenge_bytes("c".to_string(), 0).is_err());
+        let err = t.challenge_bytes("c".to_string(), u32::MAX).unwrap_err();
+        assert_eq!(
+            err.reason,
+            format!("n_bytes must be between 1 and 1048576, got {}", u32::MAX)
+        );
+        // Rejected requests leave the transcript untouched
+        let mut fresh = Transcript::new("t".to_string());
+        assert_eq!(
+            t.challenge_bytes("c".to_string(), MAX_CHALLENGE_BYTES)
+                .unwrap()
+                .len(),
+            1 << 20
+        );
+        fresh
+            .challenge_bytes("c".to_string(), MAX_CHALLENGE_BYTES)
+            .unwrap();
+        assert_eq!(
+            t.challenge_bytes("d".to_string(), 8).unwrap(),
+            fresh.challenge_bytes("d".to_string(), 8).unwrap()
+        );
+    }
+}
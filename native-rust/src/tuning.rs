@@ -0,0 +1,281 @@
+//! Tuning recommendations derived from detected hardware
+//!
+//! The one place tuning knowledge is kept: the recommendations reuse the
+//! thresholds the kernels themselves dispatch on, plus a per-chip table for
+//! what differs between Apple Silicon generations. A pure
+//! [`recommend`] maps a [`TuningInput`] to a config so the tables can be
+//! tested without the hardware.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::RustHardwareCapabilities;
+
+/// Tasks `get_recommended_config` knows about
+pub const TASKS: [&str; 3] = ["msm-bn254", "ntt-bn254", "field-mul-bn254"];
+
+/// Smallest field multiplication batch for which the GPU round trip pays off
+const METAL_MIN_BATCH: usize = 1 << 14;
+
+/// Chip families whose Metal break-even differs from [`METAL_MIN_BATCH`]
+///
+/// M1's smaller, older GPU needs twice the batch to break even.
+const METAL_MIN_BATCH_BY_CHIP: &[(&str, usize)] = &[("M1", 1 << 15)];
+
+/// Bytes per MSM bucket: a BN254 G1 point in Jacobian coordinates
+const MSM_BUCKET_BYTES: usize = 96;
+
+/// Smallest batch worth sending to Metal on the given Apple Silicon generation
+pub fn metal_min_batch(chip_family: &str) -> usize {
+    METAL_MIN_BATCH_BY_CHIP
+        .iter()
+        .find(|(family, _)| *family == chip_family)
+        .map_or(METAL_MIN_BATCH, |&(_, batch)| batch)
+}
+
+/// Hardware facts the recommendations depend on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuningInput {
+    pub chip_family: String,
+    pub performance_cores: u32,
+    pub l2_cache_bytes: u64,
+    pub has_metal: bool,
+    pub has_amx: bool,
+}
+
+impl From<&RustHardwareCapabilities> for TuningInput {
+    fn from(caps: &RustHardwareCapabilities) -> Self {
+        TuningInput {
+            chip_family: caps.chip_family.clone(),
+            performance_cores: caps.performance_cores,
+            l2_cache_bytes: caps.l2_cache_bytes.max(0) as u64,
+            has_metal: caps.has_metal,
+            has_amx: caps.has_amx,
+        }
+    }
+}
+
+/// Suggested settings for one task and input size
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecommendedConfig {
+    /// Worker threads worth using; 1 means run on the calling thread
+    pub num_threads: u32,
+    /// Pippenger window size in bits, 0 for tasks without windows
+    pub window_bits: u32,
+    /// Whether to dispatch to the GPU
+    pub use_gpu: bool,
+    /// Elements per worker chunk
+    pub chunk_size: u32,
+}
+
+/// Workers for `size` elements when chunks shorter than `min_chunk` are not
+/// worth a thread
+fn cpu_threads(size: usize, min_chunk: usize, cores: u32) -> u32 {
+    if size < min_chunk {
+        return 1;
+    }
+    (size / min_chunk).clamp(1, cores.max(1) as usize) as u32
+}
+
+/// Recommendation for `task` on `size` elements with the given hardware
+pub fn recommend(task: &str, size: u32, hw: &TuningInput) -> Result<RecommendedConfig> {
+    let n = size as usize;
+    let config = match task {
+        "msm-bn254" => {
+            let c = crate::msm::window_bits_within(n, MSM_BUCKET_BYTES, hw.l2_cache_bytes);
+            // Windows are the unit of parallelism
+            let windows = crate::msm::SCALAR_BITS.div_ceil(c) as u32;
+            RecommendedConfig {
+                num_threads: windows.min(hw.performance_cores.max(1)),
+                window_bits: c as u32,
+                use_gpu: false,
+                chunk_size: size,
+            }
+        }
+        "ntt-bn254" => {
+            crate::ntt::log2_size(n)?;
+            let threshold = crate::ntt::PARALLEL_THRESHOLD;
+            let num_threads = cpu_threads(n, threshold, hw.performance_cores);
+            RecommendedConfig {
+                num_threads,
+                window_bits: 0,
+                use_gpu: false,
+                chunk_size: n.div_ceil(num_threads as usize) as u32,
+            }
+        }
+        "field-mul-bn254" => {
+            let use_gpu = hw.has_metal && hw.has_amx && n >= metal_min_batch(&hw.chip_family);
+            let threshold = crate::bn254::MUL_BATCH_PARALLEL_THRESHOLD;
+            let num_threads = if use_gpu {
+                1
+            } else {
+                cpu_threads(n, threshold, hw.performance_cores)
+            };
+            RecommendedConfig {
+                num_threads,
+                window_bits: 0,
+                use_gpu,
+                chunk_size: n.div_ceil(num_threads as usize) as u32,
+            }
+        }
+        _ => {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "unknown task {task:?}; expected one of {}",
+                    TASKS.join(", ")
+                ),
+            ))
+        }
+    };
+    Ok(config)
+}
+
+/// Suggested thread count, MSM window, GPU use and chunk size for a task
+///
+/// `task` is one of "msm-bn254", "ntt-bn254" or "field-mul-bn254" and
+/// `size` the number of input elements (points, coefficients or products).
+#[napi]
+pub fn get_recommended_config(task: String, size: u32) -> Result<RecommendedConfig> {
+    recommend(
+        &task,
+        size,
+        &TuningInput::from(&crate::detect_rust_capabilities()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apple(family: &str, performance_cores: u32, l2_cache_bytes: u64) -> TuningInput {
+        TuningInput {
+            chip_family: family.to_string(),
+            performance_cores,
+            l2_cache_bytes,
+            has_metal: true,
+            has_amx: true,
+        }
+    }
+
+    fn linux(performance_cores: u32, l2_cache_bytes: u64) -> TuningInput {
+        TuningInput {
+            performance_cores,
+            l2_cache_bytes,
+            ..Default::default()
+        }
+    }
+
+    fn config(
+        num_threads: u32,
+        window_bits: u32,
+        use_gpu: bool,
+        chunk_size: u32,
+    ) -> RecommendedConfig {
+        RecommendedConfig {
+            num_threads,
+            window_bits,
+            use_gpu,
+            chunk_size,
+        }
+    }
+
+    #[test]
+    fn test_msm_table() {
+        let cases = [
+            (
+                apple("M2", 8, 16 << 20),
+                1 << 20,
+                config(8, 17, false, 1 << 20),
+            ),
+            (
+                apple("M1", 4, 12 << 20),
+                1 << 16,
+                config(4, 14, false, 1 << 16),
+            ),
+            (linux(16, 2 << 20), 1 << 20, config(16, 14, false, 1 << 20)),
+            (linux(64, 1 << 20), 1 << 20, config(20, 13, false, 1 << 20)),
+            (linux(8, 0), 1 << 10, config(8, 8, false, 1 << 10)),
+            (linux(8, 0), 1, config(8, 1, false, 1)),
+        ];
+        for (hw, size, expected) in cases {
+            assert_eq!(
+                recommend("msm-bn254", size, &hw).unwrap(),
+                expected,
+                "{hw:?} {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ntt_table() {
+        let cases = [
+            (
+                apple("M3", 6, 16 << 20),
+                1 << 20,
+                config(6, 0, false, 174763),
+            ),
+            (linux(16, 2 << 20), 1 << 14, config(4, 0, false, 1 << 12)),
+            (linux(16, 2 << 20), 1 << 10, config(1, 0, false, 1 << 10)),
+        ];
+        for (hw, size, expected) in cases {
+            assert_eq!(
+                recommend("ntt-bn254", size, &hw).unwrap(),
+                expected,
+                "{hw:?} {size}"
+            );
+        }
+        assert!(recommend("ntt-bn254", 1000, &linux(4, 0)).is_err());
+    }
+
+    #[test]
+    fn test_field_mul_table() {
+        let cases = [
+            (
+                apple("M2", 8, 16 << 20),
+                1 << 14,
+                config(1, 0, true, 1 << 14),
+            ),
+            (
+                apple("M1", 4, 12 << 20),
+                1 << 14,
+                config(4, 0, false, 1 << 12),
+            ),
+            (
+                apple("M1", 4, 12 << 20),
+                1 << 15,
+                config(1, 0, true, 1 << 15),
+            ),
+            (
+                apple("M4", 10, 16 << 20),
+                1 << 13,
+                config(2, 0, false, 1 << 12),
+            ),
+            (linux(16, 2 << 20), 1 << 20, config(16, 0, false, 1 << 16)),
+            (linux(16, 2 << 20), 100, config(1, 0, false, 100)),
+        ];
+        for (hw, size, expected) in cases {
+            assert_eq!(
+                recommend("field-mul-bn254", size, &hw).unwrap(),
+                expected,
+                "{hw:?} {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_metal_min_batch() {
+        assert_eq!(metal_min_batch("M1"), 1 << 15);
+        assert_eq!(metal_min_batch("M4"), METAL_MIN_BATCH);
+        assert_eq!(metal_min_batch(""), METAL_MIN_BATCH);
+    }
+
+    #[test]
+    fn test_unknown_task() {
+        let err = get_recommended_config("ntt-goldilocks".to_string(), 1024).unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.contains("msm-bn254"));
+        assert!(get_recommended_config("msm-bn254".to_string(), 1 << 12).is_ok());
+    }
+}
@@ -0,0 +1,261 @@
+//! Range checks for untrusted packed field elements
+//!
+//! Proofs and public inputs from untrusted clients must be checked to be
+//! canonical before anything reduces or trusts them. `validate_field_elements`
+//! checks a whole packed buffer in one call, in the encoding of
+//! `random_field_elements`: little-endian, at the field's element size.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::field::ELEMENTWISE_PARALLEL_THRESHOLD;
+use crate::random::FieldName;
+
+/// Outcome of [`validate_field_elements`] and the subgroup checks of
+/// [`crate::ec`]
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationResult {
+    /// Whether every element passed: is below the modulus, or is a point of
+    /// the prime-order subgroup
+    pub valid: bool,
+    /// Index of the first failing element, absent when valid
+    pub first_bad_index: Option<u32>,
+    /// Number of failing elements, absent when valid or when the check
+    /// stopped at the first failure
+    pub count_bad: Option<u32>,
+}
+
+/// Whether the little-endian `element` is below the little-endian `modulus`
+pub(crate) fn below(element: &[u8], modulus: &[u8]) -> bool {
+    element.iter().rev().lt(modulus.iter().rev())
+}
+
+/// First index and number of the elements of a packed buffer of
+/// `size`-byte elements that `is_bad` flags
+///
+/// Chunks of at least `min_len` elements are scanned on worker threads.
+/// With `stop_at_first` set, workers skip every element after the first
+/// failure any of them has found: the index is still the first, but the
+/// count only covers what was scanned.
+pub(crate) fn scan(
+    data: &[u8],
+    size: usize,
+    min_len: usize,
+    stop_at_first: bool,
+    is_bad: impl Fn(&[u8]) -> bool + Sync,
+) -> (Option<usize>, usize) {
+    let first_seen = AtomicUsize::new(usize::MAX);
+    let count = data.len() / size;
+    let per = count.div_ceil(crate::parallel::num_threads()).max(min_len);
+    let chunks: Vec<(usize, &[u8])> = data
+        .chunks(per * size)
+        .enumerate()
+        .map(|(k, chunk)| (k * per, chunk))
+        .collect();
+    crate::parallel::par_map(&chunks, |&(start, chunk)| {
+        let mut first = None;
+        let mut count = 0;
+        for (i, element) in chunk.chunks_exact(size).enumerate() {
+            let index = start + i;
+            if stop_at_first && index > first_seen.load(Ordering::Relaxed) {
+                break;
+            }
+            if is_bad(element) {
+                first.get_or_insert(index);
+                count += 1;
+                if stop_at_first {
+                    first_seen.fetch_min(index, Ordering::Relaxed);
+                }
+            }
+        }
+        (first, count)
+    })
+    .into_iter()
+    .fold((None, 0), |(first, count), (chunk_first, chunk_count)| {
+        (first.or(chunk_first), count + chunk_count)
+    })
+}
+
+impl ValidationResult {
+    /// Result of a [`scan`], leaving out the partial count of one that
+    /// stopped at the first failure
+    pub(crate) fn from_scan((first, count): (Option<usize>, usize), stop_at_first: bool) -> Self {
+        match first {
+            None => ValidationResult {
+                valid: true,
+                first_bad_index: None,
+                count_bad: None,
+            },
+            Some(index) => ValidationResult {
+                valid: false,
+                first_bad_index: Some(index as u32),
+                count_bad: (!stop_at_first).then_some(count as u32),
+            },
+        }
+    }
+}
+
+/// Check that every element of a packed little-endian buffer is below the
+/// modulus of `field`
+pub fn validate(data: &[u8], field: FieldName) -> Result<ValidationResult> {
+    let modulus = field.modulus_le_bytes();
+    if !data.len().is_multiple_of(modulus.len()) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "data: length {} is not a multiple of {} bytes",
+                data.len(),
+                modulus.len()
+            ),
+        ));
+    }
+    let scanned = scan(
+        data,
+        modulus.len(),
+        ELEMENTWISE_PARALLEL_THRESHOLD,
+        false,
+        |element| !below(element, &modulus),
+    );
+    Ok(ValidationResult::from_scan(scanned, false))
+}
+
+/// Fail unless `data` is whole elements of `field`, all below the modulus,
+/// naming the first offending element as `name[i]`
+pub(crate) fn check_canonical(data: &[u8], field: FieldName, name: &str) -> Result<()> {
+    let size = field.element_bytes();
+    if !data.len().is_multiple_of(size) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: length {} is not a multiple of {size} bytes",
+                data.len()
+            ),
+        ));
+    }
+    if let Some(i) = validate(data, field)?.first_bad_index {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}[{i}]: value is not below the {} modulus",
+                field.name()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that every element of `data` is canonical, i.e. below the modulus
+///
+/// `field` is any name `random_field_elements` accepts, and `data` holds
+/// packed little-endian elements of that field's size. Elements `>= p` are
+/// not an error: the result reports the first one's index and how many
+/// there are. A length that is not a multiple of the element size throws,
+/// as does an unknown field. Large buffers are scanned in parallel.
+#[napi]
+pub fn validate_field_elements(data: Buffer, field: String) -> Result<ValidationResult> {
+    validate(&data, FieldName::parse(&field)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_elements, SEED_BYTES};
+
+    /// `p` and `p - 1` as packed little-endian elements
+    fn p_and_p_minus_one(field: FieldName) -> (Vec<u8>, Vec<u8>) {
+        let p = field.modulus_le_bytes();
+        let mut p_minus_one = p.clone();
+        // p is odd, so only the low byte changes
+        p_minus_one[0] -= 1;
+        (p, p_minus_one)
+    }
+
+    #[test]
+    fn test_boundaries_for_every_field() {
+        for (name, field) in FieldName::ALL {
+            let size = field.element_bytes();
+            let (p, p_minus_one) = p_and_p_minus_one(field);
+            assert_eq!(p.len(), size, "{name}");
+            let valid = validate(&p_minus_one, field).unwrap();
+            assert!(valid.valid, "{name}");
+            assert_eq!((valid.first_bad_index, valid.count_bad), (None, None));
+
+            let random = random_elements(field, 20, Some(&[4; SEED_BYTES])).unwrap();
+            assert!(validate(&random, field).unwrap().valid, "{name}");
+            let data = [random.clone(), p.clone(), p_minus_one, vec![0xff; size], p].concat();
+            assert_eq!(
+                validate(&data, field).unwrap(),
+                ValidationResult {
+                    valid: false,
+                    first_bad_index: Some(20),
+                    count_bad: Some(3),
+                },
+                "{name}"
+            );
+            assert!(validate(&[], field).unwrap().valid);
+
+            let err = validate(&random[1..], field).unwrap_err();
+            assert_eq!(err.status, Status::InvalidArg);
+            assert_eq!(
+                err.reason,
+                format!(
+                    "data: length {} is not a multiple of {size} bytes",
+                    random.len() - 1
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_garbage_across_workers() {
+        let field = FieldName::Bn254Fr;
+        let n = 5 * ELEMENTWISE_PARALLEL_THRESHOLD + 3;
+        let mut data = random_elements(field, n, Some(&[2; SEED_BYTES])).unwrap();
+        assert!(validate(&data, field).unwrap().valid);
+        let bad = [
+            n - 1,
+            3 * ELEMENTWISE_PARALLEL_THRESHOLD + 1,
+            2 * ELEMENTWISE_PARALLEL_THRESHOLD,
+        ];
+        for i in bad {
+            data[i * 32..(i + 1) * 32].fill(0xff);
+        }
+        let result = validate(&data, field).unwrap();
+        assert_eq!(
+            result.first_bad_index,
+            Some(2 * ELEMENTWISE_PARALLEL_THRESHOLD as u32)
+        );
+        assert_eq!(result.count_bad, Some(3));
+        assert!(!validate(&vec![0xff; 32 * n], field).unwrap().valid);
+    }
+
+    #[test]
+    fn test_scan_stops_at_first() {
+        // One byte per element, bad when 1; a single chunk
+        let data: Vec<u8> = (0..100).map(|i| (i == 7 || i == 50) as u8).collect();
+        let checked = AtomicUsize::new(0);
+        let is_bad = |e: &[u8]| {
+            checked.fetch_add(1, Ordering::Relaxed);
+            e[0] == 1
+        };
+        assert_eq!(scan(&data, 1, 100, false, is_bad), (Some(7), 2));
+        assert_eq!(checked.swap(0, Ordering::Relaxed), 100);
+        assert_eq!(scan(&data, 1, 100, true, is_bad), (Some(7), 1));
+        assert_eq!(checked.load(Ordering::Relaxed), 8);
+
+        // Across chunks the first failure is still found
+        let stopped = ValidationResult::from_scan(scan(&data, 1, 1, true, is_bad), true);
+        assert_eq!(
+            stopped,
+            ValidationResult {
+                valid: false,
+                first_bad_index: Some(7),
+                count_bad: None,
+            }
+        );
+    }
+}
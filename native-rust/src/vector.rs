@@ -0,0 +1,822 @@
+//! Fused arithmetic over packed field element vectors
+//!
+//! `fr_linear_combination` computes `Σ c[i] · v[i]` in one pass over the
+//! output: each worker takes a range of output elements and accumulates
+//! every vector's contribution to it in a small cache-resident block before
+//! writing it once. `fr_axpy_in_place` is the `y += a · x` special case,
+//! written back into `y`. `fr_hadamard` multiplies element-wise, and the
+//! `_into` functions write element-wise products, sums and scalings into a
+//! caller-provided Buffer so a pipeline can reuse its buffers. Vectors use
+//! the encoding of `random_field_elements`: canonical, little-endian, at the
+//! field's element size.
+//!
+//! Coefficients are converted to Montgomery form once per call, so that
+//! one Montgomery multiplication with a canonical vector element gives the
+//! canonical product. Coefficients of 0 skip their vector and coefficients
+//! of 1 add it without multiplying.
+//!
+//! An output Buffer may be the same memory as any of its inputs, as in
+//! `fr_add_into(a, a, b)`: each element is read before it is written. An
+//! input that only partially overlaps the output throws.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::montgomery::{Fp, MontConfig};
+use crate::random::FieldName;
+use crate::validate::check_canonical;
+
+/// Below this many output bytes the combination runs on the calling thread
+const PARALLEL_THRESHOLD_BYTES: usize = 1 << 17;
+
+/// Output elements accumulated together before moving to the next block
+const BLOCK: usize = 1 << 10;
+
+/// A canonical element in the representation the combination works on
+trait Element: Copy + Send + Sync + PartialEq {
+    const BYTES: usize;
+    const ZERO: Self;
+
+    fn one() -> Self;
+
+    /// Decode an element already checked to be canonical
+    fn load(bytes: &[u8]) -> Self;
+    fn store(self, out: &mut [u8]);
+    fn add(self, other: Self) -> Self;
+    /// The coefficient `c` prepared for [`Element::scale`]
+    fn prepare(c: Self) -> Self;
+    /// `c · v` for a prepared coefficient
+    fn scale(c: Self, v: Self) -> Self;
+}
+
+/// The canonical limbs of an element, held as if they were Montgomery form
+#[derive(Clone, Copy)]
+struct Raw<C: MontConfig<N>, const N: usize>(Fp<C, N>);
+
+impl<C: MontConfig<N>, const N: usize> PartialEq for Raw<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<C: MontConfig<N>, const N: usize> Element for Raw<C, N> {
+    const BYTES: usize = 8 * N;
+    const ZERO: Self = Raw(Fp::zero());
+
+    fn one() -> Self {
+        let mut limbs = [0; N];
+        limbs[0] = 1;
+        Raw(Fp::from_montgomery_limbs(limbs).expect("1 is below the modulus"))
+    }
+
+    fn load(bytes: &[u8]) -> Self {
+        let limbs = std::array::from_fn(|i| {
+            u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap())
+        });
+        Raw(Fp::from_montgomery_limbs(limbs).expect("checked canonical"))
+    }
+
+    fn store(self, out: &mut [u8]) {
+        for (bytes, limb) in out.chunks_exact_mut(8).zip(self.0.to_montgomery_limbs()) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Raw(self.0 + other.0)
+    }
+
+    fn prepare(c: Self) -> Self {
+        // c · R, so the Montgomery product c · R · v / R is c · v
+        Raw(Fp::from_canonical(c.0.to_montgomery_limbs()).expect("below the modulus"))
+    }
+
+    fn scale(c: Self, v: Self) -> Self {
+        Raw(c.0 * v.0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Goldilocks(u64);
+
+impl Element for Goldilocks {
+    const BYTES: usize = 8;
+    const ZERO: Self = Goldilocks(0);
+
+    fn one() -> Self {
+        Goldilocks(1)
+    }
+
+    fn load(bytes: &[u8]) -> Self {
+        Goldilocks(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn store(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn add(self, other: Self) -> Self {
+        Goldilocks(crate::goldilocks::add(self.0, other.0))
+    }
+
+    fn prepare(c: Self) -> Self {
+        c
+    }
+
+    fn scale(c: Self, v: Self) -> Self {
+        Goldilocks(crate::goldilocks::mul(c.0, v.0))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct BabyBear(u32);
+
+impl Element for BabyBear {
+    const BYTES: usize = 4;
+    const ZERO: Self = BabyBear(0);
+
+    fn one() -> Self {
+        BabyBear(1)
+    }
+
+    fn load(bytes: &[u8]) -> Self {
+        BabyBear(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn store(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn add(self, other: Self) -> Self {
+        BabyBear(crate::babybear::add(self.0, other.0))
+    }
+
+    fn prepare(c: Self) -> Self {
+        BabyBear(crate::babybear::to_montgomery(c.0))
+    }
+
+    fn scale(c: Self, v: Self) -> Self {
+        BabyBear(crate::babybear::mont_mul(c.0, v.0))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct M31(u32);
+
+impl Element for M31 {
+    const BYTES: usize = 4;
+    const ZERO: Self = M31(0);
+
+    fn one() -> Self {
+        M31(1)
+    }
+
+    fn load(bytes: &[u8]) -> Self {
+        M31(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn store(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn add(self, other: Self) -> Self {
+        M31(crate::m31::add(self.0, other.0))
+    }
+
+    fn prepare(c: Self) -> Self {
+        c
+    }
+
+    fn scale(c: Self, v: Self) -> Self {
+        M31(crate::m31::mul(c.0, v.0))
+    }
+}
+
+/// How one vector enters the combination
+#[derive(Clone, Copy)]
+enum Term<E> {
+    Skip,
+    Add,
+    Scale(E),
+}
+
+impl<E: Element> Term<E> {
+    fn new(c: E) -> Self {
+        if c == E::ZERO {
+            Term::Skip
+        } else if c == E::one() {
+            Term::Add
+        } else {
+            Term::Scale(E::prepare(c))
+        }
+    }
+}
+
+/// `out = Σ c[i] · v[i]`, plus the current `out` when `accumulate` is set
+///
+/// Every vector is as long as `out` and already checked canonical, as is
+/// `out` when accumulating.
+fn combine<E: Element>(out: &mut [u8], vectors: &[&[u8]], coefficients: &[u8], accumulate: bool) {
+    let terms: Vec<(Term<E>, &[u8])> = coefficients
+        .chunks_exact(E::BYTES)
+        .map(|c| Term::new(E::load(c)))
+        .zip(vectors.iter().copied())
+        .filter(|(term, _)| !matches!(term, Term::Skip))
+        .collect();
+    let min_len = PARALLEL_THRESHOLD_BYTES;
+    crate::parallel::par_chunks_mut(out, E::BYTES, min_len, |offset, chunk| {
+        let mut acc = [E::ZERO; BLOCK];
+        for (b, block) in chunk.chunks_mut(BLOCK * E::BYTES).enumerate() {
+            let start = offset + b * BLOCK * E::BYTES;
+            let acc = &mut acc[..block.len() / E::BYTES];
+            for (a, bytes) in acc.iter_mut().zip(block.chunks_exact(E::BYTES)) {
+                *a = if accumulate { E::load(bytes) } else { E::ZERO };
+            }
+            for &(term, v) in &terms {
+                let v = v[start..start + block.len()].chunks_exact(E::BYTES);
+                match term {
+                    Term::Add => {
+                        for (a, x) in acc.iter_mut().zip(v) {
+                            *a = a.add(E::load(x));
+                        }
+                    }
+                    Term::Scale(c) => {
+                        for (a, x) in acc.iter_mut().zip(v) {
+                            *a = a.add(E::scale(c, E::load(x)));
+                        }
+                    }
+                    Term::Skip => {}
+                }
+            }
+            for (a, bytes) in acc.iter().zip(block.chunks_exact_mut(E::BYTES)) {
+                a.store(bytes);
+            }
+        }
+    });
+}
+
+/// `$f` instantiated for the [`Element`] type of `field`
+macro_rules! for_field {
+    ($field:expr, $f:ident) => {
+        match $field {
+            FieldName::Bn254Fr => $f::<Raw<crate::bn254::FrConfig, 4>>,
+            FieldName::Bn254Fq => $f::<Raw<crate::bn254::FqConfig, 4>>,
+            FieldName::Bls12_381Fr => $f::<Raw<crate::bls12_381::FrConfig, 4>>,
+            FieldName::Bls12_381Fp => $f::<Raw<crate::bls12_381::FpConfig, 6>>,
+            FieldName::PallasFp => $f::<Raw<crate::pasta::FpConfig, 4>>,
+            FieldName::VestaFp => $f::<Raw<crate::pasta::FqConfig, 4>>,
+            FieldName::Goldilocks => $f::<Goldilocks>,
+            FieldName::BabyBear => $f::<BabyBear>,
+            FieldName::M31 => $f::<M31>,
+        }
+    };
+}
+
+type Combine = fn(&mut [u8], &[&[u8]], &[u8], bool);
+
+/// An element-wise operation of the `_into` functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorOp {
+    Add,
+    Mul,
+}
+
+/// An input of an element-wise operation
+#[derive(Debug, Clone, Copy)]
+pub enum Operand<'a> {
+    /// The output array itself, read before each element is written
+    Output,
+    /// A packed vector as long as the output
+    Vector(&'a [u8]),
+    /// One element used at every position
+    Broadcast(&'a [u8]),
+}
+
+/// `out[i] = op(a[i], b[i])`; inputs are already checked
+fn apply<E: Element>(op: VectorOp, out: &mut [u8], a: Operand, b: Operand) {
+    // A broadcast factor is prepared once rather than per element
+    let factor = match (op, b) {
+        (VectorOp::Mul, Operand::Broadcast(c)) => Some(E::prepare(E::load(c))),
+        _ => None,
+    };
+    let min_len = PARALLEL_THRESHOLD_BYTES;
+    crate::parallel::par_chunks_mut(out, E::BYTES, min_len, |offset, chunk| {
+        for (j, dst) in chunk.chunks_exact_mut(E::BYTES).enumerate() {
+            let at = offset + j * E::BYTES;
+            let load = |operand: Operand, dst: &[u8]| match operand {
+                Operand::Output => E::load(dst),
+                Operand::Vector(v) => E::load(&v[at..at + E::BYTES]),
+                Operand::Broadcast(c) => E::load(c),
+            };
+            let x = load(a, dst);
+            let value = match (op, factor) {
+                (VectorOp::Add, _) => x.add(load(b, dst)),
+                (VectorOp::Mul, Some(c)) => E::scale(c, x),
+                (VectorOp::Mul, None) => E::scale(E::prepare(x), load(b, dst)),
+            };
+            value.store(dst);
+        }
+    });
+}
+
+type Apply = fn(VectorOp, &mut [u8], Operand, Operand);
+
+fn length_mismatch(name: &str, expected: usize, what: &str, got: usize) -> Error {
+    Error::new(
+        Status::InvalidArg,
+        format!("{name}: expected {expected} elements to match {what}, got {got}"),
+    )
+}
+
+/// `Σ coefficients[i] · vectors[i]` over packed little-endian elements
+pub fn linear_combination(
+    vectors: &[&[u8]],
+    coefficients: &[u8],
+    field: FieldName,
+) -> Result<Vec<u8>> {
+    let size = field.element_bytes();
+    check_canonical(coefficients, field, "coefficients")?;
+    if coefficients.len() / size != vectors.len() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "coefficients: expected {} elements, one per vector, got {}",
+                vectors.len(),
+                coefficients.len() / size
+            ),
+        ));
+    }
+    let len = vectors.first().map_or(0, |v| v.len());
+    for (i, v) in vectors.iter().enumerate() {
+        let name = format!("vectors[{i}]");
+        check_canonical(v, field, &name)?;
+        if v.len() != len {
+            return Err(length_mismatch(
+                &name,
+                len / size,
+                "vectors[0]",
+                v.len() / size,
+            ));
+        }
+    }
+    let mut out = vec![0; len];
+    let combine: Combine = for_field!(field, combine);
+    combine(&mut out, vectors, coefficients, false);
+    Ok(out)
+}
+
+/// `y += a · x` over packed little-endian elements, in place
+pub fn axpy(y: &mut [u8], x: &[u8], a: &[u8], field: FieldName) -> Result<()> {
+    let size = field.element_bytes();
+    check_canonical(y, field, "y")?;
+    check_canonical(x, field, "x")?;
+    if x.len() != y.len() {
+        return Err(length_mismatch("x", y.len() / size, "y", x.len() / size));
+    }
+    check_canonical(a, field, "a")?;
+    if a.len() != size {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("a: expected one {size}-byte element, got {} bytes", a.len()),
+        ));
+    }
+    let combine: Combine = for_field!(field, combine);
+    combine(y, &[x], a, true);
+    Ok(())
+}
+
+fn single_element(bytes: &[u8], size: usize, name: &str) -> Result<()> {
+    if bytes.len() != size {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected one {size}-byte element, got {} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// `out[i] = op(a[i], b[i])` over packed little-endian elements
+///
+/// `out` must be exactly as long as the vector inputs; `b` may be a
+/// broadcast element, named `scalar` in errors.
+pub fn apply_into(
+    op: VectorOp,
+    out: &mut [u8],
+    a: Operand,
+    b: Operand,
+    field: FieldName,
+) -> Result<()> {
+    let size = field.element_bytes();
+    let a_len = match a {
+        Operand::Output => out.len(),
+        Operand::Vector(v) | Operand::Broadcast(v) => v.len(),
+    };
+    for (operand, name) in [(a, "a"), (b, "b")] {
+        match operand {
+            Operand::Output => check_canonical(out, field, name)?,
+            Operand::Vector(v) => {
+                check_canonical(v, field, name)?;
+                if v.len() != a_len {
+                    return Err(length_mismatch(name, a_len / size, "a", v.len() / size));
+                }
+            }
+            Operand::Broadcast(c) => {
+                single_element(c, size, "scalar")?;
+                check_canonical(c, field, "scalar")?;
+            }
+        }
+    }
+    crate::zero_copy::check_output_len(out, a_len, "out")?;
+    let apply: Apply = for_field!(field, apply);
+    apply(op, out, a, b);
+    Ok(())
+}
+
+/// Element-wise product of two packed little-endian vectors
+pub fn hadamard(a: &[u8], b: &[u8], field: FieldName) -> Result<Vec<u8>> {
+    let mut out = vec![0; a.len()];
+    apply_into(
+        VectorOp::Mul,
+        &mut out,
+        Operand::Vector(a),
+        Operand::Vector(b),
+        field,
+    )?;
+    Ok(out)
+}
+
+/// `input` as an operand of a call writing into `out`
+fn operand<'a>(out: &Buffer, input: &'a Buffer, name: &str) -> Result<Operand<'a>> {
+    let (o, i) = (out.as_ptr_range(), input.as_ptr_range());
+    if o == i {
+        return Ok(Operand::Output);
+    }
+    if o.start < i.end && i.start < o.end {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: overlaps out without being the same array"),
+        ));
+    }
+    Ok(Operand::Vector(input))
+}
+
+fn field_or_default(field: Option<String>) -> Result<FieldName> {
+    match field {
+        Some(name) => FieldName::parse(&name),
+        None => Ok(FieldName::Bn254Fr),
+    }
+}
+
+/// Linear combination `Σ coefficients[i] · vectors[i]` of packed vectors
+///
+/// `field` is any name `random_field_elements` accepts. The vectors, the
+/// packed `coefficients` (one per vector) and the result are little-endian
+/// canonical elements of that field. Vectors of different lengths throw,
+/// as do coefficient counts that do not match and elements `>= p`. An empty
+/// list of vectors gives an empty result. Large outputs are computed in
+/// parallel.
+#[napi]
+pub fn fr_linear_combination(
+    vectors: Vec<Buffer>,
+    coefficients: Buffer,
+    field: String,
+) -> Result<Buffer> {
+    let vectors: Vec<&[u8]> = vectors.iter().map(|v| v.as_ref()).collect();
+    linear_combination(&vectors, &coefficients, FieldName::parse(&field)?).map(Buffer::from)
+}
+
+/// `y += a · x`, writing the result into the caller's `y`
+///
+/// `x` and `y` are equal-length packed vectors and `a` a single element, all
+/// little-endian canonical elements of `field`, which defaults to
+/// "bn254-fr". `y` is left unchanged when this throws.
+#[napi]
+pub fn fr_axpy_in_place(mut y: Buffer, x: Buffer, a: Buffer, field: Option<String>) -> Result<()> {
+    let field = field_or_default(field)?;
+    let a = a.to_vec();
+    match operand(&y, &x, "x")? {
+        Operand::Vector(x) => axpy(&mut y, x, &a, field),
+        _ => {
+            let x = y.to_vec();
+            axpy(&mut y, &x, &a, field)
+        }
+    }
+}
+
+/// Element-wise product `a ∘ b` of two packed vectors
+///
+/// `a`, `b` and the result are little-endian canonical elements of `field`,
+/// any name `random_field_elements` accepts. Vectors of different lengths
+/// throw, as do elements `>= p`.
+#[napi]
+pub fn fr_hadamard(a: Buffer, b: Buffer, field: String) -> Result<Buffer> {
+    hadamard(&a, &b, FieldName::parse(&field)?).map(Buffer::from)
+}
+
+/// [`fr_hadamard`] writing into `out`, which must be exactly as long as `a`
+///
+/// `out` may be the same Buffer as `a` or `b`. `field` defaults to
+/// "bn254-fr".
+#[napi]
+pub fn fr_hadamard_into(
+    mut out: Buffer,
+    a: Buffer,
+    b: Buffer,
+    field: Option<String>,
+) -> Result<()> {
+    let field = field_or_default(field)?;
+    let (a, b) = (operand(&out, &a, "a")?, operand(&out, &b, "b")?);
+    apply_into(VectorOp::Mul, &mut out, a, b, field)
+}
+
+/// Element-wise sum `a + b` written into `out`
+///
+/// As [`fr_hadamard_into`]: `out` must be exactly as long as `a` and may be
+/// the same Buffer as either input.
+#[napi]
+pub fn fr_add_into(mut out: Buffer, a: Buffer, b: Buffer, field: Option<String>) -> Result<()> {
+    let field = field_or_default(field)?;
+    let (a, b) = (operand(&out, &a, "a")?, operand(&out, &b, "b")?);
+    apply_into(VectorOp::Add, &mut out, a, b, field)
+}
+
+/// `scalar · a` written into `out`, for a single element `scalar`
+///
+/// As [`fr_hadamard_into`]: `out` must be exactly as long as `a` and may be
+/// the same Buffer.
+#[napi]
+pub fn fr_scale_into(
+    mut out: Buffer,
+    a: Buffer,
+    scalar: Buffer,
+    field: Option<String>,
+) -> Result<()> {
+    let field = field_or_default(field)?;
+    let scalar = scalar.to_vec();
+    let a = operand(&out, &a, "a")?;
+    apply_into(
+        VectorOp::Mul,
+        &mut out,
+        a,
+        Operand::Broadcast(&scalar),
+        field,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inner_product::inner_product;
+    use crate::random::{random_elements, SEED_BYTES};
+
+    /// `c · v` then `acc + c · v`, one vector at a time, via single-term
+    /// inner products
+    fn separated(vectors: &[Vec<u8>], coefficients: &[u8], field: FieldName) -> Vec<u8> {
+        let size = field.element_bytes();
+        let len = vectors.first().map_or(0, |v| v.len());
+        let mut acc = vec![0; len];
+        let mut one = vec![0; size];
+        one[0] = 1;
+        for (v, c) in vectors.iter().zip(coefficients.chunks_exact(size)) {
+            for (a, x) in acc.chunks_exact_mut(size).zip(v.chunks_exact(size)) {
+                let term = inner_product(c, x, field).unwrap();
+                let sum = inner_product(&[a.to_vec(), term].concat(), &one.repeat(2), field);
+                a.copy_from_slice(&sum.unwrap());
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn test_matches_separated_passes() {
+        for (name, field) in FieldName::ALL {
+            let size = field.element_bytes();
+            for n in [0, 1, 5, BLOCK + 7] {
+                let vectors: Vec<Vec<u8>> = (0..5)
+                    .map(|k| random_elements(field, n, Some(&[k + 1; SEED_BYTES])).unwrap())
+                    .collect();
+                let mut coefficients = random_elements(field, 5, Some(&[9; SEED_BYTES])).unwrap();
+                // A zero and a one take the fast paths
+                coefficients[size..2 * size].fill(0);
+                coefficients[3 * size..4 * size].fill(0);
+                coefficients[3 * size] = 1;
+                let refs: Vec<&[u8]> = vectors.iter().map(|v| v.as_slice()).collect();
+                let result = linear_combination(&refs, &coefficients, field).unwrap();
+                assert_eq!(
+                    result,
+                    separated(&vectors, &coefficients, field),
+                    "{name} n={n}"
+                );
+
+                let mut y = vectors[0].clone();
+                axpy(&mut y, &vectors[1], &coefficients[4 * size..], field).unwrap();
+                let expected = separated(
+                    &vectors[..2],
+                    &[&coefficients[3 * size..4 * size], &coefficients[4 * size..]].concat(),
+                    field,
+                );
+                assert_eq!(y, expected, "{name} n={n}");
+            }
+            assert!(linear_combination(&[], &[], field).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_across_workers() {
+        // Large enough that the output is split across threads
+        let field = FieldName::Bn254Fr;
+        let n = 3 * PARALLEL_THRESHOLD_BYTES / 32 + 5;
+        let vectors: Vec<Vec<u8>> = (0..3)
+            .map(|k| random_elements(field, n, Some(&[k + 20; SEED_BYTES])).unwrap())
+            .collect();
+        let coefficients = random_elements(field, 3, Some(&[30; SEED_BYTES])).unwrap();
+        let refs: Vec<&[u8]> = vectors.iter().map(|v| v.as_slice()).collect();
+        let result = linear_combination(&refs, &coefficients, field).unwrap();
+        for j in [0, n / 2, n - 1] {
+            let column: Vec<u8> = vectors
+                .iter()
+                .flat_map(|v| v[32 * j..32 * (j + 1)].to_vec())
+                .collect();
+            let expected = inner_product(&coefficients, &column, field).unwrap();
+            assert_eq!(result[32 * j..32 * (j + 1)], expected[..], "element {j}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let field = FieldName::Bn254Fr;
+        let a = random_elements(field, 4, Some(&[3; SEED_BYTES])).unwrap();
+        let c = random_elements(field, 2, Some(&[4; SEED_BYTES])).unwrap();
+        let err = linear_combination(&[&a, &a[..96]], &c, field).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "vectors[1]: expected 4 elements to match vectors[0], got 3"
+        );
+        let err = linear_combination(&[&a], &c, field).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "coefficients: expected 1 elements, one per vector, got 2"
+        );
+        let mut bad = a.clone();
+        bad[32..64].fill(0xff);
+        let err = linear_combination(&[&a, &bad], &c, field).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "vectors[1][1]: value is not below the bn254-fr modulus"
+        );
+
+        let mut y = a.clone();
+        let err = axpy(&mut y, &a[..64], &c[..32], field).unwrap_err();
+        assert_eq!(err.reason, "x: expected 4 elements to match y, got 2");
+        let err = axpy(&mut y, &a, &c, field).unwrap_err();
+        assert_eq!(err.reason, "a: expected one 32-byte element, got 64 bytes");
+        assert_eq!(y, a);
+    }
+
+    /// `a[i] · b[i]` and `a[i] + b[i]`, one element at a time via
+    /// single-term inner products
+    fn products_and_sums(a: &[u8], b: &[u8], field: FieldName) -> (Vec<u8>, Vec<u8>) {
+        let size = field.element_bytes();
+        let mut one = vec![0; size];
+        one[0] = 1;
+        let (mut products, mut sums) = (Vec::new(), Vec::new());
+        for (x, y) in a.chunks_exact(size).zip(b.chunks_exact(size)) {
+            products.extend(inner_product(x, y, field).unwrap());
+            sums.extend(inner_product(&[x, y].concat(), &one.repeat(2), field).unwrap());
+        }
+        (products, sums)
+    }
+
+    #[test]
+    fn test_elementwise_aliased_and_not() {
+        for (name, field) in FieldName::ALL {
+            let size = field.element_bytes();
+            for n in [0, 1, 5, PARALLEL_THRESHOLD_BYTES / size + 3] {
+                let a = random_elements(field, n, Some(&[40; SEED_BYTES])).unwrap();
+                let b = random_elements(field, n, Some(&[41; SEED_BYTES])).unwrap();
+                let s = random_elements(field, 1, Some(&[42; SEED_BYTES])).unwrap();
+                let (products, sums) = products_and_sums(&a, &b, field);
+                let (squares, _) = products_and_sums(&a, &a, field);
+                let (scaled, _) = products_and_sums(&a, &s.repeat(n), field);
+                assert_eq!(hadamard(&a, &b, field).unwrap(), products, "{name}");
+
+                let cases = [
+                    (
+                        VectorOp::Mul,
+                        Operand::Vector(&a),
+                        Operand::Vector(&b),
+                        &b,
+                        &products,
+                    ),
+                    (
+                        VectorOp::Mul,
+                        Operand::Output,
+                        Operand::Vector(&b),
+                        &a,
+                        &products,
+                    ),
+                    (
+                        VectorOp::Mul,
+                        Operand::Vector(&a),
+                        Operand::Output,
+                        &b,
+                        &products,
+                    ),
+                    (
+                        VectorOp::Mul,
+                        Operand::Output,
+                        Operand::Output,
+                        &a,
+                        &squares,
+                    ),
+                    (
+                        VectorOp::Add,
+                        Operand::Vector(&a),
+                        Operand::Vector(&b),
+                        &a,
+                        &sums,
+                    ),
+                    (
+                        VectorOp::Add,
+                        Operand::Output,
+                        Operand::Vector(&b),
+                        &a,
+                        &sums,
+                    ),
+                    (
+                        VectorOp::Add,
+                        Operand::Vector(&a),
+                        Operand::Output,
+                        &b,
+                        &sums,
+                    ),
+                    (
+                        VectorOp::Mul,
+                        Operand::Output,
+                        Operand::Broadcast(&s),
+                        &a,
+                        &scaled,
+                    ),
+                    (
+                        VectorOp::Mul,
+                        Operand::Vector(&a),
+                        Operand::Broadcast(&s),
+                        &b,
+                        &scaled,
+                    ),
+                ];
+                for (k, (op, x, y, initial, expected)) in cases.into_iter().enumerate() {
+                    // `initial` is what `out` holds, the aliased input if any
+                    let mut out = initial.clone();
+                    apply_into(op, &mut out, x, y, field).unwrap();
+                    assert_eq!(&out, expected, "{name} n={n} case {k}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_elementwise_rejects_bad_inputs() {
+        let field = FieldName::Bn254Fr;
+        let a = random_elements(field, 4, Some(&[43; SEED_BYTES])).unwrap();
+        let mut out = vec![0; 96];
+        let err = apply_into(
+            VectorOp::Add,
+            &mut out,
+            Operand::Vector(&a),
+            Operand::Vector(&a),
+            field,
+        )
+        .unwrap_err();
+        assert_eq!(err.reason, "out: expected 128 bytes for the result, got 96");
+        let err = hadamard(&a, &a[..96], field).unwrap_err();
+        assert_eq!(err.reason, "b: expected 4 elements to match a, got 3");
+        let mut out = a.clone();
+        let err = apply_into(
+            VectorOp::Mul,
+            &mut out,
+            Operand::Output,
+            Operand::Vector(&a[..64]),
+            field,
+        )
+        .unwrap_err();
+        assert_eq!(err.reason, "b: expected 4 elements to match a, got 2");
+        let err = apply_into(
+            VectorOp::Mul,
+            &mut out,
+            Operand::Output,
+            Operand::Broadcast(&a[..64]),
+            field,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.reason,
+            "scalar: expected one 32-byte element, got 64 bytes"
+        );
+        assert_eq!(out, a);
+    }
+}
@@ -0,0 +1,138 @@
+//! Browser exports for the `wasm` feature
+//!
+//! The `wasm` feature turns every `#[napi]` export into a no-op and exposes
+//! this smaller surface through `wasm-bindgen` instead. The arithmetic is the
+//! same code the Node addon runs: builds with `-C target-feature=+simd128`
+//! use wasm SIMD lanes for the vectorized kernels (see [`crate::neon`]),
+//! other builds the portable fallback. Everything runs on the calling thread.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use js_sys::Uint8Array;
+
+/// Capabilities of the wasm build and the engine running it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WasmCapabilities {
+    /// Whether this module was compiled with `simd128`
+    pub simd128: bool,
+    /// Whether the engine accepts SIMD modules
+    pub simd_supported: bool,
+    /// Whether the vectorized kernels run (`simd128` and not disabled)
+    pub has_simd: bool,
+    /// Worker threads used by parallel kernels, always 1
+    pub threads: u32,
+}
+
+/// Build and engine capabilities
+pub fn capabilities() -> WasmCapabilities {
+    WasmCapabilities {
+        simd128: cfg!(target_feature = "simd128"),
+        simd_supported: simd_supported(),
+        has_simd: crate::neon::supported(),
+        threads: crate::parallel::num_threads() as u32,
+    }
+}
+
+/// Whether the engine validates a module using SIMD instructions
+#[cfg(target_arch = "wasm32")]
+fn simd_supported() -> bool {
+    // Smallest module using a SIMD instruction (`i32x4.splat`)
+    const SIMD_PROBE: [u8; 31] = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b,
+        0x03, 0x02, 0x01, 0x00, 0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x00, 0xfd, 0x0f, 0xfd, 0x62,
+        0x0b,
+    ];
+    js_sys::WebAssembly::validate(&Uint8Array::from(&SIMD_PROBE[..]).into()).unwrap_or(false)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn simd_supported() -> bool {
+    false
+}
+
+fn to_js_error(e: napi::Error) -> JsError {
+    JsError::new(&e.reason)
+}
+
+fn to_bytes(values: Vec<Uint8Array>) -> Vec<Vec<u8>> {
+    values.iter().map(Uint8Array::to_vec).collect()
+}
+
+/// Build and engine capabilities as a plain object
+#[wasm_bindgen(js_name = detectCapabilities)]
+pub fn detect_capabilities() -> std::result::Result<JsValue, JsError> {
+    let json = serde_json::to_string(&capabilities())?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("failed to build capabilities object"))
+}
+
+/// BN254 scalar field multiplication over 32-byte big-endian values
+#[wasm_bindgen(js_name = bn254FieldMul)]
+pub fn bn254_field_mul(a: &[u8], b: &[u8]) -> std::result::Result<Vec<u8>, JsError> {
+    crate::bn254::bn254_field_mul(a.to_vec(), b.to_vec()).map_err(to_js_error)
+}
+
+/// Poseidon hash over BN254 with width `t`, `rf` full and `rp` partial rounds
+#[wasm_bindgen(js_name = poseidonHash)]
+pub fn poseidon_hash(
+    inputs: Vec<Uint8Array>,
+    t: u32,
+    rf: u32,
+    rp: u32,
+) -> std::result::Result<Vec<u8>, JsError> {
+    crate::poseidon::poseidon_hash(to_bytes(inputs), t, rf, rp).map_err(to_js_error)
+}
+
+/// Forward (or inverse, with `invert`) NTT over BN254 Fr
+#[wasm_bindgen(js_name = nttBn254)]
+pub fn ntt_bn254(
+    coeffs: Vec<Uint8Array>,
+    invert: bool,
+) -> std::result::Result<Vec<Uint8Array>, JsError> {
+    let values = crate::ntt::ntt_bn254(to_bytes(coeffs), invert).map_err(to_js_error)?;
+    Ok(values.iter().map(|v| Uint8Array::from(&v[..])).collect())
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn fr(v: u64) -> Uint8Array {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&v.to_be_bytes());
+        Uint8Array::from(&bytes[..])
+    }
+
+    #[wasm_bindgen_test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.threads, 1);
+        assert_eq!(caps.has_simd, caps.simd128);
+        if caps.simd128 {
+            assert!(caps.simd_supported);
+        }
+        assert!(detect_capabilities().is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_exports_match_native() {
+        let (a, b) = (fr(6).to_vec(), fr(7).to_vec());
+        assert_eq!(bn254_field_mul(&a, &b).unwrap(), fr(42).to_vec());
+
+        assert_eq!(
+            poseidon_hash(vec![fr(1), fr(2)], 3, 8, 57).unwrap(),
+            crate::poseidon::poseidon_hash_default(vec![fr(1).to_vec(), fr(2).to_vec()]).unwrap()
+        );
+
+        let coeffs: Vec<Uint8Array> = (0..16).map(fr).collect();
+        let forward = ntt_bn254(coeffs, false).unwrap();
+        let back = ntt_bn254(forward, true).unwrap();
+        let back: Vec<Vec<u8>> = back.iter().map(Uint8Array::to_vec).collect();
+        let expected: Vec<Vec<u8>> = (0..16).map(|v| fr(v).to_vec()).collect();
+        assert_eq!(back, expected);
+    }
+}
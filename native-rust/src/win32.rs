@@ -0,0 +1,235 @@
+//! Minimal Win32 bindings used by hardware and memory detection
+//!
+//! Declared directly against `kernel32`, `advapi32`, `powrprof` and `psapi`
+//! rather than through a bindings crate; only the handful of calls the
+//! detection code needs are exposed.
+
+/// `PF_ARM_NEON_INSTRUCTIONS_AVAILABLE`
+pub const PF_ARM_NEON_INSTRUCTIONS_AVAILABLE: u32 = 19;
+/// `PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE` (AES, PMULL, SHA1, SHA2)
+pub const PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE: u32 = 30;
+/// `PF_ARM_SVE_INSTRUCTIONS_AVAILABLE`
+pub const PF_ARM_SVE_INSTRUCTIONS_AVAILABLE: u32 = 46;
+/// `PF_ARM_SVE2_INSTRUCTIONS_AVAILABLE`
+pub const PF_ARM_SVE2_INSTRUCTIONS_AVAILABLE: u32 = 47;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn IsProcessorFeaturePresent(feature: u32) -> i32;
+}
+
+/// Whether the processor reports the given `PF_*` feature
+pub fn processor_feature(feature: u32) -> bool {
+    // SAFETY: IsProcessorFeaturePresent has no preconditions; unknown
+    // feature numbers simply return FALSE
+    unsafe { IsProcessorFeaturePresent(feature) != 0 }
+}
+
+/// `RelationAll` in `LOGICAL_PROCESSOR_RELATIONSHIP`
+const RELATION_ALL: u32 = 0xffff;
+/// `ERROR_INSUFFICIENT_BUFFER`
+const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+/// `HKEY_LOCAL_MACHINE`, sign-extended as the SDK headers do
+const HKEY_LOCAL_MACHINE: isize = 0x8000_0002u32 as i32 as isize;
+/// `RRF_RT_REG_SZ`
+const RRF_RT_REG_SZ: u32 = 0x2;
+
+/// `MEMORYSTATUSEX`
+#[repr(C)]
+struct MemoryStatusEx {
+    length: u32,
+    memory_load: u32,
+    total_phys: u64,
+    avail_phys: u64,
+    total_page_file: u64,
+    avail_page_file: u64,
+    total_virtual: u64,
+    avail_virtual: u64,
+    avail_extended_virtual: u64,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetLogicalProcessorInformationEx(
+        relationship: u32,
+        buffer: *mut u8,
+        length: *mut u32,
+    ) -> i32;
+    fn GetLastError() -> u32;
+    fn GlobalMemoryStatusEx(status: *mut MemoryStatusEx) -> i32;
+}
+
+/// `ProcessorInformation` in `POWER_INFORMATION_LEVEL`
+const PROCESSOR_INFORMATION: i32 = 11;
+/// `ALL_PROCESSOR_GROUPS`
+const ALL_PROCESSOR_GROUPS: u16 = 0xffff;
+
+/// `PROCESSOR_POWER_INFORMATION`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct ProcessorPowerInformation {
+    number: u32,
+    max_mhz: u32,
+    current_mhz: u32,
+    mhz_limit: u32,
+    max_idle_state: u32,
+    current_idle_state: u32,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetActiveProcessorCount(group: u16) -> u32;
+}
+
+#[link(name = "powrprof")]
+extern "system" {
+    fn CallNtPowerInformation(
+        level: i32,
+        input: *mut u8,
+        input_len: u32,
+        output: *mut u8,
+        output_len: u32,
+    ) -> i32;
+}
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn RegGetValueW(
+        key: isize,
+        sub_key: *const u16,
+        value: *const u16,
+        flags: u32,
+        kind: *mut u32,
+        data: *mut u8,
+        data_len: *mut u32,
+    ) -> i32;
+}
+
+/// Raw `SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX` records for every relation
+pub fn logical_processor_information() -> Option<Vec<u8>> {
+    let mut len = 0u32;
+    // SAFETY: a null buffer with zero length only queries the required size
+    let ok =
+        unsafe { GetLogicalProcessorInformationEx(RELATION_ALL, std::ptr::null_mut(), &mut len) };
+    if ok != 0 || unsafe { GetLastError() } != ERROR_INSUFFICIENT_BUFFER {
+        return None;
+    }
+    let mut buffer = vec![0u8; len as usize];
+    // SAFETY: the buffer is exactly `len` bytes, as requested by the first call
+    let ok =
+        unsafe { GetLogicalProcessorInformationEx(RELATION_ALL, buffer.as_mut_ptr(), &mut len) };
+    if ok == 0 {
+        return None;
+    }
+    buffer.truncate(len as usize);
+    Some(buffer)
+}
+
+/// Installed physical memory in bytes
+pub fn total_physical_memory() -> Option<u64> {
+    let mut status = MemoryStatusEx {
+        length: std::mem::size_of::<MemoryStatusEx>() as u32,
+        memory_load: 0,
+        total_phys: 0,
+        avail_phys: 0,
+        total_page_file: 0,
+        avail_page_file: 0,
+        total_virtual: 0,
+        avail_virtual: 0,
+        avail_extended_virtual: 0,
+    };
+    // SAFETY: `length` is set to the structure size as the API requires
+    let ok = unsafe { GlobalMemoryStatusEx(&mut status) };
+    (ok != 0).then_some(status.total_phys)
+}
+
+/// `ProcessorNameString` of the first processor from the registry
+pub fn processor_name() -> Option<String> {
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let sub_key = wide(r"HARDWARE\DESCRIPTION\System\CentralProcessor\0");
+    let value = wide("ProcessorNameString");
+    let mut data = [0u16; 256];
+    let mut len = std::mem::size_of_val(&data) as u32;
+    // SAFETY: both strings are NUL-terminated and `len` is the buffer size in bytes
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            sub_key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            data.as_mut_ptr().cast(),
+            &mut len,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    let chars = &data[..(len as usize / 2).min(data.len())];
+    let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    let name = String::from_utf16_lossy(&chars[..end]).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Rated and current clock of the first processor, in MHz
+pub fn processor_clock_mhz() -> Option<(u32, u32)> {
+    // SAFETY: GetActiveProcessorCount has no preconditions
+    let count = unsafe { GetActiveProcessorCount(ALL_PROCESSOR_GROUPS) }.max(1);
+    let mut records = vec![ProcessorPowerInformation::default(); count as usize];
+    let len = std::mem::size_of_val(records.as_slice()) as u32;
+    // SAFETY: the output buffer holds one record per active processor, as
+    // ProcessorInformation requires
+    let status = unsafe {
+        CallNtPowerInformation(
+            PROCESSOR_INFORMATION,
+            std::ptr::null_mut(),
+            0,
+            records.as_mut_ptr().cast(),
+            len,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    let first = records[0];
+    (first.max_mhz > 0).then_some((first.max_mhz, first.current_mhz))
+}
+
+/// `PROCESS_MEMORY_COUNTERS`
+#[repr(C)]
+#[derive(Default)]
+struct ProcessMemoryCounters {
+    cb: u32,
+    page_fault_count: u32,
+    peak_working_set_size: usize,
+    working_set_size: usize,
+    quota_peak_paged_pool_usage: usize,
+    quota_paged_pool_usage: usize,
+    quota_peak_non_paged_pool_usage: usize,
+    quota_non_paged_pool_usage: usize,
+    pagefile_usage: usize,
+    peak_pagefile_usage: usize,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCurrentProcess() -> isize;
+}
+
+#[link(name = "psapi")]
+extern "system" {
+    fn GetProcessMemoryInfo(process: isize, counters: *mut ProcessMemoryCounters, cb: u32) -> i32;
+}
+
+/// Working set (resident) size of the current process in bytes
+pub fn process_working_set_bytes() -> Option<u64> {
+    let size = std::mem::size_of::<ProcessMemoryCounters>() as u32;
+    let mut counters = ProcessMemoryCounters {
+        cb: size,
+        ..Default::default()
+    };
+    // SAFETY: GetCurrentProcess returns a pseudo-handle that needs no
+    // closing, and `cb` is set to the structure size
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+    (ok != 0).then_some(counters.working_set_size as u64)
+}
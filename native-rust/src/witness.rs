@@ -0,0 +1,296 @@
+//! Reading and writing circom `.wtns` witness files
+//!
+//! circom's witness generators write version 2 binfiles (see
+//! [`crate::circom`]) with two sections: a header holding the element size
+//! `n8`, the prime as `n8` little-endian bytes and the witness count, then
+//! the values as canonical little-endian elements. Only BN254 witnesses are
+//! read. Values and primes cross the API as 32-byte big-endian scalars, the
+//! encoding [`crate::r1cs::check_r1cs`] and the prover take.
+
+use std::collections::HashMap;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{parse_fr, Fr};
+use crate::circom::{self, Reader, FIELD_BYTES};
+use crate::random::FieldName;
+
+/// Version circom writes; version 1 files have the same layout
+const WTNS_VERSION: u32 = 2;
+
+const HEADER_SECTION: u32 = 1;
+const VALUES_SECTION: u32 = 2;
+
+fn invalid(name: &str, reason: String) -> Error {
+    Error::new(Status::InvalidArg, format!("{name}: {reason}"))
+}
+
+/// The header section of a `.wtns` file
+struct WtnsHeader<'a> {
+    field_size: usize,
+    /// Little-endian, `field_size` bytes
+    prime: &'a [u8],
+    n_witness: usize,
+}
+
+impl<'a> WtnsHeader<'a> {
+    fn read(sections: &mut HashMap<u32, Reader<'a>>, name: &str) -> Result<Self> {
+        let mut header = circom::section(sections, HEADER_SECTION, name)?;
+        let field_size = header.u32()? as usize;
+        let prime = header.take(field_size)?;
+        let n_witness = header.u32()? as usize;
+        Ok(WtnsHeader {
+            field_size,
+            prime,
+            n_witness,
+        })
+    }
+
+    /// Fail unless the witness is over BN254's scalar field, naming the
+    /// field it is over when that is one this crate knows
+    fn check_bn254(&self, name: &str) -> Result<()> {
+        if self.prime == bn254_prime_le() {
+            return Ok(());
+        }
+        let reason = match FieldName::ALL
+            .iter()
+            .find(|(_, field)| field.modulus_le_bytes() == self.prime)
+        {
+            Some((field, _)) => format!("witness was generated for {field}, expected bn254-fr"),
+            None => format!(
+                "witness was generated for an unknown {}-byte prime, expected bn254-fr",
+                self.field_size
+            ),
+        };
+        Err(invalid(name, reason))
+    }
+}
+
+fn bn254_prime_le() -> Vec<u8> {
+    FieldName::Bn254Fr.modulus_le_bytes()
+}
+
+fn bn254_prime_be() -> Vec<u8> {
+    let mut prime = bn254_prime_le();
+    prime.reverse();
+    prime
+}
+
+/// Parse a BN254 `.wtns` file
+pub fn parse(bytes: &[u8], name: &str) -> Result<Vec<Fr>> {
+    let mut sections = circom::sections(bytes, b"wtns", WTNS_VERSION, name)?;
+    let header = WtnsHeader::read(&mut sections, name)?;
+    header.check_bn254(name)?;
+    let values = circom::section(&mut sections, VALUES_SECTION, name)?.rest();
+    if values.len() != header.n_witness * FIELD_BYTES {
+        return Err(invalid(
+            name,
+            format!(
+                "expected {} values of {FIELD_BYTES} bytes, got {} bytes",
+                header.n_witness,
+                values.len()
+            ),
+        ));
+    }
+    values
+        .chunks_exact(FIELD_BYTES)
+        .enumerate()
+        .map(|(i, v)| {
+            Fr::from_le_bytes(v).ok_or_else(|| {
+                invalid(
+                    name,
+                    format!("witness[{i}] is not below the BN254 scalar field modulus"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Encode a BN254 witness in circom's layout
+pub fn encode(witness: &[Fr]) -> Vec<u8> {
+    let mut header = (FIELD_BYTES as u32).to_le_bytes().to_vec();
+    header.extend(bn254_prime_le());
+    header.extend((witness.len() as u32).to_le_bytes());
+    let values: Vec<u8> = witness.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+    let mut out = b"wtns".to_vec();
+    out.extend(WTNS_VERSION.to_le_bytes());
+    out.extend(2u32.to_le_bytes());
+    for (id, body) in [(HEADER_SECTION, header), (VALUES_SECTION, values)] {
+        out.extend(id.to_le_bytes());
+        out.extend((body.len() as u64).to_le_bytes());
+        out.extend(body);
+    }
+    out
+}
+
+/// Read the header of the `.wtns` file at `path` without its values
+fn with_header<T>(path: &str, f: impl FnOnce(&WtnsHeader) -> T) -> Result<T> {
+    let map = circom::map_file(path)?;
+    let mut sections = circom::sections(&map, b"wtns", WTNS_VERSION, path)?;
+    Ok(f(&WtnsHeader::read(&mut sections, path)?))
+}
+
+/// Read a circom `.wtns` witness as 32-byte big-endian BN254 scalars
+///
+/// Witnesses over any other prime throw, naming the field when it is one
+/// `random_field_elements` knows, e.g. "witness was generated for
+/// bls12-381-fr, expected bn254-fr". Unreadable files reject with
+/// `GenericFailure`, malformed ones with `InvalidArg`.
+#[napi]
+pub fn wtns_read(path: String) -> Result<Vec<Vec<u8>>> {
+    let bytes = std::fs::read(&path).map_err(|e| circom::io_error(&path, e))?;
+    Ok(parse(&bytes, &path)?
+        .iter()
+        .map(|v| v.to_be_bytes())
+        .collect())
+}
+
+/// Bytes per element of a `.wtns` file, from its header alone
+#[napi]
+pub fn wtns_field_size(path: String) -> Result<u32> {
+    with_header(&path, |h| h.field_size as u32)
+}
+
+/// Prime of a `.wtns` file, big-endian, from its header alone
+///
+/// Unlike [`wtns_read`] this accepts any prime.
+#[napi]
+pub fn wtns_prime(path: String) -> Result<Vec<u8>> {
+    with_header(&path, |h| h.prime.iter().rev().copied().collect())
+}
+
+/// Write a witness of 32-byte big-endian BN254 scalars as a `.wtns` file
+///
+/// `prime` is the big-endian modulus, as [`wtns_prime`] returns it, and
+/// must be BN254's scalar field modulus.
+#[napi]
+pub fn wtns_write(path: String, witness: Vec<Vec<u8>>, prime: Vec<u8>) -> Result<()> {
+    if prime != bn254_prime_be() {
+        return Err(invalid(
+            "prime",
+            "only BN254's scalar field modulus is supported".to_string(),
+        ));
+    }
+    let witness = witness
+        .iter()
+        .enumerate()
+        .map(|(i, v)| parse_fr(v, &format!("witness[{i}]")))
+        .collect::<Result<Vec<_>>>()?;
+    std::fs::write(&path, encode(&witness)).map_err(|e| circom::io_error(&path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLIER_WTNS: &[u8] = include_bytes!("../tests/fixtures/multiplier.wtns");
+
+    fn temp_path(tag: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("wtns-test-{}-{tag}.wtns", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// `[1, c, a, b]` for the multiplier circuit with `a = 3`, `b = 11`
+    fn expected() -> Vec<Vec<u8>> {
+        [1, 33, 3, 11]
+            .map(|v| Fr::from_u64(v).to_be_bytes())
+            .to_vec()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("fixture");
+        std::fs::write(&path, MULTIPLIER_WTNS).unwrap();
+        let witness = wtns_read(path.clone()).unwrap();
+        assert_eq!(witness, expected());
+        assert_eq!(wtns_field_size(path.clone()).unwrap(), 32);
+        let prime = wtns_prime(path.clone()).unwrap();
+        assert_eq!(prime, bn254_prime_be());
+
+        let copy = temp_path("copy");
+        wtns_write(copy.clone(), witness, prime).unwrap();
+        let written = std::fs::read(&copy).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&copy).unwrap();
+        assert_eq!(written, MULTIPLIER_WTNS);
+
+        // The witness satisfies the circuit it was generated for
+        let r1cs = include_bytes!("../tests/fixtures/multiplier.r1cs");
+        assert!(
+            crate::r1cs::check_r1cs(r1cs.to_vec(), expected())
+                .unwrap()
+                .satisfied
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_fields() {
+        let mut bls = MULTIPLIER_WTNS.to_vec();
+        // The prime follows the magic, version, count, section header and n8
+        let prime = FieldName::Bls12_381Fr.modulus_le_bytes();
+        bls[28..60].copy_from_slice(&prime);
+        let err = parse(&bls, "wtns").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "wtns: witness was generated for bls12-381-fr, expected bn254-fr"
+        );
+        bls[28] ^= 1;
+        let err = parse(&bls, "wtns").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "wtns: witness was generated for an unknown 32-byte prime, expected bn254-fr"
+        );
+
+        let path = temp_path("bls");
+        let mut bls = MULTIPLIER_WTNS.to_vec();
+        bls[28..60].copy_from_slice(&prime);
+        std::fs::write(&path, &bls).unwrap();
+        let header_prime = wtns_prime(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        let mut prime_be = prime;
+        prime_be.reverse();
+        assert_eq!(header_prime.unwrap(), prime_be.clone());
+        let err = wtns_write(temp_path("unused"), expected(), prime_be).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "prime: only BN254's scalar field modulus is supported"
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let truncated = &MULTIPLIER_WTNS[..MULTIPLIER_WTNS.len() - 1];
+        assert!(parse(truncated, "wtns").is_err());
+
+        let mut short = MULTIPLIER_WTNS.to_vec();
+        // n_witness sits right after the prime
+        short[60] = 5;
+        let err = parse(&short, "wtns").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "wtns: expected 5 values of 32 bytes, got 128 bytes"
+        );
+
+        let mut future = MULTIPLIER_WTNS.to_vec();
+        future[4] = 3;
+        let err = parse(&future, "wtns").unwrap_err();
+        assert_eq!(err.reason, "wtns: unsupported version 3");
+
+        let mut wide = MULTIPLIER_WTNS.to_vec();
+        let last = wide.len() - 32;
+        wide[last..].fill(0xff);
+        let err = parse(&wide, "wtns").unwrap_err();
+        assert_eq!(
+            err.reason,
+            "wtns: witness[3] is not below the BN254 scalar field modulus"
+        );
+
+        let err = wtns_read(temp_path("missing")).unwrap_err();
+        assert_eq!(err.status, Status::GenericFailure);
+        assert!(wtns_field_size(temp_path("missing")).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Zero-copy access to caller-owned typed arrays
+//!
+//! The `*_packed` and `*_into` variants of `ntt_bn254`, `msm_bn254_g1` and
+//! `bn254_field_mul_batch` read their inputs directly from a `Uint8Array`
+//! instead of an array of per-element Buffers. The array may be a Node.js
+//! `Buffer` or a view over an `ArrayBuffer` or `SharedArrayBuffer`.
+//! Elements are packed back to back in the encodings of the array-of-Buffers
+//! functions. The `_packed` variants return a new Buffer that takes ownership
+//! of the result allocation, and the `_into` variants write into a
+//! caller-provided array of exactly the result size.
+//!
+//! Safety contract: the arrays are only borrowed for the duration of the
+//! call. The caller must not resize, transfer or detach the underlying
+//! buffer during the call. Another thread must not write to a
+//! `SharedArrayBuffer` while the call runs. An `_into` output may be the
+//! same array as an input; all inputs are decoded before anything is
+//! written.
+
+use napi::{Error, JsTypedArrayValue, Result, Status, TypedArrayType};
+
+use crate::bn254::{parse_fr, Fr};
+
+/// Bytes per packed scalar field element
+pub const FR_BYTES: usize = 32;
+
+fn check_bytes(value: &JsTypedArrayValue, name: &str) -> Result<()> {
+    match value.typedarray_type {
+        TypedArrayType::Uint8 | TypedArrayType::Uint8Clamped => Ok(()),
+        other => Err(Error::new(
+            Status::InvalidArg,
+            format!("{name}: expected a Uint8Array or Buffer, got {other:?}"),
+        )),
+    }
+}
+
+/// The bytes of a `Uint8Array` argument, without copying
+pub(crate) fn bytes<'a>(value: &'a JsTypedArrayValue, name: &str) -> Result<&'a [u8]> {
+    check_bytes(value, name)?;
+    Ok(value.as_ref())
+}
+
+/// Mutable bytes of a `Uint8Array` output argument
+pub(crate) fn bytes_mut<'a>(value: &'a mut JsTypedArrayValue, name: &str) -> Result<&'a mut [u8]> {
+    check_bytes(value, name)?;
+    Ok(value.as_mut())
+}
+
+/// Split packed bytes into `width`-byte elements
+pub(crate) fn elements<'a>(
+    bytes: &'a [u8],
+    width: usize,
+    name: &str,
+) -> Result<std::slice::ChunksExact<'a, u8>> {
+    if !bytes.len().is_multiple_of(width) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: length {} is not a multiple of {width} bytes",
+                bytes.len()
+            ),
+        ));
+    }
+    Ok(bytes.chunks_exact(width))
+}
+
+/// Decode packed 32-byte big-endian canonical field elements
+pub fn parse_packed_fr(bytes: &[u8], name: &str) -> Result<Vec<Fr>> {
+    elements(bytes, FR_BYTES, name)?
+        .enumerate()
+        .map(|(i, c)| parse_fr(c, &format!("{name}[{i}]")))
+        .collect()
+}
+
+/// Encode field elements as packed 32-byte big-endian values
+pub fn encode_packed_fr(values: &[Fr]) -> Vec<u8> {
+    let mut out = vec![0u8; values.len() * FR_BYTES];
+    write_packed_fr(&mut out, values, "output").expect("output is sized for the values");
+    out
+}
+
+/// Check that an output array holds exactly `expected` bytes
+pub(crate) fn check_output_len(output: &[u8], expected: usize, name: &str) -> Result<()> {
+    if output.len() != expected {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "{name}: expected {expected} bytes for the result, got {}",
+                output.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Write field elements into a caller-provided array of exactly their size
+pub fn write_packed_fr(output: &mut [u8], values: &[Fr], name: &str) -> Result<()> {
+    check_output_len(output, values.len() * FR_BYTES, name)?;
+    for (out, v) in output.chunks_exact_mut(FR_BYTES).zip(values) {
+        out.copy_from_slice(&v.to_be_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_round_trip() {
+        let values: Vec<Fr> = (0..5).map(|v| Fr::from_u64(v * 1000 + 7)).collect();
+        let packed = encode_packed_fr(&values);
+        assert_eq!(packed.len(), 5 * FR_BYTES);
+        assert_eq!(parse_packed_fr(&packed, "a").unwrap(), values);
+        assert!(parse_packed_fr(&[], "a").unwrap().is_empty());
+
+        let err = parse_packed_fr(&packed[..33], "a").unwrap_err();
+        assert_eq!(err.status, Status::InvalidArg);
+        assert_eq!(err.reason, "a: length 33 is not a multiple of 32 bytes");
+        let mut bad = packed.clone();
+        bad[32..64].fill(0xff);
+        let err = parse_packed_fr(&bad, "a").unwrap_err();
+        assert!(err.reason.starts_with("a[1]"), "{}", err.reason);
+
+        let mut output = vec![0u8; 4 * FR_BYTES];
+        assert!(write_packed_fr(&mut output, &values, "output").is_err());
+        let mut output = vec![0u8; 5 * FR_BYTES];
+        write_packed_fr(&mut output, &values, "output").unwrap();
+        assert_eq!(output, packed);
+    }
+}
@@ -0,0 +1,702 @@
+//! Reading and writing snarkjs Groth16 `.zkey` proving keys
+//!
+//! [`zkey_read`] loads a whole key into a [`ProvingKey`] with points in the
+//! [`crate::bn254`] encodings, and [`zkey_write`] writes one back in the
+//! section order snarkjs uses, so an unmodified key round-trips byte for
+//! byte. [`zkey_info`] maps the file and reads only the header, the last
+//! coefficient and the contribution section. See [`crate::circom`] for the
+//! container layout.
+//!
+//! The format carries no checksum of its own. Section 10 holds the phase-2
+//! ceremony transcript: the BLAKE2b-512 hash of the initial key, then each
+//! contribution's updated `delta`. Reading checks that the key's `delta` is
+//! the last contribution's, or the generator when there are none, so points
+//! edited after the ceremony are caught. Only bn128 keys are supported.
+//!
+//! [`ProvingKeyCache`] keeps large keys memory-mapped between proofs,
+//! evicting the least recently loaded once a byte budget would be exceeded.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use memmap2::{Mmap, MmapOptions};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::bn254::{self, Fr, G1Affine, G2Affine};
+use crate::circom::{self, Matrix, Reader, Zkey, ZkeyHeader, FIELD_BYTES};
+use crate::montgomery::MontConfig;
+
+/// Section holding the phase-2 contributions
+const CONTRIBUTIONS_SECTION: u32 = 10;
+
+/// Bytes of the BLAKE2b-512 circuit hash that starts section 10
+const CS_HASH_BYTES: usize = 64;
+
+/// Proving key sections in the order snarkjs writes them
+const SECTION_ORDER: [u32; 9] = [1, 2, 4, 3, 9, 8, 5, 6, 7];
+
+/// Bytes of one coefficient entry: matrix, constraint, signal and value
+const COEFFICIENT_BYTES: usize = 12 + FIELD_BYTES;
+
+/// One nonzero entry of the A or B matrix
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeyCoefficient {
+    /// 0 for A, 1 for B
+    pub matrix: u32,
+    pub constraint: u32,
+    pub signal: u32,
+    /// 32-byte big-endian scalar
+    pub value: Vec<u8>,
+}
+
+/// A section outside the proving key itself, kept verbatim
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeySection {
+    pub id: u32,
+    pub data: Vec<u8>,
+}
+
+/// A Groth16 proving key over BN254
+///
+/// G1 points are 64 bytes and G2 points 128 bytes, uncompressed big-endian
+/// as in [`crate::bn254`]. `a`, `b_g1` and `b_g2` hold one point per wire,
+/// `c` one per private wire and `h` one per domain point.
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvingKey {
+    pub n_vars: u32,
+    pub n_public: u32,
+    pub domain_size: u32,
+    pub alpha_g1: Vec<u8>,
+    pub beta_g1: Vec<u8>,
+    pub beta_g2: Vec<u8>,
+    pub gamma_g2: Vec<u8>,
+    pub delta_g1: Vec<u8>,
+    pub delta_g2: Vec<u8>,
+    /// `ic[0]` plus one point per public signal
+    pub ic: Vec<Vec<u8>>,
+    pub coefficients: Vec<ZkeyCoefficient>,
+    pub a: Vec<Vec<u8>>,
+    pub b_g1: Vec<Vec<u8>>,
+    pub b_g2: Vec<Vec<u8>>,
+    pub c: Vec<Vec<u8>>,
+    pub h: Vec<Vec<u8>>,
+    /// Other sections by id, such as the contributions in section 10
+    pub extra_sections: Vec<ZkeySection>,
+}
+
+/// Summary of a `.zkey` file from [`zkey_info`]
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeyInfo {
+    /// Always `"groth16"`
+    pub protocol: String,
+    /// Always `"bn128"`
+    pub curve: String,
+    pub n_constraints: u32,
+    /// Wires including the constant 1
+    pub n_wires: u32,
+    pub n_public: u32,
+    pub domain_size: u32,
+    /// Phase-2 contributions applied after setup
+    pub n_contributions: u32,
+    /// Hex BLAKE2b-512 hash of the key before any contribution, empty
+    /// without a contribution section
+    pub cs_hash: String,
+}
+
+fn invalid(name: &str, reason: String) -> Error {
+    Error::new(Status::InvalidArg, format!("{name}: {reason}"))
+}
+
+/// Contribution count and circuit hash of section 10, checking that
+/// `delta_g1` is the last contribution's
+fn read_contributions(
+    reader: &mut Reader,
+    delta_g1: &G1Affine,
+    name: &str,
+) -> Result<(usize, [u8; CS_HASH_BYTES])> {
+    let cs_hash = reader.take(CS_HASH_BYTES)?.try_into().unwrap();
+    let n = reader.u32()? as usize;
+    let mut delta_after = bn254::g1_generator();
+    for _ in 0..n {
+        delta_after = circom::read_g1(reader)?;
+        // The contributor's proof of knowledge and transcript hash, then
+        // the contribution type and its parameters
+        reader.take(2 * 2 * FIELD_BYTES + 4 * FIELD_BYTES + 64)?;
+        reader.u32()?;
+        let params = reader.u32()? as usize;
+        reader.take(params)?;
+    }
+    if delta_after != *delta_g1 {
+        let reason = if n == 0 {
+            "delta_g1 is not the generator, but there are no contributions"
+        } else {
+            "delta_g1 does not match the last contribution"
+        };
+        return Err(invalid(name, reason.to_string()));
+    }
+    Ok((n, cs_hash))
+}
+
+/// Parse a whole `.zkey` file
+pub fn parse(bytes: &[u8], name: &str) -> Result<ProvingKey> {
+    let mut sections = circom::sections(bytes, b"zkey", 1, name)?;
+    let zkey = Zkey::read(&mut sections, name)?;
+    let mut extra_sections: Vec<ZkeySection> = sections
+        .into_iter()
+        .map(|(id, mut reader)| ZkeySection {
+            id,
+            data: reader.rest().to_vec(),
+        })
+        .collect();
+    extra_sections.sort_by_key(|s| s.id);
+    if let Some(s) = extra_sections
+        .iter()
+        .find(|s| s.id == CONTRIBUTIONS_SECTION)
+    {
+        read_contributions(&mut Reader::new(&s.data, name), &zkey.delta_g1, name)?;
+    }
+    Ok(ProvingKey::from_zkey(&zkey, extra_sections))
+}
+
+impl ProvingKey {
+    fn from_zkey(zkey: &Zkey, extra_sections: Vec<ZkeySection>) -> Self {
+        let g1s = |points: &[G1Affine]| points.iter().map(bn254::encode_g1).collect();
+        ProvingKey {
+            n_vars: zkey.n_vars as u32,
+            n_public: zkey.n_public as u32,
+            domain_size: zkey.domain_size as u32,
+            alpha_g1: bn254::encode_g1(&zkey.alpha_g1),
+            beta_g1: bn254::encode_g1(&zkey.beta_g1),
+            beta_g2: bn254::encode_g2(&zkey.beta_g2),
+            gamma_g2: bn254::encode_g2(&zkey.gamma_g2),
+            delta_g1: bn254::encode_g1(&zkey.delta_g1),
+            delta_g2: bn254::encode_g2(&zkey.delta_g2),
+            ic: g1s(&zkey.ic),
+            coefficients: zkey
+                .coefficients
+                .iter()
+                .map(|c| ZkeyCoefficient {
+                    matrix: c.matrix as u32,
+                    constraint: c.constraint,
+                    signal: c.signal,
+                    value: c.value.to_be_bytes(),
+                })
+                .collect(),
+            a: g1s(&zkey.a),
+            b_g1: g1s(&zkey.b_g1),
+            b_g2: zkey.b_g2.iter().map(bn254::encode_g2).collect(),
+            c: g1s(&zkey.c),
+            h: g1s(&zkey.h),
+            extra_sections,
+        }
+    }
+}
+
+/// Append a G1 point as little-endian Montgomery-form coordinates
+fn put_g1(out: &mut Vec<u8>, point: &G1Affine) {
+    if point.infinity {
+        out.extend([0; 2 * FIELD_BYTES]);
+        return;
+    }
+    out.extend(point.x.to_montgomery_le_bytes());
+    out.extend(point.y.to_montgomery_le_bytes());
+}
+
+fn put_g2(out: &mut Vec<u8>, point: &G2Affine) {
+    if point.infinity {
+        out.extend([0; 4 * FIELD_BYTES]);
+        return;
+    }
+    for c in [point.x.c0, point.x.c1, point.y.c0, point.y.c1] {
+        out.extend(c.to_montgomery_le_bytes());
+    }
+}
+
+/// A section of `count` points decoded from `points`
+fn point_section<T>(
+    points: &[Vec<u8>],
+    count: usize,
+    field: &str,
+    decode: fn(&[u8], &str) -> Result<T>,
+    put: fn(&mut Vec<u8>, &T),
+) -> Result<Vec<u8>> {
+    if points.len() != count {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{field}: expected {count} points, got {}", points.len()),
+        ));
+    }
+    let mut out = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        put(&mut out, &decode(p, &format!("{field}[{i}]"))?);
+    }
+    Ok(out)
+}
+
+fn put_prime<C: MontConfig<4>>(out: &mut Vec<u8>) {
+    out.extend((FIELD_BYTES as u32).to_le_bytes());
+    out.extend(C::MODULUS.iter().flat_map(|l| l.to_le_bytes()));
+}
+
+/// Encode a proving key in the snarkjs layout
+///
+/// The result is parsed back before returning, so anything encoded here is
+/// also accepted by [`parse`].
+pub fn encode(pk: &ProvingKey) -> Result<Vec<u8>> {
+    let (n_vars, n_public) = (pk.n_vars as usize, pk.n_public as usize);
+    let private = n_vars.checked_sub(n_public + 1).ok_or_else(|| {
+        invalid(
+            "pk",
+            format!("{n_public} public signals do not fit {n_vars} variables"),
+        )
+    })?;
+
+    let mut header = Vec::new();
+    put_prime::<bn254::FqConfig>(&mut header);
+    put_prime::<bn254::FrConfig>(&mut header);
+    for n in [pk.n_vars, pk.n_public, pk.domain_size] {
+        header.extend(n.to_le_bytes());
+    }
+    put_g1(&mut header, &bn254::decode_g1(&pk.alpha_g1, "alpha_g1")?);
+    put_g1(&mut header, &bn254::decode_g1(&pk.beta_g1, "beta_g1")?);
+    put_g2(&mut header, &bn254::decode_g2(&pk.beta_g2, "beta_g2")?);
+    put_g2(&mut header, &bn254::decode_g2(&pk.gamma_g2, "gamma_g2")?);
+    put_g1(&mut header, &bn254::decode_g1(&pk.delta_g1, "delta_g1")?);
+    put_g2(&mut header, &bn254::decode_g2(&pk.delta_g2, "delta_g2")?);
+
+    let mut coefficients = Vec::with_capacity(4 + pk.coefficients.len() * COEFFICIENT_BYTES);
+    coefficients.extend((pk.coefficients.len() as u32).to_le_bytes());
+    for (i, c) in pk.coefficients.iter().enumerate() {
+        let value = bn254::parse_fr(&c.value, &format!("coefficients[{i}].value"))?;
+        for n in [c.matrix, c.constraint, c.signal] {
+            coefficients.extend(n.to_le_bytes());
+        }
+        // Stored as the canonical value of c R^2
+        let scaled = Fr::from_canonical(value.to_montgomery_limbs()).expect("limbs are below r");
+        coefficients.extend(scaled.to_montgomery_le_bytes());
+    }
+
+    let (g1, g2) = (bn254::decode_g1, bn254::decode_g2);
+    let bodies = HashMap::from([
+        (1, 1u32.to_le_bytes().to_vec()),
+        (2, header),
+        (4, coefficients),
+        (3, point_section(&pk.ic, n_public + 1, "ic", g1, put_g1)?),
+        (
+            9,
+            point_section(&pk.h, pk.domain_size as usize, "h", g1, put_g1)?,
+        ),
+        (8, point_section(&pk.c, private, "c", g1, put_g1)?),
+        (5, point_section(&pk.a, n_vars, "a", g1, put_g1)?),
+        (6, point_section(&pk.b_g1, n_vars, "b_g1", g1, put_g1)?),
+        (7, point_section(&pk.b_g2, n_vars, "b_g2", g2, put_g2)?),
+    ]);
+    let mut sections: Vec<(u32, &[u8])> = SECTION_ORDER
+        .iter()
+        .map(|id| (*id, bodies[id].as_slice()))
+        .collect();
+    for s in &pk.extra_sections {
+        if SECTION_ORDER.contains(&s.id) {
+            return Err(invalid(
+                "pk",
+                format!("extra section {} is part of the proving key", s.id),
+            ));
+        }
+        sections.push((s.id, &s.data));
+    }
+
+    let mut out = b"zkey".to_vec();
+    out.extend(1u32.to_le_bytes());
+    out.extend((sections.len() as u32).to_le_bytes());
+    for (id, body) in sections {
+        out.extend(id.to_le_bytes());
+        out.extend((body.len() as u64).to_le_bytes());
+        out.extend(body);
+    }
+    parse(&out, "pk")?;
+    Ok(out)
+}
+
+/// Header counts, circuit hash and contribution count, without reading the
+/// point sections
+pub fn info(bytes: &[u8], name: &str) -> Result<ZkeyInfo> {
+    let mut sections = circom::sections(bytes, b"zkey", 1, name)?;
+    let (header, mut rest) = ZkeyHeader::read(&mut sections, name)?;
+    // alpha_g1, beta_g1, beta_g2 and gamma_g2 come before delta_g1
+    rest.take(12 * FIELD_BYTES)?;
+    let delta_g1 = circom::read_g1(&mut rest)?;
+
+    // snarkjs appends one A entry per public signal and the constant, at
+    // constraints n_constraints.., so the last entry gives the count
+    let coefficients = circom::section(&mut sections, 4, name)?.rest();
+    let last = coefficients
+        .len()
+        .checked_sub(COEFFICIENT_BYTES)
+        .filter(|&at| at >= 4)
+        .map(|at| &coefficients[at..]);
+    let n_constraints = last
+        .and_then(|entry| {
+            let word = |i: usize| u32::from_le_bytes(entry[4 * i..4 * i + 4].try_into().unwrap());
+            let public_row = word(0) == Matrix::A as u32 && word(2) as usize == header.n_public;
+            public_row.then(|| (word(1) as usize).checked_sub(header.n_public))?
+        })
+        .ok_or_else(|| {
+            invalid(
+                name,
+                "coefficients do not end with the public signal rows".to_string(),
+            )
+        })?;
+
+    let (n_contributions, cs_hash) = match sections.get_mut(&CONTRIBUTIONS_SECTION) {
+        Some(reader) => {
+            let (n, hash) = read_contributions(reader, &delta_g1, name)?;
+            (n, hash.iter().map(|b| format!("{b:02x}")).collect())
+        }
+        None => (0, String::new()),
+    };
+    Ok(ZkeyInfo {
+        protocol: "groth16".to_string(),
+        curve: "bn128".to_string(),
+        n_constraints: n_constraints as u32,
+        n_wires: header.n_vars as u32,
+        n_public: header.n_public as u32,
+        domain_size: header.domain_size as u32,
+        n_contributions: n_contributions as u32,
+        cs_hash,
+    })
+}
+
+/// Read a snarkjs Groth16 `.zkey` proving key
+///
+/// Every point is checked to be on its curve, and `delta_g1` against the
+/// contribution section when there is one. Unreadable files reject with
+/// `GenericFailure`, malformed ones with `InvalidArg`.
+#[napi]
+pub fn zkey_read(path: String) -> Result<ProvingKey> {
+    let bytes = std::fs::read(&path).map_err(|e| circom::io_error(&path, e))?;
+    parse(&bytes, &path)
+}
+
+/// Write a proving key as a `.zkey` file snarkjs can use
+///
+/// The key is validated first, as [`zkey_read`] would; nothing is written
+/// if it is malformed.
+#[napi]
+pub fn zkey_write(pk: ProvingKey, path: String) -> Result<()> {
+    let bytes = encode(&pk)?;
+    std::fs::write(&path, bytes).map_err(|e| circom::io_error(&path, e))
+}
+
+/// Protocol, curve and sizes of a `.zkey` file, reading only its header,
+/// last coefficient and contribution section
+///
+/// The constraint count excludes the rows snarkjs adds for public signals.
+#[napi]
+pub fn zkey_info(path: String) -> Result<ZkeyInfo> {
+    info(&circom::map_file(&path)?, &path)
+}
+
+/// Contents of a [`ProvingKeyCache`]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProvingKeyCacheStats {
+    /// Circuit ids, least recently loaded first
+    pub loaded_circuits: Vec<String>,
+    /// Sum of the mapped file sizes
+    pub total_bytes_mapped: u64,
+}
+
+struct MappedKey {
+    map: Mmap,
+    /// File size when it was mapped
+    bytes: u64,
+    /// Load counter value when last (re)loaded
+    used: u64,
+}
+
+#[derive(Default)]
+struct MappedKeys {
+    keys: HashMap<String, MappedKey>,
+    total_bytes: u64,
+    tick: u64,
+}
+
+impl MappedKeys {
+    fn remove(&mut self, circuit_id: &str) -> Option<MappedKey> {
+        let key = self.keys.remove(circuit_id)?;
+        self.total_bytes -= key.bytes;
+        Some(key)
+    }
+
+    fn least_recent(&self) -> Option<String> {
+        self.keys
+            .iter()
+            .min_by_key(|(_, key)| key.used)
+            .map(|(id, _)| id.clone())
+    }
+}
+
+/// Memory-mapped proving keys by circuit id, within a byte budget
+///
+/// Maps are prefaulted on load so the first proof does not page the key in
+/// from disk. Loading past `max_size_bytes` unmaps the least recently loaded
+/// keys first.
+#[napi]
+pub struct ProvingKeyCache {
+    max_size_bytes: u64,
+    inner: RwLock<MappedKeys>,
+}
+
+#[napi]
+impl ProvingKeyCache {
+    /// Cache mapping at most `max_size_bytes` of key files at once; zero or
+    /// less admits only empty files
+    #[napi(constructor)]
+    pub fn new(max_size_bytes: i64) -> Self {
+        ProvingKeyCache {
+            max_size_bytes: max_size_bytes.max(0) as u64,
+            inner: RwLock::new(MappedKeys::default()),
+        }
+    }
+
+    /// Map the key file at `path` under `circuit_id`
+    ///
+    /// Loading an id again remaps its file and marks it most recently
+    /// loaded. A file larger than the whole budget rejects with
+    /// `InvalidArg` and leaves the cache unchanged; unreadable files reject
+    /// with `GenericFailure`.
+    #[napi]
+    pub fn load(&self, circuit_id: String, path: String) -> Result<()> {
+        let bytes = std::fs::metadata(&path)
+            .map_err(|e| circom::io_error(&path, e))?
+            .len();
+        if bytes > self.max_size_bytes {
+            return Err(invalid(
+                &path,
+                format!(
+                    "{bytes} bytes exceeds the cache size of {} bytes",
+                    self.max_size_bytes
+                ),
+            ));
+        }
+        let file = std::fs::File::open(&path).map_err(|e| circom::io_error(&path, e))?;
+        // SAFETY: the map is read-only; as for any reader of the file, it must
+        // not be truncated while the map is in use
+        let map = unsafe { MmapOptions::new().populate().map(&file) }
+            .map_err(|e| circom::io_error(&path, e))?;
+
+        let mut cache = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        cache.remove(&circuit_id);
+        while cache.total_bytes + bytes > self.max_size_bytes {
+            let oldest = cache.least_recent().expect("over budget with keys mapped");
+            cache.remove(&oldest);
+        }
+        cache.tick += 1;
+        let used = cache.tick;
+        cache.total_bytes += bytes;
+        cache
+            .keys
+            .insert(circuit_id, MappedKey { map, bytes, used });
+        Ok(())
+    }
+
+    /// Unmap the key loaded under `circuit_id`, if any
+    #[napi]
+    pub fn unload(&self, circuit_id: String) {
+        let mut cache = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        cache.remove(&circuit_id);
+    }
+
+    /// Whether a key is mapped under `circuit_id`
+    #[napi]
+    pub fn is_loaded(&self, circuit_id: String) -> bool {
+        let cache = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        cache.keys.contains_key(&circuit_id)
+    }
+
+    /// Mapped circuit ids and their total size
+    #[napi]
+    pub fn cache_stats(&self) -> ProvingKeyCacheStats {
+        let cache = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let mut loaded: Vec<(&String, &MappedKey)> = cache.keys.iter().collect();
+        loaded.sort_by_key(|(_, key)| key.used);
+        ProvingKeyCacheStats {
+            loaded_circuits: loaded.into_iter().map(|(id, _)| id.clone()).collect(),
+            total_bytes_mapped: cache.total_bytes,
+        }
+    }
+}
+
+impl ProvingKeyCache {
+    /// Run `f` on the mapped bytes of `circuit_id`, if loaded
+    pub fn with_key<R>(&self, circuit_id: &str, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        let cache = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        cache.keys.get(circuit_id).map(|key| f(&key.map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circom::Coefficient;
+
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../tests/fixtures/multiplier.zkey");
+
+    fn temp_path(tag: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("zkey-test-{}-{tag}.zkey", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pk = parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+        assert_eq!((pk.n_vars, pk.n_public, pk.domain_size), (4, 1, 4));
+        assert_eq!(pk.delta_g1, bn254::encode_g1(&bn254::g1_generator()));
+        assert_eq!(pk.extra_sections.len(), 1);
+        assert_eq!(pk.extra_sections[0].id, CONTRIBUTIONS_SECTION);
+        assert_eq!(encode(&pk).unwrap(), MULTIPLIER_ZKEY);
+
+        let path = temp_path("round-trip");
+        zkey_write(pk.clone(), path.clone()).unwrap();
+        let back = zkey_read(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(back.unwrap(), pk);
+
+        // The proving key the prover uses is unchanged
+        let zkey = Zkey::parse(&encode(&pk).unwrap(), "zkey").unwrap();
+        let original = Zkey::parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+        assert_eq!(zkey.h, original.h);
+        let values = |c: &[Coefficient]| c.iter().map(|c| c.value).collect::<Vec<_>>();
+        assert_eq!(values(&zkey.coefficients), values(&original.coefficients));
+    }
+
+    #[test]
+    fn test_info() {
+        let expected = ZkeyInfo {
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+            n_constraints: 1,
+            n_wires: 4,
+            n_public: 1,
+            domain_size: 4,
+            n_contributions: 0,
+            cs_hash: MULTIPLIER_ZKEY[2512..2576]
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+        };
+        assert_eq!(info(MULTIPLIER_ZKEY, "zkey").unwrap(), expected);
+        assert_eq!(expected.cs_hash.len(), 2 * CS_HASH_BYTES);
+
+        let path = temp_path("info");
+        std::fs::write(&path, MULTIPLIER_ZKEY).unwrap();
+        let from_file = zkey_info(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rejects_bad_keys() {
+        let pk = parse(MULTIPLIER_ZKEY, "zkey").unwrap();
+
+        let mut tampered = pk.clone();
+        tampered.delta_g1 =
+            bn254::encode_g1(&bn254::g1_generator().to_jacobian().double().to_affine());
+        let err = encode(&tampered).unwrap_err();
+        assert_eq!(
+            err.reason,
+            "pk: delta_g1 is not the generator, but there are no contributions"
+        );
+
+        let mut short = pk.clone();
+        short.h.pop();
+        assert_eq!(
+            encode(&short).unwrap_err().reason,
+            "h: expected 4 points, got 3"
+        );
+
+        let mut off_curve = pk.clone();
+        off_curve.b_g2[1][127] ^= 1;
+        let err = encode(&off_curve).unwrap_err();
+        assert_eq!(err.reason, "b_g2[1]: point is not on the BN254 G2 curve");
+
+        let mut clash = pk.clone();
+        clash.extra_sections[0].id = 3;
+        assert!(encode(&clash).is_err());
+
+        let err = zkey_read(temp_path("missing")).unwrap_err();
+        assert_eq!(err.status, Status::GenericFailure);
+        assert!(zkey_info(temp_path("missing")).is_err());
+    }
+
+    #[test]
+    fn test_proving_key_cache() {
+        const LARGE: usize = 10 << 20;
+        let large = temp_path("cache-large");
+        let large_bytes: Vec<u8> = (0..LARGE).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&large, &large_bytes).unwrap();
+        let small = temp_path("cache-small");
+        std::fs::write(&small, MULTIPLIER_ZKEY).unwrap();
+        let small_len = MULTIPLIER_ZKEY.len() as u64;
+
+        let cache = ProvingKeyCache::new(LARGE as i64 + 3 * small_len as i64);
+        cache.load("large".to_string(), large.clone()).unwrap();
+        for id in ["a", "b", "c"] {
+            cache.load(id.to_string(), small.clone()).unwrap();
+            assert!(cache.is_loaded("large".to_string()), "{id}");
+        }
+        assert_eq!(
+            cache.with_key("large", |bytes| bytes == large_bytes),
+            Some(true)
+        );
+        assert_eq!(
+            cache.cache_stats(),
+            ProvingKeyCacheStats {
+                loaded_circuits: ["large", "a", "b", "c"].map(String::from).to_vec(),
+                total_bytes_mapped: LARGE as u64 + 3 * small_len,
+            }
+        );
+
+        // Reloading refreshes "large", so the next key evicts "a"
+        cache.load("large".to_string(), large.clone()).unwrap();
+        cache.load("d".to_string(), small.clone()).unwrap();
+        assert!(!cache.is_loaded("a".to_string()));
+        assert!(cache.is_loaded("large".to_string()));
+        assert_eq!(
+            cache.cache_stats().loaded_circuits,
+            ["b", "c", "large", "d"].map(String::from)
+        );
+
+        cache.unload("large".to_string());
+        cache.unload("missing".to_string());
+        assert!(!cache.is_loaded("large".to_string()));
+        assert_eq!(cache.cache_stats().total_bytes_mapped, 3 * small_len);
+
+        // Too large for the budget: rejected without evicting anything
+        let tiny = ProvingKeyCache::new(small_len as i64 - 1);
+        let err = tiny.load("large".to_string(), large.clone()).unwrap_err();
+        assert_eq!(
+            err.reason,
+            format!(
+                "{large}: {LARGE} bytes exceeds the cache size of {} bytes",
+                small_len - 1
+            )
+        );
+        let err = cache
+            .load("e".to_string(), temp_path("cache-missing"))
+            .unwrap_err();
+        assert_eq!(err.status, Status::GenericFailure);
+        assert_eq!(cache.cache_stats().loaded_circuits.len(), 3);
+
+        std::fs::remove_file(&large).unwrap();
+        std::fs::remove_file(&small).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+/**
+ * Asynchronous proving and verification through the Rust binding
+ *
+ * Registers circom's multiplier circuit (`c <== a * b`, `c` public) with its
+ * snarkjs proving key and drives `proveAsync` / `verifyAsync` from a forked
+ * Node.js process (see `poolMatchGlobs` in vitest.config.ts), so the
+ * Promises and progress callbacks arrive through a real event loop and
+ * libuv worker pool. Skipped when the Rust binding is not built.
+ */
+
+import { readFileSync } from 'node:fs';
+import { describe, it, expect, vi } from 'vitest';
+import { loadRustBinding } from './native.js';
+
+interface CancellationToken {
+  cancel(): void;
+  readonly isCancelled: boolean;
+}
+
+interface ProofProgress {
+  phase: string;
+  percent: number;
+  elapsedMs: number;
+}
+
+type OnProgress = (progress: ProofProgress) => void;
+
+interface AsyncProveBinding {
+  registerGroth16Circuit(circuitId: string, r1cs: number[], provingKey: number[]): void;
+  proveAsync(
+    circuitId: string,
+    witness: number[],
+    token?: CancellationToken | null,
+    onProgress?: OnProgress | null
+  ): Promise<number[]>;
+  proveGroth16(
+    r1cs: number[],
+    witness: number[][],
+    provingKey: number[],
+    token?: CancellationToken | null,
+    onProgress?: OnProgress | null
+  ): Promise<{ piA: number[]; publicSignals: number[][] }>;
+  verifyAsync(
+    proof: number[],
+    publicInputs: number[][],
+    token?: CancellationToken | null
+  ): Promise<boolean>;
+  CancellationToken: new () => CancellationToken;
+}
+
+const binding = loadRustBinding() as unknown as AsyncProveBinding | null;
+
+const fixture = (name: string): number[] =>
+  Array.from(readFileSync(new URL(`../native-rust/tests/fixtures/${name}`, import.meta.url)));
+
+/** 32-byte big-endian encoding of a small value */
+function scalar(value: number): number[] {
+  const out = Buffer.alloc(32);
+  out.writeUInt32BE(value, 28);
+  return Array.from(out);
+}
+
+/** Every wire of the multiplier for `3 * 11 = 33`: 1, c, a, b */
+const WITNESS = [1, 33, 3, 11].flatMap(scalar);
+
+interface ProgressRecorder {
+  updates: ProofProgress[];
+  onProgress: OnProgress;
+  done(): Promise<void>;
+}
+
+/** Collect progress updates, and wait for the last one to arrive */
+function progressRecorder(): ProgressRecorder {
+  const updates: ProofProgress[] = [];
+  return {
+    updates,
+    onProgress: (progress) => updates.push(progress),
+    // Callbacks are queued without blocking the worker, so the last ones
+    // may land after the Promise settles
+    done: () => vi.waitFor(() => expect(updates.at(-1)?.phase).toBe('final')),
+  };
+}
+
+function expectMonotonic(updates: ProofProgress[]): void {
+  expect(updates.length).toBeGreaterThan(0);
+  for (let i = 1; i < updates.length; i++) {
+    expect(updates[i].percent).toBeGreaterThanOrEqual(updates[i - 1].percent);
+    expect(updates[i].elapsedMs).toBeGreaterThanOrEqual(updates[i - 1].elapsedMs);
+  }
+  expect(updates.at(-1)).toMatchObject({ phase: 'final', percent: 100 });
+}
+
+describe.skipIf(binding === null)('async Groth16 proving', () => {
+  const b = binding as AsyncProveBinding;
+  b.registerGroth16Circuit('multiplier', fixture('multiplier.r1cs'), fixture('multiplier.zkey'));
+
+  it('proves and verifies off the main thread', async () => {
+    const proof = await b.proveAsync('multiplier', WITNESS);
+
+    // u16 id length, the id, then pi_a || pi_b || pi_c
+    expect(proof.slice(0, 2)).toEqual([0, 'multiplier'.length]);
+    expect(proof.length).toBe(2 + 'multiplier'.length + 256);
+
+    await expect(b.verifyAsync(proof, [scalar(33)])).resolves.toBe(true);
+    await expect(b.verifyAsync(proof, [scalar(34)])).resolves.toBe(false);
+  });
+
+  it('reports progress through every prover phase', async () => {
+    const recorder = progressRecorder();
+    const proof = await b.proveAsync('multiplier', WITNESS, null, recorder.onProgress);
+    await recorder.done();
+    expectMonotonic(recorder.updates);
+    const phases = recorder.updates.map((u) => u.phase).filter((p, i, all) => p !== all[i - 1]);
+    expect(phases).toEqual(['witness', 'ntt', 'msm', 'final']);
+    for (const u of recorder.updates) {
+      expect(u.percent).toBeGreaterThanOrEqual(0);
+      expect(u.percent).toBeLessThanOrEqual(100);
+    }
+    await expect(b.verifyAsync(proof, [scalar(33)])).resolves.toBe(true);
+  });
+
+  it('reports progress from proveGroth16', async () => {
+    const recorder = progressRecorder();
+    const wires = [1, 33, 3, 11].map(scalar);
+    const proof = await b.proveGroth16(
+      fixture('multiplier.r1cs'),
+      wires,
+      fixture('multiplier.zkey'),
+      null,
+      recorder.onProgress
+    );
+    expect(proof.publicSignals).toEqual([scalar(33)]);
+    await recorder.done();
+    expectMonotonic(recorder.updates);
+  });
+
+  it('rejects cancelled tasks with code CANCELLED', async () => {
+    const token = new b.CancellationToken();
+    token.cancel();
+    await expect(b.proveAsync('multiplier', WITNESS, token)).rejects.toMatchObject({
+      code: 'CANCELLED',
+    });
+    const proof = await b.proveAsync('multiplier', WITNESS);
+    await expect(b.verifyAsync(proof, [scalar(33)], token)).rejects.toMatchObject({
+      code: 'CANCELLED',
+    });
+  });
+
+  it('rejects unknown circuits and bad witnesses', async () => {
+    await expect(b.proveAsync('no-such-circuit', WITNESS)).rejects.toThrow(
+      'no proof system registered'
+    );
+    const unsatisfied = [1, 34, 3, 11].flatMap(scalar);
+    await expect(b.proveAsync('multiplier', unsatisfied)).rejects.toThrow(
+      'constraint 0 is not satisfied'
+    );
+    expect(() => b.registerGroth16Circuit('x'.repeat(65536), [], [])).toThrow(
+      'circuit_id: must be at most 65535 bytes'
+    );
+  });
+});
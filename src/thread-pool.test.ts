@@ -0,0 +1,49 @@
+/**
+ * Worker pool configuration of the Rust binding
+ *
+ * Runs in its own process (see `poolMatchGlobs` in vitest.config.ts): the
+ * pool is fixed on first use, so the configuration must come before any
+ * other call runs a parallel kernel. Skipped when the Rust binding is not
+ * built.
+ */
+
+import { describe, it, expect } from 'vitest';
+import { loadRustBinding } from './native.js';
+
+interface ThreadPoolBinding {
+  configureThreadPool(numThreads: number, stackSizeKb: number): void;
+  getThreadPoolSize(): number;
+  nttBn254Packed(coeffs: Uint8Array, invert: boolean): Buffer;
+}
+
+const binding = loadRustBinding() as unknown as ThreadPoolBinding | null;
+
+/** Packed 32-byte big-endian encodings of 1..=n */
+function coefficients(n: number): Buffer {
+  const out = Buffer.alloc(32 * n);
+  for (let i = 0; i < n; i++) {
+    out.writeUInt32BE(i + 1, 32 * i + 28);
+  }
+  return out;
+}
+
+describe.skipIf(binding === null)('thread pool configuration', () => {
+  const b = binding as ThreadPoolBinding;
+
+  it('runs an NTT on a 2-thread pool', () => {
+    b.configureThreadPool(2, 4096);
+    expect(b.getThreadPoolSize()).toBe(2);
+
+    // Large enough to be split across both workers
+    const coeffs = coefficients(1 << 14);
+    const evaluations = b.nttBn254Packed(coeffs, false);
+    expect(evaluations.length).toBe(coeffs.length);
+    expect(evaluations.equals(coeffs)).toBe(false);
+    expect(b.nttBn254Packed(evaluations, true).equals(coeffs)).toBe(true);
+  });
+
+  it('rejects reconfiguration', () => {
+    expect(() => b.configureThreadPool(4, 0)).toThrow('already configured');
+    expect(b.getThreadPoolSize()).toBe(2);
+  });
+});
@@ -0,0 +1,121 @@
+/**
+ * Zero-copy typed array entry points of the Rust binding
+ *
+ * Exercises the `*Packed` and `*Into` variants of the BN254 NTT, MSM and
+ * field multiplication with `Buffer.allocUnsafe` inputs, which Node.js
+ * carves out of a shared pool so they start at a non-zero byte offset.
+ * Skipped when the Rust binding is not built.
+ */
+
+import { describe, it, expect } from 'vitest';
+import { loadRustBinding } from './native.js';
+
+interface ZeroCopyBinding {
+  nttBn254(coeffs: number[][], invert: boolean): number[][];
+  nttBn254Packed(coeffs: Uint8Array, invert: boolean): Buffer;
+  nttBn254Into(coeffs: Uint8Array, invert: boolean, output: Uint8Array): void;
+  msmBn254G1(points: number[][], scalars: number[][]): number[];
+  msmBn254G1Packed(points: Uint8Array, scalars: Uint8Array): Buffer;
+  msmBn254G1Into(points: Uint8Array, scalars: Uint8Array, output: Uint8Array): void;
+  bn254FieldMulBatch(a: number[][], b: number[][]): number[][];
+  bn254FieldMulBatchPacked(a: Uint8Array, b: Uint8Array): Buffer;
+  bn254FieldMulBatchInto(a: Uint8Array, b: Uint8Array, output: Uint8Array): void;
+}
+
+const binding = loadRustBinding() as unknown as ZeroCopyBinding | null;
+
+const G1_GENERATOR = Buffer.from(
+  '0000000000000000000000000000000000000000000000000000000000000001' +
+    '0000000000000000000000000000000000000000000000000000000000000002',
+  'hex'
+);
+
+/** 32-byte big-endian encodings of 1..=n */
+function elements(n: number): Buffer[] {
+  return Array.from({ length: n }, (_, i) => {
+    const e = Buffer.alloc(32);
+    e.writeUInt32BE(i + 1, 28);
+    return e;
+  });
+}
+
+/** Copy into a pooled `Buffer.allocUnsafe` allocation */
+function pooled(bytes: Buffer): Buffer {
+  const out = Buffer.allocUnsafe(bytes.length);
+  bytes.copy(out);
+  return out;
+}
+
+const toArrays = (values: Buffer[]): number[][] => values.map((v) => Array.from(v));
+const concat = (values: number[][]): Buffer => Buffer.concat(values.map((v) => Buffer.from(v)));
+
+describe.skipIf(binding === null)('zero-copy typed array entry points', () => {
+  const b = binding as ZeroCopyBinding;
+
+  it('transforms packed coefficients like the array API', () => {
+    const coeffs = elements(16);
+    const expected = concat(b.nttBn254(toArrays(coeffs), false));
+    const input = pooled(Buffer.concat(coeffs));
+
+    expect(b.nttBn254Packed(input, false).equals(expected)).toBe(true);
+
+    const output = Buffer.allocUnsafe(expected.length);
+    b.nttBn254Into(input, false, output);
+    expect(output.equals(expected)).toBe(true);
+
+    // In place, and back again through the inverse
+    b.nttBn254Into(input, false, input);
+    expect(input.equals(expected)).toBe(true);
+    b.nttBn254Into(input, true, input);
+    expect(input.equals(Buffer.concat(coeffs))).toBe(true);
+  });
+
+  it('reads views over a SharedArrayBuffer', () => {
+    const coeffs = Buffer.concat(elements(8));
+    const view = new Uint8Array(new SharedArrayBuffer(coeffs.length));
+    view.set(coeffs);
+    const expected = concat(b.nttBn254(toArrays(elements(8)), false));
+    b.nttBn254Into(view, false, view);
+    expect(Buffer.from(view).equals(expected)).toBe(true);
+  });
+
+  it('multiplies packed field elements', () => {
+    const a = elements(5);
+    const expected = concat(b.bn254FieldMulBatch(toArrays(a), toArrays(a)));
+    const packed = pooled(Buffer.concat(a));
+    expect(b.bn254FieldMulBatchPacked(packed, packed).equals(expected)).toBe(true);
+    const output = Buffer.allocUnsafe(expected.length);
+    b.bn254FieldMulBatchInto(packed, packed, output);
+    expect(output.equals(expected)).toBe(true);
+  });
+
+  it('runs an MSM over packed points and scalars', () => {
+    const scalar = Buffer.alloc(32);
+    scalar[0] = 1; // little-endian 1
+    const expected = Buffer.from(
+      b.msmBn254G1(toArrays([G1_GENERATOR, G1_GENERATOR]), toArrays([scalar, scalar]))
+    );
+    const points = pooled(Buffer.concat([G1_GENERATOR, G1_GENERATOR]));
+    const scalars = pooled(Buffer.concat([scalar, scalar]));
+    expect(b.msmBn254G1Packed(points, scalars).equals(expected)).toBe(true);
+    const output = Buffer.allocUnsafe(64);
+    b.msmBn254G1Into(points, scalars, output);
+    expect(output.equals(expected)).toBe(true);
+  });
+
+  it('rejects lengths that are not whole elements', () => {
+    expect(() => b.nttBn254Packed(Buffer.allocUnsafe(33), false)).toThrow(
+      'coeffs: length 33 is not a multiple of 32 bytes'
+    );
+    expect(() => b.msmBn254G1Packed(Buffer.allocUnsafe(63), Buffer.alloc(32))).toThrow(
+      'points: length 63 is not a multiple of 64 bytes'
+    );
+    const input = Buffer.concat(elements(4));
+    expect(() => b.nttBn254Into(input, false, Buffer.allocUnsafe(64))).toThrow(
+      'output: expected 128 bytes'
+    );
+    expect(() => b.nttBn254Packed(new Uint32Array(8) as unknown as Uint8Array, false)).toThrow(
+      'expected a Uint8Array or Buffer'
+    );
+  });
+});
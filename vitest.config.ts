@@ -6,6 +6,13 @@ export default defineConfig({
     environment: 'node',
     include: ['src/**/*.{test,spec,prop.test}.ts'],
     exclude: ['node_modules/', 'dist/', 'build/', 'native/', 'native-rust/'],
+    // The native worker pool is fixed once per process, so tests that
+    // configure it need a process of their own; the async prover tests run
+    // in a forked Node.js process too, to resolve through a real event loop
+    poolMatchGlobs: [
+      ['src/thread-pool.test.ts', 'forks'],
+      ['src/async-prove.test.ts', 'forks'],
+    ],
     coverage: {
       provider: 'v8',
       reporter: ['text', 'json', 'html'],